@@ -3,6 +3,7 @@ use core::ops::{
 };
 use serde::{Deserialize, Serialize};
 use crate::common::NORMALIZE_EPSILON;
+use crate::DVec3;
 
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 #[repr(C)]
@@ -209,6 +210,32 @@ impl DVec2 {
     pub fn is_normalized(self) -> bool {
         (self.length_squared() - 1.0).abs() <= 1.0e-12
     }
+
+    pub const fn extend(self, z: f64) -> DVec3 {
+        DVec3::new(self.x, self.y, z)
+    }
+
+    /// 与 X 轴夹角为 `angle`（弧度）的单位向量。
+    pub fn from_angle(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin)
+    }
+
+    /// 逆时针旋转 `angle`（弧度）。
+    pub fn rotate(self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// 逆时针旋转 90°。
+    pub const fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// 二维叉积 `self.x * rhs.y - self.y * rhs.x`。
+    pub const fn perp_dot(self, rhs: Self) -> f64 {
+        self.x * rhs.y - self.y * rhs.x
+    }
 }
 
 impl Index<usize> for DVec2 {
@@ -430,4 +457,29 @@ mod tests {
         let max = DVec2::new(2.0, 2.0);
         assert_eq!(v.clamp(min, max), DVec2::new(1.0, 2.0));
     }
+
+    #[test]
+    fn test_extend() {
+        const V: DVec3 = DVec2::new(1.0, 2.0).extend(3.0);
+        assert_eq!(V, DVec3::new(1.0, 2.0, 3.0));
+        assert_eq!(V.xy().extend(0.0), V.with_z(0.0));
+    }
+
+    #[test]
+    fn test_planar_rotation() {
+        let eps = 1.0e-12;
+        assert!((DVec2::from_angle(0.0) - DVec2::X).length() < eps);
+        assert!((DVec2::from_angle(core::f64::consts::FRAC_PI_2) - DVec2::Y).length() < eps);
+
+        let v = DVec2::new(2.0, 1.0);
+        assert_eq!(v.perp(), DVec2::new(-1.0, 2.0));
+        assert!((v.rotate(core::f64::consts::FRAC_PI_2) - v.perp()).length() < eps);
+        assert!((v.rotate(0.3).rotate(-0.3) - v).length() < eps);
+        assert!((v.rotate(0.7).length() - v.length()).abs() < eps);
+
+        const PERP_DOT: f64 = DVec2::X.perp_dot(DVec2::Y);
+        assert_eq!(PERP_DOT, 1.0);
+        assert_eq!(v.perp_dot(v), 0.0);
+        assert_eq!(v.perp_dot(v.perp()), v.length_squared());
+    }
 }
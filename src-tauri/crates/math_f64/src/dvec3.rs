@@ -3,6 +3,7 @@ use core::ops::{
 };
 use serde::{Deserialize, Serialize};
 use crate::common::NORMALIZE_EPSILON;
+use crate::{DVec2, DVec4};
 
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 #[repr(C)]
@@ -18,6 +19,9 @@ impl DVec3 {
     pub const X: Self = Self::new(1.0, 0.0, 0.0);
     pub const Y: Self = Self::new(0.0, 1.0, 0.0);
     pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+    pub const NEG_X: Self = Self::new(-1.0, 0.0, 0.0);
+    pub const NEG_Y: Self = Self::new(0.0, -1.0, 0.0);
+    pub const NEG_Z: Self = Self::new(0.0, 0.0, -1.0);
 
     pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
@@ -225,6 +229,50 @@ impl DVec3 {
     pub fn is_normalized(self) -> bool {
         (self.length_squared() - 1.0).abs() <= 1.0e-12
     }
+
+    pub const fn xy(self) -> DVec2 {
+        DVec2::new(self.x, self.y)
+    }
+
+    pub const fn xz(self) -> DVec2 {
+        DVec2::new(self.x, self.z)
+    }
+
+    pub const fn yz(self) -> DVec2 {
+        DVec2::new(self.y, self.z)
+    }
+
+    pub const fn with_x(mut self, x: f64) -> Self {
+        self.x = x;
+        self
+    }
+
+    pub const fn with_y(mut self, y: f64) -> Self {
+        self.y = y;
+        self
+    }
+
+    pub const fn with_z(mut self, z: f64) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub const fn extend(self, w: f64) -> DVec4 {
+        DVec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.x), f(self.y), f(self.z))
+    }
+
+    /// 逐分量 `self * a + b`（融合乘加）。
+    pub fn mul_add(self, a: Self, b: Self) -> Self {
+        Self::new(
+            self.x.mul_add(a.x, b.x),
+            self.y.mul_add(a.y, b.y),
+            self.z.mul_add(a.z, b.z),
+        )
+    }
 }
 
 impl Index<usize> for DVec3 {
@@ -410,6 +458,13 @@ mod tests {
         assert_eq!(v.z, 3.0);
     }
 
+    #[test]
+    fn test_neg_axes() {
+        assert_eq!(DVec3::NEG_X, -DVec3::X);
+        assert_eq!(DVec3::NEG_Y, -DVec3::Y);
+        assert_eq!(DVec3::NEG_Z, -DVec3::Z);
+    }
+
     #[test]
     fn test_cross() {
         let v1 = DVec3::X;
@@ -435,4 +490,33 @@ mod tests {
         let v = DVec3::new(2.0, 0.0, 0.0);
         assert_eq!(v.normalize(), DVec3::X);
     }
+
+    #[test]
+    fn test_swizzles() {
+        const V: DVec3 = DVec3::new(1.0, 2.0, 3.0);
+        const XY: DVec2 = V.xy();
+        assert_eq!(XY, DVec2::new(1.0, 2.0));
+        assert_eq!(V.xz(), DVec2::new(1.0, 3.0));
+        assert_eq!(V.yz(), DVec2::new(2.0, 3.0));
+        assert_eq!(V.extend(4.0), DVec4::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(V.extend(4.0).truncate(), V);
+    }
+
+    #[test]
+    fn test_with_component() {
+        let v = DVec3::new(1.0, 2.0, 3.0);
+        assert_eq!(v.with_x(-1.0), DVec3::new(-1.0, 2.0, 3.0));
+        assert_eq!(v.with_y(-2.0), DVec3::new(1.0, -2.0, 3.0));
+        assert_eq!(v.with_z(0.0), DVec3::new(1.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_map_and_mul_add() {
+        let v = DVec3::new(-1.0, 4.0, -9.0);
+        assert_eq!(v.map(f64::abs), v.abs());
+        assert_eq!(v.map(|c| c * 2.0), v * 2.0);
+        let a = DVec3::new(2.0, 3.0, 4.0);
+        let b = DVec3::new(0.5, 0.5, 0.5);
+        assert_eq!(v.mul_add(a, b), v * a + b);
+    }
 }
@@ -3,6 +3,7 @@ use core::ops::{
 };
 use serde::{Deserialize, Serialize};
 use crate::common::NORMALIZE_EPSILON;
+use crate::DVec3;
 
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 #[repr(C)]
@@ -247,6 +248,10 @@ impl DVec4 {
     pub fn is_normalized(self) -> bool {
         (self.length_squared() - 1.0).abs() <= 1.0e-12
     }
+
+    pub const fn truncate(self) -> DVec3 {
+        DVec3::new(self.x, self.y, self.z)
+    }
 }
 
 impl Index<usize> for DVec4 {
@@ -431,4 +436,10 @@ mod tests {
         let v2 = DVec4::new(1.0, 2.0, 3.0, 4.0);
         assert_eq!(v1.dot(v2), 10.0);
     }
+
+    #[test]
+    fn test_truncate() {
+        const V: DVec3 = DVec4::new(1.0, 2.0, 3.0, 4.0).truncate();
+        assert_eq!(V, DVec3::new(1.0, 2.0, 3.0));
+    }
 }
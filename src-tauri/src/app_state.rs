@@ -1,22 +1,75 @@
 //! 应用全局状态与资源管理。
 
-use std::sync::{atomic::AtomicBool, Arc};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context};
 
 use flume::Receiver;
-use tokio::sync::{oneshot, Mutex, MutexGuard};
+use tauri::{Emitter, Manager};
+use tokio::sync::{oneshot, Mutex, MutexGuard, Notify};
 
 use crate::{
-    imu::IMUClient,
+    imu::{
+        connect_exclusive, ConnectOutcome, ConnectRetryPolicy, IMUClient, IMUConfig, Subscription,
+    },
     processor::{
-        calibration::CorrectionRequest,
+        attitude_stream::AttitudeFrame,
+        batcher::{MAX_BATCH_LEN, MAX_BATCH_WINDOW_MS},
+        calibration::{
+            AxisCalibration, CalibrationQuality, CalibrationQualityConfig, CalibrationStateHandle,
+            CalibrationStateSlot, CalibrationWizardSnapshot, CorrectionRequest, FullCalibration,
+        },
+        clock_sync::{ClockSyncEstimate, ClockSyncHandle, ClockSyncSlot},
+        fanout::{
+            spawn_fanout, OutputFanout, OutputFanoutHandle, SubscriptionId,
+            FANOUT_CHANNEL_CAPACITY, OUTPUT_CHANNEL_CAPACITY,
+        },
+        fault::{FaultInjector, FaultInjectorHandle},
+        history::{OutputHistory, OutputHistoryHandle, OutputReplay},
+        idle::{IdleEvent, IdleHooks, IdleManager, SubscriberCount, SubscriberGuard},
+        latest::{LatestFrame, LatestFrameHandle},
+        output::{OutputFrame, StageCapture},
+        output_encoding::OutputMessage,
+        parser::ImuSampleRaw,
         pipeline::{
-            diagnostics::{DiagnosticsFlag, PipelineDiagnostics},
-            PipelineConfigRequest, ProcessorPipelineConfig,
+            diagnostics::{diagnostics_channel, DiagnosticsFlag, PipelineDiagnostics},
+            ConfigApplyMode, PipelineConfigRequest, ProcessorPipelineConfig, SampleExplanation,
         },
-        Processor,
+        scheduling::{SchedulingStatus, SchedulingStatusHandle},
+        shared::{nominal_sample_interval_ms, BufferRegistry},
+        stats::{host_now_ms, ProcessorStats, ProcessorStatsHandle},
+        suppress::StreamSeq,
+        upstream::PipelineChannelClosed,
+        watchdog::{DataFlowWatchdog, WatchdogEvent, WatchdogHooks},
+        Processor, RawImuData,
+    },
+    recorder::{
+        db::verify_database,
+        device_profile::{get_device_profile, save_device_profile, DeviceProfile},
+        flight::flight_recorder_capacity,
+        location::RecordingLocation,
+        recording_status, spawn_recorder, start_recording, stop_recording, ExportRegistry,
+        RecorderCommand, RecorderOptions, RecordingSplit, RecordingStartInput, StatisticsCache,
+    },
+    replay::{
+        load_session_frames, PlaybackController, PlaybackMode, PlaybackRefused, PlaybackState,
+        PlaybackStatus, PLAYBACK_STATUS_INTERVAL,
+    },
+    selftest::{self, SelfTestReport, SelfTestStage},
+    types::{
+        bluetooth::PeripheralInfo,
+        outputs::{OutputRateMode, OutputSubscribeOptions, ResponseData},
+        recording::RecordingCalibration,
+        status::{AppStatus, ConnectionUptime},
     },
-    recorder::{spawn_recorder, RecorderCommand},
-    types::outputs::ResponseData,
+    units::{FrameDisplay, UnitPreferencesStore},
 };
 
 /// 姿态零位校准请求通道句柄。
@@ -27,6 +80,58 @@ pub struct CalibrationHandle {
 const CALIBRATION_ERROR: &str = "Failed to update axis calibration";
 const PIPELINE_CONFIG_ERROR: &str = "Failed to update pipeline config";
 const PIPELINE_CONFIG_SAVE_ERROR: &str = "Failed to save pipeline config";
+const RECORDER_STATUS_ERROR: &str = "Failed to query recording status";
+const REPORT_RATE_ERROR: &str = "Report rate must be between 0 and 250 Hz";
+const DEVICE_CONFIG_ERROR: &str = "Failed to write device config";
+const STALL_THRESHOLD_ERROR: &str = "Stall threshold must be at least 100 ms";
+const ATTITUDE_RATE_ERROR: &str = "Attitude rate must be between 1 and 1000 Hz";
+const BACKFILL_RATE_ERROR: &str = "Backfill rate must be between 1 and 1000 Hz";
+const LIVE_RATE_ERROR: &str = "Live rate must be between 1 and 1000 Hz";
+const ADAPTIVE_RATE_ERROR: &str =
+    "Adaptive rate needs 1 <= min_hz <= max_hz <= 1000 and no live_max_rate_hz";
+const BATCHING_ERROR: &str = "Batching needs a window of 1-100 ms and 1-64 frames per batch";
+const PIPELINE_CONTROL_ERROR: &str = "Failed to pause or resume processing";
+const PIPELINE_EXPLAIN_ERROR: &str = "Failed to explain sample";
+const PIPELINE_UNRESPONSIVE_ERROR: &str =
+    "Pipeline is not processing data: no reply from the processor thread";
+
+/// 数据流看门狗的检查间隔。
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// 等待处理线程回复控制请求的上限。
+///
+/// 处理线程在启动时即已创建，无设备输入时阻塞在 Selector 上仍会响应控制请求；
+/// 超时只会出现在线程卡死或被上游大量积压拖住时，此时返回错误而不是让命令一直挂起。
+const CONTROL_REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+/// 等待零位校准回复的上限：质量评估窗口最长时长再留出控制请求的余量。
+const AXIS_CALIBRATION_REPLY_TIMEOUT: Duration =
+    Duration::from_millis(CalibrationQualityConfig::MAX_WINDOW_MS + 2000);
+/// 空闲模式的检查间隔，订阅出现或开始录制后最迟这么久重新开启上报。
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 自检等待管线输出 / 录制落盘的超时。
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(3);
+/// 自检录制数据库文件名（位于录制目录，结束后删除）。
+const SELF_TEST_DB_FILE: &str = "imu_vis_selftest.sqlite";
+
+/// 等待处理线程的回复，通道关闭返回 `closed_error`，超时返回管线无响应。
+async fn await_reply<T>(
+    response_rx: oneshot::Receiver<T>,
+    closed_error: &'static str,
+) -> Result<T, &'static str> {
+    await_reply_within(response_rx, CONTROL_REPLY_TIMEOUT, closed_error).await
+}
+
+/// 同 [`await_reply`]，超时上限由调用方给出。
+async fn await_reply_within<T>(
+    response_rx: oneshot::Receiver<T>,
+    timeout: Duration,
+    closed_error: &'static str,
+) -> Result<T, &'static str> {
+    match tokio::time::timeout(timeout, response_rx).await {
+        Ok(reply) => reply.map_err(|_| closed_error),
+        Err(_) => Err(PIPELINE_UNRESPONSIVE_ERROR),
+    }
+}
 
 impl CalibrationHandle {
     /// 创建校准通道句柄与接收端。
@@ -37,15 +142,39 @@ impl CalibrationHandle {
         (Self { tx }, rx)
     }
 
-    /// 请求以当前姿态作为零位。
-    pub async fn request_axis_calibration(&self) -> Result<(), &'static str> {
+    /// 请求以当前姿态作为零位，返回采样窗口的质量评分。
+    ///
+    /// 处理线程先采集一个评估窗口再回复，评分不足且配置为拒绝时 `applied = false`。
+    pub async fn request_axis_calibration(&self) -> Result<CalibrationQuality, &'static str> {
         // response tells the caller
         // 给 Processor 发一个请求， 等待其完成后返回结果
         let (respond_to, response_rx) = oneshot::channel();
         self.tx
             .send(CorrectionRequest::SetAxis { respond_to })
             .map_err(|_| CALIBRATION_ERROR)?;
-        response_rx.await.map_err(|_| CALIBRATION_ERROR)?
+        await_reply_within(
+            response_rx,
+            AXIS_CALIBRATION_REPLY_TIMEOUT,
+            CALIBRATION_ERROR,
+        )
+        .await?
+    }
+
+    /// 请求一次性完成姿态零位、航向零点、位置归零与重力参考更新。
+    ///
+    /// 与 [`request_axis_calibration`](Self::request_axis_calibration) 共用评估窗口，
+    /// 静止校验失败时 `offsets` 为空，任何状态都不改变。
+    pub async fn request_calibrate_all(&self) -> Result<FullCalibration, &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(CorrectionRequest::CalibrateAll { respond_to })
+            .map_err(|_| CALIBRATION_ERROR)?;
+        await_reply_within(
+            response_rx,
+            AXIS_CALIBRATION_REPLY_TIMEOUT,
+            CALIBRATION_ERROR,
+        )
+        .await?
     }
 
     /// 请求设置位置。
@@ -57,7 +186,25 @@ impl CalibrationHandle {
                 respond_to,
             })
             .map_err(|_| CALIBRATION_ERROR)?;
-        response_rx.await.map_err(|_| CALIBRATION_ERROR)?
+        await_reply(response_rx, CALIBRATION_ERROR).await?
+    }
+
+    /// 请求在下一段持续静止处重新锚定世界原点。
+    pub async fn request_re_anchor_origin(&self) -> Result<(), &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(CorrectionRequest::ReAnchorOrigin { respond_to })
+            .map_err(|_| CALIBRATION_ERROR)?;
+        await_reply(response_rx, CALIBRATION_ERROR).await?
+    }
+
+    /// 请求按下一段足够长的运动方向对齐输出航向。
+    pub async fn request_arm_heading_from_motion(&self) -> Result<(), &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(CorrectionRequest::ArmHeadingFromMotion { respond_to })
+            .map_err(|_| CALIBRATION_ERROR)?;
+        await_reply(response_rx, CALIBRATION_ERROR).await?
     }
 }
 
@@ -79,7 +226,7 @@ impl PipelineConfigHandle {
         self.tx
             .send(PipelineConfigRequest::Get { respond_to })
             .map_err(|_| PIPELINE_CONFIG_ERROR)?;
-        response_rx.await.map_err(|_| PIPELINE_CONFIG_ERROR)
+        await_reply(response_rx, PIPELINE_CONFIG_ERROR).await
     }
 
     /// 更新并应用配置。
@@ -91,7 +238,94 @@ impl PipelineConfigHandle {
                 respond_to,
             })
             .map_err(|_| PIPELINE_CONFIG_ERROR)?;
-        response_rx.await.map_err(|_| PIPELINE_CONFIG_ERROR)?
+        await_reply(response_rx, PIPELINE_CONFIG_ERROR).await?
+    }
+
+    /// 增量应用配置，返回实际采用的生效方式。
+    pub async fn patch_config(
+        &self,
+        config: ProcessorPipelineConfig,
+    ) -> Result<ConfigApplyMode, &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::Patch {
+                config: Box::new(config),
+                respond_to,
+            })
+            .map_err(|_| PIPELINE_CONFIG_ERROR)?;
+        await_reply(response_rx, PIPELINE_CONFIG_ERROR).await?
+    }
+
+    /// 暂停或恢复导航，返回状态是否改变。
+    pub async fn set_paused(&self, paused: bool) -> Result<bool, &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::SetPaused { paused, respond_to })
+            .map_err(|_| PIPELINE_CONTROL_ERROR)?;
+        await_reply(response_rx, PIPELINE_CONTROL_ERROR).await
+    }
+
+    /// 丢弃导航时间基准，下一帧 dt 记 0。
+    pub async fn reset_timing(&self) -> Result<(), &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::ResetTiming { respond_to })
+            .map_err(|_| PIPELINE_CONFIG_ERROR)?;
+        await_reply(response_rx, PIPELINE_CONFIG_ERROR).await
+    }
+
+    /// 在实时管线状态的副本上演算一个样本。
+    pub async fn explain_sample(
+        &self,
+        sample: ImuSampleRaw,
+    ) -> Result<SampleExplanation, &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::Explain {
+                sample: Box::new(sample),
+                respond_to,
+            })
+            .map_err(|_| PIPELINE_EXPLAIN_ERROR)?;
+        await_reply(response_rx, PIPELINE_EXPLAIN_ERROR).await
+    }
+
+    /// 设置标称采样间隔 (ms)。
+    pub async fn set_sample_interval(&self, sample_interval_ms: f64) -> Result<(), &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::SetSampleInterval {
+                sample_interval_ms,
+                respond_to,
+            })
+            .map_err(|_| PIPELINE_CONFIG_ERROR)?;
+        await_reply(response_rx, PIPELINE_CONFIG_ERROR).await
+    }
+
+    /// 获取当前姿态零位。
+    pub async fn get_axis_offset(&self) -> Result<AxisCalibration, &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::GetAxisOffset { respond_to })
+            .map_err(|_| CALIBRATION_ERROR)?;
+        await_reply(response_rx, CALIBRATION_ERROR).await
+    }
+
+    /// 获取当前世界系的姿态零位与重力参考。
+    pub async fn get_recording_calibration(&self) -> Result<RecordingCalibration, &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::GetRecordingCalibration { respond_to })
+            .map_err(|_| CALIBRATION_ERROR)?;
+        await_reply(response_rx, CALIBRATION_ERROR).await
+    }
+
+    /// 设置姿态零位。
+    pub async fn set_axis_offset(&self, offset: AxisCalibration) -> Result<(), &'static str> {
+        let (respond_to, response_rx) = oneshot::channel();
+        self.tx
+            .send(PipelineConfigRequest::SetAxisOffset { offset, respond_to })
+            .map_err(|_| CALIBRATION_ERROR)?;
+        await_reply(response_rx, CALIBRATION_ERROR).await
     }
 }
 
@@ -99,15 +333,12 @@ impl PipelineConfigHandle {
 ///
 /// * `imu_client`: 与IMU连接相关的客户端 上游
 /// * `processor`: 数据处理器
-/// * `downstream_rx`: 交给tauri command用来收数据的通道
+/// * `output_fanout`: 输出订阅注册表，每个订阅者一条队列
 pub struct AppState {
     imu_client: Mutex<IMUClient>,
 
     processor: Processor,
 
-    /// 下游订阅通道。
-    pub downstream_rx: Receiver<ResponseData>,
-
     /// 录制控制通道。
     pub recorder_tx: flume::Sender<RecorderCommand>,
 
@@ -122,42 +353,183 @@ pub struct AppState {
 
     /// 诊断开关（跨线程共享）。
     pub diagnostics_flag: DiagnosticsFlag,
+
+    /// 处理器运行统计。
+    pub processor_stats: ProcessorStatsHandle,
+
+    /// 录制数据库存放位置。
+    pub recording_location: RecordingLocation,
+
+    /// 展示与导出使用的单位偏好，管线内部始终为国际单位。
+    pub units: UnitPreferencesStore,
+
+    /// 故障注入（仅开发构建生效），处理线程与录制线程共享。
+    pub faults: FaultInjectorHandle,
+
+    /// 处理线程与录制线程的调度提示结果。
+    scheduling: SchedulingStatusHandle,
+
+    /// 设备上报频率 (Hz)，连接时写入设备，并决定管线的标称采样间隔。
+    report_rate: AtomicU8,
+
+    /// 上游通道发送端（自检注入合成数据包）。
+    upstream_tx: flume::Sender<RawImuData>,
+
+    /// 输出订阅注册表（前端订阅、姿态流、自检与 recorder）。
+    output_fanout: OutputFanoutHandle,
+
+    /// 最新输出帧（供按需读取当前帧的面板使用）。
+    latest_frame: LatestFrameHandle,
+
+    /// 发往前端订阅的近期消息（重新订阅时回放）。
+    output_history: OutputHistoryHandle,
+
+    /// 设备/主机时钟同步结果（诊断读取）。
+    clock_sync: ClockSyncHandle,
+
+    /// 标定向导状态（处理线程发布，前端按需查询）。
+    calibration_state: CalibrationStateHandle,
+
+    /// 数据流看门狗（连接中数据停滞时提示并尝试恢复）。
+    watchdog: Mutex<DataFlowWatchdog>,
+
+    /// 数据订阅计数（空闲模式据此判断是否有人消费）。
+    subscribers: SubscriberCount,
+
+    /// 空闲模式（无人消费时停止设备上报）。
+    idle: Mutex<IdleManager>,
+
+    /// 进行中的导出（每个会话同时只允许一个，`cancel_export` 据此取消）。
+    exports: ExportRegistry,
+
+    /// 录制汇总统计的短时缓存。
+    statistics: StatisticsCache,
+
+    /// 设备档案生效期间被覆盖前的基础配置，没有档案生效时为空。
+    profile_base: Mutex<Option<ProcessorPipelineConfig>>,
+
+    /// 录制回放控制器。
+    playback: Mutex<PlaybackController>,
+    /// 回放命令唤醒回放任务，不必等到下一次超时。
+    playback_wake: Arc<Notify>,
 }
 
 impl AppState {
     /// 蓝牙数据包 -.-> |btleplug| imu_client
     /// imu_client -.-> |flume::bounded| processor
-    /// processor -.-> |flume::bounded| sub
+    /// processor -.-> |flume::bounded| fanout
+    /// fanout -.-> |每个订阅者一条 flume::bounded| sub / recorder
     /// sub -.-> |tauri ipc channel| front end
     /// 创建应用状态。
+    ///
+    /// 处理线程、录制线程与全部控制/数据通道都在这里创建，与蓝牙连接状态无关：
+    /// 未连接设备时处理线程只是没有输入，校准、录制、订阅等命令照常可用。
     pub fn new(app_handle: tauri::AppHandle) -> Self {
         let (upstream_tx, upstream_rx) = flume::bounded(256);
-        let (downstream_tx, downstream_rx) = flume::bounded(256);
+        let (output_tx, output_rx) = flume::bounded(FANOUT_CHANNEL_CAPACITY);
         let (record_tx, record_rx) = flume::bounded(2048);
+        let output_fanout = OutputFanout::new_handle();
+        // recorder 与其他订阅者一样挂在分发上，只是队列无损
+        output_fanout.add_frame_sink(record_tx.clone());
+        spawn_fanout(output_rx, output_fanout.clone());
         let (recorder_tx, recorder_rx) = flume::unbounded();
-        spawn_recorder(record_rx, recorder_rx);
+        let config_dir = app_handle.path().app_config_dir().ok();
+        let recording_location =
+            RecordingLocation::load(config_dir.clone(), app_handle.path().app_data_dir().ok());
+        let recorder_app_handle = app_handle.clone();
+        let faults = FaultInjector::new_handle();
+        let stage_capture = StageCapture::new_handle();
+        let scheduling = SchedulingStatus::new_handle();
+        spawn_recorder(
+            record_rx,
+            recorder_rx,
+            RecorderOptions {
+                min_free_bytes: recording_location.min_free_bytes(),
+                flight_recorder_frames: flight_recorder_capacity(
+                    recording_location.flight_recorder_secs(),
+                    IMUConfig::default().report_rate,
+                ),
+                on_stopped: Some(Box::new(move |event| {
+                    if let Err(e) = recorder_app_handle.emit("recording_stopped", event) {
+                        tracing::warn!("推送 recording_stopped 事件失败: {:?}", e);
+                    }
+                })),
+                faults: faults.clone(),
+                stage_capture: stage_capture.clone(),
+                ..RecorderOptions::default()
+            },
+        );
+        queue_database_repair(&recorder_tx, recording_location.db_path());
+        spawn_database_check(app_handle.clone(), recording_location.db_path());
         let (calibration_handle, calibration_rx) = CalibrationHandle::new();
         let (pipeline_config_handle, pipeline_config_rx) = PipelineConfigHandle::new();
-        let (diagnostics_tx, diagnostics_rx) = flume::bounded::<PipelineDiagnostics>(64);
+        let (diagnostics_tx, diagnostics_rx) = diagnostics_channel(64);
         let diagnostics_flag: DiagnosticsFlag = Arc::new(AtomicBool::new(false));
+        let processor_stats = ProcessorStats::new_handle();
+        let latest_frame = LatestFrame::new_handle();
+        let output_history = OutputHistory::new_handle();
+        // 处理线程与回放共用序号，回放消息不会被订阅端当作历史丢弃
+        let stream_seq = StreamSeq::new_handle();
+        let clock_sync = ClockSyncSlot::new_handle();
+        let calibration_state = CalibrationStateSlot::new_handle();
+        spawn_watchdog(app_handle.clone());
+        spawn_idle_manager(app_handle.clone());
+        let playback_wake = Arc::new(Notify::new());
+        spawn_playback(app_handle.clone(), playback_wake.clone());
+        let closed_app_handle = app_handle.clone();
+        let imu_client = IMUClient::new(upstream_tx.clone(), processor_stats.clone())
+            .with_channel_closed_hook(Arc::new(move |closed: PipelineChannelClosed| {
+                if let Err(e) = closed_app_handle.emit("pipeline_channel_closed", closed) {
+                    tracing::warn!("推送 pipeline_channel_closed 事件失败: {:?}", e);
+                }
+            }));
         AppState {
-            imu_client: Mutex::new(IMUClient::new(upstream_tx)),
+            imu_client: Mutex::new(imu_client),
             processor: Processor::new(
                 upstream_rx,
-                downstream_tx,
+                output_tx,
                 record_tx,
+                recorder_tx.clone(),
                 calibration_rx,
                 pipeline_config_rx,
                 diagnostics_flag.clone(),
                 diagnostics_tx,
+                processor_stats.clone(),
+                latest_frame.clone(),
+                output_history.clone(),
+                stream_seq.clone(),
+                clock_sync.clone(),
+                calibration_state.clone(),
+                faults.clone(),
+                stage_capture,
+                scheduling.clone(),
                 app_handle,
             ),
-            downstream_rx,
             recorder_tx,
             calibration_handle,
             pipeline_config_handle,
             diagnostics_rx,
             diagnostics_flag,
+            processor_stats,
+            recording_location,
+            units: UnitPreferencesStore::load(config_dir),
+            faults,
+            scheduling,
+            report_rate: AtomicU8::new(IMUConfig::default().report_rate),
+            upstream_tx,
+            output_fanout,
+            latest_frame,
+            output_history,
+            clock_sync,
+            calibration_state,
+            watchdog: Mutex::new(DataFlowWatchdog::default()),
+            subscribers: SubscriberCount::default(),
+            idle: Mutex::new(IdleManager::default()),
+            exports: ExportRegistry::default(),
+            statistics: StatisticsCache::default(),
+            profile_base: Mutex::new(None),
+            playback: Mutex::new(PlaybackController::with_seq(stream_seq)),
+            playback_wake,
         }
     }
 
@@ -167,15 +539,32 @@ impl AppState {
     }
 
     /// 请求姿态零位校准。
-    pub async fn request_axis_calibration(&self) -> Result<(), &'static str> {
+    pub async fn request_axis_calibration(&self) -> Result<CalibrationQuality, &'static str> {
         self.calibration_handle.request_axis_calibration().await
     }
 
+    /// 请求一次性完成水平、航向与原点校准。
+    pub async fn request_calibrate_all(&self) -> Result<FullCalibration, &'static str> {
+        self.calibration_handle.request_calibrate_all().await
+    }
+
     /// 请求设置位置。
     pub async fn request_set_position(&self, x: f64, y: f64, z: f64) -> Result<(), &'static str> {
         self.calibration_handle.request_set_position(x, y, z).await
     }
 
+    /// 请求在下一段持续静止处重新锚定世界原点。
+    pub async fn request_re_anchor_origin(&self) -> Result<(), &'static str> {
+        self.calibration_handle.request_re_anchor_origin().await
+    }
+
+    /// 请求按下一段足够长的运动方向对齐输出航向。
+    pub async fn request_arm_heading_from_motion(&self) -> Result<(), &'static str> {
+        self.calibration_handle
+            .request_arm_heading_from_motion()
+            .await
+    }
+
     /// 获取当前生效的 Pipeline 配置。
     pub async fn get_pipeline_config(&self) -> Result<ProcessorPipelineConfig, &'static str> {
         self.pipeline_config_handle.get_config().await
@@ -189,6 +578,27 @@ impl AppState {
         self.pipeline_config_handle.update_config(config).await
     }
 
+    /// 增量应用 Pipeline 配置，可调参数不重置导航状态。
+    pub async fn patch_pipeline_config(
+        &self,
+        config: ProcessorPipelineConfig,
+    ) -> Result<ConfigApplyMode, &'static str> {
+        self.pipeline_config_handle.patch_config(config).await
+    }
+
+    /// 暂停或恢复导航（蓝牙连接与原始数据不受影响），返回状态是否改变。
+    pub async fn set_processing_paused(&self, paused: bool) -> Result<bool, &'static str> {
+        self.pipeline_config_handle.set_paused(paused).await
+    }
+
+    /// 在实时管线状态的副本上演算一个样本，返回各阶段的输入、输出与参数。
+    pub async fn explain_sample(
+        &self,
+        sample: ImuSampleRaw,
+    ) -> Result<SampleExplanation, &'static str> {
+        self.pipeline_config_handle.explain_sample(sample).await
+    }
+
     /// 持久化当前生效的 Pipeline 配置到 processor.toml。
     pub async fn save_pipeline_config_to_file(&self) -> Result<(), &'static str> {
         let config = self.get_pipeline_config().await?;
@@ -197,6 +607,834 @@ impl AppState {
         std::fs::write(path, content).map_err(|_| PIPELINE_CONFIG_SAVE_ERROR)?;
         Ok(())
     }
+
+    /// 当前设备配置：上报频率取自应用状态，启用 `baro` 时订阅气压计。
+    pub async fn imu_config(&self) -> IMUConfig {
+        let barometer = self
+            .get_pipeline_config()
+            .await
+            .is_ok_and(|config| config.baro.enabled);
+        IMUConfig::with_report_rate(self.report_rate.load(Ordering::Relaxed))
+            .with_subscription(Subscription::Barometer, barometer)
+    }
+
+    /// 以当前设备配置连接设备，并按其上报频率同步管线的标称采样间隔。
+    ///
+    /// 连接前先按设备 ID 切换标定档案，见 [`apply_device_profile`](Self::apply_device_profile)。
+    /// 已连接到同一设备时直接返回当前设备信息；连着其他设备时先断开，见 [`connect_exclusive`]。
+    /// `max_attempts` 为瞬时错误下最多尝试的次数，缺省取 [`ConnectRetryPolicy::default`]。
+    /// 因设备不可达失败时短暂重新扫描，见 [`IMUClient::explain_connect_failure`]。
+    pub async fn connect_peripheral(
+        &self,
+        uuid: &str,
+        max_attempts: Option<u32>,
+    ) -> anyhow::Result<PeripheralInfo> {
+        let mut retry = ConnectRetryPolicy::default();
+        if let Some(max_attempts) = max_attempts {
+            retry = retry.with_max_attempts(max_attempts);
+        }
+        // 持锁直到连接完成：重复点击发出的请求排在后面，等前一次连上后直接沿用
+        let mut client = self.client().await;
+        let prepare = async {
+            self.apply_device_profile(uuid).await?;
+            Ok::<_, anyhow::Error>(self.imu_config().await)
+        };
+        // 帧计数、连接时长与重连次数由客户端在连接成功时更新
+        let outcome = match connect_exclusive(&mut *client, uuid, &retry, prepare).await {
+            Ok(outcome) => outcome,
+            // 设备不可达时重新扫描一次，错误中说明设备是否还在；扫描要数秒，先释放客户端锁，
+            // 期间状态查询、看门狗与断开不被阻塞
+            Err(err) => {
+                let rescan = client.failure_rescan().await;
+                drop(client);
+                return Err(rescan.explain(uuid, err).await);
+            }
+        };
+        let (info, config) = match outcome {
+            ConnectOutcome::Reused(info) => return Ok(info),
+            ConnectOutcome::Connected { info, config } => (info, config),
+        };
+        drop(client);
+        self.pipeline_config_handle
+            .set_sample_interval(config.sample_interval_ms())
+            .await
+            .map_err(|err| anyhow!(err))?;
+        Ok(info)
+    }
+
+    /// 按设备档案切换管线配置：有档案时合并到基础配置上并恢复姿态零位，没有时回到基础配置。
+    ///
+    /// 读取或合并档案失败只记录警告，按没有档案处理，不影响连接。
+    async fn apply_device_profile(&self, device_id: &str) -> anyhow::Result<()> {
+        let mut profile_base = self.profile_base.lock().await;
+        let base = match profile_base.as_ref() {
+            Some(base) => base.clone(),
+            None => self
+                .get_pipeline_config()
+                .await
+                .map_err(|err| anyhow!(err))?,
+        };
+        let profile = get_device_profile(&self.recording_db_path(), device_id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("读取设备档案失败: {:#}", e);
+                None
+            });
+        let applied = profile.and_then(|profile| match profile.apply_to(&base) {
+            Ok(config) => Some((config, profile.axis_offset)),
+            Err(e) => {
+                tracing::warn!(device_id, "设备档案无法应用: {:#}", e);
+                None
+            }
+        });
+        match applied {
+            Some((config, axis_offset)) => {
+                self.update_pipeline_config(config)
+                    .await
+                    .map_err(|err| anyhow!(err))?;
+                if let Some(offset) = axis_offset {
+                    self.pipeline_config_handle
+                        .set_axis_offset(offset)
+                        .await
+                        .map_err(|err| anyhow!(err))?;
+                }
+                *profile_base = Some(base);
+                tracing::info!(device_id, "已应用设备标定档案");
+            }
+            None => {
+                tracing::info!(device_id, "设备没有标定档案，使用基础配置");
+                if let Some(base) = profile_base.take() {
+                    self.update_pipeline_config(base)
+                        .await
+                        .map_err(|err| anyhow!(err))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 以当前生效的标定配置与姿态零位保存已连接设备的档案。
+    pub async fn save_device_profile(&self) -> anyhow::Result<DeviceProfile> {
+        let Some(peripheral) = self.client().await.connected_peripheral().await else {
+            bail!("未连接设备，无法保存设备档案");
+        };
+        let config = self
+            .get_pipeline_config()
+            .await
+            .map_err(|err| anyhow!(err))?;
+        let axis_offset = self
+            .pipeline_config_handle
+            .get_axis_offset()
+            .await
+            .map_err(|err| anyhow!(err))?;
+        let profile =
+            DeviceProfile::capture(&peripheral.id, &config, axis_offset, host_now_ms().0 as i64)?;
+        save_device_profile(&self.recording_db_path(), &profile).await?;
+        Ok(profile)
+    }
+
+    /// 设备重启后按当前设备配置重新初始化；空闲模式下不恢复上报。
+    pub async fn reinitialize_device(&self) -> anyhow::Result<()> {
+        let config = self.imu_config().await;
+        let enable_reporting = !self.idle.lock().await.is_idle();
+        let client = self.client().await;
+        if client.connected_peripheral().await.is_none() {
+            return Ok(());
+        }
+        client.reinitialize(&config, enable_reporting).await
+    }
+
+    /// 修改设备上报频率：已连接时立即写入设备，管线的标称采样间隔与飞行记录仪容量随之更新。
+    ///
+    /// `report_rate = 0` 表示 0.5 Hz，有效范围 0–250。
+    pub async fn set_report_rate(&self, report_rate: u8) -> Result<(), &'static str> {
+        if report_rate > 250 {
+            return Err(REPORT_RATE_ERROR);
+        }
+        self.report_rate.store(report_rate, Ordering::Relaxed);
+        let _ = self
+            .recorder_tx
+            .send(RecorderCommand::ResizeFlightRecorder {
+                capacity: flight_recorder_capacity(
+                    self.recording_location.flight_recorder_secs(),
+                    report_rate,
+                ),
+            });
+        let config = self.imu_config().await;
+        {
+            let client = self.client().await;
+            if client.connected_peripheral().await.is_some() {
+                client.set_config(&config).await.map_err(|err| {
+                    tracing::error!("写入设备配置失败: {:#}", err);
+                    DEVICE_CONFIG_ERROR
+                })?;
+            }
+        }
+        self.pipeline_config_handle
+            .set_sample_interval(config.sample_interval_ms())
+            .await
+    }
+
+    /// 修改数据流停滞阈值（毫秒），至少 100 ms。
+    pub async fn set_stall_threshold_ms(&self, threshold_ms: u64) -> Result<(), &'static str> {
+        if threshold_ms < 100 {
+            return Err(STALL_THRESHOLD_ERROR);
+        }
+        self.watchdog
+            .lock()
+            .await
+            .set_stall_threshold_ms(threshold_ms);
+        Ok(())
+    }
+
+    /// 最新输出帧，尚无数据时为 `None`。
+    pub fn latest_frame(&self) -> Option<ResponseData> {
+        self.latest_frame.get()
+    }
+
+    /// 按当前单位偏好格式化的最新输出帧，尚无数据时为 `None`。
+    pub fn latest_frame_display(&self) -> Option<FrameDisplay> {
+        let frame = self.latest_frame.get()?;
+        Some(self.units.converter().frame_display(&frame))
+    }
+
+    /// 设备运行时长、连接时长与重连次数。
+    pub fn connection_uptime(&self) -> ConnectionUptime {
+        self.processor_stats.snapshot().uptime(host_now_ms())
+    }
+
+    /// 最新输出帧的主机时间年龄（毫秒），尚无数据时为 `None`。
+    pub fn latest_frame_age_ms(&self) -> Option<u64> {
+        self.latest_frame.age_ms()
+    }
+
+    /// 当前设备/主机时钟同步结果，尚未建立时为 `None`。
+    pub fn clock_sync(&self) -> Option<ClockSyncEstimate> {
+        self.clock_sync.get()
+    }
+
+    /// 最新发布的标定向导状态。
+    pub fn calibration_state(&self) -> CalibrationWizardSnapshot {
+        self.calibration_state.get()
+    }
+
+    /// 订阅高频姿态流，按 `max_rate_hz` 抽取，返回订阅编号与接收端。
+    pub fn subscribe_attitude(
+        &self,
+        max_rate_hz: f64,
+    ) -> Result<(SubscriptionId, Receiver<AttitudeFrame>), &'static str> {
+        if !(1.0..=1000.0).contains(&max_rate_hz) {
+            return Err(ATTITUDE_RATE_ERROR);
+        }
+        Ok(self.output_fanout.subscribe_attitude(max_rate_hz))
+    }
+
+    /// 订阅输出流：登记订阅后按选项生成快照与回放，返回订阅编号、接收端与回放。
+    ///
+    /// 调用方先发出 [`OutputReplay::messages`]，之后只转发
+    /// [`OutputReplay::is_live`] 的实时消息。
+    pub fn subscribe_output(
+        &self,
+        options: &OutputSubscribeOptions,
+    ) -> Result<(SubscriptionId, Receiver<OutputMessage>, OutputReplay), &'static str> {
+        if options
+            .max_rate_hz
+            .is_some_and(|rate| !(1.0..=1000.0).contains(&rate))
+        {
+            return Err(BACKFILL_RATE_ERROR);
+        }
+        if options
+            .live_max_rate_hz
+            .is_some_and(|rate| !(1.0..=1000.0).contains(&rate))
+        {
+            return Err(LIVE_RATE_ERROR);
+        }
+        if options.batching
+            && !((1..=MAX_BATCH_WINDOW_MS).contains(&options.batch_window_ms)
+                && (1..=MAX_BATCH_LEN).contains(&options.batch_max_frames))
+        {
+            return Err(BATCHING_ERROR);
+        }
+        // 登记必须先于取历史，见 processor::history
+        let (id, rx) = match options.rate_mode {
+            OutputRateMode::Fixed => self.output_fanout.subscribe_full(
+                OUTPUT_CHANNEL_CAPACITY,
+                options.live_max_rate_hz,
+                options.encoding,
+            ),
+            OutputRateMode::Adaptive { min_hz, max_hz } => {
+                if !(1.0 <= min_hz && min_hz <= max_hz && max_hz <= 1000.0)
+                    || options.live_max_rate_hz.is_some()
+                {
+                    return Err(ADAPTIVE_RATE_ERROR);
+                }
+                self.output_fanout.subscribe_adaptive(
+                    OUTPUT_CHANNEL_CAPACITY,
+                    min_hz,
+                    max_hz,
+                    options.encoding,
+                )
+            }
+        };
+        let replay = self.output_history.replay(self.latest_frame.get(), options);
+        Ok((id, rx, replay))
+    }
+
+    /// 输出分发注册表（诊断订阅据此附上各订阅的当前频率）。
+    pub fn output_fanout(&self) -> OutputFanoutHandle {
+        self.output_fanout.clone()
+    }
+
+    /// 注销输出订阅（含姿态流），返回该订阅是否存在。
+    pub fn unsubscribe_output(&self, id: SubscriptionId) -> bool {
+        self.output_fanout.unsubscribe(id)
+    }
+
+    /// 登记一个数据订阅，返回的守卫在订阅结束时释放。
+    pub fn track_subscriber(&self) -> SubscriberGuard {
+        self.subscribers.acquire()
+    }
+
+    /// 当前录制数据库路径。
+    pub fn recording_db_path(&self) -> std::path::PathBuf {
+        self.recording_location.db_path()
+    }
+
+    /// 设置录制目录，并立即在录制线程中准备新目录下的数据库，见 [`queue_database_repair`]。
+    pub fn set_recording_directory(
+        &self,
+        directory: std::path::PathBuf,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let directory = self.recording_location.set_directory(directory)?;
+        queue_database_repair(&self.recorder_tx, self.recording_db_path());
+        Ok(directory)
+    }
+
+    /// 进行中的导出登记表。
+    pub fn exports(&self) -> &ExportRegistry {
+        &self.exports
+    }
+
+    /// 录制汇总统计缓存。
+    pub fn statistics(&self) -> &StatisticsCache {
+        &self.statistics
+    }
+
+    /// 加载录制会话用于回放，暂停在首帧；重跑管线时使用当前生效的配置。
+    pub async fn playback_load(
+        &self,
+        session_id: i64,
+        mode: PlaybackMode,
+    ) -> anyhow::Result<PlaybackStatus> {
+        let frames = load_session_frames(&self.recording_db_path(), session_id).await?;
+        let config = self.get_pipeline_config().await.map_err(|e| anyhow!(e))?;
+        let status = self
+            .playback
+            .lock()
+            .await
+            .load(session_id, frames, mode, config)?;
+        self.playback_wake.notify_one();
+        Ok(status)
+    }
+
+    /// 开始或继续回放；设备在上报数据或正在录制时拒绝，避免回放帧与实时帧交错。
+    pub async fn playback_play(&self) -> anyhow::Result<PlaybackStatus> {
+        if self.client().await.is_connected() {
+            return Err(PlaybackRefused::DeviceStreaming.into());
+        }
+        if recording_status(&self.recorder_tx).await?.recording {
+            return Err(PlaybackRefused::RecordingActive.into());
+        }
+        let status = self
+            .with_playback(|playback, now| playback.play(now))
+            .await?;
+        Ok(status)
+    }
+
+    /// 在回放控制器上执行一次操作（传入当前时刻），随后唤醒回放任务。
+    pub async fn with_playback<T>(
+        &self,
+        op: impl FnOnce(&mut PlaybackController, Instant) -> T,
+    ) -> T {
+        let result = op(&mut *self.playback.lock().await, Instant::now());
+        self.playback_wake.notify_one();
+        result
+    }
+
+    /// 送出已到期的回放帧，返回当前回放状态与距下一帧的等待时长。
+    ///
+    /// 回放帧直接交给输出分发，只到达前端订阅，不进入 recorder 等原始帧订阅。
+    async fn playback_tick(&self) -> (PlaybackStatus, Option<Duration>) {
+        let mut playback = self.playback.lock().await;
+        for item in playback.poll(Instant::now()) {
+            self.output_fanout.dispatch_playback(&item);
+        }
+        let wait = playback
+            .next_due()
+            .map(|due| due.saturating_duration_since(Instant::now()));
+        (playback.status(), wait)
+    }
+
+    /// 汇总连接、录制与处理状态快照。
+    pub async fn app_status(&self) -> Result<AppStatus, &'static str> {
+        let connected_peripheral = self.client().await.connected_peripheral().await;
+        let recording = recording_status(&self.recorder_tx)
+            .await
+            .map_err(|_| RECORDER_STATUS_ERROR)?;
+        let pipeline_config_hash = self.get_pipeline_config().await?.content_hash();
+        let stats = self.processor_stats.snapshot();
+        let uptime = self.connection_uptime();
+        let watchdog = self.watchdog.lock().await.snapshot();
+        let report_rate = self.report_rate.load(Ordering::Relaxed);
+
+        Ok(AppStatus {
+            connected_peripheral,
+            recording,
+            frames_since_connect: stats.frames_since_connect,
+            device_uptime_ms: uptime.device_uptime_ms,
+            connection_uptime_ms: uptime.connection_uptime_ms,
+            reconnect_count: uptime.reconnect_count,
+            last_device_timestamp_ms: stats.last_device_timestamp_ms,
+            last_host_timestamp_ms: stats.last_host_timestamp_ms,
+            gravity_estimate: stats.gravity_estimate,
+            skipped_integrations: stats.skipped_integrations,
+            quat_freezes: stats.quat_freezes,
+            parse_failures: stats.parse_failures,
+            non_finite_samples: stats.non_finite_samples,
+            input_packets_per_sec: stats.input_packets_per_sec,
+            input_frames_per_sec: stats.input_frames_per_sec,
+            input_bytes_per_sec: stats.input_bytes_per_sec,
+            report_rate,
+            report_rate_hz: 1000.0 / nominal_sample_interval_ms(report_rate),
+            pipeline_config_hash,
+            watchdog,
+            memory: BufferRegistry::global().report(),
+            fault_injection: self.faults.status(),
+            scheduling: self.scheduling.snapshot(),
+        })
+    }
+
+    async fn watchdog_tick(&self, app_handle: &tauri::AppHandle) {
+        let stats = self.processor_stats.snapshot();
+        let hooks = AppWatchdogHooks {
+            state: self,
+            app_handle,
+        };
+        let mut watchdog = self.watchdog.lock().await;
+        watchdog.set_sample_interval_ms(nominal_sample_interval_ms(
+            self.report_rate.load(Ordering::Relaxed),
+        ));
+        watchdog
+            .tick(host_now_ms().as_millis(), &stats, &hooks)
+            .await;
+    }
+
+    async fn idle_tick(&self, app_handle: &tauri::AppHandle) {
+        let Ok(config) = self.get_pipeline_config().await else {
+            return;
+        };
+        // 录制状态查询失败时按有人消费处理，宁可不省电也不能断了录制
+        let recording = recording_status(&self.recorder_tx)
+            .await
+            .map_or(true, |status| status.recording);
+        let consumed = recording || self.subscribers.get() > 0;
+        let connected = self.client().await.is_connected();
+        let hooks = AppIdleHooks {
+            state: self,
+            app_handle,
+        };
+        self.idle
+            .lock()
+            .await
+            .tick(
+                host_now_ms().as_millis(),
+                &config.idle,
+                connected,
+                consumed,
+                &hooks,
+            )
+            .await;
+    }
+}
+
+/// 在录制线程中修复上次崩溃遗留的未结束会话，不等待结果。
+///
+/// 修复前打开数据库会做完整性检查，按需备份并迁移表结构；大库迁移可能要数十秒，
+/// 提前排进录制线程，不留到第一次开始录制时才做。
+fn queue_database_repair(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    db_path: std::path::PathBuf,
+) {
+    let (repair_tx, _) = flume::bounded(1);
+    let _ = recorder_tx.send(RecorderCommand::Repair {
+        db_path,
+        reply: repair_tx,
+    });
+}
+
+/// 启动时检查录制数据库，完整性检查不通过时推送 `recordings_database_corrupt` 事件。
+fn spawn_database_check(app_handle: tauri::AppHandle, db_path: std::path::PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        if !db_path.exists() {
+            return;
+        }
+        match verify_database(&db_path).await {
+            Ok(report) if !report.ok => {
+                tracing::error!(problems = ?report.problems, "录制数据库完整性检查失败");
+                if let Err(e) = app_handle.emit("recordings_database_corrupt", report) {
+                    tracing::warn!("推送 recordings_database_corrupt 事件失败: {:?}", e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("检查录制数据库失败: {e:#}"),
+        }
+    });
+}
+
+/// 看门狗任务：定期检查数据流，AppState 交给 Tauri 托管之前跳过。
+fn spawn_watchdog(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(WATCHDOG_POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                state.watchdog_tick(&app_handle).await;
+            }
+        }
+    });
+}
+
+/// 空闲模式任务：定期统计消费者，AppState 交给 Tauri 托管之前跳过。
+fn spawn_idle_manager(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(IDLE_POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                state.idle_tick(&app_handle).await;
+            }
+        }
+    });
+}
+
+/// 回放任务：按帧到期时刻送出回放帧，播放中定期推送 `playback_status`，
+/// 状态变化（开始、暂停、播完）时立即推送；回放命令经 `wake` 随时唤醒。
+fn spawn_playback(app_handle: tauri::AppHandle, wake: Arc<Notify>) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_state = PlaybackState::Idle;
+        let mut last_emit: Option<Instant> = None;
+        loop {
+            let mut wait = PLAYBACK_STATUS_INTERVAL;
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                let (status, next_due) = state.playback_tick().await;
+                let emit_due = last_emit.is_none_or(|at| at.elapsed() >= PLAYBACK_STATUS_INTERVAL);
+                if status.state != last_state
+                    || (status.state == PlaybackState::Playing && emit_due)
+                {
+                    if let Err(e) = app_handle.emit("playback_status", &status) {
+                        tracing::warn!("推送 playback_status 事件失败: {:?}", e);
+                    }
+                    last_state = status.state;
+                    last_emit = Some(Instant::now());
+                }
+                if let Some(next_due) = next_due {
+                    wait = wait.min(next_due);
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = wake.notified() => {}
+            }
+        }
+    });
+}
+
+struct AppWatchdogHooks<'a> {
+    state: &'a AppState,
+    app_handle: &'a tauri::AppHandle,
+}
+
+impl WatchdogHooks for AppWatchdogHooks<'_> {
+    async fn is_connected(&self) -> bool {
+        let connected = self.state.client().await.is_connected();
+        // 空闲模式主动停止了上报，不算停滞
+        connected && !self.state.idle.lock().await.is_idle()
+    }
+
+    fn emit(&self, event: WatchdogEvent) {
+        let (name, payload) = match event {
+            WatchdogEvent::Stalled(stall) => ("data_stalled", stall),
+            WatchdogEvent::Resumed(stall) => ("data_resumed", stall),
+        };
+        if let Err(e) = self.app_handle.emit(name, payload) {
+            tracing::warn!("推送 {} 事件失败: {:?}", name, e);
+        }
+    }
+
+    async fn recover(&self) -> anyhow::Result<()> {
+        self.state.client().await.enable_data_reporting().await
+    }
+}
+
+struct AppIdleHooks<'a> {
+    state: &'a AppState,
+    app_handle: &'a tauri::AppHandle,
+}
+
+impl IdleHooks for AppIdleHooks<'_> {
+    fn emit(&self, event: IdleEvent) {
+        let (name, payload) = match event {
+            IdleEvent::Idle(state) => ("stream_idle", state),
+            IdleEvent::Active(state) => ("stream_active", state),
+        };
+        if let Err(e) = self.app_handle.emit(name, payload) {
+            tracing::warn!("推送 {} 事件失败: {:?}", name, e);
+        }
+    }
+
+    async fn set_reporting(&self, enabled: bool) -> anyhow::Result<()> {
+        let client = self.state.client().await;
+        if enabled {
+            client.enable_data_reporting().await
+        } else {
+            client.disable_data_reporting().await
+        }
+    }
+
+    async fn reset_timing(&self) -> anyhow::Result<()> {
+        self.state
+            .pipeline_config_handle
+            .reset_timing()
+            .await
+            .map_err(|e| anyhow!(e))
+    }
+}
+
+impl AppState {
+    /// 无硬件自检：把合成数据包经真实上游通道送入处理线程，逐环节核对结果。
+    ///
+    /// 要求设备未连接且未在录制；结束后重置处理管线（会清除姿态零位校准）。
+    /// `record` 为真时额外做一次一次性录制并读回，数据库放在当前录制目录下，
+    /// 用完即删，因此录制目录不可写时会在 recorder 环节报错。
+    pub async fn run_self_test(&self, record: bool) -> SelfTestReport {
+        let mut report = SelfTestReport::start();
+
+        let started = Instant::now();
+        let preflight = self.self_test_preflight().await;
+        if !report.record(SelfTestStage::Preflight, "idle", started, preflight) {
+            return report.finish();
+        }
+
+        let started = Instant::now();
+        let sample_interval_ms = self.imu_config().await.sample_interval_ms();
+        let packets = selftest::encode_packets(&selftest::motion_profile(sample_interval_ms));
+        let reference = match self.get_pipeline_config().await {
+            Ok(config) => selftest::run_packets(config, sample_interval_ms, &packets),
+            Err(err) => {
+                let error = anyhow!(err).context("pipeline config unavailable");
+                report.record(SelfTestStage::Pipeline, "reference", started, Err(error));
+                return report.finish();
+            }
+        };
+        let reference_ok = match reference.last() {
+            Some(last) => Ok(format!(
+                "{} frames, final position ({:.4}, {:.4}, {:.4}) m",
+                reference.len(),
+                last.nav.position.x,
+                last.nav.position.y,
+                last.nav.position.z
+            )),
+            None => Err(anyhow!("reference pipeline produced no frames")),
+        };
+        if !report.record(SelfTestStage::Pipeline, "reference", started, reference_ok) {
+            return report.finish();
+        }
+
+        let db_path = self.recording_location.directory().join(SELF_TEST_DB_FILE);
+        let mut session_id = None;
+        if record {
+            let started = Instant::now();
+            remove_self_test_db(&db_path);
+            let result = start_recording(
+                &self.recorder_tx,
+                db_path.clone(),
+                RecordingStartInput {
+                    device_id: None,
+                    name: Some("self-test".into()),
+                    tags: None,
+                    split: RecordingSplit::default(),
+                    metadata: None,
+                    deep: false,
+                },
+            )
+            .await
+            .context("recorder start failed")
+            .map(|status| {
+                session_id = status.session_id;
+                format!("session {:?} at {}", status.session_id, db_path.display())
+            });
+            report.record(SelfTestStage::Recorder, "start", started, result);
+        }
+
+        let (tap, outputs) = self.output_fanout.subscribe_frames(reference.len() + 16);
+        let stats_before = self.processor_stats.snapshot();
+        let started = Instant::now();
+        let pushed = self.push_self_test_packets(&packets).await;
+        let upstream_ok = report.record(SelfTestStage::Upstream, "push_packets", started, pushed);
+
+        if upstream_ok {
+            let frames = collect_outputs(&outputs, reference.len()).await;
+            let elapsed = started.elapsed();
+            let count = selftest::verify_frame_count(reference.len(), frames.len());
+            report.record(SelfTestStage::Pipeline, "outputs", started, count);
+
+            let check_started = Instant::now();
+            let final_state = match (reference.last(), frames.last()) {
+                (Some(expected), Some(actual)) => selftest::verify_final_state(expected, actual),
+                _ => Err(anyhow!("no pipeline output to compare")),
+            };
+            report.record(
+                SelfTestStage::Navigation,
+                "final_state",
+                check_started,
+                final_state,
+            );
+
+            let check_started = Instant::now();
+            let stats = selftest::verify_stats(
+                &stats_before,
+                &self.processor_stats.snapshot(),
+                reference.len(),
+                reference
+                    .last()
+                    .map(|f| f.nav.timestamp_ms)
+                    .unwrap_or_default(),
+                elapsed,
+            );
+            report.record(SelfTestStage::Stats, "monitor_tick", check_started, stats);
+        }
+        self.output_fanout.unsubscribe(tap);
+
+        if let Some(session_id) = session_id {
+            let started = Instant::now();
+            let result = self
+                .finish_self_test_recording(&db_path, session_id, &reference)
+                .await;
+            report.record(SelfTestStage::Recorder, "write_read_back", started, result);
+        }
+        if record {
+            remove_self_test_db(&db_path);
+        }
+
+        // 清掉合成数据留下的导航状态与帧计数
+        if self
+            .upstream_tx
+            .send_async(RawImuData::Reset)
+            .await
+            .is_err()
+        {
+            tracing::warn!("自检结束后重置处理管线失败: 上游通道已关闭");
+        }
+        self.processor_stats.reset_connection();
+
+        let report = report.finish();
+        match report.first_failure() {
+            None => tracing::info!("自检通过，用时 {:.1} ms", report.total_ms),
+            Some(check) => tracing::warn!(stage = ?check.stage, "自检失败: {}", check.detail),
+        }
+        report
+    }
+
+    async fn self_test_preflight(&self) -> anyhow::Result<String> {
+        if let Some(device) = self.client().await.connected_peripheral().await {
+            bail!(
+                "device {} is connected, disconnect before self-test",
+                device.id
+            );
+        }
+        let recording = recording_status(&self.recorder_tx)
+            .await
+            .context("recorder status unavailable")?;
+        if recording.recording {
+            bail!("a recording is in progress, stop it before self-test");
+        }
+        Ok("no device connected, recorder idle".into())
+    }
+
+    async fn push_self_test_packets(&self, packets: &[Vec<u8>]) -> anyhow::Result<String> {
+        let closed = || anyhow!("upstream channel closed, processor thread not running");
+        self.upstream_tx
+            .send_async(RawImuData::Reset)
+            .await
+            .map_err(|_| closed())?;
+        for packet in packets {
+            self.upstream_tx
+                .send_async(RawImuData::Packet {
+                    data: packet.clone(),
+                    received_at: Instant::now(),
+                    // 合成包一次性推入，到达时刻没有意义
+                    received_at_ms: None,
+                })
+                .await
+                .map_err(|_| closed())?;
+        }
+        Ok(format!("{} packets", packets.len()))
+    }
+
+    async fn finish_self_test_recording(
+        &self,
+        db_path: &Path,
+        session_id: i64,
+        reference: &[OutputFrame],
+    ) -> anyhow::Result<String> {
+        // 录制线程优先处理控制命令，先等样本全部落盘再停止
+        let deadline = Instant::now() + SELF_TEST_TIMEOUT;
+        let mut stored = 0;
+        while Instant::now() < deadline {
+            let status = recording_status(&self.recorder_tx)
+                .await
+                .context("recorder status unavailable")?;
+            stored = status.sample_count.unwrap_or_default();
+            if !status.recording || stored >= reference.len() as u64 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        stop_recording(&self.recorder_tx)
+            .await
+            .context("recorder stop failed")?;
+        if stored < reference.len() as u64 {
+            bail!(
+                "recorder insert failed: stored {stored} of {} frames",
+                reference.len()
+            );
+        }
+        selftest::verify_recording_readback(db_path, session_id, reference).await
+    }
+}
+
+/// 从旁路收齐 `expected` 帧，超时则返回已收到的部分。
+async fn collect_outputs(
+    outputs: &flume::Receiver<OutputFrame>,
+    expected: usize,
+) -> Vec<OutputFrame> {
+    let mut frames = Vec::with_capacity(expected);
+    let deadline = tokio::time::Instant::now() + SELF_TEST_TIMEOUT;
+    while frames.len() < expected {
+        match tokio::time::timeout_at(deadline, outputs.recv_async()).await {
+            Ok(Ok(frame)) => frames.push(frame),
+            Ok(Err(_)) | Err(_) => break,
+        }
+    }
+    frames
+}
+
+fn remove_self_test_db(db_path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", db_path.display()));
+    }
 }
 
 impl Drop for AppState {
@@ -204,3 +1442,143 @@ impl Drop for AppState {
         self.processor.shutdown();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::parser::ImuSampleRaw;
+    use crate::processor::pipeline::{
+        diagnostics::QueueProbe, ProcessorPipeline, NO_RAW_DATA_ERROR,
+    };
+
+    enum StandInEvent {
+        Calibration(CorrectionRequest),
+        Config(PipelineConfigRequest),
+        Packet(Vec<u8>),
+    }
+
+    /// 处理线程的最小替身：收到控制请求前先消费已到达的数据包（与真实线程的快路径一致）。
+    fn spawn_pipeline() -> (
+        CalibrationHandle,
+        PipelineConfigHandle,
+        flume::Sender<Vec<u8>>,
+    ) {
+        let (calibration, calibration_rx) = CalibrationHandle::new();
+        let (pipeline_config, pipeline_config_rx) = PipelineConfigHandle::new();
+        let (packet_tx, packet_rx) = flume::unbounded::<Vec<u8>>();
+        std::thread::spawn(move || {
+            let (diag_tx, _diag_rx) = diagnostics_channel(1);
+            let mut pipeline = ProcessorPipeline::new(
+                ProcessorPipelineConfig::default(),
+                Arc::new(AtomicBool::new(false)),
+                diag_tx,
+                QueueProbe::detached(),
+            );
+            loop {
+                let event = flume::Selector::new()
+                    .recv(&calibration_rx, |r| r.ok().map(StandInEvent::Calibration))
+                    .recv(&pipeline_config_rx, |r| r.ok().map(StandInEvent::Config))
+                    .recv(&packet_rx, |r| r.ok().map(StandInEvent::Packet))
+                    .wait();
+                if !matches!(event, Some(StandInEvent::Packet(_))) {
+                    for packet in packet_rx.try_iter() {
+                        pipeline.process_packet(&packet);
+                    }
+                }
+                match event {
+                    Some(StandInEvent::Calibration(request)) => {
+                        pipeline.handle_calibration_request(request)
+                    }
+                    Some(StandInEvent::Config(PipelineConfigRequest::SetPaused {
+                        paused,
+                        respond_to,
+                    })) => {
+                        let _ = respond_to.send(pipeline.set_paused(paused));
+                    }
+                    Some(StandInEvent::Config(_)) => {}
+                    Some(StandInEvent::Packet(packet)) => {
+                        pipeline.process_packet(&packet);
+                    }
+                    None => break,
+                }
+            }
+        });
+        (calibration, pipeline_config, packet_tx)
+    }
+
+    #[tokio::test]
+    async fn commands_before_first_packet_fail_descriptively() {
+        let (calibration, pipeline_config, _packet_tx) = spawn_pipeline();
+        let started = Instant::now();
+        assert_eq!(
+            calibration.request_axis_calibration().await,
+            Err(NO_RAW_DATA_ERROR)
+        );
+        assert_eq!(
+            calibration.request_set_position(0.0, 0.0, 0.0).await,
+            Ok(())
+        );
+        assert_eq!(pipeline_config.set_paused(true).await, Ok(true));
+        assert!(started.elapsed() < CONTROL_REPLY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn commands_work_right_after_first_packet() {
+        let (calibration, _pipeline_config, packet_tx) = spawn_pipeline();
+        // 静置 1.2 s：零位校准采满质量窗口后回复
+        let first = selftest::motion_profile(4.0)[0];
+        let still: Vec<ImuSampleRaw> = (0..300u64)
+            .map(|i| ImuSampleRaw {
+                timestamp_ms: first.timestamp_ms.add_ms(i * 4),
+                ..first
+            })
+            .collect();
+        let mut packets = selftest::encode_packets(&still).into_iter();
+        packet_tx.send(packets.next().unwrap()).unwrap();
+        let feed = async {
+            // 校准请求先到达，之后的数据包计入窗口
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            for packet in packets {
+                packet_tx.send(packet).unwrap();
+            }
+        };
+        let (quality, ()) = tokio::join!(calibration.request_axis_calibration(), feed);
+        let quality = quality.unwrap();
+        assert!(quality.applied, "{quality:?}");
+        assert!(quality.score > 90.0, "{quality:?}");
+        assert_eq!(
+            calibration.request_set_position(1.0, 2.0, 3.0).await,
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn unanswered_requests_time_out_instead_of_hanging() {
+        let (calibration, calibration_rx) = CalibrationHandle::new();
+        let (pipeline_config, pipeline_config_rx) = PipelineConfigHandle::new();
+        let started = Instant::now();
+        let (axis, position, config) = tokio::join!(
+            calibration.request_axis_calibration(),
+            calibration.request_set_position(0.0, 0.0, 0.0),
+            pipeline_config.get_config(),
+        );
+        assert_eq!(axis, Err(PIPELINE_UNRESPONSIVE_ERROR));
+        assert_eq!(position, Err(PIPELINE_UNRESPONSIVE_ERROR));
+        assert_eq!(config.unwrap_err(), PIPELINE_UNRESPONSIVE_ERROR);
+        // 零位校准要等一个质量窗口，超时更长但同样有界
+        assert!(started.elapsed() < AXIS_CALIBRATION_REPLY_TIMEOUT + CONTROL_REPLY_TIMEOUT);
+
+        // 处理线程已退出：立即返回，不等超时
+        drop((calibration_rx, pipeline_config_rx));
+        let started = Instant::now();
+        assert_eq!(
+            calibration.request_axis_calibration().await,
+            Err(CALIBRATION_ERROR)
+        );
+        assert_eq!(
+            pipeline_config.set_paused(true).await,
+            Err(PIPELINE_CONTROL_ERROR)
+        );
+        assert!(started.elapsed() < CONTROL_REPLY_TIMEOUT);
+    }
+}
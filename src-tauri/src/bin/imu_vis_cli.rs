@@ -0,0 +1,133 @@
+//! 无界面命令行工具。
+//!
+//! 不启动 Tauri 窗口，直接用库里的处理管线重跑录制数据，便于在 CI 中对比
+//! 不同管线版本在基准录制上的结果。可在 `--no-default-features` 下编译。
+//!
+//! CLI：
+//! ```bash
+//! cargo run --no-default-features --bin imu_vis_cli -- process \
+//!     --input imu_recordings.sqlite \
+//!     --config processor.toml \
+//!     --output out.csv
+//! ```
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+
+use tauri_app_lib::{
+    headless::{process_recording, OutputFormat, ProcessOptions},
+    processor::pipeline::ProcessorPipelineConfig,
+};
+
+/// `process` 子命令参数。
+struct ProcessArgs {
+    input: PathBuf,
+    session: Option<String>,
+    config: Option<PathBuf>,
+    output: Option<PathBuf>,
+    format: Option<OutputFormat>,
+}
+
+fn parse_process_args(mut it: impl Iterator<Item = String>) -> Result<ProcessArgs> {
+    let mut input: Option<PathBuf> = None;
+    let mut session: Option<String> = None;
+    let mut config: Option<PathBuf> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut format: Option<OutputFormat> = None;
+
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = Some(PathBuf::from(it.next().context("--input 缺少值")?)),
+            "--session" | "-s" => session = Some(it.next().context("--session 缺少值")?),
+            "--config" | "-c" => {
+                config = Some(PathBuf::from(it.next().context("--config 缺少值")?))
+            }
+            "--output" | "-o" => {
+                output = Some(PathBuf::from(it.next().context("--output 缺少值")?))
+            }
+            "--format" => {
+                format = Some(match it.next().context("--format 缺少值")?.as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    other => return Err(anyhow!("未知输出格式: {other}（可选 csv / json）")),
+                })
+            }
+            "-h" | "--help" => {
+                print_help();
+                std::process::exit(0);
+            }
+            other => return Err(anyhow!("未知参数: {other}")),
+        }
+    }
+
+    Ok(ProcessArgs {
+        input: input.ok_or_else(|| anyhow!("必须提供 --input <sqlite>"))?,
+        session,
+        config,
+        output,
+        format,
+    })
+}
+
+fn print_help() {
+    eprintln!(
+        "imu_vis_cli — 无界面离线处理
+
+用法:
+  imu_vis_cli process --input <sqlite> [选项]
+
+选项:
+  --input, -i <path>          录制数据库（SQLite）
+  --session, -s <id_or_name>  会话 id 或完整名称（默认最近一次录制）
+  --config, -c <path>         管线配置 TOML（默认查找 processor.toml）
+  --output, -o <path>         结果序列输出路径（不指定则只打印汇总）
+  --format <csv|json>         输出格式（默认按扩展名推断，.json 以外为 csv）
+  -h, --help                  显示帮助"
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut it = std::env::args().skip(1);
+    match it.next().as_deref() {
+        Some("process") => {}
+        Some("-h") | Some("--help") | None => {
+            print_help();
+            return Ok(());
+        }
+        Some(other) => return Err(anyhow!("未知子命令: {other}")),
+    }
+    let args = parse_process_args(it)?;
+
+    let config = match &args.config {
+        Some(path) => ProcessorPipelineConfig::load_from_path(path)?,
+        None => {
+            ProcessorPipelineConfig::load_from_default_paths_with_modified()
+                .context("读取 processor.toml 失败")?
+                .config
+        }
+    };
+    config.validate()?;
+    let format = args.format.unwrap_or_else(|| {
+        args.output
+            .as_deref()
+            .map(OutputFormat::from_path)
+            .unwrap_or(OutputFormat::Csv)
+    });
+
+    let summary = process_recording(ProcessOptions {
+        input: args.input,
+        session: args.session,
+        config,
+        output: args.output.clone(),
+        format,
+    })
+    .await?;
+
+    if let Some(path) = &args.output {
+        eprintln!("[imu_vis_cli] 写入: {}", path.display());
+    }
+    println!("{summary}");
+    Ok(())
+}
@@ -1,12 +1,15 @@
 //! 离线 replay 工具。
 //!
 //! 从 SQLite 读取已录制的原始 IMU 样本，用当前 `processor.toml` 重新跑
-//! `ProcessorPipeline`，导出轨迹 CSV + 诊断 CSV，并可选地调用
+//! `ProcessorPipeline`，导出轨迹 CSV + 诊断 CSV + 阶段中间值 CSV，并可选地调用
 //! `scripts/report.py` 生成紧凑 Markdown 报告供 Claude Code 消费。
 //!
 //! 用途：让参数/算法的调整不必重新采集真实硬件数据。同一 session + 同一
 //! `processor.toml` ⇒ 逐位一致的输出，便于回归对比。
 //!
+//! 深度录制的会话带有录制时的阶段中间值，阶段 CSV 优先导出这些原始记录，
+//! 没有时才用重跑得到的中间值。
+//!
 //! CLI：
 //! ```bash
 //! cargo run --release --bin replay -- \
@@ -24,15 +27,16 @@ use anyhow::{anyhow, Context, Result};
 use math_f64::{DQuat, DVec3};
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
 
+use tauri_app_lib::headless::sample_row_to_raw;
 use tauri_app_lib::processor::{
-    parser::ImuSampleRaw,
+    output::StageCapture,
     pipeline::{
-        diagnostics::{PipelineDiagnostics, QueueProbe},
+        diagnostics::{DiagnosticsSender, PipelineDiagnostics, QueueProbe},
         ProcessorPipeline, ProcessorPipelineConfig,
     },
-    RawImuData,
 };
-use tauri_app_lib::recorder::{db, models};
+use tauri_app_lib::recorder::{db, filename::sanitize_filename, get_stage_samples, models};
+use tauri_app_lib::types::recording::RecordingStageSample;
 
 /// 解析过的 CLI 参数。
 struct Args {
@@ -40,6 +44,7 @@ struct Args {
     reference: Option<PathBuf>,
     traj_out: Option<PathBuf>,
     diag_out: Option<PathBuf>,
+    stages_out: Option<PathBuf>,
     no_report: bool,
     db_path: Option<PathBuf>,
     /// 把管线产出的 calc_* 字段写回 SQLite，覆盖录制时存储的值。破坏性操作。
@@ -51,6 +56,7 @@ fn parse_args() -> Result<Args> {
     let mut reference: Option<PathBuf> = None;
     let mut traj_out: Option<PathBuf> = None;
     let mut diag_out: Option<PathBuf> = None;
+    let mut stages_out: Option<PathBuf> = None;
     let mut db_path: Option<PathBuf> = None;
     let mut no_report = false;
     let mut write_back = false;
@@ -62,6 +68,9 @@ fn parse_args() -> Result<Args> {
             "--reference" | "-r" => reference = Some(PathBuf::from(it.next().context("--reference 缺少值")?)),
             "--traj-out" => traj_out = Some(PathBuf::from(it.next().context("--traj-out 缺少值")?)),
             "--diag-out" => diag_out = Some(PathBuf::from(it.next().context("--diag-out 缺少值")?)),
+            "--stages-out" => {
+                stages_out = Some(PathBuf::from(it.next().context("--stages-out 缺少值")?))
+            }
             "--db" => db_path = Some(PathBuf::from(it.next().context("--db 缺少值")?)),
             "--no-report" => no_report = true,
             "--write-back" => write_back = true,
@@ -79,6 +88,7 @@ fn parse_args() -> Result<Args> {
         reference,
         traj_out,
         diag_out,
+        stages_out,
         no_report,
         db_path,
         write_back,
@@ -97,7 +107,10 @@ fn print_help() {
   --reference, -r <csv>       参考轨迹 CSV（传给报告脚本）
   --traj-out <path>           轨迹 CSV 输出路径（默认 exports/replay_<session>_trajectory.csv）
   --diag-out <path>           诊断 CSV 输出路径（默认 exports/replay_<session>_diag.csv）
-  --db <path>                 SQLite 路径（默认 imu_recordings.sqlite）
+  --stages-out <path>         阶段中间值 CSV 输出路径（默认 exports/replay_<session>_stages.csv；
+                              深度录制的会话导出录制时的记录）
+  --db <path>                 SQLite 路径（默认工作目录下 imu_recordings.sqlite；
+                              应用内录制目录可在录制面板查看）
   --no-report                 只落盘 CSV，不调 scripts/report.py
   --write-back                破坏性：用新算法产出的 calc_* 字段覆盖 SQLite 原值
   -h, --help                  显示帮助"
@@ -112,6 +125,7 @@ async fn main() -> Result<()> {
     let snapshot = ProcessorPipelineConfig::load_from_default_paths_with_modified()
         .context("读取 processor.toml 失败")?;
     let config = snapshot.config.clone();
+    config.validate()?;
     eprintln!("[replay] 使用配置: {}", snapshot.source.display());
 
     // —— 2. 打开 DB，定位 session ——
@@ -148,22 +162,31 @@ async fn main() -> Result<()> {
 
     // —— 4. 构造 pipeline（诊断常开） ——
     let (diag_tx, diag_rx) = flume::unbounded::<PipelineDiagnostics>();
-    // Stub 通道：replay 不走真实上下游，用本地 unbounded 句柄占位即可。
-    let (upstream_tx, upstream_rx) = flume::unbounded::<RawImuData>();
-    std::mem::forget(upstream_tx); // 保持发送端存活，避免队列探针误报断连
-    let (downstream_tx, _downstream_rx) = flume::unbounded();
-    let (record_tx, _record_rx) = flume::unbounded();
-    let queue_probe = QueueProbe::new(upstream_rx, downstream_tx, record_tx);
+    // replay 不走真实上下游，队列探针不连接任何通道
+    let queue_probe = QueueProbe::detached();
     let diag_flag = Arc::new(AtomicBool::new(true));
-    let mut pipeline = ProcessorPipeline::new(config.clone(), diag_flag, diag_tx, queue_probe);
+    let diag_sender = DiagnosticsSender::new(diag_tx, diag_rx.clone());
+    let mut pipeline = ProcessorPipeline::new(config.clone(), diag_flag, diag_sender, queue_probe);
+    let stage_capture = StageCapture::new_handle();
+    stage_capture.set_enabled(true);
+    pipeline.set_stage_capture(stage_capture);
 
     // —— 5. 跑管线，收集输出帧和诊断 ——
     let mut frames: Vec<TrajectoryRow> = Vec::with_capacity(rows.len());
     let mut diags: Vec<PipelineDiagnostics> = Vec::with_capacity(rows.len());
+    let mut rerun_stages: Vec<RecordingStageSample> = Vec::with_capacity(rows.len());
     // 用于 write-back 模式：保留每个输出 frame 对应的原始行 id，便于按主键回写。
     let mut frame_row_ids: Vec<i64> = Vec::with_capacity(rows.len());
+    // 录制时的航向对齐只作用于输出，重跑时按会话保存的对齐角与生效时刻复现
+    let mut heading = session.heading_yaw_deg.zip(session.heading_aligned_at_ms);
     for row in &rows {
-        let raw = row_to_raw(row);
+        if let Some((yaw_deg, aligned_at_ms)) = heading {
+            if row.timestamp_ms >= aligned_at_ms {
+                pipeline.set_heading_yaw_deg(Some(yaw_deg));
+                heading = None;
+            }
+        }
+        let raw = sample_row_to_raw(row);
         if let Some(frame) = pipeline.process_sample_raw(raw) {
             frames.push(TrajectoryRow {
                 timestamp_ms: frame.nav.timestamp_ms,
@@ -172,6 +195,12 @@ async fn main() -> Result<()> {
                 att: frame.nav.attitude,
             });
             frame_row_ids.push(row.id);
+            if let Some(stages) = &frame.stages {
+                rerun_stages.push(RecordingStageSample::from_snapshot(
+                    frame.raw.timestamp_ms.as_millis(),
+                    stages,
+                ));
+            }
         }
         while let Ok(diag) = diag_rx.try_recv() {
             diags.push(diag);
@@ -183,24 +212,43 @@ async fn main() -> Result<()> {
     }
     eprintln!("[replay] 产出帧: {} 诊断: {}", frames.len(), diags.len());
 
+    // 录制时的阶段中间值才是现场实际算出的结果，存在时优先使用
+    let recorded_stages = get_stage_samples(&db_path, session_id, None, None).await?;
+    let (stages, stages_source) = if recorded_stages.is_empty() {
+        (rerun_stages, "重跑")
+    } else {
+        (recorded_stages, "录制")
+    };
+    eprintln!(
+        "[replay] 阶段中间值: {} 行（来源: {stages_source}）",
+        stages.len()
+    );
+
     // —— 6. 写 CSV ——
     let exports_dir = db_path
         .parent()
         .context("db path 无父目录")?
         .join("exports");
     std::fs::create_dir_all(&exports_dir).context("创建 exports 目录失败")?;
+    // 会话名称可能含路径分隔符等字符，文件名只用清洗后的形式
+    let file_label = sanitize_filename(&session_label, session_id);
 
     let traj_path = args
         .traj_out
-        .unwrap_or_else(|| exports_dir.join(format!("replay_{session_label}_trajectory.csv")));
+        .unwrap_or_else(|| exports_dir.join(format!("replay_{file_label}_trajectory.csv")));
     let diag_path = args
         .diag_out
-        .unwrap_or_else(|| exports_dir.join(format!("replay_{session_label}_diag.csv")));
+        .unwrap_or_else(|| exports_dir.join(format!("replay_{file_label}_diag.csv")));
+    let stages_path = args
+        .stages_out
+        .unwrap_or_else(|| exports_dir.join(format!("replay_{file_label}_stages.csv")));
 
     write_trajectory_csv(&traj_path, &frames).context("写 trajectory.csv 失败")?;
     write_diagnostics_csv(&diag_path, &diags).context("写 diag.csv 失败")?;
+    write_stages_csv(&stages_path, &stages).context("写 stages.csv 失败")?;
     eprintln!("[replay] 写入: {}", traj_path.display());
     eprintln!("[replay] 写入: {}", diag_path.display());
+    eprintln!("[replay] 写入: {}", stages_path.display());
 
     // —— 6.5 可选：把 calc_* 字段写回 SQLite ——
     if args.write_back {
@@ -359,19 +407,6 @@ async fn write_back_calc_columns(
     Ok(())
 }
 
-fn row_to_raw(r: &models::imu_samples::Model) -> ImuSampleRaw {
-    ImuSampleRaw {
-        timestamp_ms: r.timestamp_ms as u64,
-        accel_no_g: DVec3::new(r.accel_no_g_x, r.accel_no_g_y, r.accel_no_g_z),
-        accel_with_g: DVec3::new(r.accel_with_g_x, r.accel_with_g_y, r.accel_with_g_z),
-        gyro: DVec3::new(r.gyro_x, r.gyro_y, r.gyro_z),
-        quat: DQuat::from_xyzw(r.quat_x, r.quat_y, r.quat_z, r.quat_w),
-        angle: DVec3::new(r.angle_x, r.angle_y, r.angle_z),
-        offset: DVec3::new(r.offset_x, r.offset_y, r.offset_z),
-        accel_nav: DVec3::new(r.accel_nav_x, r.accel_nav_y, r.accel_nav_z),
-    }
-}
-
 struct TrajectoryRow {
     timestamp_ms: u64,
     pos: DVec3,
@@ -478,6 +513,40 @@ fn write_diagnostics_csv(path: &Path, diags: &[PipelineDiagnostics]) -> Result<(
     Ok(())
 }
 
+fn write_stages_csv(path: &Path, stages: &[RecordingStageSample]) -> Result<()> {
+    use std::io::Write;
+    let mut f = std::io::BufWriter::new(std::fs::File::create(path)?);
+    // 列名与 imu_stage_samples 表一致
+    writeln!(
+        f,
+        "timestamp_ms,cal_accel_x,cal_accel_y,cal_accel_z,cal_gyro_x,cal_gyro_y,cal_gyro_z,\
+         filt_accel_x,filt_accel_y,filt_accel_z,filt_gyro_x,filt_gyro_y,filt_gyro_z,\
+         linear_accel_x,linear_accel_y,linear_accel_z,\
+         pre_zupt_velocity_x,pre_zupt_velocity_y,pre_zupt_velocity_z,\
+         pre_zupt_position_x,pre_zupt_position_y,pre_zupt_position_z,\
+         post_zupt_velocity_x,post_zupt_velocity_y,post_zupt_velocity_z,\
+         post_zupt_position_x,post_zupt_position_y,post_zupt_position_z,is_static"
+    )?;
+    for s in stages {
+        write!(f, "{}", s.timestamp_ms)?;
+        for v in [
+            s.calibrated_accel,
+            s.calibrated_gyro,
+            s.filtered_accel,
+            s.filtered_gyro,
+            s.linear_accel,
+            s.pre_zupt_velocity,
+            s.pre_zupt_position,
+            s.post_zupt_velocity,
+            s.post_zupt_position,
+        ] {
+            write!(f, ",{},{},{}", v.x, v.y, v.z)?;
+        }
+        writeln!(f, ",{}", s.is_static as u8)?;
+    }
+    Ok(())
+}
+
 fn split_cov(cov: Option<&[f64; 15]>) -> (f64, f64, f64, f64, f64) {
     match cov {
         None => (f64::NAN, f64::NAN, f64::NAN, f64::NAN, f64::NAN),
@@ -1,10 +1,24 @@
 //! 设备标定相关命令。
 
-use anyhow::Context;
-use sea_orm::{ConnectionTrait, EntityTrait, Statement};
 use serde::{Deserialize, Serialize};
 
-use crate::{commands::response::Response as IpcResponse, recorder::db, recorder::models};
+use tauri::State;
+
+use crate::{
+    app_state::AppState,
+    commands::response::Response as IpcResponse,
+    recorder::{
+        device_calibration::{
+            get_device_calibration as get_device_calibration_service,
+            save_device_calibration as save_device_calibration_service,
+        },
+        device_profile::{
+            delete_device_profile as delete_device_profile_service,
+            get_device_profile as get_device_profile_service, DeviceProfile,
+        },
+        models,
+    },
+};
 
 type Response<T> = std::result::Result<IpcResponse<T>, ()>;
 
@@ -27,93 +41,106 @@ pub struct DeviceCalibrationData {
 
 /// 保存设备标定结果到 SQLite。
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
+#[tracing::instrument(level = "debug", skip(state))]
 pub async fn save_device_calibration(
+    state: State<'_, AppState>,
     device_id: String,
     accel_bias: [f64; 3],
     accel_scale: [f64; 3],
     gyro_bias: [f64; 3],
     quality_error: f64,
 ) -> Response<()> {
-    let result: anyhow::Result<()> = async {
-        let key = device_id.clone();
-        let db_path = db::recording_db_path()?;
-        let conn = db::connect(&db_path).await?;
-        db::ensure_schema(&conn).await?;
-
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or_default();
-
-        let db_backend = conn.get_database_backend();
-        conn.execute(Statement::from_sql_and_values(
-            db_backend,
-            "INSERT OR REPLACE INTO device_calibrations
-                (device_id, accel_bias_x, accel_bias_y, accel_bias_z,
-                 accel_scale_x, accel_scale_y, accel_scale_z,
-                 gyro_bias_x, gyro_bias_y, gyro_bias_z,
-                 quality_error, created_at_ms)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            [
-                device_id.into(),
-                accel_bias[0].into(),
-                accel_bias[1].into(),
-                accel_bias[2].into(),
-                accel_scale[0].into(),
-                accel_scale[1].into(),
-                accel_scale[2].into(),
-                gyro_bias[0].into(),
-                gyro_bias[1].into(),
-                gyro_bias[2].into(),
-                quality_error.into(),
-                now_ms.into(),
-            ],
-        ))
-        .await
-        .context("save device calibration")?;
+    let key = device_id.clone();
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default();
+    let calibration = models::device_calibrations::Model {
+        device_id,
+        accel_bias_x: accel_bias[0],
+        accel_bias_y: accel_bias[1],
+        accel_bias_z: accel_bias[2],
+        accel_scale_x: accel_scale[0],
+        accel_scale_y: accel_scale[1],
+        accel_scale_z: accel_scale[2],
+        gyro_bias_x: gyro_bias[0],
+        gyro_bias_y: gyro_bias[1],
+        gyro_bias_z: gyro_bias[2],
+        quality_error,
+        created_at_ms: now_ms,
+    };
+    let result = save_device_calibration_service(&state.recording_db_path(), calibration).await;
+    if result.is_ok() {
         tracing::info!("device calibration saved | key={}", key);
-
-        Ok(())
     }
-    .await;
 
     Ok(result.into())
 }
 
 /// 查询设备历史标定数据。
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
+#[tracing::instrument(level = "debug", skip(state))]
 pub async fn get_device_calibration(
+    state: State<'_, AppState>,
     device_id: String,
 ) -> Response<Option<DeviceCalibrationData>> {
-    let result: anyhow::Result<Option<DeviceCalibrationData>> = async {
-        let key = device_id.clone();
-        let db_path = db::recording_db_path()?;
-        let conn = db::connect(&db_path).await?;
-        db::ensure_schema(&conn).await?;
-
-        let model = models::device_calibrations::Entity::find_by_id(device_id)
-            .one(&conn)
+    let result: anyhow::Result<Option<DeviceCalibrationData>> =
+        get_device_calibration_service(&state.recording_db_path(), &device_id)
             .await
-            .context("query device calibration")?;
+            .map(|model| {
+                if model.is_some() {
+                    tracing::info!("device calibration hit | key={}", device_id);
+                } else {
+                    tracing::info!("device calibration miss | key={}", device_id);
+                }
+                model.map(|m| DeviceCalibrationData {
+                    device_id: m.device_id,
+                    accel_bias: [m.accel_bias_x, m.accel_bias_y, m.accel_bias_z],
+                    accel_scale: [m.accel_scale_x, m.accel_scale_y, m.accel_scale_z],
+                    gyro_bias: [m.gyro_bias_x, m.gyro_bias_y, m.gyro_bias_z],
+                    quality_error: m.quality_error,
+                    created_at_ms: m.created_at_ms,
+                })
+            });
 
-        if model.is_some() {
-            tracing::info!("device calibration hit | key={}", key);
-        } else {
-            tracing::info!("device calibration miss | key={}", key);
-        }
+    Ok(result.into())
+}
 
-        Ok(model.map(|m| DeviceCalibrationData {
-            device_id: m.device_id,
-            accel_bias: [m.accel_bias_x, m.accel_bias_y, m.accel_bias_z],
-            accel_scale: [m.accel_scale_x, m.accel_scale_y, m.accel_scale_z],
-            gyro_bias: [m.gyro_bias_x, m.gyro_bias_y, m.gyro_bias_z],
-            quality_error: m.quality_error,
-            created_at_ms: m.created_at_ms,
-        }))
+/// 以当前生效的标定配置与姿态零位保存已连接设备的档案。
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+pub async fn save_device_profile(state: State<'_, AppState>) -> Response<DeviceProfile> {
+    let result = state.save_device_profile().await;
+    if let Ok(profile) = &result {
+        tracing::info!("device profile saved | key={}", profile.device_id);
+    }
+
+    Ok(result.into())
+}
+
+/// 查询设备档案。
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+pub async fn get_device_profile(
+    state: State<'_, AppState>,
+    device_id: String,
+) -> Response<Option<DeviceProfile>> {
+    let result = get_device_profile_service(&state.recording_db_path(), &device_id).await;
+
+    Ok(result.into())
+}
+
+/// 删除设备档案，返回档案是否存在；下次连接该设备时使用基础配置。
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+pub async fn delete_device_profile(
+    state: State<'_, AppState>,
+    device_id: String,
+) -> Response<bool> {
+    let result = delete_device_profile_service(&state.recording_db_path(), &device_id).await;
+    if let Ok(true) = result {
+        tracing::info!("device profile deleted | key={}", device_id);
     }
-    .await;
 
     Ok(result.into())
 }
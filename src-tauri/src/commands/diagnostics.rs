@@ -1,39 +1,96 @@
-//! 管线诊断数据订阅命令。
+//! 管线诊断数据订阅、单帧解释与时钟同步查询命令。
 
 use std::sync::atomic::Ordering;
 
-use tauri::{async_runtime::spawn, ipc::Channel, State};
+use tauri::{
+    ipc::{Channel, InvokeResponseBody},
+    State,
+};
 
 use crate::{
     app_state::AppState,
-    processor::pipeline::diagnostics::PipelineDiagnostics,
+    commands::response::Response as IpcResponse,
+    processor::{
+        clock_sync::ClockSyncEstimate,
+        parser::ImuSampleRaw,
+        pipeline::{diagnostics::relay_diagnostics, SampleExplanation},
+    },
 };
 
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+/// release 构建调用 [`explain_sample`] 时的错误。
+const EXPLAIN_DISABLED_ERROR: &str = "explain_sample is only available in debug builds";
+
 /// 订阅管线诊断数据流。
 ///
 /// 订阅时自动启用诊断采集，前端断开时自动关闭。
 /// 诊断数据包含管线各阶段中间值、ZUPT 状态、ESKF 内部状态和性能指标。
+/// 与输出订阅一样按批发送：每条消息是按帧顺序排列的数组，攒批延迟不超过默认窗口。
+///
+/// 序列化在独立线程中完成（见 [`relay_diagnostics`]），
+/// 既不占用处理线程，也不占用 async runtime 的 worker。
 #[tauri::command]
 #[tracing::instrument(level = "debug", skip(state, on_event))]
-pub fn subscribe_diagnostics(state: State<'_, AppState>, on_event: Channel<PipelineDiagnostics>) {
+pub fn subscribe_diagnostics(state: State<'_, AppState>, on_event: Channel) {
     tracing::info!("前端订阅管线诊断数据。");
     let rx = state.diagnostics_rx.clone();
     let flag = state.diagnostics_flag.clone();
+    let subscriber = state.track_subscriber();
+    let fanout = state.output_fanout();
 
     // 开启诊断采集
     flag.store(true, Ordering::Relaxed);
     // 清空旧数据
     rx.drain();
 
-    spawn(async move {
-        while let Ok(data) = rx.recv_async().await {
-            if on_event.send(data).is_err() {
-                tracing::info!("前端诊断订阅已断开，停止发送诊断数据。");
-                break;
-            }
-        }
-        // 前端断开后自动关闭诊断采集
-        flag.store(false, Ordering::Relaxed);
-        tracing::info!("诊断采集已自动关闭。");
-    });
+    let spawn_result = std::thread::Builder::new()
+        .name("DiagnosticsSerializer".into())
+        .spawn(move || {
+            let _subscriber = subscriber;
+            relay_diagnostics(
+                &rx,
+                || fanout.rates(),
+                |json| {
+                    let open = on_event.send(InvokeResponseBody::Json(json)).is_ok();
+                    if !open {
+                        tracing::info!("前端诊断订阅已断开，停止发送诊断数据。");
+                    }
+                    open
+                },
+            );
+            // 前端断开后自动关闭诊断采集
+            flag.store(false, Ordering::Relaxed);
+            tracing::info!("诊断采集已自动关闭。");
+        });
+    if let Err(e) = spawn_result {
+        tracing::error!("创建诊断序列化线程失败: {:?}", e);
+        state.diagnostics_flag.store(false, Ordering::Relaxed);
+    }
+}
+
+/// 演算一个样本流经管线的全过程（仅 debug 构建）。
+///
+/// 在实时管线状态的副本上运行，返回每个阶段的输入、输出及影响判定的参数
+/// （阈值、滤波系数、重力参考、静止状态等），实时管线不受影响。
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+pub async fn explain_sample(
+    state: State<'_, AppState>,
+    sample: ImuSampleRaw,
+) -> Response<SampleExplanation> {
+    if !cfg!(debug_assertions) {
+        return Ok(IpcResponse::error(EXPLAIN_DISABLED_ERROR));
+    }
+    match state.explain_sample(sample).await {
+        Ok(explanation) => Ok(IpcResponse::success(explanation)),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取当前设备/主机时钟同步结果（偏移、频偏、锚点数与残差），尚未建立时为空。
+pub fn get_clock_sync(state: State<'_, AppState>) -> Response<Option<ClockSyncEstimate>> {
+    Ok(IpcResponse::success(state.clock_sync()))
 }
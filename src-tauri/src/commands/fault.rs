@@ -0,0 +1,44 @@
+//! 故障注入命令（仅开发构建）。
+
+use tauri::State;
+
+use crate::{
+    app_state::AppState,
+    commands::response::Response as IpcResponse,
+    processor::fault::{FaultInjectionConfig, FaultInjectionStatus, FAULT_INJECTION_AVAILABLE},
+    types::error::ErrorCode,
+};
+
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 设置随机故障注入：按概率丢包、篡改字节、向指定阶段注入 NaN 或延迟、让录制写入失败。
+///
+/// 全部概率为零即关闭注入。返回生效后的状态，关闭时为空。
+/// 仅在 debug 构建或启用 `fault-injection` feature 时可用，否则返回 `NotSupported` 错误码。
+pub fn set_fault_injection(
+    state: State<'_, AppState>,
+    config: FaultInjectionConfig,
+) -> Response<Option<FaultInjectionStatus>> {
+    if !FAULT_INJECTION_AVAILABLE {
+        return Ok(IpcResponse::error_with_code(
+            ErrorCode::NotSupported,
+            "Fault injection is not compiled in; use a debug build or --features fault-injection",
+        ));
+    }
+    let result: anyhow::Result<Option<FaultInjectionStatus>> = state
+        .faults
+        .configure(config)
+        .map(|()| state.faults.status())
+        .map_err(anyhow::Error::from);
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取生效中的故障注入配置与注入次数，未启用时为空。
+pub fn get_fault_injection(state: State<'_, AppState>) -> Response<Option<FaultInjectionStatus>> {
+    Ok(IpcResponse::success(state.faults.status()))
+}
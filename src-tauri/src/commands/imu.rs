@@ -1,7 +1,15 @@
 use crate::{
-    app_state::AppState, commands::response::Response as IpcResponse,
-    processor::pipeline::ProcessorPipelineConfig, types::bluetooth::PeripheralInfo,
+    app_state::AppState,
+    commands::response::Response as IpcResponse,
+    imu::{device_commands_allowed, to_hex, validate_device_command, DeviceCommandError},
+    processor::{
+        calibration::{CalibrationQuality, CalibrationWizardSnapshot, FullCalibration},
+        pipeline::{ConfigApplyMode, ProcessorPipelineConfig},
+    },
+    types::bluetooth::{BluetoothAdapterInfo, BluetoothStatus, PeripheralInfo},
 };
+use std::time::Duration;
+
 use tauri::State;
 
 type Response<T> = Result<IpcResponse<T>, ()>;
@@ -23,10 +31,46 @@ pub async fn stop_scan(state: State<'_, AppState>) -> Response<()> {
 #[tauri::command]
 #[tracing::instrument(level = "debug", skip(state))]
 /// 主动请求获取设备列表
-pub async fn list_peripherals(state: State<'_, AppState>) -> Response<Vec<PeripheralInfo>> {
+///
+/// 只列出 `max_age_ms`（缺省 30 s）内被扫描发现过的设备，正连接的设备总会列出。
+pub async fn list_peripherals(
+    state: State<'_, AppState>,
+    max_age_ms: Option<u64>,
+) -> Response<Vec<PeripheralInfo>> {
     // use Result to make tauri happy
     let client = state.client().await;
-    Ok(client.list_peripherals().await.into())
+    match client.list_peripherals(max_age_ms).await {
+        Ok((peripherals, warnings)) => Ok(IpcResponse::partial(peripherals, warnings)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 列出本机蓝牙适配器及开启状态，当前使用的标记为 `selected`。
+pub async fn list_bluetooth_adapters(
+    state: State<'_, AppState>,
+) -> Response<Vec<BluetoothAdapterInfo>> {
+    Ok(state.client().await.list_adapters().await.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 指定之后扫描与连接使用的蓝牙适配器（须先断开设备）。
+pub async fn select_bluetooth_adapter(
+    state: State<'_, AppState>,
+    id: &str,
+) -> Response<BluetoothAdapterInfo> {
+    Ok(state.client().await.select_adapter(id).await.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 蓝牙可用性诊断：蓝牙服务是否可用、适配器数量与当前适配器。
+pub async fn get_bluetooth_status(state: State<'_, AppState>) -> Response<BluetoothStatus> {
+    Ok(IpcResponse::success(
+        state.client().await.bluetooth_status().await,
+    ))
 }
 
 #[tauri::command]
@@ -34,11 +78,18 @@ pub async fn list_peripherals(state: State<'_, AppState>) -> Response<Vec<Periph
 /// 连接到设备
 ///
 /// * `device_name`: 目标设备的部分名称
+/// * `max_attempts`: 连接中断、超时等瞬时错误下最多尝试的次数，缺省 3 次
+///
+/// 已连接到该设备时直接返回当前设备信息；连着其他设备时先断开再连接。
 pub async fn connect_peripheral(
     state: State<'_, AppState>,
     target_uuid: &str,
+    max_attempts: Option<u32>,
 ) -> Response<PeripheralInfo> {
-    Ok(state.client().await.connect(target_uuid).await.into())
+    Ok(state
+        .connect_peripheral(target_uuid, max_attempts)
+        .await
+        .into())
 }
 
 #[tauri::command]
@@ -51,9 +102,40 @@ pub async fn disconnect_peripheral(state: State<'_, AppState>) -> Response<Perip
 #[tauri::command]
 #[tracing::instrument(level = "debug", skip(state))]
 /// 设置姿态校正值（按当前姿态作为零位）
-pub async fn set_axis_calibration(state: State<'_, AppState>) -> Response<()> {
+///
+/// 先采集约 1 s 数据评估校准质量，返回分数与分项指标；
+/// 分数低于阈值被拒绝时 `success = false`，`data` 仍带评分。
+pub async fn set_axis_calibration(state: State<'_, AppState>) -> Response<CalibrationQuality> {
     match state.request_axis_calibration().await {
-        Ok(()) => Ok(IpcResponse::success(())),
+        Ok(quality) if quality.applied => Ok(IpcResponse::success(quality)),
+        Ok(quality) => Ok(IpcResponse::rejected(
+            quality,
+            format!(
+                "校准质量 {:.0} 分低于阈值 {:.0} 分，未应用：请将设备静置后重试",
+                quality.score, quality.min_score
+            ),
+        )),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 一次性完成水平、航向与原点校准。
+///
+/// 采集一个评估窗口校验静止，通过后在处理下一帧之前同时应用姿态零位、航向零点、
+/// 位置归零（清零速度与 ZUPT 锁定点）与重力参考；未通过时什么都不改，
+/// `success = false`，`data` 仍带评分。
+pub async fn calibrate_all(state: State<'_, AppState>) -> Response<FullCalibration> {
+    match state.request_calibrate_all().await {
+        Ok(result) if result.quality.applied => Ok(IpcResponse::success(result)),
+        Ok(result) => Ok(IpcResponse::rejected(
+            result,
+            format!(
+                "静止评分 {:.0} 分低于阈值 {:.0} 分，未应用任何校准：请将设备静置后重试",
+                result.quality.score, result.quality.min_score
+            ),
+        )),
         Err(err) => Ok(IpcResponse::error(err)),
     }
 }
@@ -68,6 +150,33 @@ pub async fn set_position(state: State<'_, AppState>, x: f64, y: f64, z: f64) ->
     }
 }
 
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 在下一段持续静止处重新锚定世界原点
+pub async fn re_anchor_origin(state: State<'_, AppState>) -> Response<()> {
+    match state.request_re_anchor_origin().await {
+        Ok(()) => Ok(IpcResponse::success(())),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 武装航向对齐：下一段足够长的运动方向成为输出的 +X（需启用 heading_align）
+pub async fn arm_heading_from_motion(state: State<'_, AppState>) -> Response<()> {
+    match state.request_arm_heading_from_motion().await {
+        Ok(()) => Ok(IpcResponse::success(())),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取标定向导当前状态，之后的变化经 `calibration_state_changed` 事件推送
+pub fn get_calibration_state(state: State<'_, AppState>) -> Response<CalibrationWizardSnapshot> {
+    Ok(IpcResponse::success(state.calibration_state()))
+}
+
 #[tauri::command]
 #[tracing::instrument(level = "debug", skip(state))]
 /// 获取当前生效的 pipeline 配置。
@@ -85,12 +194,52 @@ pub async fn update_pipeline_config(
     state: State<'_, AppState>,
     config: ProcessorPipelineConfig,
 ) -> Response<()> {
+    if let Err(errors) = config.validate() {
+        return Ok(IpcResponse::error(errors.to_string()));
+    }
     match state.update_pipeline_config(config).await {
         Ok(()) => Ok(IpcResponse::success(())),
         Err(err) => Ok(IpcResponse::error(err)),
     }
 }
 
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state, config))]
+/// 增量应用 pipeline 配置：阈值、滤波系数等原地生效不重置轨迹，
+/// 结构性变更（融合算法、标定、坐标约定）仍会重置，返回实际采用的方式。
+pub async fn patch_pipeline_config(
+    state: State<'_, AppState>,
+    config: ProcessorPipelineConfig,
+) -> Response<ConfigApplyMode> {
+    if let Err(errors) = config.validate() {
+        return Ok(IpcResponse::error(errors.to_string()));
+    }
+    match state.patch_pipeline_config(config).await {
+        Ok(mode) => Ok(IpcResponse::success(mode)),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 暂停导航：轨迹冻结，原始数据照常推送。返回状态是否改变（已暂停时为 false）。
+pub async fn pause_processing(state: State<'_, AppState>) -> Response<bool> {
+    match state.set_processing_paused(true).await {
+        Ok(changed) => Ok(IpcResponse::success(changed)),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 恢复导航，从暂停时的位姿继续积分。返回状态是否改变（未暂停时为 false）。
+pub async fn resume_processing(state: State<'_, AppState>) -> Response<bool> {
+    match state.set_processing_paused(false).await {
+        Ok(changed) => Ok(IpcResponse::success(changed)),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
 #[tauri::command]
 #[tracing::instrument(level = "debug", skip(state))]
 /// 将当前生效的 pipeline 配置保存到 processor.toml。
@@ -107,3 +256,62 @@ pub async fn save_pipeline_config(state: State<'_, AppState>) -> Response<()> {
 pub async fn get_battery_level(state: State<'_, AppState>) -> Response<u8> {
     Ok(state.client().await.get_battery_level().await.into())
 }
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 设置设备上报频率 (Hz)，已连接时立即写入设备。
+pub async fn set_report_rate(state: State<'_, AppState>, report_rate: u8) -> Response<()> {
+    match state.set_report_rate(report_rate).await {
+        Ok(()) => Ok(IpcResponse::success(())),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+/// 原始设备命令是否允许：配置开关或 debug 构建。
+async fn ensure_device_commands_allowed(state: &AppState) -> anyhow::Result<()> {
+    let config = state
+        .get_pipeline_config()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    if !device_commands_allowed(config.global.unsafe_device_commands, cfg!(debug_assertions)) {
+        return Err(DeviceCommandError::Disabled.into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 原样写入一条原始设备命令（1 ~ 20 字节），需在配置中启用 `unsafe_device_commands`
+/// 或使用 debug 构建。设备的回复用 `read_device_response` 读取。
+pub async fn write_device_command(state: State<'_, AppState>, bytes: Vec<u8>) -> Response<()> {
+    if let Err(e) = ensure_device_commands_allowed(&state).await {
+        return Ok(e.into());
+    }
+    if let Err(e) = validate_device_command(&bytes) {
+        return Ok(anyhow::Error::new(e).into());
+    }
+    Ok(state
+        .client()
+        .await
+        .write_device_command(&bytes)
+        .await
+        .into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 读取上一条原始设备命令的回复（下一条非数据帧通知），以十六进制字符串返回；
+/// `timeout_ms` 内没有收到时返回错误。
+pub async fn read_device_response(state: State<'_, AppState>, timeout_ms: u64) -> Response<String> {
+    if let Err(e) = ensure_device_commands_allowed(&state).await {
+        return Ok(e.into());
+    }
+    // 等待期间不占用客户端锁，接收任务照常运行
+    let tap = state.client().await.notification_tap();
+    let reply = tap
+        .next_reply(Duration::from_millis(timeout_ms))
+        .await
+        .map(|reply| to_hex(&reply))
+        .map_err(anyhow::Error::new);
+    Ok(reply.into())
+}
@@ -0,0 +1,28 @@
+//! 日志级别命令。
+
+use tauri::State;
+
+use crate::{
+    commands::response::Response as IpcResponse,
+    logger::{LogLevel, LoggingHandle},
+};
+
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 运行时调整日志级别；`target` 为模块路径前缀（如 `tauri_app_lib::processor::navigator`），
+/// 为空时调整默认级别。只影响控制台与日志文件输出，重启后恢复 `logging.toml` 的设置。
+pub fn set_log_level(
+    state: State<'_, LoggingHandle>,
+    target: String,
+    level: String,
+) -> Response<()> {
+    let result = level
+        .parse::<LogLevel>()
+        .and_then(|level| state.set_level(&target, level));
+    if result.is_ok() {
+        tracing::info!(target = %target, level = %level, "日志级别已调整");
+    }
+    Ok(result.into())
+}
@@ -2,10 +2,16 @@
 
 mod calibration;
 mod diagnostics;
+mod fault;
 mod imu;
+mod logging;
 mod output;
+mod playback;
 mod recording;
 mod response;
+mod selftest;
+mod status;
+mod units;
 
 /// 注册所有命令处理器。
 pub fn handlers() -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
@@ -13,24 +19,91 @@ pub fn handlers() -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static
         imu::start_scan,
         imu::stop_scan,
         imu::list_peripherals,
+        imu::list_bluetooth_adapters,
+        imu::select_bluetooth_adapter,
+        imu::get_bluetooth_status,
         imu::connect_peripheral,
         imu::disconnect_peripheral,
         imu::set_axis_calibration,
+        imu::calibrate_all,
         imu::set_position,
+        imu::re_anchor_origin,
+        imu::arm_heading_from_motion,
+        imu::get_calibration_state,
         imu::get_pipeline_config,
         imu::update_pipeline_config,
+        imu::patch_pipeline_config,
+        imu::pause_processing,
+        imu::resume_processing,
         imu::save_pipeline_config,
         imu::get_battery_level,
+        imu::set_report_rate,
+        imu::write_device_command,
+        imu::read_device_response,
         output::subscribe_output,
+        output::unsubscribe_output,
+        output::subscribe_attitude,
+        output::get_latest_frame,
+        output::get_latest_frame_age_ms,
+        output::get_schema_version,
+        playback::playback_load,
+        playback::playback_play,
+        playback::playback_pause,
+        playback::playback_seek,
+        playback::playback_set_speed,
+        playback::playback_set_loop,
+        playback::get_playback_status,
         recording::start_recording,
         recording::stop_recording,
+        recording::pause_recording,
+        recording::resume_recording,
         recording::list_recordings,
+        recording::get_recording_statistics,
+        recording::repair_recordings,
         recording::update_recording_meta,
         recording::get_recording_samples,
+        recording::get_recording_samples_binary,
+        recording::get_stage_samples,
+        recording::compare_recordings,
+        recording::smooth_recording,
+        recording::transform_recording,
         recording::export_session_csv,
+        recording::export_recording_csv_to_dir,
+        recording::export_recording_parquet,
+        recording::cancel_export,
         recording::delete_recording,
+        recording::delete_recordings,
+        recording::import_session_csv,
+        recording::add_recording_marker,
+        recording::record_sync_pulse,
+        recording::get_sync_events,
+        recording::dump_flight_recorder,
+        recording::get_recording_markers,
+        recording::flag_sample_range,
+        recording::get_recording_flags,
+        recording::delete_recording_flag,
+        recording::get_recording_pauses,
+        recording::get_recording_segments,
+        recording::verify_recordings_database,
+        recording::get_recording_directory,
+        recording::get_default_recording_directory,
+        recording::set_recording_directory,
         calibration::save_device_calibration,
         calibration::get_device_calibration,
-        diagnostics::subscribe_diagnostics
+        calibration::save_device_profile,
+        calibration::get_device_profile,
+        calibration::delete_device_profile,
+        diagnostics::subscribe_diagnostics,
+        diagnostics::explain_sample,
+        diagnostics::get_clock_sync,
+        fault::set_fault_injection,
+        fault::get_fault_injection,
+        status::get_app_status,
+        status::set_stall_threshold,
+        units::get_unit_preferences,
+        units::set_unit_preferences,
+        units::get_latest_frame_display,
+        logging::set_log_level,
+        selftest::run_self_test
     ]
 }
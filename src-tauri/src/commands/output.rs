@@ -1,23 +1,214 @@
 //! 数据输出订阅命令。
 
-use tauri::{async_runtime::spawn, ipc::Channel, State};
+use std::time::{Duration, Instant};
 
-use crate::{app_state::AppState, types::outputs::ResponseData};
+use tauri::{
+    async_runtime::spawn,
+    ipc::{Channel, InvokeResponseBody},
+    State,
+};
+
+use crate::{
+    app_state::AppState,
+    commands::response::Response as IpcResponse,
+    processor::{
+        attitude_stream::AttitudeFrame,
+        batcher::Batcher,
+        fanout::SubscriptionId,
+        output_encoding::{encode_batch, encode_message, OutputMessage},
+    },
+    types::outputs::{
+        OutputEncoding, OutputEvent, OutputSubscribeOptions, ResponseData, RESPONSE_SCHEMA_VERSION,
+    },
+};
+
+type Response<T> = Result<IpcResponse<T>, ()>;
 
 #[tauri::command]
 #[tracing::instrument(level = "debug", skip(state, on_event))]
 /// 订阅 IMU 数据输出。
-pub fn subscribe_output(state: State<'_, AppState>, on_event: Channel<ResponseData>) {
-    tracing::info!("Tauri 前端订阅 IMU 数据输出。");
-    let rx = state.downstream_rx.clone();
-    rx.drain();
+///
+/// 每条消息是带序号的完整帧，或静止抑制期间的心跳（见 `[output_suppress]`）。
+/// `options` 可要求先发最新一帧快照、再回放最近一段历史，之后才是实时消息，
+/// 回放与实时消息之间序号不重复、不缺失。
+///
+/// 默认按批发送（`output_batch` 数组）：一批最多 `batch_max_frames` 条，第一条最多等待
+/// `batch_window_ms`，批内与批间顺序不变；`batching: false` 时每条消息单独发送。
+///
+/// `encoding: "cbor"` 时每条消息以 CBOR 字节数组发出，解码后与 JSON 结构相同，
+/// 帧数据每帧只编码一次、在 CBOR 订阅间共享（见 `processor::output_encoding`）。
+///
+/// 每次订阅有自己的队列，多个窗口或面板可以同时订阅；返回的订阅编号交给
+/// `unsubscribe_output` 注销，前端通道断开时也会自动注销。
+pub fn subscribe_output(
+    state: State<'_, AppState>,
+    options: Option<OutputSubscribeOptions>,
+    on_event: Channel,
+) -> Response<SubscriptionId> {
+    let options = options.unwrap_or_default();
+    let (id, rx, replay) = match state.subscribe_output(&options) {
+        Ok(subscription) => subscription,
+        Err(err) => return Ok(IpcResponse::error(err)),
+    };
+    tracing::info!(
+        id,
+        replayed = replay.messages.len(),
+        "Tauri 前端订阅 IMU 数据输出。"
+    );
+    let subscriber = state.track_subscriber();
+    let mut batcher = options.batching.then(|| {
+        Batcher::new(
+            Duration::from_millis(options.batch_window_ms),
+            options.batch_max_frames,
+        )
+    });
+    let encoding = options.encoding;
     spawn(async move {
-        while let Ok(data) = rx.recv_async().await {
-            if on_event.send(data).is_err() {
-                // 如果发送失败，说明前端已断开连接，退出循环
-                tracing::info!("Tauri 前端订阅已断开，停止发送IMU数据。");
+        let _subscriber = subscriber;
+        let send = |event: Outgoing| match event.into_body(encoding) {
+            Ok(body) => on_event.send(body).is_ok(),
+            Err(err) => {
+                tracing::error!("序列化输出消息失败: {}", err);
+                false
+            }
+        };
+        // 回放的历史已全部就绪，直接按批切分
+        let replay_events: Vec<Outgoing> = match &batcher {
+            Some(batcher) => replay
+                .messages
+                .chunks(batcher.max_len())
+                .map(|chunk| Outgoing::Batch(chunk.iter().copied().map(Into::into).collect()))
+                .collect(),
+            None => replay
+                .messages
+                .iter()
+                .map(|&message| Outgoing::Message(Box::new(message.into())))
+                .collect(),
+        };
+        for event in replay_events {
+            if !send(event) {
+                tracing::info!("Tauri 前端订阅已断开，停止回放。");
+                return;
+            }
+        }
+        loop {
+            let deadline = batcher.as_ref().and_then(Batcher::deadline);
+            let received = match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline.into(), rx.recv_async())
+                    .await
+                    .ok(),
+                None => Some(rx.recv_async().await),
+            };
+            let event = match (received, batcher.as_mut()) {
+                (Some(Ok(data)), _) if !replay.is_live(&data.message) => continue,
+                (Some(Ok(data)), None) => Some(Outgoing::Message(Box::new(data))),
+                (Some(Ok(data)), Some(batcher)) => {
+                    batcher.push(data, Instant::now()).map(Outgoing::Batch)
+                }
+                // 窗口到期
+                (None, batcher) => batcher
+                    .and_then(|b| b.poll(Instant::now()))
+                    .map(Outgoing::Batch),
+                // 订阅已注销：发出未满的一批后退出
+                (Some(Err(_)), batcher) => {
+                    if let Some(pending) = batcher.and_then(Batcher::flush) {
+                        send(Outgoing::Batch(pending));
+                    }
+                    break;
+                }
+            };
+            if let Some(event) = event {
+                if !send(event) {
+                    // 如果发送失败，说明前端已断开连接，退出循环
+                    tracing::info!("Tauri 前端订阅已断开，停止发送IMU数据。");
+                    break;
+                }
+            }
+        }
+    });
+    Ok(IpcResponse::success(id))
+}
+
+/// 待发出的输出事件：攒批订阅为一批，否则为单条消息（装箱，与批次大小相当）。
+enum Outgoing {
+    Message(Box<OutputMessage>),
+    Batch(Vec<OutputMessage>),
+}
+
+impl Outgoing {
+    /// 按订阅的编码生成通道消息体。
+    fn into_body(self, encoding: OutputEncoding) -> serde_json::Result<InvokeResponseBody> {
+        Ok(match encoding {
+            OutputEncoding::Json => {
+                let event = match self {
+                    Self::Message(message) => OutputEvent::Message(message.message),
+                    Self::Batch(batch) => OutputEvent::Batch {
+                        output_batch: batch.into_iter().map(|m| m.message).collect(),
+                    },
+                };
+                InvokeResponseBody::Json(serde_json::to_string(&event)?)
+            }
+            OutputEncoding::Cbor => InvokeResponseBody::Raw(match self {
+                Self::Message(message) => encode_message(&message),
+                Self::Batch(batch) => encode_batch(&batch),
+            }),
+        })
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 注销 `subscribe_output` / `subscribe_attitude` 返回的订阅，返回该订阅是否仍存在。
+pub fn unsubscribe_output(state: State<'_, AppState>, id: SubscriptionId) -> Response<bool> {
+    Ok(IpcResponse::success(state.unsubscribe_output(id)))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state, on_event))]
+/// 订阅高频姿态流（仅时间戳与四元数），频率不超过 `max_rate_hz`。
+///
+/// 与 `subscribe_output` 并行：3D 姿态视图用此通道取满帧率姿态，
+/// 完整数据流可由前端按较低频率消费。返回的订阅编号交给 `unsubscribe_output` 注销。
+pub fn subscribe_attitude(
+    state: State<'_, AppState>,
+    max_rate_hz: f64,
+    on_event: Channel<AttitudeFrame>,
+) -> Response<SubscriptionId> {
+    let (id, rx) = match state.subscribe_attitude(max_rate_hz) {
+        Ok(subscription) => subscription,
+        Err(err) => return Ok(IpcResponse::error(err)),
+    };
+    tracing::info!(id, max_rate_hz, "Tauri 前端订阅姿态流。");
+    let subscriber = state.track_subscriber();
+    spawn(async move {
+        let _subscriber = subscriber;
+        while let Ok(frame) = rx.recv_async().await {
+            if on_event.send(frame).is_err() {
+                tracing::info!("姿态流订阅已断开，停止发送。");
                 break;
             }
         }
     });
+    Ok(IpcResponse::success(id))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取最新一帧输出，尚无数据时为空。
+pub fn get_latest_frame(state: State<'_, AppState>) -> Response<Option<ResponseData>> {
+    Ok(IpcResponse::success(state.latest_frame()))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取最新一帧距今的毫秒数，调用方据此判断数据是否已过期。
+pub fn get_latest_frame_age_ms(state: State<'_, AppState>) -> Response<Option<u64>> {
+    Ok(IpcResponse::success(state.latest_frame_age_ms()))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug")]
+/// 获取 `ResponseData` 结构版本，前端据此检查与后端是否匹配。
+pub fn get_schema_version() -> Response<u32> {
+    Ok(IpcResponse::success(RESPONSE_SCHEMA_VERSION))
 }
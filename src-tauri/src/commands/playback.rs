@@ -0,0 +1,88 @@
+//! 录制回放命令。
+
+use tauri::State;
+
+use crate::{
+    app_state::AppState,
+    commands::response::Response as IpcResponse,
+    replay::{PlaybackMode, PlaybackStatus},
+};
+
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 加载录制会话用于回放，暂停在首帧；`mode` 省略时播放存储的导航结果。
+pub async fn playback_load(
+    state: State<'_, AppState>,
+    session_id: i64,
+    mode: Option<PlaybackMode>,
+) -> Response<PlaybackStatus> {
+    Ok(state
+        .playback_load(session_id, mode.unwrap_or_default())
+        .await
+        .into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 开始或继续回放，已播完时从头开始；设备在上报数据或正在录制时返回
+/// `PlaybackDeviceStreaming` / `PlaybackRecordingActive`。
+pub async fn playback_play(state: State<'_, AppState>) -> Response<PlaybackStatus> {
+    Ok(state.playback_play().await.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 暂停回放。
+pub async fn playback_pause(state: State<'_, AppState>) -> Response<PlaybackStatus> {
+    let result = state.with_playback(|playback, _| playback.pause()).await;
+    Ok(result.map_err(anyhow::Error::from).into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 跳转到不早于 `timestamp_ms`（设备时间戳）的第一帧。
+pub async fn playback_seek(
+    state: State<'_, AppState>,
+    timestamp_ms: u64,
+) -> Response<PlaybackStatus> {
+    let result = state
+        .with_playback(|playback, now| playback.seek(timestamp_ms, now))
+        .await;
+    Ok(result.map_err(anyhow::Error::from).into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 设置回放倍速（0.25 ~ 8）。
+pub async fn playback_set_speed(
+    state: State<'_, AppState>,
+    multiplier: f64,
+) -> Response<PlaybackStatus> {
+    let result = state
+        .with_playback(|playback, now| playback.set_speed(multiplier, now))
+        .await;
+    Ok(result.map_err(anyhow::Error::from).into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 开启或关闭循环回放。
+pub async fn playback_set_loop(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Response<PlaybackStatus> {
+    let status = state
+        .with_playback(|playback, _| playback.set_loop(enabled))
+        .await;
+    Ok(IpcResponse::success(status))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取回放状态（位置、倍速、模式等）。
+pub async fn get_playback_status(state: State<'_, AppState>) -> Response<PlaybackStatus> {
+    let status = state.with_playback(|playback, _| playback.status()).await;
+    Ok(IpcResponse::success(status))
+}
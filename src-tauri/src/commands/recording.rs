@@ -3,21 +3,48 @@
 use crate::{
     app_state::AppState,
     commands::response::Response as IpcResponse,
+    processor::analysis::{ComparisonReport, SmoothedTrajectory, TransformedTrajectory},
     recorder::{
+        add_recording_marker as add_recording_marker_service,
+        compare_recordings as compare_recordings_service,
+        db::verify_database as verify_database_service,
         delete_recording as delete_recording_service,
+        delete_recording_flag as delete_recording_flag_service,
+        delete_recordings as delete_recordings_service,
+        dump_flight_recorder as dump_flight_recorder_service,
+        export_recording_csv_to_dir as export_recording_csv_to_dir_service,
         export_session_csv as export_session_csv_service,
+        flag_sample_range as flag_sample_range_service,
+        get_recording_flags as get_recording_flags_service,
+        get_recording_markers as get_recording_markers_service,
+        get_recording_pauses as get_recording_pauses_service,
         get_recording_samples as get_recording_samples_service,
-        list_recordings as list_recordings_service, start_recording as start_recording_service,
-        stop_recording as stop_recording_service,
-        update_recording_meta as update_recording_meta_service, RecordingStartInput,
+        get_recording_samples_binary as get_recording_samples_binary_service,
+        get_recording_segments as get_recording_segments_service,
+        get_recording_statistics as get_recording_statistics_service,
+        get_stage_samples as get_stage_samples_service, get_sync_events as get_sync_events_service,
+        import_session_csv as import_session_csv_service,
+        list_recordings as list_recordings_service, pause_recording as pause_recording_service,
+        record_sync_pulse as record_sync_pulse_service,
+        repair_recordings as repair_recordings_service,
+        resume_recording as resume_recording_service, smooth_recording as smooth_recording_service,
+        start_recording as start_recording_service, stop_recording as stop_recording_service,
+        transform_recording as transform_recording_service,
+        update_recording_meta as update_recording_meta_service, ExportGuard, ExportOptions,
+        RecordingSplit, RecordingStartInput,
     },
     types::{
         outputs,
-        recording::{RecordingMeta, RecordingStatus},
+        recording::{
+            DatabaseIntegrity, RecordingFlag, RecordingMarker, RecordingMeta, RecordingMetadata,
+            RecordingPage, RecordingPause, RecordingQuery, RecordingSegment, RecordingStageSample,
+            RecordingStatistics, RecordingStatus, RecordingSyncEvent,
+        },
     },
 };
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, State};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
 
 type Response<T> = std::result::Result<IpcResponse<T>, ()>;
 
@@ -28,6 +55,12 @@ pub struct RecordingStartOptions {
     pub name: Option<String>,
     /// 录制标签。
     pub tags: Option<Vec<String>>,
+    /// 单段最长时长（分钟），达到后自动分段。
+    pub max_duration_min: Option<f64>,
+    /// 单段最多样本数，达到后自动分段。
+    pub max_samples: Option<u64>,
+    /// 深度录制：同时写入各阶段中间值，缺省关闭。
+    pub deep: Option<bool>,
 }
 
 #[tauri::command]
@@ -39,15 +72,36 @@ pub async fn start_recording(
     options: Option<RecordingStartOptions>,
 ) -> Response<RecordingStatus> {
     let result: anyhow::Result<RecordingStatus> = async {
-        let (name, tags) = options
-            .map(|opt| (opt.name, opt.tags))
-            .unwrap_or((None, None));
+        let mut metadata = RecordingMetadata::current(
+            state.client().await.device_information(),
+            Some(&state.imu_config().await.to_bytes()),
+        )
+        .with_uptime(state.connection_uptime());
+        // 取不到时录制照常开始，只是之后无法换算到其他会话的标定系
+        metadata.calibration = state
+            .pipeline_config_handle
+            .get_recording_calibration()
+            .await
+            .ok();
+        let (name, tags, split, deep) = options
+            .map(|opt| {
+                let split = RecordingSplit {
+                    max_duration_min: opt.max_duration_min,
+                    max_samples: opt.max_samples,
+                };
+                (opt.name, opt.tags, split, opt.deep.unwrap_or(false))
+            })
+            .unwrap_or_default();
         start_recording_service(
             &state.recorder_tx,
+            state.recording_db_path(),
             RecordingStartInput {
                 device_id: None,
                 name,
                 tags,
+                split,
+                metadata: Some(metadata),
+                deep,
             },
         )
         .await
@@ -67,34 +121,114 @@ pub async fn stop_recording(state: State<'_, AppState>) -> Response<RecordingSta
 }
 
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
-/// 列出录制会话。
-pub async fn list_recordings() -> Response<Vec<RecordingMeta>> {
-    let result: anyhow::Result<Vec<RecordingMeta>> = list_recordings_service().await;
+#[tracing::instrument(level = "debug", skip(state))]
+/// 暂停录制：会话保持打开，暂停期间的数据不写入。
+pub async fn pause_recording(state: State<'_, AppState>) -> Response<RecordingStatus> {
+    let result: anyhow::Result<RecordingStatus> = pause_recording_service(&state.recorder_tx).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 恢复暂停的录制，继续写入同一会话。
+pub async fn resume_recording(state: State<'_, AppState>) -> Response<RecordingStatus> {
+    let result: anyhow::Result<RecordingStatus> =
+        resume_recording_service(&state.recorder_tx).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 按条件分页列出录制会话，省略条件时返回全部。
+pub async fn list_recordings(
+    state: State<'_, AppState>,
+    query: Option<RecordingQuery>,
+) -> Response<RecordingPage> {
+    let result: anyhow::Result<RecordingPage> =
+        list_recordings_service(&state.recording_db_path(), &query.unwrap_or_default()).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 全部录制的汇总统计（会话数、样本数、总时长、库大小、常用标签等），结果缓存几秒。
+pub async fn get_recording_statistics(state: State<'_, AppState>) -> Response<RecordingStatistics> {
+    let result: anyhow::Result<RecordingStatistics> =
+        get_recording_statistics_service(&state.recording_db_path(), state.statistics()).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 修复未正常结束的录制会话（回填结束时间与样本数并标记中断），返回修复数量。
+pub async fn repair_recordings(state: State<'_, AppState>) -> Response<u64> {
+    let result: anyhow::Result<u64> =
+        repair_recordings_service(&state.recorder_tx, state.recording_db_path()).await;
 
     Ok(result.into())
 }
 
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
+#[tracing::instrument(level = "debug", skip(state))]
 /// 更新录制会话元信息。
+///
+/// 不传 `notes` 时保留原备注，传入空白文本时清除备注。
 pub async fn update_recording_meta(
+    state: State<'_, AppState>,
     session_id: i64,
     name: Option<String>,
     tags: Option<Vec<String>>,
+    notes: Option<String>,
 ) -> Response<RecordingMeta> {
     let result: anyhow::Result<RecordingMeta> =
-        update_recording_meta_service(session_id, name, tags).await;
+        update_recording_meta_service(&state.recording_db_path(), session_id, name, tags, notes)
+            .await;
 
     Ok(result.into())
 }
 
+/// 导出选项：进度以 `export_progress` 事件推送，取消标志取自导出登记，单位取自当前偏好。
+fn export_options(app: AppHandle, state: &AppState, guard: &ExportGuard) -> ExportOptions {
+    ExportOptions {
+        units: state.units.converter(),
+        on_progress: Some(Box::new(move |progress| {
+            if let Err(e) = app.emit("export_progress", progress) {
+                tracing::warn!("推送 export_progress 事件失败: {:?}", e);
+            }
+        })),
+        cancel: Some(guard.cancel_flag()),
+        ..ExportOptions::default()
+    }
+}
+
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
+#[tracing::instrument(level = "debug", skip(app, state))]
 /// 将指定会话导出为 CSV，返回导出文件的绝对路径。
-pub async fn export_session_csv(session_id: i64) -> Response<String> {
+///
+/// 导出过程中推送 `export_progress` 事件；同一会话已在导出时返回 `ExportInProgress` 错误码，
+/// 被 `cancel_export` 取消时返回 `ExportCancelled`。`include_flags` 为真时追加 `flags` 列，
+/// 列出覆盖每个样本的数据质量标记。位置与速度按当前单位偏好（`set_unit_preferences`）换算。
+pub async fn export_session_csv(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_id: i64,
+    include_flags: Option<bool>,
+) -> Response<String> {
     let result: anyhow::Result<String> = async {
-        let path = export_session_csv_service(session_id).await?;
+        let guard = state.exports().begin(session_id)?;
+        let path = export_session_csv_service(
+            &state.recording_db_path(),
+            session_id,
+            ExportOptions {
+                include_flags: include_flags.unwrap_or(false),
+                ..export_options(app, &state, &guard)
+            },
+        )
+        .await?;
         Ok(path.to_string_lossy().to_string())
     }
     .await;
@@ -103,19 +237,430 @@ pub async fn export_session_csv(session_id: i64) -> Response<String> {
 }
 
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
+#[tracing::instrument(level = "debug", skip(app, state))]
+/// 将指定会话导出为 CSV 写入 `dir`，文件名由会话名称清洗得到，返回导出文件的绝对路径。
+///
+/// 进度事件、取消、`include_flags` 与单位同 [`export_session_csv`]。
+pub async fn export_recording_csv_to_dir(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_id: i64,
+    dir: String,
+    include_flags: Option<bool>,
+) -> Response<String> {
+    let result: anyhow::Result<String> = async {
+        let guard = state.exports().begin(session_id)?;
+        let path = export_recording_csv_to_dir_service(
+            &state.recording_db_path(),
+            session_id,
+            Path::new(&dir),
+            ExportOptions {
+                include_flags: include_flags.unwrap_or(false),
+                ..export_options(app, &state, &guard)
+            },
+        )
+        .await?;
+        Ok(path.to_string_lossy().to_string())
+    }
+    .await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(app, state))]
+/// 将指定会话导出为 Parquet（附当前管线配置），返回导出文件的绝对路径。
+///
+/// `path` 为已存在的目录时按会话名称生成文件名，否则作为完整文件路径。
+/// 进度事件、取消与单位同 [`export_session_csv`]。
+/// 仅在启用 `parquet` feature 时可用，否则返回 `NotSupported` 错误码。
+pub async fn export_recording_parquet(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_id: i64,
+    path: String,
+) -> Response<String> {
+    #[cfg(feature = "parquet")]
+    {
+        use crate::recorder::parquet_export::{
+            export_recording_parquet as export_recording_parquet_service,
+            export_recording_parquet_to_dir as export_recording_parquet_to_dir_service,
+        };
+
+        let result: anyhow::Result<String> = async {
+            let guard = state.exports().begin(session_id)?;
+            let options = export_options(app, &state, &guard);
+            let pipeline_config = state
+                .get_pipeline_config()
+                .await
+                .map_err(anyhow::Error::msg)?;
+            let db_path = state.recording_db_path();
+            let path = Path::new(&path);
+            let written = if path.is_dir() {
+                export_recording_parquet_to_dir_service(
+                    &db_path,
+                    session_id,
+                    path,
+                    &pipeline_config,
+                    options,
+                )
+                .await?
+                .0
+            } else {
+                export_recording_parquet_service(
+                    &db_path,
+                    session_id,
+                    path,
+                    &pipeline_config,
+                    options,
+                )
+                .await?;
+                path.to_path_buf()
+            };
+            Ok(written.to_string_lossy().to_string())
+        }
+        .await;
+        Ok(result.into())
+    }
+    #[cfg(not(feature = "parquet"))]
+    {
+        let _ = (app, state, session_id, path);
+        Ok(IpcResponse::error_with_code(
+            crate::types::error::ErrorCode::NotSupported,
+            "Parquet export is not compiled in; rebuild with --features parquet",
+        ))
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 取消指定会话正在进行的导出，返回是否有导出在进行。
+///
+/// 导出在当前批次写完后停止，未完成的文件删除。
+pub async fn cancel_export(state: State<'_, AppState>, session_id: i64) -> Response<bool> {
+    Ok(IpcResponse::success(state.exports().cancel(session_id)))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
 /// 删除指定录制会话及其所有样本数据。
-pub async fn delete_recording(session_id: i64) -> Response<()> {
-    let result = delete_recording_service(session_id).await;
+pub async fn delete_recording(state: State<'_, AppState>, session_id: i64) -> Response<()> {
+    let result = delete_recording_service(&state.recording_db_path(), session_id).await;
     Ok(result.into())
 }
 
 #[tauri::command]
-#[tracing::instrument(level = "debug")]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 批量删除录制会话，返回已删除的会话 ID；部分会话删除失败时以警告返回。
+pub async fn delete_recordings(
+    state: State<'_, AppState>,
+    session_ids: Vec<i64>,
+) -> Response<Vec<i64>> {
+    match delete_recordings_service(&state.recording_db_path(), &session_ids).await {
+        Ok((deleted, warnings)) => Ok(IpcResponse::partial(deleted, warnings)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 导入轨迹 CSV（`export_session_csv` 格式）为新录制；跳过的行以警告返回。
+pub async fn import_session_csv(
+    state: State<'_, AppState>,
+    path: String,
+    name: Option<String>,
+) -> Response<RecordingMeta> {
+    let result =
+        import_session_csv_service(&state.recording_db_path(), Path::new(&path), name).await;
+    match result {
+        Ok((meta, warnings)) => Ok(IpcResponse::partial(meta, warnings)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
 /// 获取录制样本。
-pub async fn get_recording_samples(session_id: i64) -> Response<Vec<outputs::ResponseData>> {
+pub async fn get_recording_samples(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Response<Vec<outputs::ResponseData>> {
     let result: anyhow::Result<Vec<outputs::ResponseData>> =
-        get_recording_samples_service(session_id).await;
+        get_recording_samples_service(&state.recording_db_path(), session_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 以二进制批量获取录制样本，前端收到 `ArrayBuffer`。
+///
+/// 返回体是原始字节，无法套用 [`IpcResponse`] 包装：失败时以错误消息 reject。
+/// 布局见 `recorder::binary`，`from_ms` / `to_ms` 为设备时间戳闭区间，缺省不限。
+pub async fn get_recording_samples_binary(
+    state: State<'_, AppState>,
+    session_id: i64,
+    from_ms: Option<u64>,
+    to_ms: Option<u64>,
+) -> Result<tauri::ipc::Response, String> {
+    get_recording_samples_binary_service(&state.recording_db_path(), session_id, from_ms, to_ms)
+        .await
+        .map(tauri::ipc::Response::new)
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取深度录制的阶段中间值，`from_ms` / `to_ms` 为设备时间戳闭区间，缺省不限。
+pub async fn get_stage_samples(
+    state: State<'_, AppState>,
+    session_id: i64,
+    from_ms: Option<u64>,
+    to_ms: Option<u64>,
+) -> Response<Vec<RecordingStageSample>> {
+    let result: anyhow::Result<Vec<RecordingStageSample>> =
+        get_stage_samples_service(&state.recording_db_path(), session_id, from_ms, to_ms).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 按设备时间戳对齐两段录制，返回 A/B 对比报告；两段时间不重叠时返回错误。
+pub async fn compare_recordings(
+    state: State<'_, AppState>,
+    session_a: i64,
+    session_b: i64,
+) -> Response<ComparisonReport> {
+    let result: anyhow::Result<ComparisonReport> =
+        compare_recordings_service(&state.recording_db_path(), session_a, session_b).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 用当前 pipeline 配置重跑录制并离线平滑轨迹，返回降采样结果；
+/// `write` 为真时同时写入样本表的 `smoothed_position_*` 列。
+pub async fn smooth_recording(
+    state: State<'_, AppState>,
+    session_id: i64,
+    write: Option<bool>,
+) -> Response<SmoothedTrajectory> {
+    let config = match state.get_pipeline_config().await {
+        Ok(config) => config,
+        Err(err) => return Ok(IpcResponse::error(err)),
+    };
+    let result: anyhow::Result<SmoothedTrajectory> = smooth_recording_service(
+        &state.recording_db_path(),
+        session_id,
+        config,
+        write.unwrap_or(false),
+    )
+    .await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 把会话 `session_id` 的轨迹换算到会话 `target_session_id` 的标定世界系，返回降采样结果；
+/// `write` 为真时同时写入样本表的 `transformed_*` 列。
+pub async fn transform_recording(
+    state: State<'_, AppState>,
+    session_id: i64,
+    target_session_id: i64,
+    write: Option<bool>,
+) -> Response<TransformedTrajectory> {
+    let result: anyhow::Result<TransformedTrajectory> = transform_recording_service(
+        &state.recording_db_path(),
+        session_id,
+        target_session_id,
+        write.unwrap_or(false),
+    )
+    .await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 在当前录制会话中插入标记，未在录制时返回错误。
+pub async fn add_recording_marker(
+    state: State<'_, AppState>,
+    label: String,
+) -> Response<RecordingMarker> {
+    let result: anyhow::Result<RecordingMarker> =
+        add_recording_marker_service(&state.recorder_tx, label).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 在当前录制会话中记录同步脉冲（主机时间、设备时间与时钟同步结果），未在录制时返回错误。
+pub async fn record_sync_pulse(
+    state: State<'_, AppState>,
+    source: String,
+) -> Response<RecordingSyncEvent> {
+    let result: anyhow::Result<RecordingSyncEvent> =
+        record_sync_pulse_service(&state.recorder_tx, source).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取指定录制会话的同步事件（手动脉冲与自动检测的敲击）。
+pub async fn get_sync_events(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Response<Vec<RecordingSyncEvent>> {
+    let result: anyhow::Result<Vec<RecordingSyncEvent>> =
+        get_sync_events_service(&state.recording_db_path(), session_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 把飞行记录仪保留的最近一段数据（默认 30 s）写成新的录制会话，不需要事先开始录制。
+pub async fn dump_flight_recorder(
+    state: State<'_, AppState>,
+    name: Option<String>,
+) -> Response<RecordingMeta> {
+    let result: anyhow::Result<RecordingMeta> =
+        dump_flight_recorder_service(&state.recorder_tx, state.recording_db_path(), name).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取指定录制会话的标记列表。
+pub async fn get_recording_markers(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Response<Vec<RecordingMarker>> {
+    let result: anyhow::Result<Vec<RecordingMarker>> =
+        get_recording_markers_service(&state.recording_db_path(), session_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 给会话中 `[from_ms, to_ms]`（设备时间戳）的样本打上数据质量标记，返回新标记。
+///
+/// 区间须落在会话样本的时间范围内，可与已有标记重叠。
+pub async fn flag_sample_range(
+    state: State<'_, AppState>,
+    session_id: i64,
+    from_ms: i64,
+    to_ms: i64,
+    label: String,
+) -> Response<RecordingFlag> {
+    let result: anyhow::Result<RecordingFlag> = flag_sample_range_service(
+        &state.recording_db_path(),
+        session_id,
+        from_ms,
+        to_ms,
+        &label,
+    )
+    .await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取指定录制会话的数据质量标记，按区间起点升序。
+pub async fn get_recording_flags(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Response<Vec<RecordingFlag>> {
+    let result: anyhow::Result<Vec<RecordingFlag>> =
+        get_recording_flags_service(&state.recording_db_path(), session_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 删除一条数据质量标记。
+pub async fn delete_recording_flag(state: State<'_, AppState>, flag_id: i64) -> Response<()> {
+    let result: anyhow::Result<()> =
+        delete_recording_flag_service(&state.recording_db_path(), flag_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取指定录制会话的暂停区间。
+pub async fn get_recording_pauses(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Response<Vec<RecordingPause>> {
+    let result: anyhow::Result<Vec<RecordingPause>> =
+        get_recording_pauses_service(&state.recording_db_path(), session_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取指定录制会话中检测到的运动分段。
+pub async fn get_recording_segments(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Response<Vec<RecordingSegment>> {
+    let result: anyhow::Result<Vec<RecordingSegment>> =
+        get_recording_segments_service(&state.recording_db_path(), session_id).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 检查当前录制数据库：返回完整性检查结果与表结构版本，不做迁移。
+///
+/// 检查不通过时仍返回成功响应，问题列在 `problems` 中。
+pub async fn verify_recordings_database(state: State<'_, AppState>) -> Response<DatabaseIntegrity> {
+    let result = verify_database_service(&state.recording_db_path()).await;
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取当前录制目录。
+pub async fn get_recording_directory(state: State<'_, AppState>) -> Response<String> {
+    let directory = state.recording_location.directory();
+    Ok(IpcResponse::success(
+        directory.to_string_lossy().to_string(),
+    ))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取默认录制目录（未自定义时使用）。
+pub async fn get_default_recording_directory(state: State<'_, AppState>) -> Response<String> {
+    let directory = state.recording_location.default_directory();
+    Ok(IpcResponse::success(
+        directory.to_string_lossy().to_string(),
+    ))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 设置录制目录：校验可写后持久化，返回实际生效的目录。
+///
+/// 已有录制不会迁移，仍保留在原目录的数据库中。
+pub async fn set_recording_directory(state: State<'_, AppState>, path: String) -> Response<String> {
+    let result: anyhow::Result<String> = state
+        .set_recording_directory(path.into())
+        .map(|directory| directory.to_string_lossy().to_string());
 
     Ok(result.into())
 }
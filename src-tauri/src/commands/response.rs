@@ -2,18 +2,34 @@
 
 use serde::Serialize;
 
+use crate::{
+    imu::{BluetoothError, DeviceUnreachable},
+    recorder::{db::DatabaseError, ExportError, RecordingStillStarting},
+    replay::PlaybackRefused,
+    types::error::ErrorCode,
+};
+
 #[derive(Debug, Serialize)]
 /// IPC 响应包装。
+///
+/// 三种形态：成功（`success = true`，无警告）、部分成功（`success = true`，
+/// `warnings` 非空，`data` 为已完成的部分）、失败（`success = false`，`data` 为空；
+/// 操作被拒绝时 `data` 为拒绝依据，如校准质量评分）。
 pub struct Response<T>
 where
     T: Serialize,
 {
-    /// 是否成功。
+    /// 是否成功（部分成功也为 `true`）。
     pub success: bool,
     /// 返回数据。
     pub data: Option<T>,
     /// 提示消息。
     pub message: String,
+    /// 警告列表：批量操作中被跳过的条目等，成功与失败时为空。
+    pub warnings: Vec<String>,
+    /// 结构化错误码，仅部分失败原因提供（如蓝牙不可用），无错误码时不输出该字段。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
 }
 
 impl<T> Response<T>
@@ -26,6 +42,41 @@ where
             success: true,
             message: "ok".to_string(),
             data: Some(data),
+            warnings: Vec::new(),
+            code: None,
+        }
+    }
+
+    /// 构造部分成功响应；没有警告时等同于 [`Response::success`]。
+    pub fn partial(data: T, warnings: Vec<String>) -> Self {
+        if warnings.is_empty() {
+            return Self::success(data);
+        }
+        Self {
+            success: true,
+            message: format!("partial: {} warning(s)", warnings.len()),
+            data: Some(data),
+            warnings,
+            code: None,
+        }
+    }
+
+    /// 构造附带数据的失败响应：操作被拒绝，`data` 说明拒绝的依据。
+    pub fn rejected<S: Into<String>>(data: T, message: S) -> Self {
+        Self {
+            success: false,
+            message: message.into(),
+            data: Some(data),
+            warnings: Vec::new(),
+            code: None,
+        }
+    }
+
+    /// 构造带错误码的失败响应。
+    pub fn error_with_code<S: Into<String>>(code: ErrorCode, message: S) -> Self {
+        Self {
+            code: Some(code),
+            ..Self::error(message)
         }
     }
 
@@ -35,6 +86,8 @@ where
             success: false,
             message: message.into(),
             data: None,
+            warnings: Vec::new(),
+            code: None,
         }
     }
 }
@@ -46,8 +99,7 @@ where
     fn from(result: anyhow::Result<T>) -> Self {
         match result {
             Ok(data) => Response::success(data),
-            // Use alternate Display to include the full context chain.
-            Err(e) => Response::error(format!("{:#}", e)),
+            Err(e) => e.into(),
         }
     }
 }
@@ -58,6 +110,119 @@ where
 {
     fn from(e: anyhow::Error) -> Self {
         // Use alternate Display to include the full context chain.
-        Response::error(format!("{:#}", e))
+        let message = format!("{:#}", e);
+        let code = e.chain().find_map(|cause| {
+            cause
+                .downcast_ref::<BluetoothError>()
+                .map(BluetoothError::code)
+                .or_else(|| cause.downcast_ref::<ExportError>().map(ExportError::code))
+                .or_else(|| {
+                    cause
+                        .downcast_ref::<DatabaseError>()
+                        .map(DatabaseError::code)
+                })
+                .or_else(|| {
+                    cause
+                        .downcast_ref::<DeviceUnreachable>()
+                        .map(DeviceUnreachable::code)
+                })
+                .or_else(|| {
+                    cause
+                        .downcast_ref::<RecordingStillStarting>()
+                        .map(RecordingStillStarting::code)
+                })
+                .or_else(|| {
+                    cause
+                        .downcast_ref::<PlaybackRefused>()
+                        .map(PlaybackRefused::code)
+                })
+        });
+        match code {
+            Some(code) => Response::error_with_code(code, message),
+            None => Response::error(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn success_partial_and_error_shapes() {
+        let success = serde_json::to_value(Response::success(vec![1, 2])).unwrap();
+        assert_eq!(
+            success,
+            json!({ "success": true, "data": [1, 2], "message": "ok", "warnings": [] })
+        );
+
+        let partial = serde_json::to_value(Response::partial(
+            vec![1],
+            vec!["session 2: not found".to_string()],
+        ))
+        .unwrap();
+        assert_eq!(
+            partial,
+            json!({
+                "success": true,
+                "data": [1],
+                "message": "partial: 1 warning(s)",
+                "warnings": ["session 2: not found"],
+            })
+        );
+        // 没有警告的部分成功就是成功
+        assert_eq!(
+            serde_json::to_value(Response::partial(vec![1, 2], Vec::new())).unwrap(),
+            success
+        );
+
+        let error: Response<Vec<i32>> =
+            anyhow::Result::<Vec<i32>>::Err(anyhow::anyhow!("disk gone").context("delete")).into();
+        assert_eq!(
+            serde_json::to_value(error).unwrap(),
+            json!({
+                "success": false,
+                "data": null,
+                "message": "delete: disk gone",
+                "warnings": [],
+            })
+        );
+
+        assert_eq!(
+            serde_json::to_value(Response::rejected(42, "too shaky")).unwrap(),
+            json!({ "success": false, "data": 42, "message": "too shaky", "warnings": [] })
+        );
+
+        // 错误链中的蓝牙错误带出错误码
+        let unavailable: Response<()> = anyhow::Error::new(BluetoothError::NoAdapter)
+            .context("开始扫描")
+            .into();
+        assert_eq!(
+            serde_json::to_value(unavailable).unwrap(),
+            json!({
+                "success": false,
+                "data": null,
+                "message": "开始扫描: 没找到蓝牙适配器",
+                "warnings": [],
+                "code": "AdapterNotFound",
+            })
+        );
+
+        let cancelled: Response<()> = anyhow::Error::new(ExportError::Cancelled).into();
+        assert_eq!(
+            serde_json::to_value(cancelled).unwrap()["code"],
+            json!("ExportCancelled")
+        );
+
+        let corrupt: Response<()> =
+            anyhow::Error::new(DatabaseError::Corrupt(vec!["page 3 is never used".into()]))
+                .context("list recordings")
+                .into();
+        assert_eq!(
+            serde_json::to_value(corrupt).unwrap()["code"],
+            json!("DatabaseCorrupt")
+        );
     }
 }
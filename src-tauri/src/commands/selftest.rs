@@ -0,0 +1,21 @@
+//! 无硬件自检命令。
+
+use crate::{
+    app_state::AppState, commands::response::Response as IpcResponse, selftest::SelfTestReport,
+};
+use tauri::State;
+
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 运行自检，`record` 默认为 `true`（包含录制写入与读回）。
+///
+/// 检查项失败不视为命令错误，结果见报告中的 `passed` 与各检查项。
+pub async fn run_self_test(
+    state: State<'_, AppState>,
+    record: Option<bool>,
+) -> Response<SelfTestReport> {
+    let report = state.run_self_test(record.unwrap_or(true)).await;
+    Ok(IpcResponse::success(report))
+}
@@ -0,0 +1,28 @@
+//! 应用状态查询命令。
+
+use crate::{
+    app_state::AppState, commands::response::Response as IpcResponse, types::status::AppStatus,
+};
+use tauri::State;
+
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取连接、录制与处理状态快照。
+pub async fn get_app_status(state: State<'_, AppState>) -> Response<AppStatus> {
+    match state.app_status().await {
+        Ok(status) => Ok(IpcResponse::success(status)),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 设置数据流停滞阈值（毫秒）：连接中超过该时长没有数据即推送 `data_stalled`。
+pub async fn set_stall_threshold(state: State<'_, AppState>, threshold_ms: u64) -> Response<()> {
+    match state.set_stall_threshold_ms(threshold_ms).await {
+        Ok(()) => Ok(IpcResponse::success(())),
+        Err(err) => Ok(IpcResponse::error(err)),
+    }
+}
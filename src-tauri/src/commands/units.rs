@@ -0,0 +1,40 @@
+//! 单位偏好与格式化展示命令。
+
+use tauri::State;
+
+use crate::{
+    app_state::AppState,
+    commands::response::Response as IpcResponse,
+    units::{FrameDisplay, UnitPreferences},
+};
+
+type Response<T> = Result<IpcResponse<T>, ()>;
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取当前单位偏好。
+pub fn get_unit_preferences(state: State<'_, AppState>) -> Response<UnitPreferences> {
+    Ok(IpcResponse::success(state.units.get()))
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 设置单位偏好并持久化，之后的导出与 `get_latest_frame_display` 立即按新单位换算。
+///
+/// 只影响展示与导出，管线与录制数据始终为国际单位，无需重启管线。
+pub fn set_unit_preferences(
+    state: State<'_, AppState>,
+    preferences: UnitPreferences,
+) -> Response<UnitPreferences> {
+    let result: anyhow::Result<UnitPreferences> =
+        state.units.set(preferences).map(|()| preferences);
+
+    Ok(result.into())
+}
+
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip(state))]
+/// 获取按单位偏好格式化的最新一帧（位置、速度、欧拉角等展示字符串），尚无数据时为空。
+pub fn get_latest_frame_display(state: State<'_, AppState>) -> Response<Option<FrameDisplay>> {
+    Ok(IpcResponse::success(state.latest_frame_display()))
+}
@@ -0,0 +1,244 @@
+//! 离线处理逻辑。
+
+use std::{
+    io::Write,
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use anyhow::{anyhow, bail, Context};
+use math_f64::{DQuat, DVec3};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::{
+    headless::types::{OutputFormat, ProcessOptions, ProcessSummary, ProcessedFrame},
+    processor::{
+        parser::{BaroSample, ImuSampleRaw},
+        pipeline::{
+            diagnostics::{diagnostics_channel, QueueProbe},
+            ProcessorPipeline, ProcessorPipelineConfig,
+        },
+        shared::DeviceTimeMs,
+    },
+    recorder::{db, models},
+};
+
+/// 读取录制会话及其全部样本（按设备时间戳升序）。
+///
+/// `session` 可为数字 id 或完整名称，缺省取最近开始的会话。
+pub async fn load_recording(
+    db_path: &Path,
+    session: Option<&str>,
+) -> anyhow::Result<(
+    models::recording_sessions::Model,
+    Vec<models::imu_samples::Model>,
+)> {
+    use models::recording_sessions::{Column, Entity};
+
+    // db::connect 以 rwc 模式打开，路径写错时会悄悄建一个空库
+    if !db_path.is_file() {
+        bail!("recording database not found: {}", db_path.display());
+    }
+    let conn = db::connect(db_path).await?;
+    db::ensure_schema(&conn).await?;
+
+    let found = match session {
+        Some(query) => {
+            let by_id = match query.parse::<i64>() {
+                Ok(id) => Entity::find_by_id(id)
+                    .one(&conn)
+                    .await
+                    .context("query session by id")?,
+                Err(_) => None,
+            };
+            match by_id {
+                Some(found) => Some(found),
+                None => Entity::find()
+                    .filter(Column::Name.eq(query))
+                    .order_by_desc(Column::StartedAtMs)
+                    .one(&conn)
+                    .await
+                    .context("query session by name")?,
+            }
+        }
+        None => Entity::find()
+            .order_by_desc(Column::StartedAtMs)
+            .one(&conn)
+            .await
+            .context("query latest session")?,
+    };
+    let session = found.ok_or_else(|| anyhow!("no matching session: {:?}", session))?;
+
+    let rows = models::imu_samples::Entity::find()
+        .filter(models::imu_samples::Column::SessionId.eq(session.id))
+        .order_by_asc(models::imu_samples::Column::TimestampMs)
+        .all(&conn)
+        .await
+        .context("query recording samples")?;
+
+    Ok((session, rows))
+}
+
+/// 把一行录制样本还原为管线输入。
+pub fn sample_row_to_raw(row: &models::imu_samples::Model) -> ImuSampleRaw {
+    ImuSampleRaw {
+        timestamp_ms: DeviceTimeMs(row.timestamp_ms as u64),
+        accel_no_g: DVec3::new(row.accel_no_g_x, row.accel_no_g_y, row.accel_no_g_z),
+        accel_with_g: DVec3::new(row.accel_with_g_x, row.accel_with_g_y, row.accel_with_g_z),
+        gyro: DVec3::new(row.gyro_x, row.gyro_y, row.gyro_z),
+        quat: DQuat::from_xyzw(row.quat_x, row.quat_y, row.quat_z, row.quat_w),
+        angle: DVec3::new(row.angle_x, row.angle_y, row.angle_z),
+        offset: DVec3::new(row.offset_x, row.offset_y, row.offset_z),
+        accel_nav: DVec3::new(row.accel_nav_x, row.accel_nav_y, row.accel_nav_z),
+        baro: match (
+            row.baro_temperature_c,
+            row.baro_pressure_hpa,
+            row.baro_altitude_m,
+        ) {
+            (Some(temperature_c), Some(pressure_hpa), Some(altitude_m)) => Some(BaroSample {
+                temperature_c,
+                pressure_hpa,
+                altitude_m,
+            }),
+            _ => None,
+        },
+    }
+}
+
+/// 用给定配置逐帧重跑管线（诊断关闭）。
+pub fn run_pipeline(
+    config: ProcessorPipelineConfig,
+    samples: impl IntoIterator<Item = ImuSampleRaw>,
+) -> Vec<ProcessedFrame> {
+    let (diag_tx, _diag_rx) = diagnostics_channel(1);
+    let mut pipeline = ProcessorPipeline::new(
+        config,
+        Arc::new(AtomicBool::new(false)),
+        diag_tx,
+        QueueProbe::detached(),
+    );
+    samples
+        .into_iter()
+        .filter_map(|sample| {
+            let frame = pipeline.process_sample_raw(sample)?;
+            Some(ProcessedFrame {
+                nav: frame.nav,
+                is_static: pipeline.is_static(),
+            })
+        })
+        .collect()
+}
+
+/// 计算汇总统计。
+///
+/// * `recorded_final_position`: 录制时存储的最终位置，用作回归参照
+pub fn summarize(
+    session_id: i64,
+    samples: usize,
+    frames: &[ProcessedFrame],
+    recorded_final_position: DVec3,
+) -> ProcessSummary {
+    let final_position = frames
+        .last()
+        .map(|frame| frame.nav.position)
+        .unwrap_or(DVec3::ZERO);
+    let max_speed_mps = frames
+        .iter()
+        .map(|frame| frame.nav.velocity.length())
+        .fold(0.0, f64::max);
+    let static_frames = frames.iter().filter(|frame| frame.is_static).count();
+    let static_fraction = if frames.is_empty() {
+        0.0
+    } else {
+        static_frames as f64 / frames.len() as f64
+    };
+
+    ProcessSummary {
+        session_id,
+        samples,
+        frames: frames.len(),
+        final_position,
+        recorded_final_position,
+        final_position_error_m: (final_position - recorded_final_position).length(),
+        max_speed_mps,
+        static_fraction,
+    }
+}
+
+/// 把结果序列写到文件。
+pub fn write_output(
+    path: &Path,
+    format: OutputFormat,
+    frames: &[ProcessedFrame],
+) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context("create output directory")?;
+    }
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("create output file {}", path.display()))?;
+    let mut out = std::io::BufWriter::new(file);
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer(&mut out, frames).context("serialize frames to json")?;
+        }
+        OutputFormat::Csv => {
+            writeln!(
+                out,
+                "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+                 calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+                 calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z,is_static"
+            )?;
+            for frame in frames {
+                let (p, v, q) = (frame.nav.position, frame.nav.velocity, frame.nav.attitude);
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{},{},{}",
+                    frame.nav.timestamp_ms,
+                    p.x,
+                    p.y,
+                    p.z,
+                    v.x,
+                    v.y,
+                    v.z,
+                    q.w,
+                    q.x,
+                    q.y,
+                    q.z,
+                    frame.is_static as u8,
+                )?;
+            }
+        }
+    }
+    out.flush().context("flush output file")?;
+    Ok(())
+}
+
+/// 端到端离线处理：读录制 → 重跑管线 → 写结果 → 汇总。
+pub async fn process_recording(options: ProcessOptions) -> anyhow::Result<ProcessSummary> {
+    let (session, rows) = load_recording(&options.input, options.session.as_deref()).await?;
+    if rows.is_empty() {
+        bail!("session {} has no samples", session.id);
+    }
+    let recorded_final_position = rows
+        .last()
+        .map(|row| {
+            DVec3::new(
+                row.calc_position_x,
+                row.calc_position_y,
+                row.calc_position_z,
+            )
+        })
+        .unwrap_or(DVec3::ZERO);
+
+    let frames = run_pipeline(options.config, rows.iter().map(sample_row_to_raw));
+    if let Some(path) = &options.output {
+        write_output(path, options.format, &frames)?;
+    }
+
+    Ok(summarize(
+        session.id,
+        rows.len(),
+        &frames,
+        recorded_final_position,
+    ))
+}
@@ -0,0 +1,19 @@
+//! 无界面离线处理。
+//!
+//! 从录制数据库读出原始样本，用给定配置重跑 [`ProcessorPipeline`]，输出导航结果
+//! 序列与汇总统计。不依赖 Tauri，可在 `--no-default-features` 下编译，便于在 CI
+//! 中对比不同管线版本在基准录制上的表现。
+//!
+//! [`ProcessorPipeline`]: crate::processor::pipeline::ProcessorPipeline
+
+/// 离线处理逻辑。
+pub mod logic;
+/// 离线处理参数与结果类型。
+pub mod types;
+
+/// 离线处理入口与各步骤。
+pub use logic::{
+    load_recording, process_recording, run_pipeline, sample_row_to_raw, summarize, write_output,
+};
+/// 离线处理参数与结果类型。
+pub use types::{OutputFormat, ProcessOptions, ProcessSummary, ProcessedFrame};
@@ -0,0 +1,107 @@
+//! 离线处理相关类型。
+
+use std::{fmt, path::PathBuf};
+
+use math_f64::DVec3;
+use serde::Serialize;
+
+use crate::processor::{navigator::NavState, pipeline::ProcessorPipelineConfig};
+
+/// 结果序列输出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 逗号分隔，表头与录制导出 CSV 一致并追加 `is_static` 列。
+    Csv,
+    /// JSON 数组。
+    Json,
+}
+
+impl OutputFormat {
+    /// 按文件扩展名推断格式，`.json` 以外一律按 CSV。
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// 离线处理参数。
+#[derive(Debug, Clone)]
+pub struct ProcessOptions {
+    /// 录制数据库路径（SQLite）。
+    pub input: PathBuf,
+    /// 会话 id 或名称，缺省取最近一次录制。
+    pub session: Option<String>,
+    /// 管线配置。
+    pub config: ProcessorPipelineConfig,
+    /// 结果序列输出路径，缺省只计算汇总。
+    pub output: Option<PathBuf>,
+    /// 结果序列输出格式。
+    pub format: OutputFormat,
+}
+
+/// 管线输出的一帧。
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ProcessedFrame {
+    /// 导航状态。
+    #[serde(flatten)]
+    pub nav: NavState,
+    /// 本帧是否处于静止（ZUPT）状态。
+    pub is_static: bool,
+}
+
+/// 离线处理汇总统计。
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSummary {
+    /// 会话 id。
+    pub session_id: i64,
+    /// 输入样本数。
+    pub samples: usize,
+    /// 管线输出帧数（去重/乱序丢弃后）。
+    pub frames: usize,
+    /// 重跑后的最终位置 (m)。
+    pub final_position: DVec3,
+    /// 录制时存储的最终位置 (m)。
+    pub recorded_final_position: DVec3,
+    /// 最终位置与录制值之差的模长 (m)。
+    pub final_position_error_m: f64,
+    /// 最大速度模长 (m/s)。
+    pub max_speed_mps: f64,
+    /// 静止帧占比，取值 [0, 1]。
+    pub static_fraction: f64,
+}
+
+impl fmt::Display for ProcessSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let p = self.final_position;
+        let r = self.recorded_final_position;
+        writeln!(f, "session:            {}", self.session_id)?;
+        writeln!(
+            f,
+            "frames:             {} / {} samples",
+            self.frames, self.samples
+        )?;
+        writeln!(
+            f,
+            "final position:     ({:.4}, {:.4}, {:.4}) m",
+            p.x, p.y, p.z
+        )?;
+        writeln!(
+            f,
+            "recorded position:  ({:.4}, {:.4}, {:.4}) m",
+            r.x, r.y, r.z
+        )?;
+        writeln!(
+            f,
+            "final pos error:    {:.4} m",
+            self.final_position_error_m
+        )?;
+        writeln!(f, "max speed:          {:.4} m/s", self.max_speed_mps)?;
+        write!(
+            f,
+            "static fraction:    {:.1}%",
+            self.static_fraction * 100.0
+        )
+    }
+}
@@ -0,0 +1,375 @@
+//! 蓝牙适配器枚举与选择。
+//!
+//! 插了 USB 蓝牙棒的机器往往有多个适配器，默认取第一个未必是想用的那个。这里记录用户
+//! 指定的适配器，未指定时取第一个；切换后丢弃缓存的适配器句柄，之后的扫描与连接使用
+//! 新适配器。
+//!
+//! 适配器 ID 取平台给出的描述字符串，同名时依次追加 `#2`、`#3`（Windows / macOS 上
+//! 描述固定为 `WinRT` / `CoreBluetooth`）。
+
+use std::future::Future;
+
+use btleplug::{
+    api::{Central as _, CentralState, Manager as _},
+    platform::{Adapter, Manager},
+};
+use tokio::sync::OnceCell;
+
+use crate::types::{
+    bluetooth::{BluetoothAdapterInfo, BluetoothStatus},
+    error::ErrorCode,
+};
+
+/// 蓝牙错误，失败响应据此附带 [`ErrorCode`]。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BluetoothError {
+    /// 蓝牙服务不可用。
+    #[error("蓝牙不可用（请确认系统蓝牙已开启）: {0}")]
+    Unavailable(String),
+    /// 没有任何适配器。
+    #[error("没找到蓝牙适配器")]
+    NoAdapter,
+    /// 指定的适配器不存在（已拔出或 ID 有误）。
+    #[error("蓝牙适配器 {0} 不存在")]
+    AdapterNotFound(String),
+}
+
+impl BluetoothError {
+    /// 对应的 IPC 错误码。
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            BluetoothError::Unavailable(_) => ErrorCode::BluetoothUnavailable,
+            BluetoothError::NoAdapter | BluetoothError::AdapterNotFound(_) => {
+                ErrorCode::AdapterNotFound
+            }
+        }
+    }
+}
+
+/// 适配器来源，测试中以假实现替换 btleplug。
+pub trait AdapterProvider {
+    /// 适配器句柄。
+    type Adapter: Clone;
+
+    /// 枚举适配器；蓝牙服务本身不可用时返回 [`BluetoothError::Unavailable`]。
+    fn adapters(&self) -> impl Future<Output = Result<Vec<Self::Adapter>, BluetoothError>> + Send;
+
+    /// 适配器描述与开启状态（平台不提供时为空）。
+    fn describe(
+        &self,
+        adapter: &Self::Adapter,
+    ) -> impl Future<Output = (String, Option<bool>)> + Send;
+}
+
+/// 通过 btleplug 枚举本机适配器。
+pub struct BtleplugAdapters;
+
+impl AdapterProvider for BtleplugAdapters {
+    type Adapter = Adapter;
+
+    async fn adapters(&self) -> Result<Vec<Adapter>, BluetoothError> {
+        let manager = Manager::new()
+            .await
+            .map_err(|e| BluetoothError::Unavailable(e.to_string()))?;
+        manager
+            .adapters()
+            .await
+            .map_err(|e| BluetoothError::Unavailable(e.to_string()))
+    }
+
+    async fn describe(&self, adapter: &Adapter) -> (String, Option<bool>) {
+        let name = adapter
+            .adapter_info()
+            .await
+            .unwrap_or_else(|_| "unknown".to_string());
+        let powered = match adapter.adapter_state().await {
+            Ok(CentralState::PoweredOn) => Some(true),
+            Ok(CentralState::PoweredOff) => Some(false),
+            Ok(CentralState::Unknown) | Err(_) => None,
+        };
+        (name, powered)
+    }
+}
+
+/// 适配器选择状态。
+pub struct AdapterSelection<P: AdapterProvider> {
+    provider: P,
+    /// 用户指定的适配器 ID，为空时取第一个。
+    selected_id: Option<String>,
+    /// 当前使用的适配器，首次使用时解析。
+    current: OnceCell<P::Adapter>,
+}
+
+impl<P: AdapterProvider> AdapterSelection<P> {
+    /// 创建选择状态，初始未指定适配器。
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            selected_id: None,
+            current: OnceCell::new(),
+        }
+    }
+
+    /// 当前使用的适配器；蓝牙暂不可用时不缓存失败，下次调用重新枚举。
+    pub async fn adapter(&self) -> Result<&P::Adapter, BluetoothError> {
+        self.current
+            .get_or_try_init(|| async {
+                let adapters = self.provider.adapters().await?;
+                let infos = self.describe_all(&adapters).await;
+                let index = self.active_index(&infos)?;
+                Ok(adapters[index].clone())
+            })
+            .await
+    }
+
+    /// 列出全部适配器，当前使用的标记为 `selected`。
+    pub async fn list(&self) -> Result<Vec<BluetoothAdapterInfo>, BluetoothError> {
+        let adapters = self.provider.adapters().await?;
+        let mut infos = self.describe_all(&adapters).await;
+        if let Ok(index) = self.active_index(&infos) {
+            infos[index].selected = true;
+        }
+        Ok(infos)
+    }
+
+    /// 指定之后使用的适配器；ID 不存在时保持原选择。
+    pub async fn select(&mut self, id: &str) -> Result<BluetoothAdapterInfo, BluetoothError> {
+        let adapters = self.provider.adapters().await?;
+        let mut infos = self.describe_all(&adapters).await;
+        let index = infos
+            .iter()
+            .position(|info| info.id == id)
+            .ok_or_else(|| BluetoothError::AdapterNotFound(id.to_string()))?;
+        self.selected_id = Some(id.to_string());
+        self.current = OnceCell::new_with(Some(adapters[index].clone()));
+        let mut info = infos.swap_remove(index);
+        info.selected = true;
+        Ok(info)
+    }
+
+    /// 蓝牙可用性诊断。
+    pub async fn status(&self) -> BluetoothStatus {
+        let (infos, error) = match self.list().await {
+            Ok(infos) => {
+                let error = match &self.selected_id {
+                    Some(id) if !infos.iter().any(|info| info.selected) => {
+                        Some(BluetoothError::AdapterNotFound(id.clone()))
+                    }
+                    _ if infos.is_empty() => Some(BluetoothError::NoAdapter),
+                    _ => None,
+                };
+                (Some(infos), error)
+            }
+            Err(error) => (None, Some(error)),
+        };
+        BluetoothStatus {
+            manager_available: infos.is_some(),
+            adapter_count: infos.as_ref().map_or(0, Vec::len),
+            selected_adapter: infos
+                .iter()
+                .flatten()
+                .find(|info| info.selected)
+                .map(|info| info.id.clone()),
+            error: error.as_ref().map(ToString::to_string),
+            error_code: error.as_ref().map(BluetoothError::code),
+        }
+    }
+
+    /// 按枚举顺序描述适配器并分配 ID。
+    async fn describe_all(&self, adapters: &[P::Adapter]) -> Vec<BluetoothAdapterInfo> {
+        let mut infos: Vec<BluetoothAdapterInfo> = Vec::with_capacity(adapters.len());
+        for adapter in adapters {
+            let (name, powered) = self.provider.describe(adapter).await;
+            let same_name = infos.iter().filter(|info| info.name == name).count();
+            let id = if same_name == 0 {
+                name.clone()
+            } else {
+                format!("{name}#{}", same_name + 1)
+            };
+            infos.push(BluetoothAdapterInfo {
+                id,
+                name,
+                powered,
+                selected: false,
+            });
+        }
+        infos
+    }
+
+    /// 当前应使用的适配器下标。
+    fn active_index(&self, infos: &[BluetoothAdapterInfo]) -> Result<usize, BluetoothError> {
+        match &self.selected_id {
+            Some(id) => infos
+                .iter()
+                .position(|info| &info.id == id)
+                .ok_or_else(|| BluetoothError::AdapterNotFound(id.clone())),
+            None if infos.is_empty() => Err(BluetoothError::NoAdapter),
+            None => Ok(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    use super::*;
+
+    type FakeAdapter = (String, Option<bool>);
+
+    /// 假适配器来源：句柄即 (描述, 开启状态)，可随时改动适配器列表或模拟蓝牙关闭。
+    struct FakeAdapters {
+        adapters: Mutex<Result<Vec<FakeAdapter>, BluetoothError>>,
+        enumerations: AtomicUsize,
+    }
+
+    impl FakeAdapters {
+        fn new(adapters: &[(&str, Option<bool>)]) -> Self {
+            let fake = Self {
+                adapters: Mutex::new(Ok(Vec::new())),
+                enumerations: AtomicUsize::new(0),
+            };
+            fake.set(adapters);
+            fake
+        }
+
+        fn set(&self, adapters: &[(&str, Option<bool>)]) {
+            *self.adapters.lock().unwrap() = Ok(adapters
+                .iter()
+                .map(|(name, powered)| (name.to_string(), *powered))
+                .collect());
+        }
+
+        fn power_off(&self) {
+            *self.adapters.lock().unwrap() =
+                Err(BluetoothError::Unavailable("org.bluez not found".into()));
+        }
+
+        fn enumerations(&self) -> usize {
+            self.enumerations.load(Ordering::Relaxed)
+        }
+    }
+
+    impl AdapterProvider for &FakeAdapters {
+        type Adapter = FakeAdapter;
+
+        async fn adapters(&self) -> Result<Vec<FakeAdapter>, BluetoothError> {
+            self.enumerations.fetch_add(1, Ordering::Relaxed);
+            self.adapters.lock().unwrap().clone()
+        }
+
+        async fn describe(&self, adapter: &FakeAdapter) -> (String, Option<bool>) {
+            adapter.clone()
+        }
+    }
+
+    fn ids(infos: &[BluetoothAdapterInfo]) -> Vec<(&str, bool)> {
+        infos
+            .iter()
+            .map(|info| (info.id.as_str(), info.selected))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn defaults_to_first_adapter_and_switches_on_select() {
+        let fake = FakeAdapters::new(&[
+            ("hci0 (usb:v1D6Bp0246)", Some(true)),
+            ("WinRT", None),
+            ("WinRT", Some(false)),
+        ]);
+        let mut selection = AdapterSelection::new(&fake);
+
+        // 同名适配器追加序号区分
+        let infos = selection.list().await.unwrap();
+        assert_eq!(
+            ids(&infos),
+            [
+                ("hci0 (usb:v1D6Bp0246)", true),
+                ("WinRT", false),
+                ("WinRT#2", false)
+            ]
+        );
+        assert_eq!(infos[2].powered, Some(false));
+        assert_eq!(
+            selection.adapter().await.unwrap().0,
+            "hci0 (usb:v1D6Bp0246)"
+        );
+
+        // 句柄缓存后不再枚举
+        let before = fake.enumerations();
+        selection.adapter().await.unwrap();
+        assert_eq!(fake.enumerations(), before);
+
+        let selected = selection.select("WinRT#2").await.unwrap();
+        assert_eq!((selected.id.as_str(), selected.selected), ("WinRT#2", true));
+        assert_eq!(
+            selection.adapter().await.unwrap(),
+            &("WinRT".to_string(), Some(false))
+        );
+        assert_eq!(
+            ids(&selection.list().await.unwrap()),
+            [
+                ("hci0 (usb:v1D6Bp0246)", false),
+                ("WinRT", false),
+                ("WinRT#2", true)
+            ]
+        );
+
+        // 不存在的 ID 不改变当前选择
+        assert_eq!(
+            selection.select("hci9").await.unwrap_err(),
+            BluetoothError::AdapterNotFound("hci9".into())
+        );
+        assert_eq!(
+            selection.status().await.selected_adapter.as_deref(),
+            Some("WinRT#2")
+        );
+    }
+
+    #[tokio::test]
+    async fn unavailable_bluetooth_and_missing_adapters_map_to_error_codes() {
+        let fake = FakeAdapters::new(&[]);
+        let mut selection = AdapterSelection::new(&fake);
+
+        // 蓝牙服务正常但没有适配器
+        let error = selection.adapter().await.unwrap_err();
+        assert_eq!(
+            (error.clone(), error.code()),
+            (BluetoothError::NoAdapter, ErrorCode::AdapterNotFound)
+        );
+        let status = selection.status().await;
+        assert!(status.manager_available);
+        assert_eq!((status.adapter_count, status.selected_adapter), (0, None));
+        assert_eq!(status.error_code, Some(ErrorCode::AdapterNotFound));
+
+        // 蓝牙关闭：失败不缓存，开启后自动恢复
+        fake.power_off();
+        let error = selection.adapter().await.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::BluetoothUnavailable);
+        assert_eq!(
+            selection.select("hci0").await.unwrap_err().code(),
+            ErrorCode::BluetoothUnavailable
+        );
+        let status = selection.status().await;
+        assert!(!status.manager_available);
+        assert_eq!(status.error_code, Some(ErrorCode::BluetoothUnavailable));
+        assert!(status.error.unwrap().contains("org.bluez not found"));
+
+        fake.set(&[("hci0", Some(true)), ("hci1", Some(true))]);
+        assert_eq!(selection.adapter().await.unwrap().0, "hci0");
+        selection.select("hci1").await.unwrap();
+
+        // 所选适配器被拔出：报 AdapterNotFound，不回退到其他适配器
+        fake.set(&[("hci0", Some(true))]);
+        let status = selection.status().await;
+        assert!(status.manager_available);
+        assert_eq!((status.adapter_count, status.selected_adapter), (1, None));
+        assert_eq!(status.error_code, Some(ErrorCode::AdapterNotFound));
+        assert!(ids(&selection.list().await.unwrap())
+            .iter()
+            .all(|(_, selected)| !selected));
+    }
+}
@@ -2,22 +2,48 @@
 
 use anyhow::{anyhow, bail, Context};
 use btleplug::{
-    api::{Central, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType},
-    platform::{Adapter, Manager, Peripheral},
+    api::{
+        bleuuid::uuid_from_u16, Central, CentralEvent, Characteristic, Peripheral as _, ScanFilter,
+        WriteType,
+    },
+    platform::{Adapter, Peripheral},
 };
 use flume::Sender;
 use futures::StreamExt;
 use std::{
     collections::BTreeSet,
-    time::{Duration, Instant},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 use tauri::async_runtime::JoinHandle;
-use tokio::sync::OnceCell;
 
 use crate::{
-    imu::config::IMUConfig, processor::RawImuData, types::bluetooth::PeripheralInfo,
+    imu::{
+        adapter::{AdapterSelection, BtleplugAdapters},
+        config::IMUConfig,
+        connect_retry::{establish_link, release_link, ConnectRetryPolicy},
+        device_command::{to_hex, NotificationTap},
+        scan_freshness::{
+            is_fresh, rescan_after_failure, Discovery, PeripheralNotFound, PeripheralSightings,
+            PeripheralSightingsHandle, DEFAULT_FRESHNESS_WINDOW_MS, RESCAN_DURATION,
+        },
+    },
+    processor::{
+        stats::{host_now_ms, ProcessorStatsHandle},
+        upstream::{
+            PipelineChannelClosed, UpstreamForwarder, UpstreamHealth, UpstreamHealthHandle,
+            UpstreamHooks,
+        },
+        RawImuData,
+    },
+    types::bluetooth::{BluetoothAdapterInfo, BluetoothStatus, DeviceInformation, PeripheralInfo},
 };
 
+/// 上游通道关闭时的回调（推送 `pipeline_channel_closed` 事件）。
+pub type ChannelClosedCallback = Arc<dyn Fn(PipelineChannelClosed) + Send + Sync>;
+
 struct NeededCharacteristics {
     write_char: Characteristic,
     notify_char: Characteristic,
@@ -28,114 +54,174 @@ struct NeededCharacteristics {
 // IMU客户端
 // URL: https://www.yuque.com/cxqwork/lkw3sg/yqa3e0?#Phg5V
 // ===============================
-/// * `central`: 蓝牙主设备(本机)
+/// * `adapters`: 蓝牙主设备(本机)适配器的选择
 /// * `peripheral`: 目前连接上的设备
 /// * `chars`: 蓝牙特征
 /// * `tx`: 接收蓝牙数据包发给下游
 /// * `handle`: 接收蓝牙数据包的task的handle
+/// * `stats`: 运行统计，接收任务每秒写入一次输入速率，连接/断开时记录连接时长与重连次数
+/// * `sample_interval_ms`: 最近下发配置的标称采样间隔（f64 位模式），接收任务据此判定迟到的通知
+/// * `device_info`: 连接时从设备信息服务读到的版本信息
+/// * `notification_tap`: 截获原始设备命令的回复，挂在接收任务里、转发给处理器之前
+/// * `upstream`: 上游通道状态，接收任务发送失败时标记关闭，此后依赖数据流的命令报错
+/// * `on_channel_closed`: 上游通道关闭时的回调
+/// * `sightings`: 各设备最近一次被发现的时间，扫描期间由发现事件更新
+/// * `discovery_task`: 监听发现事件的task的handle，每次开始扫描时重开
+/// * `scanning`: 用户是否开启了扫描，连接失败后的重新扫描据此决定是否停止扫描
 pub struct IMUClient {
-    central: OnceCell<Adapter>,
+    adapters: AdapterSelection<BtleplugAdapters>,
     peripheral: Option<Peripheral>,
     chars: Option<NeededCharacteristics>,
     tx: Sender<RawImuData>,
     handle: Option<JoinHandle<()>>,
+    stats: ProcessorStatsHandle,
+    sample_interval_ms: Arc<AtomicU64>,
+    device_info: DeviceInformation,
+    notification_tap: NotificationTap,
+    upstream: UpstreamHealthHandle,
+    on_channel_closed: Option<ChannelClosedCallback>,
+    sightings: PeripheralSightingsHandle,
+    discovery_task: Mutex<Option<JoinHandle<()>>>,
+    scanning: AtomicBool,
 }
 
 impl IMUClient {
     /// 创建 IMU 客户端。
-    pub fn new(tx: Sender<RawImuData>) -> Self {
+    pub fn new(tx: Sender<RawImuData>, stats: ProcessorStatsHandle) -> Self {
         Self {
-            central: OnceCell::new(),
+            adapters: AdapterSelection::new(BtleplugAdapters),
             peripheral: None,
             chars: None,
             tx,
             handle: None,
+            stats,
+            sample_interval_ms: Arc::new(AtomicU64::new(
+                IMUConfig::default().sample_interval_ms().to_bits(),
+            )),
+            device_info: DeviceInformation::default(),
+            notification_tap: NotificationTap::default(),
+            upstream: UpstreamHealth::new_handle(),
+            on_channel_closed: None,
+            sightings: PeripheralSightings::new_handle(),
+            discovery_task: Mutex::new(None),
+            scanning: AtomicBool::new(false),
+        }
+    }
+
+    /// 设置上游通道关闭时的回调。
+    pub fn with_channel_closed_hook(mut self, hook: ChannelClosedCallback) -> Self {
+        self.on_channel_closed = Some(hook);
+        self
+    }
+
+    /// 换上新的上游通道发送端（处理管线重启后），不必重新连接蓝牙。
+    ///
+    /// 清除通道关闭状态；设备仍连接时以新的发送端重新开启接收任务，
+    /// 此前因通道关闭停止了上报的，再重新开启主动上报。
+    // 处理管线目前不会重启，尚无调用方
+    #[allow(dead_code)]
+    pub async fn replace_sender(&mut self, tx: Sender<RawImuData>) -> anyhow::Result<()> {
+        let was_closed = self.upstream.closed().is_some();
+        self.tx = tx;
+        self.upstream.reset();
+        if !self.is_connected() {
+            return Ok(());
+        }
+        let handle = self.spawn_forwarding().await?;
+        if let Some(last_handle) = self.handle.replace(handle) {
+            last_handle.abort();
+        }
+        if was_closed {
+            self.enable_data_reporting().await?;
         }
+        tracing::info!("上游通道已替换, 恢复转发IMU数据");
+        Ok(())
     }
 
-    /// 尝试获取蓝牙 central 设备(本机)
+    /// 尝试获取蓝牙 central 设备(本机)：用户指定的适配器，未指定时取第一个
     async fn central(&self) -> anyhow::Result<&Adapter> {
-        self.central
-            .get_or_try_init(async || -> anyhow::Result<Adapter> {
-                Manager::new()
-                    .await
-                    .context("没找到蓝牙Manager")?
-                    .adapters()
-                    .await
-                    .context("没找到蓝牙Adapters")?
-                    .into_iter()
-                    .next()
-                    .ok_or(anyhow!("没找到蓝牙Adapters"))
-            })
-            .await
+        Ok(self.adapters.adapter().await?)
+    }
+
+    /// 列出本机蓝牙适配器。
+    pub async fn list_adapters(&self) -> anyhow::Result<Vec<BluetoothAdapterInfo>> {
+        Ok(self.adapters.list().await?)
+    }
+
+    /// 指定之后扫描与连接使用的适配器，须先断开当前设备。
+    pub async fn select_adapter(&mut self, id: &str) -> anyhow::Result<BluetoothAdapterInfo> {
+        if self.is_connected() {
+            bail!("请先断开当前设备再切换蓝牙适配器");
+        }
+        // 旧适配器上可能仍在扫描
+        if let Ok(central) = self.central().await {
+            let _ = central.stop_scan().await;
+        }
+        let info = self.adapters.select(id).await?;
+        tracing::info!("Bluetooth adapter selected: {}", info.id);
+        Ok(info)
+    }
+
+    /// 蓝牙可用性诊断。
+    pub async fn bluetooth_status(&self) -> BluetoothStatus {
+        self.adapters.status().await
     }
 
     /// 连接指定 uuid 的设备。
     ///
     /// * `uuid`: 指定uuid
-    pub async fn connect(&mut self, uuid: &str) -> anyhow::Result<PeripheralInfo> {
-        let peripheral = match self.find_peripheral(uuid).await {
-            Ok(it) => it,
-            Err(e) => {
-                bail!("连接到设备时发生错误: {}", e)
-            }
-        };
-
-        peripheral.connect().await.context("连接到设备")?;
-        peripheral
-            .discover_services()
-            .await
-            .context("设备发现蓝牙服务")?;
-
-        // info!("设备发现蓝牙服务");
-        let characteristics = peripheral.characteristics();
-
-        fn get_char(
-            chars: &BTreeSet<Characteristic>,
-            service_uuid: &str,
-            uuid: &str,
-        ) -> Option<Characteristic> {
-            chars
-                .iter()
-                .find(|c| {
-                    c.service_uuid.to_string().contains(service_uuid)
-                        && c.uuid.to_string().contains(uuid)
-                })
-                .cloned()
+    ///
+    /// * `config`: 连接后写入设备的配置（上报频率等）
+    ///
+    /// * `retry`: 建立连接时的重试策略，见 [`establish_link`]
+    ///
+    /// 任何一步失败都会断开已建立的链路并清空连接状态，之后可直接重新连接。
+    ///
+    /// 本方法不检查当前是否已连接，应用内经 [`connect_exclusive`](super::connect_exclusive) 调用。
+    pub async fn connect(
+        &mut self,
+        uuid: &str,
+        config: &IMUConfig,
+        retry: &ConnectRetryPolicy,
+    ) -> anyhow::Result<PeripheralInfo> {
+        if self.tx.is_disconnected() {
+            bail!("处理管线已退出, 请重启处理管线后再连接设备");
         }
+        // 重新连接即重新接上发送端
+        self.upstream.reset();
+        let peripheral = self
+            .find_peripheral(uuid)
+            .await
+            .context("连接到设备时发生错误")?;
 
-        let write_char = get_char(&characteristics, "ae30", "ae01").ok_or(anyhow!(
-            "Write characteristic not found, 蓝牙设备非指定IMU?"
-        ))?;
-
-        let notify_char = get_char(&characteristics, "ae30", "ae02").ok_or(anyhow!(
-            "Notify characteristic not found, 蓝牙设备非指定IMU?"
-        ))?;
+        let chars = establish_link(&peripheral, retry, |peripheral| {
+            find_needed_characteristics(&peripheral.characteristics())
+        })
+        .await?;
 
-        let battery_char = get_char(&characteristics, "180f", "2a19").ok_or(anyhow!(
-            "battery characteristic not found, 蓝牙设备非指定IMU?"
-        ))?;
+        self.device_info =
+            read_device_information(&peripheral, &peripheral.characteristics()).await;
 
         self.peripheral = Some(peripheral.clone());
-        self.chars = Some(NeededCharacteristics {
-            write_char,
-            notify_char,
-            battery_char,
-        });
+        self.chars = Some(chars);
 
-        match self.init_peripheral().await {
+        match self.init_peripheral(config).await {
             Ok(handle) => {
                 if let Some(last_handle) = self.handle.take() {
                     // 先中止上一个任务
                     last_handle.abort();
                 }
-                self.handle = Some(handle)
+                self.handle = Some(handle);
+                self.stats.record_connect();
+                self.sightings.record(uuid, host_now_ms());
             }
             Err(e) => {
+                // 此时还没有接收任务，也没有记录连接，只需清空状态并断开链路
                 self.peripheral = None;
                 self.chars = None;
-                self.disconnect().await?;
-                return Err(e);
+                self.device_info = DeviceInformation::default();
+                release_link(&peripheral).await;
+                return Err(e.context("初始化设备"));
             }
         };
 
@@ -147,17 +233,26 @@ impl IMUClient {
     }
 
     /// 断开当前连接的设备。
+    ///
+    /// 关闭主动上报只是尽力而为：链路已断开时写不进去，仍然清空连接状态并断开链路，
+    /// 之后可以直接重新连接。
     pub async fn disconnect(&mut self) -> anyhow::Result<PeripheralInfo> {
-        self.disable_data_reporting().await?;
+        // 先停接收任务，关闭上报失败时也不会继续转发
         if let Some(handle) = self.handle.take() {
             handle.abort();
         }
+        if let Err(e) = self.disable_data_reporting().await {
+            tracing::warn!("断开前关闭主动上报失败, 继续断开: {:#}", e);
+        }
         if let Err(e) = self.tx.send_async(RawImuData::Reset).await {
             tracing::error!("下游通道已关闭, 无法发送重置信号: {}", e);
         };
 
+        self.device_info = DeviceInformation::default();
+        self.chars = None;
         match self.peripheral.take() {
             Some(p) => {
+                self.stats.record_disconnect();
                 p.disconnect().await.context("断开设备连接")?;
                 Ok(PeripheralInfo::from_peripheral(&p)
                     .await
@@ -169,9 +264,44 @@ impl IMUClient {
         }
     }
 
+    /// 是否已连接设备（不查询设备属性）。
+    pub fn is_connected(&self) -> bool {
+        self.peripheral.is_some()
+    }
+
+    /// 当前连接的设备 ID，未连接时为空。
+    pub fn connected_uuid(&self) -> Option<String> {
+        self.peripheral.as_ref().map(|p| p.id().to_string())
+    }
+
+    /// 当前设备的蓝牙链路是否仍然连着。
+    ///
+    /// 设备关机或超出范围后 `peripheral` 仍然保留，须向适配器查询链路状态。
+    pub async fn link_alive(&self) -> bool {
+        match &self.peripheral {
+            Some(p) => p.is_connected().await.unwrap_or(false),
+            None => false,
+        }
+    }
+
+    /// 当前连接的设备信息，未连接时为空。
+    pub async fn connected_peripheral(&self) -> Option<PeripheralInfo> {
+        let peripheral = self.peripheral.as_ref()?;
+        Some(
+            PeripheralInfo::from_peripheral(peripheral)
+                .await
+                .unwrap_or_default(),
+        )
+    }
+
+    /// 当前设备的版本信息，未连接或设备不提供设备信息服务时各字段为空。
+    pub fn device_information(&self) -> DeviceInformation {
+        self.device_info.clone()
+    }
+
     /// 初始化IMU设备的连接
     /// 内部开启一个tokio线程接收蓝牙数据包
-    async fn init_peripheral(&mut self) -> anyhow::Result<JoinHandle<()>> {
+    async fn init_peripheral(&mut self, config: &IMUConfig) -> anyhow::Result<JoinHandle<()>> {
         // 保持蓝牙连接
         self.keep_bluetooth_connection().await?;
 
@@ -179,7 +309,7 @@ impl IMUClient {
         self.enable_highspeed_communication().await?;
 
         // 配置IMU
-        self.set_config(&IMUConfig::default()).await?;
+        self.set_config(config).await?;
 
         // 订阅通知
         self.subscribe_nofitication().await?;
@@ -187,37 +317,36 @@ impl IMUClient {
         // 开启数据主动上报
         self.enable_data_reporting().await?;
 
-        let (peripheral, _) = self.assert_initialzation()?;
+        self.spawn_forwarding().await
+    }
+
+    /// 开启接收任务：把通知转发到上游通道，通道关闭时停止上报并推送事件。
+    async fn spawn_forwarding(&self) -> anyhow::Result<JoinHandle<()>> {
+        let (peripheral, chars) = self.assert_initialzation()?;
 
         // 接收通知
-        let mut notification_stream = peripheral.notifications().await?;
+        let notifications = peripheral
+            .notifications()
+            .await?
+            .map(|notification| notification.value);
 
-        let tx = self.tx.clone();
+        let forwarder = UpstreamForwarder {
+            tx: self.tx.clone(),
+            stats: self.stats.clone(),
+            sample_interval_ms: self.sample_interval_ms.clone(),
+            health: self.upstream.clone(),
+        };
+        let hooks = ClientUpstreamHooks {
+            peripheral: peripheral.clone(),
+            write_char: chars.write_char.clone(),
+            on_closed: self.on_channel_closed.clone(),
+        };
+        let notification_tap = self.notification_tap.clone();
         let handle = tauri::async_runtime::spawn(async move {
-            let mut msg_count = 0;
-            let mut last_report = Instant::now();
-            while let Some(data) = notification_stream.next().await {
-                match tx.send_async(RawImuData::Packet(data.value)).await {
-                    Ok(_) => {}
-                    // 当且仅当所有Receiver被drop时返回error
-                    Err(e) => {
-                        tracing::error!("下游通道已关闭, 停止接收IMU数据: {}", e);
-                    }
-                }
-
-                msg_count += 1;
-                let elapsed = last_report.elapsed();
-                if elapsed > Duration::from_secs(1) {
-                    let elapsed_secs = elapsed.as_secs_f64();
-                    let throughput = msg_count as f64 / elapsed_secs;
-
-                    tracing::debug!(throughput, msg_count, elapsed_secs, "处理速率报告");
-
-                    // 重置计数器和计时器
-                    msg_count = 0;
-                    last_report = Instant::now();
-                }
-            }
+            // 原始命令的回复交给等待方，不计入输入速率，也不进处理器
+            forwarder
+                .run(notifications, |value| notification_tap.offer(value), &hooks)
+                .await;
         });
 
         Ok(handle)
@@ -232,12 +361,20 @@ impl IMUClient {
                 return Ok(p);
             }
         }
-        Err(anyhow!("Device not found"))
+        Err(PeripheralNotFound(target_uuid.to_string()).into())
     }
 
     /// 列举central中的peripheral
     /// 获取扫描到的设备列表。
-    pub async fn list_peripherals(&self) -> anyhow::Result<Vec<PeripheralInfo>> {
+    ///
+    /// * `max_age_ms`: 新鲜度窗口，超过这么久没被发现的设备不列出（正连接的设备除外），
+    ///   为空时取 [`DEFAULT_FRESHNESS_WINDOW_MS`]
+    ///
+    /// 读取单个设备信息失败时跳过该设备，并在返回的警告列表中说明。
+    pub async fn list_peripherals(
+        &self,
+        max_age_ms: Option<u64>,
+    ) -> anyhow::Result<(Vec<PeripheralInfo>, Vec<String>)> {
         let peripherals = self
             .central()
             .await?
@@ -245,30 +382,120 @@ impl IMUClient {
             .await
             .context("列举蓝牙从设备")?;
 
-        Ok(futures::stream::iter(peripherals)
-            .then(
-                async move |p| match PeripheralInfo::from_peripheral(&p).await {
-                    Ok(info) => {
-                        let local_name = info.local_name.clone();
-                        if local_name.is_some_and(|name| name != "Unknown") {
-                            Some(info)
-                        } else {
-                            None
-                        }
-                    }
-                    Err(e) => {
-                        // HACK
-                        tracing::warn!("fail to get PeripheralInfo : {}", e);
-                        None
+        let window = max_age_ms.unwrap_or(DEFAULT_FRESHNESS_WINDOW_MS);
+        let connected = self.connected_uuid();
+        let now = host_now_ms();
+        let mut infos = Vec::new();
+        let mut warnings = Vec::new();
+        for p in peripherals {
+            let id = p.id().to_string();
+            let last_seen_ms_ago = self.sightings.last_seen_ms_ago(&id, now);
+            if !is_fresh(last_seen_ms_ago, window, connected.as_deref() == Some(&*id)) {
+                continue;
+            }
+            match PeripheralInfo::from_peripheral(&p).await {
+                Ok(mut info) => {
+                    if info.local_name.as_ref().is_some_and(|name| name != "Unknown") {
+                        info.last_seen_ms_ago = last_seen_ms_ago;
+                        infos.push(info);
                     }
-                },
-            )
-            .filter_map(async move |x| x)
-            .collect::<Vec<_>>()
-            .await)
+                }
+                Err(e) => {
+                    tracing::warn!("fail to get PeripheralInfo : {:#}", e);
+                    warnings.push(format!("读取设备 {} 信息失败: {:#}", p.id(), e));
+                }
+            }
+        }
+        Ok((infos, warnings))
+    }
+
+    /// 取出连接失败后重新扫描所需的适配器句柄与发现记录。
+    ///
+    /// 重新扫描要持续数秒，调用方可以先释放客户端锁，再调用 [`FailureRescan::explain`]。
+    pub async fn failure_rescan(&self) -> FailureRescan {
+        FailureRescan {
+            central: self.central().await.ok().cloned(),
+            sightings: self.sightings.clone(),
+            was_scanning: self.scanning.load(Ordering::Relaxed),
+            discovery_task: Mutex::new(None),
+        }
+    }
+}
+
+/// 连接失败后的重新扫描，只持有适配器句柄，不占用客户端。
+pub struct FailureRescan {
+    central: Option<Adapter>,
+    sightings: PeripheralSightingsHandle,
+    was_scanning: bool,
+    discovery_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FailureRescan {
+    /// 连接失败后说明原因：设备不可达时短暂重新扫描，错误中注明设备是否被重新发现。
+    ///
+    /// 见 [`rescan_after_failure`]。
+    pub async fn explain(&self, uuid: &str, error: anyhow::Error) -> anyhow::Error {
+        rescan_after_failure(
+            self,
+            &self.sightings,
+            uuid,
+            error,
+            RESCAN_DURATION,
+            self.was_scanning,
+            host_now_ms,
+        )
+        .await
+    }
+
+    fn central(&self) -> anyhow::Result<&Adapter> {
+        self.central.as_ref().context("没有可用的蓝牙适配器")
+    }
+
+    fn lock_discovery_task(&self) -> std::sync::MutexGuard<'_, Option<JoinHandle<()>>> {
+        self.discovery_task
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 }
 
+impl Discovery for FailureRescan {
+    async fn start_scan(&self) -> anyhow::Result<()> {
+        let central = self.central()?;
+        let task = record_sightings(central, self.sightings.clone()).await?;
+        if let Some(last_task) = self.lock_discovery_task().replace(task) {
+            last_task.abort();
+        }
+        Ok(central.start_scan(ScanFilter::default()).await?)
+    }
+
+    async fn stop_scan(&self) -> anyhow::Result<()> {
+        Ok(self.central()?.stop_scan().await?)
+    }
+}
+
+impl Drop for FailureRescan {
+    fn drop(&mut self) {
+        if let Some(task) = self.lock_discovery_task().take() {
+            task.abort();
+        }
+    }
+}
+
+/// 开启监听发现事件的任务，把发现、广播更新的设备记入发现记录。
+async fn record_sightings(
+    central: &Adapter,
+    sightings: PeripheralSightingsHandle,
+) -> anyhow::Result<JoinHandle<()>> {
+    let mut events = central.events().await.context("订阅蓝牙发现事件")?;
+    Ok(tauri::async_runtime::spawn(async move {
+        while let Some(event) = events.next().await {
+            if let CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id) = event {
+                sightings.record(&id.to_string(), host_now_ms());
+            }
+        }
+    }))
+}
+
 /// 低级蓝牙功能
 impl IMUClient {
     /// assert 与 IMU948 相关的 Peripheral 和特征初始化成功
@@ -286,19 +513,31 @@ impl IMUClient {
     }
 
     /// 开始扫描设备。
+    ///
+    /// 同时开启监听发现事件的任务，把发现、广播更新的设备记入发现记录。
     pub async fn start_scan(&self) -> anyhow::Result<()> {
-        Ok(self
-            .central()
-            .await?
-            .start_scan(ScanFilter::default())
-            .await?)
+        let central = self.central().await?;
+        let task = record_sightings(central, self.sightings.clone()).await?;
+        if let Some(last_task) = self.lock_discovery_task().replace(task) {
+            last_task.abort();
+        }
+        central.start_scan(ScanFilter::default()).await?;
+        self.scanning.store(true, Ordering::Relaxed);
+        Ok(())
     }
 
     /// 停止扫描设备。
     pub async fn stop_scan(&self) -> anyhow::Result<()> {
+        self.scanning.store(false, Ordering::Relaxed);
         Ok(self.central().await?.stop_scan().await?)
     }
 
+    fn lock_discovery_task(&self) -> std::sync::MutexGuard<'_, Option<JoinHandle<()>>> {
+        self.discovery_task
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     /// 以无回复方式写数据的工具函数
     ///
     /// * `data`: 要写入的二进制数据
@@ -312,7 +551,42 @@ impl IMUClient {
         Ok(())
     }
 
+    /// 原样写入一条原始设备命令，并武装通知分流以截获随后的回复。
+    ///
+    /// 长度与启用开关由调用方按 [`validate_device_command`](crate::imu::validate_device_command) 校验。
+    pub async fn write_device_command(&self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.upstream.ensure_open()?;
+        tracing::info!(bytes = %to_hex(bytes), "写入原始设备命令");
+        self.notification_tap.arm();
+        self.write_no_response(bytes)
+            .await
+            .context("写入原始设备命令")
+    }
+
+    /// 原始设备命令回复的通知分流。
+    pub fn notification_tap(&self) -> NotificationTap {
+        self.notification_tap.clone()
+    }
+
     /// 保持蓝牙连接
+    /// 重新下发初始化序列：设备重启后固件设置恢复默认，但蓝牙连接与通知订阅仍在。
+    ///
+    /// `enable_reporting = false` 时不开启主动上报（空闲模式下保持停止）。
+    pub async fn reinitialize(
+        &self,
+        config: &IMUConfig,
+        enable_reporting: bool,
+    ) -> anyhow::Result<()> {
+        self.upstream.ensure_open()?;
+        self.keep_bluetooth_connection().await?;
+        self.enable_highspeed_communication().await?;
+        self.set_config(config).await?;
+        if enable_reporting {
+            self.enable_data_reporting().await?;
+        }
+        Ok(())
+    }
+
     async fn keep_bluetooth_connection(&self) -> anyhow::Result<()> {
         self.write_no_response(&[0x29]).await
     }
@@ -320,19 +594,23 @@ impl IMUClient {
     /// 向IMU写入配置项
     ///
     /// * `config`: IMU配置
-    async fn set_config(&self, config: &IMUConfig) -> anyhow::Result<()> {
-        self.write_no_response(&config.to_bytes()).await
+    pub async fn set_config(&self, config: &IMUConfig) -> anyhow::Result<()> {
+        self.write_no_response(&config.to_bytes()).await?;
+        self.sample_interval_ms
+            .store(config.sample_interval_ms().to_bits(), Ordering::Relaxed);
+        Ok(())
     }
 
     /// 停止数据主动上报
-    async fn disable_data_reporting(&self) -> anyhow::Result<()> {
+    pub async fn disable_data_reporting(&self) -> anyhow::Result<()> {
         self.write_no_response(&[0x18])
             .await
             .context("停止数据主动上报")
     }
 
     /// 开启数据主动上报
-    async fn enable_data_reporting(&self) -> anyhow::Result<()> {
+    pub async fn enable_data_reporting(&self) -> anyhow::Result<()> {
+        self.upstream.ensure_open()?;
         self.write_no_response(&[0x19])
             .await
             .context("开启数据主动上报")
@@ -368,3 +646,83 @@ impl IMUClient {
             .context("开启蓝牙高速通信特征")
     }
 }
+
+/// 接收任务在上游通道关闭时的动作：直接写设备停止上报，不经过客户端（命令此时已报错）。
+struct ClientUpstreamHooks {
+    peripheral: Peripheral,
+    write_char: Characteristic,
+    on_closed: Option<ChannelClosedCallback>,
+}
+
+impl UpstreamHooks for ClientUpstreamHooks {
+    async fn stop_reporting(&self) -> anyhow::Result<()> {
+        self.peripheral
+            .write(&self.write_char, &[0x18], WriteType::WithoutResponse)
+            .await
+            .context("停止数据主动上报")
+    }
+
+    fn emit_closed(&self, closed: PipelineChannelClosed) {
+        if let Some(on_closed) = &self.on_closed {
+            on_closed(closed);
+        }
+    }
+}
+
+/// 查找 IMU 所需的特征，缺少任何一个都说明不是指定的设备。
+fn find_needed_characteristics(
+    characteristics: &BTreeSet<Characteristic>,
+) -> Result<NeededCharacteristics, String> {
+    fn get_char(
+        chars: &BTreeSet<Characteristic>,
+        service_uuid: &str,
+        uuid: &str,
+        name: &str,
+    ) -> Result<Characteristic, String> {
+        chars
+            .iter()
+            .find(|c| {
+                c.service_uuid.to_string().contains(service_uuid)
+                    && c.uuid.to_string().contains(uuid)
+            })
+            .cloned()
+            .ok_or_else(|| format!("{name} characteristic not found, 蓝牙设备非指定IMU?"))
+    }
+
+    Ok(NeededCharacteristics {
+        write_char: get_char(characteristics, "ae30", "ae01", "Write")?,
+        notify_char: get_char(characteristics, "ae30", "ae02", "Notify")?,
+        battery_char: get_char(characteristics, "180f", "2a19", "battery")?,
+    })
+}
+
+/// 读取设备信息服务（0x180A）中的版本特征。
+///
+/// 该服务不是 IMU 协议的一部分，缺少服务或单个特征读取失败都不影响连接，对应字段留空。
+async fn read_device_information(
+    peripheral: &Peripheral,
+    characteristics: &BTreeSet<Characteristic>,
+) -> DeviceInformation {
+    let service_uuid = uuid_from_u16(DeviceInformation::SERVICE_UUID16);
+    let mut reads = Vec::new();
+    for uuid16 in DeviceInformation::CHARACTERISTIC_UUID16S {
+        let uuid = uuid_from_u16(uuid16);
+        let Some(characteristic) = characteristics
+            .iter()
+            .find(|c| c.service_uuid == service_uuid && c.uuid == uuid)
+        else {
+            continue;
+        };
+        match peripheral.read(characteristic).await {
+            Ok(value) => reads.push((uuid16, value)),
+            Err(e) => tracing::warn!("读取设备信息特征 {uuid16:#06x} 失败: {e}"),
+        }
+    }
+    let info = DeviceInformation::from_reads(reads);
+    if info.is_empty() {
+        tracing::info!("设备未提供设备信息服务");
+    } else {
+        tracing::info!(?info, "设备信息");
+    }
+    info
+}
@@ -1,6 +1,9 @@
 //! IMU 设备配置与协议构建。
 
+use crate::processor::shared::{nominal_sample_interval_ms, DEFAULT_REPORT_RATE_HZ};
+
 /// IMU 配置参数集合。
+#[derive(Debug, Clone)]
 pub struct IMUConfig {
     /// 惯导静止状态加速度阈值 (单位 dm/s²)
     ///
@@ -76,7 +79,8 @@ pub struct IMUConfig {
     /// - ❌ 运动检测
     /// - ❌ AD1 / GPIO1
     ///
-    /// ** 目前不支持修改默认订阅 **
+    /// 用 [`with_subscription`](Self::with_subscription) 增减订阅；解析器只接受
+    /// 默认订阅加可选的气压计，其余组合会导致数据包解析失败。
     subscriptions: SubscriptionFlags,
 }
 
@@ -87,7 +91,7 @@ impl Default for IMUConfig {
             zero_velocity_mode: 255,
             dynamic_zero_speed: 0,
             sensor_mode: SensorMode::new(false, 2),
-            report_rate: 250,
+            report_rate: DEFAULT_REPORT_RATE_HZ,
             gyro_filter: FilterLevel(1),
             accel_filter: FilterLevel(3),
             mag_filter: FilterLevel(5),
@@ -97,6 +101,25 @@ impl Default for IMUConfig {
 }
 
 impl IMUConfig {
+    /// 默认配置，仅替换上报频率。
+    pub fn with_report_rate(report_rate: u8) -> Self {
+        Self {
+            report_rate,
+            ..Self::default()
+        }
+    }
+
+    /// 开启或关闭一项功能订阅。
+    pub fn with_subscription(mut self, subscription: Subscription, enabled: bool) -> Self {
+        self.subscriptions.set(subscription.flag(), enabled);
+        self
+    }
+
+    /// 按上报频率换算的标称采样间隔 (ms)。
+    pub fn sample_interval_ms(&self) -> f64 {
+        nominal_sample_interval_ms(self.report_rate)
+    }
+
     /// 序列化为设备配置字节。
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = vec![0u8; 11];
@@ -141,25 +164,37 @@ impl SubscriptionFlags {
 }
 
 #[derive(Debug, Clone, Copy)]
-#[allow(unused)]
-/// 目前暂不允许修改订阅
-enum Subscription {
+// 变体与协议表的 bit 一一对应，目前只有气压计按设置增减订阅
+#[allow(dead_code)]
+/// 可订阅的功能数据，对应 `Cmd_ReportTag` 的各个 bit。
+pub enum Subscription {
+    /// 无重力加速度 (bit 0)
     AccelerationWithoutGravity,
+    /// 含重力加速度 (bit 1)
     AccelerationWithGravity,
+    /// 角速度 (bit 2)
     Gyroscope,
+    /// 磁场 (bit 3)
     Magnetometer,
+    /// 气压计温度/气压/高度 (bit 4)
     Barometer,
+    /// 四元数 (bit 5)
     Quaternion,
+    /// 欧拉角 (bit 6)
     EulerAngles,
+    /// 惯导三维位置 (bit 7)
     Position,
+    /// 活动检测 (bit 8)
     Activity,
+    /// 导航系加速度 (bit 9)
     NavigationAcceleration,
+    /// AD1 电压 (bit 10)
     ADC1,
+    /// GPIO1 电平 (bit 11)
     GPIO1,
 }
 
 impl Subscription {
-    #[allow(unused)]
     fn flag(&self) -> SubscriptionFlags {
         match self {
             Self::AccelerationWithoutGravity => SubscriptionFlags::ACC_NO_GRAVITY,
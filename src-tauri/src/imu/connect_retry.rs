@@ -0,0 +1,426 @@
+//! 建立蓝牙连接时的重试策略。
+//!
+//! macOS 上扫描刚结束就连接，常遇到 `le-connection-abort-by-local` 一类的瞬时失败，
+//! 再连一次就好。这里把"连接、发现服务、查找特征"作为一次尝试：瞬时错误（连接中断、
+//! 超时）按退避间隔重试，永久错误（设备不存在、缺少特征、权限不足）立即返回。
+//! 每次尝试失败后，若链路已经建立就先断开，保证下一次尝试与之后的手动重试都从干净状态开始。
+//! 最终错误附带全部尝试的记录。
+
+use std::{fmt, future::Future, time::Duration};
+
+use btleplug::platform::Peripheral;
+
+/// 默认最多尝试次数。
+pub const DEFAULT_CONNECT_ATTEMPTS: u32 = 3;
+/// 默认退避间隔，第 n 次失败后等待 n 倍。
+pub const DEFAULT_CONNECT_BACKOFF: Duration = Duration::from_millis(300);
+
+/// 连接重试策略。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectRetryPolicy {
+    /// 最多尝试次数（至少 1 次）。
+    pub max_attempts: u32,
+    /// 退避间隔。
+    pub backoff: Duration,
+}
+
+impl Default for ConnectRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            backoff: DEFAULT_CONNECT_BACKOFF,
+        }
+    }
+}
+
+impl ConnectRetryPolicy {
+    /// 指定最多尝试次数，0 按 1 处理。
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// 第 `attempt` 次（从 1 开始）失败后的等待时长。
+    pub fn backoff_after(&self, attempt: u32) -> Duration {
+        self.backoff * attempt
+    }
+}
+
+/// 一次尝试失败在哪一步。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStage {
+    /// 建立链路。
+    Connect,
+    /// 发现蓝牙服务。
+    DiscoverServices,
+    /// 查找所需特征。
+    Characteristics,
+}
+
+impl fmt::Display for ConnectStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConnectStage::Connect => "连接",
+            ConnectStage::DiscoverServices => "发现服务",
+            ConnectStage::Characteristics => "查找特征",
+        })
+    }
+}
+
+/// 一次失败的尝试。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectAttempt {
+    /// 第几次尝试（从 1 开始）。
+    pub attempt: u32,
+    /// 失败的步骤。
+    pub stage: ConnectStage,
+    /// 错误描述。
+    pub error: String,
+    /// 是否判定为瞬时错误。
+    pub transient: bool,
+}
+
+/// 重试后仍未连上。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("连接到设备失败（尝试 {} 次）: {}", .attempts.len(), format_attempts(.attempts))]
+pub struct ConnectError {
+    /// 全部尝试的记录，最后一条是最终的错误。
+    pub attempts: Vec<ConnectAttempt>,
+}
+
+fn format_attempts(attempts: &[ConnectAttempt]) -> String {
+    attempts
+        .iter()
+        .map(|attempt| {
+            format!(
+                "第 {} 次{}失败（{}）: {}",
+                attempt.attempt,
+                attempt.stage,
+                if attempt.transient {
+                    "瞬时"
+                } else {
+                    "永久"
+                },
+                attempt.error
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// 判定 btleplug 错误是否值得重试。
+///
+/// 各平台后端把底层错误包成 `RuntimeError` / `Other` 字符串，只能按描述判断；
+/// 认不出的一律按永久错误处理，避免对配置类错误反复重试。
+pub fn is_transient(error: &btleplug::Error) -> bool {
+    match error {
+        btleplug::Error::TimedOut(_) | btleplug::Error::NotConnected => true,
+        btleplug::Error::RuntimeError(message) => is_transient_message(message),
+        btleplug::Error::Other(source) => is_transient_message(&source.to_string()),
+        _ => false,
+    }
+}
+
+fn is_transient_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    [
+        "abort",
+        "timed out",
+        "timeout",
+        "in progress",
+        "busy",
+        "reset by",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// 可建立连接的外设，测试中以脚本化的假实现替换 btleplug。
+pub trait LinkPeripheral {
+    /// 建立链路。
+    fn connect(&self) -> impl Future<Output = Result<(), btleplug::Error>> + Send;
+    /// 发现服务。
+    fn discover_services(&self) -> impl Future<Output = Result<(), btleplug::Error>> + Send;
+    /// 链路是否仍然建立。
+    fn is_connected(&self) -> impl Future<Output = Result<bool, btleplug::Error>> + Send;
+    /// 断开链路。
+    fn disconnect(&self) -> impl Future<Output = Result<(), btleplug::Error>> + Send;
+}
+
+impl LinkPeripheral for Peripheral {
+    async fn connect(&self) -> Result<(), btleplug::Error> {
+        btleplug::api::Peripheral::connect(self).await
+    }
+
+    async fn discover_services(&self) -> Result<(), btleplug::Error> {
+        btleplug::api::Peripheral::discover_services(self).await
+    }
+
+    async fn is_connected(&self) -> Result<bool, btleplug::Error> {
+        btleplug::api::Peripheral::is_connected(self).await
+    }
+
+    async fn disconnect(&self) -> Result<(), btleplug::Error> {
+        btleplug::api::Peripheral::disconnect(self).await
+    }
+}
+
+/// 按 `policy` 建立连接，成功后返回 `lookup` 从外设上取到的结果（所需特征）。
+///
+/// `lookup` 返回 `Err` 表示设备不是所需的型号，属于永久错误。
+/// 每次尝试失败后若链路仍建立则断开（断开失败只记录日志）。
+pub async fn establish_link<P, T>(
+    peripheral: &P,
+    policy: &ConnectRetryPolicy,
+    mut lookup: impl FnMut(&P) -> Result<T, String>,
+) -> Result<T, ConnectError>
+where
+    P: LinkPeripheral,
+{
+    let mut attempts = Vec::new();
+    for attempt in 1..=policy.max_attempts.max(1) {
+        let (stage, error, transient) = match try_link(peripheral, &mut lookup).await {
+            Ok(found) => {
+                if attempt > 1 {
+                    tracing::info!("第 {} 次尝试连接成功", attempt);
+                }
+                return Ok(found);
+            }
+            Err(failure) => failure,
+        };
+        release_link(peripheral).await;
+        tracing::warn!("第 {} 次{}失败: {}", attempt, stage, error);
+        attempts.push(ConnectAttempt {
+            attempt,
+            stage,
+            error,
+            transient,
+        });
+        if !transient || attempt == policy.max_attempts {
+            break;
+        }
+        tokio::time::sleep(policy.backoff_after(attempt)).await;
+    }
+    Err(ConnectError { attempts })
+}
+
+async fn try_link<P: LinkPeripheral, T>(
+    peripheral: &P,
+    lookup: &mut impl FnMut(&P) -> Result<T, String>,
+) -> Result<T, (ConnectStage, String, bool)> {
+    let stage_error =
+        |stage, error: btleplug::Error| (stage, error.to_string(), is_transient(&error));
+    peripheral
+        .connect()
+        .await
+        .map_err(|e| stage_error(ConnectStage::Connect, e))?;
+    peripheral
+        .discover_services()
+        .await
+        .map_err(|e| stage_error(ConnectStage::DiscoverServices, e))?;
+    lookup(peripheral).map_err(|error| (ConnectStage::Characteristics, error, false))
+}
+
+/// 尝试失败后断开已建立的链路。
+pub async fn release_link<P: LinkPeripheral>(peripheral: &P) {
+    if !matches!(peripheral.is_connected().await, Ok(true)) {
+        return;
+    }
+    if let Err(e) = peripheral.disconnect().await {
+        tracing::warn!("断开未完成的连接失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Mutex,
+        },
+    };
+
+    use super::*;
+
+    /// 按脚本依次返回结果的假外设，记录链路状态与断开次数。
+    #[derive(Default)]
+    struct ScriptedPeripheral {
+        connect: Mutex<VecDeque<Result<(), btleplug::Error>>>,
+        discover: Mutex<VecDeque<Result<(), btleplug::Error>>>,
+        connected: AtomicBool,
+        disconnects: AtomicUsize,
+    }
+
+    impl ScriptedPeripheral {
+        fn new(
+            connect: Vec<Result<(), btleplug::Error>>,
+            discover: Vec<Result<(), btleplug::Error>>,
+        ) -> Self {
+            Self {
+                connect: Mutex::new(connect.into()),
+                discover: Mutex::new(discover.into()),
+                ..Default::default()
+            }
+        }
+
+        fn connected(&self) -> bool {
+            self.connected.load(Ordering::Relaxed)
+        }
+
+        fn disconnects(&self) -> usize {
+            self.disconnects.load(Ordering::Relaxed)
+        }
+    }
+
+    impl LinkPeripheral for ScriptedPeripheral {
+        async fn connect(&self) -> Result<(), btleplug::Error> {
+            let result = self.connect.lock().unwrap().pop_front().unwrap_or(Ok(()));
+            if result.is_ok() {
+                self.connected.store(true, Ordering::Relaxed);
+            }
+            result
+        }
+
+        async fn discover_services(&self) -> Result<(), btleplug::Error> {
+            self.discover.lock().unwrap().pop_front().unwrap_or(Ok(()))
+        }
+
+        async fn is_connected(&self) -> Result<bool, btleplug::Error> {
+            Ok(self.connected())
+        }
+
+        async fn disconnect(&self) -> Result<(), btleplug::Error> {
+            self.disconnects.fetch_add(1, Ordering::Relaxed);
+            self.connected.store(false, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn policy() -> ConnectRetryPolicy {
+        ConnectRetryPolicy {
+            max_attempts: 3,
+            backoff: Duration::ZERO,
+        }
+    }
+
+    fn aborted() -> btleplug::Error {
+        btleplug::Error::Other("le-connection-abort-by-local".into())
+    }
+
+    #[test]
+    fn classifies_transient_and_permanent_errors() {
+        assert!(is_transient(&aborted()));
+        assert!(is_transient(&btleplug::Error::TimedOut(
+            Duration::from_secs(5)
+        )));
+        assert!(is_transient(&btleplug::Error::NotConnected));
+        assert!(is_transient(&btleplug::Error::RuntimeError(
+            "Operation already in progress".into()
+        )));
+        assert!(!is_transient(&btleplug::Error::DeviceNotFound));
+        assert!(!is_transient(&btleplug::Error::PermissionDenied));
+        assert!(!is_transient(&btleplug::Error::NoSuchCharacteristic));
+        assert!(!is_transient(&btleplug::Error::RuntimeError(
+            "org.bluez.Error.NotAvailable".into()
+        )));
+        assert_eq!(ConnectRetryPolicy::default().max_attempts, 3);
+        assert_eq!(
+            ConnectRetryPolicy::default()
+                .with_max_attempts(0)
+                .max_attempts,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn transient_connect_failures_are_retried_until_success() {
+        let peripheral = ScriptedPeripheral::new(
+            vec![
+                Err(aborted()),
+                Err(btleplug::Error::TimedOut(Duration::from_secs(1))),
+            ],
+            vec![],
+        );
+        let found = establish_link(&peripheral, &policy(), |_| Ok("chars"))
+            .await
+            .unwrap();
+        assert_eq!(found, "chars");
+        assert!(peripheral.connected());
+        // 链路没建立起来的尝试不需要断开
+        assert_eq!(peripheral.disconnects(), 0);
+    }
+
+    #[tokio::test]
+    async fn discovery_failure_disconnects_before_retrying() {
+        let peripheral = ScriptedPeripheral::new(vec![], vec![Err(aborted())]);
+        establish_link(&peripheral, &policy(), |_| Ok(()))
+            .await
+            .unwrap();
+        assert!(peripheral.connected());
+        assert_eq!(peripheral.disconnects(), 1);
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_stop_immediately_and_leave_link_released() {
+        // 设备不存在：不重试
+        let peripheral =
+            ScriptedPeripheral::new(vec![Err(btleplug::Error::DeviceNotFound)], vec![]);
+        let error = establish_link(&peripheral, &policy(), |_| Ok(()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.attempts.len(), 1);
+        assert_eq!(error.attempts[0].stage, ConnectStage::Connect);
+        assert!(!error.attempts[0].transient);
+        assert!(!peripheral.connected());
+
+        // 缺少特征：连上后断开，不重试
+        let peripheral = ScriptedPeripheral::new(vec![], vec![]);
+        let mut lookups = 0;
+        let error = establish_link(&peripheral, &policy(), |_| {
+            lookups += 1;
+            Err::<(), _>("Write characteristic not found".to_string())
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(lookups, 1);
+        assert_eq!(error.attempts[0].stage, ConnectStage::Characteristics);
+        assert!(!peripheral.connected());
+        assert_eq!(peripheral.disconnects(), 1);
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_report_every_attempt() {
+        let peripheral = ScriptedPeripheral::new(
+            vec![Ok(()), Err(aborted()), Ok(())],
+            vec![
+                Err(btleplug::Error::TimedOut(Duration::from_secs(1))),
+                Err(aborted()),
+            ],
+        );
+        let error = establish_link(&peripheral, &policy(), |_| Ok(()))
+            .await
+            .unwrap_err();
+        let stages: Vec<_> = error
+            .attempts
+            .iter()
+            .map(|attempt| (attempt.attempt, attempt.stage))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                (1, ConnectStage::DiscoverServices),
+                (2, ConnectStage::Connect),
+                (3, ConnectStage::DiscoverServices),
+            ]
+        );
+        assert!(error.attempts.iter().all(|attempt| attempt.transient));
+        // 每次发现服务失败都断开了链路
+        assert!(!peripheral.connected());
+        assert_eq!(peripheral.disconnects(), 2);
+
+        let message = error.to_string();
+        assert!(message.contains("尝试 3 次"), "{message}");
+        assert!(message.contains("第 2 次连接失败（瞬时）: le-connection-abort-by-local"));
+    }
+}
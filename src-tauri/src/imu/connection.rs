@@ -0,0 +1,282 @@
+//! 串行、幂等的设备连接。
+//!
+//! 连接按钮被双击时会先后发出两次 `connect_peripheral`。调用方须在持有客户端锁的情况下
+//! 调用 [`connect_exclusive`]，第二次请求排在第一次之后：设备已连上则直接返回当前设备信息，
+//! 不再重新初始化，也就不会多出一个接收任务把同一批数据包转发两遍。
+//! 连接另一台设备时先断开当前设备，保证任何时候只有一个接收任务。
+
+use std::future::Future;
+
+use anyhow::Context;
+
+use crate::{
+    imu::{config::IMUConfig, connect_retry::ConnectRetryPolicy, IMUClient},
+    types::bluetooth::PeripheralInfo,
+};
+
+/// 可连接、断开设备的客户端，测试中以假实现替换 [`IMUClient`]。
+pub trait DeviceConnection {
+    /// 连接后写入设备的配置。
+    type Config: Sync;
+
+    /// 当前连接的设备 ID，未连接时为 `None`。
+    fn connected_uuid(&self) -> Option<String>;
+    /// 当前设备的链路是否仍然连着（设备关机、超出范围后为假）。
+    fn link_alive(&self) -> impl Future<Output = bool> + Send;
+    /// 当前连接的设备信息。
+    fn connected_peripheral(&self) -> impl Future<Output = Option<PeripheralInfo>> + Send;
+    /// 连接设备并开启接收任务。
+    fn connect(
+        &mut self,
+        uuid: &str,
+        config: &Self::Config,
+        retry: &ConnectRetryPolicy,
+    ) -> impl Future<Output = anyhow::Result<PeripheralInfo>> + Send;
+    /// 断开当前设备并停止接收任务。
+    fn disconnect(&mut self) -> impl Future<Output = anyhow::Result<PeripheralInfo>> + Send;
+}
+
+impl DeviceConnection for IMUClient {
+    type Config = IMUConfig;
+
+    fn connected_uuid(&self) -> Option<String> {
+        IMUClient::connected_uuid(self)
+    }
+
+    async fn link_alive(&self) -> bool {
+        IMUClient::link_alive(self).await
+    }
+
+    async fn connected_peripheral(&self) -> Option<PeripheralInfo> {
+        IMUClient::connected_peripheral(self).await
+    }
+
+    async fn connect(
+        &mut self,
+        uuid: &str,
+        config: &IMUConfig,
+        retry: &ConnectRetryPolicy,
+    ) -> anyhow::Result<PeripheralInfo> {
+        IMUClient::connect(self, uuid, config, retry).await
+    }
+
+    async fn disconnect(&mut self) -> anyhow::Result<PeripheralInfo> {
+        IMUClient::disconnect(self).await
+    }
+}
+
+/// [`connect_exclusive`] 的结果。
+#[derive(Debug)]
+pub enum ConnectOutcome<T> {
+    /// 已连接到同一设备，沿用现有连接。
+    Reused(PeripheralInfo),
+    /// 新建立了连接，附带写入设备的配置。
+    Connected {
+        /// 设备信息。
+        info: PeripheralInfo,
+        /// 连接时写入设备的配置。
+        config: T,
+    },
+}
+
+/// 连接 `uuid`：已连上同一设备时直接返回，连着其他设备时先断开。
+///
+/// 记录的设备链路已断开（设备关机、超出范围）时不沿用，先清理残留的连接状态再重新连接，
+/// 清理失败只记录日志。
+///
+/// `prepare` 只在确实需要新建连接时才执行（切换标定档案、生成设备配置），
+/// 沿用现有连接时不会被轮询。断开旧设备失败时不再尝试连接新设备。
+/// 调用方须在整个过程中持有客户端锁，否则重叠的请求仍会各自建立连接。
+pub async fn connect_exclusive<C: DeviceConnection>(
+    client: &mut C,
+    uuid: &str,
+    retry: &ConnectRetryPolicy,
+    prepare: impl Future<Output = anyhow::Result<C::Config>>,
+) -> anyhow::Result<ConnectOutcome<C::Config>> {
+    match client.connected_uuid() {
+        Some(current) if !client.link_alive().await => {
+            tracing::info!(uuid = %current, "设备链路已断开, 清理后重新连接");
+            if let Err(e) = client.disconnect().await {
+                tracing::warn!("清理已断开的设备 {current} 失败: {:#}", e);
+            }
+        }
+        Some(current) if current == uuid => {
+            tracing::info!(uuid, "设备已连接, 沿用当前连接");
+            let info = client.connected_peripheral().await.unwrap_or_default();
+            return Ok(ConnectOutcome::Reused(info));
+        }
+        Some(current) => {
+            tracing::info!(from = %current, to = uuid, "切换设备, 先断开当前设备");
+            client
+                .disconnect()
+                .await
+                .with_context(|| format!("断开当前设备 {current} 失败"))?;
+        }
+        None => {}
+    }
+    let config = prepare.await?;
+    let info = client.connect(uuid, &config, retry).await?;
+    Ok(ConnectOutcome::Connected { info, config })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use tokio::sync::Mutex;
+
+    use super::*;
+
+    /// 假客户端：连接耗时一段时间，每次连接开一个"接收任务"，断开时收回。
+    ///
+    /// 与未加保护的旧实现一样，`connect` 不检查是否已连接。
+    #[derive(Default)]
+    struct MockClient {
+        connected: Option<String>,
+        /// 设备关机或超出范围：仍记着设备 ID，但链路已断开，断开时也会失败
+        link_down: bool,
+        connects: usize,
+        disconnects: usize,
+        forwarders: usize,
+    }
+
+    impl DeviceConnection for MockClient {
+        type Config = ();
+
+        fn connected_uuid(&self) -> Option<String> {
+            self.connected.clone()
+        }
+
+        async fn link_alive(&self) -> bool {
+            self.connected.is_some() && !self.link_down
+        }
+
+        async fn connected_peripheral(&self) -> Option<PeripheralInfo> {
+            self.connected.as_ref().map(|id| PeripheralInfo {
+                id: id.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn connect(
+            &mut self,
+            uuid: &str,
+            _config: &(),
+            _retry: &ConnectRetryPolicy,
+        ) -> anyhow::Result<PeripheralInfo> {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.connects += 1;
+            self.forwarders += 1;
+            self.connected = Some(uuid.to_string());
+            self.link_down = false;
+            Ok(PeripheralInfo {
+                id: uuid.to_string(),
+                ..Default::default()
+            })
+        }
+
+        async fn disconnect(&mut self) -> anyhow::Result<PeripheralInfo> {
+            let id = self.connected.take().context("没有连接的设备可断开")?;
+            self.disconnects += 1;
+            self.forwarders -= 1;
+            if self.link_down {
+                anyhow::bail!("断开设备连接: 设备不可达");
+            }
+            Ok(PeripheralInfo {
+                id,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// 与 `AppState::connect_peripheral` 相同：持锁完成整个连接。
+    async fn connect(
+        client: &Mutex<MockClient>,
+        uuid: &str,
+        prepares: &AtomicUsize,
+    ) -> anyhow::Result<ConnectOutcome<()>> {
+        let mut client = client.lock().await;
+        let prepare = async {
+            prepares.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+        connect_exclusive(&mut *client, uuid, &ConnectRetryPolicy::default(), prepare).await
+    }
+
+    #[tokio::test]
+    async fn overlapping_connects_to_the_same_device_share_one_link() {
+        let client = Arc::new(Mutex::new(MockClient::default()));
+        let prepares = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let client = client.clone();
+                let prepares = prepares.clone();
+                tokio::spawn(async move { connect(&client, "dev-a", &prepares).await })
+            })
+            .collect();
+        let mut reused = 0;
+        for task in tasks {
+            match task.await.unwrap().unwrap() {
+                ConnectOutcome::Reused(info) => {
+                    assert_eq!(info.id, "dev-a");
+                    reused += 1;
+                }
+                ConnectOutcome::Connected { info, .. } => assert_eq!(info.id, "dev-a"),
+            }
+        }
+        assert_eq!(reused, 1);
+
+        let client = client.lock().await;
+        assert_eq!(client.connects, 1);
+        assert_eq!(client.forwarders, 1);
+        // 沿用连接时不切换档案
+        assert_eq!(prepares.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn connecting_another_device_disconnects_the_previous_one_first() {
+        let client = Mutex::new(MockClient::default());
+        let prepares = AtomicUsize::new(0);
+        connect(&client, "dev-a", &prepares).await.unwrap();
+        let outcome = connect(&client, "dev-b", &prepares).await.unwrap();
+        assert!(matches!(outcome, ConnectOutcome::Connected { info, .. } if info.id == "dev-b"));
+
+        let client = client.lock().await;
+        assert_eq!(client.connected.as_deref(), Some("dev-b"));
+        assert_eq!((client.connects, client.disconnects), (2, 1));
+        assert_eq!(client.forwarders, 1);
+        assert_eq!(prepares.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn stale_device_whose_link_dropped_is_reconnected() {
+        let client = Mutex::new(MockClient::default());
+        let prepares = AtomicUsize::new(0);
+        connect(&client, "dev-a", &prepares).await.unwrap();
+        // 设备断电重启：仍记着 dev-a，但链路已断开
+        client.lock().await.link_down = true;
+
+        let outcome = connect(&client, "dev-a", &prepares).await.unwrap();
+        assert!(matches!(outcome, ConnectOutcome::Connected { info, .. } if info.id == "dev-a"));
+        {
+            let client = client.lock().await;
+            assert_eq!((client.connects, client.disconnects), (2, 1));
+            assert_eq!(client.forwarders, 1);
+        }
+
+        // 切换设备时旧链路已断开：清理失败也照常连接新设备
+        client.lock().await.link_down = true;
+        let outcome = connect(&client, "dev-b", &prepares).await.unwrap();
+        assert!(matches!(outcome, ConnectOutcome::Connected { info, .. } if info.id == "dev-b"));
+        let client = client.lock().await;
+        assert_eq!(client.connected.as_deref(), Some("dev-b"));
+        assert_eq!(client.forwarders, 1);
+        assert_eq!(prepares.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,212 @@
+//! 原始设备命令（高级用户）。
+//!
+//! 厂商协议里还有不少没有封装的命令（如 0x27 重新校准内部磁力计、0x2A 切换板载滤波）。
+//! `write_device_command` 把任意字节原样写入设备，`read_device_response` 截获随后的回复。
+//!
+//! 写错命令可能改乱设备设置，因此默认关闭：只有 debug 构建，或 `processor.toml` 中
+//! `[global] unsafe_device_commands = true` 时才允许。
+//!
+//! 命令回复与数据帧走同一个 notify 特征，但帧头不是 0x11。[`NotificationTap`] 挂在接收任务里、
+//! 处理器之前：武装后截获下一条非 0x11 通知交给等待方，该通知不再进入处理器。
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// 单条命令的最大字节数：BLE 默认 MTU 23 减去 3 字节 ATT 头。
+pub const MAX_DEVICE_COMMAND_BYTES: usize = 20;
+
+/// 数据帧帧头，以此开头的通知属于数据流，不当作命令回复。
+const DATA_FRAME_HEADER: u8 = 0x11;
+
+/// 原始设备命令错误。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DeviceCommandError {
+    /// 未启用原始设备命令。
+    #[error(
+        "原始设备命令未启用：在 processor.toml 的 [global] 中设置 unsafe_device_commands = true"
+    )]
+    Disabled,
+    /// 命令长度不在 1 ~ 20 字节内。
+    #[error("设备命令长度为 {0} 字节，应为 1 ~ {MAX_DEVICE_COMMAND_BYTES} 字节")]
+    InvalidLength(usize),
+    /// 超时内没有收到命令回复。
+    #[error("{0} ms 内没有收到设备回复")]
+    Timeout(u64),
+}
+
+/// 是否允许原始设备命令：配置开启或 debug 构建。
+pub fn device_commands_allowed(config_enabled: bool, debug_build: bool) -> bool {
+    config_enabled || debug_build
+}
+
+/// 校验一条待写入的命令：长度 1 ~ 20 字节。
+pub fn validate_device_command(bytes: &[u8]) -> Result<(), DeviceCommandError> {
+    if bytes.is_empty() || bytes.len() > MAX_DEVICE_COMMAND_BYTES {
+        return Err(DeviceCommandError::InvalidLength(bytes.len()));
+    }
+    Ok(())
+}
+
+/// 字节序列的十六进制表示（小写、空格分隔），用于日志与回复。
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Default)]
+struct TapState {
+    /// 是否截获下一条非数据通知。
+    armed: bool,
+    /// 已截获、尚未被取走的回复。
+    reply: Option<Vec<u8>>,
+    /// 正在等待回复的一方。
+    waiter: Option<flume::Sender<Vec<u8>>>,
+}
+
+/// 通知分流：截获下一条命令回复，接收任务与命令之间共享。
+#[derive(Debug, Clone, Default)]
+pub struct NotificationTap {
+    state: Arc<Mutex<TapState>>,
+}
+
+impl NotificationTap {
+    /// 武装：丢弃旧回复，截获之后的下一条非数据通知。写命令之前调用，回复不会早于等待方到达而丢失。
+    pub fn arm(&self) {
+        let mut state = self.lock();
+        state.armed = true;
+        state.reply = None;
+    }
+
+    /// 取消武装并丢弃未取走的回复（等待超时后调用）。
+    pub fn disarm(&self) {
+        let mut state = self.lock();
+        state.armed = false;
+        state.reply = None;
+        state.waiter = None;
+    }
+
+    /// 接收任务对每条通知调用；被截获时返回 `true`，该通知不再转发给处理器。
+    pub fn offer(&self, packet: &[u8]) -> bool {
+        let mut state = self.lock();
+        if !state.armed || packet.first().is_none_or(|&b| b == DATA_FRAME_HEADER) {
+            return false;
+        }
+        state.armed = false;
+        let reply = packet.to_vec();
+        match state.waiter.take() {
+            Some(waiter) => {
+                if let Err(flume::SendError(reply)) = waiter.send(reply) {
+                    // 等待方已放弃，留给下一次读取
+                    state.reply = Some(reply);
+                }
+            }
+            None => state.reply = Some(reply),
+        }
+        true
+    }
+
+    /// 等待下一条命令回复：已截获时立即返回，否则武装后最多等待 `timeout`。
+    pub async fn next_reply(&self, timeout: Duration) -> Result<Vec<u8>, DeviceCommandError> {
+        let rx = {
+            let mut state = self.lock();
+            if let Some(reply) = state.reply.take() {
+                return Ok(reply);
+            }
+            let (tx, rx) = flume::bounded(1);
+            state.armed = true;
+            state.waiter = Some(tx);
+            rx
+        };
+        match tokio::time::timeout(timeout, rx.recv_async()).await {
+            Ok(Ok(reply)) => Ok(reply),
+            _ => {
+                self.disarm();
+                Err(DeviceCommandError::Timeout(timeout.as_millis() as u64))
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TapState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_length_is_validated() {
+        assert_eq!(
+            validate_device_command(&[]),
+            Err(DeviceCommandError::InvalidLength(0))
+        );
+        assert_eq!(validate_device_command(&[0x27]), Ok(()));
+        assert_eq!(validate_device_command(&[0xaa; 20]), Ok(()));
+        assert_eq!(
+            validate_device_command(&[0xaa; 21]),
+            Err(DeviceCommandError::InvalidLength(21))
+        );
+        assert_eq!(to_hex(&[0x2a, 0x01, 0xff]), "2a 01 ff");
+    }
+
+    #[test]
+    fn commands_require_the_config_flag_outside_debug_builds() {
+        assert!(!device_commands_allowed(false, false));
+        assert!(device_commands_allowed(true, false));
+        assert!(device_commands_allowed(false, true));
+    }
+
+    #[test]
+    fn only_the_next_non_data_notification_is_captured_after_arming() {
+        let tap = NotificationTap::default();
+        // 未武装时一律放行
+        assert!(!tap.offer(&[0x27, 0x00]));
+
+        tap.arm();
+        assert!(!tap.offer(&[0x11, 0x00, 0x01]));
+        assert!(!tap.offer(&[]));
+        assert!(tap.offer(&[0x27, 0x01]));
+        // 只截获一条
+        assert!(!tap.offer(&[0x2a, 0x02]));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let reply = runtime.block_on(tap.next_reply(Duration::from_millis(10)));
+        assert_eq!(reply, Ok(vec![0x27, 0x01]));
+    }
+
+    #[tokio::test]
+    async fn waiting_reader_receives_the_reply_routed_from_the_receive_task() {
+        let tap = NotificationTap::default();
+        let receiver = tap.clone();
+        let reader = tokio::spawn(async move { receiver.next_reply(Duration::from_secs(5)).await });
+        // 等读取方武装后再模拟通知到达：先是数据帧，再是命令回复
+        while !tap.lock().armed {
+            tokio::task::yield_now().await;
+        }
+        assert!(!tap.offer(&[0x11, 0x00]));
+        assert!(tap.offer(&[0x2a, 0x05]));
+        assert_eq!(reader.await.unwrap(), Ok(vec![0x2a, 0x05]));
+    }
+
+    #[tokio::test]
+    async fn timeout_disarms_the_tap() {
+        let tap = NotificationTap::default();
+        assert_eq!(
+            tap.next_reply(Duration::from_millis(5)).await,
+            Err(DeviceCommandError::Timeout(5))
+        );
+        // 超时后迟到的回复照常转发给处理器
+        assert!(!tap.offer(&[0x27]));
+    }
+}
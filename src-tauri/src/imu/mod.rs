@@ -1,7 +1,26 @@
 //! IMU 设备交互模块。
 
+mod adapter;
 mod client;
 mod config;
+mod connect_retry;
+mod connection;
+mod device_command;
+mod scan_freshness;
 
+/// 蓝牙错误。
+pub use adapter::BluetoothError;
 /// IMU 客户端。
 pub use client::IMUClient;
+/// IMU 设备配置。
+pub use config::{IMUConfig, Subscription};
+/// 连接重试策略。
+pub use connect_retry::ConnectRetryPolicy;
+/// 串行、幂等的设备连接。
+pub use connection::{connect_exclusive, ConnectOutcome};
+/// 原始设备命令。
+pub use device_command::{
+    device_commands_allowed, to_hex, validate_device_command, DeviceCommandError,
+};
+/// 扫描结果新鲜度与连接失败后的重新扫描。
+pub use scan_freshness::DeviceUnreachable;
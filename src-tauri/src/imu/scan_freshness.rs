@@ -0,0 +1,346 @@
+//! 扫描结果的新鲜度，以及连接失败后的重新扫描。
+//!
+//! 适配器会一直缓存旧扫描发现的设备：设备关机后仍留在 `list_peripherals` 里，
+//! 用户选中后只得到一个令人费解的连接超时。这里按发现事件记下每个设备最近一次被看到的
+//! 主机时间，列表只保留窗口内出现过的设备（正连接的设备除外）。
+//! 连接因设备不可达（找不到设备、建立链路超时或中断）失败时，短暂重新扫描一次，
+//! 在错误中说明设备是否被重新发现：没发现多半是设备已关机或超出范围。
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    imu::connect_retry::{ConnectError, ConnectStage},
+    processor::shared::HostTimeMs,
+    types::error::ErrorCode,
+};
+
+/// 默认新鲜度窗口（ms）：超过这么久没被发现的设备不再列出。
+pub const DEFAULT_FRESHNESS_WINDOW_MS: u64 = 30_000;
+
+/// 连接失败后重新扫描的时长。
+pub const RESCAN_DURATION: Duration = Duration::from_millis(2_500);
+
+/// 跨任务共享的发现记录。
+pub type PeripheralSightingsHandle = Arc<PeripheralSightings>;
+
+/// 各设备最近一次被发现的主机时间。
+#[derive(Debug, Default)]
+pub struct PeripheralSightings {
+    last_seen: Mutex<HashMap<String, HostTimeMs>>,
+}
+
+impl PeripheralSightings {
+    /// 创建共享句柄。
+    pub fn new_handle() -> PeripheralSightingsHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 记录设备在 `now` 被发现（发现事件、广播更新或连接成功）。
+    pub fn record(&self, id: &str, now: HostTimeMs) {
+        self.lock().insert(id.to_string(), now);
+    }
+
+    /// 最近一次被发现的时间，本次运行从未发现过时为空。
+    pub fn last_seen(&self, id: &str) -> Option<HostTimeMs> {
+        self.lock().get(id).copied()
+    }
+
+    /// 距最近一次被发现的毫秒数，本次运行从未发现过时为空。
+    pub fn last_seen_ms_ago(&self, id: &str, now: HostTimeMs) -> Option<u64> {
+        self.last_seen(id).map(|seen| now.delta_ms(seen))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, HostTimeMs>> {
+        self.last_seen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 设备是否应出现在列表中：窗口内被发现过，或正连接着。
+pub fn is_fresh(last_seen_ms_ago: Option<u64>, window_ms: u64, connected: bool) -> bool {
+    connected || last_seen_ms_ago.is_some_and(|age| age <= window_ms)
+}
+
+/// 适配器缓存里没有该设备。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("没有找到设备 {0}")]
+pub struct PeripheralNotFound(pub String);
+
+/// 设备不可达，附带重新扫描的结果。
+#[derive(Debug, thiserror::Error)]
+#[error("{}", describe_unreachable(.id, *.rediscovered, *.scan_ms))]
+pub struct DeviceUnreachable {
+    /// 设备 ID。
+    pub id: String,
+    /// 重新扫描期间是否再次发现了该设备。
+    pub rediscovered: bool,
+    /// 重新扫描时长（ms）。
+    pub scan_ms: u64,
+    /// 原始的连接错误。
+    #[source]
+    pub source: anyhow::Error,
+}
+
+fn describe_unreachable(id: &str, rediscovered: bool, scan_ms: u64) -> String {
+    if rediscovered {
+        format!("重新扫描时发现了设备 {id}，但无法建立连接（信号弱或被其他主机占用）")
+    } else {
+        format!("重新扫描 {scan_ms} ms 未发现设备 {id}，设备可能已关机或超出范围")
+    }
+}
+
+impl DeviceUnreachable {
+    /// 对应的 IPC 错误码。
+    pub fn code(&self) -> ErrorCode {
+        if self.rediscovered {
+            ErrorCode::DeviceUnreachable
+        } else {
+            ErrorCode::DeviceOffline
+        }
+    }
+}
+
+/// 连接失败是否因为设备不可达，值得重新扫描确认。
+///
+/// 找不到设备，或建立链路这一步遇到瞬时错误（超时、中断）算不可达；
+/// 已连上但发现服务、查找特征失败说明设备在场，不需要重新扫描。
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<PeripheralNotFound>()
+            || cause.downcast_ref::<ConnectError>().is_some_and(|error| {
+                error
+                    .attempts
+                    .last()
+                    .is_some_and(|last| last.stage == ConnectStage::Connect && last.transient)
+            })
+    })
+}
+
+/// 可启动、停止扫描的发现源，测试中以假实现替换蓝牙适配器。
+pub trait Discovery {
+    /// 开始扫描，发现事件写入发现记录。
+    fn start_scan(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+    /// 停止扫描。
+    fn stop_scan(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// 连接失败后按需重新扫描，返回交给调用方的错误。
+///
+/// 不可达类错误时扫描 `duration`，以扫描开始后是否有 `id` 的发现记录判断设备是否还在，
+/// 结果包成 [`DeviceUnreachable`]；其余错误原样返回。`was_scanning` 为真时扫描结束后不停止，
+/// 保持用户原先开启的扫描。扫描本身失败只记录日志，按未发现处理。
+pub async fn rescan_after_failure<D: Discovery>(
+    discovery: &D,
+    sightings: &PeripheralSightings,
+    id: &str,
+    error: anyhow::Error,
+    duration: Duration,
+    was_scanning: bool,
+    clock: impl Fn() -> HostTimeMs,
+) -> anyhow::Error {
+    if !is_unreachable(&error) {
+        return error;
+    }
+    tracing::info!(id, "连接失败, 重新扫描确认设备是否在场");
+    let started = clock();
+    match discovery.start_scan().await {
+        Ok(()) => {
+            tokio::time::sleep(duration).await;
+            if !was_scanning {
+                if let Err(e) = discovery.stop_scan().await {
+                    tracing::warn!("停止重新扫描失败: {:#}", e);
+                }
+            }
+        }
+        Err(e) => tracing::warn!("重新扫描失败: {:#}", e),
+    }
+    let rediscovered = sightings.last_seen(id).is_some_and(|seen| seen >= started);
+    anyhow::Error::new(DeviceUnreachable {
+        id: id.to_string(),
+        rediscovered,
+        scan_ms: duration.as_millis() as u64,
+        source: error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::imu::connect_retry::ConnectAttempt;
+
+    #[test]
+    fn stale_entries_are_hidden_unless_connected() {
+        let sightings = PeripheralSightings::default();
+        sightings.record("fresh", HostTimeMs(100_000));
+        sightings.record("stale", HostTimeMs(60_000));
+        let now = HostTimeMs(120_000);
+
+        let age = |id| sightings.last_seen_ms_ago(id, now);
+        assert_eq!(age("fresh"), Some(20_000));
+        assert_eq!(age("stale"), Some(60_000));
+        assert_eq!(age("never"), None);
+
+        let window = DEFAULT_FRESHNESS_WINDOW_MS;
+        assert!(is_fresh(age("fresh"), window, false));
+        assert!(!is_fresh(age("stale"), window, false));
+        assert!(!is_fresh(age("never"), window, false));
+        // 正连接的设备即使不再广播也保留
+        assert!(is_fresh(age("stale"), window, true));
+        assert!(is_fresh(age("never"), window, true));
+        // 窗口可调
+        assert!(is_fresh(age("stale"), 90_000, false));
+
+        // 主机时钟回拨时按刚刚发现处理
+        assert_eq!(
+            sightings.last_seen_ms_ago("fresh", HostTimeMs(90_000)),
+            Some(0)
+        );
+    }
+
+    fn connect_error(stage: ConnectStage, transient: bool) -> anyhow::Error {
+        anyhow::Error::new(ConnectError {
+            attempts: vec![ConnectAttempt {
+                attempt: 1,
+                stage,
+                error: "le-connection-abort-by-local".into(),
+                transient,
+            }],
+        })
+    }
+
+    #[test]
+    fn only_unreachable_failures_trigger_rescan() {
+        assert!(is_unreachable(
+            &anyhow::Error::new(PeripheralNotFound("dev".into())).context("连接到设备时发生错误")
+        ));
+        assert!(is_unreachable(&connect_error(ConnectStage::Connect, true)));
+        // 权限不足等永久错误、已连上后的失败都不是设备不在
+        assert!(!is_unreachable(&connect_error(
+            ConnectStage::Connect,
+            false
+        )));
+        assert!(!is_unreachable(&connect_error(
+            ConnectStage::DiscoverServices,
+            true
+        )));
+        assert!(!is_unreachable(&connect_error(
+            ConnectStage::Characteristics,
+            false
+        )));
+        assert!(!is_unreachable(&anyhow::anyhow!("处理管线已退出")));
+    }
+
+    /// 假发现源：开始扫描时把 `visible` 中的设备记为在当前时钟被发现。
+    struct MockDiscovery<'a> {
+        sightings: &'a PeripheralSightings,
+        clock: &'a AtomicU64,
+        visible: Vec<&'static str>,
+        starts: AtomicUsize,
+        stops: AtomicUsize,
+    }
+
+    impl<'a> MockDiscovery<'a> {
+        fn new(
+            sightings: &'a PeripheralSightings,
+            clock: &'a AtomicU64,
+            visible: Vec<&'static str>,
+        ) -> Self {
+            Self {
+                sightings,
+                clock,
+                visible,
+                starts: AtomicUsize::new(0),
+                stops: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl Discovery for MockDiscovery<'_> {
+        async fn start_scan(&self) -> anyhow::Result<()> {
+            self.starts.fetch_add(1, Ordering::Relaxed);
+            let now = HostTimeMs(self.clock.fetch_add(500, Ordering::Relaxed) + 500);
+            for id in &self.visible {
+                self.sightings.record(id, now);
+            }
+            Ok(())
+        }
+
+        async fn stop_scan(&self) -> anyhow::Result<()> {
+            self.stops.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn rescan_reports_whether_the_device_reappeared() {
+        let sightings = PeripheralSightings::default();
+        // 两台设备都只在很久以前的扫描里出现过
+        sightings.record("off", HostTimeMs(1_000));
+        sightings.record("busy", HostTimeMs(1_000));
+        let clock = AtomicU64::new(50_000);
+        let now = || HostTimeMs(clock.load(Ordering::Relaxed));
+        let discovery = MockDiscovery::new(&sightings, &clock, vec!["busy"]);
+
+        let error = rescan_after_failure(
+            &discovery,
+            &sightings,
+            "off",
+            connect_error(ConnectStage::Connect, true),
+            Duration::ZERO,
+            false,
+            now,
+        )
+        .await;
+        let unreachable = error.downcast_ref::<DeviceUnreachable>().unwrap();
+        assert!(!unreachable.rediscovered);
+        assert_eq!(unreachable.code(), ErrorCode::DeviceOffline);
+        assert!(format!("{error:#}").contains("可能已关机"), "{error:#}");
+        assert!(format!("{error:#}").contains("le-connection-abort-by-local"));
+        assert_eq!(discovery.stops.load(Ordering::Relaxed), 1);
+
+        // 用户原本就在扫描：不停止扫描
+        let error = rescan_after_failure(
+            &discovery,
+            &sightings,
+            "busy",
+            anyhow::Error::new(PeripheralNotFound("busy".into())),
+            Duration::ZERO,
+            true,
+            now,
+        )
+        .await;
+        let unreachable = error.downcast_ref::<DeviceUnreachable>().unwrap();
+        assert!(unreachable.rediscovered);
+        assert_eq!(unreachable.code(), ErrorCode::DeviceUnreachable);
+        assert_eq!(discovery.stops.load(Ordering::Relaxed), 1);
+        assert_eq!(discovery.starts.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn other_failures_are_returned_without_rescanning() {
+        let sightings = PeripheralSightings::default();
+        let clock = AtomicU64::new(0);
+        let discovery = MockDiscovery::new(&sightings, &clock, vec![]);
+        let error = rescan_after_failure(
+            &discovery,
+            &sightings,
+            "dev",
+            connect_error(ConnectStage::Characteristics, false),
+            Duration::ZERO,
+            false,
+            || HostTimeMs(clock.load(Ordering::Relaxed)),
+        )
+        .await;
+        assert!(error.downcast_ref::<DeviceUnreachable>().is_none());
+        assert!(error.is::<ConnectError>());
+        assert_eq!(discovery.starts.load(Ordering::Relaxed), 0);
+    }
+}
@@ -2,11 +2,18 @@
 
 #![deny(missing_docs)]
 
+#[cfg(feature = "gui")]
 use tauri::Manager as _;
 
+#[cfg(feature = "gui")]
 mod app_state;
+#[cfg(feature = "gui")]
 mod commands;
+/// 无界面离线处理入口（`imu_vis_cli` 二进制与集成测试使用）。
+pub mod headless;
+#[cfg(feature = "gui")]
 mod imu;
+#[cfg(feature = "gui")]
 mod logger;
 /// 数据处理管线（离线 replay 二进制会复用本模块）。
 pub mod processor;
@@ -15,20 +22,29 @@ pub mod processor;
 /// 子模块里的 SeaORM 实体结构未逐字段补文档，故本模块整体放宽 `missing_docs`。
 #[allow(missing_docs)]
 pub mod recorder;
+/// 应用内录制回放（播放、暂停、跳转、倍速与循环）。
+pub mod replay;
+/// 无硬件自检（应用命令与集成测试共用）。
+pub mod selftest;
 /// 前后端共享的数据结构。
 #[allow(missing_docs)]
 pub mod types;
+/// 展示与导出边界的单位换算。
+pub mod units;
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 /// 启动 Tauri 应用并注册后端能力。
 pub fn run() {
     std::env::set_var("NO_PROXY", "localhost,127.0.0.1");
-    let _log_guard = logger::init_tracing();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(commands::handlers())
         .setup(|app| {
+            // 日志文件写入应用数据目录，须在拿到 app handle 后初始化
+            app.manage(logger::init_tracing(app.path().app_data_dir().ok()));
+
             #[cfg(debug_assertions)]
             app.get_webview_window("main").unwrap().open_devtools();
 
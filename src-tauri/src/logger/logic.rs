@@ -0,0 +1,217 @@
+//! tracing 初始化与运行时级别调整。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::Context;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{
+    filter::Targets, fmt::format::FmtSpan, layer::SubscriberExt, reload, util::SubscriberInitExt,
+    Layer, Registry,
+};
+
+use crate::logger::types::{LogLevel, LoggingConfig};
+
+type TargetsHandle = reload::Handle<Targets, Registry>;
+
+/// 运行时日志级别句柄，由 Tauri 托管。
+///
+/// 按目标过滤是挂在各输出层上的 per-layer filter，而不是全局 filter：
+/// 调整级别只影响控制台与文件输出，自行按目标过滤的其他 layer 不受影响。
+pub struct LoggingHandle {
+    config: Mutex<LoggingConfig>,
+    filters: Vec<TargetsHandle>,
+    _guard: Option<WorkerGuard>,
+}
+
+impl LoggingHandle {
+    /// 调整某个目标（模块路径前缀）的级别；`target` 为空时调整默认级别。
+    ///
+    /// 同时作用于控制台与日志文件（文件仍受 `file.level` 截断）。
+    pub fn set_level(&self, target: &str, level: LogLevel) -> anyhow::Result<()> {
+        let mut config = self
+            .config
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let target = target.trim();
+        if target.is_empty() {
+            config.default_level = level;
+        } else {
+            config.targets.insert(target.to_string(), level);
+        }
+        let targets = config.targets_filter();
+        for filter in &self.filters {
+            filter
+                .reload(targets.clone())
+                .context("reload log filter")?;
+        }
+        Ok(())
+    }
+}
+
+/// 初始化 tracing 并返回级别句柄（内含文件写入线程的 guard，须保持存活）。
+///
+/// * `data_dir`: 应用数据目录，日志文件写入其下的 `logs/`；为空时写到工作目录的 `logs/`
+pub fn init_tracing(data_dir: Option<PathBuf>) -> LoggingHandle {
+    let config_path = LoggingConfig::default_config_path();
+    let (config, config_error) = match LoggingConfig::load(&config_path) {
+        Ok(config) => (config, None),
+        Err(error) => (LoggingConfig::default(), Some(error)),
+    };
+
+    let (stdout_filter, stdout_handle) = reload::Layer::new(config.targets_filter());
+    let stdout_layer = tracing_subscriber::fmt::layer()
+        .with_target(true)
+        .with_thread_ids(true)
+        .with_line_number(true)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_filter(stdout_filter);
+    // 各输出层都直接挂在 Registry 上，reload 句柄类型一致，便于统一调整
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![stdout_layer.boxed()];
+    let mut filters = vec![stdout_handle];
+
+    let mut log_dir = None;
+    let mut guard = None;
+    let mut file_error = None;
+    if config.file.enabled {
+        let dir = data_dir.unwrap_or_default().join("logs");
+        match build_file_appender(&config, &dir) {
+            Ok(appender) => {
+                let (writer, worker_guard) = tracing_appender::non_blocking(appender);
+                let (file_filter, file_handle) = reload::Layer::new(config.targets_filter());
+                filters.push(file_handle);
+                log_dir = Some(dir);
+                guard = Some(worker_guard);
+                layers.push(
+                    tracing_subscriber::fmt::layer()
+                        .json()
+                        .with_writer(writer)
+                        .with_current_span(true)
+                        .with_span_list(true)
+                        .with_filter(file_filter)
+                        .with_filter(config.file.level.0)
+                        .boxed(),
+                );
+            }
+            Err(error) => file_error = Some(error),
+        }
+    }
+
+    tracing_subscriber::registry().with(layers).init();
+
+    // subscriber 就绪后才能报告配置问题
+    if let Some(error) = config_error {
+        tracing::warn!(
+            "读取日志配置失败，使用默认配置。path: {:?}, err: {:#}",
+            config_path,
+            error
+        );
+    }
+    if let Some(error) = file_error {
+        tracing::warn!("创建日志文件失败，只输出到控制台: {:#}", error);
+    }
+    if let Some(dir) = &log_dir {
+        tracing::info!("日志文件目录: {}", dir.display());
+    }
+
+    LoggingHandle {
+        config: Mutex::new(config),
+        filters,
+        _guard: guard,
+    }
+}
+
+fn build_file_appender(
+    config: &LoggingConfig,
+    dir: &Path,
+) -> anyhow::Result<tracing_appender::rolling::RollingFileAppender> {
+    std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let mut builder = tracing_appender::rolling::RollingFileAppender::builder()
+        .rotation(config.file.rotation.into())
+        .filename_prefix(config.file.file_prefix.as_str())
+        .filename_suffix("jsonl");
+    if config.file.max_files > 0 {
+        builder = builder.max_log_files(config.file.max_files);
+    }
+    builder
+        .build(dir)
+        .with_context(|| format!("create rolling log file in {}", dir.display()))
+}
+
+/// 用测试自建的 reload 句柄构造 `LoggingHandle`。
+#[cfg(test)]
+fn handle_for_test(config: LoggingConfig, filters: Vec<TargetsHandle>) -> LoggingHandle {
+    LoggingHandle {
+        config: Mutex::new(config),
+        filters,
+        _guard: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tracing::level_filters::LevelFilter;
+    use tracing_subscriber::layer::Context as LayerContext;
+
+    use super::*;
+
+    /// 统计经过 filter 后到达的事件数。
+    struct CountingLayer(Arc<AtomicUsize>);
+
+    impl<S: tracing::Subscriber> Layer<S> for CountingLayer {
+        fn on_event(&self, _event: &tracing::Event<'_>, _ctx: LayerContext<'_, S>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn set_level_reloads_layer_filter() {
+        let config = LoggingConfig::from_toml(
+            r#"
+            default_level = "info"
+            [targets]
+            "app::navigator" = "warn"
+            "#,
+        )
+        .unwrap();
+        let (filter, reload_handle) = reload::Layer::new(config.targets_filter());
+        let filtered = Arc::new(AtomicUsize::new(0));
+        let unfiltered = Arc::new(AtomicUsize::new(0));
+        let subscriber = tracing_subscriber::registry()
+            .with(CountingLayer(filtered.clone()).with_filter(filter))
+            .with(CountingLayer(unfiltered.clone()));
+        let handle = handle_for_test(config, vec![reload_handle]);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "app::navigator::zupt", "flood");
+            tracing::warn!(target: "app::navigator::zupt", "kept");
+            tracing::info!(target: "app::recorder", "kept");
+            assert_eq!(filtered.load(Ordering::Relaxed), 2);
+
+            handle
+                .set_level("app::navigator", "debug".parse().unwrap())
+                .unwrap();
+            tracing::debug!(target: "app::navigator::zupt", "now visible");
+            handle.set_level("", "error".parse().unwrap()).unwrap();
+            tracing::info!(target: "app::recorder", "now hidden");
+            tracing::info!(target: "app::navigator", "still visible");
+            assert_eq!(filtered.load(Ordering::Relaxed), 4);
+        });
+
+        // 不带 filter 的 layer 收到全部事件，不受级别调整影响
+        assert_eq!(unfiltered.load(Ordering::Relaxed), 6);
+        let config = handle.config.lock().unwrap();
+        assert_eq!(config.default_level, LogLevel(LevelFilter::ERROR));
+        assert_eq!(
+            config.targets["app::navigator"],
+            LogLevel(LevelFilter::DEBUG)
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! 日志初始化与配置。
+//!
+//! 配置来自项目根目录的 `logging.toml`（查找规则同 `processor.toml`），缺失时使用
+//! 默认值。按目标的级别可在运行时通过 `set_log_level` 命令调整。
+
+/// tracing 初始化与运行时级别调整。
+pub mod logic;
+/// 日志配置类型。
+pub mod types;
+
+/// tracing 初始化与运行时级别调整。
+pub use logic::{init_tracing, LoggingHandle};
+/// 日志配置类型。
+pub use types::LogLevel;
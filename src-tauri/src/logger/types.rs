@@ -0,0 +1,233 @@
+//! 日志配置类型（`logging.toml`）。
+
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+
+/// 日志级别，取值同 `tracing`：`off` / `error` / `warn` / `info` / `debug` / `trace`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct LogLevel(pub LevelFilter);
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        LevelFilter::from_str(value.trim())
+            .map(LogLevel)
+            .map_err(|_| anyhow::anyhow!("invalid log level {value:?}"))
+    }
+}
+
+impl TryFrom<String> for LogLevel {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> anyhow::Result<Self> {
+        value.parse()
+    }
+}
+
+/// 日志文件滚动周期。
+///
+/// `tracing-appender` 只支持按时间滚动，不支持按文件大小；用 `max_files` 限制总占用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    /// 每小时一个文件。
+    Hourly,
+    /// 每天一个文件。
+    Daily,
+    /// 每周一个文件。
+    Weekly,
+    /// 不滚动，始终写同一个文件。
+    Never,
+}
+
+impl From<LogRotation> for tracing_appender::rolling::Rotation {
+    fn from(rotation: LogRotation) -> Self {
+        use tracing_appender::rolling::Rotation;
+        match rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Weekly => Rotation::WEEKLY,
+            LogRotation::Never => Rotation::NEVER,
+        }
+    }
+}
+
+/// 滚动日志文件配置。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FileLogConfig {
+    /// 是否写日志文件。
+    pub enabled: bool,
+    /// 文件的最低级别，在按目标过滤之后再截断一次。
+    pub level: LogLevel,
+    /// 滚动周期。
+    pub rotation: LogRotation,
+    /// 最多保留的文件数，超出后删除最旧的；0 表示不限。
+    pub max_files: usize,
+    /// 文件名前缀，实际文件名为 `<prefix>.<日期>.jsonl`。
+    pub file_prefix: String,
+}
+
+impl Default for FileLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: LogLevel(LevelFilter::INFO),
+            rotation: LogRotation::Daily,
+            max_files: 7,
+            file_prefix: "imu_vis".into(),
+        }
+    }
+}
+
+/// 日志配置。
+///
+/// ```toml
+/// default_level = "debug"
+///
+/// [targets]
+/// "tauri_app_lib::processor::navigator" = "warn"
+///
+/// [file]
+/// enabled = true
+/// rotation = "daily"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// 未单独配置的目标使用的级别。
+    pub default_level: LogLevel,
+    /// 按目标（模块路径前缀）覆盖级别，最长前缀优先。
+    pub targets: BTreeMap<String, LogLevel>,
+    /// 滚动日志文件。
+    pub file: FileLogConfig,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        let targets = [
+            ("bluez_async::events", LevelFilter::INFO),
+            ("sqlx", LevelFilter::WARN),
+        ]
+        .into_iter()
+        .map(|(target, level)| (target.to_string(), LogLevel(level)))
+        .collect();
+        Self {
+            default_level: LogLevel(LevelFilter::DEBUG),
+            targets,
+            file: FileLogConfig::default(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// 返回日志配置文件路径。
+    ///
+    /// 与 `processor.toml` 相同：优先当前目录，其次父目录（在 `src-tauri/` 下运行时）。
+    pub fn default_config_path() -> std::path::PathBuf {
+        let local = std::path::PathBuf::from("logging.toml");
+        if local.exists() {
+            return local;
+        }
+        let parent = std::path::PathBuf::from("../logging.toml");
+        if parent.exists() {
+            return parent;
+        }
+        local
+    }
+
+    /// 从 TOML 文本解析。
+    pub fn from_toml(content: &str) -> anyhow::Result<Self> {
+        toml::from_str(content).context("parse logging config")
+    }
+
+    /// 从文件加载；文件不存在时返回默认配置。
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("read logging config {}", path.display()))?;
+        Self::from_toml(&content).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// 构建按目标过滤的 filter。
+    pub fn targets_filter(&self) -> Targets {
+        Targets::new()
+            .with_targets(
+                self.targets
+                    .iter()
+                    .map(|(target, level)| (target.clone(), level.0)),
+            )
+            .with_default(self.default_level.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_overrides_and_file_section() {
+        let config = LoggingConfig::from_toml(
+            r#"
+            default_level = "INFO"
+
+            [targets]
+            "tauri_app_lib::processor::navigator" = "warn"
+            sqlx = "off"
+
+            [file]
+            enabled = true
+            rotation = "hourly"
+            max_files = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.default_level, LogLevel(LevelFilter::INFO));
+        assert_eq!(
+            config.targets["tauri_app_lib::processor::navigator"],
+            LogLevel(LevelFilter::WARN)
+        );
+        assert_eq!(config.targets["sqlx"], LogLevel(LevelFilter::OFF));
+        // 未写的字段取默认值
+        assert!(config.file.enabled);
+        assert_eq!(config.file.rotation, LogRotation::Hourly);
+        assert_eq!(config.file.max_files, 3);
+        assert_eq!(config.file.level, LogLevel(LevelFilter::INFO));
+        assert_eq!(config.file.file_prefix, "imu_vis");
+
+        let filter = config.targets_filter();
+        assert!(filter.would_enable(
+            "tauri_app_lib::processor::navigator::zupt",
+            &tracing::Level::WARN
+        ));
+        assert!(!filter.would_enable(
+            "tauri_app_lib::processor::navigator::zupt",
+            &tracing::Level::INFO
+        ));
+        assert!(filter.would_enable("tauri_app_lib::recorder", &tracing::Level::INFO));
+        assert!(!filter.would_enable("sqlx::query", &tracing::Level::ERROR));
+
+        assert_eq!(
+            LoggingConfig::from_toml("").unwrap(),
+            LoggingConfig::default()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_levels() {
+        let error = LoggingConfig::from_toml("[targets]\nsqlx = \"loud\"").unwrap_err();
+        assert!(
+            format!("{error:#}").contains("invalid log level"),
+            "{error:#}"
+        );
+        assert!(LoggingConfig::from_toml("[file]\nrotation = \"by_size\"").is_err());
+    }
+}
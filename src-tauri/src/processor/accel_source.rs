@@ -0,0 +1,273 @@
+//! 加速度来源选择与一致性检查。
+//!
+//! 设备同时上报含重力的 `accel_with_g` 与固件自行去重力的 `accel_no_g`。默认用前者：
+//! 导航器把它转到世界系后减去重力参考得到线加速度；部分固件版本的 `accel_no_g` 明显更准，
+//! 可改为直接积分它。`auto` 仍用 `accel_with_g`，同时逐帧比较两路世界系线加速度
+//! `|R·a_with_g − g_ref − R·a_no_g|`，连续超过阈值时判定不一致并推送 `accel_inconsistent`。
+//!
+//! 选出的加速度替换标定样本中的加速度，之后的滤波、派生信号、ZUPT 与导航器只看这一路。
+//! `no_g` 时把重力参考按当前姿态转回机体系加到 `accel_no_g` 上：下游减去同一重力参考后
+//! 得到的线加速度正是 `R·a_no_g`，重力初始化与自动重力估计无需区分来源。
+
+use math_f64::{DQuat, DVec3};
+use serde::{Deserialize, Serialize};
+
+use crate::processor::{
+    calibration::ImuSampleCalibrated,
+    shared::{validate::ConfigValidator, DeviceTimeMs},
+};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+/// 积分用的加速度来源。
+pub enum AccelSource {
+    /// 含重力加速度减去重力参考（默认）。
+    #[default]
+    WithG,
+    /// 设备去重力加速度，不再减重力参考。
+    NoG,
+    /// 使用含重力加速度，并逐帧与设备去重力加速度交叉检查。
+    Auto,
+}
+
+/// 加速度来源配置。
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AccelSourceConfig {
+    /// 积分用的加速度来源。
+    pub mode: AccelSource,
+    /// `auto` 模式下两路世界系线加速度之差超过该值视为不一致 (m/s²)。
+    pub consistency_thresh_ms2: f64,
+    /// 连续多少帧超过阈值判定为不一致，恢复同样需要连续多少帧低于阈值。
+    pub consistency_frames: u32,
+}
+
+impl Default for AccelSourceConfig {
+    fn default() -> Self {
+        Self {
+            mode: AccelSource::WithG,
+            consistency_thresh_ms2: 0.5,
+            consistency_frames: 25,
+        }
+    }
+}
+
+impl AccelSourceConfig {
+    /// 校验取值范围：一致性阈值 0 ~ 50 m/s²，判定帧数 1 ~ 10000。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.positive("consistency_thresh_ms2", self.consistency_thresh_ms2, 50.0);
+        v.in_range(
+            "consistency_frames",
+            self.consistency_frames as f64,
+            1.0,
+            10_000.0,
+        );
+    }
+}
+
+/// 加速度不一致事件（`accel_inconsistent`），进入不一致与恢复各推送一次。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AccelInconsistencyEvent {
+    /// 设备时间戳 (ms)。
+    pub timestamp_ms: u64,
+    /// `true` 表示两路加速度持续不一致；`false` 表示恢复一致。
+    pub inconsistent: bool,
+    /// 判定帧的世界系线加速度之差 (m/s²)。
+    pub residual_ms2: f64,
+    /// 管线重置以来检测到的不一致次数。
+    pub inconsistent_count: u64,
+}
+
+/// 加速度来源选择器，由管线持有，逐帧决定交给滤波器的加速度。
+#[derive(Debug, Clone)]
+pub struct AccelSourceSelector {
+    config: AccelSourceConfig,
+    /// 当前是否判定为不一致。
+    inconsistent: bool,
+    /// 与当前判定相反的连续帧数。
+    streak: u32,
+    /// 最近一帧的世界系线加速度之差，仅 `auto` 模式有值。
+    residual: Option<f64>,
+    inconsistent_count: u64,
+    /// 尚未被取走的不一致事件。
+    event: Option<AccelInconsistencyEvent>,
+}
+
+impl AccelSourceSelector {
+    /// 创建选择器。
+    pub fn new(config: AccelSourceConfig) -> Self {
+        Self {
+            config,
+            inconsistent: false,
+            streak: 0,
+            residual: None,
+            inconsistent_count: 0,
+            event: None,
+        }
+    }
+
+    /// 原地更新配置；切换来源时丢弃一致性判定，不一致计数保留。
+    pub fn reconfigure(&mut self, config: AccelSourceConfig) {
+        if config.mode != self.config.mode {
+            *self = Self {
+                inconsistent_count: self.inconsistent_count,
+                ..Self::new(config)
+            };
+        }
+        self.config = config;
+    }
+
+    /// 清空状态与不一致计数。
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// 最近一帧两路世界系线加速度之差 (m/s²)，非 `auto` 模式为 `None`。
+    pub fn residual(&self) -> Option<f64> {
+        self.residual
+    }
+
+    /// 当前是否判定为不一致。
+    pub fn is_inconsistent(&self) -> bool {
+        self.inconsistent
+    }
+
+    /// 检测到的不一致次数。
+    pub fn inconsistent_count(&self) -> u64 {
+        self.inconsistent_count
+    }
+
+    /// 取走最近一次不一致或恢复事件。
+    pub fn take_event(&mut self) -> Option<AccelInconsistencyEvent> {
+        self.event.take()
+    }
+
+    /// 按配置选出交给滤波器的加速度。
+    ///
+    /// * `sample`: 标定后的样本，加速度取自 `accel_with_g`
+    /// * `accel_no_g`: 经同一偏置与矩阵标定的设备去重力加速度
+    /// * `attitude`: 导航器本帧使用的姿态
+    /// * `gravity_ref`: 导航器当前的世界系重力参考
+    pub fn select(
+        &mut self,
+        sample: &ImuSampleCalibrated,
+        accel_no_g: DVec3,
+        attitude: DQuat,
+        gravity_ref: DVec3,
+    ) -> ImuSampleCalibrated {
+        match self.config.mode {
+            AccelSource::WithG => *sample,
+            AccelSource::NoG => ImuSampleCalibrated {
+                accel: accel_no_g + attitude.inverse().rotate_vec3(gravity_ref),
+                ..*sample
+            },
+            AccelSource::Auto => {
+                let residual =
+                    (attitude.rotate_vec3(sample.accel - accel_no_g) - gravity_ref).length();
+                self.check(sample.timestamp_ms, residual);
+                *sample
+            }
+        }
+    }
+
+    /// 按连续帧数判定进入或退出不一致。
+    fn check(&mut self, timestamp: DeviceTimeMs, residual: f64) {
+        self.residual = Some(residual);
+        let exceeded = residual > self.config.consistency_thresh_ms2;
+        if exceeded == self.inconsistent {
+            self.streak = 0;
+            return;
+        }
+        self.streak += 1;
+        if self.streak < self.config.consistency_frames {
+            return;
+        }
+        self.streak = 0;
+        self.inconsistent = exceeded;
+        if exceeded {
+            self.inconsistent_count += 1;
+            tracing::warn!(
+                "accel_with_g 与 accel_no_g 持续不一致 | 残差 {:.3} m/s²",
+                residual
+            );
+        } else {
+            tracing::info!("accel_with_g 与 accel_no_g 恢复一致");
+        }
+        self.event = Some(AccelInconsistencyEvent {
+            timestamp_ms: timestamp.as_millis(),
+            inconsistent: exceeded,
+            residual_ms2: residual,
+            inconsistent_count: self.inconsistent_count,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAVITY: DVec3 = DVec3::new(0.0, 0.0, 9.80665);
+
+    /// 第 `i` 帧的姿态、机体系含重力与去重力加速度（两路一致）。
+    fn sample(i: u64) -> (DQuat, ImuSampleCalibrated, DVec3) {
+        let t = i as f64 * 0.004;
+        let attitude = DQuat::from_rotation_z(0.8 * t) * DQuat::from_rotation_x(0.3 * t.sin());
+        let accel_world = DVec3::new((4.0 * t).sin(), 0.5 * (2.0 * t).cos(), 0.2);
+        let to_body = attitude.inverse();
+        let calibrated = ImuSampleCalibrated {
+            timestamp_ms: DeviceTimeMs(i * 4),
+            accel: to_body.rotate_vec3(accel_world + GRAVITY),
+            gyro: DVec3::ZERO,
+        };
+        (attitude, calibrated, to_body.rotate_vec3(accel_world))
+    }
+
+    #[test]
+    fn no_g_reconstructs_the_with_g_signal_on_consistent_data() {
+        let mut selector = AccelSourceSelector::new(AccelSourceConfig {
+            mode: AccelSource::NoG,
+            ..AccelSourceConfig::default()
+        });
+        for i in 0..500 {
+            let (attitude, calibrated, accel_no_g) = sample(i);
+            let selected = selector.select(&calibrated, accel_no_g, attitude, GRAVITY);
+            assert!((selected.accel - calibrated.accel).length() < 1e-9);
+        }
+        assert_eq!(selector.residual(), None);
+    }
+
+    #[test]
+    fn auto_flags_corrupted_no_g_stream_and_recovers() {
+        let config = AccelSourceConfig {
+            mode: AccelSource::Auto,
+            ..AccelSourceConfig::default()
+        };
+        let mut selector = AccelSourceSelector::new(config);
+        // 第 200 ~ 400 帧 accel_no_g 的 x 轴多出 1.5 m/s² 偏置
+        let corrupted = 200..400;
+        let mut events = Vec::new();
+        for i in 0..600 {
+            let (attitude, calibrated, mut accel_no_g) = sample(i);
+            if corrupted.contains(&i) {
+                accel_no_g.x += 1.5;
+            }
+            let selected = selector.select(&calibrated, accel_no_g, attitude, GRAVITY);
+            // auto 始终积分含重力加速度
+            assert_eq!(selected.accel, calibrated.accel);
+            if let Some(event) = selector.take_event() {
+                events.push((i, event.inconsistent, event.inconsistent_count));
+            }
+        }
+
+        let frames = u64::from(config.consistency_frames);
+        assert_eq!(
+            events,
+            [
+                (corrupted.start + frames - 1, true, 1),
+                (corrupted.end + frames - 1, false, 1),
+            ]
+        );
+        assert!(!selector.is_inconsistent());
+        assert!(selector.residual().unwrap() < 1e-9);
+    }
+}
@@ -0,0 +1,106 @@
+//! 按运动强度自适应的输出频率。
+//!
+//! 静止时前端几乎不需要更新，快速运动时又要满速率。自适应订阅给出 `min_hz`/`max_hz`，
+//! 分发线程对每一帧估计运动强度（角速度模长与线加速度模长各自按满量程归一化后相加，
+//! 截断到 0–1），强度上升立即跟随、下降按半衰期衰减，每 [`RATE_UPDATE_INTERVAL_MS`]
+//! 按强度在两者之间线性取一次频率。静止转为运动的那一帧总会立即放行并重新计时，
+//! 运动开始时不必等到下一次重算。
+
+use crate::processor::{attitude_stream::Decimator, navigator::MotionState, output::OutputFrame};
+
+/// 频率重算间隔 (ms，设备时间)。
+pub const RATE_UPDATE_INTERVAL_MS: u64 = 100;
+
+/// 角速度满量程 (rad/s)，达到时单项强度为 1。
+pub const INTENSITY_GYRO_FULL_SCALE: f64 = 3.0;
+
+/// 线加速度满量程 (m/s²)，达到时单项强度为 1。
+pub const INTENSITY_ACCEL_FULL_SCALE: f64 = 5.0;
+
+/// 强度回落的半衰期 (ms)。
+pub const INTENSITY_HALF_LIFE_MS: f64 = 500.0;
+
+/// 单个自适应订阅的频率控制。
+#[derive(Debug, Clone)]
+pub struct AdaptiveRate {
+    min_hz: f64,
+    max_hz: f64,
+    decimator: Decimator,
+    /// 平滑后的运动强度 (0–1)。
+    intensity: f64,
+    last_timestamp_ms: Option<u64>,
+    /// 下一次重算频率的设备时间戳。
+    next_update_ms: Option<u64>,
+    last_motion: MotionState,
+    /// 刚从静止转为运动，下一完整帧立即放行。
+    motion_started: bool,
+}
+
+impl AdaptiveRate {
+    /// 创建控制器，从 `min_hz` 开始；须满足 `0 < min_hz <= max_hz`。
+    pub fn new(min_hz: f64, max_hz: f64) -> Self {
+        Self {
+            min_hz,
+            max_hz,
+            decimator: Decimator::new(min_hz),
+            intensity: 0.0,
+            last_timestamp_ms: None,
+            next_update_ms: None,
+            last_motion: MotionState::Unknown,
+            motion_started: false,
+        }
+    }
+
+    /// 当前生效的频率 (Hz)。
+    pub fn rate_hz(&self) -> f64 {
+        self.decimator.rate_hz()
+    }
+
+    /// 当前平滑后的运动强度 (0–1)。
+    pub fn intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    /// 送入一帧更新运动强度，静止抑制掉、不会发出的帧也要送入。
+    pub fn observe(&mut self, frame: &OutputFrame) {
+        let timestamp_ms = frame.raw.timestamp_ms.as_millis();
+        let raw = &frame.raw;
+        let instant = (raw.gyro.length() / INTENSITY_GYRO_FULL_SCALE
+            + raw.accel_no_g.length() / INTENSITY_ACCEL_FULL_SCALE)
+            .clamp(0.0, 1.0);
+        let elapsed_ms = self
+            .last_timestamp_ms
+            .map_or(0, |last| timestamp_ms.saturating_sub(last));
+        let decayed = self.intensity * 0.5f64.powf(elapsed_ms as f64 / INTENSITY_HALF_LIFE_MS);
+        // NaN（异常样本）时保持衰减值
+        self.intensity = decayed.max(instant);
+        self.last_timestamp_ms = Some(timestamp_ms);
+
+        if self.last_motion == MotionState::Static && frame.motion_state == MotionState::Moving {
+            self.motion_started = true;
+        }
+        self.last_motion = frame.motion_state;
+
+        // 到期或时间戳回退（设备复位）时重算
+        let due = self.next_update_ms.is_none_or(|next| {
+            timestamp_ms >= next || next - timestamp_ms > RATE_UPDATE_INTERVAL_MS
+        });
+        if due || self.motion_started {
+            self.update_rate(timestamp_ms);
+        }
+    }
+
+    /// 最近一次送入的帧是否放行。
+    pub fn accept(&mut self, timestamp_ms: u64) -> bool {
+        if std::mem::take(&mut self.motion_started) {
+            self.decimator.restart();
+        }
+        self.decimator.accept(timestamp_ms)
+    }
+
+    fn update_rate(&mut self, timestamp_ms: u64) {
+        let rate_hz = self.min_hz + (self.max_hz - self.min_hz) * self.intensity;
+        self.decimator.set_rate_hz(rate_hz);
+        self.next_update_ms = Some(timestamp_ms + RATE_UPDATE_INTERVAL_MS);
+    }
+}
@@ -0,0 +1,215 @@
+//! 轨迹对齐与对比指标计算。
+
+use anyhow::bail;
+
+use crate::processor::analysis::types::{
+    AlignedPoint, AngleStats, ComparisonReport, TrajectoryPoint,
+};
+
+/// 报告中对齐轨迹的最大点数。
+pub(crate) const MAX_PLOT_POINTS: usize = 500;
+
+/// 在时间戳 `t` 处对轨迹插值。
+///
+/// 位置与速度线性插值，姿态 slerp；`track` 须按时间戳升序，
+/// `t` 超出轨迹时间范围时返回 `None`。
+pub fn interpolate_at(track: &[TrajectoryPoint], t: u64) -> Option<TrajectoryPoint> {
+    let first = track.first()?;
+    let last = track.last()?;
+    if t < first.timestamp_ms || t > last.timestamp_ms {
+        return None;
+    }
+
+    // 第一个时间戳 >= t 的点
+    let idx = track.partition_point(|p| p.timestamp_ms < t);
+    let hi = track[idx];
+    if hi.timestamp_ms == t || idx == 0 {
+        return Some(hi);
+    }
+    let lo = track[idx - 1];
+    let s = (t - lo.timestamp_ms) as f64 / (hi.timestamp_ms - lo.timestamp_ms) as f64;
+    Some(TrajectoryPoint {
+        timestamp_ms: t,
+        position: lo.position.lerp(hi.position, s),
+        velocity: lo.velocity.lerp(hi.velocity, s),
+        attitude: lo.attitude.slerp(hi.attitude, s),
+    })
+}
+
+/// 按设备时间戳对齐两段轨迹。
+///
+/// 以重叠窗口内点数较少的一段为参考时间轴，另一段插值到相同时刻。
+/// 返回 `(a, b)` 点对，顺序与输入一致；两段在时间上不重叠时返回错误。
+pub fn align_tracks(
+    a: &[TrajectoryPoint],
+    b: &[TrajectoryPoint],
+) -> anyhow::Result<Vec<(TrajectoryPoint, TrajectoryPoint)>> {
+    let (Some(a_first), Some(a_last)) = (a.first(), a.last()) else {
+        bail!("会话 A 没有样本");
+    };
+    let (Some(b_first), Some(b_last)) = (b.first(), b.last()) else {
+        bail!("会话 B 没有样本");
+    };
+
+    let start = a_first.timestamp_ms.max(b_first.timestamp_ms);
+    let end = a_last.timestamp_ms.min(b_last.timestamp_ms);
+    if start >= end {
+        bail!(
+            "两段录制在设备时间上没有重叠: A=[{}, {}] ms, B=[{}, {}] ms",
+            a_first.timestamp_ms,
+            a_last.timestamp_ms,
+            b_first.timestamp_ms,
+            b_last.timestamp_ms
+        );
+    }
+
+    let in_window = |p: &&TrajectoryPoint| p.timestamp_ms >= start && p.timestamp_ms <= end;
+    let a_count = a.iter().filter(in_window).count();
+    let b_count = b.iter().filter(in_window).count();
+
+    let pairs = if a_count <= b_count {
+        a.iter()
+            .filter(in_window)
+            .filter_map(|pa| interpolate_at(b, pa.timestamp_ms).map(|pb| (*pa, pb)))
+            .collect()
+    } else {
+        b.iter()
+            .filter(in_window)
+            .filter_map(|pb| interpolate_at(a, pb.timestamp_ms).map(|pa| (pa, *pb)))
+            .collect()
+    };
+    Ok(pairs)
+}
+
+/// 两个姿态之间的旋转夹角（度）。
+///
+/// 由四元数点积计算：`θ = 2·acos(|q_a · q_b|)`，取绝对值以消除 `q` 与 `-q` 的双覆盖。
+pub fn quat_angle_deg(a: math_f64::DQuat, b: math_f64::DQuat) -> f64 {
+    let dot = a.normalize().dot(b.normalize()).abs().min(1.0);
+    (2.0 * dot.acos()).to_degrees()
+}
+
+/// 对齐两段轨迹并计算对比报告。
+pub fn compare_tracks(
+    a: &[TrajectoryPoint],
+    b: &[TrajectoryPoint],
+) -> anyhow::Result<ComparisonReport> {
+    let pairs = align_tracks(a, b)?;
+    let (Some((first_a, first_b)), Some((last_a, last_b))) = (pairs.first(), pairs.last()) else {
+        bail!("重叠窗口内没有可对齐的样本");
+    };
+
+    let n = pairs.len() as f64;
+    let mut sq_err_sum = 0.0;
+    let mut max_velocity_diff_mps: f64 = 0.0;
+    let mut angle_sum = 0.0;
+    let mut angle_sq_sum = 0.0;
+    let mut angle_max: f64 = 0.0;
+    for (pa, pb) in &pairs {
+        sq_err_sum += pa.position.distance(pb.position).powi(2);
+        max_velocity_diff_mps = max_velocity_diff_mps.max((pa.velocity - pb.velocity).length());
+        let angle = quat_angle_deg(pa.attitude, pb.attitude);
+        angle_sum += angle;
+        angle_sq_sum += angle * angle;
+        angle_max = angle_max.max(angle);
+    }
+
+    let displacement_a = last_a.position - first_a.position;
+    let displacement_b = last_b.position - first_b.position;
+    let stride = pairs.len().div_ceil(MAX_PLOT_POINTS).max(1);
+    let trajectory = pairs
+        .iter()
+        .step_by(stride)
+        .map(|(pa, pb)| AlignedPoint {
+            timestamp_ms: pa.timestamp_ms,
+            position_a: pa.position,
+            position_b: pb.position,
+        })
+        .collect();
+
+    Ok(ComparisonReport {
+        overlap_start_ms: first_a.timestamp_ms,
+        overlap_end_ms: last_a.timestamp_ms,
+        aligned_samples: pairs.len(),
+        position_rmse_m: (sq_err_sum / n).sqrt(),
+        displacement_a,
+        displacement_b,
+        final_displacement_diff_m: (displacement_a - displacement_b).length(),
+        max_velocity_diff_mps,
+        attitude_diff: AngleStats {
+            mean_deg: angle_sum / n,
+            rms_deg: (angle_sq_sum / n).sqrt(),
+            max_deg: angle_max,
+        },
+        trajectory,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+
+    /// 沿 x 轴匀速运动的轨迹，位置整体偏移 `offset`。
+    fn line(start_ms: u64, step_ms: u64, count: u64, offset: DVec3) -> Vec<TrajectoryPoint> {
+        (0..count)
+            .map(|i| {
+                let t = start_ms + i * step_ms;
+                TrajectoryPoint {
+                    timestamp_ms: t,
+                    position: DVec3::new(t as f64 * 1e-3, 0.0, 0.0) + offset,
+                    velocity: DVec3::new(1.0, 0.0, 0.0),
+                    attitude: DQuat::IDENTITY,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn constant_offset_gives_offset_rmse() {
+        // A 4 ms 采样，B 10 ms 采样且整体偏移 (0, 0.03, 0.04)，RMSE 应为 0.05 m
+        let a = line(0, 4, 500, DVec3::ZERO);
+        let b = line(100, 10, 150, DVec3::new(0.0, 0.03, 0.04));
+
+        let report = compare_tracks(&a, &b).unwrap();
+        assert_eq!(report.overlap_start_ms, 100);
+        assert_eq!(report.overlap_end_ms, 1590);
+        assert_eq!(report.aligned_samples, 150);
+        assert!((report.position_rmse_m - 0.05).abs() < 1e-9);
+        assert!(report.final_displacement_diff_m < 1e-9);
+        assert!(report.max_velocity_diff_mps < 1e-12);
+        assert!(report.attitude_diff.max_deg < 1e-6);
+    }
+
+    #[test]
+    fn interpolation_is_linear_between_samples() {
+        let track = line(0, 10, 3, DVec3::ZERO);
+        let mid = interpolate_at(&track, 15).unwrap();
+        assert!((mid.position.x - 0.015).abs() < 1e-12);
+        assert!(interpolate_at(&track, 21).is_none());
+    }
+
+    #[test]
+    fn attitude_difference_uses_quaternion_angle() {
+        let mut a = line(0, 4, 100, DVec3::ZERO);
+        let b = line(0, 4, 100, DVec3::ZERO);
+        for p in &mut a {
+            p.attitude = DQuat::from_rotation_z(10f64.to_radians());
+        }
+
+        let report = compare_tracks(&a, &b).unwrap();
+        assert!((report.attitude_diff.mean_deg - 10.0).abs() < 1e-6);
+        assert!((report.attitude_diff.max_deg - 10.0).abs() < 1e-6);
+        // q 与 -q 表示同一姿态
+        assert!(quat_angle_deg(DQuat::IDENTITY, -DQuat::IDENTITY) < 1e-6);
+    }
+
+    #[test]
+    fn disjoint_sessions_are_rejected() {
+        let a = line(0, 4, 100, DVec3::ZERO);
+        let b = line(1000, 4, 100, DVec3::ZERO);
+        let err = compare_tracks(&a, &b).unwrap_err();
+        assert!(err.to_string().contains("没有重叠"));
+    }
+}
@@ -0,0 +1,29 @@
+//! 轨迹对比分析模块导出。
+//!
+//! 用于 A/B 评估两套 pipeline 配置：同一动作录两次，按设备时间戳对齐后比较
+//! 位置、速度与姿态。两段录制的采样时刻不会完全一致，这里以较稀疏的一段为
+//! 参考时间轴，对另一段做线性插值（姿态用 slerp）后逐点计算误差。
+//!
+//! 另含录制轨迹的离线平滑：以 ZUPT 静止段为锚点回摊残余漂移；
+//! 以及不同姿态零位下录制的轨迹之间的坐标换算。
+
+/// 对齐与指标计算逻辑。
+pub mod logic;
+/// 离线轨迹平滑。
+pub mod smooth;
+/// 标定世界系之间的轨迹换算。
+pub mod transform;
+/// 对比输入与报告类型。
+pub mod types;
+
+/// 对齐与对比函数。
+pub use logic::{align_tracks, compare_tracks, interpolate_at, quat_angle_deg};
+/// 离线平滑函数与结果类型。
+pub use smooth::{anchor_mask, count_anchors, smooth_positions, SmoothedPoint, SmoothedTrajectory};
+/// 世界系换算函数与结果类型。
+pub use transform::{
+    gravity_residual_deg, relative_rotation, rotation_deg, transform_point, TransformedPoint,
+    TransformedTrajectory,
+};
+/// 对比输入与报告类型。
+pub use types::{AlignedPoint, AngleStats, ComparisonReport, TrajectoryPoint};
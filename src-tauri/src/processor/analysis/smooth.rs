@@ -0,0 +1,223 @@
+//! 录制轨迹的离线平滑。
+//!
+//! 实时管线只能因果地积分：运动段内的加速度零偏使速度误差随时间线性增长，
+//! 到下一次 ZUPT 静止时才被发现，此时误差已经积进了位置。离线处理可以回看：
+//! 静止段的真实速度为零，锚点处的残余速度就是整段运动累积的速度误差。
+//! 按常值零偏模型把它沿时间线性回摊到前一运动段，再由修正后的速度重新积分位置，
+//! 锚点处的位置残差（`v_res · T / 2`）随之按二次曲线分摊到整段，效果类似 RTS 平滑。
+
+use math_f64::DVec3;
+use serde::Serialize;
+
+use crate::processor::analysis::types::TrajectoryPoint;
+
+/// 平滑后的轨迹点。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SmoothedPoint {
+    /// 设备时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 实时管线输出的位置 (m)。
+    pub causal_position: DVec3,
+    /// 平滑后的位置 (m)。
+    pub smoothed_position: DVec3,
+    /// 是否属于静止锚点段。
+    pub is_anchor: bool,
+}
+
+/// 一段录制的平滑结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SmoothedTrajectory {
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 重跑管线输出的帧数。
+    pub frame_count: usize,
+    /// 静止锚点段数。
+    pub anchor_count: usize,
+    /// 实时管线的最终位置 (m)。
+    pub causal_final_position: DVec3,
+    /// 平滑后的最终位置 (m)。
+    pub smoothed_final_position: DVec3,
+    /// 是否已写入 `smoothed_position_*` 列。
+    pub written: bool,
+    /// 降采样后的轨迹，供前端绘图。
+    pub points: Vec<SmoothedPoint>,
+}
+
+/// 标记静止锚点：持续不少于 `min_static_ms` 的静止段。
+///
+/// 运动中加减速换向处的短暂静止判定不可信（速度并不为零），按运动处理，
+/// 取值与运动分段检测的 `min_static_ms` 一致。
+pub fn anchor_mask(frames: &[(TrajectoryPoint, bool)], min_static_ms: f64) -> Vec<bool> {
+    let mut mask = vec![false; frames.len()];
+    let mut i = 0;
+    while i < frames.len() {
+        if !frames[i].1 {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < frames.len() && frames[i].1 {
+            i += 1;
+        }
+        let duration_ms = frames[i - 1].0.timestamp_ms - frames[start].0.timestamp_ms;
+        if duration_ms as f64 >= min_static_ms {
+            mask[start..i].fill(true);
+        }
+    }
+    mask
+}
+
+/// 锚点段数。
+pub fn count_anchors(mask: &[bool]) -> usize {
+    let mut previous = false;
+    mask.iter()
+        .filter(|&&anchor| {
+            let starts = anchor && !previous;
+            previous = anchor;
+            starts
+        })
+        .count()
+}
+
+/// 平滑整段轨迹，返回与输入一一对应的位置。
+///
+/// * `frames`: 按时间戳升序的 (轨迹点, 是否静止)，轨迹点的速度须为未经回溯修正的因果积分
+/// * `min_static_ms`: 静止段成为锚点的最短时长
+///
+/// 锚点内速度取零、位置保持；以锚点结尾的运动段按锚点前最后一帧的残余速度线性去漂移；
+/// 录制末尾没有锚点的运动段无从校正，只做重新积分。
+pub fn smooth_positions(frames: &[(TrajectoryPoint, bool)], min_static_ms: f64) -> Vec<DVec3> {
+    let Some((first, _)) = frames.first() else {
+        return Vec::new();
+    };
+    let anchors = anchor_mask(frames, min_static_ms);
+    let timestamp = |i: usize| frames[i].0.timestamp_ms;
+
+    let mut velocities: Vec<DVec3> = frames.iter().map(|(point, _)| point.velocity).collect();
+    let mut i = 0;
+    while i < frames.len() {
+        if anchors[i] {
+            velocities[i] = DVec3::ZERO;
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < frames.len() && !anchors[i] {
+            i += 1;
+        }
+        if i == frames.len() {
+            break;
+        }
+        // 运动段从上一锚点的最后一帧开始计时，此时速度误差为零
+        let t0 = timestamp(start.saturating_sub(1));
+        let span_ms = timestamp(i - 1) - t0;
+        let residual = velocities[i - 1];
+        for (k, velocity) in velocities.iter_mut().enumerate().take(i).skip(start) {
+            *velocity = if span_ms == 0 {
+                DVec3::ZERO
+            } else {
+                *velocity - residual * ((timestamp(k) - t0) as f64 / span_ms as f64)
+            };
+        }
+    }
+
+    let mut position = first.position;
+    let mut positions = Vec::with_capacity(frames.len());
+    positions.push(position);
+    for k in 1..frames.len() {
+        let dt = (timestamp(k) - timestamp(k - 1)) as f64 / 1000.0;
+        position += (velocities[k - 1] + velocities[k]) * (0.5 * dt);
+        positions.push(position);
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::DQuat;
+
+    use super::*;
+
+    const DT_MS: u64 = 4;
+
+    /// 静止 → x 向加速/减速 → 静止，重复两次；返回各帧 (真实加速度, 是否静止)。
+    fn profile() -> Vec<(f64, bool)> {
+        (0..600u64)
+            .map(|i| match i {
+                100..=174 | 350..=424 => (0.5, false),
+                175..=249 | 425..=499 => (-0.5, false),
+                _ => (0.0, true),
+            })
+            .collect()
+    }
+
+    /// 模拟因果积分：运动中加速度带常值零偏 `bias`，静止时速度清零、位置保持。
+    fn integrate(bias: f64) -> Vec<(TrajectoryPoint, bool)> {
+        let dt = DT_MS as f64 / 1000.0;
+        let mut velocity = DVec3::ZERO;
+        let mut position = DVec3::ZERO;
+        profile()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (accel, is_static))| {
+                let previous = velocity;
+                velocity = if is_static {
+                    DVec3::ZERO
+                } else {
+                    velocity + DVec3::X * ((accel + bias) * dt)
+                };
+                position += (previous + velocity) * (0.5 * dt);
+                let point = TrajectoryPoint {
+                    timestamp_ms: i as u64 * DT_MS,
+                    position,
+                    velocity,
+                    attitude: DQuat::IDENTITY,
+                };
+                (point, is_static)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn constant_accel_bias_is_removed_at_static_anchors() {
+        let truth = integrate(0.0);
+        let causal = integrate(0.05);
+        let smoothed = smooth_positions(&causal, 100.0);
+        assert_eq!(smoothed.len(), causal.len());
+
+        let true_end = truth.last().unwrap().0.position;
+        let causal_error = causal.last().unwrap().0.position.distance(true_end);
+        let smoothed_error = smoothed.last().unwrap().distance(true_end);
+        // 每段 600 ms 运动累积 b·T²/2 ≈ 9 mm
+        assert!(causal_error > 0.015, "causal error {causal_error}");
+        assert!(
+            smoothed_error * 10.0 < causal_error,
+            "causal {causal_error}, smoothed {smoothed_error}"
+        );
+
+        // 运动段中途同样贴近真值
+        let mid = 175;
+        let mid_error = smoothed[mid].distance(truth[mid].0.position);
+        assert!(mid_error * 10.0 < causal[mid].0.position.distance(truth[mid].0.position));
+    }
+
+    #[test]
+    fn short_static_blips_are_not_anchors() {
+        let mut frames = integrate(0.0);
+        // 换向处 5 帧的静止判定不足 100 ms，不作为锚点
+        for frame in &mut frames[173..178] {
+            frame.1 = true;
+        }
+        let mask = anchor_mask(&frames, 100.0);
+        assert!(mask[..100].iter().all(|&anchor| anchor));
+        assert!(!mask[100..250].iter().any(|&anchor| anchor));
+        assert!(mask[250..350].iter().all(|&anchor| anchor));
+        assert_eq!(count_anchors(&mask), 3);
+
+        // 没有零偏时平滑不改变轨迹
+        let smoothed = smooth_positions(&frames, 100.0);
+        for ((point, _), position) in frames.iter().zip(&smoothed) {
+            assert!(point.position.distance(*position) < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! 录制轨迹在不同标定世界系之间的换算。
+//!
+//! 导航系是设备自身参考系左乘姿态零位 `q` 得到的：`p = q · p_dev`。同一台设备中途
+//! 重新校准零位后，前后两段录制的世界系相差 `q_B · q_A⁻¹`，把 A 的位置、速度按该相对
+//! 旋转转动、姿态左乘该相对旋转，即可在 B 的世界系中与 B 叠加。
+
+use math_f64::{DQuat, DVec3};
+use serde::Serialize;
+
+use crate::{
+    processor::analysis::{logic::quat_angle_deg, types::TrajectoryPoint},
+    types::recording::RecordingCalibration,
+};
+
+/// 从 `from` 的世界系到 `to` 的世界系的相对旋转。
+pub fn relative_rotation(from: &RecordingCalibration, to: &RecordingCalibration) -> DQuat {
+    (to.axis_quat_offset.normalize() * from.axis_quat_offset.normalize().inverse()).normalize()
+}
+
+/// 把一个轨迹点换算到相对旋转 `rotation` 之后的世界系。
+pub fn transform_point(point: &TrajectoryPoint, rotation: DQuat) -> TrajectoryPoint {
+    TrajectoryPoint {
+        timestamp_ms: point.timestamp_ms,
+        position: rotation.rotate_vec3(point.position),
+        velocity: rotation.rotate_vec3(point.velocity),
+        attitude: (rotation * point.attitude).normalize(),
+    }
+}
+
+/// 换算后仍存在的重力方向偏差（度）：A 的重力参考转到 B 系后与 B 的重力参考的夹角。
+///
+/// 两次零位都在水平放置时完成的话接近 0；偏差较大说明零位时设备没有放平，
+/// 换算后的高度方向会带上倾斜。
+pub fn gravity_residual_deg(from: &RecordingCalibration, to: &RecordingCalibration) -> f64 {
+    let rotated = relative_rotation(from, to).rotate_vec3(from.gravity_ref);
+    if rotated.length() == 0.0 || to.gravity_ref.length() == 0.0 {
+        return 0.0;
+    }
+    rotated.angle_between(to.gravity_ref).to_degrees()
+}
+
+/// 换算后的轨迹点。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TransformedPoint {
+    /// 设备时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 原世界系中的位置 (m)。
+    pub original_position: DVec3,
+    /// 目标世界系中的位置 (m)。
+    pub position: DVec3,
+    /// 目标世界系中的姿态。
+    pub attitude: DQuat,
+}
+
+/// 一段录制换算到另一段录制标定系的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct TransformedTrajectory {
+    /// 被换算的会话 ID。
+    pub session_id: i64,
+    /// 提供目标世界系的会话 ID。
+    pub target_session_id: i64,
+    /// 相对旋转。
+    pub relative_rotation: DQuat,
+    /// 相对旋转的角度（度）。
+    pub rotation_deg: f64,
+    /// 换算后的重力方向偏差（度），见 [`gravity_residual_deg`]。
+    pub gravity_residual_deg: f64,
+    /// 换算的样本数。
+    pub frame_count: usize,
+    /// 是否已写入 `transformed_*` 列。
+    pub written: bool,
+    /// 降采样后的轨迹，供前端绘图。
+    pub points: Vec<TransformedPoint>,
+}
+
+/// 相对旋转的角度（度）。
+pub fn rotation_deg(rotation: DQuat) -> f64 {
+    quat_angle_deg(DQuat::IDENTITY, rotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn calibration(axis_quat_offset: DQuat) -> RecordingCalibration {
+        RecordingCalibration {
+            axis_quat_offset,
+            gravity_ref: axis_quat_offset.rotate_vec3(DVec3::new(0.0, 0.0, 9.80665)),
+        }
+    }
+
+    #[test]
+    fn transform_maps_device_frame_points_between_epochs() {
+        let a = calibration(DQuat::from_rotation_z(0.3));
+        let b = calibration(DQuat::from_rotation_z(0.3 + FRAC_PI_2));
+        let rotation = relative_rotation(&a, &b);
+        assert!((rotation_deg(rotation) - 90.0).abs() < 1e-9);
+        assert!(gravity_residual_deg(&a, &b) < 1e-9);
+
+        // 同一个设备系中的点分别在两个世界系下表达
+        let device_position = DVec3::new(1.0, 2.0, 0.5);
+        let device_attitude = DQuat::from_euler_zyx(0.1, -0.2, 0.7);
+        let in_a = TrajectoryPoint {
+            timestamp_ms: 42,
+            position: a.axis_quat_offset.rotate_vec3(device_position),
+            velocity: a.axis_quat_offset.rotate_vec3(DVec3::X),
+            attitude: a.axis_quat_offset * device_attitude,
+        };
+        let in_b_position = b.axis_quat_offset.rotate_vec3(device_position);
+        let in_b_attitude = b.axis_quat_offset * device_attitude;
+
+        let transformed = transform_point(&in_a, rotation);
+        assert_eq!(transformed.timestamp_ms, 42);
+        assert!((transformed.position - in_b_position).length() < 1e-12);
+        assert!((transformed.velocity - b.axis_quat_offset.rotate_vec3(DVec3::X)).length() < 1e-12);
+        assert!(quat_angle_deg(transformed.attitude, in_b_attitude) < 1e-6);
+
+        // 反向换算回到原值
+        let back = transform_point(&transformed, relative_rotation(&b, &a));
+        assert!((back.position - in_a.position).length() < 1e-12);
+    }
+
+    #[test]
+    fn gravity_residual_reports_tilted_calibration() {
+        let a = calibration(DQuat::IDENTITY);
+        let mut b = calibration(DQuat::from_rotation_z(FRAC_PI_2));
+        // B 的零位在设备倾斜 5° 时完成
+        b.gravity_ref = DQuat::from_rotation_x(5f64.to_radians()).rotate_vec3(b.gravity_ref);
+        assert!((gravity_residual_deg(&a, &b) - 5.0).abs() < 1e-9);
+    }
+}
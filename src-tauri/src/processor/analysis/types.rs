@@ -0,0 +1,77 @@
+//! 轨迹对比相关类型。
+
+use math_f64::{DQuat, DVec3};
+use serde::Serialize;
+
+use crate::types::outputs::ResponseData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// 参与对比的轨迹点。
+pub struct TrajectoryPoint {
+    /// 设备时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 位置 (m)。
+    pub position: DVec3,
+    /// 速度 (m/s)。
+    pub velocity: DVec3,
+    /// 姿态。
+    pub attitude: DQuat,
+}
+
+impl From<&ResponseData> for TrajectoryPoint {
+    fn from(data: &ResponseData) -> Self {
+        Self {
+            timestamp_ms: data.timestamp_ms,
+            position: data.position,
+            velocity: data.velocity,
+            attitude: data.attitude,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+/// 对齐后的一对位置，用于前端叠加绘制。
+pub struct AlignedPoint {
+    /// 参考时间轴上的设备时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 会话 A 的位置 (m)。
+    pub position_a: DVec3,
+    /// 会话 B 的位置 (m)。
+    pub position_b: DVec3,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+/// 姿态夹角统计（度）。
+pub struct AngleStats {
+    /// 平均夹角。
+    pub mean_deg: f64,
+    /// 均方根夹角。
+    pub rms_deg: f64,
+    /// 最大夹角。
+    pub max_deg: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 两段轨迹的对比报告。
+pub struct ComparisonReport {
+    /// 重叠窗口起点（设备时间戳，毫秒）。
+    pub overlap_start_ms: u64,
+    /// 重叠窗口终点（设备时间戳，毫秒）。
+    pub overlap_end_ms: u64,
+    /// 参与计算的对齐点数。
+    pub aligned_samples: usize,
+    /// 位置 RMSE (m)。
+    pub position_rmse_m: f64,
+    /// 会话 A 在重叠窗口内的位移 (m)。
+    pub displacement_a: DVec3,
+    /// 会话 B 在重叠窗口内的位移 (m)。
+    pub displacement_b: DVec3,
+    /// 两段位移之差的模长 (m)。
+    pub final_displacement_diff_m: f64,
+    /// 速度差模长的最大值 (m/s)。
+    pub max_velocity_diff_mps: f64,
+    /// 姿态夹角统计。
+    pub attitude_diff: AngleStats,
+    /// 降采样后的对齐轨迹，供前端绘图。
+    pub trajectory: Vec<AlignedPoint>,
+}
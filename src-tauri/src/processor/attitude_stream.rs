@@ -0,0 +1,214 @@
+//! 高频姿态流。
+//!
+//! 3D 姿态视图需要完整的 250 Hz 姿态才显得顺滑，而位置/速度 30 Hz 就够；
+//! 以 250 Hz 推送完整 `ResponseData` 给 webview 代价太高（主要是位置与原始数据的 JSON）。
+//! 姿态流只携带序号、时间戳和四元数数组，由输出分发线程按各订阅的最高频率抽取后
+//! 放入订阅队列（见 [`crate::processor::fanout`]）。
+
+use serde::Serialize;
+
+use crate::processor::output::OutputFrame;
+
+/// 订阅端队列容量，前端跟不上时丢旧帧而不是积压。
+pub const ATTITUDE_CHANNEL_CAPACITY: usize = 64;
+
+/// 姿态流单帧。
+///
+/// 四元数为 `[x, y, z, w]` 数组并降为 f32，序列化后约 80 字节；
+/// `seq` 按发出顺序递增，前端据此发现丢帧。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AttitudeFrame {
+    /// 本次订阅内的发出序号，从 0 开始。
+    pub seq: u64,
+    /// 设备时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 姿态四元数 `[x, y, z, w]`。
+    pub quat: [f32; 4],
+}
+
+impl AttitudeFrame {
+    /// 由输出帧生成序号为 `seq` 的姿态帧。
+    pub fn from_frame(seq: u64, frame: &OutputFrame) -> Self {
+        let quat = frame.nav.attitude;
+        Self {
+            seq,
+            timestamp_ms: frame.raw.timestamp_ms.as_millis(),
+            quat: [quat.x as f32, quat.y as f32, quat.z as f32, quat.w as f32],
+        }
+    }
+}
+
+/// 按设备时间戳抽取帧，使输出频率不超过 `max_rate_hz`。
+///
+/// 以理想发送时刻累加而不是与上一帧比较间隔，250 Hz 抽到 100 Hz 时平均仍为 100 Hz，
+/// 不会退化成每 3 帧取 1 帧；时间戳回退或长时间中断后从当前帧重新计时。
+#[derive(Debug, Clone)]
+pub struct Decimator {
+    interval_ms: f64,
+    next_due_ms: Option<f64>,
+}
+
+impl Decimator {
+    /// 创建抽取器，`max_rate_hz` 须为正数。
+    pub fn new(max_rate_hz: f64) -> Self {
+        Self {
+            interval_ms: 1000.0 / max_rate_hz,
+            next_due_ms: None,
+        }
+    }
+
+    /// 当前最高频率 (Hz)。
+    pub fn rate_hz(&self) -> f64 {
+        1000.0 / self.interval_ms
+    }
+
+    /// 调整最高频率，`max_rate_hz` 须为正数；下一次放行时刻按新间隔从上一次放行重新计算。
+    pub fn set_rate_hz(&mut self, max_rate_hz: f64) {
+        let interval_ms = 1000.0 / max_rate_hz;
+        if let Some(due) = self.next_due_ms.as_mut() {
+            *due += interval_ms - self.interval_ms;
+        }
+        self.interval_ms = interval_ms;
+    }
+
+    /// 从下一帧重新计时，下一帧必定放行。
+    pub fn restart(&mut self) {
+        self.next_due_ms = None;
+    }
+
+    /// 该时间戳的帧是否放行。
+    pub fn accept(&mut self, timestamp_ms: u64) -> bool {
+        let t = timestamp_ms as f64;
+        match self.next_due_ms {
+            Some(due) if t >= due - self.interval_ms && t < due + self.interval_ms => {
+                if t < due {
+                    return false;
+                }
+                self.next_due_ms = Some(due + self.interval_ms);
+                true
+            }
+            _ => {
+                self.next_due_ms = Some(t + self.interval_ms);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::processor::{
+        navigator::{MotionState, NavState, ZuptState},
+        parser::ImuSampleRaw,
+        shared::DeviceTimeMs,
+    };
+
+    fn frame(timestamp_ms: u64) -> OutputFrame {
+        let attitude = DQuat::from_rotation_z(timestamp_ms as f64 * 1e-3);
+        OutputFrame {
+            raw: ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_no_g: DVec3::ZERO,
+                accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+                gyro: DVec3::ZERO,
+                quat: attitude,
+                angle: DVec3::ZERO,
+                offset: DVec3::ZERO,
+                accel_nav: DVec3::ZERO,
+                baro: None,
+            },
+            nav: NavState {
+                timestamp_ms,
+                position: DVec3::ZERO,
+                velocity: DVec3::ZERO,
+                attitude,
+            },
+            motion_state: MotionState::Moving,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            clock_sync: None,
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: None,
+            debug_vectors: None,
+            stages: None,
+        }
+    }
+
+    /// 以 250 Hz 送入 `seconds` 秒，返回放行的帧数。
+    fn accepted_at(max_rate_hz: f64, seconds: u64) -> usize {
+        let mut decimator = Decimator::new(max_rate_hz);
+        (0..seconds * 250)
+            .filter(|i| decimator.accept(1_000 + i * 4))
+            .count()
+    }
+
+    #[test]
+    fn decimation_follows_requested_rate() {
+        assert_eq!(accepted_at(250.0, 2), 500);
+        // 高于源频率时全部放行
+        assert_eq!(accepted_at(1000.0, 2), 500);
+        assert_eq!(accepted_at(125.0, 2), 250);
+        // 非整除的频率按平均速率放行
+        assert_eq!(accepted_at(100.0, 2), 200);
+        assert_eq!(accepted_at(60.0, 2), 120);
+        assert_eq!(accepted_at(30.0, 2), 60);
+    }
+
+    #[test]
+    fn decimation_restarts_after_gap_or_reset() {
+        let mut decimator = Decimator::new(100.0);
+        assert!(decimator.accept(1_000));
+        assert!(!decimator.accept(1_004));
+        assert!(!decimator.accept(1_008));
+        assert!(decimator.accept(1_012));
+        // 中断 5 s 后立即放行，不补发积压的时刻
+        assert!(decimator.accept(6_000));
+        assert!(!decimator.accept(6_004));
+        assert!(decimator.accept(6_012));
+        // 设备复位导致时间戳回退
+        assert!(decimator.accept(0));
+        assert!(!decimator.accept(4));
+    }
+
+    #[test]
+    fn attitude_frame_carries_nav_attitude() {
+        let data = frame(1_234);
+        let attitude = AttitudeFrame::from_frame(7, &data);
+        assert_eq!(attitude.seq, 7);
+        assert_eq!(attitude.timestamp_ms, 1_234);
+        let expected = data.nav.attitude;
+        assert!((attitude.quat[2] - expected.z as f32).abs() < 1e-6);
+        assert!((attitude.quat[3] - expected.w as f32).abs() < 1e-6);
+    }
+
+    #[test]
+    fn serialized_frame_is_compact() {
+        let worst = AttitudeFrame {
+            seq: 123_456_789,
+            timestamp_ms: 4_294_967_295,
+            quat: [
+                -0.123_456_79,
+                -0.987_654_3,
+                -0.000_012_345_678,
+                -0.707_106_77,
+            ],
+        };
+        let json = serde_json::to_string(&worst).unwrap();
+        assert!(json.len() < 120, "{} bytes: {json}", json.len());
+        assert!(json.contains("\"quat\":["), "{json}");
+
+        let quat = DQuat::from_rotation_x(1.0) * DQuat::from_rotation_z(-2.0);
+        for i in 0..1_000 {
+            let mut data = frame(i * 4);
+            data.nav.attitude = quat * DQuat::from_rotation_y(i as f64 * 0.01);
+            let json = serde_json::to_string(&AttitudeFrame::from_frame(i, &data)).unwrap();
+            assert!(json.len() < 120, "{} bytes: {json}", json.len());
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! 发往前端的消息攒批。
+//!
+//! 高频率下每帧一条 IPC 消息，webview 主线程要为每条消息各派发一次回调。订阅按批发送：
+//! 一批的第一条消息进入后最多等待窗口时长，或攒满条数上限即发出，批内保持到达顺序。
+//! 因此攒批带来的额外延迟不超过窗口时长；订阅结束时未满的一批也会发出。
+//!
+//! 时钟由调用方传入，测试中用构造的 [`Instant`] 代替真实时间。
+
+use std::time::{Duration, Instant};
+
+/// 默认攒批窗口 (ms)，约一帧 60 Hz 画面。
+pub const DEFAULT_BATCH_WINDOW_MS: u64 = 16;
+
+/// 默认每批最多条数。
+pub const DEFAULT_BATCH_MAX_LEN: usize = 8;
+
+/// 攒批窗口上限 (ms)。
+pub const MAX_BATCH_WINDOW_MS: u64 = 100;
+
+/// 每批条数上限。
+pub const MAX_BATCH_LEN: usize = 64;
+
+/// 单个订阅的攒批状态。
+#[derive(Debug)]
+pub struct Batcher<T> {
+    window: Duration,
+    max_len: usize,
+    pending: Vec<T>,
+    /// 当前一批最晚的发出时刻，没有待发消息时为空。
+    deadline: Option<Instant>,
+}
+
+impl<T> Batcher<T> {
+    /// 创建攒批器，`max_len` 至少为 1。
+    pub fn new(window: Duration, max_len: usize) -> Self {
+        let max_len = max_len.max(1);
+        Self {
+            window,
+            max_len,
+            pending: Vec::with_capacity(max_len),
+            deadline: None,
+        }
+    }
+
+    /// 每批最多条数。
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// 当前一批必须发出的时刻，没有待发消息时为空。
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// 加入一条消息，攒满时返回整批。
+    pub fn push(&mut self, item: T, now: Instant) -> Option<Vec<T>> {
+        if self.pending.is_empty() {
+            self.deadline = Some(now + self.window);
+        }
+        self.pending.push(item);
+        (self.pending.len() >= self.max_len).then(|| self.take())
+    }
+
+    /// 窗口到期时返回未满的一批。
+    pub fn poll(&mut self, now: Instant) -> Option<Vec<T>> {
+        self.deadline
+            .is_some_and(|deadline| now >= deadline)
+            .then(|| self.take())
+    }
+
+    /// 立即发出待发消息（订阅结束时），没有时为空。
+    pub fn flush(&mut self) -> Option<Vec<T>> {
+        (!self.pending.is_empty()).then(|| self.take())
+    }
+
+    fn take(&mut self) -> Vec<T> {
+        self.deadline = None;
+        std::mem::replace(&mut self.pending, Vec::with_capacity(self.max_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(base: Instant, offset_ms: u64) -> Instant {
+        base + Duration::from_millis(offset_ms)
+    }
+
+    #[test]
+    fn flushes_on_size_timer_and_removal() {
+        let base = Instant::now();
+        let mut batcher = Batcher::new(Duration::from_millis(16), 3);
+
+        // 攒满即发
+        assert_eq!(batcher.push(1, ms(base, 0)), None);
+        assert_eq!(batcher.push(2, ms(base, 1)), None);
+        assert_eq!(batcher.deadline(), Some(ms(base, 16)));
+        assert_eq!(batcher.push(3, ms(base, 2)), Some(vec![1, 2, 3]));
+        assert_eq!(batcher.deadline(), None);
+
+        // 窗口从下一批的第一条算起，到期前不发
+        assert_eq!(batcher.push(4, ms(base, 10)), None);
+        assert_eq!(batcher.poll(ms(base, 25)), None);
+        assert_eq!(batcher.poll(ms(base, 26)), Some(vec![4]));
+        assert_eq!(batcher.poll(ms(base, 100)), None);
+
+        // 订阅注销时发出未满的一批
+        assert_eq!(batcher.push(5, ms(base, 200)), None);
+        assert_eq!(batcher.flush(), Some(vec![5]));
+        assert_eq!(batcher.flush(), None);
+    }
+
+    #[test]
+    fn no_item_is_lost_or_reordered_and_latency_is_bounded() {
+        let base = Instant::now();
+        let window_ms = 16;
+        let mut batcher = Batcher::new(Duration::from_millis(window_ms), 8);
+        let mut batches = Vec::new();
+        let mut arrivals = Vec::new();
+        let mut now_ms = 0;
+        for seq in 0..500u64 {
+            // 时快时慢的到达间隔：突发、常速与长间隔交替
+            now_ms += [0, 1, 4, 30][(seq % 7 % 4) as usize];
+            // 调用方在每个到期时刻醒来
+            if let Some(deadline) = batcher.deadline() {
+                if deadline <= ms(base, now_ms) {
+                    let batch = batcher.poll(deadline).unwrap();
+                    batches.push((batch, deadline));
+                }
+            }
+            arrivals.push(ms(base, now_ms));
+            if let Some(batch) = batcher.push(seq, ms(base, now_ms)) {
+                batches.push((batch, ms(base, now_ms)));
+            }
+        }
+        if let Some(batch) = batcher.flush() {
+            batches.push((batch, ms(base, now_ms)));
+        }
+
+        let flat: Vec<u64> = batches
+            .iter()
+            .flat_map(|(b, _)| b.iter().copied())
+            .collect();
+        assert_eq!(flat, (0..500).collect::<Vec<_>>());
+        for (batch, sent_at) in &batches {
+            assert!(!batch.is_empty() && batch.len() <= 8);
+            let first_arrival = arrivals[batch[0] as usize];
+            assert!(*sent_at - first_arrival <= Duration::from_millis(window_ms));
+        }
+        assert!(batches.len() < 500 / 2);
+    }
+}
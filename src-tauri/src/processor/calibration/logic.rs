@@ -1,17 +1,18 @@
 //! 标定逻辑实现。
 
-use math_f64::DVec3;
+use math_f64::{DQuat, DVec3};
 
 use crate::processor::{
     calibration::types::{
-        AxisCalibration, CalibrationState, ImuCalibrationConfig, ImuSampleCalibrated,
+        AxisCalibration, AxisRemap, CalibrationQuality, CalibrationQualityConfig, CalibrationState,
+        GyroUnit, ImuCalibrationConfig, ImuSampleCalibrated,
     },
     parser::ImuSampleRaw,
+    shared::{wrap_degrees, DeviceTimeMs, RunningStats},
 };
 
-const DEG_TO_RAD: f64 = std::f64::consts::PI / 180.0;
-
 /// 标定处理器。
+#[derive(Clone)]
 pub struct Calibration {
     config: ImuCalibrationConfig,
     state: CalibrationState,
@@ -31,25 +32,28 @@ impl Calibration {
     ///
     /// 返回:
     /// - 标定后的样本（去偏置 + 标定矩阵 + 角速度转弧度）。
+    ///   passby 模式下只做单位换算，下游始终拿到 rad/s。
+    ///
+    /// `raw` 须已经过 [`remap_axes`](Self::remap_axes)，偏置与矩阵均在外壳系下给出。
     ///
     /// 公式:
     /// - `a = M_a * (a_raw - b_a)`
-    /// - `w = M_g * ((gyro_deg * deg_to_rad) - b_g)`
+    /// - `w = M_g * ((gyro_raw * unit_to_rad) - b_g)`
     pub fn update(&mut self, raw: &ImuSampleRaw) -> ImuSampleCalibrated {
+        let gyro_rad = raw.gyro * self.config.gyro_unit.to_rad_per_sec();
         if self.config.passby {
             return ImuSampleCalibrated {
                 timestamp_ms: raw.timestamp_ms,
                 accel: raw.accel_with_g,
-                gyro: raw.gyro,
+                gyro: gyro_rad,
             };
         }
 
-        // 先去偏置再做矩阵标定，并将角速度转为 rad/s
+        // 先去偏置再做矩阵标定
         let accel = apply_matrix(
             self.config.accel_matrix,
             raw.accel_with_g - self.state.bias_a,
         );
-        let gyro_rad = raw.gyro * DEG_TO_RAD;
         let gyro = apply_matrix(self.config.gyro_matrix, gyro_rad - self.state.bias_g);
 
         ImuSampleCalibrated {
@@ -59,6 +63,26 @@ impl Calibration {
         }
     }
 
+    /// 把原始样本从传感器系重映射到外壳系（安装方向）。
+    ///
+    /// 在姿态零位校准与去偏置之前调用，passby 模式下同样生效：
+    /// 安装方向属于坐标系定义而非标定误差。加速度（含/不含重力）按 `accel_map`，
+    /// 角速度按 `gyro_map`；姿态四元数与欧拉角按 `accel_map` 同步重映射
+    /// （`q_body = q_sensor * q_R⁻¹`），保证原始输出、标定输出与导航结果一致。
+    pub fn remap_axes(&self, raw: &mut ImuSampleRaw) {
+        let accel_map = self.config.accel_map;
+        if !accel_map.is_identity() {
+            raw.accel_no_g = accel_map.apply(raw.accel_no_g);
+            raw.accel_with_g = accel_map.apply(raw.accel_with_g);
+            raw.quat = (raw.quat * accel_map.quat().inverse()).normalize();
+            raw.angle = euler_zyx_deg(raw.quat);
+        }
+        let gyro_map = self.config.gyro_map;
+        if !gyro_map.is_identity() {
+            raw.gyro = gyro_map.apply(raw.gyro);
+        }
+    }
+
     /// 重置标定状态。
     pub fn reset(&mut self) {
         self.state = CalibrationState::new(&self.config);
@@ -75,6 +99,22 @@ impl Calibration {
         }
     }
 
+    /// 对已去偏置的 `accel_no_g` 应用加速度标定矩阵（passby 模式下原样返回）。
+    ///
+    /// 与 [`update`](Self::update) 中的含重力加速度经同一矩阵修正，两路可以直接比较。
+    pub fn calibrate_accel_no_g(&self, accel_no_g: DVec3) -> DVec3 {
+        if self.config.passby {
+            accel_no_g
+        } else {
+            apply_matrix(self.config.accel_matrix, accel_no_g)
+        }
+    }
+
+    /// 返回原始角速度单位。
+    pub fn gyro_unit(&self) -> GyroUnit {
+        self.config.gyro_unit
+    }
+
     /// 返回当前陀螺仪偏置 (rad/s)。
     ///
     /// 用于诊断系统观测在线零偏估计的演化。
@@ -108,17 +148,28 @@ impl AxisCalibration {
     /// - `raw`: 原始 IMU 样本（会被就地修改）。
     ///
     /// 公式:
-    /// - `angle' = angle - angle_offset`
+    /// - `angle' = wrap(angle - angle_offset)`，回绕到 (-180°, 180°]
     /// - `quat' = quat_offset * quat`
     pub fn apply(&self, raw: &mut ImuSampleRaw) {
-        raw.angle -= self.angle_offset;
+        raw.angle = (raw.angle - self.angle_offset).map(wrap_degrees);
         raw.quat = self.quat_offset * raw.quat;
     }
 
+    /// 把设备世界系下的向量（如固件输出的位置 `offset`）转到零位校准后的导航系。
+    ///
+    /// 与 [`apply`](Self::apply) 对姿态的左乘是同一个旋转：姿态积分出的位置
+    /// 与经此转换的设备位置处于同一参考系。
+    pub fn to_nav_frame(&self, v: DVec3) -> DVec3 {
+        self.quat_offset.rotate_vec3(v)
+    }
+
     /// 以当前原始姿态更新零位校准参数。
+    ///
+    /// 设备四元数经过定点量化，模长不精确为 1；先归一化再取逆，
+    /// 偏移本身保持单位长度，否则每帧相乘都会把偏差带进姿态。
     pub fn update_from_raw(&mut self, raw: &ImuSampleRaw) {
         self.angle_offset = raw.angle;
-        self.quat_offset = raw.quat.inverse();
+        self.quat_offset = raw.quat.normalize().inverse();
     }
 
     /// 清空姿态零位校准。
@@ -127,6 +178,188 @@ impl AxisCalibration {
     }
 }
 
+impl AxisRemap {
+    /// 由 3x3 矩阵构造，要求正交且行列式为 +1。
+    pub fn from_matrix(matrix: [[f64; 3]; 3]) -> Result<Self, String> {
+        const TOL: f64 = 1e-6;
+        for i in 0..3 {
+            for j in 0..3 {
+                let dot: f64 = (0..3).map(|k| matrix[i][k] * matrix[j][k]).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                if (dot - expected).abs() > TOL {
+                    return Err(format!(
+                        "轴映射矩阵不正交（第 {i} 行与第 {j} 行点积为 {dot:.6}），每个外壳轴必须对应唯一的传感器轴"
+                    ));
+                }
+            }
+        }
+        let det = matrix[0][0] * (matrix[1][1] * matrix[2][2] - matrix[1][2] * matrix[2][1])
+            - matrix[0][1] * (matrix[1][0] * matrix[2][2] - matrix[1][2] * matrix[2][0])
+            + matrix[0][2] * (matrix[1][0] * matrix[2][1] - matrix[1][1] * matrix[2][0]);
+        if (det - 1.0).abs() > TOL {
+            return Err(format!(
+                "轴映射行列式为 {det:.3}，不是真旋转（镜像映射会让右手系变成左手系）"
+            ));
+        }
+        Ok(Self { matrix })
+    }
+
+    /// 是否为恒等映射。
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 把传感器系向量映射到外壳系。
+    pub fn apply(&self, v: DVec3) -> DVec3 {
+        apply_matrix(self.matrix, v)
+    }
+
+    /// 映射矩阵对应的旋转四元数。
+    pub fn quat(&self) -> DQuat {
+        let m = self.matrix;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let (x, y, z, w) = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            (
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+                0.25 * s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            (
+                0.25 * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[2][1] - m[1][2]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            (
+                (m[0][1] + m[1][0]) / s,
+                0.25 * s,
+                (m[1][2] + m[2][1]) / s,
+                (m[0][2] - m[2][0]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            (
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                0.25 * s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        };
+        DQuat::from_xyzw(x, y, z, w).normalize()
+    }
+}
+
+/// 零位校准前的质量采样窗口。
+///
+/// 累计加速度模长与角速度模长的流式统计量，
+/// 窗口跨度达到配置时长后给出 [`CalibrationQuality`]。
+pub struct CalibrationQualitySampler {
+    config: CalibrationQualityConfig,
+    gravity: f64,
+    gyro_to_rad: f64,
+    first_ms: Option<DeviceTimeMs>,
+    /// 窗口已覆盖的时长 (ms)。
+    elapsed_ms: u64,
+    accel_norm: RunningStats,
+    gyro_norm: RunningStats,
+}
+
+impl CalibrationQualitySampler {
+    /// 加速度模长方差的满分上限 ((m/s²)²)，即标准差 0.1 m/s²。
+    const ACCEL_VARIANCE_LIMIT: f64 = 0.01;
+    /// 角速度模长均值的满分上限 (rad/s)，约 3 °/s。
+    const GYRO_MEAN_LIMIT: f64 = 0.05;
+    /// 重力偏差的满分上限 (m/s²)。
+    const GRAVITY_DEVIATION_LIMIT: f64 = 0.5;
+    /// 各项权重：加速度方差、角速度均值、重力偏差。
+    const WEIGHTS: [f64; 3] = [0.4, 0.4, 0.2];
+
+    /// 创建采样窗口。
+    ///
+    /// `gyro_unit` 为原始角速度单位，采样在标定换算之前进行。
+    pub fn new(config: CalibrationQualityConfig, gravity: f64, gyro_unit: GyroUnit) -> Self {
+        Self {
+            config,
+            gravity,
+            gyro_to_rad: gyro_unit.to_rad_per_sec(),
+            first_ms: None,
+            elapsed_ms: 0,
+            accel_norm: RunningStats::new(),
+            gyro_norm: RunningStats::new(),
+        }
+    }
+
+    /// 加入一帧已重映射的原始样本，窗口跨度达到配置时长时返回 `true`。
+    pub fn push(&mut self, raw: &ImuSampleRaw) -> bool {
+        let first_ms = *self.first_ms.get_or_insert(raw.timestamp_ms);
+        self.elapsed_ms = raw.timestamp_ms.delta_ms(first_ms);
+        self.accel_norm.push(raw.accel_with_g.length());
+        self.gyro_norm.push(raw.gyro.length() * self.gyro_to_rad);
+        self.accel_norm.count() >= 2 && self.elapsed_ms >= self.config.window_ms
+    }
+
+    /// 窗口已覆盖的时长 (ms)。
+    pub fn elapsed_ms(&self) -> u64 {
+        self.elapsed_ms
+    }
+
+    /// 窗口配置时长 (ms)。
+    pub fn required_ms(&self) -> u64 {
+        self.config.window_ms
+    }
+
+    /// 按当前窗口计算质量评分，`applied` 按分数与配置判定。
+    pub fn quality(&self) -> CalibrationQuality {
+        let accel_norm_variance = self.accel_norm.variance();
+        let gyro_norm_mean = self.gyro_norm.mean();
+        let gravity_deviation = (self.accel_norm.mean() - self.gravity).abs();
+        let score = Self::score(accel_norm_variance, gyro_norm_mean, gravity_deviation);
+        CalibrationQuality {
+            score,
+            accel_norm_variance,
+            gyro_norm_mean,
+            gravity_deviation,
+            sample_count: self.accel_norm.count() as usize,
+            min_score: self.config.min_score,
+            applied: !self.config.reject_below_min || score >= self.config.min_score,
+        }
+    }
+
+    /// 综合分数 (0–100)。
+    ///
+    /// 每项按 `clamp(1 - x / limit, 0, 1)` 线性折算，再按权重求和：
+    /// - 加速度模长方差 40%，上限 0.01 (m/s²)²：手持晃动、振动；
+    /// - 角速度模长均值 40%，上限 0.05 rad/s：转动中校准；
+    /// - 重力偏差 20%，上限 0.5 m/s²：持续加速或加速度计量程、单位异常。
+    pub fn score(accel_norm_variance: f64, gyro_norm_mean: f64, gravity_deviation: f64) -> f64 {
+        let parts = [
+            accel_norm_variance / Self::ACCEL_VARIANCE_LIMIT,
+            gyro_norm_mean / Self::GYRO_MEAN_LIMIT,
+            gravity_deviation / Self::GRAVITY_DEVIATION_LIMIT,
+        ];
+        100.0
+            * parts
+                .iter()
+                .zip(Self::WEIGHTS)
+                .map(|(ratio, weight)| weight * (1.0 - ratio).clamp(0.0, 1.0))
+                .sum::<f64>()
+    }
+}
+
+/// 四元数转 Z-Y-X 欧拉角（度），与 IMU 固件输出约定一致：x/z ∈ ±180°，y ∈ ±90°。
+pub(crate) fn euler_zyx_deg(q: DQuat) -> DVec3 {
+    let roll = (2.0 * (q.w * q.x + q.y * q.z)).atan2(1.0 - 2.0 * (q.x * q.x + q.y * q.y));
+    let pitch = (2.0 * (q.w * q.y - q.z * q.x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (q.w * q.z + q.x * q.y)).atan2(1.0 - 2.0 * (q.y * q.y + q.z * q.z));
+    DVec3::new(roll, pitch, yaw).map(|rad| wrap_degrees(rad.to_degrees()))
+}
+
 fn apply_matrix(matrix: [[f64; 3]; 3], v: DVec3) -> DVec3 {
     // 3x3 矩阵乘向量
     let x = matrix[0][0] * v.x + matrix[0][1] * v.y + matrix[0][2] * v.z;
@@ -134,3 +367,167 @@ fn apply_matrix(matrix: [[f64; 3]; 3], v: DVec3) -> DVec3 {
     let z = matrix[2][0] * v.x + matrix[2][1] * v.y + matrix[2][2] * v.z;
     DVec3 { x, y, z }
 }
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use crate::processor::{
+        calibration::{
+            AxisCalibration, CalibrationQualityConfig, CalibrationQualitySampler, GyroUnit,
+            ImuCalibrationConfig,
+        },
+        parser::ImuSampleRaw,
+        shared::DeviceTimeMs,
+    };
+
+    use super::euler_zyx_deg;
+
+    fn parse_map(value: &str) -> Result<ImuCalibrationConfig, toml::de::Error> {
+        let content = toml::to_string(&ImuCalibrationConfig::default())
+            .unwrap()
+            .replace(
+                r#"accel_map = ["x", "y", "z"]"#,
+                &format!("accel_map = {value}"),
+            );
+        toml::from_str(&content)
+    }
+
+    #[test]
+    fn axis_strings_and_matrix_describe_same_rotation() {
+        let config = parse_map(r#"["-y", "x", "z"]"#).unwrap();
+        let expected = DQuat::from_rotation_z(90f64.to_radians());
+        assert!(config.accel_map.quat().dot(expected).abs() > 1.0 - 1e-12);
+        let v = config.accel_map.apply(DVec3::new(1.0, 2.0, 3.0));
+        assert!((v - expected * DVec3::new(1.0, 2.0, 3.0)).length() < 1e-12);
+
+        let matrix = parse_map("[[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]").unwrap();
+        assert_eq!(matrix.accel_map, config.accel_map);
+    }
+
+    #[test]
+    fn euler_angles_round_trip_through_quaternion() {
+        let angle = DVec3::new(30.0, -45.0, 120.0);
+        let rad = angle.map(f64::to_radians);
+        let q = DQuat::from_euler_zyx(rad.x, rad.y, rad.z);
+        assert!((euler_zyx_deg(q) - angle).length() < 1e-9);
+    }
+
+    #[test]
+    fn angle_offset_wraps_across_180() {
+        let mut axis = AxisCalibration::new();
+        let mut raw = ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(0),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::ZERO,
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::new(0.0, 10.0, 179.0),
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        };
+        axis.update_from_raw(&raw);
+
+        // 偏航正向越过 180°：原始 -179° 相对零位应为 +2°，而不是 -358°
+        raw.angle = DVec3::new(0.0, 10.0, -179.0);
+        axis.apply(&mut raw);
+        assert!((raw.angle - DVec3::new(0.0, 0.0, 2.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn quat_offset_is_unit_even_when_raw_quat_is_not() {
+        let mut axis = AxisCalibration::new();
+        // 定点量化后模长偏离 1 约 1e-4
+        let quantized = DQuat::from_rotation_z(0.8) * (1.0 + 1e-4);
+        let mut raw = ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(0),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::ZERO,
+            gyro: DVec3::ZERO,
+            quat: quantized,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        };
+        axis.update_from_raw(&raw);
+        assert!(axis.quat_offset.is_normalized());
+
+        // 取逆不再放大偏差：零位处的合成姿态只带设备四元数本身的模长偏差
+        axis.apply(&mut raw);
+        assert!((raw.quat.length() - quantized.length()).abs() < 1e-12);
+        assert!(raw.quat.dot(DQuat::IDENTITY).abs() > 1.0 - 1e-3);
+    }
+
+    #[test]
+    fn quality_score_separates_still_and_handheld_windows() {
+        const G: f64 = 9.80665;
+        // 确定性伪噪声，幅值 ±1
+        let noise = |i: u64, k: u64| (((i * 7919 + k * 104_729) % 1000) as f64 / 500.0) - 1.0;
+        let window = |accel_noise: f64, gyro_noise_dps: f64| {
+            let mut sampler = CalibrationQualitySampler::new(
+                CalibrationQualityConfig::default(),
+                G,
+                GyroUnit::DegPerSec,
+            );
+            let mut full_at = None;
+            for i in 0..300u64 {
+                let raw = ImuSampleRaw {
+                    timestamp_ms: DeviceTimeMs(5_000 + i * 4),
+                    accel_no_g: DVec3::ZERO,
+                    accel_with_g: DVec3::new(
+                        0.3 + accel_noise * noise(i, 0),
+                        -0.2 + accel_noise * noise(i, 1),
+                        (G * G - 0.13).sqrt() + accel_noise * noise(i, 2),
+                    ),
+                    gyro: DVec3::new(
+                        0.1 + gyro_noise_dps * noise(i, 3),
+                        gyro_noise_dps * noise(i, 4),
+                        gyro_noise_dps * noise(i, 5),
+                    ),
+                    quat: DQuat::IDENTITY,
+                    angle: DVec3::ZERO,
+                    offset: DVec3::ZERO,
+                    accel_nav: DVec3::ZERO,
+                    baro: None,
+                };
+                if sampler.push(&raw) {
+                    full_at = Some(i);
+                    break;
+                }
+            }
+            // 1 s 窗口在 4 ms 采样下于第 251 帧填满
+            assert_eq!(full_at, Some(250));
+            sampler.quality()
+        };
+
+        // 静置：传感器噪声 ~0.01 m/s²、~0.2 °/s
+        let still = window(0.01, 0.2);
+        assert!(still.score > 90.0, "{still:?}");
+        assert!(still.applied);
+        assert_eq!(still.sample_count, 251);
+        assert!(still.gravity_deviation < 0.01, "{still:?}");
+
+        // 手持晃动：加速度抖动 ~0.5 m/s²、角速度 ~10 °/s
+        let shaky = window(0.5, 10.0);
+        assert!(shaky.score < still.min_score, "{shaky:?}");
+        assert!(!shaky.applied);
+        assert!(shaky.accel_norm_variance > still.accel_norm_variance * 100.0);
+
+        // 分项折算与权重
+        assert_eq!(CalibrationQualitySampler::score(0.0, 0.0, 0.0), 100.0);
+        assert!((CalibrationQualitySampler::score(0.005, 0.0, 1.0) - 60.0).abs() < 1e-9);
+        assert_eq!(CalibrationQualitySampler::score(1.0, 1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn invalid_axis_maps_are_rejected_at_load() {
+        let err = parse_map(r#"["x", "x", "z"]"#).unwrap_err().to_string();
+        assert!(err.contains("不正交"), "{err}");
+        let err = parse_map(r#"["-x", "y", "z"]"#).unwrap_err().to_string();
+        assert!(err.contains("不是真旋转"), "{err}");
+        let err = parse_map(r#"["x", "w", "z"]"#).unwrap_err().to_string();
+        assert!(err.contains("无法识别的轴"), "{err}");
+    }
+}
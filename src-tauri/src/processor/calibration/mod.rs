@@ -10,13 +10,33 @@
 //!
 //! 其中 M_* 是 3x3 标定矩阵，b_* 是偏置。这里不做在线自标定，
 //! 只读取配置并做一次性修正，保证后续处理链输入一致。
+//!
+//! 单位约定：解析器按 `gyro_unit`（默认 °/s）输出角速度，标定阶段统一换算为 rad/s，
+//! 之后的滤波、ZUPT 阈值、导航与诊断均按 rad/s 解释。
+//!
+//! 零位校准质量：校准请求先采集约 1 s 原始数据，按加速度模长方差、角速度均值与
+//! 重力偏差评分，设备未保持静止时可拒绝应用，避免一次糟糕的校准污染后续所有输出。
+//!
+//! 安装方向：`accel_map`/`gyro_map` 把传感器轴重映射到外壳轴，先于零位校准与去偏置执行；
+//! 姿态四元数与欧拉角按 `accel_map` 同步重映射，保证所有输出处于同一坐标系。
 
 /// 标定逻辑。
 pub mod logic;
 /// 标定类型定义。
 pub mod types;
+/// 标定向导状态机。
+pub mod wizard;
 
-/// 标定处理器。
-pub use logic::Calibration;
+/// 标定处理器与零位校准质量采样。
+pub use logic::{Calibration, CalibrationQualitySampler};
 /// 标定类型导出。
-pub use types::{AxisCalibration, CorrectionRequest, ImuCalibrationConfig, ImuSampleCalibrated};
+pub use types::{
+    AxisCalibration, AxisMapSpec, AxisRemap, CalibrationOffsets, CalibrationQuality,
+    CalibrationQualityConfig, CorrectionRequest, FullCalibration, GyroUnit, ImuCalibrationConfig,
+    ImuSampleCalibrated,
+};
+/// 标定向导导出。
+pub use wizard::{
+    CalibrationFlow, CalibrationStateHandle, CalibrationStateSlot, CalibrationWizard,
+    CalibrationWizardResult, CalibrationWizardSnapshot, CalibrationWizardState,
+};
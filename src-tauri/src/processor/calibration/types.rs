@@ -4,14 +4,125 @@ use math_f64::{DQuat, DVec3};
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+use crate::processor::shared::{ConfigValidator, DeviceTimeMs};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 解析器输出角速度的单位。
+///
+/// 标定阶段之后的所有模块（滤波、ZUPT、导航）统一使用 rad/s。
+pub enum GyroUnit {
+    /// 度每秒（IMU 默认输出，量程 ±2000 °/s）。
+    #[default]
+    DegPerSec,
+    /// 弧度每秒。
+    RadPerSec,
+}
+
+impl GyroUnit {
+    /// 换算到 rad/s 的比例系数。
+    pub fn to_rad_per_sec(self) -> f64 {
+        match self {
+            GyroUnit::DegPerSec => std::f64::consts::PI / 180.0,
+            GyroUnit::RadPerSec => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "AxisMapSpec", into = "AxisMapSpec")]
+/// 传感器轴到外壳轴的静态重映射（安装方向）。
+///
+/// 第 i 行是外壳第 i 轴在传感器坐标系下的表示，即 `v_body = M * v_sensor`。
+/// 配置里可以写带符号的轴置换（如 `["-y", "x", "z"]`，等价于绕 z 轴 +90°），
+/// 也可以直接写 3x3 矩阵；加载时校验为正交且行列式为 +1 的真旋转。
+pub struct AxisRemap {
+    pub(crate) matrix: [[f64; 3]; 3],
+}
+
+impl Default for AxisRemap {
+    fn default() -> Self {
+        Self {
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+/// [`AxisRemap`] 的配置文件表示。
+pub enum AxisMapSpec {
+    /// 带符号的轴置换，如 `["-y", "x", "z"]`。
+    Axes([String; 3]),
+    /// 3x3 旋转矩阵（行优先）。
+    Matrix([[f64; 3]; 3]),
+}
+
+impl TryFrom<AxisMapSpec> for AxisRemap {
+    type Error = String;
+
+    fn try_from(spec: AxisMapSpec) -> Result<Self, Self::Error> {
+        match spec {
+            AxisMapSpec::Axes(axes) => {
+                let mut matrix = [[0.0; 3]; 3];
+                for (row, axis) in matrix.iter_mut().zip(axes.iter()) {
+                    let trimmed = axis.trim();
+                    let (sign, name) = match trimmed.strip_prefix('-') {
+                        Some(rest) => (-1.0, rest),
+                        None => (1.0, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+                    };
+                    let col = match name {
+                        "x" | "X" => 0,
+                        "y" | "Y" => 1,
+                        "z" | "Z" => 2,
+                        _ => {
+                            return Err(format!(
+                                "无法识别的轴 \"{axis}\"，应为 x/y/z，可带 +/- 前缀"
+                            ))
+                        }
+                    };
+                    row[col] = sign;
+                }
+                AxisRemap::from_matrix(matrix)
+            }
+            AxisMapSpec::Matrix(matrix) => AxisRemap::from_matrix(matrix),
+        }
+    }
+}
+
+impl From<AxisRemap> for AxisMapSpec {
+    fn from(remap: AxisRemap) -> Self {
+        // 带符号置换写回成轴字符串，便于在 processor.toml 中阅读
+        let mut axes: [String; 3] = Default::default();
+        for (axis, row) in axes.iter_mut().zip(remap.matrix.iter()) {
+            let Some(col) = row.iter().position(|v| v.abs() == 1.0) else {
+                return AxisMapSpec::Matrix(remap.matrix);
+            };
+            let sign = if row[col] < 0.0 { "-" } else { "" };
+            *axis = format!("{sign}{}", ["x", "y", "z"][col]);
+        }
+        AxisMapSpec::Axes(axes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
 /// IMU 标定参数配置。
 pub struct ImuCalibrationConfig {
-    /// 是否跳过标定处理。
+    /// 是否跳过标定处理（仍会做单位换算）。
+    #[serde(default)]
     pub passby: bool,
+    /// 原始角速度单位，标定阶段据此统一换算到 rad/s。
+    #[serde(default)]
+    pub gyro_unit: GyroUnit,
+    /// 加速度计安装方向重映射，同时决定姿态四元数/欧拉角的重映射。
+    #[serde(default)]
+    pub accel_map: AxisRemap,
+    /// 陀螺仪安装方向重映射。
+    #[serde(default)]
+    pub gyro_map: AxisRemap,
     /// 加速度计偏置。
     pub accel_bias: DVec3,
-    /// 陀螺仪偏置。
+    /// 陀螺仪偏置 (rad/s)。
     pub gyro_bias: DVec3,
     /// 加速度计标定矩阵。
     pub accel_matrix: [[f64; 3]; 3],
@@ -23,6 +134,9 @@ impl Default for ImuCalibrationConfig {
     fn default() -> Self {
         Self {
             passby: false,
+            gyro_unit: GyroUnit::DegPerSec,
+            accel_map: AxisRemap::default(),
+            gyro_map: AxisRemap::default(),
             accel_bias: DVec3::ZERO,
             gyro_bias: DVec3::ZERO,
             accel_matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
@@ -31,6 +145,42 @@ impl Default for ImuCalibrationConfig {
     }
 }
 
+impl ImuCalibrationConfig {
+    /// 加速度计偏置上限 (m/s²)，更大通常是单位写错或传感器损坏。
+    const MAX_ACCEL_BIAS: f64 = 2.0;
+    /// 陀螺仪偏置上限 (rad/s)。
+    const MAX_GYRO_BIAS: f64 = 1.0;
+
+    /// 校验取值范围：偏置不超过上限，标定矩阵接近单位阵（对角 [0.5, 1.5]，其余 [-0.5, 0.5]）。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        for (name, bias, max) in [
+            ("accel_bias", self.accel_bias, Self::MAX_ACCEL_BIAS),
+            ("gyro_bias", self.gyro_bias, Self::MAX_GYRO_BIAS),
+        ] {
+            v.section(name, |v| {
+                for (axis, value) in ["x", "y", "z"].into_iter().zip(bias.to_array()) {
+                    v.in_range(axis, value, -max, max);
+                }
+            });
+        }
+        for (name, matrix) in [
+            ("accel_matrix", &self.accel_matrix),
+            ("gyro_matrix", &self.gyro_matrix),
+        ] {
+            for (row, values) in matrix.iter().enumerate() {
+                for (col, &value) in values.iter().enumerate() {
+                    let field = format!("{name}[{row}][{col}]");
+                    if row == col {
+                        v.in_range(&field, value, 0.5, 1.5);
+                    } else {
+                        v.in_range(&field, value, -0.5, 0.5);
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 /// 标定运行时状态。
 pub struct CalibrationState {
@@ -54,14 +204,14 @@ impl CalibrationState {
 /// 标定后的 IMU 样本。
 pub struct ImuSampleCalibrated {
     /// 时间戳（毫秒）。
-    pub timestamp_ms: u64,
+    pub timestamp_ms: DeviceTimeMs,
     /// 标定后的加速度。
     pub accel: DVec3,
     /// 标定后的角速度（rad/s）。
     pub gyro: DVec3,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
 /// 姿态零位校准参数。
 pub struct AxisCalibration {
     /// 欧拉角偏移（用于直接减去，令当前角度归零）。
@@ -79,13 +229,102 @@ impl Default for AxisCalibration {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 姿态零位校准的数据质量评估配置。
+pub struct CalibrationQualityConfig {
+    /// 采样窗口 (ms)：收到校准请求后按设备时间戳采集这么长的原始数据再评分。
+    pub window_ms: u64,
+    /// 最低可接受分数 (0–100)。
+    pub min_score: f64,
+    /// 低于最低分时是否拒绝应用校准；关闭时照常应用，只在结果中报告分数。
+    pub reject_below_min: bool,
+}
+
+impl Default for CalibrationQualityConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 1000,
+            min_score: 60.0,
+            reject_below_min: true,
+        }
+    }
+}
+
+impl CalibrationQualityConfig {
+    /// 采样窗口下限 (ms)。
+    pub const MIN_WINDOW_MS: u64 = 100;
+    /// 采样窗口上限 (ms)，等待校准回复的超时据此留出余量。
+    pub const MAX_WINDOW_MS: u64 = 5000;
+
+    /// 校验取值范围：窗口在 [100, 5000] ms，最低分在 [0, 100]。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range(
+            "window_ms",
+            self.window_ms as f64,
+            Self::MIN_WINDOW_MS as f64,
+            Self::MAX_WINDOW_MS as f64,
+        );
+        v.in_range("min_score", self.min_score, 0.0, 100.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// 姿态零位校准的数据质量评分，随校准结果返回。
+///
+/// 评分规则见 [`CalibrationQualitySampler::score`](super::logic::CalibrationQualitySampler::score)。
+pub struct CalibrationQuality {
+    /// 综合分数 (0–100)，越高说明校准时设备越静止。
+    pub score: f64,
+    /// 窗口内加速度模长的方差 ((m/s²)²)。
+    pub accel_norm_variance: f64,
+    /// 窗口内角速度模长的均值 (rad/s)。
+    pub gyro_norm_mean: f64,
+    /// 加速度模长均值与重力常数之差的绝对值 (m/s²)。
+    pub gravity_deviation: f64,
+    /// 窗口内的样本数。
+    pub sample_count: usize,
+    /// 生效的最低可接受分数。
+    pub min_score: f64,
+    /// 是否已应用校准；分数低于阈值且配置为拒绝时为 `false`。
+    pub applied: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// 一次性校准生效后的各项偏移。
+pub struct CalibrationOffsets {
+    /// 姿态零位四元数偏移（左乘原始姿态）。
+    pub quat_offset: DQuat,
+    /// 欧拉角偏移 (°)：x/y 为水平（横滚、俯仰），z 为航向。
+    pub angle_offset: DVec3,
+    /// 航向零点 (°)，即 `angle_offset.z`。
+    pub heading_offset_deg: f64,
+    /// 更新后的世界系重力参考 (m/s²)。
+    pub gravity_ref: DVec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// 水平、航向、原点一次性校准的结果。
+pub struct FullCalibration {
+    /// 采样窗口的静止质量评分，`applied` 表示是否通过静止校验。
+    pub quality: CalibrationQuality,
+    /// 生效的偏移；未通过静止校验时为空，此时任何状态都未改变。
+    pub offsets: Option<CalibrationOffsets>,
+}
+
 /// 手动校正请求。
 pub enum CorrectionRequest {
-    /// 以最新原始姿态作为零位。
+    /// 采集一个质量评估窗口后，以窗口末帧的原始姿态作为零位。
     /// response tells the caller the result
     SetAxis {
-        /// 完成回调通道。
-        respond_to: oneshot::Sender<Result<(), &'static str>>,
+        /// 完成回调通道，返回窗口的质量评分。
+        respond_to: oneshot::Sender<Result<CalibrationQuality, &'static str>>,
+    },
+    /// 采集一个质量评估窗口，通过静止校验后一次性完成姿态零位、航向零点、
+    /// 位置归零（清零速度与 ZUPT 锁定点）与重力参考更新。
+    CalibrateAll {
+        /// 完成回调通道，返回评分与生效的偏移。
+        respond_to: oneshot::Sender<Result<FullCalibration, &'static str>>,
     },
     /// 强制设置位置。
     SetPosition {
@@ -94,4 +333,14 @@ pub enum CorrectionRequest {
         /// 完成回调通道。
         respond_to: oneshot::Sender<Result<(), &'static str>>,
     },
+    /// 重新等待下一段持续静止，并把该处锚定为世界原点。
+    ReAnchorOrigin {
+        /// 完成回调通道，回复表示已进入待锚定状态。
+        respond_to: oneshot::Sender<Result<(), &'static str>>,
+    },
+    /// 等待下一段足够长的运动，把它的水平方向对齐为输出的 +X。
+    ArmHeadingFromMotion {
+        /// 完成回调通道，回复表示已武装；未启用航向对齐时返回错误。
+        respond_to: oneshot::Sender<Result<(), &'static str>>,
+    },
 }
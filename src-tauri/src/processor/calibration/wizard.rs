@@ -0,0 +1,283 @@
+//! 标定向导状态机。
+//!
+//! 零位校准要先采满静止窗口再评分，航向对齐要等一段运动在静止中结束，前端过去只能
+//! 根据命令何时返回猜测后端走到了哪一步。[`CalibrationWizard`] 由管线持有，各标定流程
+//! 在处理线程里推进它；每次状态变化生成一个带递增序号的 [`CalibrationWizardSnapshot`]。
+//! 处理线程取走这些快照，经 [`CalibrationStateSlot::publish`] 逐个写入共享槽位后再推送
+//! `calibration_state_changed` 事件：写入时持锁，推送时不持锁，命令侧读到的状态
+//! 不会落后于已经发出的事件，事件回调也不会与读端互相阻塞。
+
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+use crate::processor::{
+    calibration::types::{CalibrationQuality, FullCalibration},
+    heading::HeadingAligned,
+};
+
+/// 静止窗口进度按此粒度 (ms) 上报，避免每帧都产生一次状态变化。
+pub const STILLNESS_PROGRESS_STEP_MS: u64 = 100;
+
+/// 正在进行的标定流程。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationFlow {
+    /// 姿态零位校准（`set_axis_calibration`）。
+    Axis,
+    /// 一次性全套校准（`calibrate_all`）。
+    CalibrateAll,
+    /// 按首段运动方向对齐航向（`arm_heading_from_motion`）。
+    HeadingAlign,
+    /// 六面加速度计标定，预留给 [`CalibrationWizardState::CapturingFace`]，后端尚未实现该流程。
+    SixFace,
+}
+
+/// 标定流程的最终结果。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalibrationWizardResult {
+    /// 姿态零位校准的质量评分。
+    Axis(CalibrationQuality),
+    /// 一次性全套校准的评分与偏移。
+    CalibrateAll(FullCalibration),
+    /// 航向对齐结果。
+    HeadingAlign(HeadingAligned),
+}
+
+/// 标定向导状态。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CalibrationWizardState {
+    /// 没有进行中的标定。
+    #[default]
+    Idle,
+    /// 采集静止窗口。
+    WaitingForStillness {
+        /// 已采集的时长 (ms)，按 [`STILLNESS_PROGRESS_STEP_MS`] 取整。
+        elapsed_ms: u64,
+        /// 窗口总时长 (ms)。
+        required_ms: u64,
+    },
+    /// 等待一段在静止中结束的运动（航向对齐）。
+    WaitingForMotion,
+    /// 采集某一面的静置数据（六面标定）。
+    CapturingFace {
+        /// 面序号 (0–5)。
+        face: u8,
+        /// 该面的采集进度 (0–1)。
+        progress: f64,
+    },
+    /// 采集完成，正在计算结果。
+    Computing,
+    /// 完成。
+    Done {
+        /// 标定结果。
+        result: CalibrationWizardResult,
+    },
+    /// 失败或被拒绝。
+    Failed {
+        /// 失败原因。
+        reason: String,
+    },
+}
+
+/// `get_calibration_state` 返回值与 `calibration_state_changed` 事件载荷。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CalibrationWizardSnapshot {
+    /// 状态序号，每次变化加一；前端据此丢弃比已知状态更旧的事件或查询结果。
+    pub seq: u64,
+    /// 当前流程，空闲时为空。
+    pub flow: Option<CalibrationFlow>,
+    /// 当前状态。
+    pub state: CalibrationWizardState,
+}
+
+/// 标定向导状态机，由管线持有并在处理线程中推进。
+#[derive(Debug, Clone, Default)]
+pub struct CalibrationWizard {
+    current: CalibrationWizardSnapshot,
+    /// 尚未被取走的状态变化，按发生先后排列。
+    transitions: Vec<CalibrationWizardSnapshot>,
+}
+
+impl CalibrationWizard {
+    /// 当前快照。
+    pub fn snapshot(&self) -> &CalibrationWizardSnapshot {
+        &self.current
+    }
+
+    /// 当前状态。
+    pub fn state(&self) -> &CalibrationWizardState {
+        &self.current.state
+    }
+
+    /// 进入 `flow` 的 `state`；与当前流程和状态都相同时不产生变化。
+    pub fn transition(&mut self, flow: CalibrationFlow, state: CalibrationWizardState) {
+        self.set(Some(flow), state);
+    }
+
+    /// 静止窗口采集进度，按 [`STILLNESS_PROGRESS_STEP_MS`] 取整后上报。
+    pub fn stillness_progress(&mut self, flow: CalibrationFlow, elapsed_ms: u64, required_ms: u64) {
+        let elapsed_ms =
+            (elapsed_ms.min(required_ms) / STILLNESS_PROGRESS_STEP_MS) * STILLNESS_PROGRESS_STEP_MS;
+        self.transition(
+            flow,
+            CalibrationWizardState::WaitingForStillness {
+                elapsed_ms,
+                required_ms,
+            },
+        );
+    }
+
+    /// 回到空闲。
+    pub fn idle(&mut self) {
+        self.set(None, CalibrationWizardState::Idle);
+    }
+
+    /// 取走尚未发布的状态变化。
+    pub fn take_transitions(&mut self) -> Vec<CalibrationWizardSnapshot> {
+        std::mem::take(&mut self.transitions)
+    }
+
+    fn set(&mut self, flow: Option<CalibrationFlow>, state: CalibrationWizardState) {
+        if self.current.flow == flow && self.current.state == state {
+            return;
+        }
+        self.current = CalibrationWizardSnapshot {
+            seq: self.current.seq + 1,
+            flow,
+            state,
+        };
+        self.transitions.push(self.current.clone());
+    }
+}
+
+/// 跨线程共享的标定向导状态句柄。
+pub type CalibrationStateHandle = Arc<CalibrationStateSlot>;
+
+/// 最新标定向导状态槽位，处理线程发布，命令侧随时读取。
+#[derive(Debug, Default)]
+pub struct CalibrationStateSlot {
+    slot: RwLock<CalibrationWizardSnapshot>,
+}
+
+impl CalibrationStateSlot {
+    /// 创建共享句柄。
+    pub fn new_handle() -> CalibrationStateHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 按先后逐个写入状态变化，每次写入释放锁后再调用 `emit` 推送事件。
+    ///
+    /// 序号不大于已发布状态的快照直接丢弃。
+    pub fn publish(
+        &self,
+        transitions: Vec<CalibrationWizardSnapshot>,
+        mut emit: impl FnMut(&CalibrationWizardSnapshot),
+    ) {
+        for snapshot in transitions {
+            {
+                let mut slot = self
+                    .slot
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                if snapshot.seq <= slot.seq {
+                    continue;
+                }
+                *slot = snapshot.clone();
+            }
+            emit(&snapshot);
+        }
+    }
+
+    /// 最新发布的状态。
+    pub fn get(&self) -> CalibrationWizardSnapshot {
+        self.slot
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wizard_dedups_repeated_states_and_quantizes_progress() {
+        let mut wizard = CalibrationWizard::default();
+        for elapsed_ms in [0, 40, 99, 100, 180, 260, 400] {
+            wizard.stillness_progress(CalibrationFlow::Axis, elapsed_ms, 250);
+        }
+        wizard.idle();
+        wizard.idle();
+        let states: Vec<_> = wizard
+            .take_transitions()
+            .into_iter()
+            .map(|s| (s.seq, s.state))
+            .collect();
+        let waiting = |elapsed_ms| CalibrationWizardState::WaitingForStillness {
+            elapsed_ms,
+            required_ms: 250,
+        };
+        assert_eq!(
+            states,
+            [
+                (1, waiting(0)),
+                (2, waiting(100)),
+                (3, waiting(200)),
+                (4, CalibrationWizardState::Idle),
+            ]
+        );
+        assert!(wizard.take_transitions().is_empty());
+
+        // 同一状态换了流程也算变化
+        wizard.transition(CalibrationFlow::Axis, CalibrationWizardState::Computing);
+        wizard.transition(
+            CalibrationFlow::CalibrateAll,
+            CalibrationWizardState::Computing,
+        );
+        assert_eq!(wizard.take_transitions().len(), 2);
+        assert_eq!(wizard.snapshot().seq, 6);
+    }
+
+    #[test]
+    fn publish_stores_before_emitting_without_holding_the_lock() {
+        let slot = CalibrationStateSlot::new_handle();
+        let mut wizard = CalibrationWizard::default();
+        wizard.transition(
+            CalibrationFlow::HeadingAlign,
+            CalibrationWizardState::WaitingForMotion,
+        );
+        wizard.transition(
+            CalibrationFlow::HeadingAlign,
+            CalibrationWizardState::Failed { reason: "x".into() },
+        );
+        let stale = wizard.snapshot().clone();
+
+        // 回调里读槽位：持锁推送会在这里死锁，先推送后写入会读到旧状态
+        let mut emitted = Vec::new();
+        slot.publish(wizard.take_transitions(), |snapshot| {
+            assert_eq!(&slot.get(), snapshot);
+            emitted.push(snapshot.seq);
+        });
+        assert_eq!(emitted, [1, 2]);
+
+        // 旧序号不会覆盖已发布的状态
+        slot.publish(vec![CalibrationWizardSnapshot::default(), stale], |_| {
+            panic!("旧状态不应推送")
+        });
+        assert_eq!(slot.get().seq, 2);
+
+        let json = serde_json::to_value(slot.get()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "seq": 2,
+                "flow": "heading_align",
+                "state": { "state": "failed", "reason": "x" },
+            })
+        );
+    }
+}
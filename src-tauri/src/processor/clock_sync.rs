@@ -0,0 +1,267 @@
+//! 设备时钟与主机时钟同步。
+//!
+//! 设备时间戳由 IMU 晶振计时，与主机系统时钟存在固定偏移与约百 ppm 的频偏，
+//! 与外部视频对齐时需要换算到主机时间。这里用每个蓝牙通知到达主机的时刻作观测，
+//! 拟合 `host = offset + rate · device`：
+//!
+//! 1. 设备时间按 [`BUCKET_MS`] 分桶，每桶只保留 `host − device` 最小的一对作为锚点。
+//!    蓝牙延迟只会让到达时刻偏晚，最小值最接近真实发送时刻，抖动与排队尖峰被滤掉；
+//! 2. 对最近 [`WINDOW_BUCKETS`] 个锚点做 Theil–Sen 拟合：斜率取两两斜率的中位数，
+//!    截距取残差中位数，少量离群锚点不影响结果。
+//!
+//! 设备复位（时间戳回退）后设备时间轴重新开始，旧锚点全部丢弃。
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, RwLock},
+};
+
+use serde::Serialize;
+
+use crate::processor::shared::{DeviceTimeMs, HostTimeMs};
+
+/// 锚点分桶宽度（设备时间，ms）。
+pub const BUCKET_MS: u64 = 1_000;
+/// 参与拟合的锚点数上限（滑动窗口），默认约 5 分钟。
+pub const WINDOW_BUCKETS: usize = 300;
+/// 发布拟合结果前至少需要的锚点数。
+pub const MIN_ANCHORS: usize = 5;
+
+/// 时钟同步拟合结果。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ClockSyncEstimate {
+    /// 设备时间 0 对应的主机 UNIX 时间 (ms)。
+    pub offset_ms: f64,
+    /// 设备时钟相对主机时钟的频偏 (ppm)，正值表示设备时钟偏快。
+    pub skew_ppm: f64,
+    /// 参与拟合的锚点数。
+    pub anchors: u32,
+    /// 锚点相对拟合直线的残差中位数 (ms)。
+    pub residual_ms: f64,
+}
+
+impl ClockSyncEstimate {
+    /// 设备时间戳对应的主机 UNIX 时间估计 (ms)。
+    pub fn host_time_ms(&self, device_ms: DeviceTimeMs) -> f64 {
+        device_to_host_ms(self.offset_ms, self.skew_ppm, device_ms.as_millis())
+    }
+}
+
+/// 按偏移与频偏把设备时间戳换算为主机 UNIX 时间 (ms)，录制会话存储的参数同样适用。
+pub fn device_to_host_ms(offset_ms: f64, skew_ppm: f64, device_ms: u64) -> f64 {
+    offset_ms + device_ms as f64 / (1.0 + skew_ppm * 1e-6)
+}
+
+/// 一个锚点：设备时间与主机接收时间 (ms)。
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    device_ms: f64,
+    host_ms: f64,
+}
+
+impl Anchor {
+    fn lag_ms(&self) -> f64 {
+        self.host_ms - self.device_ms
+    }
+}
+
+/// 时钟同步估计器，由管线持有，逐帧喂入 (设备时间戳, 主机接收时刻)。
+#[derive(Debug, Clone, Default)]
+pub struct ClockSyncEstimator {
+    /// 已封闭的锚点，按设备时间递增。
+    anchors: VecDeque<Anchor>,
+    /// 当前桶序号与桶内延迟最小的观测。
+    open: Option<(u64, Anchor)>,
+    estimate: Option<ClockSyncEstimate>,
+    /// 拟合用的两两斜率缓冲，避免每次重新分配。
+    scratch: Vec<f64>,
+}
+
+impl ClockSyncEstimator {
+    /// 丢弃全部锚点与拟合结果。
+    pub fn reset(&mut self) {
+        self.anchors.clear();
+        self.open = None;
+        self.estimate = None;
+    }
+
+    /// 当前拟合结果，锚点不足 [`MIN_ANCHORS`] 时为 `None`。
+    pub fn estimate(&self) -> Option<ClockSyncEstimate> {
+        self.estimate
+    }
+
+    /// 记录一次观测；进入新的桶时封闭上一个桶并重新拟合。
+    pub fn observe(&mut self, device_ms: DeviceTimeMs, host_ms: HostTimeMs) {
+        let bucket = device_ms.as_millis() / BUCKET_MS;
+        let anchor = Anchor {
+            device_ms: device_ms.as_millis() as f64,
+            host_ms: host_ms.as_millis() as f64,
+        };
+        match &mut self.open {
+            Some((open_bucket, best)) if *open_bucket == bucket => {
+                if anchor.lag_ms() < best.lag_ms() {
+                    *best = anchor;
+                }
+            }
+            Some((open_bucket, _)) if *open_bucket > bucket => {
+                // 时间戳回退说明设备复位，但管线尚未察觉：旧锚点不再可比
+                self.reset();
+                self.open = Some((bucket, anchor));
+            }
+            open => {
+                if let Some((_, closed)) = open.replace((bucket, anchor)) {
+                    self.anchors.push_back(closed);
+                    if self.anchors.len() > WINDOW_BUCKETS {
+                        self.anchors.pop_front();
+                    }
+                    self.refit();
+                }
+            }
+        }
+    }
+
+    /// Theil–Sen 拟合。
+    fn refit(&mut self) {
+        if self.anchors.len() < MIN_ANCHORS {
+            self.estimate = None;
+            return;
+        }
+        self.scratch.clear();
+        for (i, a) in self.anchors.iter().enumerate() {
+            for b in self.anchors.iter().skip(i + 1) {
+                self.scratch
+                    .push((b.host_ms - a.host_ms) / (b.device_ms - a.device_ms));
+            }
+        }
+        let rate = median(&mut self.scratch);
+
+        // 以第一个锚点为基准求截距，避免主机 UNIX 时间的大数与斜率相乘放大舍入
+        let base = self.anchors[0];
+        self.scratch.clear();
+        self.scratch.extend(
+            self.anchors
+                .iter()
+                .map(|a| a.host_ms - base.host_ms - rate * (a.device_ms - base.device_ms)),
+        );
+        let intercept = median(&mut self.scratch);
+
+        self.scratch.clear();
+        self.scratch.extend(self.anchors.iter().map(|a| {
+            (a.host_ms - base.host_ms - intercept - rate * (a.device_ms - base.device_ms)).abs()
+        }));
+        let residual_ms = median(&mut self.scratch);
+
+        self.estimate = Some(ClockSyncEstimate {
+            offset_ms: base.host_ms + intercept - rate * base.device_ms,
+            skew_ppm: (1.0 / rate - 1.0) * 1e6,
+            anchors: self.anchors.len() as u32,
+            residual_ms,
+        });
+    }
+}
+
+/// 中位数（偶数个取中间两数均值），会重排 `values`。
+fn median(values: &mut [f64]) -> f64 {
+    let mid = values.len() / 2;
+    let odd = values.len() % 2 == 1;
+    let (lower, upper, _) = values.select_nth_unstable_by(mid, f64::total_cmp);
+    let upper = *upper;
+    if odd {
+        return upper;
+    }
+    let lower = lower.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (lower + upper) / 2.0
+}
+
+/// 跨线程共享的时钟同步结果句柄。
+pub type ClockSyncHandle = Arc<ClockSyncSlot>;
+
+/// 最新时钟同步结果槽位，处理线程每输出一帧替换一次，命令侧随时读取。
+#[derive(Debug, Default)]
+pub struct ClockSyncSlot {
+    slot: RwLock<Option<ClockSyncEstimate>>,
+}
+
+impl ClockSyncSlot {
+    /// 创建共享句柄。
+    pub fn new_handle() -> ClockSyncHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 替换为最新结果。
+    pub fn store(&self, estimate: Option<ClockSyncEstimate>) {
+        *self
+            .slot
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = estimate;
+    }
+
+    /// 最新结果，尚未拟合时为 `None`。
+    pub fn get(&self) -> Option<ClockSyncEstimate> {
+        *self
+            .slot
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 线性同余伪随机数，输出 [0, 1)。
+    fn lcg(state: &mut u64) -> f64 {
+        *state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    #[test]
+    fn recovers_skew_under_ble_jitter_and_outliers() {
+        const HOST_START_MS: f64 = 1_700_000_000_000.0;
+        const DEVICE_START_MS: u64 = 123_456;
+        let mut state = 7;
+        for skew_ppm in [100.0, -60.0] {
+            let mut estimator = ClockSyncEstimator::default();
+            let rate = 1.0 / (1.0 + skew_ppm * 1e-6);
+            // 250 Hz 设备帧，每个通知打包 2 帧；BLE 延迟 5 ~ 35 ms，2% 的通知因排队晚到 50 ~ 500 ms
+            for i in 0..(250 * 240 / 2) {
+                let last_device_ms = DEVICE_START_MS + i * 8 + 4;
+                let mut latency_ms = 5.0 + 30.0 * lcg(&mut state);
+                if lcg(&mut state) < 0.02 {
+                    latency_ms += 50.0 + 450.0 * lcg(&mut state);
+                }
+                let sent_host_ms = HOST_START_MS + rate * (last_device_ms - DEVICE_START_MS) as f64;
+                let received_ms = (sent_host_ms + latency_ms).round() as u64;
+                let received_ms = HostTimeMs(received_ms);
+                estimator.observe(DeviceTimeMs(last_device_ms - 4), received_ms);
+                estimator.observe(DeviceTimeMs(last_device_ms), received_ms);
+            }
+
+            let estimate = estimator.estimate().expect("enough anchors");
+            assert!(
+                (estimate.skew_ppm - skew_ppm).abs() < 10.0,
+                "skew {} vs {skew_ppm}",
+                estimate.skew_ppm
+            );
+            assert_eq!(estimate.anchors, 240);
+            // 恒定最小延迟 5 ms 计入偏移，其余误差在毫秒量级
+            let device_ms = DEVICE_START_MS + 60_000;
+            let truth = HOST_START_MS + rate * 60_000.0;
+            let error_ms = estimate.host_time_ms(DeviceTimeMs(device_ms)) - truth;
+            assert!((4.0..7.0).contains(&error_ms), "error {error_ms} ms");
+        }
+    }
+
+    #[test]
+    fn timestamp_rewind_discards_old_anchors() {
+        let mut estimator = ClockSyncEstimator::default();
+        for i in 0..10_000 {
+            estimator.observe(DeviceTimeMs(i), HostTimeMs(1_000_000 + i));
+        }
+        assert!(estimator.estimate().is_some());
+        estimator.observe(DeviceTimeMs(0), HostTimeMs(2_000_000));
+        assert_eq!(estimator.estimate(), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! 派生信号计算逻辑。
+
+use crate::processor::calibration::ImuSampleCalibrated;
+use crate::processor::derived::types::DerivedConfig;
+use crate::processor::filter::{ImuSampleFiltered, LowPassFilter, LowPassFilterConfig};
+
+/// 加加速度与角加速度计算。
+#[derive(Clone)]
+pub struct DerivedSignals {
+    config: DerivedConfig,
+    /// 上一帧低通样本。
+    prev: Option<ImuSampleFiltered>,
+    /// 差分结果的平滑（加速度通道放 jerk，角速度通道放角加速度）。
+    smoother: LowPassFilter,
+}
+
+impl DerivedSignals {
+    /// 创建派生信号计算。
+    pub fn new(config: DerivedConfig) -> Self {
+        Self {
+            config,
+            prev: None,
+            smoother: LowPassFilter::new(Self::smoother_config(config)),
+        }
+    }
+
+    fn smoother_config(config: DerivedConfig) -> LowPassFilterConfig {
+        LowPassFilterConfig {
+            passby: false,
+            alpha: config.alpha,
+        }
+    }
+
+    /// 原地更新参数；启停切换时清空历史。
+    pub fn reconfigure(&mut self, config: DerivedConfig) {
+        if config.enabled != self.config.enabled {
+            self.reset();
+        }
+        self.smoother.reconfigure(Self::smoother_config(config));
+        self.config = config;
+    }
+
+    /// 计算本帧派生信号，写入 `sample.jerk` / `sample.ang_accel`。
+    ///
+    /// 单位：jerk 为 m/s³，角加速度为 rad/s²（标定后角速度为 rad/s）。
+    pub fn apply(&mut self, sample: &mut ImuSampleFiltered) {
+        if !self.config.enabled {
+            return;
+        }
+        let Some(prev) = self.prev.replace(*sample) else {
+            return;
+        };
+        if sample.timestamp_ms <= prev.timestamp_ms {
+            return;
+        }
+        let dt = sample.timestamp_ms.delta_s(prev.timestamp_ms).as_secs();
+        let smoothed = self.smoother.apply(&ImuSampleCalibrated {
+            timestamp_ms: sample.timestamp_ms,
+            accel: (sample.accel_lp - prev.accel_lp) / dt,
+            gyro: (sample.gyro_lp - prev.gyro_lp) / dt,
+        });
+        sample.jerk = Some(smoothed.accel_lp);
+        sample.ang_accel = Some(smoothed.gyro_lp);
+    }
+
+    /// 重置差分与平滑状态。
+    pub fn reset(&mut self) {
+        self.prev = None;
+        self.smoother.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::DVec3;
+
+    use super::*;
+    use crate::processor::shared::DeviceTimeMs;
+
+    fn sample(timestamp_ms: u64, accel: DVec3, gyro: DVec3) -> ImuSampleFiltered {
+        ImuSampleFiltered {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_lp: accel,
+            gyro_lp: gyro,
+            jerk: None,
+            ang_accel: None,
+        }
+    }
+
+    fn enabled() -> DerivedSignals {
+        DerivedSignals::new(DerivedConfig {
+            enabled: true,
+            ..DerivedConfig::default()
+        })
+    }
+
+    #[test]
+    fn linear_ramp_yields_constant_slope() {
+        // 加速度沿 x 以 2.5 m/s³ 线性增长，角速度绕 z 以 -1.2 rad/s² 变化
+        let jerk = DVec3::new(2.5, 0.0, 0.0);
+        let ang_accel = DVec3::new(0.0, 0.0, -1.2);
+        let mut derived = enabled();
+        let mut outputs = Vec::new();
+        for i in 0..200u64 {
+            let t = i as f64 * 0.004;
+            let mut s = sample(1000 + i * 4, jerk * t, ang_accel * t);
+            derived.apply(&mut s);
+            outputs.push(s);
+        }
+        assert!(outputs[0].jerk.is_none() && outputs[0].ang_accel.is_none());
+        for s in &outputs[1..] {
+            let (j, a) = (s.jerk.unwrap(), s.ang_accel.unwrap());
+            assert!((j.length() - jerk.length()).abs() < 1e-6, "{j:?}");
+            assert!((a - ang_accel).length() < 1e-6, "{a:?}");
+        }
+    }
+
+    #[test]
+    fn duplicate_timestamp_and_disabled_yield_none() {
+        let mut derived = enabled();
+        let mut first = sample(1000, DVec3::ZERO, DVec3::ZERO);
+        derived.apply(&mut first);
+        let mut duplicate = sample(1000, DVec3::X, DVec3::ZERO);
+        derived.apply(&mut duplicate);
+        assert!(duplicate.jerk.is_none());
+        let mut next = sample(1004, DVec3::X * 2.0, DVec3::ZERO);
+        derived.apply(&mut next);
+        assert!((next.jerk.unwrap() - DVec3::X * 250.0).length() < 1e-9);
+
+        let mut disabled = DerivedSignals::new(DerivedConfig::default());
+        for i in 0..3 {
+            let mut s = sample(1000 + i * 4, DVec3::X * i as f64, DVec3::ZERO);
+            disabled.apply(&mut s);
+            assert!(s.jerk.is_none() && s.ang_accel.is_none());
+        }
+    }
+}
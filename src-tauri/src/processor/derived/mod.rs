@@ -0,0 +1,19 @@
+//! 派生信号：加加速度（jerk）与角加速度。
+//!
+//! 冲击检测与动作质量指标需要 da/dt 与 dω/dt，前端拿到的是抽帧后的数据，
+//! 在 JS 里差分既不准也不方便，因此在滤波之后就地计算：
+//! - 用上一帧低通样本与本帧做有限差分，dt 取两帧设备时间戳之差；
+//! - 差分会放大噪声，结果再过一次独立系数的一阶低通；
+//! - 首帧与 dt 非正（重复或回退的时间戳）时输出为空。
+//!
+//! 由 `[derived]` 配置段开关，关闭时不做任何计算。
+
+/// 派生信号计算逻辑。
+pub mod logic;
+/// 派生信号配置类型。
+pub mod types;
+
+/// 派生信号计算。
+pub use logic::DerivedSignals;
+/// 派生信号配置。
+pub use types::DerivedConfig;
@@ -0,0 +1,35 @@
+//! 派生信号配置类型。
+
+use serde::{Deserialize, Serialize};
+
+use crate::processor::shared::ConfigValidator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 派生信号配置。
+pub struct DerivedConfig {
+    /// 是否启用；关闭时输出字段为空。
+    pub enabled: bool,
+    /// 差分结果的低通系数，越大越平滑。
+    pub alpha: f64,
+}
+
+impl Default for DerivedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            alpha: 0.7,
+        }
+    }
+}
+
+impl DerivedConfig {
+    /// 校验取值范围：`alpha` 须在 [0, 1)，与低通滤波一致。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.check(
+            "alpha",
+            (0.0..1.0).contains(&self.alpha),
+            format!("must be within [0, 1), got {}", self.alpha),
+        );
+    }
+}
@@ -0,0 +1,706 @@
+//! 输出分发。
+//!
+//! 处理线程每输出一帧，把原始帧与发往前端的消息一起送进分发通道；分发线程再把它们
+//! 复制到每个订阅者自己的有界队列。订阅者互不影响：前端订阅、校准向导、姿态视图、
+//! 自检与 recorder 各有一条队列，各自按订阅时的频率抽取；前端订阅也可以按运动强度
+//! 自适应频率（见 [`crate::processor::adaptive_rate`]）。
+//!
+//! 前端类订阅队列满时丢弃最旧的一条，保证订阅端拿到的总是最新数据，也不反压处理线程；
+//! recorder 以 [`OutputFanout::add_frame_sink`] 挂上无损队列，满时阻塞分发线程，
+//! 与原先处理线程直接 `send` 给 recorder 的反压语义一致。
+//! 订阅端接收端被丢弃后，下一次分发时自动注销。
+//!
+//! 录制回放经 [`OutputFanout::dispatch_playback`] 只送给前端消息与姿态订阅：
+//! 原始帧订阅（recorder、标定向导、自检）只接收实时数据，回放帧不会写进正在进行的录制。
+//!
+//! CBOR 编码的前端订阅共享同一份帧数据编码：每帧至多编码一次，见
+//! [`crate::processor::output_encoding`]。
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde::Serialize;
+
+use crate::{
+    processor::{
+        adaptive_rate::AdaptiveRate,
+        attitude_stream::{AttitudeFrame, Decimator, ATTITUDE_CHANNEL_CAPACITY},
+        output::OutputFrame,
+        output_encoding::{CborFrameData, OutputMessage},
+    },
+    types::outputs::{OutputEncoding, StreamFrame, StreamHeartbeat, StreamMessage},
+};
+
+/// 跨线程共享的分发句柄。
+pub type OutputFanoutHandle = Arc<OutputFanout>;
+
+/// 订阅编号，`unsubscribe_output` 据此注销。
+pub type SubscriptionId = u64;
+
+/// 前端输出订阅的队列容量（条），满时丢弃最旧的消息。
+pub const OUTPUT_CHANNEL_CAPACITY: usize = 256;
+
+/// 分发通道容量（条），分发线程被无损订阅者阻塞时处理线程随之阻塞。
+pub const FANOUT_CHANNEL_CAPACITY: usize = 2048;
+
+/// 处理线程每输出一帧发给分发线程的内容。
+#[derive(Debug, Clone, Copy)]
+pub struct OutputItem {
+    /// 完整输出帧。
+    pub frame: OutputFrame,
+    /// 发往前端订阅的消息，静止抑制期间可能为心跳或为空。
+    pub message: Option<StreamMessage>,
+}
+
+/// 订阅者类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriberKind {
+    /// 前端输出消息（完整帧与静止心跳）。
+    Full,
+    /// 仅姿态。
+    Attitude,
+    /// 处理线程的原始输出帧（自检、录制）。
+    Frame,
+}
+
+/// 订阅者当前的输出频率，随诊断数据推送。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SubscriberRate {
+    /// 订阅编号。
+    pub id: SubscriptionId,
+    /// 订阅者类型。
+    pub kind: SubscriberKind,
+    /// 当前生效的最高频率 (Hz)，不抽取时为空。
+    pub rate_hz: Option<f64>,
+    /// 自适应订阅当前的运动强度 (0–1)，其余订阅为空。
+    pub intensity: Option<f64>,
+}
+
+/// 前端订阅的抽取方式。
+#[derive(Debug)]
+enum FullRate {
+    /// 不抽取。
+    Unlimited,
+    /// 固定最高频率。
+    Fixed(Decimator),
+    /// 按运动强度自适应。
+    Adaptive(AdaptiveRate),
+}
+
+/// 订阅者自己的队列。
+#[derive(Debug)]
+struct Queue<T> {
+    tx: flume::Sender<T>,
+    /// 丢旧策略下用于弹出最旧的一条；为空表示无损队列，满时阻塞。
+    rx: Option<flume::Receiver<T>>,
+}
+
+impl<T> Queue<T> {
+    /// 丢旧队列，返回订阅端接收端。
+    fn drop_oldest(capacity: usize) -> (Self, flume::Receiver<T>) {
+        let (tx, rx) = flume::bounded(capacity.max(1));
+        (
+            Self {
+                tx,
+                rx: Some(rx.clone()),
+            },
+            rx,
+        )
+    }
+
+    /// 无损队列。
+    fn lossless(tx: flume::Sender<T>) -> Self {
+        Self { tx, rx: None }
+    }
+
+    /// 放入一条，返回 `false` 表示订阅端已关闭。
+    fn push(&self, mut item: T) -> bool {
+        let Some(rx) = &self.rx else {
+            return self.tx.send(item).is_ok();
+        };
+        // 自己持有一个接收端，只剩它时说明订阅端已丢弃
+        if self.tx.receiver_count() <= 1 {
+            return false;
+        }
+        loop {
+            match self.tx.try_send(item) {
+                Ok(()) => return true,
+                Err(flume::TrySendError::Full(back)) => {
+                    let _ = rx.try_recv();
+                    item = back;
+                }
+                Err(flume::TrySendError::Disconnected(_)) => return false,
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Sink {
+    Full {
+        queue: Queue<OutputMessage>,
+        rate: FullRate,
+        /// 最后放入队列的消息序号，抽取造成的缺口据此重算 `skipped`。
+        last_seq: Option<u64>,
+        /// CBOR 订阅的完整帧附带共享的帧数据编码。
+        encoding: OutputEncoding,
+    },
+    Attitude {
+        queue: Queue<AttitudeFrame>,
+        decimator: Decimator,
+        seq: u64,
+    },
+    Frame {
+        queue: Queue<OutputFrame>,
+    },
+}
+
+impl Sink {
+    fn kind(&self) -> SubscriberKind {
+        match self {
+            Self::Full { .. } => SubscriberKind::Full,
+            Self::Attitude { .. } => SubscriberKind::Attitude,
+            Self::Frame { .. } => SubscriberKind::Frame,
+        }
+    }
+
+    /// 当前频率与运动强度。
+    fn rate(&self) -> (Option<f64>, Option<f64>) {
+        match self {
+            Self::Full { rate, .. } => match rate {
+                FullRate::Unlimited => (None, None),
+                FullRate::Fixed(decimator) => (Some(decimator.rate_hz()), None),
+                FullRate::Adaptive(adaptive) => {
+                    (Some(adaptive.rate_hz()), Some(adaptive.intensity()))
+                }
+            },
+            Self::Attitude { decimator, .. } => (Some(decimator.rate_hz()), None),
+            Self::Frame { .. } => (None, None),
+        }
+    }
+
+    /// 按订阅设置投递一帧，返回 `false` 表示订阅端已关闭。
+    ///
+    /// `cbor` 为本帧数据的 CBOR 编码，第一个需要它的订阅负责编码，之后的订阅直接共享。
+    fn deliver(&mut self, item: &OutputItem, cbor: &mut Option<CborFrameData>) -> bool {
+        match self {
+            Self::Full {
+                queue,
+                rate,
+                last_seq,
+                encoding,
+            } => {
+                // 自适应频率要看到每一帧（含静止抑制掉的帧）才能跟踪运动强度
+                if let FullRate::Adaptive(adaptive) = rate {
+                    adaptive.observe(&item.frame);
+                }
+                let Some(message) = item.message else {
+                    return true;
+                };
+                // 心跳本身已很稀疏，只抽取完整帧
+                let accepted = match (&message, rate) {
+                    (StreamMessage::Frame(_), FullRate::Fixed(decimator)) => {
+                        decimator.accept(message.timestamp_ms())
+                    }
+                    (StreamMessage::Frame(_), FullRate::Adaptive(adaptive)) => {
+                        adaptive.accept(message.timestamp_ms())
+                    }
+                    _ => true,
+                };
+                if !accepted {
+                    return true;
+                }
+                let skipped = last_seq.map_or(message.skipped(), |prev| {
+                    message.seq().saturating_sub(prev + 1)
+                });
+                *last_seq = Some(message.seq());
+                let cbor = match (&message, *encoding) {
+                    (StreamMessage::Frame(frame), OutputEncoding::Cbor) => Some(
+                        cbor.get_or_insert_with(|| CborFrameData::encode(&frame.data))
+                            .clone(),
+                    ),
+                    _ => None,
+                };
+                queue.push(OutputMessage {
+                    message: with_skipped(message, skipped),
+                    cbor,
+                })
+            }
+            Self::Attitude {
+                queue,
+                decimator,
+                seq,
+            } => {
+                if !decimator.accept(item.frame.raw.timestamp_ms.as_millis()) {
+                    return true;
+                }
+                let attitude = AttitudeFrame::from_frame(*seq, &item.frame);
+                // 队列满时丢旧，序号照常递增，前端据此发现丢帧
+                *seq += 1;
+                queue.push(attitude)
+            }
+            Self::Frame { queue } => queue.push(item.frame),
+        }
+    }
+}
+
+/// 订阅者注册表。
+#[derive(Debug, Default)]
+pub struct OutputFanout {
+    next_id: AtomicU64,
+    sinks: Mutex<BTreeMap<SubscriptionId, Sink>>,
+}
+
+impl OutputFanout {
+    /// 创建共享句柄。
+    pub fn new_handle() -> OutputFanoutHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 订阅前端输出消息，`max_rate_hz` 为空时不抽取。
+    pub fn subscribe_full(
+        &self,
+        capacity: usize,
+        max_rate_hz: Option<f64>,
+        encoding: OutputEncoding,
+    ) -> (SubscriptionId, flume::Receiver<OutputMessage>) {
+        let (queue, rx) = Queue::drop_oldest(capacity);
+        let rate = match max_rate_hz {
+            Some(max_rate_hz) => FullRate::Fixed(Decimator::new(max_rate_hz)),
+            None => FullRate::Unlimited,
+        };
+        let id = self.register(Sink::Full {
+            queue,
+            rate,
+            last_seq: None,
+            encoding,
+        });
+        (id, rx)
+    }
+
+    /// 订阅前端输出消息，频率按运动强度在 `min_hz` 与 `max_hz` 之间自适应。
+    pub fn subscribe_adaptive(
+        &self,
+        capacity: usize,
+        min_hz: f64,
+        max_hz: f64,
+        encoding: OutputEncoding,
+    ) -> (SubscriptionId, flume::Receiver<OutputMessage>) {
+        let (queue, rx) = Queue::drop_oldest(capacity);
+        let id = self.register(Sink::Full {
+            queue,
+            rate: FullRate::Adaptive(AdaptiveRate::new(min_hz, max_hz)),
+            last_seq: None,
+            encoding,
+        });
+        (id, rx)
+    }
+
+    /// 订阅姿态流，按 `max_rate_hz` 抽取。
+    pub fn subscribe_attitude(
+        &self,
+        max_rate_hz: f64,
+    ) -> (SubscriptionId, flume::Receiver<AttitudeFrame>) {
+        let (queue, rx) = Queue::drop_oldest(ATTITUDE_CHANNEL_CAPACITY);
+        let id = self.register(Sink::Attitude {
+            queue,
+            decimator: Decimator::new(max_rate_hz),
+            seq: 0,
+        });
+        (id, rx)
+    }
+
+    /// 订阅原始输出帧，队列满时丢旧。
+    pub fn subscribe_frames(
+        &self,
+        capacity: usize,
+    ) -> (SubscriptionId, flume::Receiver<OutputFrame>) {
+        let (queue, rx) = Queue::drop_oldest(capacity);
+        let id = self.register(Sink::Frame { queue });
+        (id, rx)
+    }
+
+    /// 挂上无损的原始帧队列（recorder），队列满时阻塞分发线程。
+    pub fn add_frame_sink(&self, tx: flume::Sender<OutputFrame>) -> SubscriptionId {
+        self.register(Sink::Frame {
+            queue: Queue::lossless(tx),
+        })
+    }
+
+    /// 注销订阅，订阅端随后收到断开；返回该订阅是否存在。
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.lock().remove(&id).is_some()
+    }
+
+    /// 当前订阅者及其类型。
+    pub fn subscribers(&self) -> Vec<(SubscriptionId, SubscriberKind)> {
+        self.lock()
+            .iter()
+            .map(|(id, sink)| (*id, sink.kind()))
+            .collect()
+    }
+
+    /// 各订阅者当前的输出频率。
+    pub fn rates(&self) -> Vec<SubscriberRate> {
+        self.lock()
+            .iter()
+            .map(|(id, sink)| {
+                let (rate_hz, intensity) = sink.rate();
+                SubscriberRate {
+                    id: *id,
+                    kind: sink.kind(),
+                    rate_hz,
+                    intensity,
+                }
+            })
+            .collect()
+    }
+
+    /// 把一帧投递给全部订阅者，订阅端已关闭的顺带注销。
+    pub fn dispatch(&self, item: &OutputItem) {
+        let mut cbor = None;
+        self.lock().retain(|id, sink| {
+            let open = sink.deliver(item, &mut cbor);
+            if !open {
+                tracing::debug!(id, kind = ?sink.kind(), "输出订阅端已关闭，注销订阅");
+            }
+            open
+        });
+    }
+
+    /// 把一帧回放投递给前端消息与姿态订阅，跳过原始帧订阅（含 recorder）。
+    ///
+    /// 跳过的都是原始帧订阅，投递给其余订阅不会阻塞，可在回放任务中直接调用。
+    pub fn dispatch_playback(&self, item: &OutputItem) {
+        let mut cbor = None;
+        self.lock().retain(|id, sink| {
+            if matches!(sink, Sink::Frame { .. }) {
+                return true;
+            }
+            let open = sink.deliver(item, &mut cbor);
+            if !open {
+                tracing::debug!(id, kind = ?sink.kind(), "输出订阅端已关闭，注销订阅");
+            }
+            open
+        });
+    }
+
+    fn register(&self, sink: Sink) -> SubscriptionId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.lock().insert(id, sink);
+        id
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<SubscriptionId, Sink>> {
+        self.sinks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 启动分发线程，分发通道关闭后退出。
+pub fn spawn_fanout(rx: flume::Receiver<OutputItem>, fanout: OutputFanoutHandle) {
+    std::thread::Builder::new()
+        .name("imu-output-fanout".into())
+        .spawn(move || {
+            for item in rx.iter() {
+                fanout.dispatch(&item);
+            }
+        })
+        .expect("spawn output fanout thread failed");
+}
+
+/// 按订阅内相邻消息的序号缺口改写 `skipped`。
+fn with_skipped(message: StreamMessage, skipped: u64) -> StreamMessage {
+    match message {
+        StreamMessage::Frame(frame) => StreamMessage::Frame(StreamFrame { skipped, ..frame }),
+        StreamMessage::Heartbeat(heartbeat) => StreamMessage::Heartbeat(StreamHeartbeat {
+            skipped,
+            ..heartbeat
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::{
+        processor::{
+            navigator::{MotionState, NavState, ZuptState},
+            output::OutputBuilder,
+            parser::ImuSampleRaw,
+            shared::DeviceTimeMs,
+        },
+        types::outputs::StreamFrame,
+    };
+
+    fn frame(timestamp_ms: u64) -> OutputFrame {
+        let attitude = DQuat::from_rotation_z(timestamp_ms as f64 * 1e-3);
+        OutputFrame {
+            raw: ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_no_g: DVec3::ZERO,
+                accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+                gyro: DVec3::ZERO,
+                quat: attitude,
+                angle: DVec3::ZERO,
+                offset: DVec3::ZERO,
+                accel_nav: DVec3::ZERO,
+                baro: None,
+            },
+            nav: NavState {
+                timestamp_ms,
+                position: DVec3::ZERO,
+                velocity: DVec3::ZERO,
+                attitude,
+            },
+            motion_state: MotionState::Moving,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            clock_sync: None,
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: None,
+            debug_vectors: None,
+            stages: None,
+        }
+    }
+
+    /// 第 `seq` 帧，250 Hz。
+    fn item(seq: u64) -> OutputItem {
+        let frame = frame(1_000 + seq * 4);
+        OutputItem {
+            frame,
+            message: Some(StreamMessage::Frame(StreamFrame {
+                seq,
+                skipped: 0,
+                snapshot: false,
+                backfill: false,
+                data: OutputBuilder::build(&frame),
+            })),
+        }
+    }
+
+    #[test]
+    fn subscribers_receive_at_their_own_rates() {
+        let fanout = OutputFanout::default();
+        let (_, full) = fanout.subscribe_full(1024, None, OutputEncoding::Json);
+        let (_, slow) = fanout.subscribe_full(1024, Some(50.0), OutputEncoding::Json);
+        let (_, attitude) = fanout.subscribe_attitude(25.0);
+        for seq in 0..250 {
+            fanout.dispatch(&item(seq));
+        }
+
+        let full: Vec<StreamMessage> = full.drain().map(|m| m.message).collect();
+        assert_eq!(full.len(), 250);
+        assert!(full.iter().all(|m| m.skipped() == 0));
+
+        let slow: Vec<StreamMessage> = slow.drain().map(|m| m.message).collect();
+        assert_eq!(slow.len(), 50);
+        for pair in slow.windows(2) {
+            assert_eq!(pair[1].seq(), pair[0].seq() + 5);
+            // 抽取掉的帧计入缺口
+            assert_eq!(pair[1].skipped(), 4);
+        }
+
+        let attitude: Vec<AttitudeFrame> = attitude.drain().collect();
+        assert_eq!(attitude.len(), 25);
+        for (seq, frame) in attitude.iter().enumerate() {
+            assert_eq!(frame.seq, seq as u64);
+            assert_eq!(frame.timestamp_ms, 1_000 + seq as u64 * 40);
+        }
+    }
+
+    /// 第 `seq` 帧，250 Hz，指定运动状态与角速度模长 (rad/s)。
+    fn motion_item(seq: u64, motion_state: MotionState, gyro: f64) -> OutputItem {
+        let mut frame = frame(1_000 + seq * 4);
+        frame.motion_state = motion_state;
+        frame.raw.gyro = DVec3::new(0.0, 0.0, gyro);
+        OutputItem {
+            frame,
+            message: Some(StreamMessage::Frame(StreamFrame {
+                seq,
+                skipped: 0,
+                snapshot: false,
+                backfill: false,
+                data: OutputBuilder::build(&frame),
+            })),
+        }
+    }
+
+    #[test]
+    fn adaptive_rate_follows_motion_intensity() {
+        let fanout = OutputFanout::default();
+        let (id, rx) = fanout.subscribe_adaptive(1024, 5.0, 250.0, OutputEncoding::Json);
+        let rate = |fanout: &OutputFanout| {
+            let rates = fanout.rates();
+            assert_eq!(rates.len(), 1);
+            assert_eq!(rates[0].id, id);
+            (rates[0].rate_hz.unwrap(), rates[0].intensity.unwrap())
+        };
+
+        // 静止 2 s：按下限 5 Hz 发出
+        for seq in 0..500 {
+            fanout.dispatch(&motion_item(seq, MotionState::Static, 0.0));
+        }
+        assert_eq!(rx.drain().count(), 10);
+        assert_eq!(rate(&fanout), (5.0, 0.0));
+
+        // 转为运动的第一帧立即发出，不等下一次重算
+        fanout.dispatch(&motion_item(500, MotionState::Moving, 6.0));
+        let first: Vec<u64> = rx.drain().map(|m| m.message.seq()).collect();
+        assert_eq!(first, [500]);
+        assert_eq!(rate(&fanout), (250.0, 1.0));
+
+        // 快速运动期间满速率
+        for seq in 501..750 {
+            fanout.dispatch(&motion_item(seq, MotionState::Moving, 6.0));
+        }
+        let fast: Vec<StreamMessage> = rx.drain().map(|m| m.message).collect();
+        assert_eq!(fast.len(), 249);
+        assert!(fast.iter().all(|m| m.skipped() == 0));
+
+        // 回到静止后强度按半衰期回落，频率随之降低
+        for seq in 750..1500 {
+            fanout.dispatch(&motion_item(seq, MotionState::Static, 0.0));
+        }
+        let (rate_hz, intensity) = rate(&fanout);
+        assert!(intensity < 0.02, "{intensity}");
+        assert!(rate_hz < 10.0, "{rate_hz}");
+        assert!(rx.drain().count() < 3 * 250 / 2);
+    }
+
+    #[test]
+    fn overflow_drops_oldest_without_affecting_others() {
+        let fanout = OutputFanout::default();
+        let (_, stalled) = fanout.subscribe_full(4, None, OutputEncoding::Json);
+        let (_, healthy) = fanout.subscribe_full(256, None, OutputEncoding::Json);
+        let (record_tx, record_rx) = flume::bounded(256);
+        fanout.add_frame_sink(record_tx);
+        for seq in 0..100 {
+            fanout.dispatch(&item(seq));
+        }
+
+        let stalled: Vec<u64> = stalled.drain().map(|m| m.message.seq()).collect();
+        assert_eq!(stalled, vec![96, 97, 98, 99]);
+        let healthy: Vec<u64> = healthy.drain().map(|m| m.message.seq()).collect();
+        assert_eq!(healthy, (0..100).collect::<Vec<_>>());
+        assert_eq!(record_rx.len(), 100);
+    }
+
+    #[test]
+    fn cbor_subscribers_share_one_encoding_per_frame() {
+        let fanout = OutputFanout::default();
+        let (_, json) = fanout.subscribe_full(16, None, OutputEncoding::Json);
+        let (_, fast) = fanout.subscribe_full(16, None, OutputEncoding::Cbor);
+        let (_, slow) = fanout.subscribe_full(16, Some(125.0), OutputEncoding::Cbor);
+        for seq in 0..4 {
+            fanout.dispatch(&item(seq));
+        }
+
+        assert!(json.drain().all(|m| m.cbor.is_none()));
+        let fast: Vec<OutputMessage> = fast.drain().collect();
+        let slow: Vec<OutputMessage> = slow.drain().collect();
+        assert_eq!(fast.len(), 4);
+        assert_eq!(slow.len(), 2);
+        for message in &slow {
+            let shared = fast
+                .iter()
+                .find(|m| m.message.seq() == message.message.seq())
+                .unwrap();
+            assert!(shared
+                .cbor
+                .as_ref()
+                .unwrap()
+                .shares_bytes_with(message.cbor.as_ref().unwrap()));
+        }
+        // 消息头仍按订阅改写
+        assert_eq!(slow[1].message.skipped(), 1);
+        assert_eq!(fast[1].message.skipped(), 0);
+    }
+
+    #[test]
+    fn closed_and_unsubscribed_consumers_are_removed() {
+        let fanout = OutputFanout::default();
+        let (dropped, dropped_rx) = fanout.subscribe_full(16, None, OutputEncoding::Json);
+        let (removed, removed_rx) = fanout.subscribe_attitude(250.0);
+        let (kept, kept_rx) = fanout.subscribe_frames(16);
+        let (record_tx, record_rx) = flume::bounded(16);
+        let recorder = fanout.add_frame_sink(record_tx);
+        assert_eq!(fanout.subscribers().len(), 4);
+        assert!([dropped, removed, kept, recorder]
+            .windows(2)
+            .all(|w| w[0] != w[1]));
+
+        // 订阅端丢弃后下一次分发时注销
+        drop(dropped_rx);
+        drop(record_rx);
+        fanout.dispatch(&item(0));
+        assert_eq!(
+            fanout.subscribers(),
+            vec![
+                (removed, SubscriberKind::Attitude),
+                (kept, SubscriberKind::Frame)
+            ]
+        );
+
+        // 主动注销后订阅端收完积压即断开
+        assert!(fanout.unsubscribe(removed));
+        assert!(!fanout.unsubscribe(removed));
+        assert_eq!(removed_rx.drain().count(), 1);
+        assert!(removed_rx.recv().is_err());
+
+        fanout.dispatch(&item(1));
+        assert_eq!(kept_rx.drain().count(), 2);
+        assert_eq!(fanout.subscribers(), vec![(kept, SubscriberKind::Frame)]);
+    }
+
+    #[test]
+    fn playback_skips_frame_sinks() {
+        let fanout = OutputFanout::default();
+        let (_, full) = fanout.subscribe_full(16, None, OutputEncoding::Json);
+        let (_, attitude) = fanout.subscribe_attitude(250.0);
+        let (_, frames) = fanout.subscribe_frames(16);
+        let (record_tx, record_rx) = flume::bounded(16);
+        fanout.add_frame_sink(record_tx);
+        for seq in 0..3 {
+            fanout.dispatch_playback(&item(seq));
+        }
+
+        assert_eq!(full.drain().count(), 3);
+        assert_eq!(attitude.drain().count(), 3);
+        // 回放帧不进 recorder 与原始帧订阅
+        assert!(frames.is_empty());
+        assert!(record_rx.is_empty());
+        assert_eq!(fanout.subscribers().len(), 4);
+    }
+
+    #[test]
+    fn dispatcher_thread_forwards_until_input_closes() {
+        let fanout = OutputFanout::new_handle();
+        let (_, rx) = fanout.subscribe_frames(64);
+        let (tx, input) = flume::bounded(FANOUT_CHANNEL_CAPACITY);
+        spawn_fanout(input, fanout.clone());
+        for seq in 0..10 {
+            tx.send(item(seq)).unwrap();
+        }
+        drop(tx);
+        let timestamps: Vec<u64> = (0..10)
+            .map(|_| rx.recv().unwrap().raw.timestamp_ms.as_millis())
+            .collect();
+        assert_eq!(
+            timestamps,
+            (0..10).map(|seq| 1_000 + seq * 4).collect::<Vec<_>>()
+        );
+    }
+}
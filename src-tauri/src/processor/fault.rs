@@ -0,0 +1,534 @@
+//! 开发构建的随机故障注入。
+//!
+//! 韧性机制（解析失败计数、非有限值守卫、看门狗、录制写入失败容错）平时很难触发，
+//! 这里按配置的概率在处理线程与各阶段的钩子处制造故障：丢弃上游通知、
+//! 解析前篡改一个字节、向某阶段输出写入 NaN、让某阶段延迟 N ms、让录制写入失败。
+//!
+//! 钩子只在 debug 构建或开启 `fault-injection` feature 时生效；
+//! release 构建中 [`FAULT_INJECTION_AVAILABLE`] 为常量 `false`，各钩子直接返回。
+
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::processor::shared::validate::{ConfigErrors, ConfigValidator};
+
+/// 当前构建是否编译了故障注入钩子。
+pub const FAULT_INJECTION_AVAILABLE: bool =
+    cfg!(any(debug_assertions, feature = "fault-injection"));
+
+/// 单次阶段延迟上限 (ms)，避免误配置把处理线程卡死。
+pub const MAX_FAULT_DELAY_MS: u64 = 1_000;
+
+/// 可注入 NaN 或延迟的管线阶段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultStage {
+    /// 标定（含加速度来源选择）输出。
+    #[default]
+    Calibration,
+    /// 滤波（含派生信号）输出。
+    Filter,
+    /// 导航输出。
+    Navigator,
+}
+
+/// 故障注入配置，各概率按每个包/每帧独立判定。
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FaultInjectionConfig {
+    /// 丢弃整个上游蓝牙通知的概率。
+    pub drop_packet: f64,
+    /// 解析前篡改数据包中一个随机字节的概率。
+    pub corrupt_byte: f64,
+    /// 向 `nan_stage` 输出写入 NaN 的概率。
+    pub nan: f64,
+    /// NaN 注入的阶段。
+    pub nan_stage: FaultStage,
+    /// 让 `delay_stage` 延迟 `delay_ms` 的概率。
+    pub delay: f64,
+    /// 延迟注入的阶段。
+    pub delay_stage: FaultStage,
+    /// 每次延迟时长 (ms)。
+    pub delay_ms: u64,
+    /// 录制写入样本失败的概率。
+    pub recorder_insert_fail: f64,
+    /// 随机数种子，缺省时取当前时间，固定种子可复现同一故障序列。
+    pub seed: Option<u64>,
+}
+
+impl FaultInjectionConfig {
+    /// 校验取值范围：概率在 [0, 1]，延迟不超过 [`MAX_FAULT_DELAY_MS`]。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("drop_packet", self.drop_packet, 0.0, 1.0);
+        v.in_range("corrupt_byte", self.corrupt_byte, 0.0, 1.0);
+        v.in_range("nan", self.nan, 0.0, 1.0);
+        v.in_range("delay", self.delay, 0.0, 1.0);
+        v.in_range("recorder_insert_fail", self.recorder_insert_fail, 0.0, 1.0);
+        v.in_range(
+            "delay_ms",
+            self.delay_ms as f64,
+            0.0,
+            MAX_FAULT_DELAY_MS as f64,
+        );
+    }
+
+    /// 是否至少有一种故障的概率大于零。
+    pub fn is_active(&self) -> bool {
+        [
+            self.drop_packet,
+            self.corrupt_byte,
+            self.nan,
+            self.delay,
+            self.recorder_insert_fail,
+        ]
+        .iter()
+        .any(|&p| p > 0.0)
+    }
+}
+
+/// 自上次配置以来各类故障的注入次数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct FaultCounts {
+    /// 丢弃的上游通知数。
+    pub dropped_packets: u64,
+    /// 被篡改字节的数据包数。
+    pub corrupted_packets: u64,
+    /// 注入的 NaN 次数。
+    pub nan_injections: u64,
+    /// 注入的阶段延迟次数。
+    pub delays: u64,
+    /// 强制失败的录制写入次数。
+    pub recorder_insert_failures: u64,
+}
+
+/// 生效中的故障注入，`get_app_status` 中展示。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FaultInjectionStatus {
+    /// 当前配置。
+    pub config: FaultInjectionConfig,
+    /// 已注入次数。
+    pub injected: FaultCounts,
+}
+
+/// 跨线程共享的故障注入句柄。
+pub type FaultInjectorHandle = Arc<FaultInjector>;
+
+/// SplitMix64：无需额外依赖、种子固定时序列可复现。
+#[derive(Debug, Default)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 上的均匀分布。
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Debug, Default)]
+struct InjectorState {
+    config: FaultInjectionConfig,
+    rng: SplitMix64,
+}
+
+#[derive(Debug, Default)]
+struct FaultCounters {
+    dropped_packets: AtomicU64,
+    corrupted_packets: AtomicU64,
+    nan_injections: AtomicU64,
+    delays: AtomicU64,
+    recorder_insert_failures: AtomicU64,
+}
+
+impl FaultCounters {
+    fn snapshot(&self) -> FaultCounts {
+        FaultCounts {
+            dropped_packets: self.dropped_packets.load(Ordering::Relaxed),
+            corrupted_packets: self.corrupted_packets.load(Ordering::Relaxed),
+            nan_injections: self.nan_injections.load(Ordering::Relaxed),
+            delays: self.delays.load(Ordering::Relaxed),
+            recorder_insert_failures: self.recorder_insert_failures.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in [
+            &self.dropped_packets,
+            &self.corrupted_packets,
+            &self.nan_injections,
+            &self.delays,
+            &self.recorder_insert_failures,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 故障注入器，处理线程、管线各阶段与录制任务共享同一个实例。
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    /// 快路径：未启用时钩子只读这一个原子量。
+    active: AtomicBool,
+    state: Mutex<InjectorState>,
+    counts: FaultCounters,
+}
+
+impl FaultInjector {
+    /// 创建共享句柄，初始不注入任何故障。
+    pub fn new_handle() -> FaultInjectorHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 应用新配置并清零注入计数；全部概率为零即关闭注入。
+    pub fn configure(&self, config: FaultInjectionConfig) -> Result<(), ConfigErrors> {
+        let mut v = ConfigValidator::new();
+        v.section("fault_injection", |v| config.validate(v));
+        v.finish()?;
+        let seed = config.seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        let mut state = self.lock();
+        state.config = config;
+        state.rng = SplitMix64(seed);
+        self.counts.reset();
+        self.active.store(config.is_active(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 生效中的注入配置与计数，未启用时为 `None`。
+    pub fn status(&self) -> Option<FaultInjectionStatus> {
+        if !self.enabled() {
+            return None;
+        }
+        Some(FaultInjectionStatus {
+            config: self.lock().config,
+            injected: self.counts.snapshot(),
+        })
+    }
+
+    /// 是否丢弃当前上游通知。
+    pub fn drop_packet(&self) -> bool {
+        self.roll(|c| c.drop_packet, &self.counts.dropped_packets)
+    }
+
+    /// 按概率篡改数据包中的一个随机字节（按位取反，保证确实改变）。
+    pub fn corrupt_byte<'a>(&self, packet: &'a [u8]) -> Cow<'a, [u8]> {
+        if packet.is_empty() || !self.enabled() {
+            return Cow::Borrowed(packet);
+        }
+        let index = {
+            let mut state = self.lock();
+            let p = state.config.corrupt_byte;
+            if state.rng.next_f64() >= p {
+                return Cow::Borrowed(packet);
+            }
+            (state.rng.next_u64() % packet.len() as u64) as usize
+        };
+        self.counts
+            .corrupted_packets
+            .fetch_add(1, Ordering::Relaxed);
+        let mut corrupted = packet.to_vec();
+        corrupted[index] = !corrupted[index];
+        Cow::Owned(corrupted)
+    }
+
+    /// `stage` 的输出是否应写入 NaN。
+    pub fn inject_nan(&self, stage: FaultStage) -> bool {
+        self.roll(
+            |c| if c.nan_stage == stage { c.nan } else { 0.0 },
+            &self.counts.nan_injections,
+        )
+    }
+
+    /// 按概率让当前线程在 `stage` 内休眠配置的时长。
+    pub fn delay(&self, stage: FaultStage) {
+        if !self.enabled() {
+            return;
+        }
+        let delay_ms = self.lock().config.delay_ms;
+        if self.roll(
+            |c| if c.delay_stage == stage { c.delay } else { 0.0 },
+            &self.counts.delays,
+        ) {
+            std::thread::sleep(Duration::from_millis(delay_ms));
+        }
+    }
+
+    /// 本次录制写入是否应失败。
+    pub fn fail_recorder_insert(&self) -> bool {
+        self.roll(
+            |c| c.recorder_insert_fail,
+            &self.counts.recorder_insert_failures,
+        )
+    }
+
+    #[inline]
+    fn enabled(&self) -> bool {
+        FAULT_INJECTION_AVAILABLE && self.active.load(Ordering::Relaxed)
+    }
+
+    fn roll(
+        &self,
+        probability: impl FnOnce(&FaultInjectionConfig) -> f64,
+        hits: &AtomicU64,
+    ) -> bool {
+        if !self.enabled() {
+            return false;
+        }
+        let hit = {
+            let mut state = self.lock();
+            let p = probability(&state.config);
+            p > 0.0 && state.rng.next_f64() < p
+        };
+        if hit {
+            hits.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InjectorState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "fault-injection")))]
+mod tests {
+    use std::{
+        sync::{atomic::AtomicBool, Arc},
+        time::Instant,
+    };
+
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::processor::{
+        parser::{ImuParser, ImuSampleRaw},
+        pipeline::{
+            diagnostics::{diagnostics_channel, QueueProbe},
+            ProcessorPipeline, ProcessorPipelineConfig,
+        },
+        shared::{DeviceTimeMs, HostTimeMs},
+        stats::ProcessorStats,
+        watchdog::{DataFlowWatchdog, FlowStage, WatchdogEvent},
+    };
+
+    fn pipeline_with(faults: &FaultInjectorHandle) -> ProcessorPipeline {
+        let (upstream_tx, upstream_rx) = flume::unbounded();
+        std::mem::forget(upstream_tx);
+        let (downstream_tx, _) = flume::unbounded();
+        let (record_tx, _) = flume::unbounded();
+        let (diag_tx, _) = diagnostics_channel(8);
+        let mut pipeline = ProcessorPipeline::new(
+            ProcessorPipelineConfig::default(),
+            Arc::new(AtomicBool::new(false)),
+            diag_tx,
+            QueueProbe::new(upstream_rx, downstream_tx, record_tx),
+        );
+        pipeline.set_fault_injector(faults.clone());
+        pipeline
+    }
+
+    /// 250 Hz 的合成数据包，轻微晃动。
+    fn packet(i: u64) -> Vec<u8> {
+        let phase = i as f64 * 0.05;
+        ImuParser::encode(&ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(i * 4),
+            accel_no_g: DVec3::new(0.5 * phase.sin(), 0.2 * phase.cos(), 0.0),
+            accel_with_g: DVec3::new(0.5 * phase.sin(), 0.2 * phase.cos(), 9.80665),
+            gyro: DVec3::new(10.0 * phase.cos(), 0.0, 0.0),
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        })
+    }
+
+    fn configure(faults: &FaultInjector, config: FaultInjectionConfig) {
+        faults
+            .configure(FaultInjectionConfig {
+                seed: Some(42),
+                ..config
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn invalid_config_is_rejected_and_zero_config_disables() {
+        let faults = FaultInjector::new_handle();
+        let error = faults
+            .configure(FaultInjectionConfig {
+                drop_packet: 1.5,
+                delay_ms: MAX_FAULT_DELAY_MS + 1,
+                ..Default::default()
+            })
+            .unwrap_err();
+        let paths: Vec<_> = error.0.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["fault_injection.drop_packet", "fault_injection.delay_ms"]
+        );
+        assert_eq!(faults.status(), None);
+
+        configure(
+            &faults,
+            FaultInjectionConfig {
+                drop_packet: 1.0,
+                ..Default::default()
+            },
+        );
+        assert!(faults.drop_packet());
+        assert_eq!(faults.status().unwrap().injected.dropped_packets, 1);
+        configure(&faults, FaultInjectionConfig::default());
+        assert_eq!(faults.status(), None);
+        assert!(!faults.drop_packet());
+    }
+
+    #[test]
+    fn dropped_packets_trip_output_watchdog_until_disabled() {
+        let faults = FaultInjector::new_handle();
+        let mut pipeline = pipeline_with(&faults);
+        let stats = ProcessorStats::new_handle();
+        let mut watchdog = DataFlowWatchdog::default();
+        let mut events = Vec::new();
+        let start_ms = 1_000_000;
+
+        for i in 0..1500u64 {
+            // 0.5~4.5 s 之间丢弃全部通知；接收任务照常记录输入
+            let dropping = (125..1125).contains(&i);
+            if i == 125 || i == 1125 {
+                configure(
+                    &faults,
+                    FaultInjectionConfig {
+                        drop_packet: if dropping { 1.0 } else { 0.0 },
+                        ..Default::default()
+                    },
+                );
+            }
+            let now_ms = start_ms + i * 4;
+            stats.record_input_at(HostTimeMs(now_ms));
+            let data = packet(i);
+            for frame_bytes in pipeline.reassemble(&data, None) {
+                if let Some(frame) = pipeline.process_packet(&frame_bytes) {
+                    stats.record_frame_at(frame.raw.timestamp_ms, HostTimeMs(now_ms));
+                }
+            }
+            if i % 25 == 0 {
+                events.extend(watchdog.check(now_ms, true, &stats.snapshot()));
+            }
+        }
+
+        assert!(matches!(
+            events.as_slice(),
+            [WatchdogEvent::Stalled(stall), WatchdogEvent::Resumed(_)]
+                if stall.stage == FlowStage::Output
+        ));
+        assert_eq!(watchdog.snapshot().stall_count, 1);
+    }
+
+    #[test]
+    fn corrupted_bytes_count_parse_failures_and_parsing_recovers() {
+        let faults = FaultInjector::new_handle();
+        let mut pipeline = pipeline_with(&faults);
+        configure(
+            &faults,
+            FaultInjectionConfig {
+                corrupt_byte: 1.0,
+                ..Default::default()
+            },
+        );
+        for i in 0..300 {
+            pipeline.process_packet(&packet(i));
+        }
+        let status = faults.status().unwrap();
+        assert_eq!(status.injected.corrupted_packets, 300);
+        let failures = pipeline.parse_failures();
+        assert!(failures > 0 && failures < 300, "failures = {failures}");
+
+        configure(&faults, FaultInjectionConfig::default());
+        let frames = (300..400)
+            .filter_map(|i| pipeline.process_packet(&packet(i)))
+            .count();
+        assert!(frames > 0);
+        assert_eq!(pipeline.parse_failures(), failures);
+    }
+
+    #[test]
+    fn nan_in_each_stage_is_dropped_and_pipeline_recovers() {
+        for stage in [
+            FaultStage::Calibration,
+            FaultStage::Filter,
+            FaultStage::Navigator,
+        ] {
+            let faults = FaultInjector::new_handle();
+            let mut pipeline = pipeline_with(&faults);
+            for i in 0..50 {
+                assert!(pipeline.process_packet(&packet(i)).is_some());
+            }
+            configure(
+                &faults,
+                FaultInjectionConfig {
+                    nan: 1.0,
+                    nan_stage: stage,
+                    ..Default::default()
+                },
+            );
+            for i in 50..70 {
+                assert!(pipeline.process_packet(&packet(i)).is_none(), "{stage:?}");
+            }
+            assert_eq!(pipeline.non_finite_samples(), 20, "{stage:?}");
+            assert_eq!(faults.status().unwrap().injected.nan_injections, 20);
+
+            configure(&faults, FaultInjectionConfig::default());
+            for i in 70..120 {
+                let frame = pipeline.process_packet(&packet(i)).unwrap();
+                assert!(
+                    frame.nav.position.is_finite() && frame.nav.velocity.is_finite(),
+                    "{stage:?}"
+                );
+            }
+            assert_eq!(pipeline.non_finite_samples(), 20);
+        }
+    }
+
+    #[test]
+    fn stage_delay_blocks_for_configured_duration() {
+        let faults = FaultInjector::new_handle();
+        let mut pipeline = pipeline_with(&faults);
+        configure(
+            &faults,
+            FaultInjectionConfig {
+                delay: 1.0,
+                delay_stage: FaultStage::Filter,
+                delay_ms: 20,
+                ..Default::default()
+            },
+        );
+        let started = Instant::now();
+        for i in 0..3 {
+            assert!(pipeline.process_packet(&packet(i)).is_some());
+        }
+        assert!(started.elapsed().as_millis() >= 60);
+        assert_eq!(faults.status().unwrap().injected.delays, 3);
+    }
+}
@@ -6,6 +6,7 @@ use crate::processor::calibration::ImuSampleCalibrated;
 use crate::processor::filter::types::{ImuSampleFiltered, LowPassFilterConfig};
 
 /// 一阶低通滤波器。
+#[derive(Clone)]
 pub struct LowPassFilter {
     config: LowPassFilterConfig,
     prev_accel: Option<DVec3>,
@@ -22,6 +23,16 @@ impl LowPassFilter {
         }
     }
 
+    /// 原地更新参数，保留滤波状态。
+    ///
+    /// 仅在 passby 开关切换时清空历史，避免用停用前的旧值续滤。
+    pub fn reconfigure(&mut self, config: LowPassFilterConfig) {
+        if config.passby != self.config.passby {
+            self.reset();
+        }
+        self.config = config;
+    }
+
     /// 应用滤波并输出低通样本。
     ///
     /// 参数:
@@ -38,6 +49,8 @@ impl LowPassFilter {
                 timestamp_ms: sample.timestamp_ms,
                 accel_lp: sample.accel,
                 gyro_lp: sample.gyro,
+                jerk: None,
+                ang_accel: None,
             };
         }
         let alpha = self.config.alpha;
@@ -58,6 +71,8 @@ impl LowPassFilter {
             timestamp_ms: sample.timestamp_ms,
             accel_lp,
             gyro_lp,
+            jerk: None,
+            ang_accel: None,
         }
     }
 
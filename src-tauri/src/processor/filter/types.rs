@@ -3,10 +3,13 @@
 use math_f64::DVec3;
 use serde::{Deserialize, Serialize};
 
+use crate::processor::shared::{ConfigValidator, DeviceTimeMs};
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 /// 低通滤波配置。
 pub struct LowPassFilterConfig {
-    /// 是否跳过滤波处理。
+    /// 是否跳过滤波处理，跳过时样本原样传给下游。
+    #[serde(default)]
     pub passby: bool,
     /// 滤波系数，越大越平滑。
     pub alpha: f64,
@@ -21,13 +24,28 @@ impl Default for LowPassFilterConfig {
     }
 }
 
+impl LowPassFilterConfig {
+    /// 校验取值范围：`alpha` 须在 [0, 1)，取 1 时输出永远停在第一帧。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.check(
+            "alpha",
+            (0.0..1.0).contains(&self.alpha),
+            format!("must be within [0, 1), got {}", self.alpha),
+        );
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize)]
 /// 低通滤波后的 IMU 样本。
 pub struct ImuSampleFiltered {
     /// 时间戳（毫秒）。
-    pub timestamp_ms: u64,
+    pub timestamp_ms: DeviceTimeMs,
     /// 低通滤波后的加速度。
     pub accel_lp: DVec3,
     /// 低通滤波后的角速度。
     pub gyro_lp: DVec3,
+    /// 加加速度（m/s³），派生信号关闭、首帧或 dt 非正时为空。
+    pub jerk: Option<DVec3>,
+    /// 角加速度（rad/s²），为空的条件同 `jerk`。
+    pub ang_accel: Option<DVec3>,
 }
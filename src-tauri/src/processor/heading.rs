@@ -0,0 +1,344 @@
+//! 按首段运动方向对齐航向。
+//!
+//! 空中书写等场景里，用户希望“前方”就是自己第一次移动的方向，而不是磁北或设备自身朝向。
+//! 对齐器在启用且被 `arm_heading_from_motion` 武装后，等待武装之后的第一段运动：
+//! 运动在静止中结束、且水平位移不小于 `min_displacement_m` 时，取该位移在世界系中的
+//! 偏航角，之后对输出的导航状态（位置、速度、姿态）施加绕 Z 轴的旋转，使该方向落在 +X 上。
+//! 内部导航状态不变，旋转只作用于输出帧；零位校准与安装方向重映射发生在导航之前，
+//! 因而对齐角始终在校准后的世界系中计算。
+
+use math_f64::DQuat;
+use serde::{Deserialize, Serialize};
+
+use crate::processor::{
+    output::OutputFrame,
+    segment::{SegmentConfig, SegmentDetector},
+    shared::validate::ConfigValidator,
+};
+
+/// 航向对齐配置。
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HeadingAlignConfig {
+    /// 是否启用。关闭时不施加旋转，武装请求被拒绝。
+    pub enabled: bool,
+    /// 用于对齐的运动段最小水平位移 (m)，更短的运动忽略并继续等待。
+    pub min_displacement_m: f64,
+    /// 静止需持续的时长 (ms) 才算运动结束。
+    pub min_static_ms: f64,
+}
+
+impl Default for HeadingAlignConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_displacement_m: 0.3,
+            min_static_ms: 100.0,
+        }
+    }
+}
+
+impl HeadingAlignConfig {
+    /// 校验取值范围：最小位移 0 ~ 10 m，静止时长 0 ~ 10000 ms。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.positive("min_displacement_m", self.min_displacement_m, 10.0);
+        v.in_range("min_static_ms", self.min_static_ms, 0.0, 10_000.0);
+    }
+}
+
+/// `heading_aligned` 事件载荷：输出航向已按首段运动方向对齐。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HeadingAligned {
+    /// 运动段结束（静止起点）的设备时间戳 (ms)。
+    pub timestamp_ms: u64,
+    /// 施加到输出的绕 Z 轴旋转 (°，逆时针为正)，即首段运动方向偏航角取反。
+    pub yaw_deg: f64,
+    /// 该运动段的水平位移 (m)。
+    pub displacement_m: f64,
+}
+
+/// 航向对齐器，由管线持有，在导航之后、运动分段之前处理输出帧。
+#[derive(Debug, Clone)]
+pub struct HeadingAligner {
+    config: HeadingAlignConfig,
+    /// 是否在等待下一段运动。
+    armed: bool,
+    /// 运动边界检测，看到的是旋转前的世界系位置。
+    detector: SegmentDetector,
+    /// 当前施加到输出的旋转角 (rad)，未对齐时为空。
+    yaw_rad: Option<f64>,
+    /// 尚未被取走的对齐事件。
+    event: Option<HeadingAligned>,
+}
+
+impl HeadingAligner {
+    /// 创建对齐器，初始未武装、不旋转。
+    pub fn new(config: HeadingAlignConfig) -> Self {
+        Self {
+            config,
+            armed: false,
+            detector: SegmentDetector::new(Self::segment_config(config)),
+            yaw_rad: None,
+            event: None,
+        }
+    }
+
+    fn segment_config(config: HeadingAlignConfig) -> SegmentConfig {
+        SegmentConfig {
+            enabled: true,
+            min_duration_ms: 0.0,
+            min_path_length_m: 0.0,
+            min_static_ms: config.min_static_ms,
+        }
+    }
+
+    /// 原地更新配置；关闭时清除武装状态与已施加的旋转。
+    pub fn reconfigure(&mut self, config: HeadingAlignConfig) {
+        if !config.enabled {
+            self.reset();
+        }
+        self.config = config;
+        self.detector.reconfigure(Self::segment_config(config));
+    }
+
+    /// 清空武装状态与已施加的旋转。
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// 零位校准改变世界系航向后调用：丢弃已施加的旋转与进行中的运动，武装状态保留。
+    pub fn rebase(&mut self) {
+        self.yaw_rad = None;
+        self.detector.reset();
+    }
+
+    /// 武装：等待下一段足够长的运动重新对齐，已施加的旋转保留到新对齐生效。
+    ///
+    /// 未启用时返回错误。
+    pub fn arm(&mut self) -> Result<(), &'static str> {
+        if !self.config.enabled {
+            return Err("heading alignment is disabled");
+        }
+        self.armed = true;
+        // 只认武装之后开始的运动
+        self.detector.reset();
+        Ok(())
+    }
+
+    /// 是否在等待运动。
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// 当前施加到输出的旋转 (°)，未对齐时为 `None`。
+    pub fn yaw_deg(&self) -> Option<f64> {
+        self.yaw_rad.map(f64::to_degrees)
+    }
+
+    /// 直接设定施加到输出的旋转 (°)，`None` 表示不旋转；不改变武装状态。
+    ///
+    /// 离线重跑录制时用会话保存的对齐角复现录制时的输出。
+    pub fn set_yaw_deg(&mut self, yaw_deg: Option<f64>) {
+        self.yaw_rad = yaw_deg.map(f64::to_radians);
+    }
+
+    /// 取走最近一次对齐事件。
+    pub fn take_event(&mut self) -> Option<HeadingAligned> {
+        self.event.take()
+    }
+
+    /// 消费一帧旋转前的输出；武装中遇到满足条件的运动段时更新旋转并生成事件。
+    ///
+    /// 本帧完成对齐时返回该事件的副本，事件本身仍留待 [`take_event`](Self::take_event) 取走。
+    pub fn update(&mut self, frame: &OutputFrame) -> Option<HeadingAligned> {
+        if !self.config.enabled || !self.armed {
+            return None;
+        }
+        let segment = self.detector.update(frame)?;
+        let displacement = segment.displacement;
+        let horizontal_m = displacement.x.hypot(displacement.y);
+        if horizontal_m < self.config.min_displacement_m {
+            return None;
+        }
+        let yaw_rad = -displacement.y.atan2(displacement.x);
+        self.yaw_rad = Some(yaw_rad);
+        self.armed = false;
+        let event = HeadingAligned {
+            timestamp_ms: segment.end_ms,
+            yaw_deg: yaw_rad.to_degrees(),
+            displacement_m: horizontal_m,
+        };
+        self.event = Some(event);
+        tracing::info!(
+            yaw_deg = yaw_rad.to_degrees(),
+            displacement_m = horizontal_m,
+            "航向已按首段运动方向对齐"
+        );
+        Some(event)
+    }
+
+    /// 对输出帧的导航状态施加当前旋转，并记录生效的对齐角。
+    pub fn apply(&self, frame: &mut OutputFrame) {
+        let Some(yaw_rad) = self.yaw_rad else {
+            return;
+        };
+        let rotation = DQuat::from_rotation_z(yaw_rad);
+        frame.nav.position = rotation * frame.nav.position;
+        frame.nav.velocity = rotation * frame.nav.velocity;
+        frame.nav.attitude = rotation * frame.nav.attitude;
+        if let Some(vectors) = frame.debug_vectors.as_mut() {
+            vectors.gravity_ref = rotation * vectors.gravity_ref;
+            vectors.accel_world = rotation * vectors.accel_world;
+            vectors.accel_lin = rotation * vectors.accel_lin;
+        }
+        frame.heading_yaw_deg = Some(yaw_rad.to_degrees());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::DVec3;
+
+    use super::*;
+    use crate::processor::{
+        navigator::{MotionState, NavState, ZuptState},
+        parser::ImuSampleRaw,
+        shared::DeviceTimeMs,
+    };
+
+    fn frame(timestamp_ms: u64, motion_state: MotionState, position: DVec3) -> OutputFrame {
+        OutputFrame {
+            raw: ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_no_g: DVec3::ZERO,
+                accel_with_g: DVec3::ZERO,
+                gyro: DVec3::ZERO,
+                quat: DQuat::IDENTITY,
+                angle: DVec3::ZERO,
+                offset: DVec3::ZERO,
+                accel_nav: DVec3::ZERO,
+                baro: None,
+            },
+            nav: NavState {
+                timestamp_ms,
+                position,
+                velocity: DVec3::ZERO,
+                attitude: DQuat::IDENTITY,
+            },
+            motion_state,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            clock_sync: None,
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: None,
+            debug_vectors: None,
+            stages: None,
+        }
+    }
+
+    /// 从 `from` 静止出发，沿 `direction` 走 `frames` 帧（每帧 0.01 m）后静止 200 ms。
+    fn stroke(start_ms: u64, from: DVec3, direction: DVec3, frames: u64) -> Vec<OutputFrame> {
+        let step = direction.normalize() * 0.01;
+        let mut out = vec![frame(start_ms, MotionState::Static, from)];
+        for i in 1..=frames {
+            out.push(frame(
+                start_ms + i * 4,
+                MotionState::Moving,
+                from + step * i as f64,
+            ));
+        }
+        let end = from + step * frames as f64;
+        for i in 1..=50 {
+            out.push(frame(start_ms + (frames + i) * 4, MotionState::Static, end));
+        }
+        out
+    }
+
+    /// 逐帧对齐并返回旋转后的输出。
+    fn run(aligner: &mut HeadingAligner, frames: Vec<OutputFrame>) -> Vec<OutputFrame> {
+        frames
+            .into_iter()
+            .map(|mut frame| {
+                aligner.update(&frame);
+                aligner.apply(&mut frame);
+                frame
+            })
+            .collect()
+    }
+
+    fn enabled() -> HeadingAlignConfig {
+        HeadingAlignConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diagonal_first_stroke_is_rotated_onto_positive_x() {
+        let mut aligner = HeadingAligner::new(enabled());
+        aligner.arm().unwrap();
+
+        // 武装后的首段运动沿世界系 (-1, 1) 方向走 0.5 m
+        let diagonal = DVec3::new(-1.0, 1.0, 0.0);
+        let first = run(&mut aligner, stroke(1000, DVec3::ZERO, diagonal, 50));
+        let event = aligner.take_event().unwrap();
+        assert!(!aligner.is_armed());
+        assert!((event.yaw_deg + 135.0).abs() < 1e-9, "{event:?}");
+        assert!((event.displacement_m - 0.5).abs() < 1e-9);
+        assert_eq!(event.timestamp_ms, 1000 + 51 * 4);
+        // 对齐在运动结束时生效，之后的帧落在 +X 轴上
+        let end = first.last().unwrap();
+        assert!((end.nav.position.x - 0.5).abs() < 1e-9, "{:?}", end.nav);
+        assert!(end.nav.position.y.abs() < 1e-9);
+        assert_eq!(end.heading_yaw_deg, Some(event.yaw_deg));
+
+        // 同一方向的后续轨迹整体沿 +X 前进
+        let second = run(
+            &mut aligner,
+            stroke(2000, DVec3::new(-0.5, 0.5, 0.0) / 2f64.sqrt(), diagonal, 30),
+        );
+        for frame in &second {
+            assert!(frame.nav.position.y.abs() < 1e-9, "{:?}", frame.nav);
+        }
+        assert!((second.last().unwrap().nav.position.x - 0.8).abs() < 1e-9);
+        // 姿态随之旋转
+        let heading = second[0].nav.attitude * DVec3::X;
+        assert!(
+            (heading - DQuat::from_rotation_z(-135f64.to_radians()) * DVec3::X).length() < 1e-9
+        );
+        assert!(aligner.take_event().is_none());
+    }
+
+    #[test]
+    fn short_strokes_are_skipped_and_rearming_replaces_the_rotation() {
+        let mut aligner = HeadingAligner::new(enabled());
+        // 未武装时不对齐
+        run(&mut aligner, stroke(0, DVec3::ZERO, DVec3::Y, 50));
+        assert!(aligner.take_event().is_none());
+        assert_eq!(aligner.yaw_deg(), None);
+
+        aligner.arm().unwrap();
+        // 水平位移不足：竖直运动与 0.2 m 的短划都忽略
+        run(&mut aligner, stroke(1000, DVec3::ZERO, DVec3::Z, 50));
+        run(&mut aligner, stroke(2000, DVec3::ZERO, DVec3::Y, 20));
+        assert!(aligner.is_armed());
+        run(&mut aligner, stroke(3000, DVec3::ZERO, DVec3::Y, 40));
+        assert!((aligner.yaw_deg().unwrap() + 90.0).abs() < 1e-9);
+
+        // 重新武装：按内部世界系重新计算，不与上一次旋转叠加
+        aligner.arm().unwrap();
+        assert!((aligner.yaw_deg().unwrap() + 90.0).abs() < 1e-9);
+        let out = run(&mut aligner, stroke(4000, DVec3::ZERO, -DVec3::X, 40));
+        assert!((aligner.yaw_deg().unwrap().abs() - 180.0).abs() < 1e-9);
+        let end = out.last().unwrap().nav.position;
+        assert!((end - DVec3::new(0.4, 0.0, 0.0)).length() < 1e-9, "{end:?}");
+
+        // 关闭后不再旋转，也不能武装
+        aligner.reconfigure(HeadingAlignConfig::default());
+        assert_eq!(aligner.yaw_deg(), None);
+        assert!(aligner.arm().is_err());
+    }
+}
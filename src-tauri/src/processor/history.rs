@@ -0,0 +1,292 @@
+//! 输出订阅的近期历史。
+//!
+//! 前端重载后重新订阅时只能从下一帧开始画：图表是空的，3D 模型在当前姿态突然出现且没有轨迹。
+//! 处理线程把发往前端订阅的每条消息（完整帧与心跳）同时写入定长历史；订阅时按选项
+//! 先发最新一帧快照，再按订阅方的最高频率回放最近 `backfill_ms` 的历史，之后才转发实时消息。
+//!
+//! 处理线程先写历史再交给分发线程，订阅端先登记自己的队列再取历史，以取历史时的最后序号为界：
+//! 队列里序号不超过它的消息已包含在回放中，直接丢弃。回放与实时消息拼接后序号连续，
+//! 缺口都由 `skipped` 标注（抽取掉的历史帧同样计入）。
+
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    processor::{
+        attitude_stream::Decimator,
+        shared::{BoundedBuffer, BufferRegistry},
+    },
+    types::outputs::{
+        OutputSubscribeOptions, ResponseData, StreamFrame, StreamHeartbeat, StreamMessage,
+    },
+};
+
+/// 历史容量（条），250 Hz 下约 10 s。
+pub const OUTPUT_HISTORY_CAPACITY: usize = 2500;
+
+/// 跨线程共享的历史句柄。
+pub type OutputHistoryHandle = Arc<OutputHistory>;
+
+/// 发往前端订阅的近期消息。
+#[derive(Debug)]
+pub struct OutputHistory {
+    messages: Mutex<BoundedBuffer<StreamMessage>>,
+}
+
+impl Default for OutputHistory {
+    fn default() -> Self {
+        Self::new(OUTPUT_HISTORY_CAPACITY)
+    }
+}
+
+/// 订阅开始时要先发出的消息，以及区分实时消息的序号分界。
+#[derive(Debug)]
+pub struct OutputReplay {
+    /// 快照与回放消息，按发送顺序排列。
+    pub messages: Vec<StreamMessage>,
+    /// 回放覆盖到的最后序号。
+    boundary: Option<u64>,
+}
+
+impl OutputReplay {
+    /// 队列中的消息是否尚未包含在回放中，应当转发。
+    pub fn is_live(&self, message: &StreamMessage) -> bool {
+        self.boundary
+            .is_none_or(|boundary| message.seq() > boundary)
+    }
+}
+
+impl OutputHistory {
+    /// 创建容量为 `capacity` 条的历史，登记到全局缓冲区登记表。
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: Mutex::new(
+                BoundedBuffer::new(capacity).register(BufferRegistry::global(), "output_history"),
+            ),
+        }
+    }
+
+    /// 创建共享句柄。
+    pub fn new_handle() -> OutputHistoryHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 记录一条消息，处理线程在发往订阅通道之前调用；满时丢弃最旧的一条。
+    pub fn push(&self, message: StreamMessage) {
+        self.lock().push(message);
+    }
+
+    /// 按订阅选项生成快照与回放消息。
+    ///
+    /// 快照取自最新帧缓存 `latest`（可能比最后一条历史更新，静止抑制的帧也会写入缓存），
+    /// 序号记为分界序号；回放最后一条总是保留，抽取只作用于更早的消息。
+    pub fn replay(
+        &self,
+        latest: Option<ResponseData>,
+        options: &OutputSubscribeOptions,
+    ) -> OutputReplay {
+        let history = self.lock();
+        let boundary = history.back().map(StreamMessage::seq);
+        let mut messages = Vec::new();
+
+        if let Some(data) = latest.filter(|_| options.include_snapshot) {
+            messages.push(StreamMessage::Frame(StreamFrame {
+                seq: boundary.unwrap_or_default(),
+                skipped: 0,
+                snapshot: true,
+                backfill: false,
+                data,
+            }));
+        }
+
+        if let (Some(last), true) = (history.back(), options.backfill_ms > 0) {
+            // 设备复位后时间戳回退，窗口只取与最后一条连续的那一段
+            let end_ms = last.timestamp_ms();
+            let start_ms = end_ms.saturating_sub(options.backfill_ms);
+            let len = history
+                .iter()
+                .rev()
+                .take_while(|m| (start_ms..=end_ms).contains(&m.timestamp_ms()))
+                .count();
+            let mut decimator = options.max_rate_hz.map(Decimator::new);
+            let mut prev_seq = None;
+            for (i, message) in history.range(history.len() - len..).enumerate() {
+                let accepted = decimator
+                    .as_mut()
+                    .is_none_or(|decimator| decimator.accept(message.timestamp_ms()));
+                if !accepted && i + 1 < len {
+                    continue;
+                }
+                let skipped = prev_seq.map_or(message.skipped(), |prev| message.seq() - prev - 1);
+                prev_seq = Some(message.seq());
+                messages.push(as_backfill(*message, skipped));
+            }
+        }
+
+        OutputReplay { messages, boundary }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BoundedBuffer<StreamMessage>> {
+        self.messages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 标记为回放消息，`skipped` 按回放内相邻消息的序号缺口重算。
+fn as_backfill(message: StreamMessage, skipped: u64) -> StreamMessage {
+    match message {
+        StreamMessage::Frame(frame) => StreamMessage::Frame(StreamFrame {
+            skipped,
+            backfill: true,
+            ..frame
+        }),
+        StreamMessage::Heartbeat(heartbeat) => StreamMessage::Heartbeat(StreamHeartbeat {
+            skipped,
+            backfill: true,
+            ..heartbeat
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::processor::{
+        navigator::{MotionState, ZuptState},
+        suppress::{OutputSuppressConfig, StaticSuppressor},
+    };
+    use crate::types::outputs::RESPONSE_SCHEMA_VERSION;
+
+    fn data(timestamp_ms: u64) -> ResponseData {
+        // 每 1.2 s 中静止 0.6 s：静止段只发心跳
+        let moving = timestamp_ms % 1200 < 600;
+        ResponseData {
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms,
+            accel: DVec3::ZERO,
+            accel_with_g: DVec3::ZERO,
+            gyro: DVec3::ZERO,
+            attitude: DQuat::IDENTITY,
+            velocity: DVec3::ZERO,
+            position: DVec3::new(if moving { timestamp_ms as f64 } else { 0.0 }, 0.0, 0.0),
+            accel_saturated: false,
+            motion_state: if moving {
+                MotionState::Moving
+            } else {
+                MotionState::Static
+            },
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            host_time_estimate_ms: None,
+            debug_vectors: None,
+        }
+    }
+
+    #[test]
+    fn backfill_and_live_messages_are_contiguous_by_seq() {
+        let history = OutputHistory::new_handle();
+        let (tx, rx) = flume::unbounded();
+        let (subscribed_tx, subscribed_rx) = flume::bounded(0);
+        let total = 3000u64;
+
+        // 处理线程：静止抑制 → 写历史 → 发通道，中途等订阅端就绪后继续
+        let producer = {
+            let history = history.clone();
+            thread::spawn(move || {
+                let config = OutputSuppressConfig {
+                    enabled: true,
+                    ..OutputSuppressConfig::default()
+                };
+                let mut suppressor = StaticSuppressor::default();
+                let mut last_seq = 0;
+                for i in 0..total {
+                    if i == 1500 {
+                        subscribed_rx.recv().unwrap();
+                    }
+                    if let Some(message) = suppressor.process(&data(i * 4), &config) {
+                        history.push(message);
+                        tx.send(message).unwrap();
+                        last_seq = message.seq();
+                    }
+                }
+                last_seq
+            })
+        };
+
+        // 订阅端：运行到一半时订阅，与处理线程并发清空通道、取回放
+        subscribed_tx.send(()).unwrap();
+        rx.drain();
+        let options = OutputSubscribeOptions {
+            include_snapshot: true,
+            backfill_ms: 2000,
+            max_rate_hz: Some(60.0),
+            ..OutputSubscribeOptions::default()
+        };
+        let replay = history.replay(Some(data(0)), &options);
+        let last_seq = producer.join().unwrap();
+        let live: Vec<_> = rx.drain().filter(|m| replay.is_live(m)).collect();
+
+        let StreamMessage::Frame(snapshot) = replay.messages[0] else {
+            panic!("expected snapshot");
+        };
+        assert!(snapshot.snapshot);
+        let backfill = &replay.messages[1..];
+        assert!(backfill.iter().all(|m| match m {
+            StreamMessage::Frame(frame) => frame.backfill && !frame.snapshot,
+            StreamMessage::Heartbeat(heartbeat) => heartbeat.backfill,
+        }));
+        assert_eq!(backfill.last().unwrap().seq(), snapshot.seq);
+        // 回放覆盖约 2 s（起点可能落在心跳间隔内），按 60 Hz 抽取
+        let span = backfill.last().unwrap().timestamp_ms() - backfill[0].timestamp_ms();
+        assert!((1750..=2000).contains(&span), "{span}");
+        assert!(backfill.len() < 130, "{}", backfill.len());
+
+        let stream: Vec<_> = backfill.iter().chain(&live).collect();
+        for pair in stream.windows(2) {
+            assert_eq!(pair[1].seq() - pair[0].seq() - 1, pair[1].skipped());
+        }
+        // 末尾的静止帧可能落在心跳间隔内被抑制，以最后一条实际发出的消息为准
+        assert_eq!(stream.last().unwrap().seq(), last_seq);
+    }
+
+    #[test]
+    fn options_default_to_live_only() {
+        let history = OutputHistory::new(4);
+        let mut suppressor = StaticSuppressor::default();
+        for i in 0..10 {
+            let message = suppressor
+                .process(&data(i * 4), &OutputSuppressConfig::default())
+                .unwrap();
+            history.push(message);
+        }
+        assert_eq!(history.lock().len(), 4);
+
+        let replay = history.replay(Some(data(36)), &OutputSubscribeOptions::default());
+        assert!(replay.messages.is_empty());
+        let next = suppressor
+            .process(&data(40), &OutputSuppressConfig::default())
+            .unwrap();
+        assert!(!replay.is_live(&history.lock()[3]));
+        assert!(replay.is_live(&next));
+
+        // 历史不足时回放全部，不抽取时逐条保留
+        let options = OutputSubscribeOptions {
+            backfill_ms: 10_000,
+            ..OutputSubscribeOptions::default()
+        };
+        let seqs: Vec<_> = history
+            .replay(None, &options)
+            .messages
+            .iter()
+            .map(|m| (m.seq(), m.skipped()))
+            .collect();
+        assert_eq!(seqs, [(6, 0), (7, 0), (8, 0), (9, 0)]);
+    }
+}
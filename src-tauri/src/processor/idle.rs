@@ -0,0 +1,419 @@
+//! 无人消费时的空闲模式。
+//!
+//! 窗口最小化后没有任何输出订阅、也没有在录制时，设备仍以 250 Hz 上报，
+//! 白白消耗电池与蓝牙带宽。空闲管理器统计当前消费者（输出/姿态/诊断订阅与录制），
+//! 连续无人消费超过宽限期后发送停止主动上报命令（0x18）并推送 `stream_idle`；
+//! 订阅出现或开始录制时重置管线时间基准、重新开启上报（0x19）并推送 `stream_active`，
+//! 空闲期间的时间间隔不会被积分进轨迹。
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::processor::shared::validate::ConfigValidator;
+
+/// 默认宽限期 (ms)。
+pub const DEFAULT_IDLE_GRACE_MS: u64 = 30_000;
+
+/// 空闲模式配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IdleConfig {
+    /// 是否启用空闲模式。
+    pub enabled: bool,
+    /// 无人消费多久后停止上报 (ms)。
+    pub grace_period_ms: u64,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            grace_period_ms: DEFAULT_IDLE_GRACE_MS,
+        }
+    }
+}
+
+impl IdleConfig {
+    /// 校验取值范围：宽限期 1 s ~ 1 h。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range(
+            "grace_period_ms",
+            self.grace_period_ms as f64,
+            1_000.0,
+            3_600_000.0,
+        );
+    }
+}
+
+/// 数据订阅计数，各订阅转发循环持有一个 [`SubscriberGuard`]。
+#[derive(Debug, Clone, Default)]
+pub struct SubscriberCount(Arc<AtomicUsize>);
+
+impl SubscriberCount {
+    /// 登记一个订阅，守卫释放时注销。
+    pub fn acquire(&self) -> SubscriberGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        SubscriberGuard(self.0.clone())
+    }
+
+    /// 当前订阅数。
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 订阅守卫。
+#[derive(Debug)]
+pub struct SubscriberGuard(Arc<AtomicUsize>);
+
+impl Drop for SubscriberGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// `stream_idle` / `stream_active` 事件载荷。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StreamIdleState {
+    /// 是否处于空闲（已停止上报）。
+    pub idle: bool,
+    /// 空闲事件为判定时已无人消费的时长，恢复事件为整段空闲时长 (ms)。
+    pub duration_ms: u64,
+}
+
+/// 空闲管理器事件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// 进入空闲，应停止上报。
+    Idle(StreamIdleState),
+    /// 恢复消费，应重新开启上报。
+    Active(StreamIdleState),
+}
+
+/// 空闲管理器对外的动作，应用中由 `AppState` 实现，测试中替换为记录器。
+pub trait IdleHooks {
+    /// 推送事件。
+    fn emit(&self, event: IdleEvent);
+    /// 发送开启（`true`）或停止（`false`）主动上报命令。
+    fn set_reporting(&self, enabled: bool) -> impl Future<Output = anyhow::Result<()>> + Send;
+    /// 重置管线时间基准，恢复后首帧不积分空闲间隔。
+    fn reset_timing(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// 空闲管理器状态。
+#[derive(Debug, Default)]
+pub struct IdleManager {
+    /// 最后一次有消费者（或连接建立）的主机时间。
+    last_consumed_ms: Option<u64>,
+    /// 进入空闲的主机时间。
+    idle_since_ms: Option<u64>,
+}
+
+impl IdleManager {
+    /// 是否处于空闲（已停止上报）。
+    pub fn is_idle(&self) -> bool {
+        self.idle_since_ms.is_some()
+    }
+
+    /// 按当前消费者判定一次，进入与退出空闲时各返回一个事件。
+    ///
+    /// 断开连接时静默清除状态（重新连接会开启上报）；关闭功能时若正处于空闲则立即恢复。
+    pub fn check(
+        &mut self,
+        now_ms: u64,
+        config: &IdleConfig,
+        connected: bool,
+        consumed: bool,
+    ) -> Option<IdleEvent> {
+        if !connected {
+            self.last_consumed_ms = None;
+            self.idle_since_ms = None;
+            return None;
+        }
+        if consumed || !config.enabled {
+            self.last_consumed_ms = Some(now_ms);
+            let since_ms = self.idle_since_ms.take()?;
+            return Some(IdleEvent::Active(StreamIdleState {
+                idle: false,
+                duration_ms: now_ms.saturating_sub(since_ms),
+            }));
+        }
+        if self.idle_since_ms.is_some() {
+            return None;
+        }
+        let last_consumed_ms = *self.last_consumed_ms.get_or_insert(now_ms);
+        let unconsumed_ms = now_ms.saturating_sub(last_consumed_ms);
+        if unconsumed_ms < config.grace_period_ms {
+            return None;
+        }
+        self.idle_since_ms = Some(now_ms);
+        Some(IdleEvent::Idle(StreamIdleState {
+            idle: true,
+            duration_ms: unconsumed_ms,
+        }))
+    }
+
+    /// 判定一次并执行相应动作。
+    ///
+    /// 命令写入失败时回退状态：停止失败则重新计宽限期，开启失败则保持空闲、下次检查重试。
+    pub async fn tick<H: IdleHooks>(
+        &mut self,
+        now_ms: u64,
+        config: &IdleConfig,
+        connected: bool,
+        consumed: bool,
+        hooks: &H,
+    ) {
+        let Some(event) = self.check(now_ms, config, connected, consumed) else {
+            return;
+        };
+        match event {
+            IdleEvent::Idle(state) => {
+                if let Err(error) = hooks.set_reporting(false).await {
+                    tracing::warn!("停止主动上报失败: {:#}", error);
+                    self.idle_since_ms = None;
+                    self.last_consumed_ms = Some(now_ms);
+                    return;
+                }
+                tracing::info!(unconsumed_ms = state.duration_ms, "无人消费数据，进入空闲");
+            }
+            IdleEvent::Active(state) => {
+                // 先重置时间基准再开启上报，保证恢复后的首帧不积分空闲间隔
+                if let Err(error) = hooks.reset_timing().await {
+                    tracing::warn!("重置管线时间基准失败: {:#}", error);
+                }
+                if let Err(error) = hooks.set_reporting(true).await {
+                    tracing::warn!("重新开启主动上报失败: {:#}", error);
+                    self.idle_since_ms = Some(now_ms - state.duration_ms);
+                    return;
+                }
+                tracing::info!(idle_ms = state.duration_ms, "数据恢复消费，退出空闲");
+            }
+        }
+        hooks.emit(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicBool, Mutex};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Action {
+        Disable,
+        Enable,
+        ResetTiming,
+        Emit(IdleEvent),
+    }
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        fail_writes: AtomicBool,
+        actions: Mutex<Vec<Action>>,
+    }
+
+    impl RecordingHooks {
+        fn take(&self) -> Vec<Action> {
+            std::mem::take(&mut *self.actions.lock().unwrap())
+        }
+    }
+
+    impl IdleHooks for RecordingHooks {
+        fn emit(&self, event: IdleEvent) {
+            self.actions.lock().unwrap().push(Action::Emit(event));
+        }
+
+        async fn set_reporting(&self, enabled: bool) -> anyhow::Result<()> {
+            let action = if enabled {
+                Action::Enable
+            } else {
+                Action::Disable
+            };
+            self.actions.lock().unwrap().push(action);
+            if self.fail_writes.load(Ordering::Relaxed) {
+                anyhow::bail!("device did not answer");
+            }
+            Ok(())
+        }
+
+        async fn reset_timing(&self) -> anyhow::Result<()> {
+            self.actions.lock().unwrap().push(Action::ResetTiming);
+            Ok(())
+        }
+    }
+
+    const T0: u64 = 1_700_000_000_000;
+
+    /// 在 `[from_ms, to_ms]` 内每 250 ms 检查一次。
+    async fn run(
+        manager: &mut IdleManager,
+        hooks: &RecordingHooks,
+        config: &IdleConfig,
+        subscribers: &SubscriberCount,
+        range: std::ops::RangeInclusive<u64>,
+    ) {
+        for t in range.step_by(250) {
+            let consumed = subscribers.get() > 0;
+            manager.tick(T0 + t, config, true, consumed, hooks).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn subscriber_changes_drive_disable_and_enable_sequence() {
+        let config = IdleConfig::default();
+        let subscribers = SubscriberCount::default();
+        let mut manager = IdleManager::default();
+        let hooks = RecordingHooks::default();
+
+        // 有订阅时从不空闲
+        let output = subscribers.acquire();
+        let attitude = subscribers.acquire();
+        run(&mut manager, &hooks, &config, &subscribers, 0..=60_000).await;
+        assert!(hooks.take().is_empty());
+
+        // 最后一个订阅在 60 s 后断开，宽限期满（90 s）才停止上报
+        drop(output);
+        drop(attitude);
+        assert_eq!(subscribers.get(), 0);
+        run(&mut manager, &hooks, &config, &subscribers, 60_250..=89_750).await;
+        assert!(hooks.take().is_empty());
+        run(
+            &mut manager,
+            &hooks,
+            &config,
+            &subscribers,
+            90_000..=120_000,
+        )
+        .await;
+        assert_eq!(
+            hooks.take(),
+            [
+                Action::Disable,
+                Action::Emit(IdleEvent::Idle(StreamIdleState {
+                    idle: true,
+                    duration_ms: 30_000,
+                })),
+            ]
+        );
+        assert!(manager.is_idle());
+
+        // 新订阅出现：先重置时间基准，再开启上报
+        let _resubscribed = subscribers.acquire();
+        run(
+            &mut manager,
+            &hooks,
+            &config,
+            &subscribers,
+            120_250..=130_000,
+        )
+        .await;
+        assert_eq!(
+            hooks.take(),
+            [
+                Action::ResetTiming,
+                Action::Enable,
+                Action::Emit(IdleEvent::Active(StreamIdleState {
+                    idle: false,
+                    duration_ms: 30_250,
+                })),
+            ]
+        );
+        assert!(!manager.is_idle());
+    }
+
+    #[tokio::test]
+    async fn recording_disabled_feature_and_disconnect_keep_reporting() {
+        let mut config = IdleConfig {
+            enabled: true,
+            grace_period_ms: 1_000,
+        };
+        let mut manager = IdleManager::default();
+        let hooks = RecordingHooks::default();
+
+        // 录制中即使无人订阅也不空闲
+        for t in (0..=5_000).step_by(250) {
+            manager.tick(T0 + t, &config, true, true, &hooks).await;
+        }
+        assert!(hooks.take().is_empty());
+
+        // 录制结束后宽限期满进入空闲；关闭功能立即恢复上报
+        for t in (5_250..=6_250).step_by(250) {
+            manager.tick(T0 + t, &config, true, false, &hooks).await;
+        }
+        assert_eq!(hooks.take()[0], Action::Disable);
+        config.enabled = false;
+        manager.tick(T0 + 6_500, &config, true, false, &hooks).await;
+        assert_eq!(hooks.take()[..2], [Action::ResetTiming, Action::Enable]);
+        for t in (6_750..=20_000).step_by(250) {
+            manager.tick(T0 + t, &config, true, false, &hooks).await;
+        }
+        assert!(hooks.take().is_empty());
+
+        // 未连接时不计时，连接建立后重新计宽限期
+        config.enabled = true;
+        manager
+            .tick(T0 + 30_000, &config, false, false, &hooks)
+            .await;
+        manager
+            .tick(T0 + 40_000, &config, true, false, &hooks)
+            .await;
+        manager
+            .tick(T0 + 40_750, &config, true, false, &hooks)
+            .await;
+        assert!(hooks.take().is_empty());
+        manager
+            .tick(T0 + 41_000, &config, true, false, &hooks)
+            .await;
+        assert_eq!(hooks.take()[0], Action::Disable);
+    }
+
+    #[tokio::test]
+    async fn failed_writes_roll_back_state() {
+        let config = IdleConfig {
+            enabled: true,
+            grace_period_ms: 1_000,
+        };
+        let mut manager = IdleManager::default();
+        let hooks = RecordingHooks::default();
+        hooks.fail_writes.store(true, Ordering::Relaxed);
+
+        // 停止失败：不推送事件，下一个宽限期后再试
+        manager.tick(T0, &config, true, false, &hooks).await;
+        manager.tick(T0 + 1_000, &config, true, false, &hooks).await;
+        assert_eq!(hooks.take(), [Action::Disable]);
+        assert!(!manager.is_idle());
+        manager.tick(T0 + 1_500, &config, true, false, &hooks).await;
+        assert!(hooks.take().is_empty());
+
+        // 开启失败：保持空闲，下次检查重试
+        hooks.fail_writes.store(false, Ordering::Relaxed);
+        manager.tick(T0 + 2_000, &config, true, false, &hooks).await;
+        assert!(manager.is_idle());
+        hooks.take();
+        hooks.fail_writes.store(true, Ordering::Relaxed);
+        manager.tick(T0 + 3_000, &config, true, true, &hooks).await;
+        assert_eq!(hooks.take(), [Action::ResetTiming, Action::Enable]);
+        assert!(manager.is_idle());
+        hooks.fail_writes.store(false, Ordering::Relaxed);
+        manager.tick(T0 + 3_250, &config, true, true, &hooks).await;
+        assert_eq!(
+            hooks.take(),
+            [
+                Action::ResetTiming,
+                Action::Enable,
+                Action::Emit(IdleEvent::Active(StreamIdleState {
+                    idle: false,
+                    duration_ms: 1_250,
+                })),
+            ]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! 最新输出帧。
+//!
+//! 部分面板只在打开对话框时需要当前一帧（如设置位置时预填当前位置），
+//! 订阅输出流再立即取消既浪费又有竞态。处理线程每输出一帧整体替换一次，
+//! 不依赖是否有前端订阅；读端拿到的始终是某一帧的完整副本，不会读到半更新的字段。
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use crate::types::outputs::ResponseData;
+
+/// 跨线程共享的最新帧句柄。
+pub type LatestFrameHandle = Arc<LatestFrame>;
+
+#[derive(Debug, Clone, Copy)]
+struct LatestEntry {
+    data: ResponseData,
+    updated_at: Instant,
+}
+
+/// 最新输出帧槽位。
+#[derive(Debug, Default)]
+pub struct LatestFrame {
+    slot: RwLock<Option<LatestEntry>>,
+}
+
+impl LatestFrame {
+    /// 创建共享句柄。
+    pub fn new_handle() -> LatestFrameHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 整体替换为新的一帧，处理线程每输出一帧调用一次。
+    pub fn store(&self, data: ResponseData) {
+        let entry = LatestEntry {
+            data,
+            updated_at: Instant::now(),
+        };
+        *self
+            .slot
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(entry);
+    }
+
+    /// 最新一帧，尚无数据时为 `None`。
+    pub fn get(&self) -> Option<ResponseData> {
+        self.entry().map(|entry| entry.data)
+    }
+
+    /// 距最新一帧写入经过的毫秒数（主机时间），尚无数据时为 `None`。
+    pub fn age_ms(&self) -> Option<u64> {
+        self.entry()
+            .map(|entry| entry.updated_at.elapsed().as_millis() as u64)
+    }
+
+    fn entry(&self) -> Option<LatestEntry> {
+        *self
+            .slot
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+    };
+
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::processor::navigator::{MotionState, ZuptState};
+    use crate::types::outputs::RESPONSE_SCHEMA_VERSION;
+
+    /// 所有字段都由同一个序号导出，读端据此检查是否读到混合帧。
+    fn frame(seq: u64) -> ResponseData {
+        let v = seq as f64;
+        ResponseData {
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms: seq,
+            accel: DVec3::splat(v),
+            accel_with_g: DVec3::splat(v),
+            gyro: DVec3::splat(v),
+            attitude: DQuat::IDENTITY,
+            velocity: DVec3::splat(v),
+            position: DVec3::splat(v),
+            accel_saturated: seq % 2 == 1,
+            motion_state: MotionState::Moving,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            host_time_estimate_ms: None,
+            debug_vectors: None,
+        }
+    }
+
+    fn assert_consistent(data: &ResponseData) {
+        let v = data.timestamp_ms as f64;
+        for field in [
+            data.accel,
+            data.accel_with_g,
+            data.gyro,
+            data.velocity,
+            data.position,
+        ] {
+            assert_eq!(
+                field,
+                DVec3::splat(v),
+                "帧 {} 字段不一致",
+                data.timestamp_ms
+            );
+        }
+        assert_eq!(data.accel_saturated, data.timestamp_ms % 2 == 1);
+    }
+
+    #[test]
+    fn empty_until_first_store() {
+        let latest = LatestFrame::default();
+        assert!(latest.get().is_none());
+        assert!(latest.age_ms().is_none());
+
+        latest.store(frame(7));
+        assert_eq!(latest.get().unwrap().timestamp_ms, 7);
+        assert!(latest.age_ms().unwrap() < 1000);
+    }
+
+    #[test]
+    fn concurrent_reads_see_whole_frames() {
+        const WRITES: u64 = 200_000;
+        let latest = LatestFrame::new_handle();
+        let done = Arc::new(AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let latest = latest.clone();
+                let done = done.clone();
+                thread::spawn(move || {
+                    let mut reads = 0u64;
+                    let mut last_seq = 0;
+                    // 写者结束前线程可能尚未调度到，至少读一次再退出
+                    while !done.load(Ordering::Acquire) || reads == 0 {
+                        if let Some(data) = latest.get() {
+                            assert_consistent(&data);
+                            // 单写者：读到的序号不会回退
+                            assert!(data.timestamp_ms >= last_seq);
+                            last_seq = data.timestamp_ms;
+                            reads += 1;
+                        }
+                        let _ = latest.age_ms();
+                    }
+                    reads
+                })
+            })
+            .collect();
+
+        let writer = {
+            let latest = latest.clone();
+            thread::spawn(move || {
+                for seq in 1..=WRITES {
+                    latest.store(frame(seq));
+                }
+            })
+        };
+        writer.join().unwrap();
+        done.store(true, Ordering::Release);
+        for reader in readers {
+            assert!(reader.join().unwrap() > 0);
+        }
+        assert_eq!(latest.get().unwrap().timestamp_ms, WRITES);
+    }
+}
@@ -3,40 +3,108 @@
 //! 这里负责启动处理线程，并把原始数据送入 pipeline。
 //! pipeline 内部遵循固定链路：解析 → 标定 → 滤波 → Navigator → 输出。
 
+// 处理线程只在桌面应用中运行；无界面构建（CLI/replay）直接驱动 `ProcessorPipeline`。
+use std::time::Instant;
+#[cfg(feature = "gui")]
 use std::{
     thread::{self, JoinHandle},
     time::Duration,
 };
 
+#[cfg(feature = "gui")]
 use flume::{Receiver, RecvTimeoutError};
-use tauri::Emitter as _;
+#[cfg(feature = "gui")]
+use tauri::{Emitter as _, Manager as _};
 
+use crate::processor::shared::HostTimeMs;
+
+#[cfg(feature = "gui")]
 use crate::{
+    app_state::AppState,
     processor::{
-        calibration::CorrectionRequest,
-        output::{OutputBuilder, OutputFrame},
+        calibration::{CalibrationStateHandle, CorrectionRequest},
+        clock_sync::ClockSyncHandle,
+        fanout::OutputItem,
+        fault::FaultInjectorHandle,
+        history::OutputHistoryHandle,
+        latest::LatestFrameHandle,
+        output::{OutputBuilder, OutputFrame, StageCaptureHandle},
         pipeline::{
-            diagnostics::{DiagnosticsFlag, PipelineDiagnostics, QueueProbe},
-            PipelineConfigRequest, ProcessorPipeline, ProcessorPipelineConfig,
+            diagnostics::{DiagnosticsFlag, DiagnosticsSender, QueueProbe},
+            ConfigInvalid, ConfigLoadStage, PipelineConfigRequest, ProcessorPipeline,
+            ProcessorPipelineConfig,
         },
+        scheduling::{apply_thread_hints, SchedulingStatusHandle},
+        shared::ConfigErrors,
+        stats::ProcessorStatsHandle,
+        suppress::{StaticSuppressor, StreamSeqHandle},
     },
-    types::outputs::ResponseData,
+    recorder::{RecorderCommand, PAUSE_MARKER_LABEL, RESUME_MARKER_LABEL},
 };
 
+/// 加速度来源选择与一致性检查。
+pub mod accel_source;
+/// 按运动强度自适应的输出频率。
+pub mod adaptive_rate;
+/// 轨迹对比分析模块。
+pub mod analysis;
+/// 高频姿态流。
+pub mod attitude_stream;
+/// 发往前端的消息攒批。
+pub mod batcher;
 /// 标定模块。
 pub mod calibration;
+/// 设备时钟与主机时钟同步。
+pub mod clock_sync;
+/// 派生信号（加加速度、角加速度）模块。
+pub mod derived;
+/// 输出分发（每个订阅者一条队列）。
+pub mod fanout;
+/// 开发构建的随机故障注入。
+pub mod fault;
 /// 滤波模块。
 pub mod filter;
+/// 按首段运动方向对齐航向。
+pub mod heading;
+/// 输出订阅的近期历史。
+pub mod history;
+/// 无人消费时的空闲模式。
+pub mod idle;
+/// 最新输出帧。
+pub mod latest;
 /// 导航融合模块。
 pub mod navigator;
 /// 输出构建模块。
 pub mod output;
+/// 输出订阅的 CBOR 编码。
+pub mod output_encoding;
 /// 解析模块。
 pub mod parser;
 /// 管线模块。
 pub mod pipeline;
+/// 设备四元数健康监测。
+pub mod quat_health;
+/// 处理线程与录制线程的调度提示。
+pub mod scheduling;
+/// 运动分段检测模块。
+pub mod segment;
+/// 时间戳去重与排序模块。
+pub mod sequencer;
+/// 共用工具。
+pub mod shared;
+/// 处理器运行统计。
+pub mod stats;
+/// 静止帧抑制。
+pub mod suppress;
+/// 敲击同步检测。
+pub mod sync_tap;
+/// 蓝牙通知到处理线程的上游转发。
+pub mod upstream;
+/// 数据流看门狗。
+pub mod watchdog;
 
 
+#[cfg(feature = "gui")]
 /// 数据处理器实例，启动独立线程消费 IMU 流。
 pub struct Processor {
     shutdown_tx: Option<flume::Sender<()>>,
@@ -44,14 +112,26 @@ pub struct Processor {
     config_watcher_thread: Option<JoinHandle<()>>,
 }
 
+#[cfg(feature = "gui")]
+/// 处理线程连续直取数据包的上限，超过后回到 Selector 检查控制通道。
+const MAX_PACKET_BURST: usize = 64;
+
 /// 原始 IMU 数据包枚举。
 pub enum RawImuData {
     /// 蓝牙原始数据帧。
-    Packet(Vec<u8>),
+    Packet {
+        /// 通知载荷，可能拼接多帧。
+        data: Vec<u8>,
+        /// 通知到达主机的时刻，用于统计接收到处理的延迟。
+        received_at: Instant,
+        /// 通知到达主机的 UNIX 时间 (ms)，用于时钟同步；合成包为空。
+        received_at_ms: Option<HostTimeMs>,
+    },
     /// 管线重置信号。
     Reset,
 }
 
+#[cfg(feature = "gui")]
 impl Processor {
     /// 数据处理器实例。
     ///
@@ -61,40 +141,68 @@ impl Processor {
     /// 通道关系（上游 -> 下游）：
     /// - `imu::client` 通过 `upstream_tx` 推送原始蓝牙包。
     /// - 本处理器线程持有 `upstream_rx`，消费原始包并运行 pipeline。
-    /// - 处理结果通过 `downstream_tx` 发给 `AppState.downstream_rx`，
-    ///   再由 tauri command/IPC 推给前端。
-    /// - 同时通过 `record_tx` 发给 recorder 线程持久化存储。
+    /// - 处理结果连同发往前端的消息通过 `output_tx` 交给输出分发线程，
+    ///   再复制给每个订阅者（前端订阅、姿态流、自检、recorder）；启用静止抑制时
+    ///   静止帧只给前端发心跳。
+    /// - 检测到的运动分段推送 `motion_segment` 事件，并经 `recorder_tx` 写入录制。
     ///
     /// * `upstream_rx`: 接收来自 imu_client 的原始蓝牙二进制数据
-    /// * `downstream_tx`: 发给 AppState 的 rx（command 中接收）
-    /// * `record_tx`: 发给 recorder 线程的录制通道
+    /// * `output_tx`: 发给输出分发线程的通道
+    /// * `record_tx`: recorder 的录制队列（只用于诊断中读取队列深度）
+    /// * `recorder_tx`: recorder 控制通道（写入运动分段）
     /// * `calibration_rx`: 手动校正请求通道
-    /// 数据处理器实例。
+    /// * `stats`: 运行统计，每输出一帧更新一次
+    /// * `latest_frame`: 最新输出帧，无论是否有前端订阅都会更新
+    /// * `output_history`: 发往前端订阅的近期消息，供重新订阅时回放
+    /// * `stream_seq`: 前端消息序号计数器，与录制回放共用
+    /// * `clock_sync`: 最新的设备/主机时钟同步结果
+    /// * `calibration_state`: 标定向导状态，每次变化后推送 `calibration_state_changed`
+    /// * `faults`: 故障注入钩子（仅开发构建生效）
+    /// * `stage_capture`: 阶段中间值采集开关，由录制线程在深度录制期间打开
+    /// * `scheduling`: 调度提示结果，处理线程启动时写入本线程结果并通知录制线程应用
     ///
     /// 新增 `diagnostics_flag` / `diagnostics_tx` 用于诊断数据采集。
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         upstream_rx: flume::Receiver<RawImuData>,
-        downstream_tx: flume::Sender<ResponseData>,
+        output_tx: flume::Sender<OutputItem>,
         record_tx: flume::Sender<OutputFrame>,
+        recorder_tx: flume::Sender<RecorderCommand>,
         calibration_rx: flume::Receiver<CorrectionRequest>,
         pipeline_config_rx: flume::Receiver<PipelineConfigRequest>,
         diagnostics_flag: DiagnosticsFlag,
-        diagnostics_tx: flume::Sender<PipelineDiagnostics>,
+        diagnostics_tx: DiagnosticsSender,
+        stats: ProcessorStatsHandle,
+        latest_frame: LatestFrameHandle,
+        output_history: OutputHistoryHandle,
+        stream_seq: StreamSeqHandle,
+        clock_sync: ClockSyncHandle,
+        calibration_state: CalibrationStateHandle,
+        faults: FaultInjectorHandle,
+        stage_capture: StageCaptureHandle,
+        scheduling: SchedulingStatusHandle,
         app_handle: tauri::AppHandle,
     ) -> Self {
         let (shutdown_tx, shutdown_rx) = flume::unbounded::<()>();
         let (config, config_rx, config_watcher_thread) =
-            Self::init_config_watcher(shutdown_rx.clone());
+            Self::init_config_watcher(shutdown_rx.clone(), app_handle.clone());
 
         let app_handle = app_handle.clone();
-        let queue_probe = QueueProbe::new(
-            upstream_rx.clone(),
-            downstream_tx.clone(),
-            record_tx.clone(),
-        );
+        let queue_probe = QueueProbe::new(upstream_rx.clone(), output_tx.clone(), record_tx);
         let processor_thread = thread::Builder::new()
             .name("DataProcessorThread".into())
             .spawn(move || {
+                // 调度提示只在线程启动时按初始配置应用一次，失败仅记警告
+                let hints = config.global.scheduling;
+                scheduling.set_processor(apply_thread_hints(
+                    "processor",
+                    hints.processor_priority(),
+                    hints.processor_core,
+                ));
+                let _ = recorder_tx.send(RecorderCommand::ApplyScheduling {
+                    priority: hints.recorder_priority(),
+                    status: scheduling,
+                });
                 let mut current_config = config.clone();
                 let mut pipeline = ProcessorPipeline::new(
                     config,
@@ -102,11 +210,15 @@ impl Processor {
                     diagnostics_tx,
                     queue_probe,
                 );
+                pipeline.set_fault_injector(faults);
+                pipeline.set_stage_capture(stage_capture);
                 let mut config_enabled = true;
+                let mut packet_burst = 0usize;
+                let mut suppressor = StaticSuppressor::with_seq(stream_seq);
 
                 loop {
                     enum PipelineEvent {
-                        Packet(Vec<u8>),
+                        Packet(Vec<u8>, Instant, Option<HostTimeMs>),
                         Calibration(CorrectionRequest),
                         UpstreamClosed,
                         CalibrationClosed,
@@ -118,66 +230,173 @@ impl Processor {
                         Shutdown,
                     }
 
-                    let mut selector = flume::Selector::new()
-                        .recv(&upstream_rx, |result| match result {
-                            Ok(data) => match data {
-                                RawImuData::Packet(packet) => PipelineEvent::Packet(packet),
-                                RawImuData::Reset => PipelineEvent::Reset,
-                            },
-                            Err(e) => {
-                                tracing::error!("从上游通道接收数据失败: {:?}", e);
-                                PipelineEvent::UpstreamClosed
-                            }
-                        })
-                        .recv(&calibration_rx, |result| match result {
-                            Ok(request) => PipelineEvent::Calibration(request),
-                            Err(e) => {
-                                tracing::warn!("从校准通道接收请求失败: {:?}", e);
-                                PipelineEvent::CalibrationClosed
-                            }
-                        });
-
-                    selector = selector.recv(&shutdown_rx, |_result| PipelineEvent::Shutdown);
+                    fn upstream_event(data: RawImuData) -> PipelineEvent {
+                        match data {
+                            RawImuData::Packet {
+                                data,
+                                received_at,
+                                received_at_ms,
+                            } => PipelineEvent::Packet(data, received_at, received_at_ms),
+                            RawImuData::Reset => PipelineEvent::Reset,
+                        }
+                    }
 
-                    selector = selector.recv(&pipeline_config_rx, |result| match result {
-                        Ok(request) => PipelineEvent::PipelineConfigRequest(Box::new(request)),
-                        Err(e) => {
-                            tracing::warn!("从 pipeline 配置请求通道接收失败: {:?}", e);
-                            PipelineEvent::PipelineConfigClosed
+                    // 快路径：上游有积压时直接取包，跳过 Selector 构建（每次都要分配，
+                    // 并在全部通道上注册/注销等待钩子）。连续取满 MAX_PACKET_BURST 个包后
+                    // 强制走一次 Selector，避免控制请求在高频输入下长时间得不到处理。
+                    let fast_packet = if packet_burst < MAX_PACKET_BURST {
+                        upstream_rx.try_recv().ok()
+                    } else {
+                        None
+                    };
+                    let event = match fast_packet {
+                        Some(data) => {
+                            packet_burst += 1;
+                            upstream_event(data)
                         }
-                    });
+                        None => {
+                            packet_burst = 0;
+                            let mut selector = flume::Selector::new()
+                                .recv(&upstream_rx, |result| match result {
+                                    Ok(data) => upstream_event(data),
+                                    Err(e) => {
+                                        tracing::error!("从上游通道接收数据失败: {:?}", e);
+                                        PipelineEvent::UpstreamClosed
+                                    }
+                                })
+                                .recv(&calibration_rx, |result| match result {
+                                    Ok(request) => PipelineEvent::Calibration(request),
+                                    Err(e) => {
+                                        tracing::warn!("从校准通道接收请求失败: {:?}", e);
+                                        PipelineEvent::CalibrationClosed
+                                    }
+                                });
+
+                            selector =
+                                selector.recv(&shutdown_rx, |_result| PipelineEvent::Shutdown);
 
-                    if config_enabled {
-                        selector = selector.recv(&config_rx, |result| match result {
-                            Ok(config) => PipelineEvent::ConfigUpdated(Box::new(config)),
-                            Err(e) => {
-                                tracing::warn!("从配置通道接收失败: {:?}", e);
-                                PipelineEvent::ConfigClosed
+                            selector = selector.recv(&pipeline_config_rx, |result| match result {
+                                Ok(request) => {
+                                    PipelineEvent::PipelineConfigRequest(Box::new(request))
+                                }
+                                Err(e) => {
+                                    tracing::warn!("从 pipeline 配置请求通道接收失败: {:?}", e);
+                                    PipelineEvent::PipelineConfigClosed
+                                }
+                            });
+
+                            if config_enabled {
+                                selector = selector.recv(&config_rx, |result| match result {
+                                    Ok(config) => PipelineEvent::ConfigUpdated(Box::new(config)),
+                                    Err(e) => {
+                                        tracing::warn!("从配置通道接收失败: {:?}", e);
+                                        PipelineEvent::ConfigClosed
+                                    }
+                                });
                             }
-                        });
-                    }
 
-                    let event = selector.wait();
+                            selector.wait()
+                        }
+                    };
 
                     match event {
-                        PipelineEvent::Packet(data) => {
-                            if let Some(frame) = pipeline.process_packet(&data) {
-                                let response_data = OutputBuilder::build(&frame);
-                                // 可视化路径用 try_send：通道满就丢帧，不反压到 BLE reader。
-                                // 原因：前端可视化 60 Hz 就够，若 IPC/Canvas 偶尔跟不上也不应
-                                // 让 BLE 读线程和 pipeline 线程被拖累。录制路径下方仍用同步 send
-                                // 保证完整性。
-                                match downstream_tx.try_send(response_data) {
-                                    Ok(_) => {}
-                                    Err(flume::TrySendError::Full(_)) => {
-                                        // 可视化帧丢弃，不是 error。静默即可。
+                        PipelineEvent::Packet(data, received_at, received_at_ms) => {
+                            // 高速模式下一个通知可能拼接多帧，被 MTU 截断的帧跨通知拼接，
+                            // 逐帧按顺序处理；末尾的帧头分片留到下一个通知
+                            for packet in pipeline.reassemble(&data, Some(received_at)) {
+                                let frame = pipeline.process_packet_at(
+                                    &packet,
+                                    Some(received_at),
+                                    received_at_ms,
+                                );
+                                // 丢帧（解析失败、非有限值）时同样要更新计数
+                                stats.set_parse_failures(pipeline.parse_failures());
+                                stats.set_non_finite_samples(pipeline.non_finite_samples());
+                                if let Some(frame) = frame {
+                                    stats.record_frame(frame.raw.timestamp_ms);
+                                    stats.set_gravity_estimate(pipeline.gravity_estimate());
+                                    stats.set_skipped_integrations(pipeline.skipped_integrations());
+                                    stats.set_quat_freezes(pipeline.quat_freeze_count());
+                                    clock_sync.store(frame.clock_sync);
+                                    let response_data = OutputBuilder::build(&frame);
+                                    latest_frame.store(response_data);
+                                    let message = suppressor
+                                        .process(&response_data, &current_config.output_suppress);
+                                    // 先写历史再交给分发线程，订阅端据此确定回放与实时的分界
+                                    if let Some(message) = message {
+                                        output_history.push(message);
                                     }
-                                    Err(flume::TrySendError::Disconnected(_)) => {
-                                        tracing::error!("下游通道已断开");
+                                    // 分发线程对前端订阅丢旧不阻塞，只有 recorder 的无损队列
+                                    // 满时才会反压到这里，保证录制完整。
+                                    if let Err(e) = output_tx.send(OutputItem { frame, message }) {
+                                        tracing::error!("输出分发通道已断开: {:?}", e);
                                     }
                                 }
-                                if let Err(e) = record_tx.send(frame) {
-                                    tracing::error!("记录数据失败: {:?}", e);
+                                if let Some(segment) = pipeline.take_segment() {
+                                    if let Err(e) = app_handle.emit("motion_segment", segment) {
+                                        tracing::warn!("推送 motion_segment 事件失败: {:?}", e);
+                                    }
+                                    // 未在录制时 recorder 直接丢弃
+                                    let _ = recorder_tx.send(RecorderCommand::Segment { segment });
+                                }
+                                if let Some(tap) = pipeline.take_sync_tap() {
+                                    if let Err(e) = app_handle.emit("sync_tap", tap) {
+                                        tracing::warn!("推送 sync_tap 事件失败: {:?}", e);
+                                    }
+                                    // 未在录制时 recorder 直接丢弃
+                                    let _ = recorder_tx.send(RecorderCommand::SyncTap { tap });
+                                }
+                                if let Some(event) = pipeline.take_quat_freeze() {
+                                    if let Err(e) = app_handle.emit("quat_freeze", event) {
+                                        tracing::warn!("推送 quat_freeze 事件失败: {:?}", e);
+                                    }
+                                }
+                                if let Some(event) = pipeline.take_origin_anchored() {
+                                    if let Err(e) = app_handle.emit("origin_anchored", event) {
+                                        tracing::warn!("推送 origin_anchored 事件失败: {:?}", e);
+                                    }
+                                }
+                                if let Some(event) = pipeline.take_heading_aligned() {
+                                    if let Err(e) = app_handle.emit("heading_aligned", event) {
+                                        tracing::warn!("推送 heading_aligned 事件失败: {:?}", e);
+                                    }
+                                }
+                                if let Some(event) = pipeline.take_accel_inconsistency() {
+                                    if let Err(e) = app_handle.emit("accel_inconsistent", event) {
+                                        tracing::warn!("推送 accel_inconsistent 事件失败: {:?}", e);
+                                    }
+                                }
+                                if let Some(reset) = pipeline.take_device_reset() {
+                                    if let Err(e) = app_handle.emit("device_reset", reset) {
+                                        tracing::warn!("推送 device_reset 事件失败: {:?}", e);
+                                    }
+                                    if reset.rebooted {
+                                        if let Err(e) = app_handle.emit("device_rebooted", reset) {
+                                            tracing::warn!(
+                                                "推送 device_rebooted 事件失败: {:?}",
+                                                e
+                                            );
+                                        }
+                                        // 重启后固件设置恢复默认，按当前配置重新下发
+                                        let app_handle = app_handle.clone();
+                                        tauri::async_runtime::spawn(async move {
+                                            let Some(state) = app_handle.try_state::<AppState>()
+                                            else {
+                                                return;
+                                            };
+                                            match state.reinitialize_device().await {
+                                                Ok(()) => {
+                                                    tracing::info!("设备重启后已重新下发配置")
+                                                }
+                                                Err(e) => {
+                                                    tracing::error!(
+                                                        "设备重启后重新初始化失败: {:#}",
+                                                        e
+                                                    )
+                                                }
+                                            }
+                                        });
+                                    }
                                 }
                             }
                         }
@@ -223,6 +442,77 @@ impl Processor {
                                 }
                                 tracing::info!("处理管线配置已通过命令更新");
                             }
+                            PipelineConfigRequest::Patch { config, respond_to } => {
+                                let new_config = *config;
+                                current_config = new_config.clone();
+                                let mode = pipeline.patch_config(new_config);
+                                if let Err(e) = app_handle.emit("config_update", ()) {
+                                    tracing::warn!("推送 config_update 事件失败: {:?}", e);
+                                }
+                                if respond_to.send(Ok(mode)).is_err() {
+                                    tracing::warn!("返回 pipeline 配置更新结果失败: 接收端已关闭");
+                                }
+                                tracing::info!(?mode, "处理管线配置已增量更新");
+                            }
+                            PipelineConfigRequest::SetPaused { paused, respond_to } => {
+                                let changed = pipeline.set_paused(paused);
+                                if changed {
+                                    // 暂停区间以标记写入录制，未在录制时 recorder 直接丢弃
+                                    let label = if paused {
+                                        PAUSE_MARKER_LABEL
+                                    } else {
+                                        RESUME_MARKER_LABEL
+                                    };
+                                    let (reply, _) = flume::bounded(1);
+                                    let _ = recorder_tx.send(RecorderCommand::Marker {
+                                        label: label.to_string(),
+                                        reply,
+                                    });
+                                    tracing::info!(paused, "导航暂停状态已切换");
+                                }
+                                if respond_to.send(changed).is_err() {
+                                    tracing::warn!("返回暂停结果失败: 接收端已关闭");
+                                }
+                            }
+                            PipelineConfigRequest::SetSampleInterval {
+                                sample_interval_ms,
+                                respond_to,
+                            } => {
+                                pipeline.set_sample_interval_ms(sample_interval_ms);
+                                if respond_to.send(()).is_err() {
+                                    tracing::warn!("返回采样间隔更新结果失败: 接收端已关闭");
+                                }
+                                tracing::info!(sample_interval_ms, "标称采样间隔已更新");
+                            }
+                            PipelineConfigRequest::ResetTiming { respond_to } => {
+                                pipeline.reset_timing();
+                                if respond_to.send(()).is_err() {
+                                    tracing::warn!("返回时间基准重置结果失败: 接收端已关闭");
+                                }
+                                tracing::info!("导航时间基准已重置");
+                            }
+                            PipelineConfigRequest::GetAxisOffset { respond_to } => {
+                                if respond_to.send(pipeline.axis_offset()).is_err() {
+                                    tracing::warn!("返回姿态零位失败: 接收端已关闭");
+                                }
+                            }
+                            PipelineConfigRequest::GetRecordingCalibration { respond_to } => {
+                                if respond_to.send(pipeline.recording_calibration()).is_err() {
+                                    tracing::warn!("返回录制标定失败: 接收端已关闭");
+                                }
+                            }
+                            PipelineConfigRequest::SetAxisOffset { offset, respond_to } => {
+                                pipeline.set_axis_offset(offset);
+                                if respond_to.send(()).is_err() {
+                                    tracing::warn!("返回姿态零位设置结果失败: 接收端已关闭");
+                                }
+                                tracing::info!("姿态零位已恢复");
+                            }
+                            PipelineConfigRequest::Explain { sample, respond_to } => {
+                                if respond_to.send(pipeline.explain_sample(*sample)).is_err() {
+                                    tracing::warn!("返回样本解释失败: 接收端已关闭");
+                                }
+                            }
                         },
                         PipelineEvent::ConfigClosed => {
                             config_enabled = false;
@@ -236,6 +526,13 @@ impl Processor {
                             break;
                         }
                     }
+
+                    // 先写入共享状态再推送，收到事件后查询不会读到更旧的状态
+                    calibration_state.publish(pipeline.take_calibration_transitions(), |state| {
+                        if let Err(e) = app_handle.emit("calibration_state_changed", state) {
+                            tracing::warn!("推送 calibration_state_changed 事件失败: {:?}", e);
+                        }
+                    });
                 }
             })
             .unwrap_or_else(|e| panic!("创建核心处理线程失败 : {:?}", e));
@@ -263,11 +560,15 @@ impl Processor {
 
     /// 初始化配置监听器。
     ///
+    /// 启动与热重载读到的配置都先校验：启动时不通过则回退到默认配置，
+    /// 热重载时不通过则保留当前配置，两种情况都推送 `config_invalid` 事件。
+    ///
     /// # Return:
     ///   config: 第一次默认的配置
     ///   config_rx: 配置更新通道接收端
     fn init_config_watcher(
         shutdown_rx: flume::Receiver<()>,
+        app_handle: tauri::AppHandle,
     ) -> (
         ProcessorPipelineConfig,
         Receiver<ProcessorPipelineConfig>,
@@ -275,10 +576,25 @@ impl Processor {
     ) {
         let (config, initial_modified) =
             match ProcessorPipelineConfig::load_from_default_paths_with_modified() {
-                Ok(snapshot) => {
-                    tracing::info!("读取配置文件 {:?} : {:?}", snapshot.source, snapshot.config);
-                    (snapshot.config, Some(snapshot.modified))
-                }
+                Ok(snapshot) => match snapshot.config.validate() {
+                    Ok(()) => {
+                        tracing::info!(
+                            "读取配置文件 {:?} : {:?}",
+                            snapshot.source,
+                            snapshot.config
+                        );
+                        (snapshot.config, Some(snapshot.modified))
+                    }
+                    Err(errors) => {
+                        tracing::warn!(
+                            "processor.toml 校验失败，使用默认配置。path: {:?}, {}",
+                            snapshot.source,
+                            errors
+                        );
+                        Self::emit_config_invalid(&app_handle, ConfigLoadStage::Startup, errors);
+                        (ProcessorPipelineConfig::default(), Some(snapshot.modified))
+                    }
+                },
                 Err(err) => {
                     tracing::warn!(
                         "读取 processor.toml 失败，使用默认配置。path: {:?}, err: {:#}",
@@ -300,7 +616,17 @@ impl Processor {
                             if last_modified != Some(modified) {
                                 last_modified = Some(modified);
                                 tracing::info!("处理管线配置文件变更，路径: {:?}", snapshot.source);
-                                if config_tx.send(snapshot.config).is_err() {
+                                if let Err(errors) = snapshot.config.validate() {
+                                    tracing::warn!(
+                                        "processor.toml 校验失败，保留当前配置。{}",
+                                        errors
+                                    );
+                                    Self::emit_config_invalid(
+                                        &app_handle,
+                                        ConfigLoadStage::HotReload,
+                                        errors,
+                                    );
+                                } else if config_tx.send(snapshot.config).is_err() {
                                     break;
                                 }
                             }
@@ -324,8 +650,23 @@ impl Processor {
 
         (config, config_rx, config_watcher_thread)
     }
+
+    fn emit_config_invalid(
+        app_handle: &tauri::AppHandle,
+        stage: ConfigLoadStage,
+        errors: ConfigErrors,
+    ) {
+        let payload = ConfigInvalid {
+            stage,
+            errors: errors.0,
+        };
+        if let Err(e) = app_handle.emit("config_invalid", payload) {
+            tracing::warn!("推送 config_invalid 事件失败: {:?}", e);
+        }
+    }
 }
 
+#[cfg(feature = "gui")]
 impl Drop for Processor {
     fn drop(&mut self) {
         self.shutdown();
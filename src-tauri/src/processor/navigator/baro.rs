@@ -0,0 +1,257 @@
+//! 气压计相对高度辅助竖直位置。
+//!
+//! 竖直方向是积分漂移最严重的轴。启用 `baro.enabled` 后连接时订阅气压计（控制位 bit 4），
+//! 首段持续静止期内对气压取均值作为参考气压，之后按与参考的气压比换算相对高度；
+//! 导航器以一阶互补滤波把积分位置的 z 缓慢拉向"建立参考时的 z + 相对高度"，x/y 不受影响。
+//!
+//! 混合系数按 `1 - exp(-gain·dt)` 离散化，时间常数恰为 `1 / gain` 秒，与采样率无关。
+//! 静止期间位置由 ZUPT 锁定，不施加修正。
+
+use serde::Serialize;
+
+use crate::processor::{navigator::types::BaroConfig, shared::RunningStats};
+
+/// 建立参考气压所需的连续静止帧数。
+pub const REFERENCE_FRAMES: u64 = 25;
+
+/// 国际标准大气压高公式的高度系数 (m)。
+const ISA_HEIGHT_M: f64 = 44_330.77;
+/// 国际标准大气压高公式的指数 `1 / 5.25588`。
+const ISA_EXPONENT: f64 = 1.0 / 5.25588;
+
+/// 气压换算为相对参考气压处的高度 (m)，气压低于参考时为正。
+///
+/// `h = 44330.77 · (1 − (p / p_ref)^(1/5.25588))`，海平面附近约 8.3 m/hPa。
+pub fn relative_altitude_m(pressure_hpa: f64, reference_hpa: f64) -> f64 {
+    ISA_HEIGHT_M * (1.0 - (pressure_hpa / reference_hpa).powf(ISA_EXPONENT))
+}
+
+/// 一步互补混合：`z` 向 `target` 移动的量，时间常数 `1 / gain` 秒。
+pub fn blend_step(z: f64, target: f64, gain: f64, dt: f64) -> f64 {
+    (target - z) * (1.0 - (-gain * dt).exp())
+}
+
+/// 气压参考：参考气压与建立参考时的积分 z。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BaroReference {
+    /// 首段持续静止期的平均气压 (hPa)。
+    pub pressure_hpa: f64,
+    /// 建立参考时的积分位置 z (m)。
+    pub z_m: f64,
+}
+
+/// 气压高度计：建立参考气压，并给出每帧的 z 修正量。
+///
+/// 每次 [`rearm`](Self::rearm)（重置、手动设置位置、原点锚定）后在下一段持续静止期
+/// 重新建立参考。
+#[derive(Debug, Clone)]
+pub struct BaroAltimeter {
+    config: BaroConfig,
+    pressure: RunningStats,
+    reference: Option<BaroReference>,
+    relative_altitude_m: Option<f64>,
+    correction_m: f64,
+}
+
+impl BaroAltimeter {
+    /// 创建高度计，参考待首段持续静止期建立。
+    pub fn new(config: BaroConfig) -> Self {
+        Self {
+            config,
+            pressure: RunningStats::new(),
+            reference: None,
+            relative_altitude_m: None,
+            correction_m: 0.0,
+        }
+    }
+
+    /// 原地更新参数，已建立的参考保留。
+    pub fn reconfigure(&mut self, config: BaroConfig) {
+        self.config = config;
+    }
+
+    /// 丢弃参考，下一段持续静止期重新建立。
+    pub fn rearm(&mut self) {
+        self.pressure.reset();
+        self.reference = None;
+        self.relative_altitude_m = None;
+        self.correction_m = 0.0;
+    }
+
+    /// 当前参考，尚未建立时为空。
+    pub fn reference(&self) -> Option<BaroReference> {
+        self.reference
+    }
+
+    /// 最近一帧相对参考的气压高度 (m)，参考未建立或本帧无气压数据时为空。
+    pub fn relative_altitude_m(&self) -> Option<f64> {
+        self.relative_altitude_m
+    }
+
+    /// 最近一帧施加到 z 的修正量 (m)。
+    pub fn correction_m(&self) -> f64 {
+        self.correction_m
+    }
+
+    /// 输入一帧：`z` 为本帧积分位置的竖直分量，`is_static` 为 ZUPT 静止判定，`dt` 为积分步长 (s)。
+    ///
+    /// 返回应加到 z 上的修正量；未启用、本帧无气压数据、参考未建立或静止时为 0。
+    pub fn update(&mut self, pressure_hpa: Option<f64>, z: f64, is_static: bool, dt: f64) -> f64 {
+        self.correction_m = 0.0;
+        let Some(pressure_hpa) = pressure_hpa.filter(|_| self.config.enabled) else {
+            self.relative_altitude_m = None;
+            return 0.0;
+        };
+        let Some(reference) = self.reference else {
+            self.observe_reference(pressure_hpa, z, is_static);
+            self.relative_altitude_m = None;
+            return 0.0;
+        };
+
+        let relative = relative_altitude_m(pressure_hpa, reference.pressure_hpa);
+        self.relative_altitude_m = Some(relative);
+        if !is_static {
+            self.correction_m = blend_step(z, reference.z_m + relative, self.config.gain, dt);
+        }
+        self.correction_m
+    }
+
+    /// 累计持续静止期的气压，满 [`REFERENCE_FRAMES`] 帧后建立参考。
+    fn observe_reference(&mut self, pressure_hpa: f64, z: f64, is_static: bool) {
+        if !is_static {
+            // 要求的是"持续"静止，从头累计
+            self.pressure.reset();
+            return;
+        }
+        self.pressure.push(pressure_hpa);
+        if self.pressure.count() < REFERENCE_FRAMES {
+            return;
+        }
+        let reference = BaroReference {
+            pressure_hpa: self.pressure.mean(),
+            z_m: z,
+        };
+        tracing::info!(
+            "气压参考建立 | p_ref={:.3} hPa | z={:.3} m ({} 帧)",
+            reference.pressure_hpa,
+            reference.z_m,
+            self.pressure.count()
+        );
+        self.reference = Some(reference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEA_LEVEL_HPA: f64 = 1013.25;
+    const DT: f64 = 0.004;
+
+    fn enabled(gain: f64) -> BaroAltimeter {
+        BaroAltimeter::new(BaroConfig {
+            enabled: true,
+            gain,
+        })
+    }
+
+    /// 标准大气中高度 `h` 处的气压，[`relative_altitude_m`] 的逆。
+    fn pressure_at(h: f64, reference_hpa: f64) -> f64 {
+        reference_hpa * (1.0 - h / ISA_HEIGHT_M).powf(1.0 / ISA_EXPONENT)
+    }
+
+    /// 以 `reference_hpa` 建立参考（z = 0）。
+    fn with_reference(mut altimeter: BaroAltimeter, reference_hpa: f64) -> BaroAltimeter {
+        for _ in 0..REFERENCE_FRAMES {
+            assert_eq!(altimeter.update(Some(reference_hpa), 0.0, true, DT), 0.0);
+        }
+        assert!(altimeter.reference().is_some());
+        altimeter
+    }
+
+    #[test]
+    fn pressure_ramp_converts_to_relative_altitude() {
+        assert_eq!(relative_altitude_m(SEA_LEVEL_HPA, SEA_LEVEL_HPA), 0.0);
+        // 海平面附近约 8.3 m/hPa
+        let ten_metres = relative_altitude_m(SEA_LEVEL_HPA - 1.2, SEA_LEVEL_HPA);
+        assert!((ten_metres - 9.99).abs() < 0.05, "{ten_metres}");
+
+        // 气压线性下降的斜坡：高度单调上升，且与标准大气逆换算一致
+        let mut last = f64::NEG_INFINITY;
+        for step in 0..=100 {
+            let pressure = SEA_LEVEL_HPA - 0.12 * step as f64;
+            let h = relative_altitude_m(pressure, SEA_LEVEL_HPA);
+            assert!(h > last);
+            assert!((pressure_at(h, SEA_LEVEL_HPA) - pressure).abs() < 1e-9);
+            last = h;
+        }
+        // 高原上同样的气压差对应更大的高度差
+        let plateau = relative_altitude_m(700.0 - 1.2, 700.0);
+        assert!(plateau > ten_metres * 1.4);
+    }
+
+    #[test]
+    fn reference_needs_an_uninterrupted_static_window() {
+        let mut altimeter = enabled(1.0);
+        for _ in 0..REFERENCE_FRAMES - 1 {
+            altimeter.update(Some(SEA_LEVEL_HPA), 0.3, true, DT);
+        }
+        // 运动打断，窗口重来
+        altimeter.update(Some(SEA_LEVEL_HPA), 0.3, false, DT);
+        assert_eq!(altimeter.reference(), None);
+        for i in 0..REFERENCE_FRAMES {
+            let pressure = SEA_LEVEL_HPA + if i % 2 == 0 { 0.01 } else { -0.01 };
+            altimeter.update(Some(pressure), 0.3, true, DT);
+        }
+        let reference = altimeter.reference().unwrap();
+        assert!((reference.pressure_hpa - SEA_LEVEL_HPA).abs() < 1e-3);
+        assert_eq!(reference.z_m, 0.3);
+
+        // 未启用或无气压数据时不修正
+        let mut disabled = BaroAltimeter::new(BaroConfig::default());
+        assert_eq!(
+            disabled.update(Some(SEA_LEVEL_HPA - 1.2), 0.0, false, DT),
+            0.0
+        );
+        assert_eq!(altimeter.update(None, 0.0, false, DT), 0.0);
+        assert_eq!(altimeter.relative_altitude_m(), None);
+
+        altimeter.rearm();
+        assert_eq!(altimeter.reference(), None);
+    }
+
+    #[test]
+    fn blend_reaches_63_percent_of_a_step_after_one_time_constant() {
+        let gain = 0.5;
+        let mut altimeter = with_reference(enabled(gain), SEA_LEVEL_HPA);
+        // 气压阶跃到 10 m 高度处，积分位置保持 0：τ = 1/gain 秒后走完 1 - 1/e
+        let pressure = pressure_at(10.0, SEA_LEVEL_HPA);
+        let steps = (1.0 / gain / DT).round() as usize;
+        let mut z = 0.0;
+        for _ in 0..steps {
+            z += altimeter.update(Some(pressure), z, false, DT);
+        }
+        let expected = 10.0 * (1.0 - (-1.0f64).exp());
+        assert!((z - expected).abs() < 1e-6, "z={z} expected={expected}");
+
+        // 静止期间不修正，但仍给出相对高度
+        assert_eq!(altimeter.update(Some(pressure), z, true, DT), 0.0);
+        assert!((altimeter.relative_altitude_m().unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn blend_tracks_a_pressure_ramp_with_a_lag_of_one_time_constant() {
+        let gain = 0.25;
+        let mut altimeter = with_reference(enabled(gain), SEA_LEVEL_HPA);
+        // 匀速爬升 0.5 m/s 的气压斜坡：一阶滤波稳态滞后为 速率 · τ = 2 m
+        let rate = 0.5;
+        let mut z = 0.0;
+        let mut h = 0.0;
+        for step in 1..=(60.0 / DT) as usize {
+            h = rate * step as f64 * DT;
+            z += altimeter.update(Some(pressure_at(h, SEA_LEVEL_HPA)), z, false, DT);
+        }
+        let lag = h - z;
+        assert!((lag - rate / gain).abs() < 0.01, "lag={lag}");
+    }
+}
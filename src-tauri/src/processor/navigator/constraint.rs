@@ -0,0 +1,35 @@
+//! 平面约束（桌面 2D 模式）。
+//!
+//! 桌面场景下竖直方向的位移全部是误差。启用 `horizontal` 后每帧积分完成时：
+//! - 去掉速度在重力方向上的分量；
+//! - 把位置在重力方向上的分量钳到固定高度。
+//!
+//! "竖直"取 `gravity_ref` 方向而不是原始 Z 轴，因此与轴校准后的参考系一致。
+
+use math_f64::DVec3;
+
+use crate::processor::navigator::types::{NavState, PlaneConstraintConfig, PlaneConstraintMode};
+
+/// 对导航状态施加平面约束。
+///
+/// 返回本帧被投影去除的沿重力方向速度分量 (m/s)，未启用时为 0。
+pub fn apply_plane_constraint(
+    config: &PlaneConstraintConfig,
+    gravity_ref: DVec3,
+    state: &mut NavState,
+) -> f64 {
+    match config.mode {
+        PlaneConstraintMode::None => 0.0,
+        PlaneConstraintMode::Horizontal => {
+            let up = gravity_ref.normalize_or_zero();
+            if up == DVec3::ZERO {
+                return 0.0;
+            }
+            let removed = state.velocity.dot(up);
+            state.velocity -= up * removed;
+            let height = state.position.dot(up);
+            state.position += up * (config.height_m - height);
+            removed
+        }
+    }
+}
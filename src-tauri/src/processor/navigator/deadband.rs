@@ -0,0 +1,117 @@
+//! 线加速度积分死区。
+//!
+//! 手持设备"几乎静止"（如书写小字）时，线加速度噪声低于 ZUPT 阈值却不为零，
+//! 积分后速度缓慢爬升，位置每分钟漂移数厘米。死区把幅值低于 `accel_deadband_ms2`
+//! 的线加速度在积分前视为 0：
+//! - `magnitude`：模长低于阈值时整体置零，超过阈值时原样保留；
+//! - `per_axis`：三个轴分别判定，低于阈值的分量置零。
+//!
+//! 死区是硬阈值，超过阈值的加速度不做扣减，真实运动的积分不受影响。
+//! 运动中线加速度达到 `accel_deadband_bypass_ms2` 时整帧跳过死区，
+//! 避免逐轴模式把剧烈运动中的小分量抹掉、扭曲方向。
+
+use math_f64::DVec3;
+
+use crate::processor::navigator::types::{AccelDeadbandMode, MotionState, TrajectoryConfig};
+
+/// 对积分用的世界系线加速度施加死区。
+///
+/// `motion_state` 为上一帧的 ZUPT 判定结果。返回本帧被置零的加速度模长 (m/s²)，
+/// 未启用或被旁路时为 0。
+pub fn apply_accel_deadband(
+    config: &TrajectoryConfig,
+    motion_state: MotionState,
+    accel_lin: &mut DVec3,
+) -> f64 {
+    let band = config.accel_deadband_ms2;
+    if band <= 0.0 {
+        return 0.0;
+    }
+    let magnitude = accel_lin.length();
+    if motion_state == MotionState::Moving && magnitude >= config.accel_deadband_bypass_ms2 {
+        return 0.0;
+    }
+    let kept = match config.accel_deadband_mode {
+        AccelDeadbandMode::Magnitude => {
+            if magnitude < band {
+                DVec3::ZERO
+            } else {
+                *accel_lin
+            }
+        }
+        AccelDeadbandMode::PerAxis => {
+            let keep = |v: f64| if v.abs() < band { 0.0 } else { v };
+            DVec3::new(keep(accel_lin.x), keep(accel_lin.y), keep(accel_lin.z))
+        }
+    };
+    let clipped = (*accel_lin - kept).length();
+    *accel_lin = kept;
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: AccelDeadbandMode) -> TrajectoryConfig {
+        TrajectoryConfig {
+            accel_deadband_ms2: 0.05,
+            accel_deadband_mode: mode,
+            accel_deadband_bypass_ms2: 1.0,
+            ..TrajectoryConfig::default()
+        }
+    }
+
+    #[test]
+    fn magnitude_mode_zeroes_small_vectors_and_keeps_large_ones_intact() {
+        let config = config(AccelDeadbandMode::Magnitude);
+
+        let mut small = DVec3::new(0.03, -0.03, 0.0);
+        let clipped = apply_accel_deadband(&config, MotionState::Static, &mut small);
+        assert_eq!(small, DVec3::ZERO);
+        assert!((clipped - 0.03 * 2f64.sqrt()).abs() < 1e-12);
+
+        // 单轴分量低于阈值，但模长超过阈值：方向保持不变
+        let mut large = DVec3::new(0.2, 0.01, 0.0);
+        let clipped = apply_accel_deadband(&config, MotionState::Moving, &mut large);
+        assert_eq!(large, DVec3::new(0.2, 0.01, 0.0));
+        assert_eq!(clipped, 0.0);
+    }
+
+    #[test]
+    fn per_axis_mode_zeroes_each_small_component() {
+        let config = config(AccelDeadbandMode::PerAxis);
+        let mut accel = DVec3::new(0.2, 0.01, -0.04);
+        let clipped = apply_accel_deadband(&config, MotionState::Moving, &mut accel);
+        assert_eq!(accel, DVec3::new(0.2, 0.0, 0.0));
+        assert!((clipped - DVec3::new(0.0, 0.01, -0.04).length()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn high_accel_while_moving_bypasses_the_band() {
+        let config = config(AccelDeadbandMode::PerAxis);
+
+        let mut moving = DVec3::new(3.0, 0.02, 0.0);
+        assert_eq!(
+            apply_accel_deadband(&config, MotionState::Moving, &mut moving),
+            0.0
+        );
+        assert_eq!(moving, DVec3::new(3.0, 0.02, 0.0));
+
+        // 同样的加速度在运动状态未判定时仍按死区处理
+        let mut unknown = DVec3::new(3.0, 0.02, 0.0);
+        apply_accel_deadband(&config, MotionState::Unknown, &mut unknown);
+        assert_eq!(unknown, DVec3::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn zero_band_is_disabled() {
+        let config = TrajectoryConfig::default();
+        let mut accel = DVec3::new(0.01, 0.0, 0.0);
+        assert_eq!(
+            apply_accel_deadband(&config, MotionState::Static, &mut accel),
+            0.0
+        );
+        assert_eq!(accel, DVec3::new(0.01, 0.0, 0.0));
+    }
+}
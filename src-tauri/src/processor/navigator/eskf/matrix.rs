@@ -65,8 +65,8 @@ impl Mat15 {
     /// 非对角元素为零。
     pub fn from_diagonal(diag: &[f64; N]) -> Self {
         let mut m = Self::zeros();
-        for i in 0..N {
-            m.data[i * N + i] = diag[i];
+        for (i, &d) in diag.iter().enumerate() {
+            m.data[i * N + i] = d;
         }
         m
     }
@@ -77,8 +77,8 @@ impl Mat15 {
     /// （不确定性的平方）。
     pub fn diagonal(&self) -> [f64; N] {
         let mut d = [0.0; N];
-        for i in 0..N {
-            d[i] = self.data[i * N + i];
+        for (i, di) in d.iter_mut().enumerate() {
+            *di = self.data[i * N + i];
         }
         d
     }
@@ -170,10 +170,10 @@ impl Mat15 {
     /// （由于 H = [0|I₃|0|0|0]，有 P*Hᵀ = P[:, 3..6]）。
     pub fn velocity_cols(&self) -> [[f64; 3]; N] {
         let mut out = [[0.0; 3]; N];
-        for i in 0..N {
-            out[i][0] = self.data[i * N + 3];
-            out[i][1] = self.data[i * N + 4];
-            out[i][2] = self.data[i * N + 5];
+        for (i, row) in out.iter_mut().enumerate() {
+            row[0] = self.data[i * N + 3];
+            row[1] = self.data[i * N + 4];
+            row[2] = self.data[i * N + 5];
         }
         out
     }
@@ -340,9 +340,9 @@ mod tests {
             1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0,
         ];
         let m = Mat15::from_diagonal(&diag);
-        for i in 0..15 {
+        for (i, &d) in diag.iter().enumerate() {
             for j in 0..15 {
-                let expected = if i == j { diag[i] } else { 0.0 };
+                let expected = if i == j { d } else { 0.0 };
                 assert!(
                     (m.get(i, j) - expected).abs() < 1e-12,
                     "from_diagonal failed at ({},{}): {} vs {}",
@@ -378,10 +378,10 @@ mod tests {
             m.set(i, 5, (i * 10 + 2) as f64);
         }
         let cols = m.velocity_cols();
-        for i in 0..15 {
-            assert!((cols[i][0] - (i * 10) as f64).abs() < 1e-12);
-            assert!((cols[i][1] - (i * 10 + 1) as f64).abs() < 1e-12);
-            assert!((cols[i][2] - (i * 10 + 2) as f64).abs() < 1e-12);
+        for (i, col) in cols.iter().enumerate() {
+            assert!((col[0] - (i * 10) as f64).abs() < 1e-12);
+            assert!((col[1] - (i * 10 + 1) as f64).abs() < 1e-12);
+            assert!((col[2] - (i * 10 + 2) as f64).abs() < 1e-12);
         }
     }
 }
@@ -67,7 +67,14 @@ use self::matrix::Mat15;
 use self::predict::{build_f_matrix, build_q_matrix, propagate_covariance};
 use self::update::{apply_state_injection, zupt_update};
 use crate::processor::filter::ImuSampleFiltered;
-use crate::processor::navigator::types::{NavState, NavigatorConfig};
+use crate::processor::navigator::constraint::apply_plane_constraint;
+use crate::processor::navigator::deadband::apply_accel_deadband;
+use crate::processor::navigator::gravity::GravityEstimator;
+use crate::processor::navigator::types::{MotionState, NavState, NavigatorConfig};
+use crate::processor::shared::{
+    frames_for_window, DeviceTimeMs, DurationS, RateLimitedLogger, WindowCounter,
+    DEFAULT_SAMPLE_INTERVAL_MS,
+};
 
 /// 基于 ESKF 的惯性导航器。
 ///
@@ -75,6 +82,7 @@ use crate::processor::navigator::types::{NavState, NavigatorConfig};
 /// 在线估计并补偿陀螺仪和加速度计偏差。
 ///
 /// 算法细节见模块级文档。
+#[derive(Clone)]
 pub struct EskfNavigator {
     /// 导航器配置（轨迹、ZUPT、重力、ESKF 参数）。
     config: NavigatorConfig,
@@ -89,13 +97,13 @@ pub struct EskfNavigator {
     /// 15x15 误差状态协方差矩阵。
     covariance: Mat15,
     /// 上一次处理样本的时间戳 (ms)。
-    last_timestamp_ms: Option<u64>,
+    last_timestamp_ms: Option<DeviceTimeMs>,
     /// 用于迟滞判断的上一次静止检测结果。
     last_is_static: Option<bool>,
     /// 进入静止状态的计数器（迟滞）。
-    static_enter_count: u32,
+    static_enter: WindowCounter,
     /// 退出静止状态的计数器（迟滞）。
-    static_exit_count: u32,
+    static_exit: WindowCounter,
     /// 上一帧世界坐标系线性加速度（用于梯形积分）。
     last_accel_lin: Option<DVec3>,
     /// gravity_ref 是否已被首帧 bootstrap 过。
@@ -110,6 +118,14 @@ pub struct EskfNavigator {
     gravity_init_sum: DVec3,
     /// 初始化窗口内低 gyro 帧数。
     gravity_init_static_frames: u32,
+    /// 静止期重力模长自动估计。
+    gravity_estimator: GravityEstimator,
+    /// ZUPT 修正日志限频。
+    zupt_log: RateLimitedLogger,
+    /// 标称采样间隔 (ms)，用于把时间窗口换算成帧数。
+    sample_interval_ms: f64,
+    /// 平面约束本帧去除的沿重力方向速度分量 (m/s)。
+    diag_plane_removed_velocity: f64,
 
     // —— 诊断用字段 ——
     /// 最近一帧 ZUPT 检测的陀螺仪范数 (rad/s)。
@@ -118,8 +134,12 @@ pub struct EskfNavigator {
     diag_accel_norm: f64,
     /// 最近一帧的世界系线性加速度 (m/s²)。
     diag_linear_accel: DVec3,
+    /// 最近一帧 ZUPT 修正前的导航状态。
+    diag_pre_zupt: Option<NavState>,
+    /// 死区本帧置零的线加速度模长 (m/s²)。
+    diag_deadband_clipped: f64,
     /// 当前积分步长 (s)。
-    diag_dt: f64,
+    diag_dt: DurationS,
     /// 最近一次 ZUPT 更新的创新向量。
     diag_last_innovation: Option<DVec3>,
 }
@@ -161,18 +181,24 @@ impl EskfNavigator {
             covariance: Mat15::from_diagonal(&init_diag),
             last_timestamp_ms: None,
             last_is_static: None,
-            static_enter_count: 0,
-            static_exit_count: 0,
+            static_enter: WindowCounter::default(),
+            static_exit: WindowCounter::default(),
             last_accel_lin: None,
             gravity_initialized: false,
             gravity_locked: false,
             gravity_init_total_frames: 0,
             gravity_init_sum: DVec3::ZERO,
             gravity_init_static_frames: 0,
+            gravity_estimator: GravityEstimator::new(config.auto_gravity),
+            zupt_log: RateLimitedLogger::per_second(),
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            diag_plane_removed_velocity: 0.0,
             diag_gyro_norm: 0.0,
             diag_accel_norm: 0.0,
             diag_linear_accel: DVec3::ZERO,
-            diag_dt: 0.0,
+            diag_pre_zupt: None,
+            diag_deadband_clipped: 0.0,
+            diag_dt: DurationS::ZERO,
             diag_last_innovation: None,
         }
     }
@@ -185,9 +211,14 @@ impl EskfNavigator {
     /// 3. 误差协方差预测（F、Q、P 传播）
     /// 4. ZUPT 检测（迟滞）
     /// 5. 若静止：执行 ZUPT 量测更新和状态注入
+    /// 6. 平面约束（若启用）
     pub fn update(&mut self, attitude: DQuat, sample: &ImuSampleFiltered) -> NavState {
         self.nav_state.attitude = attitude;
-        self.nav_state.timestamp_ms = sample.timestamp_ms;
+        self.nav_state.timestamp_ms = sample.timestamp_ms.as_millis();
+        self.diag_plane_removed_velocity = 0.0;
+        // 直通或 dt 非正提前返回时，修正前后相同
+        self.diag_pre_zupt = Some(self.nav_state);
+        self.diag_deadband_clipped = 0.0;
 
         // gravity_ref 三种初始化策略（按优先级）：
         //
@@ -203,7 +234,8 @@ impl EskfNavigator {
             let gyro_norm_init = sample.gyro_lp.length();
             const CLEAN_G_MAG_THRESH: f64 = 0.15; // m/s² — |R*a| 距真 g 的容差
             const CLEAN_GYRO_THRESH: f64 = 0.15; // rad/s
-            const INIT_WINDOW_FRAMES: u32 = 100; // ~400 ms @ 250 Hz
+            const INIT_WINDOW_MS: f64 = 400.0;
+            const INIT_MIN_CLEAN_MS: f64 = 40.0;
             let is_clean = g_mag_err < CLEAN_G_MAG_THRESH && gyro_norm_init < CLEAN_GYRO_THRESH;
 
             if !self.gravity_initialized {
@@ -225,8 +257,9 @@ impl EskfNavigator {
                     self.gravity_init_static_frames += 1;
                 }
                 self.gravity_init_total_frames += 1;
-                if self.gravity_init_total_frames >= INIT_WINDOW_FRAMES {
-                    if self.gravity_init_static_frames >= 10 {
+                let interval = self.sample_interval_ms;
+                if self.gravity_init_total_frames >= frames_for_window(INIT_WINDOW_MS, interval) {
+                    if self.gravity_init_static_frames >= frames_for_window(INIT_MIN_CLEAN_MS, interval) {
                         let refined = self.gravity_init_sum
                             / self.gravity_init_static_frames as f64;
                         tracing::info!(
@@ -248,29 +281,35 @@ impl EskfNavigator {
             }
         }
 
+        // auto_gravity：只替换模长，方向保持校准/bootstrap 得到的 gravity_ref 方向。
+        if self.gravity_locked {
+            if let Some(magnitude) = self.gravity_estimator.observe(sample) {
+                self.gravity_ref = self.gravity_ref.normalize_or_zero() * magnitude;
+            }
+        }
+
         if self.config.trajectory.passby {
             self.last_timestamp_ms = Some(sample.timestamp_ms);
             return self.nav_state;
         }
 
         // --- 步骤 1：计算 dt ---
+        let trajectory = &self.config.trajectory;
         let dt = self
             .last_timestamp_ms
             .map(|ts| {
-                clamp_dt_s(
-                    sample.timestamp_ms.saturating_sub(ts),
-                    self.config.trajectory.dt_min_ms,
-                    self.config.trajectory.dt_max_ms,
-                )
+                sample
+                    .timestamp_ms
+                    .clamped_delta_s(ts, trajectory.dt_min_ms, trajectory.dt_max_ms)
             })
-            .unwrap_or(0.0);
+            .unwrap_or(DurationS::ZERO);
         self.last_timestamp_ms = Some(sample.timestamp_ms);
 
+        self.diag_dt = dt;
+        let dt = dt.as_secs();
         if dt <= 0.0 {
-            self.diag_dt = 0.0;
             return self.nav_state;
         }
-        self.diag_dt = dt;
 
         // --- 步骤 2：名义状态传播（梯形积分） ---
         // 从原始（滤波后）量测中扣除估计偏差。
@@ -279,13 +318,22 @@ impl EskfNavigator {
         let a_lin = a_world - self.gravity_ref;
         self.diag_linear_accel = a_lin;
 
+        // 死区只作用于名义状态积分，协方差传播仍使用实际比力。
+        let mut a_integrated = a_lin;
+        self.diag_deadband_clipped = apply_accel_deadband(
+            &self.config.trajectory,
+            MotionState::from_static_flag(self.last_is_static),
+            &mut a_integrated,
+        );
+
         // 梯形积分：对当前和上一帧加速度取平均。
-        let a_prev = self.last_accel_lin.unwrap_or(a_lin);
+        let a_prev = self.last_accel_lin.unwrap_or(a_integrated);
         let v_prev = self.nav_state.velocity;
-        let v_next = v_prev + (a_prev + a_lin) * (0.5 * dt);
+        let v_next = v_prev + (a_prev + a_integrated) * (0.5 * dt);
         self.nav_state.velocity = v_next;
         self.nav_state.position += (v_prev + v_next) * (0.5 * dt);
-        self.last_accel_lin = Some(a_lin);
+        self.last_accel_lin = Some(a_integrated);
+        self.diag_pre_zupt = Some(self.nav_state);
 
         // --- 步骤 3：误差协方差预测 ---
         let f = build_f_matrix(attitude, a_lin, dt);
@@ -303,7 +351,7 @@ impl EskfNavigator {
         self.diag_gyro_norm = gyro_norm;
         self.diag_accel_norm = accel_norm;
         self.diag_last_innovation = None; // 每帧重置，仅 ZUPT 帧有值
-        let is_static = self.detect_static(gyro_norm, accel_norm);
+        let is_static = self.detect_static(sample.timestamp_ms, gyro_norm, accel_norm);
 
         // --- 步骤 5：ZUPT 量测更新 ---
         if is_static {
@@ -335,8 +383,9 @@ impl EskfNavigator {
             self.nav_state.velocity = DVec3::ZERO;
             self.last_accel_lin = None;
 
-            if sample.timestamp_ms % 1000 < 4 {
+            if let Some(suppressed) = self.zupt_log.check() {
                 tracing::info!(
+                    suppressed,
                     "ESKF ZUPT 更新 | vel=[{:.4}, {:.4}, {:.4}] | bias_g=[{:.5}, {:.5}, {:.5}] | bias_a=[{:.4}, {:.4}, {:.4}]",
                     self.nav_state.velocity.x,
                     self.nav_state.velocity.y,
@@ -351,6 +400,13 @@ impl EskfNavigator {
             }
         }
 
+        // --- 步骤 6：平面约束（桌面 2D 模式）---
+        self.diag_plane_removed_velocity = apply_plane_constraint(
+            &self.config.plane_constraint,
+            self.gravity_ref,
+            &mut self.nav_state,
+        );
+
         self.nav_state
     }
 
@@ -368,16 +424,16 @@ impl EskfNavigator {
 
     /// 迟滞进入计数器。
     pub fn zupt_enter_count(&self) -> u32 {
-        self.static_enter_count
+        self.static_enter.frames()
     }
 
     /// 迟滞退出计数器。
     pub fn zupt_exit_count(&self) -> u32 {
-        self.static_exit_count
+        self.static_exit.frames()
     }
 
     /// 当前积分步长 (s)。
-    pub fn current_dt(&self) -> f64 {
+    pub fn current_dt(&self) -> DurationS {
         self.diag_dt
     }
 
@@ -386,6 +442,16 @@ impl EskfNavigator {
         self.diag_linear_accel
     }
 
+    /// 最近一帧 ZUPT 修正前的导航状态，尚未更新过时为当前状态。
+    pub fn pre_zupt_nav_state(&self) -> NavState {
+        self.diag_pre_zupt.unwrap_or(self.nav_state)
+    }
+
+    /// 死区本帧置零的线加速度模长 (m/s²)。
+    pub fn deadband_clipped(&self) -> f64 {
+        self.diag_deadband_clipped
+    }
+
     /// ESKF 协方差对角线（15 个值）。
     pub fn eskf_cov_diag(&self) -> [f64; 15] {
         self.covariance.diagonal()
@@ -401,6 +467,21 @@ impl EskfNavigator {
         self.bias_accel
     }
 
+    /// 自动估计的重力模长 (m/s²)，未启用或尚未完成时为 `None`。
+    pub fn gravity_estimate(&self) -> Option<f64> {
+        self.gravity_estimator.estimate()
+    }
+
+    /// 世界系重力参考向量 (m/s²)。
+    pub fn gravity_ref(&self) -> DVec3 {
+        self.gravity_ref
+    }
+
+    /// 平面约束本帧去除的沿重力方向速度分量 (m/s)。
+    pub fn plane_removed_velocity(&self) -> f64 {
+        self.diag_plane_removed_velocity
+    }
+
     /// 取出并清除上次 ZUPT 更新的创新向量。
     pub fn take_last_innovation(&mut self) -> Option<DVec3> {
         self.diag_last_innovation.take()
@@ -411,6 +492,11 @@ impl EskfNavigator {
         self.last_is_static.unwrap_or(false)
     }
 
+    /// 当前运动状态，初始化期间为 `Unknown`。
+    pub fn motion_state(&self) -> MotionState {
+        MotionState::from_static_flag(self.last_is_static)
+    }
+
     /// 在姿态零点校准后设置重力参考向量。
     ///
     /// `quat_offset` 是用于轴对齐的左乘四元数。
@@ -421,6 +507,7 @@ impl EskfNavigator {
         // 手动校准立即锁定 gravity_ref，绕过初始化窗口的自动 refine。
         self.gravity_initialized = true;
         self.gravity_locked = true;
+        self.gravity_estimator.arm();
         tracing::info!(
             "ESKF 重力参考更新 | g_ref=[{:.3}, {:.3}, {:.3}]",
             self.gravity_ref.x,
@@ -429,6 +516,11 @@ impl EskfNavigator {
         );
     }
 
+    /// 平移名义位置（外部辅助修正，如气压高度），速度与协方差不变。
+    pub fn shift_position(&mut self, delta: DVec3) {
+        self.nav_state.position += delta;
+    }
+
     /// 手动设置位置（例如用于坐标校正）。
     pub fn set_position(&mut self, position: DVec3) {
         tracing::info!(
@@ -444,6 +536,36 @@ impl EskfNavigator {
         self.nav_state.velocity = DVec3::ZERO;
     }
 
+    /// 原地更新可调参数，名义状态、协方差与偏差估计保留。
+    ///
+    /// 新的过程/观测噪声从下一帧起生效；`init_sigma_*` 只在重置时使用。
+    pub fn reconfigure(&mut self, config: NavigatorConfig) {
+        self.config = config;
+    }
+
+    /// 设置标称采样间隔 (ms)，导航状态保留。
+    pub fn set_sample_interval_ms(&mut self, sample_interval_ms: f64) {
+        self.sample_interval_ms = sample_interval_ms;
+    }
+
+    /// 最近一帧导航状态。
+    pub fn nav_state(&self) -> NavState {
+        self.nav_state
+    }
+
+    /// 设备复位后丢弃时间基准，下一帧 dt 记 0；上一帧线加速度一并丢弃，其余状态保持。
+    pub fn reset_timing(&mut self) {
+        self.last_timestamp_ms = None;
+        self.last_accel_lin = None;
+    }
+
+    /// 丢弃 ZUPT 检测状态，运动状态回到未知，由后续帧重新判定。
+    pub fn reset_zupt(&mut self) {
+        self.last_is_static = None;
+        self.static_enter.reset();
+        self.static_exit.reset();
+    }
+
     /// 将所有内部状态重置为初始值。
     pub fn reset(&mut self) {
         let eskf = &self.config.eskf;
@@ -472,18 +594,21 @@ impl EskfNavigator {
         self.gravity_init_total_frames = 0;
         self.gravity_init_sum = DVec3::ZERO;
         self.gravity_init_static_frames = 0;
+        self.gravity_estimator.arm();
         self.bias_gyro = DVec3::ZERO;
         self.bias_accel = DVec3::ZERO;
         self.covariance = Mat15::from_diagonal(&init_diag);
         self.last_timestamp_ms = None;
         self.last_is_static = None;
-        self.static_enter_count = 0;
-        self.static_exit_count = 0;
+        self.static_enter.reset();
+        self.static_exit.reset();
         self.last_accel_lin = None;
         self.diag_gyro_norm = 0.0;
         self.diag_accel_norm = 0.0;
         self.diag_linear_accel = DVec3::ZERO;
-        self.diag_dt = 0.0;
+        self.diag_pre_zupt = None;
+        self.diag_deadband_clipped = 0.0;
+        self.diag_dt = DurationS::ZERO;
         self.diag_last_innovation = None;
 
         tracing::info!("ESKF 导航器已重置");
@@ -491,9 +616,14 @@ impl EskfNavigator {
 
     /// 基于迟滞的 ZUPT 静止检测。
     ///
-    /// 使用进入/退出阈值和帧计数器，避免静止与运动状态之间快速抖动。
+    /// 使用进入/退出阈值和判定窗口，避免静止与运动状态之间快速抖动；低频上报时窗口按设备时间计算。
     /// 该逻辑与旧版 `SmoothHysteresis` 检测相同，但不包含平滑衰减修正。
-    fn detect_static(&mut self, gyro_norm: f64, accel_norm: f64) -> bool {
+    fn detect_static(
+        &mut self,
+        timestamp_ms: DeviceTimeMs,
+        gyro_norm: f64,
+        accel_norm: f64,
+    ) -> bool {
         let zupt = &self.config.zupt;
         let entering = gyro_norm < zupt.gyro_enter_thresh
             && accel_norm < zupt.accel_enter_thresh;
@@ -503,36 +633,29 @@ impl EskfNavigator {
         let prev_is_static = self.last_is_static.unwrap_or(false);
         let mut is_static = prev_is_static;
 
+        let interval = self.sample_interval_ms;
         if prev_is_static {
-            if exiting {
-                self.static_exit_count = self.static_exit_count.saturating_add(1);
-            } else {
-                self.static_exit_count = 0;
-            }
-            if self.static_exit_count >= zupt.exit_frames.max(1) {
+            if self
+                .static_exit
+                .observe(exiting, timestamp_ms, zupt.exit_window_ms, interval)
+            {
                 is_static = false;
-                self.static_exit_count = 0;
                 tracing::info!(
                     "ESKF ZUPT: 退出静止状态 | gyro={:.4} rad/s | accel_lin={:.4} m/s²",
                     gyro_norm,
                     accel_norm
                 );
             }
-        } else {
-            if entering {
-                self.static_enter_count = self.static_enter_count.saturating_add(1);
-            } else {
-                self.static_enter_count = 0;
-            }
-            if self.static_enter_count >= zupt.enter_frames.max(1) {
-                is_static = true;
-                self.static_enter_count = 0;
-                tracing::info!(
-                    "ESKF ZUPT: 进入静止状态 | gyro={:.4} rad/s | accel_lin={:.4} m/s²",
-                    gyro_norm,
-                    accel_norm
-                );
-            }
+        } else if self
+            .static_enter
+            .observe(entering, timestamp_ms, zupt.enter_window_ms, interval)
+        {
+            is_static = true;
+            tracing::info!(
+                "ESKF ZUPT: 进入静止状态 | gyro={:.4} rad/s | accel_lin={:.4} m/s²",
+                gyro_norm,
+                accel_norm
+            );
         }
 
         self.last_is_static = Some(is_static);
@@ -540,14 +663,6 @@ impl EskfNavigator {
     }
 }
 
-/// 将毫秒时间差限制在 `[dt_min_ms, dt_max_ms]` 范围内，并转换为秒。
-fn clamp_dt_s(delta_ms: u64, dt_min_ms: u64, dt_max_ms: u64) -> f64 {
-    let lower = dt_min_ms.max(1);
-    let upper = dt_max_ms.max(lower);
-    let clamped = delta_ms.clamp(lower, upper);
-    clamped as f64 / 1000.0
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,6 +673,8 @@ mod tests {
     fn test_config() -> NavigatorConfig {
         NavigatorConfig {
             gravity: 9.80665,
+            auto_gravity: Default::default(),
+            plane_constraint: Default::default(),
             trajectory: TrajectoryConfig {
                 passby: false,
                 dt_max_ms: 1000,
@@ -569,12 +686,15 @@ mod tests {
                 accel_enter_thresh: 0.22,
                 gyro_exit_thresh: 0.2,
                 accel_exit_thresh: 0.3,
-                enter_frames: 1,
-                exit_frames: 1,
+                enter_window_ms: 0.0,
+                exit_window_ms: 0.0,
                 ..ZuptConfig::default()
             },
             navigator_impl: NavigatorImplType::Eskf,
             eskf: EskfConfig::default(),
+            position_source: Default::default(),
+            auto_origin: Default::default(),
+            baro: Default::default(),
         }
     }
 
@@ -587,9 +707,11 @@ mod tests {
         // 输入若干静止样本。
         for i in 0..20 {
             let sample = ImuSampleFiltered {
-                timestamp_ms: i * 20,
+                timestamp_ms: DeviceTimeMs(i * 20),
                 accel_lp: DVec3::new(0.0, 0.0, gravity + 0.01),
                 gyro_lp: DVec3::new(0.005, 0.005, 0.005),
+                jerk: None,
+                ang_accel: None,
             };
             nav.update(attitude, &sample);
         }
@@ -609,16 +731,20 @@ mod tests {
         let gravity = 9.80665;
 
         let sample = ImuSampleFiltered {
-            timestamp_ms: 0,
+            timestamp_ms: DeviceTimeMs(0),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 1.0),
             gyro_lp: DVec3::new(0.0, 0.0, 0.3),
+            jerk: None,
+            ang_accel: None,
         };
         nav.update(attitude, &sample);
 
         let sample2 = ImuSampleFiltered {
-            timestamp_ms: 100,
+            timestamp_ms: DeviceTimeMs(100),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 1.0),
             gyro_lp: DVec3::new(0.0, 0.0, 0.3),
+            jerk: None,
+            ang_accel: None,
         };
         nav.update(attitude, &sample2);
 
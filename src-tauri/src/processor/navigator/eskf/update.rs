@@ -102,8 +102,8 @@ pub fn zupt_update(p: &mut Mat15, v_nominal: DVec3, zupt_noise: f64) -> Vec15 {
 
     // 误差状态：δx = K * y（15x1 = 15x3 * 3x1）。
     let mut dx = Vec15::zeros();
-    for i in 0..15 {
-        dx.set(i, k[i][0] * y.x + k[i][1] * y.y + k[i][2] * y.z);
+    for (i, ki) in k.iter().enumerate() {
+        dx.set(i, ki[0] * y.x + ki[1] * y.y + ki[2] * y.z);
     }
 
     // 更新协方差：P = (I - K * H) * P。
@@ -0,0 +1,159 @@
+//! 静止期重力模长自动估计。
+//!
+//! 配置的 `gravity` 与传感器实测静止模长之间常有几 mm/s² 的差异（当地重力不同、
+//! 加速度计刻度误差），该差值会以常值竖直加速度的形式被积分成米级漂移。
+//! 启用 `auto_gravity` 后，(重新)校准后的第一段持续静止期内对滤波后加速度模长
+//! 取均值，作为重力参考的模长；方向仍由轴校准四元数决定。
+
+use crate::processor::{
+    filter::ImuSampleFiltered, navigator::types::AutoGravityConfig, shared::RunningStats,
+};
+
+/// 估计值的硬性合理范围 (m/s²)，超出即拒绝。
+pub const AUTO_GRAVITY_RANGE: (f64, f64) = (9.5, 10.1);
+
+/// 判定静止的角速度上限 (rad/s)。
+const STATIC_GYRO_THRESH: f64 = 0.15;
+/// 相邻帧加速度模长的最大变化 (m/s²)，超过视为运动。
+const STATIC_ACCEL_STEP_THRESH: f64 = 0.05;
+
+/// 重力模长估计器。
+///
+/// 独立于 ZUPT 做静止判断，这样 ZUPT 关闭时依然可以估计。
+/// 每次 [`arm`](Self::arm) 后最多产出一个结果：接受或拒绝后即停止估计，
+/// 直到下一次校准重新 arm。
+#[derive(Debug, Clone)]
+pub struct GravityEstimator {
+    config: AutoGravityConfig,
+    armed: bool,
+    magnitude: RunningStats,
+    last_magnitude: Option<f64>,
+    estimate: Option<f64>,
+}
+
+impl GravityEstimator {
+    /// 创建估计器，启用时立即 arm。
+    pub fn new(config: AutoGravityConfig) -> Self {
+        Self {
+            config,
+            armed: config.enabled,
+            magnitude: RunningStats::new(),
+            last_magnitude: None,
+            estimate: None,
+        }
+    }
+
+    /// 校准后重新开始估计，并清除上一次的估计值。
+    pub fn arm(&mut self) {
+        self.armed = self.config.enabled;
+        self.estimate = None;
+        self.restart_window();
+    }
+
+    /// 当前生效的估计值 (m/s²)。
+    pub fn estimate(&self) -> Option<f64> {
+        self.estimate
+    }
+
+    /// 输入一帧样本，窗口完成且估计值合理时返回新的重力模长。
+    pub fn observe(&mut self, sample: &ImuSampleFiltered) -> Option<f64> {
+        if !self.armed {
+            return None;
+        }
+
+        let magnitude = sample.accel_lp.length();
+        let steady = self
+            .last_magnitude
+            .is_none_or(|last| (magnitude - last).abs() < STATIC_ACCEL_STEP_THRESH);
+        self.last_magnitude = Some(magnitude);
+
+        if sample.gyro_lp.length() >= STATIC_GYRO_THRESH || !steady {
+            // 静止被打断，要求的是"持续"静止，从头累计
+            self.magnitude.reset();
+            return None;
+        }
+
+        self.magnitude.push(magnitude);
+        if self.magnitude.count() < u64::from(self.config.window_frames.max(1)) {
+            return None;
+        }
+
+        self.armed = false;
+        let mean = self.magnitude.mean();
+        let (min, max) = AUTO_GRAVITY_RANGE;
+        if (min..=max).contains(&mean) {
+            tracing::info!(
+                "自动重力估计完成 | |g|={:.4} m/s² ({} 帧)",
+                mean,
+                self.magnitude.count()
+            );
+            self.estimate = Some(mean);
+            Some(mean)
+        } else {
+            tracing::warn!(
+                "自动重力估计超出合理范围 [{min}, {max}]，已拒绝 | |g|={:.4} m/s²",
+                mean
+            );
+            None
+        }
+    }
+
+    fn restart_window(&mut self) {
+        self.magnitude.reset();
+        self.last_magnitude = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::DVec3;
+
+    use super::*;
+    use crate::processor::shared::DeviceTimeMs;
+
+    fn sample(magnitude: f64, gyro: f64) -> ImuSampleFiltered {
+        ImuSampleFiltered {
+            timestamp_ms: DeviceTimeMs(0),
+            accel_lp: DVec3::new(0.0, 0.0, magnitude),
+            gyro_lp: DVec3::new(0.0, 0.0, gyro),
+            jerk: None,
+            ang_accel: None,
+        }
+    }
+
+    fn enabled(window_frames: u32) -> AutoGravityConfig {
+        AutoGravityConfig {
+            enabled: true,
+            window_frames,
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_restarts_on_motion() {
+        let mut estimator = GravityEstimator::new(enabled(10));
+        for _ in 0..9 {
+            assert_eq!(estimator.observe(&sample(9.86, 0.0)), None);
+        }
+        // 运动打断，窗口重来
+        assert_eq!(estimator.observe(&sample(9.86, 1.0)), None);
+        for _ in 0..9 {
+            assert_eq!(estimator.observe(&sample(9.86, 0.0)), None);
+        }
+        let g = estimator.observe(&sample(9.86, 0.0)).unwrap();
+        assert!((g - 9.86).abs() < 1e-12);
+        // 每次 arm 只出一个结果
+        assert_eq!(estimator.observe(&sample(9.86, 0.0)), None);
+
+        estimator.arm();
+        assert_eq!(estimator.estimate(), None);
+        for _ in 0..10 {
+            assert_eq!(estimator.observe(&sample(10.4, 0.0)), None);
+        }
+        assert_eq!(estimator.estimate(), None);
+
+        let mut disabled = GravityEstimator::new(AutoGravityConfig::default());
+        for _ in 0..1000 {
+            assert_eq!(disabled.observe(&sample(9.86, 0.0)), None);
+        }
+    }
+}
@@ -7,7 +7,16 @@ use math_f64::{DQuat, DVec3};
 
 use crate::processor::{
     filter::ImuSampleFiltered,
-    navigator::types::{IntegratorImpl, NavState, NavigatorConfig, ZuptImpl},
+    navigator::{
+        constraint::apply_plane_constraint,
+        deadband::apply_accel_deadband,
+        gravity::GravityEstimator,
+        types::{IntegratorImpl, MotionState, NavState, NavigatorConfig, ZuptImpl},
+    },
+    shared::{
+        frames_for_window, DeviceTimeMs, DurationS, RateLimitedLogger, WindowCounter,
+        DEFAULT_SAMPLE_INTERVAL_MS,
+    },
 };
 
 /// 传统导航融合器（Legacy）。
@@ -18,16 +27,17 @@ use crate::processor::{
 /// 3) 提交（写回内部状态）
 ///
 /// 此实现为原始版本，不包含卡尔曼滤波或偏差估计。
+#[derive(Clone)]
 pub struct LegacyNavigator {
     config: NavigatorConfig,
     nav_state: NavState,
     gravity_ref: DVec3,
-    last_timestamp_ms: Option<u64>,
-    current_dt_s: f64,
+    last_timestamp_ms: Option<DeviceTimeMs>,
+    current_dt: DurationS,
     last_accel_lin: Option<DVec3>,
     last_is_static: Option<bool>,
-    static_enter_count: u32,
-    static_exit_count: u32,
+    static_enter: WindowCounter,
+    static_exit: WindowCounter,
     static_position: Option<DVec3>,
     /// 运动段起始时间戳（用于 backward correction）。
     swing_start_time: Option<u64>,
@@ -43,6 +53,14 @@ pub struct LegacyNavigator {
     gravity_init_sum: DVec3,
     /// 初始化窗口内低 gyro 帧数。
     gravity_init_static_frames: u32,
+    /// 静止期重力模长自动估计。
+    gravity_estimator: GravityEstimator,
+    /// ZUPT 修正日志限频。
+    zupt_log: RateLimitedLogger,
+    /// 标称采样间隔 (ms)，用于把时间窗口换算成帧数。
+    sample_interval_ms: f64,
+    /// 平面约束本帧去除的沿重力方向速度分量 (m/s)。
+    diag_plane_removed_velocity: f64,
 
     // —— 诊断用字段（仅用于读取，不影响导航逻辑）——
     /// 最近一帧 ZUPT 检测的陀螺仪范数 (rad/s)。
@@ -51,6 +69,10 @@ pub struct LegacyNavigator {
     diag_accel_norm: f64,
     /// 最近一帧的世界系线性加速度 (m/s²)。
     diag_linear_accel: DVec3,
+    /// 最近一帧 ZUPT 修正前的导航状态。
+    diag_pre_zupt: Option<NavState>,
+    /// 死区本帧置零的线加速度模长 (m/s²)。
+    diag_deadband_clipped: f64,
     /// 本帧是否触发了后向修正。
     diag_backward_triggered: bool,
     /// 后向修正量 (m)。
@@ -71,11 +93,11 @@ impl LegacyNavigator {
             },
             gravity_ref: DVec3::new(0.0, 0.0, gravity),
             last_timestamp_ms: None,
-            current_dt_s: 0.0,
+            current_dt: DurationS::ZERO,
             last_accel_lin: None,
             last_is_static: None,
-            static_enter_count: 0,
-            static_exit_count: 0,
+            static_enter: WindowCounter::default(),
+            static_exit: WindowCounter::default(),
             static_position: None,
             swing_start_time: None,
             swing_start_position: None,
@@ -84,9 +106,15 @@ impl LegacyNavigator {
             gravity_init_total_frames: 0,
             gravity_init_sum: DVec3::ZERO,
             gravity_init_static_frames: 0,
+            gravity_estimator: GravityEstimator::new(config.auto_gravity),
+            zupt_log: RateLimitedLogger::per_second(),
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            diag_plane_removed_velocity: 0.0,
             diag_gyro_norm: 0.0,
             diag_accel_norm: 0.0,
             diag_linear_accel: DVec3::ZERO,
+            diag_pre_zupt: None,
+            diag_deadband_clipped: 0.0,
             diag_backward_triggered: false,
             diag_backward_correction_mag: 0.0,
         }
@@ -97,6 +125,16 @@ impl LegacyNavigator {
         self.last_is_static.unwrap_or(false)
     }
 
+    /// 当前运动状态，初始化期间为 `Unknown`。
+    pub fn motion_state(&self) -> MotionState {
+        MotionState::from_static_flag(self.last_is_static)
+    }
+
+    /// 静止锁定的位置。
+    pub fn locked_position(&self) -> Option<DVec3> {
+        self.static_position
+    }
+
     /// 设置姿态零位校准后的重力参考向量。
     ///
     /// `quat_offset` 为姿态零位校准使用的左乘四元数，导航中应使用同一参考系
@@ -107,6 +145,7 @@ impl LegacyNavigator {
         // 手动校准立即锁定 gravity_ref，绕过初始化窗口的自动 refine。
         self.gravity_initialized = true;
         self.gravity_locked = true;
+        self.gravity_estimator.arm();
         tracing::info!(
             "重力参考更新 | g_ref=[{:.3}, {:.3}, {:.3}]",
             self.gravity_ref.x,
@@ -120,6 +159,8 @@ impl LegacyNavigator {
         // 每帧重置事件标记
         self.diag_backward_triggered = false;
         self.diag_backward_correction_mag = 0.0;
+        self.diag_plane_removed_velocity = 0.0;
+        self.diag_deadband_clipped = 0.0;
 
         // gravity_ref 三种初始化策略，逻辑与 EskfNavigator 一致。
         // 见 eskf/mod.rs 的详细注释。
@@ -128,8 +169,9 @@ impl LegacyNavigator {
             let g_mag_err = (g_now.length() - self.config.gravity).abs();
             let gyro_norm_init = sample.gyro_lp.length();
             const CLEAN_G_MAG_THRESH: f64 = 0.15;
-            const CLEAN_GYRO_THRESH: f64 = 0.15;
-            const INIT_WINDOW_FRAMES: u32 = 100;
+            const CLEAN_GYRO_THRESH: f64 = 0.15; // rad/s
+            const INIT_WINDOW_MS: f64 = 400.0;
+            const INIT_MIN_CLEAN_MS: f64 = 40.0;
             let is_clean = g_mag_err < CLEAN_G_MAG_THRESH && gyro_norm_init < CLEAN_GYRO_THRESH;
 
             if !self.gravity_initialized {
@@ -148,8 +190,9 @@ impl LegacyNavigator {
                     self.gravity_init_static_frames += 1;
                 }
                 self.gravity_init_total_frames += 1;
-                if self.gravity_init_total_frames >= INIT_WINDOW_FRAMES {
-                    if self.gravity_init_static_frames >= 10 {
+                let interval = self.sample_interval_ms;
+                if self.gravity_init_total_frames >= frames_for_window(INIT_WINDOW_MS, interval) {
+                    if self.gravity_init_static_frames >= frames_for_window(INIT_MIN_CLEAN_MS, interval) {
                         let refined = self.gravity_init_sum
                             / self.gravity_init_static_frames as f64;
                         tracing::info!(
@@ -166,8 +209,23 @@ impl LegacyNavigator {
             }
         }
 
+        // auto_gravity：只替换模长，方向保持校准/bootstrap 得到的 gravity_ref 方向。
+        if self.gravity_locked {
+            if let Some(magnitude) = self.gravity_estimator.observe(sample) {
+                self.gravity_ref = self.gravity_ref.normalize_or_zero() * magnitude;
+            }
+        }
+
         self.predict(attitude, sample);
+        self.diag_pre_zupt = Some(self.nav_state);
         self.apply_zupt(sample);
+        if !self.config.trajectory.passby {
+            self.diag_plane_removed_velocity = apply_plane_constraint(
+                &self.config.plane_constraint,
+                self.gravity_ref,
+                &mut self.nav_state,
+            );
+        }
         self.nav_state
     }
 
@@ -185,17 +243,17 @@ impl LegacyNavigator {
 
     /// 迟滞进入计数器。
     pub fn zupt_enter_count(&self) -> u32 {
-        self.static_enter_count
+        self.static_enter.frames()
     }
 
     /// 迟滞退出计数器。
     pub fn zupt_exit_count(&self) -> u32 {
-        self.static_exit_count
+        self.static_exit.frames()
     }
 
     /// 当前积分步长 (s)。
-    pub fn current_dt(&self) -> f64 {
-        self.current_dt_s
+    pub fn current_dt(&self) -> DurationS {
+        self.current_dt
     }
 
     /// 最近一帧世界系线性加速度 (m/s²)。
@@ -203,6 +261,16 @@ impl LegacyNavigator {
         self.diag_linear_accel
     }
 
+    /// 最近一帧 ZUPT 修正前的导航状态，尚未更新过时为当前状态。
+    pub fn pre_zupt_nav_state(&self) -> NavState {
+        self.diag_pre_zupt.unwrap_or(self.nav_state)
+    }
+
+    /// 死区本帧置零的线加速度模长 (m/s²)。
+    pub fn deadband_clipped(&self) -> f64 {
+        self.diag_deadband_clipped
+    }
+
     /// 本帧是否触发了后向修正。
     pub fn backward_triggered(&self) -> bool {
         self.diag_backward_triggered
@@ -213,6 +281,29 @@ impl LegacyNavigator {
         self.diag_backward_correction_mag
     }
 
+    /// 自动估计的重力模长 (m/s²)，未启用或尚未完成时为 `None`。
+    pub fn gravity_estimate(&self) -> Option<f64> {
+        self.gravity_estimator.estimate()
+    }
+
+    /// 世界系重力参考向量 (m/s²)。
+    pub fn gravity_ref(&self) -> DVec3 {
+        self.gravity_ref
+    }
+
+    /// 平面约束本帧去除的沿重力方向速度分量 (m/s)。
+    pub fn plane_removed_velocity(&self) -> f64 {
+        self.diag_plane_removed_velocity
+    }
+
+    /// 平移位置（外部辅助修正，如气压高度）：静止锁定点随之平移，速度不变。
+    pub fn shift_position(&mut self, delta: DVec3) {
+        self.nav_state.position += delta;
+        if let Some(static_position) = self.static_position.as_mut() {
+            *static_position += delta;
+        }
+    }
+
     /// 手动设置位置（用于校正）。
     pub fn set_position(&mut self, position: DVec3) {
         tracing::info!(
@@ -233,6 +324,43 @@ impl LegacyNavigator {
         }
     }
 
+    /// 原地更新可调参数（阈值、衰减、dt 限幅），位置/速度/ZUPT 状态保留。
+    ///
+    /// 调用方保证重力、积分器与 ZUPT 实现不变，见
+    /// [`ProcessorPipelineConfig::requires_reset`](crate::processor::pipeline::ProcessorPipelineConfig::requires_reset)。
+    pub fn reconfigure(&mut self, config: NavigatorConfig) {
+        self.config = config;
+    }
+
+    /// 设置标称采样间隔 (ms)，导航状态保留。
+    pub fn set_sample_interval_ms(&mut self, sample_interval_ms: f64) {
+        self.sample_interval_ms = sample_interval_ms;
+    }
+
+    /// 最近一帧导航状态。
+    pub fn nav_state(&self) -> NavState {
+        self.nav_state
+    }
+
+    /// 设备复位后丢弃时间基准：下一帧 dt 记 0，跨越复位的摆动不做后向修正。
+    ///
+    /// 上一帧线加速度一并丢弃，梯形积分不会跨越间隔取平均。
+    pub fn reset_timing(&mut self) {
+        self.last_timestamp_ms = None;
+        self.last_accel_lin = None;
+        self.swing_start_time = None;
+    }
+
+    /// 丢弃 ZUPT 检测状态与静止锁定位置，运动状态回到未知，由后续帧重新判定。
+    pub fn reset_zupt(&mut self) {
+        self.last_is_static = None;
+        self.static_enter.reset();
+        self.static_exit.reset();
+        self.static_position = None;
+        self.swing_start_time = None;
+        self.swing_start_position = None;
+    }
+
     /// 重置内部状态。
     pub fn reset(&mut self) {
         self.nav_state = NavState {
@@ -247,35 +375,44 @@ impl LegacyNavigator {
         self.gravity_init_total_frames = 0;
         self.gravity_init_sum = DVec3::ZERO;
         self.gravity_init_static_frames = 0;
+        self.gravity_estimator.arm();
         self.last_timestamp_ms = None;
-        self.current_dt_s = 0.0;
+        self.current_dt = DurationS::ZERO;
         self.last_accel_lin = None;
         self.last_is_static = None;
-        self.static_enter_count = 0;
-        self.static_exit_count = 0;
+        self.static_enter.reset();
+        self.static_exit.reset();
         self.static_position = None;
         self.swing_start_time = None;
         self.swing_start_position = None;
         self.diag_gyro_norm = 0.0;
         self.diag_accel_norm = 0.0;
         self.diag_linear_accel = DVec3::ZERO;
+        self.diag_pre_zupt = None;
+        self.diag_deadband_clipped = 0.0;
         self.diag_backward_triggered = false;
         self.diag_backward_correction_mag = 0.0;
+        self.diag_plane_removed_velocity = 0.0;
     }
 
     fn predict(&mut self, attitude: DQuat, sample: &ImuSampleFiltered) {
         self.nav_state.attitude = attitude;
-        self.nav_state.timestamp_ms = sample.timestamp_ms;
+        self.nav_state.timestamp_ms = sample.timestamp_ms.as_millis();
 
         if self.config.trajectory.passby {
             return;
         }
 
-        let dt = self
+        let trajectory = &self.config.trajectory;
+        self.current_dt = self
             .last_timestamp_ms
-            .map(|ts| clamp_dt_s(sample.timestamp_ms.saturating_sub(ts), self.config.trajectory.dt_min_ms, self.config.trajectory.dt_max_ms))
-            .unwrap_or(0.0);
-        self.current_dt_s = dt;
+            .map(|ts| {
+                sample
+                    .timestamp_ms
+                    .clamped_delta_s(ts, trajectory.dt_min_ms, trajectory.dt_max_ms)
+            })
+            .unwrap_or(DurationS::ZERO);
+        let dt = self.current_dt.as_secs();
         self.last_timestamp_ms = Some(sample.timestamp_ms);
 
         let a_world = attitude.rotate_vec3(sample.accel_lp);
@@ -290,6 +427,8 @@ impl LegacyNavigator {
                 a_lin *= clamp / mag;
             }
         }
+        self.diag_deadband_clipped =
+            apply_accel_deadband(&self.config.trajectory, self.motion_state(), &mut a_lin);
 
         if dt <= 0.0 {
             self.last_accel_lin = Some(a_lin);
@@ -351,7 +490,7 @@ impl LegacyNavigator {
         let accel_world = self.nav_state.attitude.rotate_vec3(sample.accel_lp);
         let accel_lin = accel_world - self.gravity_ref;
         let accel_norm = accel_lin.length();
-        let dt = self.current_dt_s;
+        let dt = self.current_dt.as_secs();
 
         // 保存诊断值
         self.diag_gyro_norm = gyro_norm;
@@ -363,7 +502,7 @@ impl LegacyNavigator {
                     gyro_norm < self.config.zupt.gyro_thresh && accel_norm < self.config.zupt.accel_thresh;
                 self.apply_static_transition(is_static, gyro_norm, accel_norm);
                 if is_static {
-                    self.apply_hard_lock(accel_lin, sample.timestamp_ms);
+                    self.apply_hard_lock(accel_lin);
                 }
             }
             ZuptImpl::SmoothHysteresis => {
@@ -374,31 +513,25 @@ impl LegacyNavigator {
 
                 let prev_is_static = self.last_is_static.unwrap_or(false);
                 let mut is_static = prev_is_static;
+                let zupt = &self.config.zupt;
+                let timestamp_ms = sample.timestamp_ms;
+                let interval = self.sample_interval_ms;
                 if prev_is_static {
-                    if exiting {
-                        self.static_exit_count = self.static_exit_count.saturating_add(1);
-                    } else {
-                        self.static_exit_count = 0;
-                    }
-                    if self.static_exit_count >= self.config.zupt.exit_frames.max(1) {
+                    if self.static_exit.observe(
+                        exiting,
+                        timestamp_ms,
+                        zupt.exit_window_ms,
+                        interval,
+                    ) {
                         is_static = false;
-                        self.static_exit_count = 0;
-                    }
-                } else {
-                    if entering {
-                        self.static_enter_count = self.static_enter_count.saturating_add(1);
-                    } else {
-                        self.static_enter_count = 0;
-                    }
-                    if self.static_enter_count >= self.config.zupt.enter_frames.max(1) {
-                        is_static = true;
-                        self.static_enter_count = 0;
                     }
+                } else if self.static_enter.observe(entering, timestamp_ms, zupt.enter_window_ms, interval) {
+                    is_static = true;
                 }
 
                 self.apply_static_transition(is_static, gyro_norm, accel_norm);
                 if is_static {
-                    self.apply_smooth_static(dt, accel_lin, sample.timestamp_ms);
+                    self.apply_smooth_static(dt, accel_lin);
                 }
             }
         }
@@ -433,6 +566,8 @@ impl LegacyNavigator {
             self.swing_start_time = None;
             self.swing_start_position = None;
             self.static_position = Some(self.nav_state.position);
+            // 静止段内的残余加速度不参与下一段运动的首步积分
+            self.last_accel_lin = None;
             tracing::info!(
                 "ZUPT: 进入静止状态 | gyro={:.4} rad/s | accel_lin={:.4} m/s² | vel=[{:.3}, {:.3}, {:.3}]",
                 gyro_norm,
@@ -455,7 +590,7 @@ impl LegacyNavigator {
         self.last_is_static = Some(is_static);
     }
 
-    fn apply_hard_lock(&mut self, accel_lin: DVec3, timestamp_ms: u64) {
+    fn apply_hard_lock(&mut self, accel_lin: DVec3) {
         let vel_before = self.nav_state.velocity;
         let pos_before = self.nav_state.position;
         self.nav_state.velocity = DVec3::ZERO;
@@ -463,8 +598,9 @@ impl LegacyNavigator {
             self.nav_state.position = static_position;
         }
 
-        if timestamp_ms % 1000 < 4 {
+        if let Some(suppressed) = self.zupt_log.check() {
             tracing::info!(
+                suppressed,
                 "ZUPT 硬修正 | vel_before=[{:.3}, {:.3}, {:.3}] → [0, 0, 0] | pos_before=[{:.3}, {:.3}, {:.3}] | pos_locked=[{:.3}, {:.3}, {:.3}] | a_lin=[{:.3}, {:.3}, {:.3}]",
                 vel_before.x,
                 vel_before.y,
@@ -482,7 +618,7 @@ impl LegacyNavigator {
         }
     }
 
-    fn apply_smooth_static(&mut self, dt: f64, accel_lin: DVec3, timestamp_ms: u64) {
+    fn apply_smooth_static(&mut self, dt: f64, accel_lin: DVec3) {
         let vel_before = self.nav_state.velocity;
         let pos_before = self.nav_state.position;
 
@@ -499,8 +635,9 @@ impl LegacyNavigator {
             self.nav_state.position += (static_position - self.nav_state.position) * alpha_p;
         }
 
-        if timestamp_ms % 1000 < 4 {
+        if let Some(suppressed) = self.zupt_log.check() {
             tracing::info!(
+                suppressed,
                 "ZUPT 平滑修正 | vel_before=[{:.3}, {:.3}, {:.3}] | vel_after=[{:.3}, {:.3}, {:.3}] | pos_before=[{:.3}, {:.3}, {:.3}] | pos_after=[{:.3}, {:.3}, {:.3}] | a_lin=[{:.3}, {:.3}, {:.3}]",
                 vel_before.x,
                 vel_before.y,
@@ -521,11 +658,3 @@ impl LegacyNavigator {
         }
     }
 }
-
-/// 将毫秒间隔钳位到 `[dt_min_ms, dt_max_ms]` 范围并转换为秒。
-fn clamp_dt_s(delta_ms: u64, dt_min_ms: u64, dt_max_ms: u64) -> f64 {
-    let lower = dt_min_ms.max(1);
-    let upper = dt_max_ms.max(lower);
-    let clamped = delta_ms.clamp(lower, upper);
-    clamped as f64 / 1000.0
-}
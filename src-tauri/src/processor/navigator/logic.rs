@@ -11,13 +11,25 @@ use math_f64::{DQuat, DVec3};
 use crate::processor::{
     filter::ImuSampleFiltered,
     navigator::{
+        baro::{BaroAltimeter, BaroReference},
         eskf::EskfNavigator,
         legacy::LegacyNavigator,
-        types::{NavState, NavigatorConfig, NavigatorImplType},
+        position::{PositionCandidates, PositionFusion},
+        types::{
+            AutoOriginConfig, DebugVectors, MotionState, NavState, NavigatorConfig,
+            NavigatorImplType, OriginAnchored, PositionSource, TrajectoryConfig, ZuptState,
+        },
+        uncertainty::PositionUncertainty,
     },
+    shared::{DeviceTimeMs, DurationS, DEFAULT_SAMPLE_INTERVAL_MS},
 };
 
 /// 导航器内部实现枚举。
+///
+/// ESKF 变体携带 15x15 协方差，体积远大于 Legacy；导航器只在构建/重置时
+/// 创建一次，不值得为此多一层 Box 间接寻址。
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
 enum NavigatorInner {
     /// 传统积分 + ZUPT 修正。
     Legacy(LegacyNavigator),
@@ -36,8 +48,27 @@ enum NavigatorInner {
 /// ```toml
 /// navigator_impl = "legacy"   # 或 "eskf"
 /// ```
+#[derive(Clone)]
 pub struct Navigator {
     inner: NavigatorInner,
+    position: PositionFusion,
+    trajectory: TrajectoryConfig,
+    sample_interval_ms: f64,
+    /// 上一帧设备时间戳，用于发现超长间隔。
+    last_timestamp_ms: Option<DeviceTimeMs>,
+    /// 因间隔过大而跳过积分的次数。
+    skipped_integrations: u64,
+    /// Legacy 模式下的位置不确定度估计。
+    uncertainty: PositionUncertainty,
+    auto_origin: AutoOriginConfig,
+    /// 等待首段持续静止来锚定原点，期间位置输出固定为零。
+    origin_pending: bool,
+    /// 等待锚定期间本段静止开始的设备时间戳。
+    origin_static_since_ms: Option<DeviceTimeMs>,
+    /// 尚未被取走的原点锚定事件。
+    origin_anchored: Option<OriginAnchored>,
+    /// 气压计高度辅助。
+    baro: BaroAltimeter,
 }
 
 impl Navigator {
@@ -47,15 +78,195 @@ impl Navigator {
             NavigatorImplType::Legacy => NavigatorInner::Legacy(LegacyNavigator::new(config)),
             NavigatorImplType::Eskf => NavigatorInner::Eskf(EskfNavigator::new(config)),
         };
-        Self { inner }
+        Self {
+            inner,
+            position: PositionFusion::new(config.position_source),
+            trajectory: config.trajectory,
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            last_timestamp_ms: None,
+            skipped_integrations: 0,
+            uncertainty: PositionUncertainty::default(),
+            auto_origin: config.auto_origin,
+            origin_pending: config.auto_origin.enabled,
+            origin_static_since_ms: None,
+            origin_anchored: None,
+            baro: BaroAltimeter::new(config.baro),
+        }
     }
 
     /// 更新一帧导航状态。
+    ///
+    /// 与上一帧的间隔超过 [`integration_gap_limit_ms`](Self::integration_gap_limit_ms) 时
+    /// 本帧不积分（重新播种时间基准），并重置 ZUPT 状态，避免过期的静止锁把位置拉回。
     pub fn update(&mut self, attitude: DQuat, sample: &ImuSampleFiltered) -> NavState {
-        match &mut self.inner {
+        if let Some(last) = self.last_timestamp_ms {
+            let gap_ms = sample.timestamp_ms.delta_ms(last);
+            let limit_ms = self.integration_gap_limit_ms();
+            if gap_ms > limit_ms {
+                self.skipped_integrations += 1;
+                tracing::warn!(
+                    gap_ms,
+                    limit_ms,
+                    skipped_integrations = self.skipped_integrations,
+                    "帧间隔过大（主机休眠或长时间断流），跳过本帧积分并重置 ZUPT"
+                );
+                self.reset_timing();
+                self.reset_zupt();
+            }
+        }
+        self.last_timestamp_ms = Some(sample.timestamp_ms);
+        let mut nav = match &mut self.inner {
             NavigatorInner::Legacy(n) => n.update(attitude, sample),
             NavigatorInner::Eskf(n) => n.update(attitude, sample),
+        };
+        if self.origin_pending {
+            if self.track_origin(sample.timestamp_ms) {
+                nav = self.nav_state();
+            } else {
+                nav.position = DVec3::ZERO;
+                nav.velocity = DVec3::ZERO;
+            }
+        }
+        if self.origin_pending || self.is_static() {
+            self.uncertainty.anchor();
+        } else if !self.trajectory.passby {
+            self.uncertainty.propagate(
+                self.trajectory.position_sigma_accel_noise,
+                self.current_dt().as_secs(),
+            );
+        }
+        nav
+    }
+
+    /// 气压计高度辅助：按本帧气压把积分位置的 z 缓慢拉向气压相对高度，x/y 不变。
+    ///
+    /// 在 [`update`](Self::update) 之后调用，`nav` 为其返回值；等待原点锚定期间不修正。
+    pub fn fuse_baro(&mut self, mut nav: NavState, pressure_hpa: Option<f64>) -> NavState {
+        if self.origin_pending {
+            return nav;
+        }
+        let is_static = self.is_static();
+        let dt = self.current_dt().as_secs();
+        let dz = self
+            .baro
+            .update(pressure_hpa, nav.position.z, is_static, dt);
+        if dz != 0.0 {
+            let delta = DVec3::new(0.0, 0.0, dz);
+            match &mut self.inner {
+                NavigatorInner::Legacy(n) => n.shift_position(delta),
+                NavigatorInner::Eskf(n) => n.shift_position(delta),
+            }
+            nav.position += delta;
         }
+        nav
+    }
+
+    /// 最近一帧相对参考的气压高度 (m)，参考未建立或无气压数据时为空。
+    pub fn baro_relative_altitude_m(&self) -> Option<f64> {
+        self.baro.relative_altitude_m()
+    }
+
+    /// 最近一帧气压计施加到 z 的修正量 (m)。
+    pub fn baro_correction_m(&self) -> f64 {
+        self.baro.correction_m()
+    }
+
+    /// 气压参考，尚未建立时为空。
+    pub fn baro_reference(&self) -> Option<BaroReference> {
+        self.baro.reference()
+    }
+
+    /// 积分位置的不确定度 (m)。
+    ///
+    /// Legacy 按双积分白噪声模型估计，静止或手动设置位置时清零；
+    /// ESKF 直接取位置误差协方差的迹开方。
+    pub fn position_sigma_m(&self) -> f64 {
+        match &self.inner {
+            NavigatorInner::Legacy(_) => self.uncertainty.sigma_m(),
+            NavigatorInner::Eskf(n) => {
+                let cov = n.eskf_cov_diag();
+                (cov[6] + cov[7] + cov[8]).max(0.0).sqrt()
+            }
+        }
+    }
+
+    /// 等待原点锚定时统计连续静止时长，满 `static_ms` 后把当前位置设为原点。
+    ///
+    /// 返回本帧是否完成锚定。
+    fn track_origin(&mut self, timestamp_ms: DeviceTimeMs) -> bool {
+        if !self.is_static() {
+            self.origin_static_since_ms = None;
+            return false;
+        }
+        let since = *self.origin_static_since_ms.get_or_insert(timestamp_ms);
+        if (timestamp_ms.delta_ms(since) as f64) < self.auto_origin.static_ms {
+            return false;
+        }
+        self.move_origin(DVec3::ZERO);
+        self.origin_pending = false;
+        self.origin_static_since_ms = None;
+        let timestamp_ms = timestamp_ms.as_millis();
+        self.origin_anchored = Some(OriginAnchored { timestamp_ms });
+        tracing::info!(timestamp_ms, "首段持续静止，世界系原点锚定到当前位置");
+        true
+    }
+
+    /// 重新等待下一段持续静止来锚定原点，不论 `auto_origin.enabled`。
+    ///
+    /// 锚定前位置输出固定为零，之后的积分从锚定处重新开始。
+    pub fn re_anchor_origin(&mut self) {
+        self.origin_pending = true;
+        self.origin_static_since_ms = None;
+    }
+
+    /// 是否仍在等待原点锚定。
+    pub fn is_origin_pending(&self) -> bool {
+        self.origin_pending
+    }
+
+    /// 取走最近一次原点锚定事件。
+    pub fn take_origin_anchored(&mut self) -> Option<OriginAnchored> {
+        self.origin_anchored.take()
+    }
+
+    /// 按配置的位置来源选出本帧输出位置。
+    ///
+    /// `integrated` 为 [`update`](Self::update) 返回的积分位置，`device_nav` 为已经
+    /// 零位校准转到导航系的设备位置；导航器内部状态始终保持本地积分结果。
+    /// 等待原点锚定期间固定输出零，锚定时设备位置原点随之重新对齐。
+    pub fn select_position(&mut self, integrated: DVec3, device_nav: DVec3) -> DVec3 {
+        if self.origin_pending {
+            return DVec3::ZERO;
+        }
+        let is_static = self.is_static();
+        self.position.update(integrated, device_nav, is_static)
+    }
+
+    /// 当前配置的位置来源。
+    pub fn position_source(&self) -> PositionSource {
+        self.position.source()
+    }
+
+    /// 最近一帧的两路候选位置。
+    pub fn position_candidates(&self) -> PositionCandidates {
+        self.position.candidates()
+    }
+
+    /// 下一帧重新对齐设备位置原点（零位校准或设备复位后）。
+    pub fn reanchor_position(&mut self) {
+        self.position.reanchor();
+    }
+
+    /// 最近一帧对外输出的导航状态：位置取 [`select_position`](Self::select_position) 的结果。
+    pub fn output_nav_state(&self) -> NavState {
+        let mut nav = self.nav_state();
+        if self.origin_pending {
+            nav.position = DVec3::ZERO;
+            nav.velocity = DVec3::ZERO;
+        } else if let Some(position) = self.position.output() {
+            nav.position = position;
+        }
+        nav
     }
 
     /// 返回当前是否处于 ZUPT 静止状态。
@@ -75,21 +286,136 @@ impl Navigator {
     }
 
     /// 手动设置位置（用于校正）。
+    ///
+    /// 手动指定的位置即为原点参照，尚未完成的自动原点锚定随之取消。
     pub fn set_position(&mut self, position: DVec3) {
+        if self.origin_pending {
+            tracing::info!("手动设置位置，取消等待中的自动原点锚定");
+        }
+        self.origin_pending = false;
+        self.origin_static_since_ms = None;
+        self.move_origin(position);
+    }
+
+    /// 把当前位置改为 `position`，速度清零，设备位置与不确定度重新锚定。
+    fn move_origin(&mut self, position: DVec3) {
+        self.position.reanchor();
+        self.uncertainty.anchor();
+        // 位置跳变后旧参考的 z 不再可比
+        self.baro.rearm();
         match &mut self.inner {
             NavigatorInner::Legacy(n) => n.set_position(position),
             NavigatorInner::Eskf(n) => n.set_position(position),
         }
     }
 
+    /// 原地更新参数，导航状态保留。实现类型须与当前一致。
+    pub fn reconfigure(&mut self, config: NavigatorConfig) {
+        // 关闭自动锚定时放弃等待；开启只在下次连接或重置时生效
+        if self.auto_origin.enabled && !config.auto_origin.enabled {
+            self.origin_pending = false;
+            self.origin_static_since_ms = None;
+        }
+        self.auto_origin = config.auto_origin;
+        self.trajectory = config.trajectory;
+        self.baro.reconfigure(config.baro);
+        self.position.reconfigure(config.position_source);
+        match &mut self.inner {
+            NavigatorInner::Legacy(n) => n.reconfigure(config),
+            NavigatorInner::Eskf(n) => n.reconfigure(config),
+        }
+    }
+
+    /// 设置标称采样间隔 (ms)，ZUPT 与重力初始化窗口据此换算帧数。
+    pub fn set_sample_interval_ms(&mut self, sample_interval_ms: f64) {
+        self.sample_interval_ms = sample_interval_ms;
+        match &mut self.inner {
+            NavigatorInner::Legacy(n) => n.set_sample_interval_ms(sample_interval_ms),
+            NavigatorInner::Eskf(n) => n.set_sample_interval_ms(sample_interval_ms),
+        }
+    }
+
+    /// 最近一帧导航状态，尚未更新时为初始状态。
+    pub fn nav_state(&self) -> NavState {
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.nav_state(),
+            NavigatorInner::Eskf(n) => n.nav_state(),
+        }
+    }
+
+    /// 设备复位（时间戳回退）后丢弃时间基准，位置/速度/姿态保持。
+    pub fn reset_timing(&mut self) {
+        self.last_timestamp_ms = None;
+        match &mut self.inner {
+            NavigatorInner::Legacy(n) => n.reset_timing(),
+            NavigatorInner::Eskf(n) => n.reset_timing(),
+        }
+    }
+
+    /// 清空 ZUPT 迟滞计数与静止锁定点，下一帧重新检测静止。
+    pub fn reset_zupt(&mut self) {
+        match &mut self.inner {
+            NavigatorInner::Legacy(n) => n.reset_zupt(),
+            NavigatorInner::Eskf(n) => n.reset_zupt(),
+        }
+    }
+
+    /// 跳过积分的帧间隔阈值 (ms)：`max_integration_dt_ms`、`dt_max_ms`
+    /// 与 3 倍标称采样间隔三者取大。
+    pub fn integration_gap_limit_ms(&self) -> u64 {
+        let interval_ms = (self.sample_interval_ms * 3.0).ceil() as u64;
+        self.trajectory
+            .max_integration_dt_ms
+            .max(self.trajectory.dt_max_ms)
+            .max(interval_ms)
+    }
+
+    /// 因帧间隔过大而跳过积分的累计次数。
+    pub fn skipped_integrations(&self) -> u64 {
+        self.skipped_integrations
+    }
+
     /// 重置内部状态。
     pub fn reset(&mut self) {
+        self.position.reset();
+        self.last_timestamp_ms = None;
+        self.skipped_integrations = 0;
+        self.uncertainty.anchor();
+        self.origin_pending = self.auto_origin.enabled;
+        self.origin_static_since_ms = None;
+        self.origin_anchored = None;
+        self.baro.rearm();
         match &mut self.inner {
             NavigatorInner::Legacy(n) => n.reset(),
             NavigatorInner::Eskf(n) => n.reset(),
         }
     }
 
+    /// 当前运动状态，等待原点锚定期间为 `Unknown`。
+    pub fn motion_state(&self) -> MotionState {
+        if self.origin_pending {
+            return MotionState::Unknown;
+        }
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.motion_state(),
+            NavigatorInner::Eskf(n) => n.motion_state(),
+        }
+    }
+
+    /// 最近一帧 ZUPT 检测结果。ESKF 通过量测更新约束位置，没有位置锁。
+    pub fn zupt_state(&self) -> ZuptState {
+        let locked_position = match &self.inner {
+            NavigatorInner::Legacy(n) => n.locked_position(),
+            NavigatorInner::Eskf(_) => None,
+        };
+        ZuptState {
+            is_static: self.is_static(),
+            gyro_norm: self.zupt_gyro_norm(),
+            accel_lin_norm: self.zupt_accel_norm(),
+            locked_position,
+        }
+    }
+
     // —— 诊断访问器 ——
 
     /// 最近一帧 ZUPT 检测的陀螺仪范数 (rad/s)。
@@ -125,7 +451,7 @@ impl Navigator {
     }
 
     /// 当前积分步长 (s)。
-    pub fn current_dt(&self) -> f64 {
+    pub fn current_dt(&self) -> DurationS {
         match &self.inner {
             NavigatorInner::Legacy(n) => n.current_dt(),
             NavigatorInner::Eskf(n) => n.current_dt(),
@@ -140,6 +466,14 @@ impl Navigator {
         }
     }
 
+    /// 最近一帧 ZUPT 修正前的导航状态（积分之后、ZUPT 与平面约束之前）。
+    pub fn pre_zupt_nav_state(&self) -> NavState {
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.pre_zupt_nav_state(),
+            NavigatorInner::Eskf(n) => n.pre_zupt_nav_state(),
+        }
+    }
+
     /// ESKF 协方差对角线。Legacy 模式返回 `None`。
     pub fn eskf_cov_diag(&self) -> Option<[f64; 15]> {
         match &self.inner {
@@ -172,6 +506,49 @@ impl Navigator {
         }
     }
 
+    /// 自动估计的重力模长 (m/s²)，未启用或尚未完成时为 `None`。
+    pub fn gravity_estimate(&self) -> Option<f64> {
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.gravity_estimate(),
+            NavigatorInner::Eskf(n) => n.gravity_estimate(),
+        }
+    }
+
+    /// 世界系重力参考向量 (m/s²)。
+    pub fn gravity_ref(&self) -> DVec3 {
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.gravity_ref(),
+            NavigatorInner::Eskf(n) => n.gravity_ref(),
+        }
+    }
+
+    /// 最近一帧的重力参考与世界系加速度。
+    pub fn debug_vectors(&self) -> DebugVectors {
+        let gravity_ref = self.gravity_ref();
+        let accel_lin = self.last_linear_accel();
+        DebugVectors {
+            gravity_ref,
+            accel_world: accel_lin + gravity_ref,
+            accel_lin,
+        }
+    }
+
+    /// 平面约束本帧去除的沿重力方向速度分量 (m/s)。
+    pub fn plane_removed_velocity(&self) -> f64 {
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.plane_removed_velocity(),
+            NavigatorInner::Eskf(n) => n.plane_removed_velocity(),
+        }
+    }
+
+    /// 死区本帧置零的线加速度模长 (m/s²)，未启用或被旁路时为 0。
+    pub fn deadband_clipped(&self) -> f64 {
+        match &self.inner {
+            NavigatorInner::Legacy(n) => n.deadband_clipped(),
+            NavigatorInner::Eskf(n) => n.deadband_clipped(),
+        }
+    }
+
     /// 后向修正是否在本帧触发。仅 Legacy 模式有效。
     pub fn backward_triggered(&self) -> bool {
         match &self.inner {
@@ -196,19 +573,26 @@ mod tests {
     use crate::processor::{
         filter::ImuSampleFiltered,
         navigator::{
-            types::{IntegratorImpl, ZuptImpl},
-            Navigator, NavigatorConfig, TrajectoryConfig, ZuptConfig,
+            types::{AccelDeadbandMode, IntegratorImpl, NavigatorImplType, ZuptImpl},
+            AutoGravityConfig, AutoOriginConfig, MotionState, Navigator, NavigatorConfig,
+            PlaneConstraintConfig, PlaneConstraintMode, TrajectoryConfig, ZuptConfig,
         },
+        shared::DeviceTimeMs,
     };
 
     /// 构造默认配置的辅助函数（Legacy 模式）。
     fn default_config(gravity: f64) -> NavigatorConfig {
         NavigatorConfig {
             gravity,
+            auto_gravity: Default::default(),
+            plane_constraint: Default::default(),
             trajectory: TrajectoryConfig::default(),
             zupt: ZuptConfig::default(),
             navigator_impl: Default::default(),
             eskf: Default::default(),
+            position_source: Default::default(),
+            auto_origin: Default::default(),
+            baro: Default::default(),
         }
     }
 
@@ -236,24 +620,32 @@ mod tests {
         // 当成重力参考（那会让所有后续帧看起来都处于静止）。
         navigator.set_gravity_reference(DQuat::IDENTITY);
         let moving_0 = ImuSampleFiltered {
-            timestamp_ms: 0,
+            timestamp_ms: DeviceTimeMs(0),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 1.0),
             gyro_lp: DVec3::new(0.0, 0.0, 0.3),
+            jerk: None,
+            ang_accel: None,
         };
         let moving_1 = ImuSampleFiltered {
-            timestamp_ms: 100,
+            timestamp_ms: DeviceTimeMs(100),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 1.0),
             gyro_lp: DVec3::new(0.0, 0.0, 0.3),
+            jerk: None,
+            ang_accel: None,
         };
         let static_0 = ImuSampleFiltered {
-            timestamp_ms: 200,
+            timestamp_ms: DeviceTimeMs(200),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 0.05),
             gyro_lp: DVec3::new(0.01, 0.01, 0.01),
+            jerk: None,
+            ang_accel: None,
         };
         let static_1 = ImuSampleFiltered {
-            timestamp_ms: 300,
+            timestamp_ms: DeviceTimeMs(300),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 0.05),
             gyro_lp: DVec3::new(0.01, 0.01, 0.01),
+            jerk: None,
+            ang_accel: None,
         };
 
         let _ = navigator.update(attitude, &moving_0);
@@ -290,14 +682,18 @@ mod tests {
         let attitude = DQuat::IDENTITY;
         navigator.set_gravity_reference(DQuat::IDENTITY);
         let static_0 = ImuSampleFiltered {
-            timestamp_ms: 0,
+            timestamp_ms: DeviceTimeMs(0),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 0.01),
             gyro_lp: DVec3::new(0.01, 0.01, 0.01),
+            jerk: None,
+            ang_accel: None,
         };
         let static_1 = ImuSampleFiltered {
-            timestamp_ms: 20,
+            timestamp_ms: DeviceTimeMs(20),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 0.01),
             gyro_lp: DVec3::new(0.01, 0.01, 0.01),
+            jerk: None,
+            ang_accel: None,
         };
 
         let _ = navigator.update(attitude, &static_0);
@@ -306,9 +702,11 @@ mod tests {
         navigator.set_position(DVec3::ZERO);
 
         let static_2 = ImuSampleFiltered {
-            timestamp_ms: 40,
+            timestamp_ms: DeviceTimeMs(40),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 0.01),
             gyro_lp: DVec3::new(0.01, 0.01, 0.01),
+            jerk: None,
+            ang_accel: None,
         };
         let nav = navigator.update(attitude, &static_2);
 
@@ -347,14 +745,18 @@ mod tests {
         let accel_static = q_offset.rotate_vec3(DVec3::new(0.0, 0.0, gravity));
 
         let sample_0 = ImuSampleFiltered {
-            timestamp_ms: 0,
+            timestamp_ms: DeviceTimeMs(0),
             accel_lp: accel_static,
             gyro_lp: DVec3::ZERO,
+            jerk: None,
+            ang_accel: None,
         };
         let sample_1 = ImuSampleFiltered {
-            timestamp_ms: 20,
+            timestamp_ms: DeviceTimeMs(20),
             accel_lp: accel_static,
             gyro_lp: DVec3::ZERO,
+            jerk: None,
+            ang_accel: None,
         };
 
         let _ = navigator.update(attitude, &sample_0);
@@ -364,6 +766,203 @@ mod tests {
         assert!(nav.position.length() < 1e-12);
     }
 
+    /// 机体系加速度必须先按姿态旋转到世界系再去重力积分：
+    /// 倾斜 90° 时沿世界 +Y 的线加速度只能积分出世界 +Y 方向的速度。
+    #[test]
+    fn tilted_body_accel_is_rotated_to_world_before_integration() {
+        let gravity = 9.80665;
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let mut navigator = Navigator::new(NavigatorConfig {
+                trajectory: TrajectoryConfig {
+                    passby: false,
+                    dt_max_ms: 1000,
+                    ..TrajectoryConfig::default()
+                },
+                zupt: ZuptConfig {
+                    passby: true,
+                    ..ZuptConfig::default()
+                },
+                navigator_impl,
+                ..default_config(gravity)
+            });
+            navigator.set_gravity_reference(DQuat::IDENTITY);
+
+            let attitude = DQuat::from_rotation_x(std::f64::consts::FRAC_PI_2);
+            let a_lin_world = DVec3::new(0.0, 1.0, 0.0);
+            let accel_body = attitude
+                .inverse()
+                .rotate_vec3(a_lin_world + DVec3::new(0.0, 0.0, gravity));
+
+            let mut nav = None;
+            for timestamp_ms in [0, 100] {
+                nav = Some(navigator.update(
+                    attitude,
+                    &ImuSampleFiltered {
+                        timestamp_ms: DeviceTimeMs(timestamp_ms),
+                        accel_lp: accel_body,
+                        gyro_lp: DVec3::ZERO,
+                        jerk: None,
+                        ang_accel: None,
+                    },
+                ));
+            }
+            let nav = nav.unwrap();
+
+            assert!(
+                (nav.velocity.y - 0.1).abs() < 1e-9,
+                "{navigator_impl:?}: v={:?}",
+                nav.velocity
+            );
+            assert!(nav.velocity.xz().length() < 1e-9);
+        }
+    }
+
+    /// 实测静止模长 9.86 与配置 9.80665 不符：关闭 auto_gravity 时竖直速度持续漂移，
+    /// 开启后首段静止窗口结束即修正，速度只保留窗口期内的小残差。
+    ///
+    /// ESKF 不受 `zupt.passby` 控制，静止时总会硬归零速度，因此改为检查
+    /// 去重力后的原始线加速度残差。
+    #[test]
+    fn auto_gravity_removes_vertical_drift_from_gravity_magnitude_mismatch() {
+        let gravity = 9.80665;
+        let measured = 9.86;
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let mut final_vz = [0.0; 2];
+            let mut final_residual = [0.0; 2];
+            for (i, enabled) in [false, true].into_iter().enumerate() {
+                let mut navigator = Navigator::new(NavigatorConfig {
+                    trajectory: TrajectoryConfig {
+                        passby: false,
+                        ..TrajectoryConfig::default()
+                    },
+                    zupt: ZuptConfig {
+                        passby: true,
+                        ..ZuptConfig::default()
+                    },
+                    auto_gravity: AutoGravityConfig {
+                        enabled,
+                        window_frames: 100,
+                    },
+                    navigator_impl,
+                    ..default_config(gravity)
+                });
+                navigator.set_gravity_reference(DQuat::IDENTITY);
+
+                let mut nav = None;
+                // 10 s @ 250 Hz
+                for k in 0..2500u64 {
+                    nav = Some(navigator.update(
+                        DQuat::IDENTITY,
+                        &ImuSampleFiltered {
+                            timestamp_ms: DeviceTimeMs(k * 4),
+                            accel_lp: DVec3::new(0.0, 0.0, measured),
+                            gyro_lp: DVec3::ZERO,
+                            jerk: None,
+                            ang_accel: None,
+                        },
+                    ));
+                }
+                final_vz[i] = nav.unwrap().velocity.z;
+                final_residual[i] = navigator.zupt_accel_norm();
+
+                if enabled {
+                    let estimate = navigator.gravity_estimate().unwrap();
+                    assert!((estimate - measured).abs() < 1e-9);
+                } else {
+                    assert_eq!(navigator.gravity_estimate(), None);
+                }
+            }
+
+            match navigator_impl {
+                NavigatorImplType::Legacy => {
+                    let [vz_off, vz_on] = final_vz;
+                    assert!(vz_off > 0.4, "vz_off={vz_off}");
+                    assert!(vz_on.abs() < 0.05, "vz_on={vz_on}");
+                }
+                NavigatorImplType::Eskf => {
+                    let [residual_off, residual_on] = final_residual;
+                    assert!((residual_off - (measured - gravity)).abs() < 1e-9);
+                    assert!(residual_on < 1e-9);
+                }
+            }
+        }
+    }
+
+    /// 重力参考倾斜 30° 时，平面约束只去掉沿 gravity_ref 的分量：
+    /// 水平面内的运动与不约束时一致，沿重力方向的漂移被清除。
+    #[test]
+    fn horizontal_plane_constraint_follows_tilted_gravity_reference() {
+        let gravity = 9.80665;
+        let q_offset = DQuat::from_rotation_x(std::f64::consts::FRAC_PI_6);
+        let up = q_offset.rotate_vec3(DVec3::Z);
+        let horizontal = q_offset.rotate_vec3(DVec3::ONE.with_z(0.0));
+        // 水平面内 1.4 m/s² 加速度 + 沿重力方向 0.05 m/s² 漂移
+        let accel = up * (gravity + 0.05) + horizontal;
+
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let run = |mode: PlaneConstraintMode| {
+                let mut navigator = Navigator::new(NavigatorConfig {
+                    trajectory: TrajectoryConfig {
+                        passby: false,
+                        ..TrajectoryConfig::default()
+                    },
+                    zupt: ZuptConfig {
+                        passby: true,
+                        ..ZuptConfig::default()
+                    },
+                    plane_constraint: PlaneConstraintConfig {
+                        mode,
+                        height_m: 0.0,
+                    },
+                    navigator_impl,
+                    ..default_config(gravity)
+                });
+                navigator.set_gravity_reference(q_offset);
+
+                let mut nav = None;
+                for k in 0..100u64 {
+                    nav = Some(navigator.update(
+                        DQuat::IDENTITY,
+                        &ImuSampleFiltered {
+                            timestamp_ms: DeviceTimeMs(k * 20),
+                            accel_lp: accel,
+                            gyro_lp: DVec3::ZERO,
+                            jerk: None,
+                            ang_accel: None,
+                        },
+                    ));
+                }
+                (nav.unwrap(), navigator.plane_removed_velocity())
+            };
+
+            let (free, free_removed) = run(PlaneConstraintMode::None);
+            let (held, held_removed) = run(PlaneConstraintMode::Horizontal);
+
+            assert_eq!(free_removed, 0.0);
+            assert!(free.velocity.dot(up) > 0.05, "{navigator_impl:?}");
+            assert!(free.position.dot(up) > 0.05, "{navigator_impl:?}");
+
+            // 每帧去掉的只是一帧积分出的竖直速度
+            assert!(
+                held_removed > 0.0 && held_removed < 0.01,
+                "{navigator_impl:?}"
+            );
+            assert!(held.velocity.dot(up).abs() < 1e-12, "{navigator_impl:?}");
+            assert!(held.position.dot(up).abs() < 1e-12, "{navigator_impl:?}");
+
+            let free_h = free.velocity - up * free.velocity.dot(up);
+            assert!(
+                (held.velocity - free_h).length() < 1e-9,
+                "{navigator_impl:?}"
+            );
+            let free_p = free.position - up * free.position.dot(up);
+            assert!(
+                (held.position - free_p).length() < 1e-9,
+                "{navigator_impl:?}"
+            );
+        }
+    }
+
     #[test]
     fn rk4_position_differs_from_trapezoid_under_varying_accel() {
         let gravity = 9.80665;
@@ -400,14 +999,18 @@ mod tests {
         nav_trapezoid.set_gravity_reference(DQuat::IDENTITY);
         nav_rk4.set_gravity_reference(DQuat::IDENTITY);
         let s0 = ImuSampleFiltered {
-            timestamp_ms: 0,
+            timestamp_ms: DeviceTimeMs(0),
             accel_lp: DVec3::new(0.0, 0.0, gravity),
             gyro_lp: DVec3::ZERO,
+            jerk: None,
+            ang_accel: None,
         };
         let s1 = ImuSampleFiltered {
-            timestamp_ms: 1000,
+            timestamp_ms: DeviceTimeMs(1000),
             accel_lp: DVec3::new(0.0, 0.0, gravity + 1.0),
             gyro_lp: DVec3::ZERO,
+            jerk: None,
+            ang_accel: None,
         };
 
         let _ = nav_trapezoid.update(attitude, &s0);
@@ -422,4 +1025,460 @@ mod tests {
         assert!((out_rk4.position.z - (1.0 / 6.0)).abs() < 1e-12);
         assert!((out_trapezoid.position.z - out_rk4.position.z).abs() > 1e-6);
     }
+
+    #[test]
+    fn trapezoid_tracks_sinusoidal_accel_far_better_than_euler_at_50hz() {
+        let gravity = 9.80665;
+        // a(t) = A·sin(ωt)，v(0) = 0：v = A/ω·(1 - cos ωt)，p = A/ω·t - A/ω²·sin ωt
+        let amplitude = 2.0;
+        let omega = 2.0 * std::f64::consts::PI * 1.5;
+        let step_ms = 20;
+        let max_error = |integrator: IntegratorImpl| {
+            let mut navigator = Navigator::new(NavigatorConfig {
+                trajectory: TrajectoryConfig {
+                    passby: false,
+                    integrator,
+                    ..TrajectoryConfig::default()
+                },
+                zupt: ZuptConfig {
+                    passby: true,
+                    ..ZuptConfig::default()
+                },
+                ..default_config(gravity)
+            });
+            navigator.set_gravity_reference(DQuat::IDENTITY);
+            let (mut velocity_error, mut position_error) = (0.0f64, 0.0f64);
+            for k in 0..=100u64 {
+                let t = (k * step_ms) as f64 / 1000.0;
+                let nav = navigator.update(
+                    DQuat::IDENTITY,
+                    &ImuSampleFiltered {
+                        timestamp_ms: DeviceTimeMs(k * step_ms),
+                        accel_lp: DVec3::new(amplitude * (omega * t).sin(), 0.0, gravity),
+                        gyro_lp: DVec3::ZERO,
+                        jerk: None,
+                        ang_accel: None,
+                    },
+                );
+                let velocity = amplitude / omega * (1.0 - (omega * t).cos());
+                let position =
+                    amplitude / omega * t - amplitude / omega.powi(2) * (omega * t).sin();
+                velocity_error = velocity_error.max((nav.velocity.x - velocity).abs());
+                position_error = position_error.max((nav.position.x - position).abs());
+            }
+            (velocity_error, position_error)
+        };
+
+        let (euler_v, euler_p) = max_error(IntegratorImpl::LegacyEuler);
+        let (trapezoid_v, trapezoid_p) = max_error(IntegratorImpl::Trapezoid);
+        assert!(
+            euler_v > 5.0 * trapezoid_v,
+            "velocity: euler {euler_v} trapezoid {trapezoid_v}"
+        );
+        assert!(
+            euler_p > 5.0 * trapezoid_p,
+            "position: euler {euler_p} trapezoid {trapezoid_p}"
+        );
+    }
+
+    #[test]
+    fn trapezoid_does_not_average_acceleration_across_a_gap() {
+        let gravity = 9.80665;
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let mut navigator = Navigator::new(NavigatorConfig {
+                trajectory: TrajectoryConfig {
+                    passby: false,
+                    integrator: IntegratorImpl::Trapezoid,
+                    ..TrajectoryConfig::default()
+                },
+                zupt: ZuptConfig {
+                    passby: true,
+                    ..ZuptConfig::default()
+                },
+                navigator_impl,
+                ..default_config(gravity)
+            });
+            navigator.set_gravity_reference(DQuat::IDENTITY);
+            let sample = |timestamp_ms: u64, accel_x: f64| ImuSampleFiltered {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_lp: DVec3::new(accel_x, 0.0, gravity),
+                gyro_lp: DVec3::new(0.0, 0.0, 1.0),
+                jerk: None,
+                ang_accel: None,
+            };
+
+            let mut timestamp_ms = 0;
+            for _ in 0..20 {
+                navigator.update(DQuat::IDENTITY, &sample(timestamp_ms, 3.0));
+                timestamp_ms += 4;
+            }
+            // 间隔之后加速度已消失：首个积分步不应再带上间隔前的 3 m/s²
+            timestamp_ms += 5_000;
+            let after_gap = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms, 0.0));
+            assert_eq!(navigator.skipped_integrations(), 1, "{navigator_impl:?}");
+            let next = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms + 4, 0.0));
+            assert!(
+                (next.velocity - after_gap.velocity).length() < 1e-9,
+                "{navigator_impl:?}: {:?} -> {:?}",
+                after_gap.velocity,
+                next.velocity
+            );
+        }
+    }
+
+    #[test]
+    fn motion_state_follows_static_moving_static_sequence() {
+        let gravity = 9.80665;
+        let mut navigator = Navigator::new(NavigatorConfig {
+            trajectory: TrajectoryConfig {
+                passby: false,
+                dt_max_ms: 1000,
+                ..TrajectoryConfig::default()
+            },
+            zupt: ZuptConfig {
+                passby: false,
+                gyro_thresh: 0.2,
+                accel_thresh: 0.2,
+                impl_type: ZuptImpl::LegacyHardLock,
+                ..ZuptConfig::default()
+            },
+            ..default_config(gravity)
+        });
+        navigator.set_gravity_reference(DQuat::IDENTITY);
+        assert_eq!(navigator.motion_state(), MotionState::Unknown);
+        assert_eq!(navigator.zupt_state().locked_position, None);
+
+        let sample = |timestamp_ms: u64, accel_z: f64, gyro_z: f64| ImuSampleFiltered {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_lp: DVec3::new(0.0, 0.0, gravity + accel_z),
+            gyro_lp: DVec3::new(0.0, 0.0, gyro_z),
+            jerk: None,
+            ang_accel: None,
+        };
+
+        let mut states = Vec::new();
+        for (i, (accel_z, gyro_z)) in [
+            (0.01, 0.01),
+            (0.01, 0.01),
+            (1.0, 0.5),
+            (1.0, 0.5),
+            (0.01, 0.01),
+            (0.01, 0.01),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let _ = navigator.update(DQuat::IDENTITY, &sample(i as u64 * 20, accel_z, gyro_z));
+            let zupt = navigator.zupt_state();
+            assert_eq!(
+                zupt.is_static,
+                zupt.gyro_norm < 0.2 && zupt.accel_lin_norm < 0.2
+            );
+            assert_eq!(zupt.is_static, zupt.locked_position.is_some());
+            states.push(navigator.motion_state());
+        }
+
+        use MotionState::{Moving, Static};
+        assert_eq!(states, vec![Static, Static, Moving, Moving, Static, Static]);
+    }
+
+    #[test]
+    fn long_gap_skips_integration_and_keeps_position_continuous() {
+        let gravity = 9.80665;
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let mut navigator = Navigator::new(NavigatorConfig {
+                trajectory: TrajectoryConfig {
+                    passby: false,
+                    ..TrajectoryConfig::default()
+                },
+                zupt: ZuptConfig {
+                    passby: false,
+                    ..ZuptConfig::default()
+                },
+                navigator_impl,
+                ..default_config(gravity)
+            });
+            navigator.set_gravity_reference(DQuat::IDENTITY);
+
+            // 沿 x 持续加速并转动，保持 Moving
+            let sample = |timestamp_ms: u64| ImuSampleFiltered {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_lp: DVec3::new(2.0, 0.0, gravity),
+                gyro_lp: DVec3::new(0.0, 0.0, 1.0),
+                jerk: None,
+                ang_accel: None,
+            };
+
+            let mut timestamp_ms = 0;
+            let mut before = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms));
+            for _ in 0..50 {
+                timestamp_ms += 4;
+                before = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms));
+            }
+            assert!(before.velocity.x > 0.1, "{navigator_impl:?}");
+            assert_eq!(navigator.skipped_integrations(), 0);
+
+            // 主机休眠 5 s 后恢复：该帧不积分，位置不跳变
+            timestamp_ms += 5_000;
+            let after_gap = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms));
+            assert_eq!(navigator.skipped_integrations(), 1, "{navigator_impl:?}");
+            assert!(
+                (after_gap.position - before.position).length() < 1e-9,
+                "{navigator_impl:?}: {:?} -> {:?}",
+                before.position,
+                after_gap.position
+            );
+            assert_eq!(navigator.zupt_state().locked_position, None);
+
+            // 之后按正常步长继续积分
+            timestamp_ms += 4;
+            let next = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms));
+            let step = (next.position - after_gap.position).length();
+            assert!(step > 0.0 && step < 0.01, "{navigator_impl:?}: step {step}");
+            assert_eq!(navigator.skipped_integrations(), 1);
+
+            navigator.reset();
+            assert_eq!(navigator.skipped_integrations(), 0);
+        }
+    }
+
+    #[test]
+    fn position_sigma_grows_in_motion_and_resets_at_static_entry() {
+        let gravity = 9.80665;
+        let mut navigator = Navigator::new(NavigatorConfig {
+            trajectory: TrajectoryConfig {
+                passby: false,
+                dt_max_ms: 1000,
+                ..TrajectoryConfig::default()
+            },
+            zupt: ZuptConfig {
+                passby: false,
+                gyro_thresh: 0.2,
+                accel_thresh: 0.2,
+                impl_type: ZuptImpl::LegacyHardLock,
+                ..ZuptConfig::default()
+            },
+            ..default_config(gravity)
+        });
+        navigator.set_gravity_reference(DQuat::IDENTITY);
+
+        let sample = |timestamp_ms: u64, moving: bool| ImuSampleFiltered {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_lp: DVec3::new(if moving { 1.0 } else { 0.01 }, 0.0, gravity),
+            gyro_lp: DVec3::new(0.0, 0.0, if moving { 0.5 } else { 0.01 }),
+            jerk: None,
+            ang_accel: None,
+        };
+
+        let mut timestamp_ms = 0;
+        let mut step = |navigator: &mut Navigator, moving: bool| {
+            let _ = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms, moving));
+            timestamp_ms += 4;
+            navigator.position_sigma_m()
+        };
+
+        for _ in 0..10 {
+            assert_eq!(step(&mut navigator, false), 0.0);
+        }
+
+        // 运动期间单调增长
+        let mut last = 0.0;
+        for _ in 0..100 {
+            let sigma = step(&mut navigator, true);
+            assert!(sigma > last, "{sigma} <= {last}");
+            last = sigma;
+        }
+
+        // 进入静止立即清零
+        assert_eq!(step(&mut navigator, false), 0.0);
+        assert_eq!(step(&mut navigator, false), 0.0);
+
+        // 再次运动时从零重新增长
+        let resumed = step(&mut navigator, true);
+        assert!(resumed > 0.0 && resumed < last, "{resumed} vs {last}");
+        assert!(step(&mut navigator, true) > resumed);
+
+        navigator.set_position(DVec3::ZERO);
+        assert_eq!(navigator.position_sigma_m(), 0.0);
+    }
+
+    /// 拿起、放下过程不计入轨迹：首段连续静止满 `static_ms` 才锚定原点，
+    /// 之前位置恒为零、运动状态为 `Unknown`，之后正常积分。
+    #[test]
+    fn auto_origin_anchors_at_first_sustained_static_period() {
+        let gravity = 9.80665;
+        let mut navigator = Navigator::new(NavigatorConfig {
+            trajectory: TrajectoryConfig {
+                passby: false,
+                dt_max_ms: 1000,
+                ..TrajectoryConfig::default()
+            },
+            zupt: ZuptConfig {
+                passby: false,
+                gyro_thresh: 0.2,
+                accel_thresh: 0.2,
+                impl_type: ZuptImpl::LegacyHardLock,
+                ..ZuptConfig::default()
+            },
+            auto_origin: AutoOriginConfig {
+                enabled: true,
+                static_ms: 100.0,
+            },
+            ..default_config(gravity)
+        });
+        navigator.set_gravity_reference(DQuat::IDENTITY);
+
+        let sample = |timestamp_ms: u64, moving: bool| ImuSampleFiltered {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_lp: DVec3::new(if moving { 1.0 } else { 0.01 }, 0.0, gravity),
+            gyro_lp: DVec3::new(0.0, 0.0, if moving { 0.5 } else { 0.01 }),
+            jerk: None,
+            ang_accel: None,
+        };
+
+        let mut timestamp_ms = 0;
+        let mut step = |navigator: &mut Navigator, moving: bool| {
+            let nav = navigator.update(DQuat::IDENTITY, &sample(timestamp_ms, moving));
+            let position = navigator.select_position(nav.position, DVec3::ZERO);
+            timestamp_ms += 10;
+            position
+        };
+
+        // 拿起：运动中位置保持为零
+        for _ in 0..20 {
+            assert_eq!(step(&mut navigator, true), DVec3::ZERO);
+            assert_eq!(navigator.motion_state(), MotionState::Unknown);
+        }
+        // 短暂停顿不足 static_ms，再次运动重新计时
+        for _ in 0..5 {
+            step(&mut navigator, false);
+        }
+        step(&mut navigator, true);
+        assert!(navigator.is_origin_pending());
+
+        // 放下：连续静止 100 ms 后锚定
+        for _ in 0..10 {
+            assert_eq!(step(&mut navigator, false), DVec3::ZERO);
+            assert!(navigator.is_origin_pending());
+        }
+        assert_eq!(step(&mut navigator, false), DVec3::ZERO);
+        assert!(!navigator.is_origin_pending());
+        let anchored = navigator.take_origin_anchored().unwrap();
+        assert_eq!(anchored.timestamp_ms, 360);
+        assert!(navigator.take_origin_anchored().is_none());
+        assert_eq!(navigator.motion_state(), MotionState::Static);
+
+        // 锚定后正常积分
+        let mut position = DVec3::ZERO;
+        for _ in 0..10 {
+            position = step(&mut navigator, true);
+        }
+        assert!(position.x > 0.0, "{position:?}");
+        assert_eq!(navigator.motion_state(), MotionState::Moving);
+
+        // 重新锚定：等待期间位置归零；手动设置位置会取消等待
+        navigator.re_anchor_origin();
+        assert_eq!(step(&mut navigator, true), DVec3::ZERO);
+        navigator.set_position(DVec3::new(1.0, 2.0, 3.0));
+        assert!(!navigator.is_origin_pending());
+        assert_eq!(
+            navigator.output_nav_state().position,
+            DVec3::new(1.0, 2.0, 3.0)
+        );
+        assert!(navigator.take_origin_anchored().is_none());
+    }
+
+    /// 零均值的近静止噪声，任意时刻模长低于 0.05 m/s²。
+    fn small_noise(i: u64) -> DVec3 {
+        let i = i as f64;
+        DVec3::new(
+            0.012 * (0.7 * i).sin(),
+            0.010 * (1.3 * i).sin(),
+            0.020 * (0.37 * i).cos(),
+        )
+    }
+
+    /// 100 Hz 逐帧积分世界系线加速度（姿态为单位四元数），返回最终导航状态。
+    fn integrate_linear_accel(
+        deadband_ms2: f64,
+        mode: AccelDeadbandMode,
+        frames: u64,
+        accel_lin: impl Fn(u64) -> DVec3,
+    ) -> (super::NavState, f64) {
+        let gravity = 9.80665;
+        let mut navigator = Navigator::new(NavigatorConfig {
+            trajectory: TrajectoryConfig {
+                passby: false,
+                accel_deadband_ms2: deadband_ms2,
+                accel_deadband_mode: mode,
+                ..TrajectoryConfig::default()
+            },
+            zupt: ZuptConfig {
+                passby: true,
+                ..ZuptConfig::default()
+            },
+            ..default_config(gravity)
+        });
+        navigator.set_gravity_reference(DQuat::IDENTITY);
+
+        let mut nav = None;
+        let mut max_clipped: f64 = 0.0;
+        for i in 0..frames {
+            nav = Some(navigator.update(
+                DQuat::IDENTITY,
+                &ImuSampleFiltered {
+                    timestamp_ms: DeviceTimeMs(i * 10),
+                    accel_lp: accel_lin(i) + DVec3::new(0.0, 0.0, gravity),
+                    gyro_lp: DVec3::ZERO,
+                    jerk: None,
+                    ang_accel: None,
+                },
+            ));
+            max_clipped = max_clipped.max(navigator.deadband_clipped());
+        }
+        (nav.unwrap(), max_clipped)
+    }
+
+    /// 低于死区的噪声（含 0.015 m/s² 残余偏置）30 s 内不产生位移；关闭死区时漂移数米。
+    #[test]
+    fn accel_deadband_suppresses_creep_from_sub_threshold_noise() {
+        let noise = |i| small_noise(i) + DVec3::new(0.015, 0.0, 0.0);
+
+        let (free, free_clipped) =
+            integrate_linear_accel(0.0, AccelDeadbandMode::Magnitude, 3_000, noise);
+        assert!(free.position.length() > 1.0, "{:?}", free.position);
+        assert_eq!(free_clipped, 0.0);
+
+        for mode in [AccelDeadbandMode::Magnitude, AccelDeadbandMode::PerAxis] {
+            let (nav, clipped) = integrate_linear_accel(0.05, mode, 3_000, noise);
+            assert!(nav.position.length() < 1e-9, "{mode:?}: {:?}", nav.position);
+            assert!(nav.velocity.length() < 1e-9, "{mode:?}: {:?}", nav.velocity);
+            assert!(clipped > 0.0, "{mode:?}");
+        }
+    }
+
+    /// 1 s 近静止后施加 0.2 m/s² 的真实阶跃，2 s 位移与理论值 ½at² 的误差小于 2%。
+    #[test]
+    fn accel_deadband_keeps_genuine_step_accurate() {
+        const STEP_FRAME: u64 = 100;
+        const FRAMES: u64 = 301;
+        let step = |i| {
+            let a = if i >= STEP_FRAME { 0.2 } else { 0.0 };
+            small_noise(i) + DVec3::new(a, 0.0, 0.0)
+        };
+        let t = (FRAMES - 1 - STEP_FRAME) as f64 * 0.01;
+        let expected = 0.5 * 0.2 * t * t;
+
+        for mode in [AccelDeadbandMode::Magnitude, AccelDeadbandMode::PerAxis] {
+            let (nav, _) = integrate_linear_accel(0.05, mode, FRAMES, step);
+            let error = (nav.position.x - expected).abs() / expected;
+            assert!(
+                error < 0.02,
+                "{mode:?}: x={} expected={expected}",
+                nav.position.x
+            );
+            let velocity_error = (nav.velocity.x - 0.2 * t).abs() / (0.2 * t);
+            assert!(velocity_error < 0.02, "{mode:?}");
+        }
+    }
 }
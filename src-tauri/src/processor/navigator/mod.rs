@@ -4,18 +4,33 @@
 //! - `legacy`：传统直接积分 + ZUPT 硬/平滑修正
 //! - `eskf`：15-state 误差状态卡尔曼滤波（推荐）
 
+/// 气压计相对高度辅助竖直位置。
+pub mod baro;
+/// 平面约束（桌面 2D 模式）。
+pub mod constraint;
+/// 线加速度积分死区。
+pub mod deadband;
 /// ESKF（误差状态卡尔曼滤波）导航器。
 pub mod eskf;
+/// 静止期重力模长自动估计。
+pub mod gravity;
 /// 传统导航融合器（直接积分 + ZUPT）。
 pub mod legacy;
 /// 导航融合包装器（enum dispatch）。
 pub mod logic;
+/// 输出位置来源选择（本地积分 / 设备位置 / 融合）。
+pub mod position;
 /// 导航融合配置与类型。
 pub mod types;
+/// 位置不确定度的启发式估计。
+pub mod uncertainty;
 
 /// 导航融合器（根据配置自动选择实现）。
 pub use logic::Navigator;
 /// 导航融合相关类型导出。
 pub use types::{
-    EskfConfig, NavState, NavigatorConfig, NavigatorImplType, TrajectoryConfig, ZuptConfig,
+    AccelDeadbandMode, AutoGravityConfig, AutoOriginConfig, BaroConfig, DebugVectors,
+    DebugVectorsConfig, EskfConfig, MotionState, NavState, NavigatorConfig, NavigatorImplType,
+    OriginAnchored, PlaneConstraintConfig, PlaneConstraintMode, PositionSource,
+    PositionSourceConfig, TrajectoryConfig, ZuptConfig, ZuptState,
 };
@@ -0,0 +1,200 @@
+//! 输出位置来源选择。
+//!
+//! IM948 固件自带惯导引擎，在 `offset` 字段输出设备世界系下的位置估计；某些动作下
+//! 它比本地积分更稳。这里把两路候选放到同一参考系和同一原点下，按配置选择其一或互补融合：
+//! - 设备位置先由管线经零位校准转到导航系（与姿态左乘同一个 `quat_offset`）；
+//! - 重置、手动设位置或零位校准后的首帧把设备位置的原点对齐到本地积分位置，
+//!   两路候选从同一点出发，切换来源不会跳变；
+//! - `blended` 以本地积分的增量外推，每帧按权重向设备位置靠拢；ZUPT 静止时权重取零，
+//!   静止锁定的位置不被设备估计的漂移带走。
+
+use math_f64::DVec3;
+use serde::Serialize;
+
+use crate::processor::navigator::types::{PositionSource, PositionSourceConfig};
+
+/// 两路候选位置（导航系，m）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct PositionCandidates {
+    /// 本地积分位置。
+    pub integrated: DVec3,
+    /// 设备输出位置（已转到导航系并对齐原点）。
+    pub device: DVec3,
+}
+
+/// 位置来源选择与融合状态。
+#[derive(Debug, Clone, Default)]
+pub struct PositionFusion {
+    config: PositionSourceConfig,
+    /// 设备位置在导航系下的原点，为空时下一帧重新对齐。
+    device_origin: Option<DVec3>,
+    /// 上一帧本地积分位置（融合外推用）。
+    last_integrated: Option<DVec3>,
+    /// 上一帧输出位置。
+    output: Option<DVec3>,
+    candidates: PositionCandidates,
+}
+
+impl PositionFusion {
+    /// 创建位置融合状态。
+    pub fn new(config: PositionSourceConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// 原地更新配置；来源变化时从本地积分位置重新开始融合。
+    pub fn reconfigure(&mut self, config: PositionSourceConfig) {
+        if config.mode != self.config.mode {
+            self.output = None;
+            self.last_integrated = None;
+        }
+        self.config = config;
+    }
+
+    /// 当前配置的位置来源。
+    pub fn source(&self) -> PositionSource {
+        self.config.mode
+    }
+
+    /// 输入本帧两路位置，返回输出位置。
+    ///
+    /// * `integrated`: 本地积分位置
+    /// * `device_nav`: 已转到导航系的设备位置（未对齐原点）
+    /// * `is_static`: 本帧 ZUPT 是否判定静止
+    pub fn update(&mut self, integrated: DVec3, device_nav: DVec3, is_static: bool) -> DVec3 {
+        let origin = *self.device_origin.get_or_insert(device_nav - integrated);
+        let device = device_nav - origin;
+        self.candidates = PositionCandidates { integrated, device };
+
+        let output = match self.config.mode {
+            PositionSource::Integrated => integrated,
+            PositionSource::DeviceOffset => device,
+            PositionSource::Blended => {
+                let predicted = match (self.output, self.last_integrated) {
+                    (Some(output), Some(last)) => output + (integrated - last),
+                    _ => integrated,
+                };
+                let weight = if is_static {
+                    0.0
+                } else {
+                    self.config.blend_weight
+                };
+                predicted + (device - predicted) * weight
+            }
+        };
+        self.last_integrated = Some(integrated);
+        self.output = Some(output);
+        output
+    }
+
+    /// 最近一帧的两路候选位置。
+    pub fn candidates(&self) -> PositionCandidates {
+        self.candidates
+    }
+
+    /// 最近一帧输出位置，尚未更新时为空。
+    pub fn output(&self) -> Option<DVec3> {
+        self.output
+    }
+
+    /// 下一帧重新对齐设备位置原点，并从本地积分位置重新开始融合。
+    ///
+    /// 手动设位置、零位校准（导航系旋转）与设备复位（固件位置清零）后调用。
+    pub fn reanchor(&mut self) {
+        self.device_origin = None;
+        self.last_integrated = None;
+        self.output = None;
+    }
+
+    /// 重置全部状态。
+    pub fn reset(&mut self) {
+        self.reanchor();
+        self.candidates = PositionCandidates::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 本地积分沿 x 匀速，设备估计沿 x 快 10% 且带固定原点偏移；第 40~59 帧静止。
+    fn run(mode: PositionSource, blend_weight: f64) -> Vec<(PositionCandidates, DVec3, bool)> {
+        let mut fusion = PositionFusion::new(PositionSourceConfig { mode, blend_weight });
+        let origin = DVec3::new(3.0, -2.0, 1.0);
+        let mut x = 0.0;
+        (0..100)
+            .map(|i| {
+                let is_static = (40..60).contains(&i);
+                if !is_static {
+                    x += 0.01;
+                }
+                let integrated = DVec3::X * x;
+                let device_nav = origin + DVec3::X * (x * 1.1);
+                let output = fusion.update(integrated, device_nav, is_static);
+                (fusion.candidates(), output, is_static)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn candidates_share_origin_and_sources_select_them() {
+        let frames = run(PositionSource::Integrated, 0.5);
+        // 首帧对齐原点，之后设备候选按自身增量前进
+        assert!((frames[0].0.device - frames[0].0.integrated).length() < 1e-12);
+        let last = frames.last().unwrap().0;
+        assert!((last.device.x - (0.01 + (last.integrated.x - 0.01) * 1.1)).abs() < 1e-12);
+        assert!(frames.iter().all(|(c, out, _)| *out == c.integrated));
+
+        let frames = run(PositionSource::DeviceOffset, 0.5);
+        assert!(frames.iter().all(|(c, out, _)| *out == c.device));
+    }
+
+    #[test]
+    fn blend_weight_extremes() {
+        // 权重 0：与本地积分完全一致
+        for (candidates, output, _) in run(PositionSource::Blended, 0.0) {
+            assert!((output - candidates.integrated).length() < 1e-12);
+        }
+
+        // 权重 1：运动中完全跟随设备，静止时保持不动
+        let frames = run(PositionSource::Blended, 1.0);
+        for (candidates, output, is_static) in &frames {
+            if !is_static {
+                assert!((*output - candidates.device).length() < 1e-12);
+            }
+        }
+        for window in frames[40..60].windows(2) {
+            assert_eq!(window[0].1, window[1].1);
+        }
+
+        // 中间权重落在两路之间
+        let (candidates, output, _) = run(PositionSource::Blended, 0.3)[99];
+        assert!(output.x > candidates.integrated.x && output.x < candidates.device.x);
+    }
+
+    #[test]
+    fn reanchor_realigns_device_origin_without_jump() {
+        let mut fusion = PositionFusion::new(PositionSourceConfig {
+            mode: PositionSource::DeviceOffset,
+            blend_weight: 0.0,
+        });
+        fusion.update(DVec3::ZERO, DVec3::new(5.0, 0.0, 0.0), false);
+        assert_eq!(
+            fusion.update(DVec3::X, DVec3::new(6.5, 0.0, 0.0), false),
+            DVec3::new(1.5, 0.0, 0.0)
+        );
+
+        // 设备复位后固件位置清零：重新对齐到当前积分位置
+        fusion.reanchor();
+        assert_eq!(
+            fusion.update(DVec3::X * 2.0, DVec3::ZERO, false),
+            DVec3::X * 2.0
+        );
+        assert_eq!(
+            fusion.update(DVec3::X * 2.0, DVec3::Y, false),
+            DVec3::new(2.0, 1.0, 0.0)
+        );
+    }
+}
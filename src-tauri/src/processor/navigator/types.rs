@@ -3,6 +3,8 @@
 use math_f64::{DQuat, DVec3};
 use serde::{Deserialize, Serialize};
 
+use crate::processor::shared::ConfigValidator;
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 /// 轨迹积分实现。
@@ -16,6 +18,17 @@ pub enum IntegratorImpl {
     Rk4,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+/// 加速度死区的作用方式。
+pub enum AccelDeadbandMode {
+    /// 按线加速度模长判定，低于阈值时整体置零（默认，不改变方向）。
+    #[default]
+    Magnitude,
+    /// 三个轴分别判定，低于阈值的分量置零。
+    PerAxis,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 #[serde(default)]
 /// 轨迹积分配置。
@@ -29,9 +42,22 @@ pub struct TrajectoryConfig {
     pub dt_min_ms: u64,
     /// 最大积分步长（毫秒）。
     pub dt_max_ms: u64,
+    /// 相邻帧间隔超过该值（毫秒）时跳过本帧积分并重置 ZUPT 状态。
+    ///
+    /// 主机休眠唤醒或长时间断流后的首帧间隔可达数秒，钳位后积分仍会让位置瞬移。
+    /// 实际阈值不小于 `dt_max_ms` 与 3 倍标称采样间隔，低上报频率下不会误判。
+    pub max_integration_dt_ms: u64,
     /// 线加速度幅值钳位（m/s²），防止传感器饱和尖峰被积分。0 表示不钳位。
     #[serde(default)]
     pub accel_clamp_ms2: f64,
+    /// 线加速度死区（m/s²），低于该值的分量积分前视为 0，抑制近静止时噪声造成的缓慢漂移。0 表示不启用。
+    pub accel_deadband_ms2: f64,
+    /// 死区按模长还是逐轴判定。
+    pub accel_deadband_mode: AccelDeadbandMode,
+    /// 运动中线加速度模长达到该值（m/s²）时不施加死区，避免扭曲真实动态。
+    pub accel_deadband_bypass_ms2: f64,
+    /// 位置不确定度估计用的加速度噪声谱密度 k_a（m/s²/√Hz），仅 Legacy 模式使用。
+    pub position_sigma_accel_noise: f64,
 }
 
 impl Default for TrajectoryConfig {
@@ -41,11 +67,54 @@ impl Default for TrajectoryConfig {
             integrator: IntegratorImpl::default(),
             dt_min_ms: 1,
             dt_max_ms: 50,
+            max_integration_dt_ms: 50,
             accel_clamp_ms2: 0.0,
+            accel_deadband_ms2: 0.0,
+            accel_deadband_mode: AccelDeadbandMode::default(),
+            accel_deadband_bypass_ms2: 1.0,
+            position_sigma_accel_noise: 0.05,
         }
     }
 }
 
+impl TrajectoryConfig {
+    /// 校验取值范围：`1 <= dt_min_ms <= dt_max_ms <= 1000`，跳过阈值在 [1, 60000] ms，
+    /// 加速度限幅非负，死区在 [0, 1] m/s² 且不超过旁路阈值，位置不确定度噪声在 [0, 10] m/s²/√Hz。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("dt_min_ms", self.dt_min_ms as f64, 1.0, 1000.0);
+        v.in_range("dt_max_ms", self.dt_max_ms as f64, 1.0, 1000.0);
+        v.in_range(
+            "max_integration_dt_ms",
+            self.max_integration_dt_ms as f64,
+            1.0,
+            60_000.0,
+        );
+        v.check(
+            "dt_min_ms",
+            self.dt_min_ms <= self.dt_max_ms,
+            "must not exceed dt_max_ms",
+        );
+        v.non_negative("accel_clamp_ms2", self.accel_clamp_ms2);
+        v.in_range("accel_deadband_ms2", self.accel_deadband_ms2, 0.0, 1.0);
+        v.positive(
+            "accel_deadband_bypass_ms2",
+            self.accel_deadband_bypass_ms2,
+            50.0,
+        );
+        v.check(
+            "accel_deadband_ms2",
+            self.accel_deadband_ms2 <= self.accel_deadband_bypass_ms2,
+            "must not exceed accel_deadband_bypass_ms2",
+        );
+        v.in_range(
+            "position_sigma_accel_noise",
+            self.position_sigma_accel_noise,
+            0.0,
+            10.0,
+        );
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 /// ZUPT 约束实现。
@@ -78,10 +147,10 @@ pub struct ZuptConfig {
     pub gyro_exit_thresh: f64,
     /// 退出静止的线加速度阈值（m/s²）。
     pub accel_exit_thresh: f64,
-    /// 进入静止的连续帧数。
-    pub enter_frames: u32,
-    /// 退出静止的连续帧数。
-    pub exit_frames: u32,
+    /// 进入静止需连续满足阈值的时长（毫秒），按标称采样间隔换算成帧数。
+    pub enter_window_ms: f64,
+    /// 退出静止需连续满足阈值的时长（毫秒），按标称采样间隔换算成帧数。
+    pub exit_window_ms: f64,
     /// 速度衰减时间常数（毫秒）。
     pub vel_decay_tau_ms: f64,
     /// 位置锁定时间常数（毫秒）。
@@ -104,8 +173,8 @@ impl Default for ZuptConfig {
             accel_enter_thresh: 0.22,
             gyro_exit_thresh: 0.2,
             accel_exit_thresh: 0.3,
-            enter_frames: 3,
-            exit_frames: 3,
+            enter_window_ms: 12.0,
+            exit_window_ms: 12.0,
             vel_decay_tau_ms: 70.0,
             pos_lock_tau_ms: 110.0,
             vel_zero_eps: 0.03,
@@ -114,6 +183,117 @@ impl Default for ZuptConfig {
     }
 }
 
+impl ZuptConfig {
+    /// 角速度阈值上限 (rad/s)。
+    const MAX_GYRO_THRESH: f64 = 10.0;
+    /// 线加速度阈值上限 (m/s²)。
+    const MAX_ACCEL_THRESH: f64 = 50.0;
+    /// 判定窗口与时间常数上限 (ms)。
+    const MAX_WINDOW_MS: f64 = 5000.0;
+
+    /// 校验取值范围：阈值为正且不超过上限，进入阈值不高于退出阈值，时间常数为正。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        for (field, value) in [
+            ("gyro_thresh", self.gyro_thresh),
+            ("gyro_enter_thresh", self.gyro_enter_thresh),
+            ("gyro_exit_thresh", self.gyro_exit_thresh),
+        ] {
+            v.positive(field, value, Self::MAX_GYRO_THRESH);
+        }
+        for (field, value) in [
+            ("accel_thresh", self.accel_thresh),
+            ("accel_enter_thresh", self.accel_enter_thresh),
+            ("accel_exit_thresh", self.accel_exit_thresh),
+        ] {
+            v.positive(field, value, Self::MAX_ACCEL_THRESH);
+        }
+        v.check(
+            "gyro_enter_thresh",
+            self.gyro_enter_thresh <= self.gyro_exit_thresh,
+            "must not exceed gyro_exit_thresh",
+        );
+        v.check(
+            "accel_enter_thresh",
+            self.accel_enter_thresh <= self.accel_exit_thresh,
+            "must not exceed accel_exit_thresh",
+        );
+        v.in_range(
+            "enter_window_ms",
+            self.enter_window_ms,
+            0.0,
+            Self::MAX_WINDOW_MS,
+        );
+        v.in_range(
+            "exit_window_ms",
+            self.exit_window_ms,
+            0.0,
+            Self::MAX_WINDOW_MS,
+        );
+        v.positive(
+            "vel_decay_tau_ms",
+            self.vel_decay_tau_ms,
+            Self::MAX_WINDOW_MS,
+        );
+        v.positive("pos_lock_tau_ms", self.pos_lock_tau_ms, Self::MAX_WINDOW_MS);
+        v.in_range("vel_zero_eps", self.vel_zero_eps, 0.0, 1.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 运动状态（ZUPT 静止判定的对外表示）。
+pub enum MotionState {
+    /// 静止，ZUPT 生效。
+    Static,
+    /// 运动。
+    Moving,
+    /// 尚未判定（重力初始化期间或 ZUPT 关闭）。
+    #[default]
+    Unknown,
+}
+
+impl MotionState {
+    /// 由导航器内部的 `Option<bool>` 静止标志转换。
+    pub fn from_static_flag(flag: Option<bool>) -> Self {
+        match flag {
+            Some(true) => Self::Static,
+            Some(false) => Self::Moving,
+            None => Self::Unknown,
+        }
+    }
+
+    /// 录制表 `motion_state` 列的存储值。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Static => "static",
+            Self::Moving => "moving",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// 从录制表存储值解析，未知取值视为 [`Unknown`](Self::Unknown)。
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "static" => Self::Static,
+            "moving" => Self::Moving,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+/// 每帧 ZUPT 检测结果，供前端着色与徽标显示。
+pub struct ZuptState {
+    /// 是否处于静止状态。
+    pub is_static: bool,
+    /// 检测用陀螺仪范数 (rad/s)。
+    pub gyro_norm: f64,
+    /// 检测用线加速度范数 (m/s²)。
+    pub accel_lin_norm: f64,
+    /// 静止锁定的位置（仅 Legacy 导航器有位置锁）。
+    pub locked_position: Option<DVec3>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize)]
 /// 导航状态。
 pub struct NavState {
@@ -202,6 +382,221 @@ impl Default for EskfConfig {
     }
 }
 
+impl EskfConfig {
+    /// 校验取值范围：噪声与初始标准差均为正且不超过 100。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        for (field, value) in [
+            ("gyro_noise", self.gyro_noise),
+            ("accel_noise", self.accel_noise),
+            ("pos_noise", self.pos_noise),
+            ("gyro_bias_walk", self.gyro_bias_walk),
+            ("accel_bias_walk", self.accel_bias_walk),
+            ("zupt_velocity_noise", self.zupt_velocity_noise),
+            ("init_sigma_attitude", self.init_sigma_attitude),
+            ("init_sigma_velocity", self.init_sigma_velocity),
+            ("init_sigma_position", self.init_sigma_position),
+            ("init_sigma_gyro_bias", self.init_sigma_gyro_bias),
+            ("init_sigma_accel_bias", self.init_sigma_accel_bias),
+        ] {
+            v.positive(field, value, 100.0);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+/// 平面约束模式。
+pub enum PlaneConstraintMode {
+    /// 不约束（默认）。
+    #[default]
+    None,
+    /// 水平面约束：沿重力方向的速度清零、位置钳到固定高度。
+    Horizontal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, Default)]
+#[serde(default)]
+/// 平面约束配置（桌面 2D 模式）。
+pub struct PlaneConstraintConfig {
+    /// 约束模式。
+    pub mode: PlaneConstraintMode,
+    /// 约束平面沿重力方向的高度（m）。
+    pub height_m: f64,
+}
+
+impl PlaneConstraintConfig {
+    /// 校验取值范围：高度为有限值。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.finite("height_m", self.height_m);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+/// 输出位置的来源。
+pub enum PositionSource {
+    /// 本地积分（默认）。
+    #[default]
+    Integrated,
+    /// 设备固件输出的位置（`offset` 字段），经零位校准转到导航系。
+    DeviceOffset,
+    /// 本地积分与设备位置的互补融合，静止时只信本地积分。
+    Blended,
+}
+
+impl PositionSource {
+    /// 录制表 `position_source` 列的存储值。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Integrated => "integrated",
+            Self::DeviceOffset => "device_offset",
+            Self::Blended => "blended",
+        }
+    }
+
+    /// 从录制表存储值解析，未知取值视为 [`Integrated`](Self::Integrated)。
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "device_offset" => Self::DeviceOffset,
+            "blended" => Self::Blended,
+            _ => Self::Integrated,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 位置来源配置。
+pub struct PositionSourceConfig {
+    /// 输出位置的来源。
+    pub mode: PositionSource,
+    /// `blended` 模式下每帧向设备位置靠拢的权重，0 = 纯积分，1 = 运动中完全跟随设备。
+    pub blend_weight: f64,
+}
+
+impl Default for PositionSourceConfig {
+    fn default() -> Self {
+        Self {
+            mode: PositionSource::Integrated,
+            blend_weight: 0.05,
+        }
+    }
+}
+
+impl PositionSourceConfig {
+    /// 校验取值范围：融合权重位于 [0, 1]。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("blend_weight", self.blend_weight, 0.0, 1.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 静止期重力模长自动估计配置。
+pub struct AutoGravityConfig {
+    /// 是否启用。启用后校准后的首段持续静止期均值会替代配置的 `gravity` 模长。
+    pub enabled: bool,
+    /// 估计窗口帧数（需连续静止）。
+    pub window_frames: u32,
+}
+
+impl Default for AutoGravityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_frames: 250,
+        }
+    }
+}
+
+impl AutoGravityConfig {
+    /// 校验取值范围：窗口帧数为正。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.check("window_frames", self.window_frames > 0, "must be > 0");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 世界系原点自动锚定配置。
+pub struct AutoOriginConfig {
+    /// 是否启用。启用后连接（或重置）到首段持续静止之间位置固定输出零，
+    /// 静止满 `static_ms` 后以该处为原点开始正常积分。
+    pub enabled: bool,
+    /// 判定为持续静止所需的时长 (ms)。
+    pub static_ms: f64,
+}
+
+impl Default for AutoOriginConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            static_ms: 500.0,
+        }
+    }
+}
+
+impl AutoOriginConfig {
+    /// 校验取值范围：静止时长 0 ~ 10000 ms。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("static_ms", self.static_ms, 0.0, 10_000.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 气压计高度辅助配置。
+pub struct BaroConfig {
+    /// 是否启用。启用后连接时订阅气压计，积分位置的 z 向气压相对高度缓慢收敛。
+    pub enabled: bool,
+    /// 互补滤波增益 (1/s)，时间常数为 `1 / gain` 秒；越大越信任气压计。
+    pub gain: f64,
+}
+
+impl Default for BaroConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gain: 0.2,
+        }
+    }
+}
+
+impl BaroConfig {
+    /// 校验取值范围：增益 (0, 10] 1/s。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.positive("gain", self.gain, 10.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+/// 调试向量输出配置。
+pub struct DebugVectorsConfig {
+    /// 是否在输出帧附带 [`DebugVectors`]；关闭时字段为空，也不做额外计算。
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// 每帧导航调试向量 (m/s²)，供前端绘制重力参考与加速度箭头。
+///
+/// 与输出位置同一坐标系：航向对齐生效后随轨迹一起旋转。
+pub struct DebugVectors {
+    /// 重力参考向量。
+    pub gravity_ref: DVec3,
+    /// 世界系加速度（含重力，已扣除 ESKF 加速度计偏差）。
+    pub accel_world: DVec3,
+    /// 世界系线性加速度，即 `accel_world - gravity_ref`（钳位与死区之前）。
+    pub accel_lin: DVec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+/// `origin_anchored` 事件载荷：世界系原点已锚定到首段持续静止处。
+pub struct OriginAnchored {
+    /// 锚定帧的设备时间戳 (ms)。
+    pub timestamp_ms: u64,
+}
+
 #[derive(Debug, Clone, Copy)]
 /// 导航融合配置。
 pub struct NavigatorConfig {
@@ -211,8 +606,18 @@ pub struct NavigatorConfig {
     pub zupt: ZuptConfig,
     /// 重力加速度（m/s²）。
     pub gravity: f64,
+    /// 重力模长自动估计配置。
+    pub auto_gravity: AutoGravityConfig,
+    /// 平面约束配置。
+    pub plane_constraint: PlaneConstraintConfig,
     /// 导航器实现类型。
     pub navigator_impl: NavigatorImplType,
     /// ESKF 参数配置。
     pub eskf: EskfConfig,
+    /// 位置来源配置。
+    pub position_source: PositionSourceConfig,
+    /// 世界系原点自动锚定配置。
+    pub auto_origin: AutoOriginConfig,
+    /// 气压计高度辅助配置。
+    pub baro: BaroConfig,
 }
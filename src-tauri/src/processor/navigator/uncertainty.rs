@@ -0,0 +1,63 @@
+//! 位置不确定度的启发式估计。
+//!
+//! 把世界系线加速度误差建模为谱密度 `k_a²` 的白噪声，经两次积分得到位置误差方差。
+//! 按离散步长精确传播 `[p, v]` 的 2×2 协方差，结果与采样率无关；
+//! 静止（ZUPT 进入）或手动设置位置时视为重新锚定，方差清零。
+
+/// 双积分白噪声模型下的位置/速度误差协方差（单轴）。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PositionUncertainty {
+    /// 位置方差 (m²)。
+    p_pp: f64,
+    /// 位置-速度协方差 (m²/s)。
+    p_pv: f64,
+    /// 速度方差 (m²/s²)。
+    p_vv: f64,
+}
+
+impl PositionUncertainty {
+    /// 按步长 `dt` (s) 传播一步。
+    ///
+    /// * `accel_noise`: 加速度噪声谱密度 `k_a` (m/s²/√Hz)
+    pub fn propagate(&mut self, accel_noise: f64, dt: f64) {
+        if dt <= 0.0 {
+            return;
+        }
+        let q = accel_noise * accel_noise;
+        let Self { p_pp, p_pv, p_vv } = *self;
+        self.p_pp = p_pp + 2.0 * p_pv * dt + p_vv * dt * dt + q * dt * dt * dt / 3.0;
+        self.p_pv = p_pv + p_vv * dt + q * dt * dt / 2.0;
+        self.p_vv = p_vv + q * dt;
+    }
+
+    /// 重新锚定：位置与速度视为已知，方差清零。
+    pub fn anchor(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 位置标准差 (m)。
+    pub fn sigma_m(&self) -> f64 {
+        self.p_pp.max(0.0).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn growth_is_independent_of_sample_rate() {
+        let mut fast = PositionUncertainty::default();
+        let mut slow = PositionUncertainty::default();
+        for _ in 0..250 {
+            fast.propagate(0.05, 0.004);
+        }
+        for _ in 0..100 {
+            slow.propagate(0.05, 0.01);
+        }
+        // 连续时间解：σ² = k_a²·t³/3
+        let expected = (0.05f64 * 0.05 / 3.0).sqrt();
+        assert!((fast.sigma_m() - expected).abs() < 1e-12);
+        assert!((slow.sigma_m() - expected).abs() < 1e-12);
+    }
+}
@@ -3,7 +3,7 @@
 use math_f64::DVec3;
 
 use crate::processor::output::types::OutputFrame;
-use crate::types::outputs::ResponseData;
+use crate::types::outputs::{ResponseData, RESPONSE_SCHEMA_VERSION};
 
 /// IMU 加速度量程饱和检测阈值（m/s²）。
 ///
@@ -25,9 +25,12 @@ pub struct OutputBuilder;
 
 impl OutputBuilder {
     /// 从输出帧构建前端响应数据。
+    ///
+    /// 调试向量原样透传：是否附带由处理管线按 `debug_vectors.enabled` 决定。
     pub fn build(frame: &OutputFrame) -> ResponseData {
         ResponseData {
-            timestamp_ms: frame.raw.timestamp_ms,
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms: frame.raw.timestamp_ms.as_millis(),
             accel: frame.raw.accel_no_g,
             accel_with_g: frame.raw.accel_with_g,
             gyro: frame.raw.gyro,
@@ -35,6 +38,16 @@ impl OutputBuilder {
             velocity: frame.nav.velocity,
             position: frame.nav.position,
             accel_saturated: is_accel_saturated(frame.raw.accel_with_g),
+            motion_state: frame.motion_state,
+            zupt: frame.zupt,
+            position_source: frame.position_source,
+            jerk: frame.jerk,
+            ang_accel: frame.ang_accel,
+            position_sigma_m: frame.position_sigma_m,
+            host_time_estimate_ms: frame
+                .clock_sync
+                .map(|sync| sync.host_time_ms(frame.raw.timestamp_ms)),
+            debug_vectors: frame.debug_vectors,
         }
     }
 }
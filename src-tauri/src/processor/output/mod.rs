@@ -16,4 +16,4 @@ pub use logic::ACCEL_SATURATION_THRESHOLD_MS2;
 /// 饱和检测 helper。
 pub use logic::is_accel_saturated;
 /// 输出帧类型导出。
-pub use types::OutputFrame;
+pub use types::{OutputFrame, StageCapture, StageCaptureHandle, StageSnapshot};
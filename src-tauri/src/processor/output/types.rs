@@ -1,6 +1,15 @@
 //! 输出相关类型。
 
-use crate::processor::navigator::NavState;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use math_f64::DVec3;
+use serde::Serialize;
+
+use crate::processor::clock_sync::ClockSyncEstimate;
+use crate::processor::navigator::{DebugVectors, MotionState, NavState, PositionSource, ZuptState};
 use crate::processor::parser::ImuSampleRaw;
 
 #[derive(Debug, Clone, Copy)]
@@ -10,4 +19,75 @@ pub struct OutputFrame {
     pub raw: ImuSampleRaw,
     /// 导航状态。
     pub nav: NavState,
+    /// 运动状态。
+    pub motion_state: MotionState,
+    /// ZUPT 检测结果。
+    pub zupt: ZuptState,
+    /// 导航状态中位置的来源。
+    pub position_source: PositionSource,
+    /// 加加速度（m/s³，派生信号）。
+    pub jerk: Option<DVec3>,
+    /// 角加速度（rad/s²，派生信号）。
+    pub ang_accel: Option<DVec3>,
+    /// 位置不确定度（m，1σ）。
+    pub position_sigma_m: f64,
+    /// 当前设备/主机时钟同步结果，锚点不足时为空。
+    pub clock_sync: Option<ClockSyncEstimate>,
+    /// 施加到导航输出的航向对齐旋转 (°)，未对齐时为空。
+    pub heading_yaw_deg: Option<f64>,
+    /// 气压计换算的相对高度 (m)，未启用或参考未建立时为空。
+    pub baro_relative_altitude_m: Option<f64>,
+    /// 重力参考与世界系加速度，`debug_vectors.enabled` 关闭时为空；不写入录制。
+    pub debug_vectors: Option<DebugVectors>,
+    /// 各阶段中间值，[`StageCapture`] 关闭时为空；深度录制写入 `imu_stage_samples`。
+    pub stages: Option<StageSnapshot>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+/// 一帧在各处理阶段的中间值，供事后排查漂移。
+pub struct StageSnapshot {
+    /// 标定后（并按配置选出加速度来源）的加速度 (m/s²)。
+    pub calibrated_accel: DVec3,
+    /// 标定后的角速度 (rad/s)。
+    pub calibrated_gyro: DVec3,
+    /// 低通滤波后的加速度 (m/s²)。
+    pub filtered_accel: DVec3,
+    /// 低通滤波后的角速度 (rad/s)。
+    pub filtered_gyro: DVec3,
+    /// 积分用的世界系线加速度 (m/s²)。
+    pub linear_accel: DVec3,
+    /// ZUPT 修正前的导航状态（本帧积分结果）。
+    pub nav_pre_zupt: NavState,
+    /// ZUPT 与平面约束之后、气压计融合与位置来源选择之前的导航状态。
+    pub nav_post_zupt: NavState,
+    /// 本帧是否判定为静止。
+    pub is_static: bool,
+}
+
+/// 阶段中间值采集开关，由录制线程在深度录制期间打开。
+///
+/// 管线与录制线程共用一个句柄；关闭时管线不组装 [`StageSnapshot`]。
+#[derive(Debug, Default)]
+pub struct StageCapture {
+    enabled: AtomicBool,
+}
+
+/// 共享的采集开关句柄。
+pub type StageCaptureHandle = Arc<StageCapture>;
+
+impl StageCapture {
+    /// 创建关闭状态的共享句柄。
+    pub fn new_handle() -> StageCaptureHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 是否采集。
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 打开或关闭采集。
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
 }
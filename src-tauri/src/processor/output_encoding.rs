@@ -0,0 +1,364 @@
+//! 输出订阅的 CBOR 编码（`encoding: "cbor"`）。
+//!
+//! JSON 序列化占了分发开销的大头。CBOR 订阅的消息以字节数组发出，解码后与 JSON 订阅
+//! 的消息结构完全相同（字段名、嵌套与 `null` 一致），前端只多一步解码。
+//!
+//! 编码布局：
+//! - 完整帧：定长 map，依次为 `seq`、`skipped`、`snapshot`、`backfill`，随后是
+//!   [`ResponseData`] 按声明顺序的全部字段（与 JSON 一样平铺）
+//! - 心跳：[`StreamHeartbeat`] 按声明顺序编码的定长 map
+//! - 攒批：只有 `output_batch` 一个键的 map，值为上述消息组成的定长数组
+//!
+//! 浮点数按不丢精度的最短宽度写入（f16 / f32 / f64），`Option` 为空时写 `null`。
+//! 帧数据部分每帧只编码一次（[`CborFrameData`]），所有 CBOR 订阅共享同一份字节，
+//! 各订阅只另写自己的消息头。字段顺序由黄金字节测试固定，改动 [`ResponseData`]
+//! 时须同时更新测试与前端解码。
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::types::outputs::{ResponseData, StreamMessage};
+
+/// CBOR 主类型：数组。
+const MAJOR_ARRAY: u8 = 4;
+/// CBOR 主类型：map。
+const MAJOR_MAP: u8 = 5;
+/// 攒批消息的键。
+const BATCH_KEY: &str = "output_batch";
+
+/// 一帧 [`ResponseData`] 的 CBOR 编码（去掉 map 头的键值对），在订阅间共享。
+#[derive(Debug, Clone)]
+pub struct CborFrameData {
+    /// 键值对数。
+    fields: u64,
+    /// 依次排列的键值对。
+    body: Arc<[u8]>,
+}
+
+impl CborFrameData {
+    /// 编码一帧数据。
+    pub fn encode(data: &ResponseData) -> Self {
+        let encoded = to_cbor(data);
+        let (fields, body) = split_map_header(&encoded);
+        Self {
+            fields,
+            body: body.into(),
+        }
+    }
+
+    /// 是否与 `other` 共享同一份编码字节。
+    pub fn shares_bytes_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.body, &other.body)
+    }
+}
+
+/// 分发给前端订阅的一条消息；CBOR 订阅的完整帧附带共享的帧数据编码。
+#[derive(Debug, Clone)]
+pub struct OutputMessage {
+    /// 消息本身（`skipped` 已按订阅改写）。
+    pub message: StreamMessage,
+    /// 帧数据的 CBOR 编码，JSON 订阅、心跳与回放消息为空，编码时按需补上。
+    pub cbor: Option<CborFrameData>,
+}
+
+impl From<StreamMessage> for OutputMessage {
+    fn from(message: StreamMessage) -> Self {
+        Self {
+            message,
+            cbor: None,
+        }
+    }
+}
+
+/// 编码单条消息。
+pub fn encode_message(message: &OutputMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_message(&mut out, message);
+    out
+}
+
+/// 编码一批消息（`{ output_batch: [...] }`）。
+pub fn encode_batch(messages: &[OutputMessage]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_header(&mut out, MAJOR_MAP, 1);
+    write_value(&mut out, BATCH_KEY);
+    write_header(&mut out, MAJOR_ARRAY, messages.len() as u64);
+    for message in messages {
+        write_message(&mut out, message);
+    }
+    out
+}
+
+fn write_message(out: &mut Vec<u8>, message: &OutputMessage) {
+    let StreamMessage::Frame(frame) = &message.message else {
+        write_value(out, &message.message);
+        return;
+    };
+    let encoded;
+    let data = match &message.cbor {
+        Some(data) => data,
+        None => {
+            encoded = CborFrameData::encode(&frame.data);
+            &encoded
+        }
+    };
+    write_header(out, MAJOR_MAP, 4 + data.fields);
+    write_value(out, "seq");
+    write_value(out, &frame.seq);
+    write_value(out, "skipped");
+    write_value(out, &frame.skipped);
+    write_value(out, "snapshot");
+    write_value(out, &frame.snapshot);
+    write_value(out, "backfill");
+    write_value(out, &frame.backfill);
+    out.extend_from_slice(&data.body);
+}
+
+fn to_cbor<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn write_value<T: Serialize + ?Sized>(out: &mut Vec<u8>, value: &T) {
+    // 写入内存缓冲不会失败，输出结构也都是定长的
+    ciborium::into_writer(value, out).expect("CBOR 编码输出消息失败");
+}
+
+/// 写入定长数组或 map 的头部。
+fn write_header(out: &mut Vec<u8>, major: u8, len: u64) {
+    let major = major << 5;
+    match len {
+        0..=23 => out.push(major | len as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, len as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&len.to_be_bytes());
+        }
+    }
+}
+
+/// 拆出定长 map 的键值对数与其后的内容。
+fn split_map_header(encoded: &[u8]) -> (u64, &[u8]) {
+    let (&initial, rest) = encoded.split_first().expect("CBOR 编码为空");
+    assert_eq!(initial >> 5, MAJOR_MAP, "帧数据应编码为 map");
+    let extra = match initial & 0x1f {
+        len @ 0..=23 => return (u64::from(len), rest),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => panic!("帧数据应编码为定长 map"),
+    };
+    let (len, body) = rest.split_at(extra);
+    let len = len
+        .iter()
+        .fold(0u64, |acc, &byte| (acc << 8) | u64::from(byte));
+    (len, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::{
+        processor::navigator::{MotionState, PositionSource, ZuptState},
+        types::outputs::{OutputEvent, StreamFrame, StreamHeartbeat, RESPONSE_SCHEMA_VERSION},
+    };
+
+    fn data(timestamp_ms: u64) -> ResponseData {
+        let t = timestamp_ms as f64 * 1e-3;
+        ResponseData {
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms,
+            accel: DVec3::new(0.12 * t.sin(), -0.03, 0.5 * t.cos()),
+            accel_with_g: DVec3::new(0.12 * t.sin(), -0.03, 9.80665 + 0.5 * t.cos()),
+            gyro: DVec3::new(1.5 * t.cos(), 0.25, -3.0 * t.sin()),
+            attitude: DQuat::from_rotation_z(t),
+            velocity: DVec3::new(0.2 * t, 0.0, -0.01 * t),
+            position: DVec3::new(0.1 * t * t, 0.0, 0.003 * t),
+            accel_saturated: false,
+            motion_state: MotionState::Moving,
+            zupt: ZuptState::default(),
+            position_source: PositionSource::default(),
+            jerk: Some(DVec3::new(0.5, -0.25, 1.0 / 3.0)),
+            ang_accel: None,
+            position_sigma_m: 0.02 * t,
+            host_time_estimate_ms: Some(1.7e12 + t * 1e3),
+            debug_vectors: None,
+        }
+    }
+
+    fn frame(seq: u64, skipped: u64) -> StreamMessage {
+        StreamMessage::Frame(StreamFrame {
+            seq,
+            skipped,
+            snapshot: false,
+            backfill: seq.is_multiple_of(2),
+            data: data(1_000 + seq * 4),
+        })
+    }
+
+    fn heartbeat(seq: u64) -> StreamMessage {
+        StreamMessage::Heartbeat(StreamHeartbeat {
+            seq,
+            timestamp_ms: 1_000 + seq * 4,
+            unchanged: true,
+            skipped: 3,
+            backfill: false,
+        })
+    }
+
+    fn decode(bytes: &[u8]) -> serde_json::Value {
+        ciborium::from_reader(bytes).unwrap()
+    }
+
+    #[test]
+    fn decodes_to_the_same_document_as_json() {
+        let messages = [frame(7, 0), heartbeat(8), frame(12, 3)];
+        for message in messages {
+            let expected = serde_json::to_value(OutputEvent::Message(message)).unwrap();
+            assert_eq!(decode(&encode_message(&message.into())), expected);
+        }
+
+        // 共享的帧数据编码与按需编码结果一致
+        let StreamMessage::Frame(shared) = frame(5, 0) else {
+            unreachable!()
+        };
+        let with_cache = OutputMessage {
+            message: frame(5, 2),
+            cbor: Some(CborFrameData::encode(&shared.data)),
+        };
+        assert_eq!(
+            encode_message(&with_cache),
+            encode_message(&frame(5, 2).into())
+        );
+
+        let batch: Vec<OutputMessage> = messages.iter().map(|&m| m.into()).collect();
+        let expected = serde_json::to_value(OutputEvent::Batch {
+            output_batch: messages.to_vec(),
+        })
+        .unwrap();
+        assert_eq!(decode(&encode_batch(&batch)), expected);
+        assert_eq!(
+            decode(&encode_batch(&[])),
+            serde_json::json!({ "output_batch": [] })
+        );
+    }
+
+    #[test]
+    fn header_lengths_round_trip() {
+        for len in [0, 23, 24, 255, 256, 65_535, 65_536, 1 << 32] {
+            let mut out = Vec::new();
+            write_header(&mut out, MAJOR_MAP, len);
+            assert_eq!(split_map_header(&out), (len, &[][..]), "{len}");
+        }
+    }
+
+    /// 字段顺序与编码宽度的黄金字节：改动 `ResponseData` 或消息头时此处会失败，
+    /// 须同步更新前端解码与文档。
+    #[test]
+    fn frame_layout_matches_golden_bytes() {
+        let message = StreamMessage::Frame(StreamFrame {
+            seq: 1,
+            skipped: 0,
+            snapshot: true,
+            backfill: false,
+            data: ResponseData {
+                schema_version: RESPONSE_SCHEMA_VERSION,
+                timestamp_ms: 1_000,
+                accel: DVec3::ZERO,
+                accel_with_g: DVec3::new(0.0, 0.0, 9.75),
+                gyro: DVec3::ZERO,
+                attitude: DQuat::IDENTITY,
+                velocity: DVec3::ZERO,
+                position: DVec3::new(1.5, 0.0, 0.0),
+                accel_saturated: false,
+                motion_state: MotionState::Static,
+                zupt: ZuptState::default(),
+                position_source: PositionSource::default(),
+                jerk: None,
+                ang_accel: None,
+                position_sigma_m: 0.0,
+                host_time_estimate_ms: None,
+                debug_vectors: None,
+            },
+        });
+        let hex: String = encode_message(&message.into())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        assert_eq!(hex, GOLDEN_FRAME);
+    }
+
+    const GOLDEN_FRAME: &str = concat!(
+        "b5637365710167736b69707065640068736e617073686f74f5686261636b6669",
+        "6c6cf46e736368656d615f76657273696f6e036c74696d657374616d705f6d73",
+        "1903e865616363656ca36178f900006179f90000617af900006c616363656c5f",
+        "776974685f67a36178f900006179f90000617af948e0646779726fa36178f900",
+        "006179f90000617af90000686174746974756465a46178f900006179f9000061",
+        "7af900006177f93c006876656c6f63697479a36178f900006179f90000617af9",
+        "000068706f736974696f6ea36178f93e006179f90000617af900006f61636365",
+        "6c5f736174757261746564f46c6d6f74696f6e5f737461746566737461746963",
+        "647a757074a46969735f737461746963f4696779726f5f6e6f726df900006e61",
+        "6363656c5f6c696e5f6e6f726df900006f6c6f636b65645f706f736974696f6e",
+        "f66f706f736974696f6e5f736f757263656a696e7465677261746564646a6572",
+        "6bf669616e675f616363656cf670706f736974696f6e5f7369676d615f6df900",
+        "0075686f73745f74696d655f657374696d6174655f6d73f66d64656275675f76",
+        "6563746f7273f6",
+    );
+
+    /// 1000 帧：CBOR 比 JSON 更小、更快（JSON 与原先通道发送时一样逐条 `serde_json` 序列化）。
+    ///
+    /// 各取 5 轮中最快的一轮，减少并行测试带来的抖动。
+    #[test]
+    fn cbor_is_smaller_and_faster_than_json() {
+        let messages: Vec<StreamMessage> = (0..1_000).map(|seq| frame(seq, 0)).collect();
+        let json = || -> usize {
+            messages
+                .iter()
+                .map(|&m| serde_json::to_vec(&OutputEvent::Message(m)).unwrap().len())
+                .sum()
+        };
+        let cbor = || -> usize {
+            messages
+                .iter()
+                .map(|&m| encode_message(&m.into()).len())
+                .sum()
+        };
+        let (json_bytes, json_elapsed) = best_of(5, json);
+        let (cbor_bytes, cbor_elapsed) = best_of(5, cbor);
+
+        assert!(
+            cbor_bytes < json_bytes,
+            "cbor {cbor_bytes} B, json {json_bytes} B"
+        );
+        assert!(
+            cbor_elapsed < json_elapsed,
+            "cbor {cbor_elapsed:?}, json {json_elapsed:?}"
+        );
+    }
+
+    fn best_of(rounds: usize, mut run: impl FnMut() -> usize) -> (usize, Duration) {
+        (0..rounds)
+            .map(|_| {
+                let started = Instant::now();
+                let bytes = run();
+                (bytes, started.elapsed())
+            })
+            .min_by_key(|&(_, elapsed)| elapsed)
+            .unwrap()
+    }
+}
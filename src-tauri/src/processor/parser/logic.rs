@@ -1,9 +1,11 @@
 //! 原始蓝牙数据包解析逻辑。
 
-use anyhow::bail;
 use math_f64::{DQuat, DVec3};
 
-use crate::processor::parser::types::ImuSampleRaw;
+use crate::processor::{
+    parser::types::{BaroSample, ImuSampleRaw, ParseError},
+    shared::{wrap_degrees, DeviceTimeMs},
+};
 
 // ===============================
 // IMU解析器
@@ -18,6 +20,29 @@ impl ImuParser {
     const SCALE_ANGLE: f64 = 0.0054931640625; // 角度 [-180~+180] 180/32768
     const SCALE_ANGLE_SPEED: f64 = 0.06103515625; // 角速度 [-2000~+2000] 2000/32768
     const SCALE_OFFSET: f64 = 1.0 / 1000.0; // 偏移量，m
+    const SCALE_TEMPERATURE: f64 = 0.01; // 温度，℃
+    const SCALE_PRESSURE: f64 = 0.0002384185791; // 气压，hPa
+    const SCALE_ALTITUDE: f64 = 0.0010728836; // 高度，m
+
+    /// 功能数据帧头。
+    pub const FRAME_HEADER: u8 = 0x11;
+    /// 订阅全部字段（含气压计）时一帧的长度。
+    pub const MAX_FRAME_LEN: usize = 59;
+    /// 帧头 + 控制位 + 时间戳的长度。
+    const HEADER_LEN: usize = 7;
+    /// 气压计控制位：温度 S16 + 气压 S24 + 高度 S24，共 8 字节。
+    const BARO_BIT: u16 = 0x0010;
+    /// 各控制位对应字段的字节数；磁场（bit 3）不订阅，不计入。
+    const FIELD_LENGTHS: [(u16, usize); 8] = [
+        (0x0001, 6),
+        (0x0002, 6),
+        (0x0004, 6),
+        (Self::BARO_BIT, 8),
+        (0x0020, 8),
+        (0x0040, 6),
+        (0x0080, 6),
+        (0x0200, 6),
+    ];
 
     /// 从小端字节读取一个有符号 16 位整数
     fn read_i16(buf: &[u8]) -> i16 {
@@ -25,6 +50,12 @@ impl ImuParser {
         i16::from_le_bytes([buf[0], buf[1]])
     }
 
+    /// 从小端字节读取一个有符号 24 位整数
+    fn read_i24(buf: &[u8]) -> i32 {
+        // 放进 i32 的高 24 位再算术右移，完成符号扩展
+        i32::from_le_bytes([0, buf[0], buf[1], buf[2]]) >> 8
+    }
+
     /// 连续读取三个 i16，并按比例系数转换为 Vector3
     fn read_vec3(buf: &[u8], scale: f64) -> DVec3 {
         let x = Self::read_i16(&buf[0..2]) as f64 * scale;
@@ -33,6 +64,17 @@ impl ImuParser {
         DVec3 { x, y, z }
     }
 
+    /// 字段 `[start_l, start_l + len)` 超出缓冲区时返回 [`ParseError::TooShort`]。
+    fn ensure_len(buf: &[u8], start_l: usize, len: usize) -> Result<(), ParseError> {
+        if start_l + len > buf.len() {
+            return Err(ParseError::TooShort {
+                len: buf.len(),
+                expected: start_l + len,
+            });
+        }
+        Ok(())
+    }
+
     /// 尝试解析 DVec3 字段，如果控制位未设置，则返回错误。
     /// 返回 (解析后的 DVec3, 下一个起始索引)
     fn try_parse_vec3(
@@ -41,20 +83,15 @@ impl ImuParser {
         bit_mask: u16,
         start_l: usize,
         scale: f64,
-    ) -> anyhow::Result<(DVec3, usize)> {
+    ) -> Result<(DVec3, usize), ParseError> {
         if (ctl & bit_mask) != 0 {
             const LEN: usize = 6;
-            if start_l + LEN > buf.len() {
-                bail!(
-                    "data buffer not long enough for Vec3 field (bit {})",
-                    bit_mask
-                )
-            }
+            Self::ensure_len(buf, start_l, LEN)?;
             // 解析值并推进索引
             let vec = Self::read_vec3(&buf[start_l..], scale);
             Ok((vec, start_l + LEN))
         } else {
-            bail!("数据包没有设置指定控制位, 期望控制位为 : {}", bit_mask)
+            Err(ParseError::MissingField { ctl, bit: bit_mask })
         }
     }
 
@@ -65,12 +102,10 @@ impl ImuParser {
         ctl: u16,
         bit_mask: u16,
         start_l: usize,
-    ) -> anyhow::Result<(DQuat, usize)> {
+    ) -> Result<(DQuat, usize), ParseError> {
         if (ctl & bit_mask) != 0 {
             const LEN: usize = 8;
-            if start_l + LEN > buf.len() {
-                bail!("data buffer not long enough for quat (bit {})", bit_mask)
-            }
+            Self::ensure_len(buf, start_l, LEN)?;
             // 按比例系数还原四元数分量
             let w = Self::read_i16(&buf[start_l..]) as f64 * Self::SCALE_QUAT;
             let x = Self::read_i16(&buf[start_l + 2..]) as f64 * Self::SCALE_QUAT;
@@ -79,22 +114,56 @@ impl ImuParser {
             let quat = DQuat { w, x, y, z };
             Ok((quat, start_l + LEN))
         } else {
-            bail!("数据包没有设置指定控制位, 期望控制位为 : {}", bit_mask)
+            Err(ParseError::MissingField { ctl, bit: bit_mask })
+        }
+    }
+
+    /// 解析可选的气压计数据块，控制位未设置时为空。
+    /// 返回 (解析后的气压计数据, 下一个起始索引)
+    fn try_parse_baro(
+        buf: &[u8],
+        ctl: u16,
+        start_l: usize,
+    ) -> Result<(Option<BaroSample>, usize), ParseError> {
+        if (ctl & Self::BARO_BIT) == 0 {
+            return Ok((None, start_l));
         }
+        const LEN: usize = 8;
+        Self::ensure_len(buf, start_l, LEN)?;
+        let baro = BaroSample {
+            temperature_c: Self::read_i16(&buf[start_l..]) as f64 * Self::SCALE_TEMPERATURE,
+            pressure_hpa: Self::read_i24(&buf[start_l + 2..]) as f64 * Self::SCALE_PRESSURE,
+            altitude_m: Self::read_i24(&buf[start_l + 5..]) as f64 * Self::SCALE_ALTITUDE,
+        };
+        Ok((Some(baro), start_l + LEN))
+    }
+
+    /// 检查帧头与长度，不解码字段。
+    ///
+    /// 不是 0x11 帧时为 [`ParseError::BadHeader`]；字节数不足帧头控制位声明的一帧
+    /// （不足 3 字节时按帧头长度计）时为 [`ParseError::TooShort`]。
+    /// [`parse`](Self::parse) 先做同样的检查。
+    pub fn check_frame(buf: &[u8]) -> Result<(), ParseError> {
+        if buf.first() != Some(&Self::FRAME_HEADER) {
+            return Err(ParseError::BadHeader);
+        }
+        let expected = Self::frame_len(buf).unwrap_or(Self::HEADER_LEN);
+        if buf.len() < expected {
+            return Err(ParseError::TooShort {
+                len: buf.len(),
+                expected,
+            });
+        }
+        Ok(())
     }
 
     /// 解析订阅的功能数据 (数据体第一个字节为0x11)
     ///
     /// * `buf`: 蓝牙数据包
     /// * 返回: 解析后的原始样本
-    pub fn parse(buf: &[u8]) -> anyhow::Result<ImuSampleRaw> {
-        // 头部检查
-        if buf.is_empty() || buf[0] != 0x11 {
-            bail!("[error] data head not defined")
-        }
-        if buf.len() < 7 {
-            bail!("[error] buffer too short, expected at least 7 bytes")
-        }
+    pub fn parse(buf: &[u8]) -> Result<ImuSampleRaw, ParseError> {
+        // 帧头与长度检查
+        Self::check_frame(buf)?;
 
         let ctl = ((buf[2] as u16) << 8) | (buf[1] as u16); // 前两个直接功能订阅标识
 
@@ -115,13 +184,18 @@ impl ImuParser {
         // (bit 2)
         let (gyro, l3) = Self::try_parse_vec3(buf, ctl, 0x0004, l2, Self::SCALE_ANGLE_SPEED)?;
 
-        // bit3 磁场, bit4 气压计不订阅
+        // bit3 磁场不订阅
+
+        // (bit 4) 气压计按配置订阅，可缺省
+        let (baro, l3) = Self::try_parse_baro(buf, ctl, l3)?;
 
         // (bit 5)
         let (quat, l4) = Self::try_parse_quat(buf, ctl, 0x0020, l3)?;
 
         // (bit 6)
         let (angle, l5) = Self::try_parse_vec3(buf, ctl, 0x0040, l4, Self::SCALE_ANGLE)?;
+        // i16::MIN 对应 -180°，与 +180° 是同一姿态，统一到 (-180°, 180°]
+        let angle = angle.map(wrap_degrees);
 
         // (bit 7)
         let (offset, l6) = Self::try_parse_vec3(buf, ctl, 0x0080, l5, Self::SCALE_OFFSET)?;
@@ -130,7 +204,7 @@ impl ImuParser {
         let (accel_nav, _l_final) = Self::try_parse_vec3(buf, ctl, 0x0200, l6, Self::SCALE_ACCEL)?;
 
         Ok(ImuSampleRaw {
-            timestamp_ms,
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
             accel_no_g,
             accel_with_g,
             gyro,
@@ -138,6 +212,265 @@ impl ImuParser {
             angle,
             offset,
             accel_nav,
+            baro,
         })
     }
+
+    /// 按帧头控制位计算一帧的长度；不是 0x11 帧或不足 3 字节时为空。
+    pub fn frame_len(buf: &[u8]) -> Option<usize> {
+        if buf.len() < 3 || buf[0] != Self::FRAME_HEADER {
+            return None;
+        }
+        let ctl = u16::from_le_bytes([buf[1], buf[2]]);
+        let body: usize = Self::FIELD_LENGTHS
+            .iter()
+            .filter(|(bit, _)| ctl & bit != 0)
+            .map(|(_, len)| len)
+            .sum();
+        Some(Self::HEADER_LEN + body)
+    }
+
+    /// 把一个蓝牙通知切分为帧。
+    ///
+    /// 高速模式下设备偶尔把两帧 0x11 数据拼进同一个通知。仅当完整一帧之后紧跟 0x11
+    /// 帧头时才切分，其余字节（截断的后续帧等）作为最后一段原样返回，由
+    /// [`parse`](Self::parse) 报错，不影响前面已完整的帧；单帧通知返回自身。
+    pub fn split_frames(buf: &[u8]) -> Vec<&[u8]> {
+        let mut frames = Vec::with_capacity(1);
+        let mut rest = buf;
+        while let Some(len) =
+            Self::frame_len(rest).filter(|&len| len < rest.len() && rest[len] == Self::FRAME_HEADER)
+        {
+            let (frame, tail) = rest.split_at(len);
+            frames.push(frame);
+            rest = tail;
+        }
+        frames.push(rest);
+        frames
+    }
+
+    /// 默认订阅的全部字段控制位：bit 0/1/2/5/6/7/9，气压计（bit 4）另按样本追加。
+    const FULL_SUBSCRIPTION: u16 = 0x0001 | 0x0002 | 0x0004 | 0x0020 | 0x0040 | 0x0080 | 0x0200;
+
+    /// 将样本编码为 0x11 数据包，是 [`parse`](Self::parse) 的逆操作。
+    ///
+    /// 数值按协议比例系数量化并饱和到 i16（气压与高度为 i24），时间戳截断为 32 位；
+    /// 样本带气压计数据时置位 bit 4 并写入该数据块。
+    /// 用于合成测试数据与基准测试，使解析开销也计入测量。
+    pub fn encode(sample: &ImuSampleRaw) -> Vec<u8> {
+        fn push_i16(buf: &mut Vec<u8>, value: f64, scale: f64) {
+            let raw = (value / scale)
+                .round()
+                .clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+            buf.extend_from_slice(&raw.to_le_bytes());
+        }
+        fn push_i24(buf: &mut Vec<u8>, value: f64, scale: f64) {
+            const MAX: f64 = ((1 << 23) - 1) as f64;
+            let raw = (value / scale).round().clamp(-MAX - 1.0, MAX) as i32;
+            buf.extend_from_slice(&raw.to_le_bytes()[..3]);
+        }
+        fn push_vec3(buf: &mut Vec<u8>, v: DVec3, scale: f64) {
+            push_i16(buf, v.x, scale);
+            push_i16(buf, v.y, scale);
+            push_i16(buf, v.z, scale);
+        }
+
+        let ctl = match sample.baro {
+            Some(_) => Self::FULL_SUBSCRIPTION | Self::BARO_BIT,
+            None => Self::FULL_SUBSCRIPTION,
+        };
+        let mut buf = Vec::with_capacity(59);
+        buf.push(Self::FRAME_HEADER);
+        buf.extend_from_slice(&ctl.to_le_bytes());
+        buf.extend_from_slice(&(sample.timestamp_ms.as_millis() as u32).to_le_bytes());
+        push_vec3(&mut buf, sample.accel_no_g, Self::SCALE_ACCEL);
+        push_vec3(&mut buf, sample.accel_with_g, Self::SCALE_ACCEL);
+        push_vec3(&mut buf, sample.gyro, Self::SCALE_ANGLE_SPEED);
+        if let Some(baro) = sample.baro {
+            push_i16(&mut buf, baro.temperature_c, Self::SCALE_TEMPERATURE);
+            push_i24(&mut buf, baro.pressure_hpa, Self::SCALE_PRESSURE);
+            push_i24(&mut buf, baro.altitude_m, Self::SCALE_ALTITUDE);
+        }
+        push_i16(&mut buf, sample.quat.w, Self::SCALE_QUAT);
+        push_i16(&mut buf, sample.quat.x, Self::SCALE_QUAT);
+        push_i16(&mut buf, sample.quat.y, Self::SCALE_QUAT);
+        push_i16(&mut buf, sample.quat.z, Self::SCALE_QUAT);
+        push_vec3(&mut buf, sample.angle, Self::SCALE_ANGLE);
+        push_vec3(&mut buf, sample.offset, Self::SCALE_OFFSET);
+        push_vec3(&mut buf, sample.accel_nav, Self::SCALE_ACCEL);
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+
+    #[test]
+    fn encode_roundtrips_through_parse() {
+        let sample = ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(123_456),
+            accel_no_g: DVec3::new(0.1, -0.2, 0.3),
+            accel_with_g: DVec3::new(0.5, -1.0, 9.8),
+            gyro: DVec3::new(10.0, -20.0, 30.0),
+            quat: DQuat {
+                w: 0.9,
+                x: 0.1,
+                y: -0.3,
+                z: 0.2,
+            },
+            angle: DVec3::new(45.0, -90.0, 179.0),
+            offset: DVec3::new(1.234, -0.5, 0.0),
+            accel_nav: DVec3::new(0.0, 0.1, -0.1),
+            baro: None,
+        };
+        let packet = ImuParser::encode(&sample);
+        assert_eq!(packet.len(), 51);
+
+        let parsed = ImuParser::parse(&packet).unwrap();
+        assert_eq!(parsed.timestamp_ms, sample.timestamp_ms);
+        let close = |a: DVec3, b: DVec3, scale: f64| (a - b).length() <= scale;
+        assert!(close(
+            parsed.accel_with_g,
+            sample.accel_with_g,
+            ImuParser::SCALE_ACCEL
+        ));
+        assert!(close(
+            parsed.gyro,
+            sample.gyro,
+            ImuParser::SCALE_ANGLE_SPEED
+        ));
+        assert!(close(parsed.angle, sample.angle, ImuParser::SCALE_ANGLE));
+        assert!(close(parsed.offset, sample.offset, ImuParser::SCALE_OFFSET));
+        assert!((parsed.quat.w - sample.quat.w).abs() <= ImuParser::SCALE_QUAT);
+    }
+
+    #[test]
+    fn barometer_block_is_parsed_when_subscribed() {
+        let sample = ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(42),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::new(0.0, 0.0, 9.8),
+            gyro: DVec3::new(1.0, 2.0, 3.0),
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: Some(BaroSample {
+                temperature_c: -12.34,
+                pressure_hpa: 1013.25,
+                altitude_m: -123.4,
+            }),
+        };
+        let packet = ImuParser::encode(&sample);
+        assert_eq!(packet.len(), ImuParser::MAX_FRAME_LEN);
+        assert_eq!(ImuParser::frame_len(&packet), Some(59));
+
+        let parsed = ImuParser::parse(&packet).unwrap();
+        let baro = parsed.baro.unwrap();
+        let expected = sample.baro.unwrap();
+        assert!(
+            (baro.temperature_c - expected.temperature_c).abs() <= ImuParser::SCALE_TEMPERATURE
+        );
+        assert!((baro.pressure_hpa - expected.pressure_hpa).abs() <= ImuParser::SCALE_PRESSURE);
+        // 负高度经 24 位符号扩展还原
+        assert!((baro.altitude_m - expected.altitude_m).abs() <= ImuParser::SCALE_ALTITUDE);
+        // 气压计块之后的字段位置随之后移
+        assert!((parsed.gyro - sample.gyro).length() <= ImuParser::SCALE_ANGLE_SPEED);
+        assert!((parsed.quat.w - 1.0).abs() <= ImuParser::SCALE_QUAT);
+
+        // 未订阅时为空
+        let without = ImuSampleRaw {
+            baro: None,
+            ..sample
+        };
+        assert!(ImuParser::parse(&ImuParser::encode(&without))
+            .unwrap()
+            .baro
+            .is_none());
+    }
+
+    #[test]
+    fn parsed_angles_land_in_half_open_range() {
+        let sample = ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(0),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::ZERO,
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        };
+        let mut packet = ImuParser::encode(&sample);
+        // 欧拉角字段位于 7 + 3 * 6 + 8 = 33 字节处：写入 i16::MIN 与 i16::MAX
+        packet[33..35].copy_from_slice(&i16::MIN.to_le_bytes());
+        packet[35..37].copy_from_slice(&i16::MAX.to_le_bytes());
+        let angle = ImuParser::parse(&packet).unwrap().angle;
+        assert_eq!(angle.x, 180.0);
+        assert!(angle.y > 179.99 && angle.y < 180.0);
+        assert_eq!(angle.z, 0.0);
+    }
+
+    #[test]
+    fn notifications_with_concatenated_frames_are_split() {
+        let frame = |timestamp_ms: u64| {
+            ImuParser::encode(&ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_no_g: DVec3::ZERO,
+                accel_with_g: DVec3::new(0.0, 0.0, 9.8),
+                gyro: DVec3::ZERO,
+                quat: DQuat::IDENTITY,
+                angle: DVec3::ZERO,
+                offset: DVec3::ZERO,
+                accel_nav: DVec3::ZERO,
+                baro: None,
+            })
+        };
+        let parse_all = |buf: &[u8]| -> (Vec<u64>, usize) {
+            let results: Vec<_> = ImuParser::split_frames(buf)
+                .into_iter()
+                .map(ImuParser::parse)
+                .collect();
+            let errors = results.iter().filter(|r| r.is_err()).count();
+            let timestamps = results
+                .into_iter()
+                .filter_map(Result::ok)
+                .map(|s| s.timestamp_ms.as_millis())
+                .collect();
+            (timestamps, errors)
+        };
+        assert_eq!(ImuParser::frame_len(&frame(0)), Some(51));
+
+        // 单帧：与直接 parse 相同；帧尾的非 0x11 填充字节照旧忽略
+        let single = frame(100);
+        assert_eq!(ImuParser::split_frames(&single), [&single[..]]);
+        assert_eq!(parse_all(&single), (vec![100], 0));
+        let mut padded = frame(100);
+        padded.extend_from_slice(&[0, 0]);
+        assert_eq!(parse_all(&padded), (vec![100], 0));
+
+        // 两帧拼接：按顺序各出一个样本
+        let double = [frame(100), frame(104)].concat();
+        assert_eq!(ImuParser::split_frames(&double).len(), 2);
+        assert_eq!(parse_all(&double), (vec![100, 104], 0));
+        let triple = [frame(100), frame(104), frame(108)].concat();
+        assert_eq!(parse_all(&triple), (vec![100, 104, 108], 0));
+
+        // 后半截断：第一帧保留，第二帧报错
+        let truncated = &double[..51 + 20];
+        assert_eq!(parse_all(truncated), (vec![100], 1));
+        // 后半控制位缺字段：同样只丢第二帧
+        let mut corrupted = double.clone();
+        corrupted[52] = 0x01;
+        corrupted[53] = 0x00;
+        assert_eq!(parse_all(&corrupted), (vec![100], 1));
+
+        // 首帧本身损坏时整体交给 parse 报错
+        assert_eq!(parse_all(&[0x12, 0, 0]), (vec![], 1));
+        assert_eq!(parse_all(&[]), (vec![], 1));
+    }
 }
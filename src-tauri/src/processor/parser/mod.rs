@@ -20,10 +20,14 @@
 
 /// 解析实现。
 pub mod logic;
+/// 蓝牙通知分片重组。
+pub mod reassembly;
 /// 原始样本类型。
 pub mod types;
 
 /// 原始数据解析器。
 pub use logic::ImuParser;
-/// 原始样本类型与兼容别名。
-pub use types::ImuSampleRaw;
+/// 分片重组。
+pub use reassembly::{FrameReassembler, ReassemblyConfig, ReassemblyStats};
+/// 原始样本类型、兼容别名与解析错误。
+pub use types::{BaroSample, ImuSampleRaw, ParseError};
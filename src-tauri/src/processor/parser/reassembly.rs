@@ -0,0 +1,431 @@
+//! 蓝牙通知分片重组。
+//!
+//! 部分平台（较旧的 Windows 蓝牙协议栈）协商到的 MTU 小于一帧 0x11 数据，一帧会被拆成
+//! 两个通知：前一个帧头完整但字节不足（[`ParseError::TooShort`]），后一个没有帧头。
+//! 直接解析时两半都被拒绝。[`FrameReassembler`] 在解析之前把帧头分片暂存起来，与下一个
+//! 不以 0x11 开头的通知拼接后重新切分；拼接后仍不完整时再等一个通知，连续两次仍不完整、
+//! 等待超时、下一个通知以新帧开始或暂存超过上限时丢弃并计数，损坏的数据不会卡住缓冲。
+//!
+//! 续片本身以 0x11 开头时会被当作新帧，该帧丢弃并计数。
+
+use std::{borrow::Cow, ops::Range, time::Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::processor::{
+    parser::{logic::ImuParser, types::ParseError},
+    shared::{rate_limit, validate::ConfigValidator},
+};
+
+/// 分片重组配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ReassemblyConfig {
+    /// 是否启用。关闭时通知按原样切分，截断的帧交给解析报错。
+    pub enabled: bool,
+    /// 帧头分片等待续片的最长时间 (ms)，按主机接收时刻计。
+    pub max_hold_ms: u64,
+    /// 暂存分片的字节上限。
+    pub max_held_bytes: usize,
+}
+
+impl Default for ReassemblyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_hold_ms: 100,
+            max_held_bytes: 128,
+        }
+    }
+}
+
+impl ReassemblyConfig {
+    /// 校验取值范围：等待时长 1 ~ 1000 ms，暂存上限不小于一帧最大长度、不超过 4096 字节。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.check(
+            "max_hold_ms",
+            (1..=1_000).contains(&self.max_hold_ms),
+            format!("must be in [1, 1000], got {}", self.max_hold_ms),
+        );
+        v.check(
+            "max_held_bytes",
+            (ImuParser::MAX_FRAME_LEN..=4_096).contains(&self.max_held_bytes),
+            format!(
+                "must be in [{}, 4096], got {}",
+                ImuParser::MAX_FRAME_LEN,
+                self.max_held_bytes
+            ),
+        );
+    }
+}
+
+/// 重组统计（自上次重置起累计）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ReassemblyStats {
+    /// 拼接成功的帧数。
+    pub reassembled: u64,
+    /// 拼接失败而丢弃的分片数。
+    pub dropped: u64,
+}
+
+/// 暂存的帧头分片。
+#[derive(Debug)]
+struct HeldFragment {
+    bytes: Vec<u8>,
+    /// 首个分片的主机接收时刻；合成包没有接收时刻，不参与超时。
+    since: Option<Instant>,
+    /// 已拼接但仍不完整的次数。
+    attempts: u32,
+}
+
+/// 分片重组器，由管线持有，在解析之前处理每个蓝牙通知。
+#[derive(Debug)]
+pub struct FrameReassembler {
+    config: ReassemblyConfig,
+    held: Option<HeldFragment>,
+    stats: ReassemblyStats,
+}
+
+impl FrameReassembler {
+    /// 拼接后仍不完整的次数达到该值时丢弃分片。
+    pub const MAX_ATTEMPTS: u32 = 2;
+
+    /// 创建重组器，初始没有暂存分片。
+    pub fn new(config: ReassemblyConfig) -> Self {
+        Self {
+            config,
+            held: None,
+            stats: ReassemblyStats::default(),
+        }
+    }
+
+    /// 原地更新配置；关闭时丢弃暂存分片。
+    pub fn reconfigure(&mut self, config: ReassemblyConfig) {
+        if !config.enabled && self.held.is_some() {
+            self.drop_fragment("分片重组已关闭");
+        }
+        self.config = config;
+    }
+
+    /// 清空暂存分片与统计。
+    pub fn reset(&mut self) {
+        self.held = None;
+        self.stats = ReassemblyStats::default();
+    }
+
+    /// 累计统计。
+    pub fn stats(&self) -> ReassemblyStats {
+        self.stats
+    }
+
+    /// 是否有暂存分片在等待续片。
+    pub fn is_holding(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// 处理一个蓝牙通知，返回可以交给解析的各帧。
+    ///
+    /// 末尾是帧头完整但字节不足的分片时暂存，不在返回值中；与暂存分片拼接的通知
+    /// 返回拼接后的帧。`received_at` 为通知到达主机的时刻，合成包传 `None`。
+    pub fn push<'a>(
+        &mut self,
+        notification: &'a [u8],
+        received_at: Option<Instant>,
+    ) -> Vec<Cow<'a, [u8]>> {
+        if !self.config.enabled {
+            return ImuParser::split_frames(notification)
+                .into_iter()
+                .map(Cow::Borrowed)
+                .collect();
+        }
+        let continuation = notification.first() != Some(&ImuParser::FRAME_HEADER);
+        let joined = match self.held.take() {
+            Some(held) if self.expired(&held, received_at) => {
+                self.drop_fragment("分片等待续片超时");
+                None
+            }
+            Some(_) if !continuation => {
+                self.drop_fragment("下一个通知以新帧开始");
+                None
+            }
+            Some(held) => {
+                let mut bytes = held.bytes;
+                bytes.extend_from_slice(notification);
+                Some(HeldFragment {
+                    bytes,
+                    since: held.since,
+                    attempts: held.attempts + 1,
+                })
+            }
+            None => None,
+        };
+        match joined {
+            Some(joined) => {
+                let ranges = self.split(
+                    &joined.bytes,
+                    Some((joined.since, joined.attempts)),
+                    received_at,
+                );
+                ranges
+                    .into_iter()
+                    .map(|range| Cow::Owned(joined.bytes[range].to_vec()))
+                    .collect()
+            }
+            None => self
+                .split(notification, None, received_at)
+                .into_iter()
+                .map(|range| Cow::Borrowed(&notification[range]))
+                .collect(),
+        }
+    }
+
+    /// 切分缓冲，末尾的帧头分片暂存或丢弃，返回其余各帧的字节范围。
+    ///
+    /// `joined` 为拼接缓冲对应分片的首次接收时刻与拼接次数，普通通知为 `None`。
+    fn split(
+        &mut self,
+        buf: &[u8],
+        joined: Option<(Option<Instant>, u32)>,
+        received_at: Option<Instant>,
+    ) -> Vec<Range<usize>> {
+        let mut start = 0;
+        let mut ranges: Vec<Range<usize>> = ImuParser::split_frames(buf)
+            .into_iter()
+            .map(|frame| {
+                let range = start..start + frame.len();
+                start = range.end;
+                range
+            })
+            .collect();
+        if joined.is_some() {
+            // 拼接结果重新完整解析一次，长度够但内容无效的拼接同样丢弃
+            match ImuParser::parse(&buf[ranges[0].clone()]) {
+                Ok(_) => self.stats.reassembled += 1,
+                // 仍不完整，按末尾分片处理
+                Err(ParseError::TooShort { .. }) => {}
+                Err(_) => {
+                    self.drop_fragment("拼接后数据无效");
+                    return Vec::new();
+                }
+            }
+        }
+        let Some(last) = ranges.last().cloned() else {
+            return ranges;
+        };
+        if !matches!(
+            ImuParser::check_frame(&buf[last.clone()]),
+            Err(ParseError::TooShort { .. })
+        ) {
+            return ranges;
+        }
+        ranges.pop();
+        // 只有拼接缓冲只剩这一个分片时才是同一分片的又一次尝试，否则是新的帧头分片
+        let (since, attempts) = match joined {
+            Some(held) if ranges.is_empty() => held,
+            _ => (received_at, 0),
+        };
+        if attempts >= Self::MAX_ATTEMPTS {
+            self.drop_fragment("分片连续两次拼接仍不完整");
+        } else if last.len() > self.config.max_held_bytes {
+            self.drop_fragment("分片超过暂存上限");
+        } else {
+            self.held = Some(HeldFragment {
+                bytes: buf[last].to_vec(),
+                since,
+                attempts,
+            });
+        }
+        ranges
+    }
+
+    fn expired(&self, held: &HeldFragment, received_at: Option<Instant>) -> bool {
+        match (held.since, received_at) {
+            (Some(since), Some(now)) => {
+                now.saturating_duration_since(since).as_millis()
+                    > u128::from(self.config.max_hold_ms)
+            }
+            _ => false,
+        }
+    }
+
+    fn drop_fragment(&mut self, reason: &'static str) {
+        self.held = None;
+        self.stats.dropped += 1;
+        let dropped = self.stats.dropped;
+        rate_limit::PER_SECOND.warn("ble_reassembly", || {
+            format!("丢弃蓝牙帧分片: {reason}（累计 {dropped}）")
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::processor::{
+        parser::types::{BaroSample, ImuSampleRaw},
+        shared::DeviceTimeMs,
+    };
+
+    fn frame(timestamp_ms: u64, baro: bool) -> Vec<u8> {
+        ImuParser::encode(&ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::new(0.0, 0.0, 9.8),
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: baro.then_some(BaroSample {
+                temperature_c: 20.0,
+                pressure_hpa: 1000.0,
+                altitude_m: 100.0,
+            }),
+        })
+    }
+
+    /// 依次送入通知，返回解析成功的时间戳与解析失败的帧数。
+    fn feed(reassembler: &mut FrameReassembler, notifications: &[&[u8]]) -> (Vec<u64>, usize) {
+        let mut timestamps = Vec::new();
+        let mut errors = 0;
+        for notification in notifications {
+            for packet in reassembler.push(notification, None) {
+                match ImuParser::parse(&packet) {
+                    Ok(sample) => timestamps.push(sample.timestamp_ms.as_millis()),
+                    Err(_) => errors += 1,
+                }
+            }
+        }
+        (timestamps, errors)
+    }
+
+    #[test]
+    fn frames_split_at_any_offset_are_reassembled() {
+        for baro in [false, true] {
+            let frames: Vec<Vec<u8>> = (0..3).map(|i| frame(100 + i * 5, baro)).collect();
+            let len = frames[0].len();
+            // 帧头内部、帧头之后与最后一个字节之前各处截断
+            for offset in [1, 2, 3, 7, 20, len / 2, len - 1] {
+                let mut reassembler = FrameReassembler::new(ReassemblyConfig::default());
+                let mut notifications: Vec<&[u8]> = Vec::new();
+                for frame in &frames {
+                    let (head, tail) = frame.split_at(offset);
+                    // 续片恰好以 0x11 开头时会被当作新帧，协议允许的场景下跳过
+                    if tail[0] == ImuParser::FRAME_HEADER {
+                        continue;
+                    }
+                    notifications.push(head);
+                    notifications.push(tail);
+                }
+                let expected = notifications.len() / 2;
+                let (timestamps, errors) = feed(&mut reassembler, &notifications);
+                assert_eq!(timestamps.len(), expected, "baro {baro}, offset {offset}");
+                assert_eq!(errors, 0, "baro {baro}, offset {offset}");
+                assert_eq!(reassembler.stats().reassembled, expected as u64);
+                assert_eq!(reassembler.stats().dropped, 0);
+                assert!(!reassembler.is_holding());
+            }
+        }
+    }
+
+    #[test]
+    fn three_way_split_and_mixed_notifications_are_reassembled() {
+        let (a, b, c) = (frame(1, false), frame(2, false), frame(3, false));
+        let mut reassembler = FrameReassembler::new(ReassemblyConfig::default());
+
+        // 一帧拆成三片：第一次拼接仍不完整，第二次完成
+        let (timestamps, errors) = feed(&mut reassembler, &[&a[..10], &a[10..30], &a[30..]]);
+        assert_eq!((timestamps, errors), (vec![1], 0));
+
+        // 完整一帧后跟下一帧的前半，续片带着再下一帧的前半
+        let mut first = b.clone();
+        first.extend_from_slice(&c[..20]);
+        let mut second = c[20..].to_vec();
+        second.extend_from_slice(&a[..5]);
+        let (timestamps, errors) = feed(&mut reassembler, &[&first, &second, &a[5..]]);
+        assert_eq!((timestamps, errors), (vec![2, 3, 1], 0));
+        assert_eq!(
+            reassembler.stats(),
+            ReassemblyStats {
+                reassembled: 3,
+                dropped: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn corrupt_fragments_are_dropped_without_wedging() {
+        let good = frame(7, false);
+        let mut reassembler = FrameReassembler::new(ReassemblyConfig::default());
+
+        // 帧头分片后紧跟新帧：分片丢弃，新帧照常解析
+        let (timestamps, _) = feed(&mut reassembler, &[&good[..10], &good]);
+        assert_eq!(timestamps, [7]);
+        assert_eq!(reassembler.stats().dropped, 1);
+
+        // 连续两次拼接仍不完整：丢弃，之后的帧不受影响
+        let (timestamps, _) = feed(
+            &mut reassembler,
+            &[&good[..10], &[0x00; 3], &[0x00; 3], &good],
+        );
+        assert_eq!(timestamps, [7]);
+        assert_eq!(reassembler.stats().dropped, 2);
+
+        // 拼接后长度够但缺少必需字段：丢弃，不交给解析
+        let mut missing = good[..13].to_vec();
+        missing[1] = 0x01;
+        missing[2] = 0x00;
+        let (timestamps, errors) = feed(&mut reassembler, &[&missing[..5], &missing[5..], &good]);
+        assert_eq!((timestamps, errors), (vec![7], 0));
+        assert_eq!(reassembler.stats().dropped, 3);
+
+        // 超过暂存上限：不暂存
+        let mut small = FrameReassembler::new(ReassemblyConfig {
+            max_held_bytes: 8,
+            ..ReassemblyConfig::default()
+        });
+        let (timestamps, _) = feed(&mut small, &[&good[..20], &good[20..], &good]);
+        assert_eq!(timestamps, [7]);
+        assert_eq!(small.stats().dropped, 1);
+        assert!(!small.is_holding());
+
+        // 没有暂存时的无帧头通知照旧交给解析报错
+        let (timestamps, errors) = feed(&mut reassembler, &[&good[20..]]);
+        assert_eq!((timestamps.len(), errors), (0, 1));
+    }
+
+    #[test]
+    fn held_fragment_expires_after_max_hold() {
+        let good = frame(9, false);
+        let mut reassembler = FrameReassembler::new(ReassemblyConfig {
+            max_hold_ms: 50,
+            ..ReassemblyConfig::default()
+        });
+        let t0 = Instant::now();
+        assert!(reassembler.push(&good[..10], Some(t0)).is_empty());
+        let late = t0 + Duration::from_millis(80);
+        assert_eq!(reassembler.push(&good[10..], Some(late)).len(), 1);
+        assert_eq!(reassembler.stats().dropped, 1);
+        assert_eq!(reassembler.stats().reassembled, 0);
+
+        // 期限内到达的续片照常拼接
+        assert!(reassembler.push(&good[..10], Some(t0)).is_empty());
+        let packets = reassembler.push(&good[10..], Some(t0 + Duration::from_millis(20)));
+        assert_eq!(
+            ImuParser::parse(&packets[0]).unwrap().timestamp_ms,
+            DeviceTimeMs(9)
+        );
+
+        // 关闭后按原样切分
+        reassembler.reconfigure(ReassemblyConfig {
+            enabled: false,
+            ..ReassemblyConfig::default()
+        });
+        assert_eq!(reassembler.push(&good[..10], None).len(), 1);
+        assert!(!reassembler.is_holding());
+    }
+}
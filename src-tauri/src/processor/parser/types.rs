@@ -1,13 +1,15 @@
 //! IMU 原始样本类型定义。
 
 use math_f64::{DQuat, DVec3};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize)]
+use crate::processor::shared::DeviceTimeMs;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 /// 从蓝牙数据包中解析出的原始数据体, 保证数据均为有效值
 pub struct ImuSampleRaw {
     /// 运行时间ms
-    pub timestamp_ms: u64,
+    pub timestamp_ms: DeviceTimeMs,
     /// 没有G的重力加速度 m/s^2
     pub accel_no_g: DVec3,
     /// 有G的重力加速度 m/s^2
@@ -22,4 +24,42 @@ pub struct ImuSampleRaw {
     pub offset: DVec3,
     /// 导航系加速度
     pub accel_nav: DVec3,
+    /// 气压计数据，未订阅（控制位 bit 4）时为空
+    #[serde(default)]
+    pub baro: Option<BaroSample>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+/// 气压计数据块：温度、气压与设备按标准大气换算的高度
+pub struct BaroSample {
+    /// 板上温度 ℃
+    pub temperature_c: f64,
+    /// 气压 hPa
+    pub pressure_hpa: f64,
+    /// 设备换算的海拔高度 m
+    pub altitude_m: f64,
+}
+
+/// 数据包解析错误。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    /// 不是 0x11 功能数据帧（含空包）。
+    #[error("数据包帧头不是 0x11")]
+    BadHeader,
+    /// 字节数不足帧头控制位声明的一帧，可能是被 MTU 截断的分片。
+    #[error("数据包长度 {len} 字节，不足一帧所需的 {expected} 字节")]
+    TooShort {
+        /// 实际字节数。
+        len: usize,
+        /// 一帧所需的字节数。
+        expected: usize,
+    },
+    /// 控制位缺少必需字段。
+    #[error("数据包控制位 {ctl:#06x} 缺少必需字段 (bit {bit:#06x})")]
+    MissingField {
+        /// 帧头控制位。
+        ctl: u16,
+        /// 缺少的字段控制位。
+        bit: u16,
+    },
 }
@@ -3,14 +3,116 @@
 //! 提供 [`PipelineDiagnostics`] 结构体，捕获每帧处理管线各阶段的中间值和性能指标。
 //! 仅在诊断开关开启时采集，关闭时通过 [`AtomicBool`] 门控实现零开销。
 
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
+use flume::RecvTimeoutError;
 use math_f64::DVec3;
 use serde::Serialize;
 
+use crate::processor::{
+    batcher::{Batcher, DEFAULT_BATCH_MAX_LEN, DEFAULT_BATCH_WINDOW_MS},
+    calibration::GyroUnit,
+    fanout::SubscriberRate,
+};
+
+/// 诊断通道发送端。
+///
+/// 管线线程只负责把 [`PipelineDiagnostics`]（纯 POD）投递进有界通道，
+/// JSON 序列化由订阅端的独立线程完成，不占用处理线程。
+/// 通道满时丢弃**最旧**的一帧，保证订阅端看到的总是最新数据。
+#[derive(Clone)]
+pub struct DiagnosticsSender {
+    tx: flume::Sender<PipelineDiagnostics>,
+    /// 仅用于满时弹出最旧帧。
+    evict_rx: flume::Receiver<PipelineDiagnostics>,
+}
+
+impl DiagnosticsSender {
+    /// 由同一通道的收发两端构造。
+    pub fn new(
+        tx: flume::Sender<PipelineDiagnostics>,
+        evict_rx: flume::Receiver<PipelineDiagnostics>,
+    ) -> Self {
+        Self { tx, evict_rx }
+    }
+
+    /// 投递一帧诊断数据，通道满时先丢弃最旧一帧再重试。
+    ///
+    /// 永不阻塞；与订阅端并发消费时重试仍可能失败，此时直接丢弃本帧。
+    pub fn send_latest(&self, diag: PipelineDiagnostics) {
+        match self.tx.try_send(diag) {
+            Ok(()) | Err(flume::TrySendError::Disconnected(_)) => {}
+            Err(flume::TrySendError::Full(diag)) => {
+                let _ = self.evict_rx.try_recv();
+                let _ = self.tx.try_send(diag);
+            }
+        }
+    }
+}
+
+/// 创建容量为 `capacity` 的诊断通道。
+///
+/// 返回的接收端交给订阅命令消费。
+pub fn diagnostics_channel(
+    capacity: usize,
+) -> (DiagnosticsSender, flume::Receiver<PipelineDiagnostics>) {
+    let (tx, rx) = flume::bounded(capacity);
+    (DiagnosticsSender::new(tx, rx.clone()), rx)
+}
+
+/// 诊断订阅端的序列化循环，在订阅命令开启的独立线程中运行。
+///
+/// 从 `rx` 取出诊断帧，填入 `rates()` 给出的输出订阅频率，按默认窗口攒批后序列化成 JSON
+/// 数组交给 `send`。`send` 返回 `false`（前端已断开）时立即返回；通道关闭时先发出
+/// 未满的一批再返回。
+pub fn relay_diagnostics(
+    rx: &flume::Receiver<PipelineDiagnostics>,
+    rates: impl Fn() -> Vec<SubscriberRate>,
+    mut send: impl FnMut(String) -> bool,
+) {
+    let mut batcher = Batcher::new(
+        Duration::from_millis(DEFAULT_BATCH_WINDOW_MS),
+        DEFAULT_BATCH_MAX_LEN,
+    );
+    let mut send_batch = |batch: Vec<PipelineDiagnostics>| match serde_json::to_string(&batch) {
+        Ok(json) => send(json),
+        Err(e) => {
+            tracing::error!("序列化诊断数据失败: {}", e);
+            true
+        }
+    };
+    loop {
+        let received = match batcher.deadline() {
+            Some(deadline) => rx.recv_deadline(deadline),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let batch = match received {
+            Ok(mut data) => {
+                data.out_subscriber_rates = rates();
+                batcher.push(data, Instant::now())
+            }
+            Err(RecvTimeoutError::Timeout) => batcher.poll(Instant::now()),
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Some(pending) = batcher.flush() {
+                    send_batch(pending);
+                }
+                return;
+            }
+        };
+        if let Some(batch) = batch {
+            if !send_batch(batch) {
+                return;
+            }
+        }
+    }
+}
+
 /// 诊断开关标记，跨线程共享。
 ///
 /// 使用 `Relaxed` 语序即可——只需最终可见性，无需与其他内存操作同步。
@@ -28,7 +130,29 @@ pub struct PipelineDiagnostics {
     /// 帧时间戳 (ms)，与主数据路径一致。
     pub timestamp_ms: u64,
 
+    // —— 时间戳排序 ——
+    /// 累计丢弃的重复时间戳帧数。
+    pub seq_duplicates: u64,
+    /// 累计丢弃的迟到帧数。
+    pub seq_late: u64,
+    /// 累计重排的乱序帧数。
+    pub seq_reordered: u64,
+    /// 累计检测到的设备复位次数。
+    pub seq_device_resets: u64,
+    /// 累计检测到的丢帧次数（间隔超过标称间隔 1.5 倍）。
+    pub seq_gaps: u64,
+    /// 按标称间隔估算的累计丢失帧数。
+    pub seq_missing_frames: u64,
+
+    // —— 蓝牙分片重组 ——
+    /// 累计拼接成功的分片帧数。
+    pub ble_reassembled: u64,
+    /// 累计拼接失败而丢弃的分片数。
+    pub ble_reassembly_dropped: u64,
+
     // —— 标定阶段 ——
+    /// 标定是否被跳过（`calibration.passby`），跳过时只做角速度单位换算。
+    pub cal_bypassed: bool,
     /// 当前加速度计偏置 (m/s²)。
     pub cal_accel_bias: DVec3,
     /// 当前陀螺仪偏置 (rad/s)，含在线 EMA 更新。
@@ -37,12 +161,22 @@ pub struct PipelineDiagnostics {
     pub cal_accel_pre: DVec3,
     /// 标定后加速度 (m/s²)，经偏置去除 + 矩阵修正。
     pub cal_accel_post: DVec3,
-    /// 标定前角速度 (deg/s)，IMU 原始输出。
+    /// 标定前角速度，IMU 原始输出，单位见 `cal_gyro_unit`。
     pub cal_gyro_pre: DVec3,
+    /// `cal_gyro_pre` 的单位。
+    pub cal_gyro_unit: GyroUnit,
     /// 标定后角速度 (rad/s)，经单位转换 + 偏置去除 + 矩阵修正。
     pub cal_gyro_post: DVec3,
 
+    // —— 加速度来源 ——
+    /// `auto` 模式下两路世界系线加速度之差 (m/s²)，其他模式为空。
+    pub accel_source_residual: Option<f64>,
+    /// `auto` 模式下两路加速度是否持续不一致。
+    pub accel_source_inconsistent: bool,
+
     // —— 滤波阶段 ——
+    /// 滤波是否被跳过（`filter.passby`），跳过时前后取值相同。
+    pub filt_bypassed: bool,
     /// 滤波前加速度 (m/s²)。
     pub filt_accel_pre: DVec3,
     /// 滤波后加速度 (m/s²)。
@@ -67,8 +201,30 @@ pub struct PipelineDiagnostics {
     // —— 导航阶段 ——
     /// 积分时间步长 (s)。
     pub nav_dt: f64,
+    /// 因帧间隔过大而跳过积分的累计次数。
+    pub nav_skipped_integrations: u64,
+    /// 姿态模长超出容差、完整归一化的累计次数。
+    pub nav_quat_renormalizations: u64,
     /// 世界系线性加速度 (m/s²)，去重力后。
     pub nav_linear_accel: DVec3,
+    /// auto_gravity 估计的重力模长 (m/s²)，未启用或未完成时为空。
+    pub nav_gravity_estimate: Option<f64>,
+    /// 平面约束本帧去除的沿重力方向速度分量 (m/s)，未启用时为 0。
+    pub nav_plane_removed_velocity: f64,
+    /// 积分死区本帧置零的线加速度模长 (m/s²)，未启用或被旁路时为 0。
+    pub nav_deadband_clipped: f64,
+    /// 本地积分位置 (m)，与设备位置对照。
+    pub nav_integrated_position: DVec3,
+    /// 设备输出位置 (m)，已转到导航系并与本地积分对齐原点。
+    pub nav_device_position: DVec3,
+
+    // —— 气压计高度辅助 ——
+    /// 本帧原始气压 (hPa)，未订阅气压计时为空。
+    pub baro_pressure_hpa: Option<f64>,
+    /// 相对参考的气压高度 (m)，未启用或参考未建立时为空。
+    pub baro_relative_altitude_m: Option<f64>,
+    /// 本帧施加到积分位置 z 的修正量 (m)。
+    pub baro_correction_m: f64,
 
     // —— 饱和检测 ——
     /// 本帧加速度计是否触发饱和（任一轴 |accel_with_g| > 152 m/s²）。
@@ -99,12 +255,18 @@ pub struct PipelineDiagnostics {
     pub perf_process_us: u64,
     /// 上游通道（蓝牙 → 处理器）当前队列深度。
     pub perf_upstream_queue_len: u32,
-    /// 下游通道（处理器 → 前端）当前队列深度。
+    /// 下游通道（处理器 → 输出分发线程）当前队列深度。
     pub perf_downstream_queue_len: u32,
     /// 录制通道当前队列深度。
     pub perf_record_queue_len: u32,
-    /// 蓝牙收包间隔 (ms)，即本帧与上帧的时间戳差值。
+    /// 蓝牙收包间隔 (ms)，即本帧与上帧的通知到达时刻之差；合成包按处理开始时刻计算。
     pub perf_ble_interval_ms: f64,
+    /// 蓝牙通知到达主机到本帧开始处理的延迟 (ms)，含解析与上游通道排队；合成包为空。
+    pub perf_receive_latency_ms: Option<f64>,
+
+    // —— 输出分发 ——
+    /// 各输出订阅当前的频率；管线留空，由诊断订阅线程发送前从分发注册表填入。
+    pub out_subscriber_rates: Vec<SubscriberRate>,
 }
 
 /// 通道队列深度探针，用于在诊断中读取各通道的当前排队长度。
@@ -113,7 +275,7 @@ pub struct PipelineDiagnostics {
 /// 调用 `.len()` 获取当前队列深度，O(1) 开销。
 pub struct QueueProbe {
     upstream: flume::Receiver<crate::processor::RawImuData>,
-    downstream: flume::Sender<crate::types::outputs::ResponseData>,
+    downstream: flume::Sender<crate::processor::fanout::OutputItem>,
     record: flume::Sender<crate::processor::output::OutputFrame>,
 }
 
@@ -121,7 +283,7 @@ impl QueueProbe {
     /// 创建队列探针。
     pub fn new(
         upstream: flume::Receiver<crate::processor::RawImuData>,
-        downstream: flume::Sender<crate::types::outputs::ResponseData>,
+        downstream: flume::Sender<crate::processor::fanout::OutputItem>,
         record: flume::Sender<crate::processor::output::OutputFrame>,
     ) -> Self {
         Self {
@@ -131,6 +293,16 @@ impl QueueProbe {
         }
     }
 
+    /// 创建不连接任何真实通道的探针，供离线处理（replay/CLI）使用，队列深度恒为 0。
+    pub fn detached() -> Self {
+        let (upstream_tx, upstream) = flume::unbounded();
+        // 保持发送端存活，避免队列探针误报断连
+        std::mem::forget(upstream_tx);
+        let (downstream, _) = flume::unbounded();
+        let (record, _) = flume::unbounded();
+        Self::new(upstream, downstream, record)
+    }
+
     /// 读取上游通道队列深度。
     pub fn upstream_len(&self) -> usize {
         self.upstream.len()
@@ -152,7 +324,7 @@ impl QueueProbe {
     }
 
     /// 克隆下游通道发送端句柄。
-    pub fn downstream_tx(&self) -> flume::Sender<crate::types::outputs::ResponseData> {
+    pub fn downstream_tx(&self) -> flume::Sender<crate::processor::fanout::OutputItem> {
         self.downstream.clone()
     }
 
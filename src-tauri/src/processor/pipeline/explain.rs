@@ -0,0 +1,70 @@
+//! 单帧处理过程解释（dry-run）。
+//!
+//! 排查"轨迹往左偏"一类问题时，需要逐阶段追踪某个样本的取值。
+//! [`ProcessorPipeline::explain_sample`](super::ProcessorPipeline::explain_sample)
+//! 在实时管线状态的副本上跑一遍给定样本，记录每个阶段的输入、输出以及影响判定的参数
+//! （阈值、滤波系数、重力参考、静止状态等），实时状态不受影响。
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::types::outputs::ResponseData;
+
+/// 单个阶段的输入、输出与参数。
+#[derive(Debug, Clone, Serialize)]
+pub struct StageExplanation {
+    /// 阶段名，与处理顺序一致：`remap_axes` → `axis_calibration` → `calibration`
+    /// → `quat_health` → `accel_source` → `filter` → `derived` → `navigator` → `heading`
+    /// → `segment`。
+    pub stage: &'static str,
+    /// 阶段是否被配置跳过（`passby` 或功能未启用），跳过时输出即输入。
+    pub bypassed: bool,
+    /// 阶段输入。
+    pub input: Value,
+    /// 阶段输出。
+    pub output: Value,
+    /// 影响本阶段结果的配置与状态。
+    pub params: Value,
+}
+
+impl StageExplanation {
+    /// 序列化输入、输出，构造阶段记录。
+    pub fn new(
+        stage: &'static str,
+        input: &impl Serialize,
+        output: &impl Serialize,
+        params: Value,
+    ) -> Self {
+        Self {
+            stage,
+            bypassed: false,
+            input: to_value(input),
+            output: to_value(output),
+            params,
+        }
+    }
+
+    /// 标记阶段是否被跳过。
+    pub fn bypassed(mut self, bypassed: bool) -> Self {
+        self.bypassed = bypassed;
+        self
+    }
+}
+
+/// 一个样本流经整条管线的解释。
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleExplanation {
+    /// 样本时间戳 (ms)。
+    pub timestamp_ms: u64,
+    /// 导航是否处于暂停（暂停时导航阶段只输出冻结状态）。
+    pub paused: bool,
+    /// 按处理顺序排列的各阶段记录。
+    pub stages: Vec<StageExplanation>,
+    /// 该样本实际处理时会输出的数据。
+    pub output: ResponseData,
+}
+
+/// 序列化失败时记为 `null`，不让整份解释失败。
+fn to_value(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
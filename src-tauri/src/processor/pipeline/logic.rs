@@ -1,40 +1,150 @@
 //! IMU 处理管线实现。
 
 use std::{
+    borrow::Cow,
     path::{Path, PathBuf},
     sync::atomic::Ordering,
     time::{Instant, SystemTime},
 };
 
 use anyhow::Context;
+use math_f64::DVec3;
+use serde_json::json;
+use tokio::sync::oneshot;
 
 use crate::processor::{
-    calibration::{AxisCalibration, Calibration, CorrectionRequest},
+    accel_source::{AccelInconsistencyEvent, AccelSourceSelector},
+    calibration::{
+        AxisCalibration, Calibration, CalibrationFlow, CalibrationOffsets, CalibrationQuality,
+        CalibrationQualitySampler, CalibrationWizard, CalibrationWizardResult,
+        CalibrationWizardSnapshot, CalibrationWizardState, CorrectionRequest, FullCalibration,
+    },
+    clock_sync::{ClockSyncEstimate, ClockSyncEstimator},
+    derived::DerivedSignals,
+    fault::{FaultInjector, FaultInjectorHandle, FaultStage},
     filter::LowPassFilter,
-    navigator::{Navigator, NavigatorConfig},
-    output::{is_accel_saturated, OutputFrame},
-    parser::{ImuParser, ImuSampleRaw},
+    heading::{HeadingAligned, HeadingAligner},
+    navigator::{Navigator, NavigatorConfig, OriginAnchored},
+    output::{
+        is_accel_saturated, OutputBuilder, OutputFrame, StageCapture, StageCaptureHandle,
+        StageSnapshot,
+    },
+    parser::{FrameReassembler, ImuParser, ImuSampleRaw},
     pipeline::{
-        diagnostics::{DiagnosticsFlag, PipelineDiagnostics, QueueProbe},
-        types::ProcessorPipelineConfig,
+        diagnostics::{DiagnosticsFlag, DiagnosticsSender, PipelineDiagnostics, QueueProbe},
+        explain::{SampleExplanation, StageExplanation},
+        types::{ConfigApplyMode, ProcessorPipelineConfig},
     },
+    quat_health::{QuatFreezeEvent, QuatHealthMonitor, QuatNormGuard},
+    segment::{MotionSegment, SegmentDetector},
+    sequencer::{DeviceReset, PacketSequencer, SequencerOutput, SequencerStats},
+    shared::{rate_limit, HostTimeMs, DEFAULT_SAMPLE_INTERVAL_MS},
+    sync_tap::{SyncTap, SyncTapDetector},
 };
+use crate::types::recording::RecordingCalibration;
+
+/// 尚未收到任何数据包时请求零位校准的错误。
+pub const NO_RAW_DATA_ERROR: &str = "尚未收到任何 IMU 数据包，无法进行零位校准，请先连接设备";
+/// 上一次零位校准仍在采样时再次请求的错误。
+const CALIBRATION_BUSY_ERROR: &str = "零位校准正在采样，请等待当前校准完成";
+
+/// 零位校准请求的回复通道，区分单独的姿态零位与一次性全套校准。
+enum AxisCalibrationReply {
+    /// 仅姿态零位。
+    Axis(oneshot::Sender<Result<CalibrationQuality, &'static str>>),
+    /// 姿态零位 + 航向零点 + 位置归零 + 重力参考。
+    All(oneshot::Sender<Result<FullCalibration, &'static str>>),
+}
+
+impl AxisCalibrationReply {
+    /// 对应的标定向导流程。
+    fn flow(&self) -> CalibrationFlow {
+        match self {
+            Self::Axis(_) => CalibrationFlow::Axis,
+            Self::All(_) => CalibrationFlow::CalibrateAll,
+        }
+    }
+
+    /// 请求方是否已放弃等待。
+    fn is_closed(&self) -> bool {
+        match self {
+            Self::Axis(tx) => tx.is_closed(),
+            Self::All(tx) => tx.is_closed(),
+        }
+    }
+
+    /// 回复错误。
+    fn send_error(self, error: &'static str) {
+        let sent = match self {
+            Self::Axis(tx) => tx.send(Err(error)).is_ok(),
+            Self::All(tx) => tx.send(Err(error)).is_ok(),
+        };
+        if !sent {
+            tracing::error!("标定 response 接受端在发送前已被丢弃");
+        }
+    }
+}
+
+/// 正在采样的零位校准请求。
+struct PendingAxisCalibration {
+    sampler: CalibrationQualitySampler,
+    respond_to: AxisCalibrationReply,
+}
 
 /// IMU 处理管线。
 pub struct ProcessorPipeline {
+    /// 蓝牙通知分片重组，在解析之前执行。
+    reassembler: FrameReassembler,
+    sequencer: PacketSequencer,
+    /// 尚未被取走的设备复位事件。
+    device_reset: Option<DeviceReset>,
     axis_calibration: AxisCalibration,
+    /// 零位校准后的姿态模长守卫。
+    quat_norm: QuatNormGuard,
     calibration: Calibration,
+    /// 设备四元数冻结监测，冻结期间以陀螺积分姿态代替。
+    quat_health: QuatHealthMonitor,
+    /// 积分用的加速度来源选择与一致性检查。
+    accel_source: AccelSourceSelector,
     filter: LowPassFilter,
+    derived: DerivedSignals,
     navigator: Navigator,
+    /// 按首段运动方向对齐输出航向，只作用于输出帧。
+    heading: HeadingAligner,
+    segment_detector: SegmentDetector,
+    /// 尚未被取走的运动分段。
+    segment: Option<MotionSegment>,
+    /// 敲击同步检测。
+    sync_tap: SyncTapDetector,
     latest_raw: Option<ImuSampleRaw>,
+    /// 正在采集质量窗口的零位校准请求。
+    pending_axis: Option<PendingAxisCalibration>,
+    /// 标定向导状态，跨配置重建保留以保持序号连续。
+    wizard: CalibrationWizard,
+    /// 当前生效配置，用于判断增量更新能否原地生效。
+    config: ProcessorPipelineConfig,
+    /// 标称采样间隔 (ms)，由设备上报频率决定，不属于 processor.toml。
+    sample_interval_ms: f64,
+    /// 是否暂停导航：暂停时原始数据照常解析输出，导航状态冻结。
+    paused: bool,
     /// 上一帧主机接收时刻（用于计算真实 BLE 收包间隔）。
     prev_receive_instant: Option<Instant>,
+    /// 设备时钟与主机时钟的同步估计。
+    clock_sync: ClockSyncEstimator,
     /// 诊断开关。
     diagnostics_flag: DiagnosticsFlag,
-    /// 诊断数据发送通道。
-    diagnostics_tx: flume::Sender<PipelineDiagnostics>,
+    /// 诊断数据发送通道（满时丢最旧帧）。
+    diagnostics_tx: DiagnosticsSender,
     /// 通道队列深度探针。
     queue_probe: QueueProbe,
+    /// 故障注入钩子，未启用时各钩子直接返回。
+    faults: FaultInjectorHandle,
+    /// 阶段中间值采集开关，深度录制期间打开；配置重建后保留。
+    stage_capture: StageCaptureHandle,
+    /// 解析失败的累计包数。
+    parse_failures: u64,
+    /// 因某阶段输出非有限值（NaN/Inf）而丢弃的累计帧数。
+    non_finite_samples: u64,
 }
 
 /// 处理管线配置快照。
@@ -52,34 +162,81 @@ impl ProcessorPipeline {
     pub fn new(
         config: ProcessorPipelineConfig,
         diagnostics_flag: DiagnosticsFlag,
-        diagnostics_tx: flume::Sender<PipelineDiagnostics>,
+        diagnostics_tx: DiagnosticsSender,
         queue_probe: QueueProbe,
     ) -> Self {
+        let active_config = config.clone();
         let ProcessorPipelineConfig {
             global,
+            reassembly,
+            sequencer,
             calibration,
+            // 零位校准请求到达时从 self.config 读取
+            calibration_quality: _,
             filter,
+            derived,
             trajectory,
             zupt,
+            plane_constraint,
             navigator_impl,
             eskf,
+            position_source,
+            segment,
+            // 空闲模式由 AppState 管理，管线不使用
+            idle: _,
+            // 静止抑制在处理线程的输出分发中执行
+            output_suppress: _,
+            quat_health,
+            accel_source,
+            auto_origin,
+            baro,
+            heading_align,
+            sync_tap,
+            // 输出帧组装时从 self.config 读取
+            debug_vectors: _,
         } = config;
         Self {
+            reassembler: FrameReassembler::new(reassembly),
+            sequencer: PacketSequencer::new(sequencer),
+            device_reset: None,
             axis_calibration: AxisCalibration::new(),
+            quat_norm: QuatNormGuard::default(),
             calibration: Calibration::new(calibration),
+            quat_health: QuatHealthMonitor::new(quat_health),
+            accel_source: AccelSourceSelector::new(accel_source),
             filter: LowPassFilter::new(filter),
+            derived: DerivedSignals::new(derived),
             navigator: Navigator::new(NavigatorConfig {
                 trajectory,
                 zupt,
                 gravity: global.gravity,
+                auto_gravity: global.auto_gravity,
+                plane_constraint,
                 navigator_impl,
                 eskf,
+                position_source,
+                auto_origin,
+                baro,
             }),
+            heading: HeadingAligner::new(heading_align),
+            segment_detector: SegmentDetector::new(segment),
+            segment: None,
+            sync_tap: SyncTapDetector::new(sync_tap, global.gravity),
             latest_raw: None,
+            pending_axis: None,
+            wizard: CalibrationWizard::default(),
+            config: active_config,
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            paused: false,
             prev_receive_instant: None,
+            clock_sync: ClockSyncEstimator::default(),
             diagnostics_flag,
             diagnostics_tx,
             queue_probe,
+            faults: FaultInjector::new_handle(),
+            stage_capture: StageCapture::new_handle(),
+            parse_failures: 0,
+            non_finite_samples: 0,
         }
     }
 
@@ -87,6 +244,18 @@ impl ProcessorPipeline {
     /// 并自动执行一次姿态零位校准。
     pub fn reset_with_config(&mut self, config: ProcessorPipelineConfig) {
         let last_raw = self.latest_raw;
+        let pending_axis = self.pending_axis.take();
+        let wizard = std::mem::take(&mut self.wizard);
+        let sample_interval_ms = self.sample_interval_ms;
+        let paused = self.paused;
+        // 时钟同步只与设备时间轴有关，配置重建不影响
+        let clock_sync = std::mem::take(&mut self.clock_sync);
+        // 分片属于传输层，等待中的续片跨越重建继续拼接
+        let mut reassembler = std::mem::replace(
+            &mut self.reassembler,
+            FrameReassembler::new(config.reassembly),
+        );
+        reassembler.reconfigure(config.reassembly);
         let diag_flag = self.diagnostics_flag.clone();
         let diag_tx = self.diagnostics_tx.clone();
         // QueueProbe 内部是 flume 的 clone 句柄，创建新的
@@ -95,7 +264,23 @@ impl ProcessorPipeline {
             self.queue_probe.downstream_tx(),
             self.queue_probe.record_tx(),
         );
+        let faults = self.faults.clone();
+        let stage_capture = self.stage_capture.clone();
+        let parse_failures = self.parse_failures;
+        let non_finite_samples = self.non_finite_samples;
         *self = Self::new(config, diag_flag, diag_tx, queue_probe);
+        self.faults = faults;
+        self.stage_capture = stage_capture;
+        self.parse_failures = parse_failures;
+        self.non_finite_samples = non_finite_samples;
+        self.set_sample_interval_ms(sample_interval_ms);
+        self.paused = paused;
+        self.clock_sync = clock_sync;
+        self.reassembler = reassembler;
+        // 采样中的校准请求跨越重建继续，窗口按新的评估配置结束
+        self.pending_axis = pending_axis;
+        self.wizard = wizard;
+        self.settle_heading_wizard();
         if let Some(raw) = last_raw {
             self.axis_calibration.update_from_raw(&raw);
             self.navigator
@@ -103,17 +288,156 @@ impl ProcessorPipeline {
         }
     }
 
+    /// 增量应用配置：可调参数原地生效，结构性变更退回 [`reset_with_config`](Self::reset_with_config)。
+    ///
+    /// 返回实际采用的生效方式，判定规则见
+    /// [`ProcessorPipelineConfig::requires_reset`]。
+    pub fn patch_config(&mut self, config: ProcessorPipelineConfig) -> ConfigApplyMode {
+        if self.config.requires_reset(&config) {
+            self.reset_with_config(config);
+            return ConfigApplyMode::Reset;
+        }
+
+        self.reassembler.reconfigure(config.reassembly);
+        self.quat_health.reconfigure(config.quat_health);
+        self.accel_source.reconfigure(config.accel_source);
+        self.filter.reconfigure(config.filter);
+        self.derived.reconfigure(config.derived);
+        self.navigator.reconfigure(NavigatorConfig {
+            trajectory: config.trajectory,
+            zupt: config.zupt,
+            gravity: config.global.gravity,
+            auto_gravity: config.global.auto_gravity,
+            plane_constraint: config.plane_constraint,
+            navigator_impl: config.navigator_impl,
+            eskf: config.eskf,
+            position_source: config.position_source,
+            auto_origin: config.auto_origin,
+            baro: config.baro,
+        });
+        self.heading.reconfigure(config.heading_align);
+        self.settle_heading_wizard();
+        self.segment_detector.reconfigure(config.segment);
+        self.sync_tap.reconfigure(config.sync_tap);
+        self.config = config;
+        ConfigApplyMode::InPlace
+    }
+
+    /// 设置标称采样间隔 (ms)，导航状态保留。
+    ///
+    /// 设备上报频率变化时调用；按帧计数的窗口与丢帧检测阈值据此换算。
+    pub fn set_sample_interval_ms(&mut self, sample_interval_ms: f64) {
+        self.sample_interval_ms = sample_interval_ms;
+        self.sequencer.set_sample_interval_ms(sample_interval_ms);
+        self.navigator.set_sample_interval_ms(sample_interval_ms);
+    }
+
+    /// 当前标称采样间隔 (ms)。
+    pub fn sample_interval_ms(&self) -> f64 {
+        self.sample_interval_ms
+    }
+
+    /// 当前姿态零位。
+    pub fn axis_offset(&self) -> AxisCalibration {
+        self.axis_calibration
+    }
+
+    /// 当前世界系的姿态零位与重力参考，开始录制时写入会话元数据。
+    pub fn recording_calibration(&self) -> RecordingCalibration {
+        RecordingCalibration {
+            axis_quat_offset: self.axis_calibration.quat_offset,
+            gravity_ref: self.navigator.gravity_ref(),
+        }
+    }
+
+    /// 恢复保存的姿态零位（设备档案），重力参考随之更新。
+    pub fn set_axis_offset(&mut self, offset: AxisCalibration) {
+        self.axis_calibration = offset;
+        self.navigator
+            .set_gravity_reference(self.axis_calibration.quat_offset);
+    }
+
+    /// 暂停或恢复导航，返回状态是否改变。
+    ///
+    /// 暂停期间仍解析、标定、滤波并输出原始数据，但导航器既不积分也不做 ZUPT，
+    /// 输出重复冻结时的导航状态；恢复时丢弃时间基准，首帧 dt 记 0，
+    /// 不会把暂停期间的时长积分进去。
+    pub fn set_paused(&mut self, paused: bool) -> bool {
+        if self.paused == paused {
+            return false;
+        }
+        self.paused = paused;
+        if !paused {
+            self.navigator.reset_timing();
+        }
+        true
+    }
+
+    /// 导航是否处于暂停状态。
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 丢弃导航时间基准，下一帧 dt 记 0，导航状态保留。
+    ///
+    /// 设备停止上报一段时间后重新开启时调用，避免把中断时长积分进轨迹。
+    pub fn reset_timing(&mut self) {
+        self.navigator.reset_timing();
+    }
+
+    /// 把一个蓝牙通知切分为帧，拼接被 MTU 截断的分片。
+    ///
+    /// 末尾的帧头分片暂存到下一个通知，不在返回值中；返回的各帧逐个交给
+    /// [`process_packet_at`](Self::process_packet_at)。
+    pub fn reassemble<'a>(
+        &mut self,
+        notification: &'a [u8],
+        received_at: Option<Instant>,
+    ) -> Vec<Cow<'a, [u8]>> {
+        if self.faults.drop_packet() {
+            return Vec::new();
+        }
+        self.reassembler.push(notification, received_at)
+    }
+
+    /// 接入故障注入器，处理线程启动时调用；配置重建后保留。
+    pub fn set_fault_injector(&mut self, faults: FaultInjectorHandle) {
+        self.faults = faults;
+    }
+
+    /// 接入阶段中间值采集开关，与录制线程共用；配置重建后保留。
+    pub fn set_stage_capture(&mut self, stage_capture: StageCaptureHandle) {
+        self.stage_capture = stage_capture;
+    }
+
     /// 处理单个原始数据包并输出帧。
     pub fn process_packet(&mut self, packet: &[u8]) -> Option<OutputFrame> {
+        self.process_packet_at(packet, None, None)
+    }
+
+    /// 处理单个原始数据包并输出帧。
+    ///
+    /// * `received_at`: 蓝牙通知到达主机的时刻，诊断据此统计收包间隔与接收到处理的延迟
+    /// * `received_at_ms`: 同一时刻的主机 UNIX 时间 (ms)，参与设备/主机时钟同步；
+    ///   合成包（自检、基准测试）传 `None`
+    pub fn process_packet_at(
+        &mut self,
+        packet: &[u8],
+        received_at: Option<Instant>,
+        received_at_ms: Option<HostTimeMs>,
+    ) -> Option<OutputFrame> {
+        let packet = self.faults.corrupt_byte(packet);
         // 解析原始蓝牙包
-        let raw = match ImuParser::parse(packet) {
+        let raw = match ImuParser::parse(&packet) {
             Ok(sample) => sample,
             Err(e) => {
-                tracing::warn!("IMU 数据解析失败: {:?}", e);
+                self.parse_failures += 1;
+                // 连接不稳时可能每包都失败，限频避免刷屏
+                rate_limit::PER_SECOND.warn("imu_parse", || format!("IMU 数据解析失败: {e}"));
                 return None;
             }
         };
-        self.process_sample_raw(raw)
+        self.process_sample(raw, received_at, received_at_ms)
     }
 
     /// 处理已解析的原始样本并输出帧。
@@ -121,7 +445,43 @@ impl ProcessorPipeline {
     /// 与 [`process_packet`](Self::process_packet) 共享全部后续流水线，
     /// 但跳过蓝牙字节解析。供离线 replay CLI 使用，以便从 SQLite 中读取
     /// 已存储的 [`ImuSampleRaw`] 重跑管线。
-    pub fn process_sample_raw(&mut self, mut raw: ImuSampleRaw) -> Option<OutputFrame> {
+    pub fn process_sample_raw(&mut self, raw: ImuSampleRaw) -> Option<OutputFrame> {
+        self.process_sample(raw, None, None)
+    }
+
+    fn process_sample(
+        &mut self,
+        raw: ImuSampleRaw,
+        received_at: Option<Instant>,
+        received_at_ms: Option<HostTimeMs>,
+    ) -> Option<OutputFrame> {
+        let device_timestamp_ms = raw.timestamp_ms;
+        // 去重/排序必须在标定与滤波之前：二者都带状态，乱序帧会污染递推
+        let SequencerOutput {
+            sample,
+            device_reset,
+        } = self.sequencer.push(raw);
+        if let Some(reset) = device_reset {
+            if reset.rebooted && self.config.sequencer.reset_on_reboot {
+                self.navigator.reset();
+            }
+            self.navigator.reset_timing();
+            self.quat_health.reset();
+            // 固件复位后设备位置从零重新累计
+            self.navigator.reanchor_position();
+            // 设备时间轴重新开始，旧锚点不再可比
+            self.clock_sync.reset();
+            self.sync_tap.reset();
+            self.device_reset = Some(reset);
+        }
+        // 用到达时的时间戳配对：排序缓冲放出的可能是更早到达的帧
+        if let Some(received_at_ms) = received_at_ms {
+            self.clock_sync.observe(device_timestamp_ms, received_at_ms);
+        }
+        let mut raw = sample?;
+        // 安装方向重映射先于零位校准：latest_raw 与后续所有阶段都在外壳系下
+        self.calibration.remap_axes(&mut raw);
+
         let diag_enabled = self.diagnostics_flag.load(Ordering::Relaxed);
         let t_start = if diag_enabled {
             Some(Instant::now())
@@ -130,37 +490,113 @@ impl ProcessorPipeline {
         };
 
         self.latest_raw = Some(raw);
+        self.sample_axis_calibration(&raw);
+        // 敲击看含重力的原始加速度模长，与姿态无关，暂停导航时照常检测
+        self.sync_tap.update(raw.timestamp_ms, raw.accel_with_g);
 
         self.axis_calibration.apply(&mut raw);
+        // 零位偏移与量化后的设备四元数相乘，模长不再精确为 1；
+        // 关闭四元数健康监测时设备姿态原样透传
+        if self.config.quat_health.enabled {
+            raw.quat = self.quat_norm.apply(raw.quat);
+        }
 
         // 对 accel_no_g 应用加速度计偏置修正（IMU 固件输出的去重力加速度仍含偏置）
         raw.accel_no_g -= self.calibration.accel_bias();
 
-        // 处理链：标定 -> 滤波 -> 导航融合 -> 输出
+        // 处理链：标定 -> 滤波 -> 派生信号 -> 导航融合 -> 输出
         let calibrated = self.calibration.update(&raw);
+        // 设备四元数冻结时以陀螺积分姿态代替，输出帧与导航器看到同一姿态
+        raw.quat = self
+            .quat_health
+            .update(raw.timestamp_ms, raw.quat, calibrated.gyro);
+        // 按配置选出积分用的加速度，滤波及之后的阶段只看这一路
+        let mut calibrated = self.accel_source.select(
+            &calibrated,
+            self.calibration.calibrate_accel_no_g(raw.accel_no_g),
+            raw.quat,
+            self.navigator.gravity_ref(),
+        );
+        self.faults.delay(FaultStage::Calibration);
+        if self.faults.inject_nan(FaultStage::Calibration) {
+            calibrated.accel.x = f64::NAN;
+        }
+        // 非有限值进入滤波器会永久污染递推状态，整帧丢弃
+        if !(calibrated.accel.is_finite() && calibrated.gyro.is_finite()) {
+            return self.drop_non_finite("calibration");
+        }
 
-        let filtered = self.filter.apply(&calibrated);
-
-        let nav = self.navigator.update(raw.quat, &filtered);
-
-        // 在线陀螺零偏估计：静止时用标定后的角速度更新零偏
-        if self.navigator.is_static() {
-            self.calibration
-                .update_gyro_bias_online(calibrated.gyro);
+        let mut filtered = self.filter.apply(&calibrated);
+        self.derived.apply(&mut filtered);
+        self.faults.delay(FaultStage::Filter);
+        if self.faults.inject_nan(FaultStage::Filter) {
+            filtered.accel_lp.x = f64::NAN;
+        }
+        if !(filtered.accel_lp.is_finite() && filtered.gyro_lp.is_finite()) {
+            return self.drop_non_finite("filter");
         }
 
+        // ZUPT 前后的导航状态，仅用于阶段采集；暂停时两者都取冻结的输出状态
+        let mut stage_navs = None;
+        let nav = if self.paused {
+            self.navigator.output_nav_state()
+        } else {
+            let nav = self.navigator.update(raw.quat, &filtered);
+            stage_navs = Some((self.navigator.pre_zupt_nav_state(), nav));
+            let mut nav = self
+                .navigator
+                .fuse_baro(nav, raw.baro.map(|baro| baro.pressure_hpa));
+            // 设备位置与姿态走同一零位校准，再按配置的来源选出输出位置
+            let device_nav = self.axis_calibration.to_nav_frame(raw.offset);
+            nav.position = self.navigator.select_position(nav.position, device_nav);
+            self.faults.delay(FaultStage::Navigator);
+            if self.faults.inject_nan(FaultStage::Navigator) {
+                nav.position.x = f64::NAN;
+            }
+            // 导航状态已被污染，重置后从下一帧重新积分
+            if !(nav.position.is_finite() && nav.velocity.is_finite() && nav.attitude.is_finite()) {
+                self.navigator.reset();
+                return self.drop_non_finite("navigator");
+            }
+            // 在线陀螺零偏估计：静止时用标定后的角速度更新零偏
+            if self.navigator.is_static() {
+                self.calibration
+                    .update_gyro_bias_online(calibrated.gyro);
+            }
+            nav
+        };
+
         // —— 诊断采集：仅当开关开启时执行 ——
         if let Some(t_start) = t_start {
+            let sequencer_stats = self.sequencer.stats();
+            let reassembly_stats = self.reassembler.stats();
+            let position_candidates = self.navigator.position_candidates();
             let diag = PipelineDiagnostics {
-                timestamp_ms: raw.timestamp_ms,
+                timestamp_ms: raw.timestamp_ms.as_millis(),
+                // 时间戳排序
+                seq_duplicates: sequencer_stats.duplicates,
+                seq_late: sequencer_stats.late,
+                seq_reordered: sequencer_stats.reordered,
+                seq_device_resets: sequencer_stats.device_resets,
+                seq_gaps: sequencer_stats.gaps,
+                seq_missing_frames: sequencer_stats.missing_frames,
+                // 蓝牙分片重组
+                ble_reassembled: reassembly_stats.reassembled,
+                ble_reassembly_dropped: reassembly_stats.dropped,
                 // 标定阶段
+                cal_bypassed: self.config.calibration.passby,
                 cal_accel_bias: self.calibration.accel_bias(),
                 cal_gyro_bias: self.calibration.gyro_bias(),
                 cal_accel_pre: raw.accel_with_g,
                 cal_accel_post: calibrated.accel,
                 cal_gyro_pre: raw.gyro,
+                cal_gyro_unit: self.calibration.gyro_unit(),
                 cal_gyro_post: calibrated.gyro,
+                // 加速度来源
+                accel_source_residual: self.accel_source.residual(),
+                accel_source_inconsistent: self.accel_source.is_inconsistent(),
                 // 滤波阶段
+                filt_bypassed: self.config.filter.passby,
                 filt_accel_pre: calibrated.accel,
                 filt_accel_post: filtered.accel_lp,
                 filt_gyro_pre: calibrated.gyro,
@@ -172,8 +608,19 @@ impl ProcessorPipeline {
                 zupt_enter_count: self.navigator.zupt_enter_count(),
                 zupt_exit_count: self.navigator.zupt_exit_count(),
                 // 导航阶段
-                nav_dt: self.navigator.current_dt(),
+                nav_dt: self.navigator.current_dt().as_secs(),
+                nav_skipped_integrations: self.navigator.skipped_integrations(),
+                nav_quat_renormalizations: self.quat_norm.renormalizations(),
                 nav_linear_accel: self.navigator.last_linear_accel(),
+                nav_gravity_estimate: self.navigator.gravity_estimate(),
+                nav_plane_removed_velocity: self.navigator.plane_removed_velocity(),
+                nav_deadband_clipped: self.navigator.deadband_clipped(),
+                nav_integrated_position: position_candidates.integrated,
+                nav_device_position: position_candidates.device,
+                // 气压计高度辅助
+                baro_pressure_hpa: raw.baro.map(|baro| baro.pressure_hpa),
+                baro_relative_altitude_m: self.navigator.baro_relative_altitude_m(),
+                baro_correction_m: self.navigator.baro_correction_m(),
                 // 饱和检测：IM948 量程 ±16g，超过 152 m/s² 视为截断
                 accel_saturated: is_accel_saturated(raw.accel_with_g),
                 // ESKF 专属
@@ -191,42 +638,459 @@ impl ProcessorPipeline {
                 perf_record_queue_len: self.queue_probe.record_len() as u32,
                 perf_ble_interval_ms: self
                     .prev_receive_instant
-                    .map(|prev| t_start.duration_since(prev).as_secs_f64() * 1000.0)
+                    .map(|prev| {
+                        received_at
+                            .unwrap_or(t_start)
+                            .saturating_duration_since(prev)
+                            .as_secs_f64()
+                            * 1000.0
+                    })
                     .unwrap_or(0.0),
+                perf_receive_latency_ms: received_at
+                    .map(|at| t_start.saturating_duration_since(at).as_secs_f64() * 1000.0),
+                out_subscriber_rates: Vec::new(),
             };
-            let _ = self.diagnostics_tx.try_send(diag);
+            self.diagnostics_tx.send_latest(diag);
         }
 
-        self.prev_receive_instant = Some(Instant::now());
-        Some(OutputFrame { raw, nav })
+        // 有通知到达时刻时按它计算收包间隔；否则仅诊断开启时取处理开始时刻，关闭时省去一次取时钟
+        self.prev_receive_instant = received_at.or(t_start);
+        let mut frame = OutputFrame {
+            raw,
+            nav,
+            motion_state: self.navigator.motion_state(),
+            zupt: self.navigator.zupt_state(),
+            position_source: self.navigator.position_source(),
+            jerk: filtered.jerk,
+            ang_accel: filtered.ang_accel,
+            position_sigma_m: self.navigator.position_sigma_m(),
+            clock_sync: self.clock_sync.estimate(),
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: self.navigator.baro_relative_altitude_m(),
+            debug_vectors: self
+                .config
+                .debug_vectors
+                .enabled
+                .then(|| self.navigator.debug_vectors()),
+            stages: self.stage_capture.enabled().then(|| {
+                let (nav_pre_zupt, nav_post_zupt) = stage_navs.unwrap_or((nav, nav));
+                StageSnapshot {
+                    calibrated_accel: calibrated.accel,
+                    calibrated_gyro: calibrated.gyro,
+                    filtered_accel: filtered.accel_lp,
+                    filtered_gyro: filtered.gyro_lp,
+                    linear_accel: self.navigator.last_linear_accel(),
+                    nav_pre_zupt,
+                    nav_post_zupt,
+                    is_static: self.navigator.is_static(),
+                }
+            }),
+        };
+        // 对齐看旋转前的轨迹，运动分段与输出看旋转后的轨迹
+        if !self.paused {
+            if let Some(aligned) = self.heading.update(&frame) {
+                if self.wizard.snapshot().flow == Some(CalibrationFlow::HeadingAlign) {
+                    self.wizard.transition(
+                        CalibrationFlow::HeadingAlign,
+                        CalibrationWizardState::Done {
+                            result: CalibrationWizardResult::HeadingAlign(aligned),
+                        },
+                    );
+                }
+            }
+        }
+        self.heading.apply(&mut frame);
+        if !self.paused {
+            if let Some(segment) = self.segment_detector.update(&frame) {
+                self.segment = Some(segment);
+            }
+        }
+        Some(frame)
+    }
+
+    /// 在实时状态的副本上演算一个样本，返回各阶段的输入、输出与参数。
+    ///
+    /// 各带状态的阶段先克隆再推进，管线本身不变；样本直接进入处理链，
+    /// 不经时间戳排序，也不参与零位校准采样与诊断采集。
+    pub fn explain_sample(&self, sample: ImuSampleRaw) -> SampleExplanation {
+        let mut stages = Vec::with_capacity(12);
+
+        let mut raw = sample;
+        self.calibration.remap_axes(&mut raw);
+        stages.push(StageExplanation::new(
+            "remap_axes",
+            &sample,
+            &raw,
+            json!({
+                "accel_map": self.config.calibration.accel_map,
+                "gyro_map": self.config.calibration.gyro_map,
+            }),
+        ));
+
+        let remapped = raw;
+        self.axis_calibration.apply(&mut raw);
+        let composed_norm = raw.quat.length();
+        if self.config.quat_health.enabled {
+            raw.quat = self.quat_norm.clone().apply(raw.quat);
+        }
+        stages.push(StageExplanation::new(
+            "axis_calibration",
+            &remapped,
+            &raw,
+            json!({
+                "angle_offset": self.axis_calibration.angle_offset,
+                "quat_offset": self.axis_calibration.quat_offset,
+                "composed_norm": composed_norm,
+            }),
+        ));
+
+        let mut calibration = self.calibration.clone();
+        raw.accel_no_g -= calibration.accel_bias();
+        let calibrated = calibration.update(&raw);
+        stages.push(
+            StageExplanation::new(
+                "calibration",
+                &raw,
+                &calibrated,
+                json!({
+                    "passby": self.config.calibration.passby,
+                    "gyro_unit": calibration.gyro_unit(),
+                    "accel_bias": calibration.accel_bias(),
+                    "gyro_bias": calibration.gyro_bias(),
+                    "accel_matrix": self.config.calibration.accel_matrix,
+                    "gyro_matrix": self.config.calibration.gyro_matrix,
+                }),
+            )
+            .bypassed(self.config.calibration.passby),
+        );
+
+        let device_quat = raw.quat;
+        let mut quat_health = self.quat_health.clone();
+        raw.quat = quat_health.update(raw.timestamp_ms, raw.quat, calibrated.gyro);
+        stages.push(
+            StageExplanation::new(
+                "quat_health",
+                &json!({ "quat": device_quat, "gyro": calibrated.gyro }),
+                &raw.quat,
+                json!({
+                    "config": self.config.quat_health,
+                    "substituting": quat_health.is_substituting(),
+                }),
+            )
+            .bypassed(!self.config.quat_health.enabled),
+        );
+
+        let mut accel_source = self.accel_source.clone();
+        let accel_no_g = calibration.calibrate_accel_no_g(raw.accel_no_g);
+        let with_g = calibrated;
+        let calibrated =
+            accel_source.select(&with_g, accel_no_g, raw.quat, self.navigator.gravity_ref());
+        stages.push(StageExplanation::new(
+            "accel_source",
+            &json!({ "with_g": with_g, "accel_no_g": accel_no_g, "attitude": raw.quat }),
+            &calibrated,
+            json!({
+                "config": self.config.accel_source,
+                "gravity_ref": self.navigator.gravity_ref(),
+                "residual": accel_source.residual(),
+                "inconsistent": accel_source.is_inconsistent(),
+            }),
+        ));
+
+        let mut filtered = self.filter.clone().apply(&calibrated);
+        stages.push(
+            StageExplanation::new("filter", &calibrated, &filtered, json!(self.config.filter))
+                .bypassed(self.config.filter.passby),
+        );
+
+        let unsmoothed = filtered;
+        self.derived.clone().apply(&mut filtered);
+        stages.push(
+            StageExplanation::new(
+                "derived",
+                &unsmoothed,
+                &json!({ "jerk": filtered.jerk, "ang_accel": filtered.ang_accel }),
+                json!(self.config.derived),
+            )
+            .bypassed(!self.config.derived.enabled),
+        );
+
+        let mut navigator = self.navigator.clone();
+        let nav_params = json!({
+            "paused": self.paused,
+            "navigator_impl": self.config.navigator_impl,
+            "was_static": navigator.is_static(),
+            "gravity_ref": navigator.gravity_ref(),
+            "gravity_estimate": navigator.gravity_estimate(),
+            "integration_gap_limit_ms": navigator.integration_gap_limit_ms(),
+            "trajectory": self.config.trajectory,
+            "zupt": self.config.zupt,
+            "plane_constraint": self.config.plane_constraint,
+            "position_source": self.config.position_source,
+        });
+        let mut baro_input = None;
+        let nav = if self.paused {
+            navigator.output_nav_state()
+        } else {
+            let nav = navigator.update(raw.quat, &filtered);
+            baro_input = Some(nav);
+            let mut nav = navigator.fuse_baro(nav, raw.baro.map(|baro| baro.pressure_hpa));
+            let device_nav = self.axis_calibration.to_nav_frame(raw.offset);
+            nav.position = navigator.select_position(nav.position, device_nav);
+            nav
+        };
+        let mut frame = OutputFrame {
+            raw,
+            nav,
+            motion_state: navigator.motion_state(),
+            zupt: navigator.zupt_state(),
+            position_source: navigator.position_source(),
+            jerk: filtered.jerk,
+            ang_accel: filtered.ang_accel,
+            position_sigma_m: navigator.position_sigma_m(),
+            clock_sync: self.clock_sync.estimate(),
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: navigator.baro_relative_altitude_m(),
+            debug_vectors: self
+                .config
+                .debug_vectors
+                .enabled
+                .then(|| navigator.debug_vectors()),
+            stages: None,
+        };
+        stages.push(
+            StageExplanation::new(
+                "navigator",
+                &json!({ "attitude": raw.quat, "sample": filtered }),
+                &json!({
+                    "nav": nav,
+                    "motion_state": frame.motion_state,
+                    "zupt": frame.zupt,
+                    "position_source": frame.position_source,
+                    "dt_s": navigator.current_dt(),
+                    "linear_accel": navigator.last_linear_accel(),
+                    "deadband_clipped": navigator.deadband_clipped(),
+                    "position_candidates": navigator.position_candidates(),
+                }),
+                nav_params,
+            )
+            .bypassed(self.config.trajectory.passby),
+        );
+
+        stages.push(
+            StageExplanation::new(
+                "baro",
+                &json!({
+                    "baro": raw.baro,
+                    "position_z": baro_input.map(|nav| nav.position.z),
+                }),
+                &json!({
+                    "relative_altitude_m": navigator.baro_relative_altitude_m(),
+                    "correction_m": navigator.baro_correction_m(),
+                }),
+                json!({
+                    "config": self.config.baro,
+                    "reference": navigator.baro_reference(),
+                }),
+            )
+            .bypassed(!self.config.baro.enabled),
+        );
+
+        let unaligned = frame.nav;
+        let mut heading = self.heading.clone();
+        if !self.paused {
+            heading.update(&frame);
+        }
+        heading.apply(&mut frame);
+        stages.push(
+            StageExplanation::new(
+                "heading",
+                &unaligned,
+                &json!({ "nav": frame.nav, "yaw_deg": frame.heading_yaw_deg }),
+                json!({ "config": self.config.heading_align, "armed": self.heading.is_armed() }),
+            )
+            .bypassed(!self.config.heading_align.enabled),
+        );
+
+        let segment = if self.paused {
+            None
+        } else {
+            self.segment_detector.clone().update(&frame)
+        };
+        stages.push(
+            StageExplanation::new(
+                "segment",
+                &json!({ "motion_state": frame.motion_state, "position": frame.nav.position }),
+                &segment,
+                json!(self.config.segment),
+            )
+            .bypassed(!self.config.segment.enabled),
+        );
+
+        let tap = self
+            .sync_tap
+            .clone()
+            .update(remapped.timestamp_ms, remapped.accel_with_g);
+        stages.push(
+            StageExplanation::new(
+                "sync_tap",
+                &json!({ "accel_with_g": remapped.accel_with_g }),
+                &tap,
+                json!(self.config.sync_tap),
+            )
+            .bypassed(!self.config.sync_tap.enabled),
+        );
+
+        SampleExplanation {
+            timestamp_ms: raw.timestamp_ms.as_millis(),
+            paused: self.paused,
+            stages,
+            output: OutputBuilder::build(&frame),
+        }
+    }
+
+    /// 最近一帧是否处于静止（ZUPT）状态。
+    pub fn is_static(&self) -> bool {
+        self.navigator.is_static()
+    }
+
+    /// auto_gravity 估计的重力模长 (m/s²)。
+    pub fn gravity_estimate(&self) -> Option<f64> {
+        self.navigator.gravity_estimate()
+    }
+
+    /// 导航因帧间隔过大而跳过积分的累计次数。
+    pub fn skipped_integrations(&self) -> u64 {
+        self.navigator.skipped_integrations()
+    }
+
+    /// 解析失败的累计包数。
+    pub fn parse_failures(&self) -> u64 {
+        self.parse_failures
+    }
+
+    /// 因输出非有限值而丢弃的累计帧数。
+    pub fn non_finite_samples(&self) -> u64 {
+        self.non_finite_samples
+    }
+
+    /// 记录一次非有限值丢帧。
+    fn drop_non_finite(&mut self, stage: &'static str) -> Option<OutputFrame> {
+        self.non_finite_samples += 1;
+        rate_limit::PER_SECOND.warn("non_finite_sample", || {
+            format!("{stage} 阶段输出非有限值，已丢弃该帧")
+        });
+        None
+    }
+
+    /// 检测到设备四元数冻结的累计次数。
+    pub fn quat_freeze_count(&self) -> u64 {
+        self.quat_health.freeze_count()
+    }
+
+    /// 检测到两路加速度不一致的累计次数。
+    pub fn accel_inconsistency_count(&self) -> u64 {
+        self.accel_source.inconsistent_count()
+    }
+
+    /// 时间戳排序累计统计。
+    pub fn sequencer_stats(&self) -> SequencerStats {
+        self.sequencer.stats()
+    }
+
+    /// 取走最近一次检测到的设备复位事件。
+    pub fn take_device_reset(&mut self) -> Option<DeviceReset> {
+        self.device_reset.take()
+    }
+
+    /// 取走最近一次结束的运动分段。
+    pub fn take_segment(&mut self) -> Option<MotionSegment> {
+        self.segment.take()
+    }
+
+    /// 取走最近一次敲击同步事件。
+    pub fn take_sync_tap(&mut self) -> Option<SyncTap> {
+        self.sync_tap.take_event()
+    }
+
+    /// 取走最近一次世界系原点锚定事件。
+    pub fn take_origin_anchored(&mut self) -> Option<OriginAnchored> {
+        self.navigator.take_origin_anchored()
+    }
+
+    /// 取走最近一次航向对齐事件。
+    pub fn take_heading_aligned(&mut self) -> Option<HeadingAligned> {
+        self.heading.take_event()
+    }
+
+    /// 当前标定向导状态。
+    pub fn calibration_state(&self) -> &CalibrationWizardSnapshot {
+        self.wizard.snapshot()
+    }
+
+    /// 取走尚未发布的标定向导状态变化，按发生先后排列。
+    pub fn take_calibration_transitions(&mut self) -> Vec<CalibrationWizardSnapshot> {
+        self.wizard.take_transitions()
+    }
+
+    /// 当前施加到输出的航向对齐旋转 (°)，未对齐时为 `None`。
+    pub fn heading_yaw_deg(&self) -> Option<f64> {
+        self.heading.yaw_deg()
+    }
+
+    /// 直接设定输出的航向对齐旋转 (°)，离线重跑录制时复现录制时的输出。
+    pub fn set_heading_yaw_deg(&mut self, yaw_deg: Option<f64>) {
+        self.heading.set_yaw_deg(yaw_deg);
+    }
+
+    /// 取走最近一次设备四元数冻结或恢复事件。
+    pub fn take_quat_freeze(&mut self) -> Option<QuatFreezeEvent> {
+        self.quat_health.take_event()
+    }
+
+    /// 当前设备/主机时钟同步结果，锚点不足时为 `None`。
+    pub fn clock_sync(&self) -> Option<ClockSyncEstimate> {
+        self.clock_sync.estimate()
+    }
+
+    /// 取走最近一次加速度不一致或恢复事件。
+    pub fn take_accel_inconsistency(&mut self) -> Option<AccelInconsistencyEvent> {
+        self.accel_source.take_event()
     }
 
     /// 重置内部状态
     pub fn reset(&mut self) {
+        self.reassembler.reset();
+        self.sequencer.reset();
+        self.device_reset = None;
         self.axis_calibration.reset();
         self.calibration.reset();
+        self.quat_health.reset();
+        self.accel_source.reset();
         self.filter.reset();
+        self.derived.reset();
         self.navigator.reset();
+        self.heading.reset();
+        self.settle_heading_wizard();
+        self.segment_detector.reset();
+        self.segment = None;
+        self.sync_tap.reset();
         self.latest_raw = None;
         self.prev_receive_instant = None;
+        self.clock_sync.reset();
     }
 
     /// 响应姿态零位校准请求。
+    ///
+    /// 零位校准不立即生效：先按 `calibration_quality.window_ms` 采集原始数据评分，
+    /// 窗口填满后在 [`sample_axis_calibration`](Self::sample_axis_calibration) 中应用并回复。
     pub fn handle_calibration_request(&mut self, request: CorrectionRequest) {
         match request {
             CorrectionRequest::SetAxis { respond_to } => {
-                let result = match self.latest_raw {
-                    Some(raw) => {
-                        self.axis_calibration.update_from_raw(&raw);
-                        self.navigator
-                            .set_gravity_reference(self.axis_calibration.quat_offset);
-                        Ok(())
-                    }
-                    None => Err("在前未接收到任何原始数据包，无法进行校准"),
-                };
-                if respond_to.send(result).is_err() {
-                    tracing::error!("标定 response 接受端在发送前已被丢弃");
-                };
+                self.start_axis_calibration(AxisCalibrationReply::Axis(respond_to));
+            }
+            CorrectionRequest::CalibrateAll { respond_to } => {
+                self.start_axis_calibration(AxisCalibrationReply::All(respond_to));
             }
             CorrectionRequest::SetPosition {
                 position,
@@ -237,6 +1101,175 @@ impl ProcessorPipeline {
                     tracing::error!("位置校正 response 接受端在发送前已被丢弃");
                 };
             }
+            CorrectionRequest::ReAnchorOrigin { respond_to } => {
+                self.navigator.re_anchor_origin();
+                if respond_to.send(Ok(())).is_err() {
+                    tracing::error!("原点重新锚定 response 接受端在发送前已被丢弃");
+                };
+            }
+            CorrectionRequest::ArmHeadingFromMotion { respond_to } => {
+                let result = self.heading.arm();
+                let state = match result {
+                    Ok(()) => CalibrationWizardState::WaitingForMotion,
+                    Err(reason) => CalibrationWizardState::Failed {
+                        reason: reason.to_string(),
+                    },
+                };
+                self.wizard.transition(CalibrationFlow::HeadingAlign, state);
+                if respond_to.send(result).is_err() {
+                    tracing::error!("航向对齐 response 接受端在发送前已被丢弃");
+                };
+            }
+        }
+    }
+
+    /// 开始采集零位校准的质量评估窗口；无数据或已有窗口在采样时直接回复错误。
+    fn start_axis_calibration(&mut self, respond_to: AxisCalibrationReply) {
+        let error = if self.latest_raw.is_none() {
+            Some(NO_RAW_DATA_ERROR)
+        } else if self
+            .pending_axis
+            .as_ref()
+            .is_some_and(|pending| !pending.respond_to.is_closed())
+        {
+            // 请求方已放弃的窗口（如数据中断导致超时）直接被新请求替换
+            Some(CALIBRATION_BUSY_ERROR)
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            // 采样中的窗口不受打扰，向导状态保持不变
+            if error == NO_RAW_DATA_ERROR {
+                self.wizard.transition(
+                    respond_to.flow(),
+                    CalibrationWizardState::Failed {
+                        reason: error.to_string(),
+                    },
+                );
+            }
+            respond_to.send_error(error);
+            return;
+        }
+        let gravity = self
+            .gravity_estimate()
+            .unwrap_or(self.config.global.gravity);
+        let sampler = CalibrationQualitySampler::new(
+            self.config.calibration_quality,
+            gravity,
+            self.config.calibration.gyro_unit,
+        );
+        self.wizard
+            .stillness_progress(respond_to.flow(), 0, sampler.required_ms());
+        self.pending_axis = Some(PendingAxisCalibration {
+            sampler,
+            respond_to,
+        });
+    }
+
+    /// 配置重建或重置清除了航向对齐的武装状态时，等待运动的向导回到空闲。
+    fn settle_heading_wizard(&mut self) {
+        if *self.wizard.state() == CalibrationWizardState::WaitingForMotion
+            && !self.heading.is_armed()
+        {
+            self.wizard.idle();
+        }
+    }
+
+    /// 把一帧原始样本计入采样中的零位校准窗口，窗口填满后评分、按结果应用并回复。
+    ///
+    /// 在本帧进入标定之前调用，校准结果对本帧即已生效。
+    fn sample_axis_calibration(&mut self, raw: &ImuSampleRaw) {
+        let Some(pending) = &mut self.pending_axis else {
+            return;
+        };
+        let full = pending.sampler.push(raw);
+        self.wizard.stillness_progress(
+            pending.respond_to.flow(),
+            pending.sampler.elapsed_ms(),
+            pending.sampler.required_ms(),
+        );
+        if !full {
+            return;
+        }
+        let Some(PendingAxisCalibration {
+            sampler,
+            respond_to,
+        }) = self.pending_axis.take()
+        else {
+            return;
+        };
+        let flow = respond_to.flow();
+        if respond_to.is_closed() {
+            // 请求方已超时放弃，不再悄悄改变零位
+            tracing::warn!("零位校准请求方已放弃，丢弃采样结果");
+            self.wizard.transition(
+                flow,
+                CalibrationWizardState::Failed {
+                    reason: "请求方已放弃等待，采样结果未应用".to_string(),
+                },
+            );
+            return;
+        }
+        self.wizard
+            .transition(flow, CalibrationWizardState::Computing);
+        let mut quality = sampler.quality();
+        if let AxisCalibrationReply::All(_) = respond_to {
+            // 一次性校准同时把当前位置定为原点，静止校验不受 reject_below_min 影响
+            quality.applied = quality.score >= quality.min_score;
+        }
+        if quality.applied {
+            self.axis_calibration.update_from_raw(raw);
+            self.navigator
+                .set_gravity_reference(self.axis_calibration.quat_offset);
+            self.navigator.reanchor_position();
+            // 积分姿态属于旧零位，冻结判定随新零位从头开始
+            self.quat_health.rebase();
+            // 世界系航向随零位改变，旧的对齐角不再对应首段运动方向
+            self.heading.rebase();
+        } else {
+            tracing::warn!(
+                score = quality.score,
+                min_score = quality.min_score,
+                "零位校准质量不足，未应用"
+            );
+        }
+        let (result, sent) = match respond_to {
+            AxisCalibrationReply::Axis(tx) => (
+                CalibrationWizardResult::Axis(quality),
+                tx.send(Ok(quality)).is_ok(),
+            ),
+            AxisCalibrationReply::All(tx) => {
+                let offsets = quality.applied.then(|| {
+                    // 原点、速度与 ZUPT 锁定点一并清零，下一帧从静止的原点开始
+                    self.navigator.set_position(DVec3::ZERO);
+                    self.navigator.reset_zupt();
+                    CalibrationOffsets {
+                        quat_offset: self.axis_calibration.quat_offset,
+                        angle_offset: self.axis_calibration.angle_offset,
+                        heading_offset_deg: self.axis_calibration.angle_offset.z,
+                        gravity_ref: self.navigator.gravity_ref(),
+                    }
+                });
+                let full = FullCalibration { quality, offsets };
+                (
+                    CalibrationWizardResult::CalibrateAll(full),
+                    tx.send(Ok(full)).is_ok(),
+                )
+            }
+        };
+        let state = if quality.applied {
+            CalibrationWizardState::Done { result }
+        } else {
+            CalibrationWizardState::Failed {
+                reason: format!(
+                    "零位校准质量不足：评分 {:.1} 低于 {:.1}，设备需保持静止",
+                    quality.score, quality.min_score
+                ),
+            }
+        };
+        self.wizard.transition(flow, state);
+        if !sent {
+            tracing::error!("标定 response 接受端在发送前已被丢弃");
         }
     }
 }
@@ -259,6 +1292,11 @@ impl ProcessorPipelineConfig {
         local
     }
 
+    /// 从指定 TOML 文件加载配置。
+    pub fn load_from_path(path: &Path) -> anyhow::Result<Self> {
+        read_config_with_modified(path).map(|(config, _)| config)
+    }
+
     /// 从默认路径加载配置与修改时间。
     pub fn load_from_default_paths_with_modified() -> anyhow::Result<PipelineConfigSnapshot> {
         let path = Self::default_config_path();
@@ -271,6 +1309,18 @@ impl ProcessorPipelineConfig {
             modified,
         })
     }
+
+    /// 计算配置内容哈希（十六进制）。
+    ///
+    /// 基于 TOML 序列化结果，仅用于同一进程内判断配置是否变化，不保证跨版本稳定。
+    pub fn content_hash(&self) -> String {
+        use std::hash::{Hash, Hasher};
+
+        let content = toml::to_string(self).unwrap_or_default();
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        content.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
 }
 
 fn read_config_with_modified(path: &Path) -> anyhow::Result<(ProcessorPipelineConfig, SystemTime)> {
@@ -280,7 +1330,1374 @@ fn read_config_with_modified(path: &Path) -> anyhow::Result<(ProcessorPipelineCo
         .with_context(|| format!("读取文件元数据失败: {}", path.display()))?
         .modified()
         .with_context(|| format!("读取文件修改时间失败: {}", path.display()))?;
-    let config = toml::from_str::<ProcessorPipelineConfig>(&content)
+    let config = ProcessorPipelineConfig::from_toml_str(&content)
         .with_context(|| format!("解析 TOML 配置失败: {}", path.display()))?;
     Ok((config, modified))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{atomic::AtomicBool, Arc},
+        time::Instant,
+    };
+
+    use math_f64::{DQuat, DVec2, DVec3};
+    use serde_json::json;
+
+    use crate::processor::{
+        accel_source::AccelSource,
+        calibration::{
+            logic::euler_zyx_deg, AxisMapSpec, AxisRemap, CalibrationFlow, CalibrationStateSlot,
+            CalibrationWizardResult, CalibrationWizardSnapshot, CalibrationWizardState,
+            CorrectionRequest, GyroUnit,
+        },
+        fanout::{SubscriberKind, SubscriberRate},
+        navigator::types::{MotionState, NavState, NavigatorImplType, ZuptImpl},
+        output::{OutputBuilder, OutputFrame},
+        parser::{ImuParser, ImuSampleRaw},
+        pipeline::{
+            diagnostics::{
+                diagnostics_channel, relay_diagnostics, PipelineDiagnostics, QueueProbe,
+            },
+            ConfigApplyMode, ProcessorPipeline, ProcessorPipelineConfig,
+        },
+        segment::SegmentConfig,
+        sequencer::SequencerConfig,
+        shared::{nominal_sample_interval_ms, DeviceTimeMs},
+    };
+
+    /// 静止放置的合成样本。
+    fn static_sample(timestamp_ms: u64) -> ImuSampleRaw {
+        ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        }
+    }
+
+    fn build_pipeline(
+        diag_enabled: bool,
+        capacity: usize,
+    ) -> (ProcessorPipeline, flume::Receiver<PipelineDiagnostics>) {
+        build_pipeline_with(ProcessorPipelineConfig::default(), diag_enabled, capacity)
+    }
+
+    fn build_pipeline_with(
+        config: ProcessorPipelineConfig,
+        diag_enabled: bool,
+        capacity: usize,
+    ) -> (ProcessorPipeline, flume::Receiver<PipelineDiagnostics>) {
+        let (upstream_tx, upstream_rx) = flume::unbounded();
+        std::mem::forget(upstream_tx);
+        let (downstream_tx, _) = flume::unbounded();
+        let (record_tx, _) = flume::unbounded();
+        let (diag_tx, diag_rx) = diagnostics_channel(capacity);
+        let pipeline = ProcessorPipeline::new(
+            config,
+            Arc::new(AtomicBool::new(diag_enabled)),
+            diag_tx,
+            QueueProbe::new(upstream_rx, downstream_tx, record_tx),
+        );
+        (pipeline, diag_rx)
+    }
+
+    #[test]
+    fn full_diagnostics_channel_drops_oldest_frames() {
+        let (mut pipeline, diag_rx) = build_pipeline(true, 8);
+        for i in 0..50 {
+            pipeline.process_sample_raw(static_sample(i * 4));
+        }
+        let timestamps: Vec<u64> = diag_rx.drain().map(|d| d.timestamp_ms).collect();
+        let expected: Vec<u64> = (42..50).map(|i| i * 4).collect();
+        assert_eq!(timestamps, expected);
+    }
+
+    #[test]
+    fn relayed_diagnostics_match_direct_serialization() {
+        let (mut pipeline, diag_rx) = build_pipeline(true, 256);
+        for i in 0..100 {
+            pipeline.process_sample_raw(static_sample(i * 4));
+        }
+        let rate = SubscriberRate {
+            id: 3,
+            kind: SubscriberKind::Full,
+            rate_hz: Some(60.0),
+            intensity: None,
+        };
+        let direct: Vec<PipelineDiagnostics> = diag_rx
+            .drain()
+            .map(|d| PipelineDiagnostics {
+                out_subscriber_rates: vec![rate],
+                ..d
+            })
+            .collect();
+        assert_eq!(direct.len(), 100);
+
+        // 经真实的诊断通道与订阅端序列化线程
+        let (sender, rx) = diagnostics_channel(256);
+        let serializer = std::thread::spawn(move || {
+            let mut payloads = Vec::new();
+            relay_diagnostics(
+                &rx,
+                || vec![rate],
+                |json| {
+                    payloads.push(json.into_bytes());
+                    true
+                },
+            );
+            payloads
+        });
+        for d in &direct {
+            sender.send_latest(d.clone());
+        }
+        drop(sender);
+        let payloads = serializer.join().unwrap();
+
+        let mut offset = 0;
+        for payload in payloads {
+            let len = serde_json::from_slice::<Vec<serde_json::Value>>(&payload)
+                .unwrap()
+                .len();
+            let expected = serde_json::to_vec(&direct[offset..offset + len]).unwrap();
+            assert_eq!(payload, expected);
+            offset += len;
+        }
+        assert_eq!(offset, direct.len());
+    }
+
+    /// 诊断开启时处理线程只多一次结构体填充和 `try_send`，开销应在微秒级。
+    ///
+    /// 依赖墙钟计时，负载高的机器上不稳定，需要时手动运行 `cargo test -- --ignored`。
+    #[test]
+    #[ignore = "依赖墙钟计时"]
+    fn diagnostics_overhead_on_processing_thread_is_small() {
+        const FRAMES: u64 = 2000;
+        let mean_us = |diag_enabled: bool| {
+            let (mut pipeline, _diag_rx) = build_pipeline(diag_enabled, 64);
+            let start = Instant::now();
+            for i in 0..FRAMES {
+                pipeline.process_sample_raw(static_sample(i * 4));
+            }
+            start.elapsed().as_secs_f64() * 1e6 / FRAMES as f64
+        };
+        let off = mean_us(false);
+        let on = mean_us(true);
+        println!("process_sample_raw: 诊断关闭 {off:.2} µs/帧, 开启 {on:.2} µs/帧");
+        assert!(on < off + 20.0, "诊断开销过大: off={off:.2}µs on={on:.2}µs");
+    }
+
+    /// 1 kHz 输入下单包处理的中位数预算 (µs)。
+    ///
+    /// CI 机器较慢时可通过环境变量 `IMU_VIS_PACKET_BUDGET_US` 放宽。
+    const DEFAULT_PACKET_BUDGET_US: f64 = 20.0;
+
+    /// 1 kHz 合成运动数据，编码成 0x11 数据包以包含解析开销。
+    fn synthetic_packet(i: u64) -> Vec<u8> {
+        let t = i as f64 / 1000.0;
+        let phase = 2.0 * std::f64::consts::PI * 1.5 * t;
+        let accel_xy = DVec2::new(0.8 * phase.sin(), 0.3 * phase.cos());
+        ImuParser::encode(&ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(i),
+            accel_no_g: accel_xy.extend(0.0),
+            accel_with_g: accel_xy.extend(9.80665),
+            gyro: DVec3::new(20.0 * phase.cos(), 5.0 * phase.sin(), 0.0),
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        })
+    }
+
+    #[test]
+    fn process_packet_meets_per_packet_budget() {
+        const PACKETS: u64 = 100_000;
+        let budget_us = std::env::var("IMU_VIS_PACKET_BUDGET_US")
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(DEFAULT_PACKET_BUDGET_US);
+
+        let packets: Vec<Vec<u8>> = (0..PACKETS).map(synthetic_packet).collect();
+        let (mut pipeline, _diag_rx) = build_pipeline(false, 8);
+        let mut elapsed_ns: Vec<u64> = Vec::with_capacity(packets.len());
+        for packet in &packets {
+            let start = Instant::now();
+            let frame = std::hint::black_box(pipeline.process_packet(packet));
+            elapsed_ns.push(start.elapsed().as_nanos() as u64);
+            assert!(frame.is_some());
+        }
+
+        elapsed_ns.sort_unstable();
+        let percentile_us = |p: f64| {
+            let index = ((elapsed_ns.len() - 1) as f64 * p) as usize;
+            elapsed_ns[index] as f64 / 1000.0
+        };
+        let (median, p99) = (percentile_us(0.5), percentile_us(0.99));
+        println!("process_packet: 中位数 {median:.2} µs, p99 {p99:.2} µs ({PACKETS} 包)");
+        assert!(
+            median < budget_us,
+            "单包处理中位数 {median:.2} µs 超出预算 {budget_us} µs"
+        );
+    }
+
+    /// 静止 → x 向加速/减速脉冲 → 静止，4 ms 一帧。
+    fn motion_segment(start_ms: u64) -> Vec<ImuSampleRaw> {
+        (0..200u64)
+            .map(|i| {
+                let ax = match i {
+                    60..=84 => 1.0,
+                    85..=109 => -1.0,
+                    _ => 0.0,
+                };
+                ImuSampleRaw {
+                    accel_no_g: DVec3::new(ax, 0.0, 0.0),
+                    accel_with_g: DVec3::new(ax, 0.0, 9.80665),
+                    ..static_sample(start_ms + i * 4)
+                }
+            })
+            .collect()
+    }
+
+    /// 逐帧送入管线，返回放行帧的 (时间戳, 位置)。
+    fn integrate(pipeline: &mut ProcessorPipeline, samples: &[ImuSampleRaw]) -> Vec<(u64, DVec3)> {
+        samples
+            .iter()
+            .filter_map(|&sample| pipeline.process_sample_raw(sample))
+            .map(|frame| (frame.raw.timestamp_ms.as_millis(), frame.nav.position))
+            .collect()
+    }
+
+    fn assert_same_trajectory(actual: &[(u64, DVec3)], expected: &[(u64, DVec3)]) {
+        let actual_ts: Vec<u64> = actual.iter().map(|(ts, _)| *ts).collect();
+        let expected_ts: Vec<u64> = expected.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(actual_ts, expected_ts);
+        let (last, reference) = (actual.last().unwrap().1, expected.last().unwrap().1);
+        assert!(reference.x > 0.003, "参考轨迹应有明显位移: {reference:?}");
+        assert!(
+            (last - reference).length() < 1e-9,
+            "位置偏离参考: {last:?} vs {reference:?}"
+        );
+    }
+
+    #[test]
+    fn duplicate_and_swapped_packets_match_in_order_reference() {
+        let config = ProcessorPipelineConfig {
+            sequencer: SequencerConfig {
+                reorder: true,
+                ..SequencerConfig::default()
+            },
+            ..ProcessorPipelineConfig::default()
+        };
+        let ordered = motion_segment(1000);
+        let (mut reference, _) = build_pipeline_with(config.clone(), false, 8);
+        let expected = integrate(&mut reference, &ordered);
+
+        // 运动段内：两帧重复到达、一对相邻帧互换
+        let mut perturbed = ordered.clone();
+        perturbed.insert(71, ordered[70]);
+        perturbed.insert(96, ordered[94]);
+        perturbed.swap(80, 81);
+        let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+        let actual = integrate(&mut pipeline, &perturbed);
+
+        assert_same_trajectory(&actual, &expected);
+        let stats = pipeline.sequencer_stats();
+        assert_eq!((stats.duplicates, stats.reordered, stats.late), (2, 1, 0));
+    }
+
+    #[test]
+    fn duplicate_packets_are_dropped_without_lookahead() {
+        let ordered = motion_segment(1000);
+        let (mut reference, _) = build_pipeline(false, 8);
+        let expected = integrate(&mut reference, &ordered);
+
+        let mut perturbed = ordered.clone();
+        perturbed.insert(71, ordered[70]);
+        perturbed.insert(92, ordered[90]);
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        let actual = integrate(&mut pipeline, &perturbed);
+
+        assert_same_trajectory(&actual, &expected);
+        assert_eq!(pipeline.sequencer_stats().duplicates, 2);
+    }
+
+    #[test]
+    fn backwards_timestamp_jump_is_treated_as_device_reset() {
+        // 参考：两段运动在同一条时间线上连续
+        let mut ordered = motion_segment(1000);
+        ordered.extend(motion_segment(1000 + 200 * 4));
+        let (mut reference, _) = build_pipeline(false, 8);
+        let expected = integrate(&mut reference, &ordered);
+
+        // 实际：第二段设备复位后时间戳从 0 重新计数（复位发生在静止期）
+        let mut restarted = motion_segment(1000);
+        restarted.extend(motion_segment(0));
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        let actual = integrate(&mut pipeline, &restarted);
+
+        assert_eq!(actual.len(), expected.len());
+        let (last, reference_last) = (actual.last().unwrap().1, expected.last().unwrap().1);
+        assert!(
+            reference_last.x > 0.006,
+            "参考轨迹应有两段位移: {reference_last:?}"
+        );
+        // 复位帧 dt 记 0，静止期位置锁定少收敛一步，只允许这一点残差
+        assert!(
+            (last - reference_last).length() < 1e-6,
+            "位置偏离参考: {last:?} vs {reference_last:?}"
+        );
+        assert_eq!(pipeline.sequencer_stats().device_resets, 1);
+        let reset = pipeline.take_device_reset().expect("应记录设备复位");
+        assert_eq!(
+            (reset.previous_timestamp_ms, reset.timestamp_ms),
+            (1000 + 199 * 4, 0)
+        );
+        assert!(pipeline.take_device_reset().is_none());
+    }
+
+    #[test]
+    fn only_reboot_is_reported_as_device_reboot() {
+        // 32 位计数回绕：设备时间戳按 u32 截断，管线输出与未回绕的参考一致
+        let wrap = 1u64 << 32;
+        let unwrapped = motion_segment(wrap - 400);
+        let (mut reference, _) = build_pipeline(false, 8);
+        let expected = integrate(&mut reference, &unwrapped);
+        let wrapped: Vec<ImuSampleRaw> = unwrapped
+            .iter()
+            .map(|&sample| ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(sample.timestamp_ms.as_millis() % wrap),
+                ..sample
+            })
+            .collect();
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        assert_same_trajectory(&integrate(&mut pipeline, &wrapped), &expected);
+        assert!(pipeline.take_device_reset().is_none());
+        assert_eq!(pipeline.sequencer_stats().rollovers, 1);
+
+        // 重启：运行一段时间后时间戳回到 0
+        let mut rebooted = motion_segment(20_000);
+        rebooted.extend(motion_segment(0));
+        let (mut single, _) = build_pipeline(false, 8);
+        let single_last = integrate(&mut single, &motion_segment(0)).last().unwrap().1;
+        for reset_on_reboot in [false, true] {
+            let mut config = ProcessorPipelineConfig::default();
+            config.sequencer.reset_on_reboot = reset_on_reboot;
+            let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+            let last = integrate(&mut pipeline, &rebooted).last().unwrap().1;
+            let reset = pipeline.take_device_reset().expect("应记录设备复位");
+            assert!(reset.rebooted);
+            // 开启 reset_on_reboot 时导航从零开始，只剩重启后一段位移（约为两段的一半）
+            let restarted = (last - single_last).length() < 1e-4;
+            assert_eq!(restarted, reset_on_reboot, "{last:?} vs {single_last:?}");
+        }
+    }
+
+    /// 按给定采样间隔运行：静止 → 100 ms 运动 → 静止，并在 800 ms 处丢两帧。
+    ///
+    /// 返回 (切到运动的时刻, 重新判为静止的时刻, 丢帧统计)，时刻均相对首帧 (ms)。
+    fn run_at_interval(sample_interval_ms: u64) -> (u64, u64, (u64, u64)) {
+        let mut config = ProcessorPipelineConfig::default();
+        config.filter.passby = true;
+        config.zupt.enter_window_ms = 60.0;
+        config.zupt.exit_window_ms = 20.0;
+        let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+        pipeline.set_sample_interval_ms(sample_interval_ms as f64);
+
+        let dropped = [800, 800 + sample_interval_ms];
+        let mut moving_at = None;
+        let mut static_at = None;
+        for t in (0..1000).step_by(sample_interval_ms as usize) {
+            if dropped.contains(&t) {
+                continue;
+            }
+            let ax = if (500..600).contains(&t) { 1.0 } else { 0.0 };
+            let frame = pipeline
+                .process_sample_raw(ImuSampleRaw {
+                    accel_no_g: DVec3::new(ax, 0.0, 0.0),
+                    accel_with_g: DVec3::new(ax, 0.0, 9.80665),
+                    ..static_sample(t)
+                })
+                .expect("无排序时每帧都应放行");
+            match frame.motion_state {
+                MotionState::Moving if t >= 500 && moving_at.is_none() => moving_at = Some(t),
+                MotionState::Static if t >= 600 && static_at.is_none() => static_at = Some(t),
+                _ => {}
+            }
+        }
+        let stats = pipeline.sequencer_stats();
+        (
+            moving_at.expect("应切到运动"),
+            static_at.expect("应重新判为静止"),
+            (stats.gaps, stats.missing_frames),
+        )
+    }
+
+    #[test]
+    fn zupt_windows_and_gap_detection_follow_report_rate() {
+        let (fast_moving, fast_static, fast_gaps) = run_at_interval(4);
+        let (slow_moving, slow_static, slow_gaps) = run_at_interval(10);
+
+        // 同一时长窗口：250 Hz 下 5/15 帧，100 Hz 下 2/6 帧，切换时刻相差不超过一帧
+        assert_eq!((fast_moving, fast_static), (516, 656));
+        assert_eq!((slow_moving, slow_static), (510, 650));
+        assert_eq!(fast_gaps, (1, 2));
+        assert_eq!(slow_gaps, (1, 2));
+    }
+
+    #[test]
+    fn half_hertz_reporting_keeps_zupt_windows_in_device_time() {
+        let mut config = ProcessorPipelineConfig::default();
+        config.filter.passby = true;
+        config.zupt.enter_window_ms = 3000.0;
+        config.zupt.exit_window_ms = 20.0;
+        let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+        pipeline.set_sample_interval_ms(nominal_sample_interval_ms(0));
+
+        // 0.5 Hz：20–24 s 两帧有加速度，其余静止
+        let mut transitions = Vec::new();
+        let mut last_state = None;
+        for t in (0..60_000).step_by(2_000) {
+            let ax = if (20_000..24_000).contains(&t) {
+                1.0
+            } else {
+                0.0
+            };
+            let frame = pipeline
+                .process_sample_raw(ImuSampleRaw {
+                    accel_no_g: DVec3::new(ax, 0.0, 0.0),
+                    accel_with_g: DVec3::new(ax, 0.0, 9.80665),
+                    ..static_sample(t)
+                })
+                .expect("无排序时每帧都应放行");
+            if last_state != Some(frame.motion_state) {
+                last_state = Some(frame.motion_state);
+                transitions.push((t, frame.motion_state));
+            }
+        }
+
+        // 3 s 窗口按设备时间算：静止帧跨度达到 4 s（第三帧）才进入，而不是按帧数换算的两帧；
+        // 退出窗口不足一帧也要两帧确认
+        assert_eq!(
+            transitions,
+            [
+                (0, MotionState::Moving),
+                (4_000, MotionState::Static),
+                (22_000, MotionState::Moving),
+                (28_000, MotionState::Static),
+            ]
+        );
+        // 2 s 一帧是标称间隔，不算丢帧
+        let stats = pipeline.sequencer_stats();
+        assert_eq!((stats.gaps, stats.missing_frames), (0, 0));
+    }
+
+    /// 以恒定绕 z 轴角速度（IMU 原始单位）静止旋转的样本序列。
+    fn rotating_samples(gyro_z: f64, count: u64) -> impl Iterator<Item = ImuSampleRaw> {
+        (0..count).map(move |i| ImuSampleRaw {
+            gyro: DVec3::new(0.0, 0.0, gyro_z),
+            ..static_sample(i * 4)
+        })
+    }
+
+    #[test]
+    fn gyro_in_degrees_reaches_filter_as_radians() {
+        // passby 只跳过偏置与矩阵，单位换算必须保留
+        for passby in [false, true] {
+            let mut config = ProcessorPipelineConfig::default();
+            config.calibration.passby = passby;
+            let (mut pipeline, diag_rx) = build_pipeline_with(config, true, 512);
+            for raw in rotating_samples(10.0, 100) {
+                pipeline.process_sample_raw(raw);
+            }
+
+            let diag = diag_rx.drain().last().expect("诊断帧");
+            assert_eq!(diag.cal_gyro_unit, GyroUnit::DegPerSec);
+            assert_eq!(diag.cal_gyro_pre.z, 10.0);
+            assert!(
+                (diag.filt_gyro_post.z - 0.1745).abs() < 1e-4,
+                "passby={passby}: {}",
+                diag.filt_gyro_post.z
+            );
+        }
+    }
+
+    #[test]
+    fn fully_bypassed_chain_outputs_parsed_sample() {
+        let mut config = ProcessorPipelineConfig::default();
+        config.calibration.passby = true;
+        config.calibration.gyro_unit = GyroUnit::RadPerSec;
+        config.filter.passby = true;
+        config.derived.enabled = false;
+        config.quat_health.enabled = false;
+        config.trajectory.passby = true;
+        config.zupt.passby = true;
+        let (mut pipeline, diag_rx) = build_pipeline_with(config, true, 8);
+
+        for i in 0..200 {
+            let packet = synthetic_packet(i);
+            let parsed = ImuParser::parse(&packet).unwrap();
+            let frame = pipeline.process_packet(&packet).expect("顺序帧立即输出");
+            let expected = OutputBuilder::build(&OutputFrame {
+                raw: parsed,
+                nav: NavState {
+                    timestamp_ms: parsed.timestamp_ms.as_millis(),
+                    position: DVec3::ZERO,
+                    velocity: DVec3::ZERO,
+                    attitude: parsed.quat,
+                },
+                motion_state: frame.motion_state,
+                zupt: frame.zupt,
+                position_source: frame.position_source,
+                jerk: None,
+                ang_accel: None,
+                position_sigma_m: 0.0,
+                clock_sync: None,
+                heading_yaw_deg: None,
+                baro_relative_altitude_m: None,
+                debug_vectors: None,
+                stages: None,
+            });
+            assert_eq!(
+                serde_json::to_value(OutputBuilder::build(&frame)).unwrap(),
+                serde_json::to_value(expected).unwrap(),
+                "frame {i}"
+            );
+        }
+
+        let diag = diag_rx.drain().last().expect("诊断帧");
+        assert!(diag.cal_bypassed && diag.filt_bypassed);
+        assert_eq!(diag.filt_accel_post, diag.cal_accel_pre);
+
+        // 解释视图仍列出全部阶段，被跳过的阶段带标记
+        let explanation =
+            pipeline.explain_sample(ImuParser::parse(&synthetic_packet(200)).unwrap());
+        let bypassed: Vec<&str> = explanation
+            .stages
+            .iter()
+            .filter(|stage| stage.bypassed)
+            .map(|stage| stage.stage)
+            .collect();
+        assert_eq!(explanation.stages.len(), 12);
+        assert_eq!(
+            bypassed,
+            [
+                "calibration",
+                "quat_health",
+                "filter",
+                "derived",
+                "navigator",
+                "baro",
+                "heading",
+                "segment",
+                "sync_tap"
+            ]
+        );
+    }
+
+    #[test]
+    fn frozen_device_quat_is_replaced_by_gyro_attitude() {
+        let (mut pipeline, _) = build_pipeline_with(ProcessorPipelineConfig::default(), false, 8);
+        // 60°/s 绕 z 轴转动 1 s，设备四元数全程停在初始姿态
+        let last = rotating_samples(60.0, 250)
+            .filter_map(|raw| pipeline.process_sample_raw(raw))
+            .last()
+            .unwrap();
+        let event = pipeline.take_quat_freeze().expect("应检测到冻结");
+        assert!(event.frozen);
+        assert_eq!(pipeline.quat_freeze_count(), 1);
+
+        let expected = DQuat::from_rotation_z((60.0 * 0.996f64).to_radians());
+        let error_deg = (2.0 * last.raw.quat.dot(expected).abs().min(1.0).acos()).to_degrees();
+        assert!(error_deg < 0.5, "{error_deg}");
+    }
+
+    /// 静止 0.4 s 后边转动边加速的样本，`accel_no_g` 与 `accel_with_g` 一致。
+    fn consistent_motion(count: u64) -> Vec<ImuSampleRaw> {
+        let gravity = DVec3::new(0.0, 0.0, 9.80665);
+        (0..count)
+            .map(|i| {
+                let moving = i >= 100;
+                let t = i.saturating_sub(100) as f64 * 0.004;
+                let quat = DQuat::from_rotation_z(0.4 * t) * DQuat::from_rotation_x(0.1 * t.sin());
+                let accel_world = if moving {
+                    DVec3::new(
+                        0.6 * (5.0 * t).sin() + 0.3,
+                        0.3 * (3.0 * t).sin(),
+                        0.1 * t.sin(),
+                    )
+                } else {
+                    DVec3::ZERO
+                };
+                ImuSampleRaw {
+                    timestamp_ms: DeviceTimeMs(i * 4),
+                    accel_no_g: quat.inverse() * accel_world,
+                    accel_with_g: quat.inverse() * (accel_world + gravity),
+                    gyro: DVec3::new(0.0, 0.0, if moving { 23.0 } else { 0.0 }),
+                    quat,
+                    angle: euler_zyx_deg(quat),
+                    offset: DVec3::ZERO,
+                    accel_nav: DVec3::ZERO,
+                    baro: None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_g_source_matches_with_g_trajectory_on_consistent_data() {
+        let samples = consistent_motion(400);
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let with_g = ProcessorPipelineConfig {
+                navigator_impl,
+                ..ProcessorPipelineConfig::default()
+            };
+            let mut no_g = with_g.clone();
+            no_g.accel_source.mode = AccelSource::NoG;
+            let (mut reference, _) = build_pipeline_with(with_g, false, 8);
+            let (mut pipeline, _) = build_pipeline_with(no_g, false, 8);
+
+            let expected = integrate(&mut reference, &samples);
+            let actual = integrate(&mut pipeline, &samples);
+            let reference_end = expected.last().unwrap().1;
+            assert!(
+                reference_end.length() > 0.01,
+                "应有明显位移: {reference_end:?}"
+            );
+            for ((ts, a), (_, e)) in actual.iter().zip(&expected) {
+                assert!(
+                    (*a - *e).length() < 1e-9,
+                    "{navigator_impl:?} @{ts}: {a:?} vs {e:?}"
+                );
+            }
+            assert_eq!(actual.len(), expected.len());
+        }
+    }
+
+    #[test]
+    fn auto_source_flags_corrupted_accel_no_g() {
+        let mut config = ProcessorPipelineConfig::default();
+        config.accel_source.mode = AccelSource::Auto;
+        let (mut pipeline, diag_rx) = build_pipeline_with(config, true, 512);
+        let (mut reference, _) = build_pipeline(false, 8);
+        let mut samples = consistent_motion(400);
+        // 运动中途 accel_no_g 多出 2 m/s² 偏置
+        for sample in &mut samples[200..300] {
+            sample.accel_no_g.y += 2.0;
+        }
+
+        let mut events = Vec::new();
+        for sample in &samples {
+            let actual = pipeline.process_sample_raw(*sample).unwrap();
+            let expected = reference.process_sample_raw(*sample).unwrap();
+            // auto 积分的仍是含重力加速度
+            assert_eq!(actual.nav.position, expected.nav.position);
+            if let Some(event) = pipeline.take_accel_inconsistency() {
+                events.push((event.timestamp_ms, event.inconsistent));
+            }
+            if sample.timestamp_ms == DeviceTimeMs(250 * 4) {
+                let diag = diag_rx.drain().last().expect("诊断帧");
+                assert!(diag.accel_source_inconsistent);
+                assert!((diag.accel_source_residual.unwrap() - 2.0).abs() < 1e-6);
+            }
+        }
+        assert_eq!(events, [(224 * 4, true), (324 * 4, false)]);
+        assert_eq!(pipeline.accel_inconsistency_count(), 1);
+    }
+
+    #[test]
+    fn zupt_threshold_applies_to_physical_angular_rate() {
+        // gyro_thresh = 0.1 rad/s ≈ 5.73 °/s
+        let decide = |deg_per_sec: f64| {
+            let mut config = ProcessorPipelineConfig::default();
+            config.zupt.impl_type = ZuptImpl::LegacyHardLock;
+            config.zupt.gyro_thresh = 0.1;
+            let (mut pipeline, _diag_rx) = build_pipeline_with(config, false, 8);
+            for raw in rotating_samples(deg_per_sec, 200) {
+                pipeline.process_sample_raw(raw);
+            }
+            pipeline.is_static()
+        };
+
+        assert!(decide(5.0), "5 °/s = 0.087 rad/s 应判为静止");
+        assert!(!decide(7.0), "7 °/s = 0.122 rad/s 应判为运动");
+    }
+
+    #[test]
+    fn axis_remap_matches_physically_rotated_input() {
+        // 传感器绕 z 轴 +90° 安装：v_body = R * v_sensor
+        let mount = DQuat::from_rotation_z(90f64.to_radians());
+        let body: Vec<ImuSampleRaw> = (0..300u64)
+            .map(|i| {
+                let t = i as f64 * 0.004;
+                let quat = DQuat::from_rotation_z(0.4 * t) * DQuat::from_rotation_x(0.1 * t.sin());
+                let accel_no_g = DVec3::new(0.6 * (5.0 * t).sin(), 0.3 * (3.0 * t).cos(), 0.1);
+                ImuSampleRaw {
+                    timestamp_ms: DeviceTimeMs(i * 4),
+                    accel_no_g,
+                    accel_with_g: accel_no_g + quat.inverse() * DVec3::new(0.0, 0.0, 9.80665),
+                    gyro: DVec3::new(3.0, -1.5, 23.0),
+                    quat,
+                    angle: euler_zyx_deg(quat),
+                    offset: DVec3::ZERO,
+                    accel_nav: DVec3::ZERO,
+                    baro: None,
+                }
+            })
+            .collect();
+        let sensor: Vec<ImuSampleRaw> = body
+            .iter()
+            .map(|b| {
+                let quat = b.quat * mount;
+                ImuSampleRaw {
+                    accel_no_g: mount.inverse() * b.accel_no_g,
+                    accel_with_g: mount.inverse() * b.accel_with_g,
+                    gyro: mount.inverse() * b.gyro,
+                    quat,
+                    angle: euler_zyx_deg(quat),
+                    ..*b
+                }
+            })
+            .collect();
+
+        let remap =
+            AxisRemap::try_from(AxisMapSpec::Axes(["-y".into(), "x".into(), "z".into()])).unwrap();
+        let mut config = ProcessorPipelineConfig::default();
+        config.calibration.accel_map = remap;
+        config.calibration.gyro_map = remap;
+        let (mut reference, _) = build_pipeline(false, 8);
+        let (mut remapped, _) = build_pipeline_with(config, false, 8);
+
+        for (b, s) in body.into_iter().zip(sensor) {
+            let expected = reference.process_sample_raw(b).unwrap();
+            let actual = remapped.process_sample_raw(s).unwrap();
+            let close = |a: DVec3, e: DVec3| (a - e).length() < 1e-9;
+            assert!(close(actual.raw.accel_no_g, expected.raw.accel_no_g));
+            assert!(close(actual.raw.accel_with_g, expected.raw.accel_with_g));
+            assert!(close(actual.raw.gyro, expected.raw.gyro));
+            assert!(close(actual.raw.angle, expected.raw.angle));
+            assert!(actual.raw.quat.dot(expected.raw.quat).abs() > 1.0 - 1e-12);
+            assert!(close(actual.nav.position, expected.nav.position));
+            assert!(close(actual.nav.velocity, expected.nav.velocity));
+            assert!(actual.nav.attitude.dot(expected.nav.attitude).abs() > 1.0 - 1e-12);
+        }
+    }
+
+    /// 运动段结束后停在中途的管线与最后一帧位置。
+    fn pipeline_after_motion(segment: &[ImuSampleRaw]) -> (ProcessorPipeline, DVec3) {
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        let last = integrate(&mut pipeline, &segment[..150]).last().unwrap().1;
+        assert!(last.x > 0.003, "运动段应有明显位移: {last:?}");
+        (pipeline, last)
+    }
+
+    #[test]
+    fn threshold_tweak_keeps_position_continuous() {
+        let segment = motion_segment(1000);
+        let (mut pipeline, last) = pipeline_after_motion(&segment);
+
+        let mut tweaked = ProcessorPipelineConfig::default();
+        tweaked.zupt.gyro_enter_thresh *= 1.2;
+        tweaked.zupt.vel_decay_tau_ms *= 2.0;
+        tweaked.filter.alpha = 0.5;
+        tweaked.eskf.accel_noise *= 2.0;
+        assert_eq!(pipeline.patch_config(tweaked), ConfigApplyMode::InPlace);
+
+        let next = integrate(&mut pipeline, &segment[150..]).first().unwrap().1;
+        assert!(
+            (next - last).length() < 1e-3,
+            "位置跳变: {last:?} -> {next:?}"
+        );
+    }
+
+    #[test]
+    fn debug_vectors_toggle_in_place_without_resetting_navigation() {
+        let segment = motion_segment(1000);
+        let (mut pipeline, _) = pipeline_after_motion(&segment);
+        let (mut reference, _) = pipeline_after_motion(&segment);
+
+        let mut enabled = ProcessorPipelineConfig::default();
+        enabled.debug_vectors.enabled = true;
+        assert_eq!(pipeline.patch_config(enabled), ConfigApplyMode::InPlace);
+        for &sample in &segment[150..175] {
+            let expected = reference.process_sample_raw(sample).unwrap();
+            let actual = pipeline.process_sample_raw(sample).unwrap();
+            assert_eq!(expected.debug_vectors, None);
+            let vectors = actual.debug_vectors.expect("开启后每帧附带调试向量");
+            assert_eq!(vectors.gravity_ref, pipeline.navigator.gravity_ref());
+            assert_eq!(vectors.accel_lin, pipeline.navigator.last_linear_accel());
+            let residual = vectors.accel_world - vectors.gravity_ref - vectors.accel_lin;
+            assert!(residual.length() < 1e-12);
+            // 导航状态未被重置，与未切换的参考管线逐位一致
+            assert_eq!(actual.nav.position, expected.nav.position);
+            assert_eq!(actual.nav.velocity, expected.nav.velocity);
+
+            let data = serde_json::to_value(OutputBuilder::build(&actual)).unwrap();
+            assert!(data["debug_vectors"]["accel_world"].is_object());
+        }
+
+        let disabled = ProcessorPipelineConfig::default();
+        assert_eq!(pipeline.patch_config(disabled), ConfigApplyMode::InPlace);
+        for &sample in &segment[175..200] {
+            let expected = reference.process_sample_raw(sample).unwrap();
+            let actual = pipeline.process_sample_raw(sample).unwrap();
+            assert_eq!(actual.debug_vectors, None);
+            assert_eq!(actual.nav.position, expected.nav.position);
+
+            let data = serde_json::to_value(OutputBuilder::build(&actual)).unwrap();
+            assert!(data["debug_vectors"].is_null());
+        }
+    }
+
+    #[test]
+    fn pause_freezes_navigation_and_resumes_without_jump() {
+        let segment = motion_segment(1000);
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        // 停在加速段中途，速度不为零
+        integrate(&mut pipeline, &segment[..75]);
+        let frozen = pipeline.navigator.nav_state();
+        assert!(frozen.velocity.x > 0.01, "应处于运动中: {frozen:?}");
+
+        assert!(pipeline.set_paused(true));
+        assert!(!pipeline.set_paused(true));
+        // 暂停期间原始数据照常输出，导航状态重复冻结值
+        for &sample in &segment[75..150] {
+            let frame = pipeline.process_sample_raw(sample).unwrap();
+            assert_eq!(frame.raw.timestamp_ms, sample.timestamp_ms);
+            assert_eq!(frame.raw.accel_no_g.x, sample.accel_no_g.x);
+            assert_eq!(frame.nav.position, frozen.position);
+            assert_eq!(frame.nav.velocity, frozen.velocity);
+        }
+
+        assert!(pipeline.set_paused(false));
+        let resumed = integrate(&mut pipeline, &segment[150..]);
+        assert!(
+            (resumed[0].1 - frozen.position).length() < 1e-9,
+            "恢复首帧位置跳变: {:?} -> {:?}",
+            frozen.position,
+            resumed[0].1
+        );
+    }
+
+    #[test]
+    fn resume_reseeds_dt_after_long_pause() {
+        let segment = motion_segment(1000);
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        integrate(&mut pipeline, &segment[..85]);
+        let frozen = pipeline.navigator.nav_state();
+        let speed = frozen.velocity.length();
+        assert!(speed > 0.01, "应处于运动中: {frozen:?}");
+
+        // 暂停 2 s 后以匀速段恢复：首帧不积分，次帧只前进一个采样间隔
+        pipeline.set_paused(true);
+        integrate(&mut pipeline, &segment[85..100]);
+        pipeline.set_paused(false);
+        let later: Vec<ImuSampleRaw> = segment[150..160]
+            .iter()
+            .map(|&sample| ImuSampleRaw {
+                timestamp_ms: sample.timestamp_ms.add_ms(2000),
+                ..sample
+            })
+            .collect();
+        let resumed = integrate(&mut pipeline, &later);
+        assert!((resumed[0].1 - frozen.position).length() < 1e-9);
+        let step = (resumed[1].1 - resumed[0].1).length();
+        assert!(
+            step <= speed * 0.004 * 1.5,
+            "恢复后单步位移过大: {step} m（速度 {speed} m/s）"
+        );
+    }
+
+    #[test]
+    fn reset_timing_skips_reporting_gap() {
+        let segment = motion_segment(1000);
+        let (mut pipeline, _) = build_pipeline(false, 8);
+        integrate(&mut pipeline, &segment[..85]);
+        let before = pipeline.navigator.nav_state();
+        let speed = before.velocity.length();
+        assert!(speed > 0.01, "应处于运动中: {before:?}");
+
+        // 设备停止上报 40 s 后重新开启：首帧不积分，次帧只前进一个采样间隔
+        pipeline.reset_timing();
+        let later: Vec<ImuSampleRaw> = segment[85..95]
+            .iter()
+            .map(|&sample| ImuSampleRaw {
+                timestamp_ms: sample.timestamp_ms.add_ms(40_000),
+                ..sample
+            })
+            .collect();
+        let resumed = integrate(&mut pipeline, &later);
+        assert!((resumed[0].1 - before.position).length() < 1e-9);
+        let step = (resumed[1].1 - resumed[0].1).length();
+        assert!(
+            step <= speed * 0.004 * 1.5,
+            "重新开启后单步位移过大: {step} m（速度 {speed} m/s）"
+        );
+    }
+
+    #[test]
+    fn device_offset_follows_integrated_frame_after_axis_calibration() {
+        // 设备倾斜安装，零位校准后导航系相对设备世界系旋转
+        let mount = DQuat::from_rotation_z(0.7) * DQuat::from_rotation_x(0.2);
+        let gravity = DVec3::new(0.0, 0.0, 9.80665);
+        let dt = 0.004;
+        let (mut velocity, mut offset) = (0.0, 0.0);
+        // 设备世界系沿 x 加速再减速，offset 为固件给出的真实位置
+        let samples: Vec<ImuSampleRaw> = (0..250u64)
+            .map(|i| {
+                let accel = match i {
+                    60..=84 => 1.0,
+                    85..=109 => -1.0,
+                    _ => 0.0,
+                };
+                velocity += accel * dt;
+                offset += velocity * dt;
+                let accel_world = DVec3::X * accel;
+                ImuSampleRaw {
+                    timestamp_ms: DeviceTimeMs(1000 + i * 4),
+                    accel_no_g: mount.inverse() * accel_world,
+                    accel_with_g: mount.inverse() * (accel_world + gravity),
+                    quat: mount,
+                    angle: euler_zyx_deg(mount),
+                    offset: DVec3::X * offset,
+                    ..static_sample(0)
+                }
+            })
+            .collect();
+
+        // 运动前的 240 ms 静止期足够采满 100 ms 的质量窗口
+        let mut config = ProcessorPipelineConfig::default();
+        config.calibration_quality.window_ms = 100;
+        let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+        pipeline.process_sample_raw(samples[0]);
+        let (respond_to, mut response_rx) = tokio::sync::oneshot::channel();
+        pipeline.handle_calibration_request(CorrectionRequest::SetAxis { respond_to });
+        integrate(&mut pipeline, &samples[1..]);
+        assert!(response_rx.try_recv().unwrap().unwrap().applied);
+
+        // 积分位移受滤波与 ZUPT 影响量值略小，但两路候选经同一零位校准，方向一致
+        let candidates = pipeline.navigator.position_candidates();
+        assert!(
+            candidates.integrated.length() > 0.003,
+            "应有明显位移: {candidates:?}"
+        );
+        let expected_dir = mount.inverse() * DVec3::X;
+        for position in [candidates.integrated, candidates.device] {
+            assert!(
+                position.normalize().dot(expected_dir) > 0.999,
+                "位移方向偏离导航系: {candidates:?}"
+            );
+        }
+        // 未经转换的设备位置方向明显不同
+        assert!(DVec3::X.dot(expected_dir) < 0.9);
+    }
+
+    #[test]
+    fn derived_signals_follow_config() {
+        let ramp = |pipeline: &mut ProcessorPipeline| {
+            (0..250u64)
+                .filter_map(|i| {
+                    let mut sample = static_sample(i * 4);
+                    sample.accel_with_g.z += 3.0 * (i as f64 * 0.004);
+                    pipeline.process_sample_raw(sample)
+                })
+                .collect::<Vec<_>>()
+        };
+        let (mut pipeline, _) = build_pipeline(false, 1);
+        assert!(ramp(&mut pipeline).iter().all(|f| f.jerk.is_none()));
+
+        // 原地启用后从下一帧开始差分；加速度以 3 m/s³ 线性增长
+        let mut enabled = ProcessorPipelineConfig::default();
+        enabled.derived.enabled = true;
+        assert_eq!(pipeline.patch_config(enabled), ConfigApplyMode::InPlace);
+        pipeline.reset();
+        let frames = ramp(&mut pipeline);
+        assert!(frames[0].jerk.is_none() && frames[0].ang_accel.is_none());
+        let last = frames.last().unwrap();
+        let jerk = last.jerk.unwrap();
+        assert!((jerk.length() - 3.0).abs() < 1e-3, "{jerk:?}");
+        assert!(last.ang_accel.unwrap().length() < 1e-9);
+    }
+
+    #[test]
+    fn algorithm_change_resets_pipeline() {
+        let segment = motion_segment(1000);
+        let (mut pipeline, _) = pipeline_after_motion(&segment);
+
+        let mut switched = ProcessorPipelineConfig::default();
+        switched.navigator_impl = match switched.navigator_impl {
+            NavigatorImplType::Legacy => NavigatorImplType::Eskf,
+            NavigatorImplType::Eskf => NavigatorImplType::Legacy,
+        };
+        assert_eq!(pipeline.patch_config(switched), ConfigApplyMode::Reset);
+
+        let next = integrate(&mut pipeline, &segment[150..]).first().unwrap().1;
+        assert!(next.length() < 1e-6, "重置后位置应回到原点: {next:?}");
+    }
+
+    #[test]
+    fn static_move_static_move_static_yields_two_segments() {
+        let mut config = ProcessorPipelineConfig::default();
+        config.segment.enabled = true;
+        config.segment.min_path_length_m = 0.005;
+        let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+
+        // 先静止 600 ms 完成重力初始化，再接两段 静止 → 脉冲 → 静止
+        let mut samples: Vec<ImuSampleRaw> = (0..150).map(|i| static_sample(i * 4)).collect();
+        samples.extend(motion_segment(600));
+        samples.extend(motion_segment(1400));
+
+        let mut segments = Vec::new();
+        let mut moving = Vec::new();
+        for sample in samples {
+            let frame = pipeline.process_sample_raw(sample).unwrap();
+            if frame.motion_state == MotionState::Moving {
+                moving.push(frame.nav.timestamp_ms);
+            }
+            segments.extend(pipeline.take_segment());
+        }
+        // 加减速换向处的短暂静止判定并入同一段，不会拆成两段
+        assert_eq!(segments.len(), 2, "{segments:?}");
+
+        for (segment, pulse_start) in segments.iter().zip([840, 1640]) {
+            // 起点为运动前最后一个静止帧
+            let first_moving = *moving.iter().find(|&&ts| ts > segment.start_ms).unwrap();
+            assert_eq!(first_moving, segment.start_ms + 4);
+            assert!(segment.start_ms >= pulse_start - 4 && segment.start_ms < pulse_start + 100);
+            // 脉冲持续 200 ms，终点在其后
+            assert!(segment.end_ms >= pulse_start + 200, "{segment:?}");
+            assert!(!moving.contains(&segment.end_ms));
+            assert_eq!(segment.duration_ms, segment.end_ms - segment.start_ms);
+
+            // 沿 +x 直线运动，末段略有回退，路径长度略大于位移
+            assert!(segment.displacement.x > 0.005, "{segment:?}");
+            assert!(segment.displacement.y.abs() < 1e-9 && segment.displacement.z.abs() < 1e-9);
+            assert!(segment.path_length_m >= segment.displacement_m);
+            assert!(
+                segment.path_length_m < segment.displacement_m * 1.2,
+                "{segment:?}"
+            );
+            assert!(
+                segment.peak_speed_mps > 0.05 && segment.peak_speed_mps < 0.1,
+                "peak speed {segment:?}"
+            );
+        }
+        // 两段运动相同，指标应一致
+        let (first, second) = (segments[0], segments[1]);
+        assert_eq!(first.duration_ms, second.duration_ms);
+        assert!((first.path_length_m - second.path_length_m).abs() < 1e-6);
+        assert!((first.displacement_m - second.displacement_m).abs() < 1e-6);
+        assert!(pipeline.take_segment().is_none());
+
+        // 默认最短路径 1 cm，同样的运动被当作抖动丢弃
+        let (mut pipeline, _) = build_pipeline_with(
+            ProcessorPipelineConfig {
+                segment: SegmentConfig {
+                    enabled: true,
+                    ..SegmentConfig::default()
+                },
+                ..ProcessorPipelineConfig::default()
+            },
+            false,
+            8,
+        );
+        let mut samples: Vec<ImuSampleRaw> = (0..150).map(|i| static_sample(i * 4)).collect();
+        samples.extend(motion_segment(600));
+        for sample in samples {
+            pipeline.process_sample_raw(sample);
+            assert!(pipeline.take_segment().is_none());
+        }
+    }
+
+    #[test]
+    fn explain_sample_leaves_live_state_untouched() {
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let config = ProcessorPipelineConfig {
+                navigator_impl,
+                ..ProcessorPipelineConfig::default()
+            };
+            let segment = motion_segment(1000);
+            let (mut live, _) = build_pipeline_with(config.clone(), false, 8);
+            let (mut reference, _) = build_pipeline_with(config.clone(), false, 8);
+            let (mut probe, _) = build_pipeline_with(config, false, 8);
+            for pipeline in [&mut live, &mut reference, &mut probe] {
+                integrate(pipeline, &segment[..80]);
+            }
+
+            // 与下一帧同时间戳、加速度明显不同的样本
+            let sample = ImuSampleRaw {
+                accel_no_g: DVec3::new(3.0, 0.5, 0.0),
+                accel_with_g: DVec3::new(3.0, 0.5, 9.80665),
+                gyro: DVec3::new(0.0, 0.0, 20.0),
+                ..segment[80]
+            };
+            let explanation = live.explain_sample(sample);
+            let stages: Vec<&str> = explanation.stages.iter().map(|s| s.stage).collect();
+            assert_eq!(
+                stages,
+                [
+                    "remap_axes",
+                    "axis_calibration",
+                    "calibration",
+                    "quat_health",
+                    "accel_source",
+                    "filter",
+                    "derived",
+                    "navigator",
+                    "baro",
+                    "heading",
+                    "segment",
+                    "sync_tap"
+                ]
+            );
+            let filter = &explanation.stages[5];
+            assert_eq!(filter.params["alpha"], json!(live.config.filter.alpha));
+            let navigator = &explanation.stages[7];
+            assert_eq!(navigator.params["was_static"], json!(false));
+            assert!(navigator.params["zupt"]["gyro_enter_thresh"].is_number());
+            assert!(navigator.params["gravity_ref"].is_object());
+
+            // 解释结果与真正处理该样本一致
+            let frame = probe.process_sample_raw(sample).unwrap();
+            assert_eq!(explanation.timestamp_ms, frame.raw.timestamp_ms.as_millis());
+            assert_eq!(explanation.output.position, frame.nav.position);
+            assert_eq!(explanation.output.velocity, frame.nav.velocity);
+            // 重复解释结果不变
+            assert_eq!(
+                serde_json::to_value(live.explain_sample(sample)).unwrap(),
+                serde_json::to_value(&explanation).unwrap()
+            );
+
+            // 之后的实时输出与未解释过的参考管线逐位一致
+            let actual = integrate(&mut live, &segment[80..]);
+            let expected = integrate(&mut reference, &segment[80..]);
+            assert_eq!(actual.len(), 120);
+            assert_eq!(actual, expected, "{navigator_impl:?}");
+        }
+    }
+
+    /// 倾斜静置的合成样本：姿态为 `tilt`，含重力加速度为重力在机体系下的投影。
+    fn tilted_static_sample(tilt: DQuat, timestamp_ms: u64) -> ImuSampleRaw {
+        ImuSampleRaw {
+            accel_with_g: tilt.inverse().rotate_vec3(DVec3::new(0.0, 0.0, 9.80665)),
+            quat: tilt,
+            angle: euler_zyx_deg(tilt),
+            ..static_sample(timestamp_ms)
+        }
+    }
+
+    #[test]
+    fn calibrate_all_levels_zeroes_heading_and_origin_atomically() {
+        let tilt = (DQuat::from_rotation_z(0.8) * DQuat::from_rotation_x(0.3)).normalize();
+        for navigator_impl in [NavigatorImplType::Legacy, NavigatorImplType::Eskf] {
+            let mut config = ProcessorPipelineConfig {
+                navigator_impl,
+                ..ProcessorPipelineConfig::default()
+            };
+            config.calibration_quality.window_ms = 250;
+            let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+
+            // 先走一段再倾斜静置：位置不在原点
+            let segment = motion_segment(0);
+            integrate(&mut pipeline, &segment[..150]);
+            let mut ts = segment[149].timestamp_ms.as_millis();
+            let mut next = || {
+                ts += 4;
+                tilted_static_sample(tilt, ts)
+            };
+            for _ in 0..100 {
+                pipeline.process_sample_raw(next());
+            }
+            assert!(pipeline.navigator.nav_state().position.x > 0.003);
+
+            // 静止校验失败：抖动 + 转动，什么都不改
+            let before = pipeline.navigator.nav_state().position;
+            let (respond_to, mut response_rx) = tokio::sync::oneshot::channel();
+            pipeline.handle_calibration_request(CorrectionRequest::CalibrateAll { respond_to });
+            let mut shake = 0.0;
+            let result = loop {
+                shake = -shake + 0.5;
+                let sample = ImuSampleRaw {
+                    accel_with_g: next().accel_with_g * (1.0 + shake / 9.8),
+                    gyro: DVec3::new(0.0, 0.0, 30.0),
+                    ..next()
+                };
+                pipeline.process_sample_raw(sample);
+                if let Ok(result) = response_rx.try_recv() {
+                    break result.unwrap();
+                }
+            };
+            assert!(!result.quality.applied, "{result:?}");
+            assert!(result.offsets.is_none());
+            assert_eq!(pipeline.axis_calibration.quat_offset, DQuat::IDENTITY);
+            assert!((pipeline.navigator.nav_state().position - before).length() < 0.01);
+
+            for _ in 0..50 {
+                pipeline.process_sample_raw(next());
+            }
+            let (respond_to, mut response_rx) = tokio::sync::oneshot::channel();
+            pipeline.handle_calibration_request(CorrectionRequest::CalibrateAll { respond_to });
+            let (result, frame) = loop {
+                let frame = pipeline.process_sample_raw(next()).unwrap();
+                if let Ok(result) = response_rx.try_recv() {
+                    break (result.unwrap(), frame);
+                }
+            };
+            assert!(result.quality.applied, "{result:?}");
+            let offsets = result.offsets.unwrap();
+            assert!((offsets.heading_offset_deg - 0.8f64.to_degrees()).abs() < 1e-6);
+            assert!((offsets.angle_offset.x - 0.3f64.to_degrees()).abs() < 1e-6);
+            assert!((offsets.gravity_ref.length() - 9.80665).abs() < 1e-9);
+
+            // 完成校准的这一帧即已生效：姿态归零、位于原点且静止
+            // （速度只剩本帧一步积分的滤波残差）
+            assert!(frame.raw.quat.dot(DQuat::IDENTITY).abs() > 1.0 - 1e-9);
+            assert!(frame.raw.angle.length() < 1e-6, "{:?}", frame.raw.angle);
+            let position = frame.nav.position;
+            assert!(position.length() < 1e-5, "{position:?}");
+            let velocity = frame.nav.velocity;
+            assert!(velocity.length() < 1e-3, "{velocity:?}");
+            // 之后的静置帧停留在原点附近（滤波与零偏估计的残差）
+            for _ in 0..50 {
+                let frame = pipeline.process_sample_raw(next()).unwrap();
+                assert!(frame.raw.quat.dot(DQuat::IDENTITY).abs() > 1.0 - 1e-9);
+                let position = frame.nav.position;
+                assert!(position.length() < 1e-3, "{position:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn calibration_wizard_reports_scripted_flows_in_order() {
+        let mut config = ProcessorPipelineConfig::default();
+        config.calibration_quality.window_ms = 250;
+        config.heading_align.enabled = true;
+        config.heading_align.min_displacement_m = 0.001;
+        let (mut pipeline, _) = build_pipeline_with(config, false, 8);
+
+        // 处理线程的发布方式：每个事件之后取走状态变化，写入槽位后再推送
+        let slot = CalibrationStateSlot::new_handle();
+        let mut events: Vec<CalibrationWizardSnapshot> = Vec::new();
+        let mut publish = |pipeline: &mut ProcessorPipeline| {
+            slot.publish(pipeline.take_calibration_transitions(), |snapshot| {
+                assert_eq!(&slot.get(), snapshot, "推送时槽位应已更新");
+                events.push(snapshot.clone());
+            });
+        };
+        let mut ts = 1000;
+        let mut next = || {
+            ts += 4;
+            ts
+        };
+        let label = |snapshot: &CalibrationWizardSnapshot| {
+            let state = serde_json::to_value(&snapshot.state).unwrap();
+            match &snapshot.state {
+                CalibrationWizardState::WaitingForStillness { elapsed_ms, .. } => {
+                    format!("waiting_for_stillness:{elapsed_ms}")
+                }
+                CalibrationWizardState::Done { .. } => {
+                    format!("done:{}", state["result"]["kind"].as_str().unwrap())
+                }
+                _ => state["state"].as_str().unwrap().to_string(),
+            }
+        };
+
+        // 尚无数据：直接失败
+        let (respond_to, _response_rx) = tokio::sync::oneshot::channel();
+        pipeline.handle_calibration_request(CorrectionRequest::SetAxis { respond_to });
+        publish(&mut pipeline);
+
+        // 静止中一次性校准：采满窗口后计算并完成
+        for _ in 0..10 {
+            pipeline.process_sample_raw(static_sample(next()));
+            publish(&mut pipeline);
+        }
+        let (respond_to, mut response_rx) = tokio::sync::oneshot::channel();
+        pipeline.handle_calibration_request(CorrectionRequest::CalibrateAll { respond_to });
+        publish(&mut pipeline);
+        for _ in 0..100 {
+            pipeline.process_sample_raw(static_sample(next()));
+            publish(&mut pipeline);
+        }
+        assert!(response_rx.try_recv().unwrap().unwrap().quality.applied);
+
+        // 武装航向对齐后走一段，运动在静止中结束即完成
+        let (respond_to, mut response_rx) = tokio::sync::oneshot::channel();
+        pipeline.handle_calibration_request(CorrectionRequest::ArmHeadingFromMotion { respond_to });
+        assert_eq!(response_rx.try_recv().unwrap(), Ok(()));
+        publish(&mut pipeline);
+        for sample in motion_segment(next()) {
+            pipeline.process_sample_raw(sample);
+            publish(&mut pipeline);
+        }
+        for _ in 0..200 {
+            next();
+        }
+        let aligned = pipeline.take_heading_aligned().unwrap();
+        assert_eq!(
+            pipeline.calibration_state().state,
+            CalibrationWizardState::Done {
+                result: CalibrationWizardResult::HeadingAlign(aligned),
+            }
+        );
+
+        // 抖动加转动中一次性校准：静止校验不通过而失败
+        let (respond_to, mut response_rx) = tokio::sync::oneshot::channel();
+        pipeline.handle_calibration_request(CorrectionRequest::CalibrateAll { respond_to });
+        publish(&mut pipeline);
+        let mut shake = 0.0;
+        while response_rx.try_recv().is_err() {
+            shake = -shake + 0.5;
+            pipeline.process_sample_raw(ImuSampleRaw {
+                accel_with_g: DVec3::new(0.0, 0.0, 9.80665 + shake),
+                gyro: DVec3::new(0.0, 0.0, 30.0),
+                ..static_sample(next())
+            });
+            publish(&mut pipeline);
+        }
+
+        // 重置清除了武装状态，等待运动的向导回到空闲
+        let (respond_to, _response_rx) = tokio::sync::oneshot::channel();
+        pipeline.handle_calibration_request(CorrectionRequest::ArmHeadingFromMotion { respond_to });
+        pipeline.reset();
+        publish(&mut pipeline);
+
+        let labels: Vec<String> = events.iter().map(label).collect();
+        assert_eq!(
+            labels,
+            [
+                "failed",
+                "waiting_for_stillness:0",
+                "waiting_for_stillness:100",
+                "waiting_for_stillness:200",
+                "computing",
+                "done:calibrate_all",
+                "waiting_for_motion",
+                "done:heading_align",
+                "waiting_for_stillness:0",
+                "waiting_for_stillness:100",
+                "waiting_for_stillness:200",
+                "computing",
+                "failed",
+                "waiting_for_motion",
+                "idle",
+            ]
+        );
+        let flows: Vec<_> = events.iter().map(|e| e.flow).collect();
+        assert_eq!(flows[0], Some(CalibrationFlow::Axis));
+        assert_eq!(flows[5], Some(CalibrationFlow::CalibrateAll));
+        assert_eq!(flows[7], Some(CalibrationFlow::HeadingAlign));
+        assert_eq!(flows[14], None);
+        // 序号连续，事件按发生先后推送，槽位停在最后一个状态
+        let seqs: Vec<u64> = events.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, (1..=events.len() as u64).collect::<Vec<_>>());
+        assert_eq!(&slot.get(), events.last().unwrap());
+        assert_eq!(pipeline.calibration_state(), events.last().unwrap());
+    }
+}
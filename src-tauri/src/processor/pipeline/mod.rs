@@ -6,12 +6,18 @@
 
 /// 管线诊断数据采集。
 pub mod diagnostics;
+/// 单帧处理过程解释。
+pub mod explain;
 /// 管线逻辑。
 pub mod logic;
 /// 管线配置类型。
 pub mod types;
 
+/// 单帧处理过程解释。
+pub use explain::{SampleExplanation, StageExplanation};
 /// 处理管线。
-pub use logic::ProcessorPipeline;
+pub use logic::{ProcessorPipeline, NO_RAW_DATA_ERROR};
 /// 处理管线配置。
-pub use types::{PipelineConfigRequest, ProcessorPipelineConfig};
+pub use types::{
+    ConfigApplyMode, ConfigInvalid, ConfigLoadStage, PipelineConfigRequest, ProcessorPipelineConfig,
+};
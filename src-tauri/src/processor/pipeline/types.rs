@@ -1,22 +1,70 @@
 //! 处理管线配置类型。
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Serialize};
 use tokio::sync::oneshot;
 
-use crate::processor::calibration::ImuCalibrationConfig;
+use crate::processor::accel_source::AccelSourceConfig;
+use crate::processor::calibration::{
+    AxisCalibration, CalibrationQualityConfig, ImuCalibrationConfig,
+};
+use crate::processor::derived::DerivedConfig;
 use crate::processor::filter::LowPassFilterConfig;
-use crate::processor::navigator::{EskfConfig, NavigatorImplType, TrajectoryConfig, ZuptConfig};
+use crate::processor::heading::HeadingAlignConfig;
+use crate::processor::idle::IdleConfig;
+use crate::processor::navigator::{
+    AutoGravityConfig, AutoOriginConfig, BaroConfig, DebugVectorsConfig, EskfConfig,
+    NavigatorImplType, PlaneConstraintConfig, PositionSourceConfig, TrajectoryConfig, ZuptConfig,
+};
+use crate::processor::parser::{ImuSampleRaw, ReassemblyConfig};
+use crate::processor::pipeline::SampleExplanation;
+use crate::processor::quat_health::QuatHealthConfig;
+use crate::processor::scheduling::SchedulingConfig;
+use crate::processor::segment::SegmentConfig;
+use crate::processor::sequencer::SequencerConfig;
+use crate::processor::shared::{ConfigError, ConfigErrors, ConfigValidator};
+use crate::processor::suppress::OutputSuppressConfig;
+use crate::processor::sync_tap::SyncTapConfig;
+use crate::types::recording::RecordingCalibration;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// 全局配置参数。
 pub struct GlobalConfig {
     /// 重力加速度常数（m/s²）。
     pub gravity: f64,
+    /// 静止期重力模长自动估计。
+    #[serde(default)]
+    pub auto_gravity: AutoGravityConfig,
+    /// 允许写入原始设备命令（`write_device_command`），debug 构建始终允许。
+    #[serde(default)]
+    pub unsafe_device_commands: bool,
+    /// 处理线程与录制线程的调度提示，应用启动时生效。
+    #[serde(default)]
+    pub scheduling: SchedulingConfig,
 }
 
 impl Default for GlobalConfig {
     fn default() -> Self {
-        Self { gravity: 9.80665 }
+        Self {
+            gravity: 9.80665,
+            auto_gravity: AutoGravityConfig::default(),
+            unsafe_device_commands: false,
+            scheduling: SchedulingConfig::default(),
+        }
+    }
+}
+
+impl GlobalConfig {
+    /// 校验取值范围：重力模长在地表实际范围 [9.5, 10.1] 内。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("gravity", self.gravity, 9.5, 10.1);
+        v.section("auto_gravity", |v| self.auto_gravity.validate(v));
+    }
+
+    /// 变化时是否必须重建管线：调度提示只影响线程，不计入。
+    pub fn requires_reset(&self, new: &Self) -> bool {
+        self.gravity != new.gravity
+            || self.auto_gravity != new.auto_gravity
+            || self.unsafe_device_commands != new.unsafe_device_commands
     }
 }
 
@@ -25,20 +73,192 @@ impl Default for GlobalConfig {
 pub struct ProcessorPipelineConfig {
     /// 全局配置。
     pub global: GlobalConfig,
+    /// 蓝牙通知分片重组配置。
+    #[serde(default)]
+    pub reassembly: ReassemblyConfig,
+    /// 时间戳去重与排序配置。
+    #[serde(default)]
+    pub sequencer: SequencerConfig,
     /// 标定配置。
     pub calibration: ImuCalibrationConfig,
+    /// 姿态零位校准的质量评估配置。
+    #[serde(default)]
+    pub calibration_quality: CalibrationQualityConfig,
     /// 滤波配置。
     pub filter: LowPassFilterConfig,
+    /// 派生信号（加加速度、角加速度）配置。
+    #[serde(default)]
+    pub derived: DerivedConfig,
     /// 轨迹计算配置。
     pub trajectory: TrajectoryConfig,
     /// ZUPT 配置。
     pub zupt: ZuptConfig,
+    /// 平面约束配置。
+    #[serde(default)]
+    pub plane_constraint: PlaneConstraintConfig,
     /// 导航器实现类型。
     #[serde(default)]
     pub navigator_impl: NavigatorImplType,
     /// ESKF 参数配置。
     #[serde(default)]
     pub eskf: EskfConfig,
+    /// 位置来源配置（本地积分 / 设备位置 / 融合）。
+    #[serde(default)]
+    pub position_source: PositionSourceConfig,
+    /// 运动分段检测配置。
+    #[serde(default)]
+    pub segment: SegmentConfig,
+    /// 空闲模式配置（无人消费时停止设备上报）。
+    #[serde(default)]
+    pub idle: IdleConfig,
+    /// 前端输出的静止帧抑制配置。
+    #[serde(default)]
+    pub output_suppress: OutputSuppressConfig,
+    /// 设备四元数冻结监测配置。
+    #[serde(default)]
+    pub quat_health: QuatHealthConfig,
+    /// 积分用的加速度来源（含重力 / 设备去重力 / 交叉检查）。
+    #[serde(default)]
+    pub accel_source: AccelSourceConfig,
+    /// 世界系原点自动锚定到首段持续静止处。
+    #[serde(default)]
+    pub auto_origin: AutoOriginConfig,
+    /// 气压计高度辅助竖直位置。
+    #[serde(default)]
+    pub baro: BaroConfig,
+    /// 按首段运动方向对齐输出航向。
+    #[serde(default)]
+    pub heading_align: HeadingAlignConfig,
+    /// 敲击同步检测（加速度尖峰作为视频对齐点）。
+    #[serde(default)]
+    pub sync_tap: SyncTapConfig,
+    /// 输出帧附带重力参考与加速度调试向量。
+    #[serde(default)]
+    pub debug_vectors: DebugVectorsConfig,
+}
+
+impl ProcessorPipelineConfig {
+    /// 校验各配置段的取值范围，返回全部越界字段。
+    ///
+    /// 错误路径与 `processor.toml` 的段名、字段名一致（如 `zupt.accel_thresh`）。
+    /// 加载、热重载与 IPC 更新都先校验，不通过则整体拒绝。
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut v = ConfigValidator::new();
+        v.section("global", |v| self.global.validate(v));
+        v.section("reassembly", |v| self.reassembly.validate(v));
+        v.section("sequencer", |v| self.sequencer.validate(v));
+        v.section("calibration", |v| self.calibration.validate(v));
+        v.section("calibration_quality", |v| {
+            self.calibration_quality.validate(v)
+        });
+        v.section("filter", |v| self.filter.validate(v));
+        v.section("derived", |v| self.derived.validate(v));
+        v.section("trajectory", |v| self.trajectory.validate(v));
+        v.section("zupt", |v| self.zupt.validate(v));
+        v.section("plane_constraint", |v| self.plane_constraint.validate(v));
+        v.section("eskf", |v| self.eskf.validate(v));
+        v.section("position_source", |v| self.position_source.validate(v));
+        v.section("segment", |v| self.segment.validate(v));
+        v.section("idle", |v| self.idle.validate(v));
+        v.section("output_suppress", |v| self.output_suppress.validate(v));
+        v.section("quat_health", |v| self.quat_health.validate(v));
+        v.section("accel_source", |v| self.accel_source.validate(v));
+        v.section("auto_origin", |v| self.auto_origin.validate(v));
+        v.section("baro", |v| self.baro.validate(v));
+        v.section("heading_align", |v| self.heading_align.validate(v));
+        v.section("sync_tap", |v| self.sync_tap.validate(v));
+        v.finish()
+    }
+
+    /// 从 `self` 切换到 `new` 是否必须重建管线。
+    ///
+    /// 融合算法、坐标约定（标定、重力、平面约束）、积分器与 ZUPT 实现的变化会改变
+    /// 状态的含义，只能重置；前瞻缓冲里的帧属于旧窗口，排序配置变化同样重置。
+    /// 其余参数（滤波系数、派生信号、ZUPT 阈值与衰减、dt 限幅、ESKF 噪声、运动分段、
+    /// 加速度来源）可原地生效。
+    pub fn requires_reset(&self, new: &Self) -> bool {
+        self.navigator_impl != new.navigator_impl
+            || self.global.requires_reset(&new.global)
+            || self.sequencer != new.sequencer
+            || self.calibration != new.calibration
+            || self.plane_constraint != new.plane_constraint
+            || self.trajectory.integrator != new.trajectory.integrator
+            || self.zupt.impl_type != new.zupt.impl_type
+    }
+
+    /// 解析 TOML 配置文本。
+    ///
+    /// 处理阶段（[`STAGE_SWITCH_SECTIONS`]）的开关以 `passby = true` 为规范写法；
+    /// 同时接受取反的 `enabled = false`，两者同时出现且矛盾时报错。
+    pub fn from_toml_str(content: &str) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = content.parse()?;
+        let mut migrated = false;
+        for section in STAGE_SWITCH_SECTIONS {
+            let Some(toml::Value::Table(section_table)) = table.get_mut(section) else {
+                continue;
+            };
+            let Some(enabled) = section_table.remove("enabled") else {
+                continue;
+            };
+            let Some(enabled) = enabled.as_bool() else {
+                return Err(de::Error::custom(format!("{section}.enabled 必须是布尔值")));
+            };
+            match section_table.get("passby").map(toml::Value::as_bool) {
+                Some(Some(passby)) if passby == enabled => {
+                    return Err(de::Error::custom(format!(
+                        "{section}.passby 与 {section}.enabled 相互矛盾"
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    section_table.insert("passby".into(), toml::Value::Boolean(!enabled));
+                }
+            }
+            migrated = true;
+        }
+        if migrated {
+            toml::Value::Table(table).try_into()
+        } else {
+            // 未改写时直接解析原文，错误信息保留行列位置
+            toml::from_str(content)
+        }
+    }
+}
+
+/// 以 `passby` 作开关的处理阶段配置段。
+///
+/// 可选功能（`derived`、`segment`、`quat_health` 等）默认关闭，开关仍叫 `enabled`。
+pub const STAGE_SWITCH_SECTIONS: [&str; 4] = ["calibration", "filter", "trajectory", "zupt"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 配置变更的生效方式。
+pub enum ConfigApplyMode {
+    /// 原地更新参数，滤波、导航与 ZUPT 状态保留。
+    InPlace,
+    /// 结构性变更，管线已重建（位置归零并重新做姿态零位校准）。
+    Reset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 配置文件的加载时机。
+pub enum ConfigLoadStage {
+    /// 应用启动。
+    Startup,
+    /// 运行中文件变更触发的热重载。
+    HotReload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+/// `config_invalid` 事件载荷：`processor.toml` 未通过校验而被拒绝。
+///
+/// 启动时回退到默认配置，热重载时保留当前配置。
+pub struct ConfigInvalid {
+    /// 加载时机。
+    pub stage: ConfigLoadStage,
+    /// 全部校验错误。
+    pub errors: Vec<ConfigError>,
 }
 
 /// Pipeline 运行时配置请求。
@@ -55,4 +275,270 @@ pub enum PipelineConfigRequest {
         /// 请求响应通道。
         respond_to: oneshot::Sender<Result<(), &'static str>>,
     },
+    /// 增量应用配置：能原地生效的参数不重置状态。
+    Patch {
+        /// 新配置。
+        config: Box<ProcessorPipelineConfig>,
+        /// 请求响应通道，返回实际采用的生效方式。
+        respond_to: oneshot::Sender<Result<ConfigApplyMode, &'static str>>,
+    },
+    /// 暂停或恢复导航（连接与原始数据不受影响）。
+    SetPaused {
+        /// 是否暂停。
+        paused: bool,
+        /// 请求响应通道，返回状态是否改变。
+        respond_to: oneshot::Sender<bool>,
+    },
+    /// 设置标称采样间隔（设备上报频率变化时），导航状态保留。
+    SetSampleInterval {
+        /// 标称采样间隔 (ms)。
+        sample_interval_ms: f64,
+        /// 请求响应通道。
+        respond_to: oneshot::Sender<()>,
+    },
+    /// 丢弃导航时间基准，下一帧 dt 记 0（设备停止上报后重新开启时）。
+    ResetTiming {
+        /// 请求响应通道。
+        respond_to: oneshot::Sender<()>,
+    },
+    /// 获取当前姿态零位。
+    GetAxisOffset {
+        /// 请求响应通道。
+        respond_to: oneshot::Sender<AxisCalibration>,
+    },
+    /// 获取当前世界系的姿态零位与重力参考（开始录制时写入元数据）。
+    GetRecordingCalibration {
+        /// 请求响应通道。
+        respond_to: oneshot::Sender<RecordingCalibration>,
+    },
+    /// 设置姿态零位（连接设备时恢复档案中保存的零位）。
+    SetAxisOffset {
+        /// 姿态零位。
+        offset: AxisCalibration,
+        /// 请求响应通道。
+        respond_to: oneshot::Sender<()>,
+    },
+    /// 在实时状态的副本上演算一个样本，管线状态不变。
+    Explain {
+        /// 待解释的原始样本。
+        sample: Box<ImuSampleRaw>,
+        /// 请求响应通道。
+        respond_to: oneshot::Sender<SampleExplanation>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Mutation = fn(&mut ProcessorPipelineConfig);
+
+    /// 每项只破坏一个字段，并给出预期的错误路径。
+    const INVALID_CASES: &[(&str, Mutation)] = &[
+        ("global.gravity", |c| c.global.gravity = 0.0),
+        ("global.gravity", |c| c.global.gravity = 10.5),
+        ("global.auto_gravity.window_frames", |c| {
+            c.global.auto_gravity.window_frames = 0
+        }),
+        ("reassembly.max_hold_ms", |c| c.reassembly.max_hold_ms = 0),
+        ("reassembly.max_held_bytes", |c| {
+            c.reassembly.max_held_bytes = 16
+        }),
+        ("sequencer.lookahead_frames", |c| {
+            c.sequencer.lookahead_frames = 1000
+        }),
+        ("sequencer.lookahead_ms", |c| {
+            c.sequencer.lookahead_ms = 60_000
+        }),
+        ("calibration.accel_bias.x", |c| {
+            c.calibration.accel_bias.x = 9.8
+        }),
+        ("calibration.gyro_bias.z", |c| {
+            c.calibration.gyro_bias.z = f64::NAN
+        }),
+        ("calibration.accel_matrix[1][1]", |c| {
+            c.calibration.accel_matrix[1][1] = 0.0
+        }),
+        ("calibration.gyro_matrix[0][2]", |c| {
+            c.calibration.gyro_matrix[0][2] = 2.0
+        }),
+        ("calibration_quality.window_ms", |c| {
+            c.calibration_quality.window_ms = 10
+        }),
+        ("calibration_quality.min_score", |c| {
+            c.calibration_quality.min_score = 120.0
+        }),
+        ("filter.alpha", |c| c.filter.alpha = -0.1),
+        ("filter.alpha", |c| c.filter.alpha = 1.0),
+        ("derived.alpha", |c| c.derived.alpha = 1.0),
+        ("trajectory.dt_min_ms", |c| c.trajectory.dt_min_ms = 0),
+        ("trajectory.dt_max_ms", |c| c.trajectory.dt_max_ms = 5000),
+        ("trajectory.max_integration_dt_ms", |c| {
+            c.trajectory.max_integration_dt_ms = 0
+        }),
+        ("trajectory.dt_min_ms", |c| {
+            c.trajectory.dt_min_ms = 20;
+            c.trajectory.dt_max_ms = 10;
+        }),
+        ("trajectory.accel_clamp_ms2", |c| {
+            c.trajectory.accel_clamp_ms2 = -1.0
+        }),
+        ("trajectory.accel_deadband_ms2", |c| {
+            c.trajectory.accel_deadband_ms2 = -0.1
+        }),
+        ("trajectory.accel_deadband_ms2", |c| {
+            c.trajectory.accel_deadband_ms2 = 0.5;
+            c.trajectory.accel_deadband_bypass_ms2 = 0.3;
+        }),
+        ("trajectory.accel_deadband_bypass_ms2", |c| {
+            c.trajectory.accel_deadband_bypass_ms2 = 0.0
+        }),
+        ("trajectory.position_sigma_accel_noise", |c| {
+            c.trajectory.position_sigma_accel_noise = -0.1
+        }),
+        ("zupt.gyro_thresh", |c| c.zupt.gyro_thresh = 0.0),
+        ("zupt.accel_thresh", |c| c.zupt.accel_thresh = 200.0),
+        ("zupt.gyro_exit_thresh", |c| c.zupt.gyro_exit_thresh = 50.0),
+        ("zupt.accel_enter_thresh", |c| {
+            c.zupt.accel_enter_thresh = c.zupt.accel_exit_thresh + 0.5
+        }),
+        ("zupt.gyro_enter_thresh", |c| {
+            c.zupt.gyro_enter_thresh = c.zupt.gyro_exit_thresh + 0.1
+        }),
+        ("zupt.enter_window_ms", |c| c.zupt.enter_window_ms = -10.0),
+        ("zupt.exit_window_ms", |c| c.zupt.exit_window_ms = 60_000.0),
+        ("zupt.vel_decay_tau_ms", |c| c.zupt.vel_decay_tau_ms = 0.0),
+        ("zupt.pos_lock_tau_ms", |c| {
+            c.zupt.pos_lock_tau_ms = f64::INFINITY
+        }),
+        ("zupt.vel_zero_eps", |c| c.zupt.vel_zero_eps = 5.0),
+        ("plane_constraint.height_m", |c| {
+            c.plane_constraint.height_m = f64::NAN
+        }),
+        ("eskf.gyro_noise", |c| c.eskf.gyro_noise = 0.0),
+        ("eskf.init_sigma_accel_bias", |c| {
+            c.eskf.init_sigma_accel_bias = -0.1
+        }),
+        ("segment.min_duration_ms", |c| {
+            c.segment.min_duration_ms = -1.0
+        }),
+        ("segment.min_path_length_m", |c| {
+            c.segment.min_path_length_m = f64::NAN
+        }),
+        ("segment.min_static_ms", |c| {
+            c.segment.min_static_ms = -100.0
+        }),
+        ("position_source.blend_weight", |c| {
+            c.position_source.blend_weight = 1.5
+        }),
+        ("idle.grace_period_ms", |c| c.idle.grace_period_ms = 0),
+        ("output_suppress.position_eps_m", |c| {
+            c.output_suppress.position_eps_m = -0.001
+        }),
+        ("output_suppress.heartbeat_ms", |c| {
+            c.output_suppress.heartbeat_ms = 0
+        }),
+        ("quat_health.gyro_thresh", |c| {
+            c.quat_health.gyro_thresh = 0.0
+        }),
+        ("quat_health.freeze_frames", |c| {
+            c.quat_health.freeze_frames = 1
+        }),
+        ("accel_source.consistency_thresh_ms2", |c| {
+            c.accel_source.consistency_thresh_ms2 = 0.0
+        }),
+        ("accel_source.consistency_frames", |c| {
+            c.accel_source.consistency_frames = 0
+        }),
+        ("auto_origin.static_ms", |c| c.auto_origin.static_ms = -1.0),
+        ("baro.gain", |c| c.baro.gain = 0.0),
+        ("heading_align.min_displacement_m", |c| {
+            c.heading_align.min_displacement_m = 0.0
+        }),
+        ("heading_align.min_static_ms", |c| {
+            c.heading_align.min_static_ms = -1.0
+        }),
+        ("sync_tap.threshold_g", |c| c.sync_tap.threshold_g = 1.0),
+        ("sync_tap.max_duration_ms", |c| {
+            c.sync_tap.max_duration_ms = 0.0
+        }),
+        ("sync_tap.refractory_ms", |c| {
+            c.sync_tap.refractory_ms = -1.0
+        }),
+    ];
+
+    #[test]
+    fn default_and_shipped_configs_are_valid() {
+        ProcessorPipelineConfig::default().validate().unwrap();
+        let shipped = std::fs::read_to_string(ProcessorPipelineConfig::default_config_path())
+            .expect("read processor.toml");
+        let shipped = ProcessorPipelineConfig::from_toml_str(&shipped).unwrap();
+        shipped.validate().unwrap();
+    }
+
+    #[test]
+    fn enabled_is_accepted_as_inverted_passby() {
+        let canonical = toml::to_string(&ProcessorPipelineConfig::default()).unwrap();
+        let legacy = canonical
+            .replacen("[filter]\npassby = false", "[filter]\nenabled = false", 1)
+            .replacen("[zupt]\npassby = false", "[zupt]\nenabled = true", 1);
+        assert_ne!(legacy, canonical);
+        let config = ProcessorPipelineConfig::from_toml_str(&legacy).unwrap();
+        assert!(config.filter.passby);
+        assert!(!config.zupt.passby);
+
+        let conflicting = canonical.replacen(
+            "[filter]\npassby = false",
+            "[filter]\npassby = false\nenabled = false",
+            1,
+        );
+        let error = ProcessorPipelineConfig::from_toml_str(&conflicting).unwrap_err();
+        assert!(error.to_string().contains("filter.passby"), "{error}");
+    }
+
+    #[test]
+    fn each_invalid_field_is_reported_alone() {
+        for (path, mutate) in INVALID_CASES {
+            let mut config = ProcessorPipelineConfig::default();
+            mutate(&mut config);
+            let errors = config.validate().expect_err(path).0;
+            let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+            assert_eq!(paths, [*path], "{errors:?}");
+        }
+    }
+
+    /// 按错误路径在序列化的 TOML 中逐段查找，`name[i][j]` 表示数组下标。
+    fn lookup<'a>(root: &'a toml::Value, path: &str) -> Option<&'a toml::Value> {
+        path.split('.').try_fold(root, |value, segment| {
+            let mut parts = segment.split('[');
+            let mut value = value.get(parts.next()?)?;
+            for index in parts {
+                value = value.get(index.strip_suffix(']')?.parse::<usize>().ok()?)?;
+            }
+            Some(value)
+        })
+    }
+
+    #[test]
+    fn error_paths_name_toml_fields() {
+        let mut config = ProcessorPipelineConfig::default();
+        for (_, mutate) in INVALID_CASES {
+            mutate(&mut config);
+        }
+        let errors = config.validate().unwrap_err();
+        assert!(errors.0.len() >= 25, "{errors}");
+
+        // 在合法配置的 TOML 中查找，确保每条路径都能在 processor.toml 里找到对应字段
+        let root = toml::Value::try_from(ProcessorPipelineConfig::default()).unwrap();
+        for error in &errors.0 {
+            assert!(
+                lookup(&root, &error.path).is_some(),
+                "{} not found in TOML",
+                error.path
+            );
+        }
+        assert!(errors
+            .to_string()
+            .contains("\n- zupt.accel_thresh: must be > 0 and <= 50, got 200"));
+    }
 }
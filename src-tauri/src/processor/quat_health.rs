@@ -0,0 +1,367 @@
+//! 设备姿态四元数健康监测。
+//!
+//! 部分固件状态下设备融合输出的四元数会停住数秒（数值完全不变），陀螺数据却照常上报；
+//! 管线把 `raw.quat` 直接交给导航器，这段时间的轨迹会按错误的姿态旋转。
+//! 监测器在陀螺模长超过阈值、四元数却连续 `freeze_frames` 帧没有变化时判定冻结，
+//! 改用本地陀螺积分（从最后一个正常的四元数起算）作为姿态来源；设备四元数恢复变化后
+//! 在 `blend_frames` 帧内从积分姿态 slerp 过渡回设备姿态，避免输出姿态跳变。
+//!
+//! 另有 [`QuatNormGuard`] 守住姿态模长：设备四元数经定点量化、再与零位偏移相乘后
+//! 模长会偏离 1，`rotate_vec3` 的结果随之按模长平方缩放，重力扣除随之出现偏差。
+
+use math_f64::{DQuat, DVec3};
+use serde::{Deserialize, Serialize};
+
+use crate::processor::shared::{validate::ConfigValidator, DeviceTimeMs};
+
+/// 帧间隔超过该值时不积分（断流、设备复位），避免一次性转过大角度。
+const MAX_INTEGRATION_GAP_MS: u64 = 500;
+
+/// 四元数健康监测配置。
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct QuatHealthConfig {
+    /// 是否启用。
+    pub enabled: bool,
+    /// 相邻两帧姿态差小于该值视为未变化 (°)。
+    pub freeze_eps_deg: f64,
+    /// 陀螺模长超过该值时四元数仍不变才计入冻结 (rad/s)。
+    pub gyro_thresh: f64,
+    /// 连续多少帧不变判定为冻结。
+    pub freeze_frames: u32,
+    /// 恢复后从积分姿态过渡回设备姿态的帧数，0 表示立即切回。
+    pub blend_frames: u32,
+}
+
+impl Default for QuatHealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            freeze_eps_deg: 0.001,
+            gyro_thresh: 0.3,
+            freeze_frames: 25,
+            blend_frames: 25,
+        }
+    }
+}
+
+impl QuatHealthConfig {
+    /// 校验取值范围：姿态阈值 0 ~ 1°，冻结判定 2 ~ 10000 帧，过渡不超过 10000 帧。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("freeze_eps_deg", self.freeze_eps_deg, 0.0, 1.0);
+        v.positive("gyro_thresh", self.gyro_thresh, 50.0);
+        v.in_range("freeze_frames", self.freeze_frames as f64, 2.0, 10_000.0);
+        v.in_range("blend_frames", self.blend_frames as f64, 0.0, 10_000.0);
+    }
+}
+
+/// 四元数冻结事件（`quat_freeze`），进入冻结与恢复各推送一次。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QuatFreezeEvent {
+    /// 设备时间戳 (ms)。
+    pub timestamp_ms: u64,
+    /// `true` 表示检测到冻结、改用陀螺积分；`false` 表示设备姿态恢复、开始过渡回设备姿态。
+    pub frozen: bool,
+    /// 管线重置以来检测到的冻结次数。
+    pub freeze_count: u64,
+}
+
+/// 当前姿态来源。
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttitudeSource {
+    /// 设备四元数。
+    Device,
+    /// 本地陀螺积分。
+    Gyro,
+    /// 从积分姿态过渡回设备姿态，记录已过渡的帧数。
+    Blend(u32),
+}
+
+/// 四元数健康监测器，由管线持有，逐帧决定交给导航器的姿态。
+#[derive(Debug, Clone)]
+pub struct QuatHealthMonitor {
+    config: QuatHealthConfig,
+    source: AttitudeSource,
+    /// 上一帧的设备四元数。
+    last_device: Option<DQuat>,
+    last_timestamp_ms: Option<DeviceTimeMs>,
+    /// 转动中设备四元数连续未变化的帧数。
+    unchanged_frames: u32,
+    /// 从最后一个正常的设备四元数起用陀螺积分的姿态。
+    integrated: DQuat,
+    freeze_count: u64,
+    /// 尚未被取走的冻结事件。
+    event: Option<QuatFreezeEvent>,
+}
+
+impl QuatHealthMonitor {
+    /// 创建监测器。
+    pub fn new(config: QuatHealthConfig) -> Self {
+        Self {
+            config,
+            source: AttitudeSource::Device,
+            last_device: None,
+            last_timestamp_ms: None,
+            unchanged_frames: 0,
+            integrated: DQuat::IDENTITY,
+            freeze_count: 0,
+            event: None,
+        }
+    }
+
+    /// 原地更新配置，冻结状态保留；关闭时立即切回设备姿态。
+    pub fn reconfigure(&mut self, config: QuatHealthConfig) {
+        self.config = config;
+        if !config.enabled {
+            self.source = AttitudeSource::Device;
+            self.unchanged_frames = 0;
+        }
+    }
+
+    /// 清空状态与冻结计数。
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// 姿态零位变化后调用：丢弃冻结判定与积分姿态，冻结计数保留。
+    pub fn rebase(&mut self) {
+        *self = Self {
+            freeze_count: self.freeze_count,
+            ..Self::new(self.config)
+        };
+    }
+
+    /// 检测到的冻结次数。
+    pub fn freeze_count(&self) -> u64 {
+        self.freeze_count
+    }
+
+    /// 当前是否在用陀螺积分代替设备姿态（含过渡期）。
+    pub fn is_substituting(&self) -> bool {
+        self.source != AttitudeSource::Device
+    }
+
+    /// 取走最近一次冻结或恢复事件。
+    pub fn take_event(&mut self) -> Option<QuatFreezeEvent> {
+        self.event.take()
+    }
+
+    /// 输入一帧设备四元数与标定后的角速度 (rad/s)，返回交给导航器的姿态。
+    pub fn update(&mut self, timestamp: DeviceTimeMs, device: DQuat, gyro: DVec3) -> DQuat {
+        if !self.config.enabled {
+            return device;
+        }
+        // 时间戳回退或间隔过长时本帧不积分
+        let dt = match self.last_timestamp_ms {
+            Some(prev) if timestamp.delta_ms(prev) <= MAX_INTEGRATION_GAP_MS => {
+                timestamp.delta_s(prev).as_secs()
+            }
+            _ => 0.0,
+        };
+        self.last_timestamp_ms = Some(timestamp);
+        let timestamp_ms = timestamp.as_millis();
+        let eps_rad = self.config.freeze_eps_deg.to_radians();
+        let moved = self
+            .last_device
+            .is_none_or(|last| angle_between(last, device) > eps_rad);
+        self.last_device = Some(device);
+        self.integrated = self.integrated.integrate_angular_velocity(gyro, dt);
+
+        match self.source {
+            AttitudeSource::Device => {
+                if moved || gyro.length() <= self.config.gyro_thresh {
+                    // 正常或真实静止：积分起点跟随设备姿态
+                    self.unchanged_frames = 0;
+                    self.integrated = device;
+                    return device;
+                }
+                self.unchanged_frames += 1;
+                if self.unchanged_frames < self.config.freeze_frames {
+                    return device;
+                }
+                self.freeze_count += 1;
+                self.source = AttitudeSource::Gyro;
+                self.event = Some(QuatFreezeEvent {
+                    timestamp_ms,
+                    frozen: true,
+                    freeze_count: self.freeze_count,
+                });
+                tracing::warn!(
+                    "设备四元数已冻结 {} 帧，改用陀螺积分姿态",
+                    self.unchanged_frames
+                );
+                self.integrated
+            }
+            AttitudeSource::Gyro => {
+                if !moved {
+                    return self.integrated;
+                }
+                self.event = Some(QuatFreezeEvent {
+                    timestamp_ms,
+                    frozen: false,
+                    freeze_count: self.freeze_count,
+                });
+                tracing::info!("设备四元数恢复变化，过渡回设备姿态");
+                self.blend(0, device)
+            }
+            AttitudeSource::Blend(done) => self.blend(done, device),
+        }
+    }
+
+    /// 过渡第 `done + 1` 帧：积分姿态继续随陀螺推进，按比例 slerp 到设备姿态。
+    fn blend(&mut self, done: u32, device: DQuat) -> DQuat {
+        let step = done + 1;
+        if step >= self.config.blend_frames {
+            self.source = AttitudeSource::Device;
+            self.unchanged_frames = 0;
+            self.integrated = device;
+            return device;
+        }
+        self.source = AttitudeSource::Blend(step);
+        let s = step as f64 / self.config.blend_frames as f64;
+        self.integrated.slerp(device, s)
+    }
+}
+
+/// 姿态模长偏离 1 超过该值时完整归一化并计数。
+pub const QUAT_NORM_TOLERANCE: f64 = 1e-6;
+
+/// 姿态模长守卫，由管线持有，逐帧处理零位校准后的姿态。
+///
+/// 偏差在 [`QUAT_NORM_TOLERANCE`] 以内时用一阶近似收回（偏差降到 1e-12 量级），
+/// 超出时完整归一化并计入调试监视器的归一化次数。随 `quat_health.enabled` 一起开关。
+#[derive(Debug, Clone, Default)]
+pub struct QuatNormGuard {
+    renormalizations: u64,
+}
+
+impl QuatNormGuard {
+    /// 返回模长收回 1 的姿态。
+    pub fn apply(&mut self, q: DQuat) -> DQuat {
+        // |q|² - 1 ≈ 2(|q| - 1)，省去开方
+        if (q.length_squared() - 1.0).abs() <= 2.0 * QUAT_NORM_TOLERANCE {
+            return q.normalize_fast();
+        }
+        self.renormalizations += 1;
+        q.normalize()
+    }
+
+    /// 完整归一化的累计次数。
+    pub fn renormalizations(&self) -> u64 {
+        self.renormalizations
+    }
+}
+
+/// 两个姿态之间的夹角 (rad)。
+fn angle_between(a: DQuat, b: DQuat) -> f64 {
+    2.0 * a.dot(b).abs().min(1.0).acos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT_MS: u64 = 4;
+
+    #[test]
+    fn norm_guard_holds_composed_attitude_at_unit_length() {
+        // 每一步都乘上模长略大于 1 的小转动（相当于量化后的设备四元数），
+        // 不加守卫时模长按乘积累积漂移
+        let step = |i: u32| {
+            let off = 1.0 + 3e-7 * (1.0 + (i % 5) as f64 * 0.1);
+            DQuat::from_rotation_x(1e-3) * DQuat::from_rotation_z(2e-3 * (i % 3) as f64) * off
+        };
+        let mut guard = QuatNormGuard::default();
+        let mut guarded = DQuat::IDENTITY;
+        let mut unguarded = DQuat::IDENTITY;
+        for i in 0..5_000 {
+            guarded = guard.apply(guarded * step(i));
+            unguarded *= step(i);
+            assert!(
+                (guarded.length() - 1.0).abs() < 1e-9,
+                "step {i}: {}",
+                guarded.length()
+            );
+        }
+        assert!(
+            (unguarded.length() - 1.0).abs() > 1e-3,
+            "{}",
+            unguarded.length()
+        );
+        // 一阶近似每帧都收回偏差，从不累积到需要完整归一化
+        assert_eq!(guard.renormalizations(), 0);
+        assert!(guarded.dot(unguarded.normalize()).abs() > 1.0 - 1e-12);
+
+        // 单帧偏差超出容差（零位偏移取自未归一化的四元数等）时完整归一化并计数
+        let skewed = guard.apply(DQuat::from_rotation_y(0.3) * 1.0005);
+        assert!((skewed.length() - 1.0).abs() < 1e-12);
+        assert_eq!(guard.renormalizations(), 1);
+    }
+
+    #[test]
+    fn frozen_quat_during_rotation_is_bridged_by_gyro_and_blends_back() {
+        let config = QuatHealthConfig::default();
+        let mut monitor = QuatHealthMonitor::new(config);
+        // 绕 z 轴 1 rad/s 匀速转动 2 s，第 0.5 ~ 1.3 s 设备四元数冻结；
+        // 陀螺带 0.05 rad/s 零偏，积分姿态会漂移，恢复时需要过渡
+        let bias = 0.05;
+        let gyro = DVec3::new(0.0, 0.0, 1.0 + bias);
+        let truth = |i: u64| DQuat::from_rotation_z((i * DT_MS) as f64 / 1000.0);
+        let frozen = 125..325;
+        let mut outputs = Vec::new();
+        for i in 0..500 {
+            let device = if frozen.contains(&i) {
+                truth(frozen.start - 1)
+            } else {
+                truth(i)
+            };
+            outputs.push(monitor.update(DeviceTimeMs(i * DT_MS), device, gyro));
+            if i == frozen.start + u64::from(config.freeze_frames) - 1 {
+                assert_eq!(
+                    monitor.take_event().map(|e| (e.frozen, e.freeze_count)),
+                    Some((true, 1))
+                );
+            }
+            if i == frozen.end {
+                assert_eq!(
+                    monitor.take_event().map(|e| (e.frozen, e.freeze_count)),
+                    Some((false, 1))
+                );
+            }
+        }
+        assert_eq!(monitor.freeze_count(), 1);
+        assert!(!monitor.is_substituting());
+
+        // 冻结判定之后输出跟随真实转动，误差只来自零偏
+        let detected = frozen.start + u64::from(config.freeze_frames) - 1;
+        let drift = bias * ((frozen.end - frozen.start) * DT_MS) as f64 / 1000.0;
+        for i in detected..frozen.end {
+            let error = angle_between(outputs[i as usize], truth(i));
+            assert!(error <= drift + 1e-9, "frame {i}: {error}");
+        }
+        // 全程相邻帧姿态变化有界：判定时追赶冻结期间的转动，恢复时漂移分摊到过渡帧
+        let step = DT_MS as f64 / 1000.0;
+        let catch_up = (1.0 + bias) * step * f64::from(config.freeze_frames);
+        for (i, pair) in outputs.windows(2).enumerate() {
+            let jump = angle_between(pair[0], pair[1]);
+            let bound = if i as u64 + 1 == detected {
+                step + catch_up
+            } else {
+                step * 1.5
+            };
+            assert!(jump <= bound + 1e-9, "frame {}: {jump}", i + 1);
+        }
+        assert_eq!(outputs[499], truth(499));
+    }
+
+    #[test]
+    fn static_device_with_constant_quat_is_not_a_freeze() {
+        let mut monitor = QuatHealthMonitor::new(QuatHealthConfig::default());
+        let quat = DQuat::from_rotation_x(0.3);
+        for i in 0..1000 {
+            let gyro = DVec3::new(0.01, -0.02, 0.0);
+            assert_eq!(monitor.update(DeviceTimeMs(i * DT_MS), quat, gyro), quat);
+        }
+        assert_eq!(monitor.freeze_count(), 0);
+        assert!(monitor.take_event().is_none());
+    }
+}
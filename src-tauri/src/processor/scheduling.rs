@@ -0,0 +1,376 @@
+//! 处理线程与录制线程的调度提示（优先级、核心绑定）。
+//!
+//! 低配 Windows 笔记本上 webview 会抢走处理线程的时间片，队列先积压再集中追赶，
+//! 前端表现为卡顿。这里按 `[global.scheduling]` 配置提高处理线程优先级、可选地
+//! 绑定到一个核心，并可降低录制线程优先级。各平台实现：
+//!
+//! - Windows：`SetThreadPriority` / `SetThreadAffinityMask`；
+//! - macOS：`pthread_set_qos_class_self_np`，系统不支持绑定核心；
+//! - Linux：按线程 ID `setpriority` 调整 nice 值（SCHED_OTHER 下生效，提高优先级需要
+//!   `CAP_SYS_NICE` 或 `RLIMIT_NICE`），`sched_setaffinity` 绑定核心。
+//!
+//! 全部尽力而为：失败只记警告，结果写入 [`SchedulingStatus`] 供 `get_app_status` 展示。
+//! 只在线程启动时应用，修改配置后重启应用生效。
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// `[global.scheduling]` 调度提示配置，缺省全部关闭。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SchedulingConfig {
+    /// 提高处理线程优先级。
+    pub raise_processor_priority: bool,
+    /// 把处理线程绑定到该编号的 CPU 核心（从 0 开始），缺省不绑定。
+    pub processor_core: Option<usize>,
+    /// 降低录制线程优先级，把时间片让给处理线程。
+    pub lower_recorder_priority: bool,
+}
+
+impl SchedulingConfig {
+    /// 处理线程要设置的优先级，未请求时为空。
+    pub fn processor_priority(&self) -> Option<ThreadPriority> {
+        self.raise_processor_priority
+            .then_some(ThreadPriority::High)
+    }
+
+    /// 录制线程要设置的优先级，未请求时为空。
+    pub fn recorder_priority(&self) -> Option<ThreadPriority> {
+        self.lower_recorder_priority.then_some(ThreadPriority::Low)
+    }
+}
+
+/// 线程优先级档位，各平台映射见模块文档。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadPriority {
+    /// 低于普通。
+    Low,
+    /// 普通。
+    Normal,
+    /// 高于普通（仍在普通调度类内，不会饿死系统线程）。
+    High,
+}
+
+/// 单项调度提示的结果。
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HintOutcome {
+    /// 配置未请求。
+    #[default]
+    NotRequested,
+    /// 已生效。
+    Applied,
+    /// 系统拒绝或平台不支持。
+    Failed {
+        /// 失败原因。
+        error: String,
+    },
+}
+
+/// 一个线程的调度提示结果。
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct ThreadHintsReport {
+    /// 优先级。
+    pub priority: HintOutcome,
+    /// 核心绑定。
+    pub affinity: HintOutcome,
+}
+
+/// 各线程的调度提示结果，线程尚未启动时为空。
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct SchedulingReport {
+    /// 处理线程。
+    pub processor: Option<ThreadHintsReport>,
+    /// 录制线程。
+    pub recorder: Option<ThreadHintsReport>,
+}
+
+/// 跨线程共享的调度提示结果：各线程应用后写入，`get_app_status` 读取。
+#[derive(Debug, Default)]
+pub struct SchedulingStatus {
+    report: Mutex<SchedulingReport>,
+}
+
+/// 共享的调度提示结果句柄。
+pub type SchedulingStatusHandle = Arc<SchedulingStatus>;
+
+impl SchedulingStatus {
+    /// 创建空的共享句柄。
+    pub fn new_handle() -> SchedulingStatusHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 记录处理线程的结果。
+    pub fn set_processor(&self, report: ThreadHintsReport) {
+        self.report
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .processor = Some(report);
+    }
+
+    /// 记录录制线程的结果。
+    pub fn set_recorder(&self, report: ThreadHintsReport) {
+        self.report
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .recorder = Some(report);
+    }
+
+    /// 当前结果快照。
+    pub fn snapshot(&self) -> SchedulingReport {
+        self.report
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// 对当前线程应用调度提示，失败记警告并写入结果。
+///
+/// `thread` 只用于日志；`priority` / `core` 为空表示该项未请求。
+pub fn apply_thread_hints(
+    thread: &str,
+    priority: Option<ThreadPriority>,
+    core: Option<usize>,
+) -> ThreadHintsReport {
+    ThreadHintsReport {
+        priority: priority.map_or(HintOutcome::NotRequested, |priority| {
+            outcome(thread, "priority", set_current_thread_priority(priority))
+        }),
+        affinity: core.map_or(HintOutcome::NotRequested, |core| {
+            outcome(thread, "affinity", pin_current_thread(core))
+        }),
+    }
+}
+
+fn outcome(thread: &str, hint: &str, result: io::Result<()>) -> HintOutcome {
+    match result {
+        Ok(()) => {
+            tracing::info!(thread, hint, "调度提示已生效");
+            HintOutcome::Applied
+        }
+        Err(error) => {
+            tracing::warn!(thread, hint, %error, "调度提示未生效，按默认调度运行");
+            HintOutcome::Failed {
+                error: error.to_string(),
+            }
+        }
+    }
+}
+
+/// 设置当前线程的优先级。
+pub fn set_current_thread_priority(priority: ThreadPriority) -> io::Result<()> {
+    sys::set_priority(priority)
+}
+
+/// 把当前线程绑定到编号为 `core` 的 CPU 核心。
+pub fn pin_current_thread(core: usize) -> io::Result<()> {
+    sys::pin(core)
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::io;
+
+    use super::ThreadPriority;
+
+    pub fn set_priority(priority: ThreadPriority) -> io::Result<()> {
+        let nice = match priority {
+            ThreadPriority::Low => 10,
+            ThreadPriority::Normal => 0,
+            ThreadPriority::High => -10,
+        };
+        // Linux 上以线程 ID 调用 setpriority 只影响该线程
+        // SAFETY: gettid 与 setpriority 只读写内核中的调度参数，不涉及内存。
+        let rc =
+            unsafe { libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, nice) };
+        if rc == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn pin(core: usize) -> io::Result<()> {
+        if core >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("core {core} exceeds CPU_SETSIZE"),
+            ));
+        }
+        // SAFETY: cpu_set_t 是纯位图，全零即空集合；CPU_SET 的下标已检查。
+        let rc = unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+        };
+        if rc == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod sys {
+    use std::io;
+
+    use super::ThreadPriority;
+
+    pub fn set_priority(priority: ThreadPriority) -> io::Result<()> {
+        let qos = match priority {
+            ThreadPriority::Low => libc::qos_class_t::QOS_CLASS_UTILITY,
+            ThreadPriority::Normal => libc::qos_class_t::QOS_CLASS_DEFAULT,
+            ThreadPriority::High => libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE,
+        };
+        // SAFETY: 只修改当前线程的 QoS 类别。
+        let rc = unsafe { libc::pthread_set_qos_class_self_np(qos, 0) };
+        if rc == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::from_raw_os_error(rc))
+        }
+    }
+
+    pub fn pin(_core: usize) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "macOS does not support pinning threads to a core",
+        ))
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::{ffi::c_void, io};
+
+    use super::ThreadPriority;
+
+    const THREAD_PRIORITY_BELOW_NORMAL: i32 = -1;
+    const THREAD_PRIORITY_NORMAL: i32 = 0;
+    const THREAD_PRIORITY_ABOVE_NORMAL: i32 = 1;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThread() -> *mut c_void;
+        fn SetThreadPriority(thread: *mut c_void, priority: i32) -> i32;
+        fn SetThreadAffinityMask(thread: *mut c_void, mask: usize) -> usize;
+    }
+
+    pub fn set_priority(priority: ThreadPriority) -> io::Result<()> {
+        let level = match priority {
+            ThreadPriority::Low => THREAD_PRIORITY_BELOW_NORMAL,
+            ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::High => THREAD_PRIORITY_ABOVE_NORMAL,
+        };
+        // SAFETY: GetCurrentThread 返回无需关闭的伪句柄。
+        if unsafe { SetThreadPriority(GetCurrentThread(), level) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn pin(core: usize) -> io::Result<()> {
+        if core >= usize::BITS as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("core {core} exceeds the affinity mask width"),
+            ));
+        }
+        // SAFETY: 同上，掩码位已检查。
+        if unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << core) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod sys {
+    use std::io;
+
+    use super::ThreadPriority;
+
+    pub fn set_priority(_priority: ThreadPriority) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "thread priority is not supported on this platform",
+        ))
+    }
+
+    pub fn pin(_core: usize) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "thread affinity is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::pipeline::{types::GlobalConfig, ProcessorPipelineConfig};
+
+    #[test]
+    fn scheduling_section_is_optional_and_parsed() {
+        let base = "gravity = 9.8\n";
+        let global: GlobalConfig = toml::from_str(base).unwrap();
+        assert_eq!(global.scheduling, SchedulingConfig::default());
+        assert_eq!(global.scheduling.processor_priority(), None);
+        assert_eq!(global.scheduling.recorder_priority(), None);
+
+        let toml = format!(
+            "{base}[scheduling]\nraise_processor_priority = true\n\
+             processor_core = 2\nlower_recorder_priority = true\n"
+        );
+        let tuned: GlobalConfig = toml::from_str(&toml).unwrap();
+        let scheduling = tuned.scheduling;
+        assert_eq!(scheduling.processor_priority(), Some(ThreadPriority::High));
+        assert_eq!(scheduling.processor_core, Some(2));
+        assert_eq!(scheduling.recorder_priority(), Some(ThreadPriority::Low));
+
+        // 只在线程启动时生效，改动不重建管线
+        let before = ProcessorPipelineConfig {
+            global,
+            ..Default::default()
+        };
+        let after = ProcessorPipelineConfig {
+            global: tuned,
+            ..Default::default()
+        };
+        assert!(!before.requires_reset(&after));
+    }
+
+    #[test]
+    fn unrequested_hints_are_reported_without_touching_the_thread() {
+        let status = SchedulingStatus::new_handle();
+        assert_eq!(status.snapshot(), SchedulingReport::default());
+
+        let report = apply_thread_hints("test", None, None);
+        assert_eq!(report, ThreadHintsReport::default());
+        status.set_processor(report.clone());
+        assert_eq!(status.snapshot().processor, Some(report));
+        assert_eq!(status.snapshot().recorder, None);
+    }
+
+    #[test]
+    fn shim_returns_on_this_platform() {
+        // 放在独立线程里，不影响测试线程；结果依平台与权限而定，只要求正常返回
+        std::thread::spawn(|| {
+            let report = apply_thread_hints("smoke", Some(ThreadPriority::Low), Some(0));
+            assert_ne!(report.priority, HintOutcome::NotRequested);
+            assert_ne!(report.affinity, HintOutcome::NotRequested);
+            let _ = set_current_thread_priority(ThreadPriority::High);
+            assert!(pin_current_thread(usize::MAX).is_err());
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+//! 运动分段检测逻辑。
+
+use math_f64::DVec3;
+
+use crate::processor::{
+    navigator::MotionState,
+    output::OutputFrame,
+    segment::types::{MotionSegment, SegmentConfig},
+};
+
+/// 正在累积的运动段。
+#[derive(Debug, Clone, Copy)]
+struct OpenSegment {
+    start_ms: u64,
+    start_position: DVec3,
+    last_position: DVec3,
+    path_length_m: f64,
+    peak_speed_mps: f64,
+    /// 运动后首个静止帧的时间戳，静止尚未确认时为候选终点。
+    static_since_ms: Option<u64>,
+}
+
+/// 运动分段检测器，按帧消费管线输出。
+#[derive(Debug, Clone)]
+pub struct SegmentDetector {
+    config: SegmentConfig,
+    /// 上一帧的时间戳与位置（仅在上一帧静止时有效），作为下一段的起点。
+    last_static: Option<(u64, DVec3)>,
+    open: Option<OpenSegment>,
+}
+
+impl SegmentDetector {
+    /// 创建检测器。
+    pub fn new(config: SegmentConfig) -> Self {
+        Self {
+            config,
+            last_static: None,
+            open: None,
+        }
+    }
+
+    /// 原地更新配置；关闭时丢弃正在累积的段。
+    pub fn reconfigure(&mut self, config: SegmentConfig) {
+        if !config.enabled {
+            self.reset();
+        }
+        self.config = config;
+    }
+
+    /// 清空状态。
+    pub fn reset(&mut self) {
+        self.last_static = None;
+        self.open = None;
+    }
+
+    /// 消费一帧；一段运动在静止中结束且通过过滤条件时返回分段。
+    pub fn update(&mut self, frame: &OutputFrame) -> Option<MotionSegment> {
+        if !self.config.enabled {
+            return None;
+        }
+        let timestamp_ms = frame.nav.timestamp_ms;
+        let position = frame.nav.position;
+        match frame.motion_state {
+            MotionState::Moving => {
+                if let Some(open) = self.open.as_mut() {
+                    open.static_since_ms = None;
+                    open.extend(position, frame.nav.velocity.length());
+                } else if let Some((start_ms, start_position)) = self.last_static {
+                    let mut open = OpenSegment {
+                        start_ms,
+                        start_position,
+                        last_position: start_position,
+                        path_length_m: 0.0,
+                        peak_speed_mps: 0.0,
+                        static_since_ms: None,
+                    };
+                    open.extend(position, frame.nav.velocity.length());
+                    self.open = Some(open);
+                }
+                self.last_static = None;
+                None
+            }
+            MotionState::Static => {
+                self.last_static = Some((timestamp_ms, position));
+                let open = self.open.as_mut()?;
+                open.extend(position, frame.nav.velocity.length());
+                let end_ms = *open.static_since_ms.get_or_insert(timestamp_ms);
+                if ((timestamp_ms - end_ms) as f64) < self.config.min_static_ms {
+                    return None;
+                }
+                let segment = self.open.take()?.finish(end_ms, position);
+                self.accepts(&segment).then_some(segment)
+            }
+            MotionState::Unknown => {
+                // 判定中断（ZUPT 关闭、重新初始化），这段运动没有可信的边界
+                self.reset();
+                None
+            }
+        }
+    }
+
+    fn accepts(&self, segment: &MotionSegment) -> bool {
+        segment.duration_ms as f64 >= self.config.min_duration_ms
+            && segment.path_length_m >= self.config.min_path_length_m
+    }
+}
+
+impl OpenSegment {
+    fn extend(&mut self, position: DVec3, speed: f64) {
+        self.path_length_m += position.distance(self.last_position);
+        self.last_position = position;
+        self.peak_speed_mps = self.peak_speed_mps.max(speed);
+    }
+
+    fn finish(self, end_ms: u64, end_position: DVec3) -> MotionSegment {
+        let displacement = end_position - self.start_position;
+        MotionSegment {
+            start_ms: self.start_ms,
+            end_ms,
+            duration_ms: end_ms.saturating_sub(self.start_ms),
+            path_length_m: self.path_length_m,
+            displacement,
+            displacement_m: displacement.length(),
+            peak_speed_mps: self.peak_speed_mps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::DQuat;
+
+    use super::*;
+    use crate::processor::{
+        navigator::{NavState, ZuptState},
+        parser::ImuSampleRaw,
+        shared::DeviceTimeMs,
+    };
+
+    fn frame(timestamp_ms: u64, motion_state: MotionState, x: f64, vx: f64) -> OutputFrame {
+        OutputFrame {
+            raw: ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_no_g: DVec3::ZERO,
+                accel_with_g: DVec3::ZERO,
+                gyro: DVec3::ZERO,
+                quat: DQuat::IDENTITY,
+                angle: DVec3::ZERO,
+                offset: DVec3::ZERO,
+                accel_nav: DVec3::ZERO,
+                baro: None,
+            },
+            nav: NavState {
+                timestamp_ms,
+                position: DVec3::new(x, 0.0, 0.0),
+                velocity: DVec3::new(vx, 0.0, 0.0),
+                attitude: DQuat::IDENTITY,
+            },
+            motion_state,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            clock_sync: None,
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: None,
+            debug_vectors: None,
+            stages: None,
+        }
+    }
+
+    /// 静止 → 运动（往返）→ 静止，返回检测结果。
+    fn run(
+        detector: &mut SegmentDetector,
+        start_ms: u64,
+        moving_frames: u64,
+    ) -> Vec<MotionSegment> {
+        let mut frames = vec![frame(start_ms, MotionState::Static, 0.0, 0.0)];
+        for i in 1..=moving_frames {
+            // 先沿 +x 走 0.01 m/帧，过半后折返
+            let k = i.min(moving_frames - i) as f64;
+            frames.push(frame(start_ms + i * 4, MotionState::Moving, k * 0.01, 2.5));
+        }
+        frames.push(frame(
+            start_ms + (moving_frames + 1) * 4,
+            MotionState::Static,
+            0.0,
+            0.0,
+        ));
+        frames.iter().filter_map(|f| detector.update(f)).collect()
+    }
+
+    #[test]
+    fn path_length_counts_out_and_back_motion() {
+        let mut detector = SegmentDetector::new(SegmentConfig {
+            enabled: true,
+            min_static_ms: 0.0,
+            ..Default::default()
+        });
+        let segments = run(&mut detector, 1000, 100);
+        assert_eq!(segments.len(), 1);
+        let segment = segments[0];
+        assert_eq!((segment.start_ms, segment.end_ms), (1000, 1404));
+        assert_eq!(segment.duration_ms, 404);
+        // 往返 0.5 m，位移为零
+        assert!((segment.path_length_m - 1.0).abs() < 1e-9, "{segment:?}");
+        assert!(segment.displacement_m < 1e-12);
+        assert_eq!(segment.peak_speed_mps, 2.5);
+    }
+
+    #[test]
+    fn short_twitches_and_unbounded_motion_are_ignored() {
+        let mut detector = SegmentDetector::new(SegmentConfig {
+            enabled: true,
+            min_duration_ms: 200.0,
+            min_path_length_m: 0.01,
+            min_static_ms: 0.0,
+        });
+        // 时长不足
+        assert!(run(&mut detector, 0, 20).is_empty());
+        // 路径不足：原地抖动
+        let twitch = [
+            frame(0, MotionState::Static, 0.0, 0.0),
+            frame(300, MotionState::Moving, 0.001, 0.01),
+            frame(600, MotionState::Static, 0.0, 0.0),
+        ];
+        assert!(twitch.iter().all(|f| detector.update(f).is_none()));
+
+        // 判定中断后恢复的运动没有静止起点，不计入
+        detector.update(&frame(0, MotionState::Unknown, 0.0, 0.0));
+        assert!(detector
+            .update(&frame(4, MotionState::Moving, 1.0, 1.0))
+            .is_none());
+        assert!(detector
+            .update(&frame(400, MotionState::Static, 2.0, 0.0))
+            .is_none());
+
+        // 关闭时不输出
+        let mut disabled = SegmentDetector::new(SegmentConfig::default());
+        assert!(run(&mut disabled, 0, 100).is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//! 运动分段检测。
+//!
+//! 利用 ZUPT 已有的静止/运动判定自动切分“动作”：两段静止之间的一次运动结束时
+//! （运动 → 静止）输出一条分段记录，包含起止时间、路径长度、位移与峰值速度。
+//! 起点取运动前最后一个静止帧，终点取运动后持续静止段的第一帧；静止需持续
+//! `min_static_ms` 才确认结束，更短的静止判定并入当前段。
+//! 未在静止后开始的运动（如上电即在动、重力初始化期间）不计入。
+
+/// 分段检测逻辑。
+pub mod logic;
+/// 分段配置与记录类型。
+pub mod types;
+
+/// 分段检测器。
+pub use logic::SegmentDetector;
+/// 分段配置与记录类型。
+pub use types::{MotionSegment, SegmentConfig};
@@ -0,0 +1,60 @@
+//! 运动分段类型。
+
+use math_f64::DVec3;
+use serde::{Deserialize, Serialize};
+
+use crate::processor::shared::ConfigValidator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 运动分段检测配置。
+pub struct SegmentConfig {
+    /// 是否启用。
+    pub enabled: bool,
+    /// 最短时长 (ms)，更短的运动视为抖动丢弃。
+    pub min_duration_ms: f64,
+    /// 最短路径长度 (m)，更短的运动视为抖动丢弃。
+    pub min_path_length_m: f64,
+    /// 静止需持续的时长 (ms) 才算运动结束；更短的静止判定（如加减速换向时
+    /// 加速度过零）并入当前段。
+    pub min_static_ms: f64,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_duration_ms: 200.0,
+            min_path_length_m: 0.01,
+            min_static_ms: 100.0,
+        }
+    }
+}
+
+impl SegmentConfig {
+    /// 校验取值范围。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.non_negative("min_duration_ms", self.min_duration_ms);
+        v.non_negative("min_path_length_m", self.min_path_length_m);
+        v.non_negative("min_static_ms", self.min_static_ms);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// 一次运动分段（`motion_segment` 事件载荷，录制时写入 `recording_segments`）。
+pub struct MotionSegment {
+    /// 起点设备时间戳 (ms)：运动前最后一个静止帧。
+    pub start_ms: u64,
+    /// 终点设备时间戳 (ms)：运动后持续静止段的第一帧。
+    pub end_ms: u64,
+    /// 时长 (ms)。
+    pub duration_ms: u64,
+    /// 路径长度 (m)：逐帧位置增量模长之和，累计到静止确认为止（含 ZUPT 收敛）。
+    pub path_length_m: f64,
+    /// 位移向量 (m)：静止确认时的位置 − 起点位置。
+    pub displacement: DVec3,
+    /// 位移模长 (m)。
+    pub displacement_m: f64,
+    /// 峰值速度 (m/s)。
+    pub peak_speed_mps: f64,
+}
@@ -0,0 +1,340 @@
+//! 时间戳排序逻辑。
+
+use std::collections::VecDeque;
+
+use crate::processor::{
+    parser::ImuSampleRaw,
+    sequencer::types::{DeviceReset, SequencerConfig, SequencerOutput, SequencerStats},
+    shared::{DeviceTimeMs, DEFAULT_SAMPLE_INTERVAL_MS},
+};
+
+/// 相邻放行帧间隔超过标称间隔的该倍数时计为一次丢帧。
+const GAP_THRESHOLD_FACTOR: f64 = 1.5;
+
+/// 设备时间戳为 32 位毫秒计数，约 49.7 天回绕一次。
+const TIMESTAMP_WRAP_MS: u64 = 1 << 32;
+/// 回绕判定容差 (ms)：上一帧距回绕点、本帧距零点都在此范围内才算回绕。
+const ROLLOVER_MARGIN_MS: u64 = 10_000;
+/// 重启后首帧时间戳的上限 (ms)：固件从零计数，连上后很快就有数据。
+const REBOOT_MAX_TIMESTAMP_MS: u64 = 5_000;
+/// 判定重启时上一帧时间戳的下限 (ms)：先前的时间戳须明显大于重启后的取值。
+const REBOOT_MIN_PREVIOUS_MS: u64 = 10_000;
+
+/// 按设备时间戳整理样本：去重、前瞻重排、复位检测。
+///
+/// 每次 [`push`](Self::push) 最多放行一帧，缓冲长度因此恒不超过
+/// `lookahead_frames + 1`，逐帧处理接口保持不变。
+pub struct PacketSequencer {
+    config: SequencerConfig,
+    /// 标称采样间隔 (ms)。
+    sample_interval_ms: f64,
+    /// 按时间戳升序暂存的样本。
+    pending: VecDeque<ImuSampleRaw>,
+    /// 最近一次放行的时间戳。
+    last_released_ms: Option<u64>,
+    /// 已见的最大时间戳（含缓冲中）。
+    newest_ms: Option<u64>,
+    /// 32 位时间戳回绕后累加的偏移，放行的时间戳据此展开为连续时间线。
+    epoch_offset_ms: u64,
+    stats: SequencerStats,
+}
+
+impl PacketSequencer {
+    /// 创建排序器。
+    pub fn new(config: SequencerConfig) -> Self {
+        Self {
+            config,
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            pending: VecDeque::with_capacity(config.lookahead_frames + 1),
+            last_released_ms: None,
+            newest_ms: None,
+            epoch_offset_ms: 0,
+            stats: SequencerStats::default(),
+        }
+    }
+
+    /// 送入一帧，返回按序放行的样本（若有）。
+    pub fn push(&mut self, mut sample: ImuSampleRaw) -> SequencerOutput {
+        let raw_ts = sample.timestamp_ms.as_millis();
+        let mut ts = raw_ts + self.epoch_offset_ms;
+        let mut device_reset = None;
+
+        if let Some(newest) = self.newest_ms {
+            if Self::is_rollover(newest - self.epoch_offset_ms, raw_ts) {
+                // 32 位计数回绕：时间线连续，展开后按普通帧处理
+                self.epoch_offset_ms += TIMESTAMP_WRAP_MS;
+                ts = raw_ts + self.epoch_offset_ms;
+                self.stats.rollovers += 1;
+                tracing::info!(
+                    previous_timestamp_ms = newest,
+                    timestamp_ms = raw_ts,
+                    "设备时间戳 32 位回绕"
+                );
+            }
+        }
+        sample.timestamp_ms = DeviceTimeMs(ts);
+
+        if let Some(newest) = self.newest_ms {
+            if newest.saturating_sub(ts) as f64 > self.window_ms() {
+                // 回退超过窗口：设备复位。缓冲中属于旧时间线的帧一并丢弃。
+                self.stats.device_resets += 1;
+                let rebooted =
+                    raw_ts <= REBOOT_MAX_TIMESTAMP_MS && newest >= REBOOT_MIN_PREVIOUS_MS;
+                tracing::warn!(
+                    previous_timestamp_ms = newest,
+                    timestamp_ms = raw_ts,
+                    dropped_pending = self.pending.len(),
+                    rebooted,
+                    "设备时间戳回退，按设备复位处理"
+                );
+                self.pending.clear();
+                self.last_released_ms = None;
+                self.newest_ms = None;
+                // 新时间线从设备原始计数开始
+                self.epoch_offset_ms = 0;
+                ts = raw_ts;
+                sample.timestamp_ms = DeviceTimeMs(ts);
+                device_reset = Some(DeviceReset {
+                    previous_timestamp_ms: newest,
+                    timestamp_ms: ts,
+                    rebooted,
+                });
+            } else if self.is_duplicate(ts) {
+                self.stats.duplicates += 1;
+                return SequencerOutput {
+                    sample: None,
+                    device_reset,
+                };
+            } else if self.last_released_ms.is_some_and(|last| ts < last) {
+                // 其时间位置已经放行，插回会让 dt 变负，只能丢弃
+                self.stats.late += 1;
+                return SequencerOutput {
+                    sample: None,
+                    device_reset,
+                };
+            }
+        }
+
+        self.newest_ms = Some(self.newest_ms.map_or(ts, |newest| newest.max(ts)));
+        let index = self
+            .pending
+            .partition_point(|queued| queued.timestamp_ms.as_millis() < ts);
+        if index < self.pending.len() {
+            self.stats.reordered += 1;
+        }
+        self.pending.insert(index, sample);
+
+        SequencerOutput {
+            sample: self.release(),
+            device_reset,
+        }
+    }
+
+    /// 设置标称采样间隔 (ms)，缓冲与统计保留。
+    pub fn set_sample_interval_ms(&mut self, sample_interval_ms: f64) {
+        self.sample_interval_ms = sample_interval_ms;
+    }
+
+    /// 累计统计。
+    pub fn stats(&self) -> SequencerStats {
+        self.stats
+    }
+
+    /// 清空缓冲与统计。
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.last_released_ms = None;
+        self.newest_ms = None;
+        self.epoch_offset_ms = 0;
+        self.stats = SequencerStats::default();
+    }
+
+    /// 上一帧接近 32 位上限、本帧接近零且前向间隔很小：计数回绕而非复位。
+    ///
+    /// 与重启的区别在于上一帧时间戳的量级：重启前的时间戳远小于回绕点。
+    fn is_rollover(previous_raw_ms: u64, raw_ts: u64) -> bool {
+        previous_raw_ms >= TIMESTAMP_WRAP_MS - ROLLOVER_MARGIN_MS
+            && raw_ts + TIMESTAMP_WRAP_MS - previous_raw_ms <= ROLLOVER_MARGIN_MS
+    }
+
+    /// 生效的前瞻窗口 (ms)。
+    fn window_ms(&self) -> f64 {
+        (self.config.lookahead_ms as f64)
+            .max(self.config.lookahead_frames as f64 * self.sample_interval_ms)
+    }
+
+    fn is_duplicate(&self, ts: u64) -> bool {
+        self.last_released_ms == Some(ts)
+            || self
+                .pending
+                .iter()
+                .any(|queued| queued.timestamp_ms.as_millis() == ts)
+    }
+
+    fn release(&mut self) -> Option<ImuSampleRaw> {
+        let should_release = if self.config.reorder {
+            let span = match (self.pending.front(), self.newest_ms) {
+                (Some(oldest), Some(newest)) => {
+                    newest.saturating_sub(oldest.timestamp_ms.as_millis())
+                }
+                _ => 0,
+            };
+            self.pending.len() > self.config.lookahead_frames || span as f64 > self.window_ms()
+        } else {
+            true
+        };
+        if !should_release {
+            return None;
+        }
+        let sample = self.pending.pop_front()?;
+        if let Some(last) = self.last_released_ms {
+            let delta_ms = sample.timestamp_ms.as_millis().saturating_sub(last) as f64;
+            if delta_ms > self.sample_interval_ms * GAP_THRESHOLD_FACTOR {
+                self.stats.gaps += 1;
+                let missing = (delta_ms / self.sample_interval_ms).round() as u64;
+                self.stats.missing_frames += missing.saturating_sub(1).max(1);
+            }
+        }
+        self.last_released_ms = Some(sample.timestamp_ms.as_millis());
+        Some(sample)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+
+    fn sample(timestamp_ms: u64) -> ImuSampleRaw {
+        ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        }
+    }
+
+    fn released(sequencer: &mut PacketSequencer, timestamps: &[u64]) -> Vec<u64> {
+        timestamps
+            .iter()
+            .filter_map(|&ts| sequencer.push(sample(ts)).sample)
+            .map(|s| s.timestamp_ms.as_millis())
+            .collect()
+    }
+
+    #[test]
+    fn reorder_buffer_releases_swapped_pair_in_order() {
+        let mut sequencer = PacketSequencer::new(SequencerConfig {
+            reorder: true,
+            ..SequencerConfig::default()
+        });
+        let out = released(&mut sequencer, &[0, 4, 12, 8, 16, 20, 24]);
+        assert_eq!(out, vec![0, 4, 8, 12, 16]);
+        assert_eq!(sequencer.stats().reordered, 1);
+    }
+
+    #[test]
+    fn passthrough_drops_duplicates_and_late_frames() {
+        let mut sequencer = PacketSequencer::new(SequencerConfig::default());
+        let out = released(&mut sequencer, &[0, 4, 4, 8, 16, 12, 20]);
+        assert_eq!(out, vec![0, 4, 8, 16, 20]);
+        let stats = sequencer.stats();
+        assert_eq!(
+            (stats.duplicates, stats.late, stats.device_resets),
+            (1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn gap_detection_scales_with_sample_interval() {
+        // 同样是"丢了 2 帧"：250 Hz 下跳过 8 ms，100 Hz 下跳过 20 ms
+        let mut fast = PacketSequencer::new(SequencerConfig::default());
+        released(&mut fast, &[0, 4, 8, 20, 24]);
+
+        let mut slow = PacketSequencer::new(SequencerConfig::default());
+        slow.set_sample_interval_ms(10.0);
+        let out = released(&mut slow, &[0, 10, 20, 50, 60]);
+        assert_eq!(out, vec![0, 10, 20, 50, 60]);
+
+        for stats in [fast.stats(), slow.stats()] {
+            assert_eq!((stats.gaps, stats.missing_frames), (1, 2));
+            assert_eq!(stats.device_resets, 0);
+        }
+
+        // 100 Hz 下一帧乱序（回退 10 ms）仍在窗口内，不应判为设备复位
+        let mut slow = PacketSequencer::new(SequencerConfig {
+            reorder: true,
+            ..SequencerConfig::default()
+        });
+        slow.set_sample_interval_ms(10.0);
+        let out = released(&mut slow, &[0, 10, 30, 20, 40, 50, 60]);
+        assert_eq!(out, vec![0, 10, 20, 30, 40]);
+        let stats = slow.stats();
+        assert_eq!(
+            (stats.reordered, stats.device_resets, stats.gaps),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn rollover_continues_timeline_and_reboot_resets() {
+        // 回绕：上一帧距 2^32 只差 2 ms，本帧从 2 重新计数，时间线连续
+        let mut sequencer = PacketSequencer::new(SequencerConfig::default());
+        let before = TIMESTAMP_WRAP_MS - 2;
+        let mut resets = Vec::new();
+        let mut out = Vec::new();
+        for ts in [before - 4, before, 2, 6] {
+            let output = sequencer.push(sample(ts));
+            resets.extend(output.device_reset);
+            out.extend(output.sample.map(|s| s.timestamp_ms.as_millis()));
+        }
+        assert!(resets.is_empty());
+        assert_eq!(
+            out,
+            vec![
+                before - 4,
+                before,
+                TIMESTAMP_WRAP_MS + 2,
+                TIMESTAMP_WRAP_MS + 6
+            ]
+        );
+        let stats = sequencer.stats();
+        assert_eq!(
+            (stats.rollovers, stats.device_resets, stats.gaps),
+            (1, 0, 0)
+        );
+
+        // 重启：运行约一小时后时间戳回到零附近
+        let mut sequencer = PacketSequencer::new(SequencerConfig::default());
+        released(&mut sequencer, &[3_600_000, 3_600_004]);
+        let output = sequencer.push(sample(120));
+        let reset = output.device_reset.expect("应判为复位");
+        assert!(reset.rebooted);
+        assert_eq!(output.sample.unwrap().timestamp_ms, DeviceTimeMs(120));
+        assert_eq!(sequencer.stats().rollovers, 0);
+
+        // 回绕之后再重启：展开的时间线被丢弃，从设备原始计数重新开始
+        let mut sequencer = PacketSequencer::new(SequencerConfig::default());
+        released(&mut sequencer, &[before, 2, 60_000]);
+        let output = sequencer.push(sample(40));
+        assert!(output.device_reset.unwrap().rebooted);
+        assert_eq!(output.sample.unwrap().timestamp_ms, DeviceTimeMs(40));
+
+        // 时间戳大幅回退但未回到零附近：普通复位，不需要重新下发配置
+        let mut sequencer = PacketSequencer::new(SequencerConfig::default());
+        released(&mut sequencer, &[3_600_000]);
+        assert!(
+            !sequencer
+                .push(sample(1_800_000))
+                .device_reset
+                .unwrap()
+                .rebooted
+        );
+    }
+}
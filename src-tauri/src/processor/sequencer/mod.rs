@@ -0,0 +1,21 @@
+//! 时间戳排序模块导出。
+//!
+//! BLE 通知偶尔乱序或重复到达：同一设备时间戳出现两次、随后出现一个间隔翻倍的帧。
+//! 直接送进 Navigator 会让重复帧得到 dt_min、下一帧得到双倍 dt，等于把同一段
+//! 间隔积分了两次。这里在标定/滤波之前按设备时间戳整理样本：
+//!
+//! - 与已见样本时间戳完全相同的帧直接丢弃；
+//! - 可选的前瞻缓冲（默认 2 帧 / 8 ms）把迟到的帧插回正确位置后再放行；
+//! - 回退超过缓冲窗口的时间戳视为设备复位，清空缓冲并通知 Navigator 重置 dt 基准。
+//!
+//! 前瞻缓冲会增加固定延迟，因此默认关闭；关闭时迟到帧（回退不超过窗口）被丢弃。
+
+/// 排序逻辑。
+pub mod logic;
+/// 排序配置与统计类型。
+pub mod types;
+
+/// 时间戳排序器。
+pub use logic::PacketSequencer;
+/// 排序配置、输出与统计类型。
+pub use types::{DeviceReset, SequencerConfig, SequencerOutput, SequencerStats};
@@ -0,0 +1,104 @@
+//! 时间戳排序相关类型。
+
+use serde::{Deserialize, Serialize};
+
+use crate::processor::{parser::ImuSampleRaw, shared::ConfigValidator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+/// 时间戳排序配置。
+pub struct SequencerConfig {
+    /// 是否启用前瞻重排（会增加最多 `lookahead_frames` 帧延迟）。
+    pub reorder: bool,
+    /// 前瞻缓冲最多暂存的帧数。
+    pub lookahead_frames: usize,
+    /// 前瞻窗口 (ms)：缓冲内时间跨度超过该值即放行最旧帧；
+    /// 时间戳回退超过该值视为设备复位。
+    ///
+    /// 实际窗口不小于 `lookahead_frames` 个标称采样间隔，低上报频率下不会把
+    /// 一帧乱序误判为复位。
+    pub lookahead_ms: u64,
+    /// 检测到设备重启时是否一并重置导航状态（速度、位置）。
+    ///
+    /// 关闭时只重置 dt 基准并重新对齐设备位置原点，轨迹从重启前的位置继续。
+    #[serde(default)]
+    pub reset_on_reboot: bool,
+}
+
+impl Default for SequencerConfig {
+    fn default() -> Self {
+        Self {
+            reorder: false,
+            lookahead_frames: 2,
+            lookahead_ms: 8,
+            reset_on_reboot: false,
+        }
+    }
+}
+
+impl SequencerConfig {
+    /// 前瞻缓冲上限（帧）。
+    const MAX_LOOKAHEAD_FRAMES: usize = 64;
+    /// 前瞻窗口上限 (ms)。
+    const MAX_LOOKAHEAD_MS: u64 = 1000;
+
+    /// 校验取值范围。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.check(
+            "lookahead_frames",
+            self.lookahead_frames <= Self::MAX_LOOKAHEAD_FRAMES,
+            format!(
+                "must be <= {}, got {}",
+                Self::MAX_LOOKAHEAD_FRAMES,
+                self.lookahead_frames
+            ),
+        );
+        v.check(
+            "lookahead_ms",
+            self.lookahead_ms <= Self::MAX_LOOKAHEAD_MS,
+            format!(
+                "must be <= {}, got {}",
+                Self::MAX_LOOKAHEAD_MS,
+                self.lookahead_ms
+            ),
+        );
+    }
+}
+
+/// 设备复位信息（时间戳大幅回退）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DeviceReset {
+    /// 复位前最新的设备时间戳 (ms)。
+    pub previous_timestamp_ms: u64,
+    /// 复位后首帧的设备时间戳 (ms)。
+    pub timestamp_ms: u64,
+    /// 是否判定为设备重启：时间戳从较大值回到零附近，固件设置已恢复默认，需要重新下发配置。
+    pub rebooted: bool,
+}
+
+/// 单次入队的结果。
+#[derive(Debug, Clone, Copy)]
+pub struct SequencerOutput {
+    /// 按时间顺序放行的样本，缓冲未满或本帧被丢弃时为空。
+    pub sample: Option<ImuSampleRaw>,
+    /// 本帧触发了设备复位。
+    pub device_reset: Option<DeviceReset>,
+}
+
+/// 排序统计（自上次重置起累计）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SequencerStats {
+    /// 丢弃的重复时间戳帧数。
+    pub duplicates: u64,
+    /// 丢弃的迟到帧数（其时间位置已放行）。
+    pub late: u64,
+    /// 被插回正确位置的乱序帧数。
+    pub reordered: u64,
+    /// 检测到的设备复位次数。
+    pub device_resets: u64,
+    /// 32 位设备时间戳回绕次数（按连续时间线展开，不计为复位）。
+    pub rollovers: u64,
+    /// 相邻放行帧间隔超过标称间隔 1.5 倍的次数（丢帧）。
+    pub gaps: u64,
+    /// 按标称间隔估算的丢失帧数。
+    pub missing_frames: u64,
+}
@@ -0,0 +1,61 @@
+//! 角度回绕工具。
+//!
+//! 欧拉角在 ±180° 处不连续：零位取在 179° 后再转 2°，原始角变为 -179°，
+//! 直接相减得到 -358°，前端会画成一整圈的突跳。所有欧拉角的差值都应先回绕。
+
+/// 把角度回绕到 (-180°, 180°]。
+pub fn wrap_degrees(degrees: f64) -> f64 {
+    let wrapped = degrees.rem_euclid(360.0);
+    if wrapped > 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// 两个角度之差 `a - b`，取 (-180°, 180°] 内的最短路径。
+pub fn angle_diff_degrees(a: f64, b: f64) -> f64 {
+    wrap_degrees(a - b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_into_half_open_range() {
+        assert_eq!(wrap_degrees(180.0), 180.0);
+        assert_eq!(wrap_degrees(-180.0), 180.0);
+        assert_eq!(wrap_degrees(181.0), -179.0);
+        assert_eq!(wrap_degrees(-181.0), 179.0);
+        assert_eq!(wrap_degrees(540.0), 180.0);
+        assert_eq!(wrap_degrees(-719.5), 0.5);
+        assert_eq!(wrap_degrees(0.0), 0.0);
+    }
+
+    #[test]
+    fn difference_crosses_boundary_in_both_directions() {
+        // 零位 179°，正向转 2° 到 -179°
+        assert!((angle_diff_degrees(-179.0, 179.0) - 2.0).abs() < 1e-12);
+        // 零位 -179°，反向转 2° 到 179°
+        assert!((angle_diff_degrees(179.0, -179.0) + 2.0).abs() < 1e-12);
+        assert_eq!(angle_diff_degrees(90.0, -90.0), 180.0);
+    }
+
+    #[test]
+    fn sweep_through_180_is_continuous() {
+        let offset = 179.0;
+        let diffs: Vec<f64> = (0..=80)
+            .map(|i| wrap_degrees(170.0 + i as f64 * 0.25))
+            .map(|raw| angle_diff_degrees(raw, offset))
+            .collect();
+        for pair in diffs.windows(2) {
+            assert!(
+                (pair[1] - pair[0] - 0.25).abs() < 1e-9,
+                "差值序列不连续: {pair:?}"
+            );
+        }
+        assert!((diffs[0] + 9.0).abs() < 1e-9);
+        assert!((diffs[80] - 11.0).abs() < 1e-9);
+    }
+}
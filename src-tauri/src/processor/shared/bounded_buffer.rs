@@ -0,0 +1,395 @@
+//! 有界缓冲区与内存占用登记。
+//!
+//! 长时间会话里常驻内存的历史（输出回放历史、飞行记录仪）都是"满了丢最旧"的队列。
+//! [`BoundedBuffer`] 统一这种淘汰策略：条数上限必选，字节上限可选（按估算闭包累计），
+//! 任一超限就从最旧一端淘汰。
+//!
+//! 缓冲区可以登记到 [`BufferRegistry`]：每次写入后更新登记表中的条数与估算字节数，
+//! 释放时自动注销；[`BufferRegistry::report`] 汇总所有在册缓冲区的占用，供状态快照展示。
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    ops::{Index, RangeBounds},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock,
+    },
+};
+
+use serde::Serialize;
+
+/// 单个缓冲区的占用情况。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BufferUsage {
+    /// 缓冲区名称。
+    pub name: String,
+    /// 当前条数。
+    pub items: usize,
+    /// 当前估算字节数。
+    pub bytes: usize,
+    /// 条数上限。
+    pub max_items: usize,
+    /// 字节上限，未设置时为空。
+    pub max_bytes: Option<usize>,
+}
+
+type UsageCell = Arc<Mutex<BufferUsage>>;
+
+#[derive(Debug, Default)]
+struct RegistryInner {
+    next_id: AtomicU64,
+    entries: Mutex<BTreeMap<u64, UsageCell>>,
+}
+
+/// 具名缓冲区登记表，跨线程共享。
+#[derive(Debug, Clone, Default)]
+pub struct BufferRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+impl BufferRegistry {
+    /// 进程内共用的登记表，状态快照从这里取内存报告。
+    pub fn global() -> &'static BufferRegistry {
+        static GLOBAL: OnceLock<BufferRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(BufferRegistry::default)
+    }
+
+    /// 所有在册缓冲区的占用，按登记先后排列。
+    pub fn report(&self) -> Vec<BufferUsage> {
+        self.lock()
+            .values()
+            .map(|usage| lock_usage(usage).clone())
+            .collect()
+    }
+
+    fn register(&self, usage: BufferUsage) -> BufferRegistration {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let usage = Arc::new(Mutex::new(usage));
+        self.lock().insert(id, usage.clone());
+        BufferRegistration {
+            registry: self.clone(),
+            id,
+            usage,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u64, UsageCell>> {
+        self.inner
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn lock_usage(usage: &UsageCell) -> MutexGuard<'_, BufferUsage> {
+    usage
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// 缓冲区在登记表中的条目，释放时注销。
+#[derive(Debug)]
+struct BufferRegistration {
+    registry: BufferRegistry,
+    id: u64,
+    usage: UsageCell,
+}
+
+impl Drop for BufferRegistration {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
+
+/// 单条目字节数估算。
+type SizeEstimate<T> = Box<dyn Fn(&T) -> usize + Send + Sync>;
+
+/// 有界先进先出缓冲区：超过条数上限或字节上限时淘汰最旧的条目。
+///
+/// 按条数上限预分配，写满后不再分配。最新写入的一条总会保留，即使它单独超过字节上限；
+/// 条数上限为 0 时不保留任何条目（关闭）。
+pub struct BoundedBuffer<T> {
+    items: VecDeque<T>,
+    max_items: usize,
+    max_bytes: Option<usize>,
+    estimate: SizeEstimate<T>,
+    bytes: usize,
+    registration: Option<BufferRegistration>,
+}
+
+impl<T> BoundedBuffer<T> {
+    /// 创建条数上限为 `max_items` 的缓冲区，每条按 `size_of::<T>()` 估算字节数。
+    pub fn new(max_items: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(max_items),
+            max_items,
+            max_bytes: None,
+            estimate: Box::new(|_| std::mem::size_of::<T>()),
+            bytes: 0,
+            registration: None,
+        }
+    }
+
+    /// 设置字节上限，`estimate` 估算单条占用（如堆上的字符串与数组）。
+    pub fn with_max_bytes(
+        mut self,
+        max_bytes: usize,
+        estimate: impl Fn(&T) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self.estimate = Box::new(estimate);
+        self.bytes = self.items.iter().map(&self.estimate).sum();
+        self.evict();
+        self
+    }
+
+    /// 以 `name` 登记到 `registry`，之后每次变化都会更新登记表。
+    pub fn register(mut self, registry: &BufferRegistry, name: &str) -> Self {
+        self.registration = Some(registry.register(self.usage(name)));
+        self
+    }
+
+    /// 写入一条，超限时从最旧一端淘汰。
+    pub fn push(&mut self, item: T) {
+        if self.max_items == 0 {
+            return;
+        }
+        // 先腾出位置再写入，写满后 push_back 不会触发扩容
+        while self.items.len() >= self.max_items && self.pop_oldest() {}
+        self.bytes += (self.estimate)(&item);
+        self.items.push_back(item);
+        self.evict();
+    }
+
+    /// 调整条数上限，保留最新的条目。
+    pub fn set_max_items(&mut self, max_items: usize) {
+        self.max_items = max_items;
+        self.evict();
+        if max_items > self.items.capacity() {
+            self.items.reserve_exact(max_items - self.items.len());
+        } else {
+            self.items.shrink_to(max_items);
+        }
+        self.publish();
+    }
+
+    /// 清空全部条目。
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.bytes = 0;
+        self.publish();
+    }
+
+    /// 条数上限。
+    pub fn max_items(&self) -> usize {
+        self.max_items
+    }
+
+    /// 当前条数。
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// 是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 当前估算字节数。
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// 最新的一条。
+    pub fn back(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// 按写入先后迭代。
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+
+    /// 按写入先后迭代下标范围内的条目（0 为最旧）。
+    pub fn range<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.range(range)
+    }
+
+    fn evict(&mut self) {
+        while (self.items.len() > self.max_items || self.over_bytes()) && self.pop_oldest() {}
+        self.publish();
+    }
+
+    fn pop_oldest(&mut self) -> bool {
+        let Some(oldest) = self.items.pop_front() else {
+            return false;
+        };
+        self.bytes -= (self.estimate)(&oldest);
+        true
+    }
+
+    fn over_bytes(&self) -> bool {
+        self.items.len() > 1 && self.max_bytes.is_some_and(|max| self.bytes > max)
+    }
+
+    fn usage(&self, name: &str) -> BufferUsage {
+        BufferUsage {
+            name: name.to_string(),
+            items: self.items.len(),
+            bytes: self.bytes,
+            max_items: self.max_items,
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    fn publish(&self) {
+        if let Some(registration) = &self.registration {
+            let mut usage = lock_usage(&registration.usage);
+            usage.items = self.items.len();
+            usage.bytes = self.bytes;
+            usage.max_items = self.max_items;
+            usage.max_bytes = self.max_bytes;
+        }
+    }
+}
+
+impl<T> Index<usize> for BoundedBuffer<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.items[index]
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BoundedBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoundedBuffer")
+            .field("len", &self.items.len())
+            .field("bytes", &self.bytes)
+            .field("max_items", &self.max_items)
+            .field("max_bytes", &self.max_bytes)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents<T: Clone>(buffer: &BoundedBuffer<T>) -> Vec<T> {
+        buffer.iter().cloned().collect()
+    }
+
+    #[test]
+    fn item_limit_evicts_oldest_first() {
+        let mut buffer = BoundedBuffer::new(3);
+        for i in 0..5u32 {
+            buffer.push(i);
+        }
+        assert_eq!(contents(&buffer), [2, 3, 4]);
+        assert_eq!(buffer.bytes(), 3 * std::mem::size_of::<u32>());
+
+        buffer.set_max_items(2);
+        assert_eq!(contents(&buffer), [3, 4]);
+        buffer.set_max_items(4);
+        buffer.push(5);
+        buffer.push(6);
+        assert_eq!(contents(&buffer), [3, 4, 5, 6]);
+        assert_eq!(buffer[0], 3);
+
+        let mut disabled = BoundedBuffer::new(0);
+        disabled.push(1u32);
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn wrapping_does_not_grow_the_allocation() {
+        let mut buffer = BoundedBuffer::new(4);
+        let capacity = buffer.items.capacity();
+        for i in 0..20u32 {
+            buffer.push(i);
+            assert_eq!(buffer.items.capacity(), capacity);
+        }
+        assert_eq!(contents(&buffer), [16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn byte_limit_evicts_oldest_first_but_keeps_the_newest() {
+        let mut buffer = BoundedBuffer::new(100).with_max_bytes(10, |s: &String| s.len());
+        buffer.push("aaaa".to_string());
+        buffer.push("bbb".to_string());
+        buffer.push("cc".to_string());
+        assert_eq!(contents(&buffer), ["aaaa", "bbb", "cc"]);
+        assert_eq!(buffer.bytes(), 9);
+
+        // 超出 10 字节：淘汰 "aaaa" 即可
+        buffer.push("dd".to_string());
+        assert_eq!(contents(&buffer), ["bbb", "cc", "dd"]);
+        assert_eq!(buffer.bytes(), 7);
+
+        // 单条超限：其余全部淘汰，只留最新一条
+        buffer.push("x".repeat(12));
+        assert_eq!(contents(&buffer), ["x".repeat(12)]);
+        assert_eq!(buffer.bytes(), 12);
+
+        // 两个上限同时生效，先触发的为准
+        let mut buffer = BoundedBuffer::new(2).with_max_bytes(100, |s: &String| s.len());
+        for s in ["a", "b", "c"] {
+            buffer.push(s.to_string());
+        }
+        assert_eq!(contents(&buffer), ["b", "c"]);
+    }
+
+    #[test]
+    fn registry_report_tracks_buffer_contents() {
+        let registry = BufferRegistry::default();
+        let mut items = BoundedBuffer::new(3).register(&registry, "items");
+        let mut strings = BoundedBuffer::new(10)
+            .with_max_bytes(6, |s: &String| s.len())
+            .register(&registry, "strings");
+
+        for i in 0..5u64 {
+            items.push(i);
+        }
+        for s in ["abc", "de", "fgh"] {
+            strings.push(s.to_string());
+        }
+        assert_eq!(contents(&strings), ["de", "fgh"]);
+
+        let expected = |buffer_items, bytes, max_items, max_bytes, name: &str| BufferUsage {
+            name: name.to_string(),
+            items: buffer_items,
+            bytes,
+            max_items,
+            max_bytes,
+        };
+        assert_eq!(
+            registry.report(),
+            [
+                expected(items.len(), items.bytes(), 3, None, "items"),
+                expected(2, 5, 10, Some(6), "strings"),
+            ]
+        );
+        assert_eq!(registry.report()[0].bytes, 3 * 8);
+
+        items.clear();
+        strings.set_max_items(1);
+        assert_eq!(
+            registry.report(),
+            [
+                expected(0, 0, 3, None, "items"),
+                expected(1, 3, 1, Some(6), "strings"),
+            ]
+        );
+
+        // 释放即注销
+        drop(items);
+        assert_eq!(registry.report().len(), 1);
+        drop(strings);
+        assert!(registry.report().is_empty());
+    }
+}
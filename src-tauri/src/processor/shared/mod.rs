@@ -0,0 +1,34 @@
+//! 处理链各阶段共用的小工具。
+
+/// 角度回绕。
+pub mod angle;
+/// 有界缓冲区与内存登记。
+pub mod bounded_buffer;
+/// 限频日志。
+pub mod rate_limit;
+/// 流式均值 / 方差。
+pub mod running_stats;
+/// 标称采样间隔。
+pub mod timing;
+/// 时间单位新类型。
+pub mod units;
+/// 配置取值校验。
+pub mod validate;
+
+/// 角度回绕与差值。
+pub use angle::{angle_diff_degrees, wrap_degrees};
+/// 有界缓冲区。
+pub use bounded_buffer::{BoundedBuffer, BufferRegistry, BufferUsage};
+/// 限频日志器。
+pub use rate_limit::{KeyedRateLimiter, RateLimitedLogger};
+/// 流式统计量。
+pub use running_stats::{RunningStats, RunningStatsVec3};
+/// 标称采样间隔换算。
+pub use timing::{
+    frames_for_window, nominal_sample_interval_ms, WindowCounter, DEFAULT_REPORT_RATE_HZ,
+    DEFAULT_SAMPLE_INTERVAL_MS, WALL_TIME_WINDOW_INTERVAL_MS,
+};
+/// 时间单位。
+pub use units::{DeviceTimeMs, DurationS, HostTimeMs};
+/// 配置校验。
+pub use validate::{ConfigError, ConfigErrors, ConfigValidator};
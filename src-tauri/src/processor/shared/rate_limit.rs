@@ -0,0 +1,237 @@
+//! 限频日志工具。
+//!
+//! 高频路径（每帧调用）上的状态日志需要限频，否则 1 kHz 输入下日志本身就会
+//! 成为瓶颈。旧做法 `timestamp_ms % 1000 < 4` 依赖设备时间戳的步长：250 Hz 时
+//! 每秒一条，1 kHz 时每秒四条，时间戳抖动时甚至一条都没有。
+//!
+//! 两种用法：
+//! - [`RateLimitedLogger`]：由单个组件持有的闸门（如导航器的 ZUPT 日志），`&mut` 调用；
+//! - [`KeyedRateLimiter`]：按静态键共享、跨线程可用，直接输出日志并在消息末尾附上
+//!   `(suppressed N similar)`。没有合适持有者的路径（解析失败、录制写入失败）用全局的
+//!   [`PER_SECOND`]。
+
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// 按主机时间限频的日志闸门。
+///
+/// 本身不输出日志，只决定"这次该不该打"，格式化开销只在放行时才产生：
+///
+/// ```ignore
+/// if let Some(suppressed) = self.zupt_log.check() {
+///     tracing::info!(suppressed, "ZUPT 修正 | ...");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimitedLogger {
+    interval: Duration,
+    last_emit: Option<Instant>,
+    suppressed: u64,
+}
+
+impl RateLimitedLogger {
+    /// 创建日志闸门，`interval` 内最多放行一次。
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_emit: None,
+            suppressed: 0,
+        }
+    }
+
+    /// 每秒最多放行一次。
+    pub const fn per_second() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+
+    /// 检查是否放行。
+    ///
+    /// 放行时返回自上次放行以来被抑制的次数，否则返回 `None`。
+    pub fn check(&mut self) -> Option<u64> {
+        self.check_at(Instant::now())
+    }
+
+    /// 以指定时刻检查是否放行。
+    pub fn check_at(&mut self, now: Instant) -> Option<u64> {
+        match self.last_emit {
+            Some(last) if now.saturating_duration_since(last) < self.interval => {
+                self.suppressed += 1;
+                None
+            }
+            _ => {
+                self.last_emit = Some(now);
+                Some(std::mem::take(&mut self.suppressed))
+            }
+        }
+    }
+}
+
+/// 全局按键限频表：每个键每秒最多输出一次。
+pub static PER_SECOND: KeyedRateLimiter = KeyedRateLimiter::new(Duration::from_secs(1));
+
+/// 按静态键共享的限频日志，线程安全。
+///
+/// 每个键各有一个 [`RateLimitedLogger`] 闸门；消息以闭包传入，只在放行时格式化：
+///
+/// ```ignore
+/// PER_SECOND.warn("imu_parse", || format!("IMU 数据解析失败: {e:?}"));
+/// ```
+#[derive(Debug)]
+pub struct KeyedRateLimiter {
+    interval: Duration,
+    gates: Mutex<BTreeMap<&'static str, RateLimitedLogger>>,
+}
+
+impl KeyedRateLimiter {
+    /// 创建限频表，每个键在 `interval` 内最多放行一次。
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            gates: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// 检查 `key` 是否放行，语义同 [`RateLimitedLogger::check`]。
+    pub fn check(&self, key: &'static str) -> Option<u64> {
+        self.check_at(key, Instant::now())
+    }
+
+    /// 以指定时刻检查 `key` 是否放行。
+    pub fn check_at(&self, key: &'static str, now: Instant) -> Option<u64> {
+        self.lock()
+            .entry(key)
+            .or_insert_with(|| RateLimitedLogger::new(self.interval))
+            .check_at(now)
+    }
+
+    /// 限频输出 warn 日志。
+    pub fn warn(&self, key: &'static str, message: impl FnOnce() -> String) {
+        if let Some(suppressed) = self.check(key) {
+            tracing::warn!(
+                key,
+                suppressed,
+                "{}",
+                with_suppressed(message(), suppressed)
+            );
+        }
+    }
+
+    /// 限频输出 info 日志。
+    pub fn info(&self, key: &'static str, message: impl FnOnce() -> String) {
+        if let Some(suppressed) = self.check(key) {
+            tracing::info!(
+                key,
+                suppressed,
+                "{}",
+                with_suppressed(message(), suppressed)
+            );
+        }
+    }
+
+    /// 限频输出 error 日志。
+    pub fn error(&self, key: &'static str, message: impl FnOnce() -> String) {
+        if let Some(suppressed) = self.check(key) {
+            tracing::error!(
+                key,
+                suppressed,
+                "{}",
+                with_suppressed(message(), suppressed)
+            );
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<&'static str, RateLimitedLogger>> {
+        self.gates
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 有被抑制的同类日志时在消息末尾附上 `(suppressed N similar)`。
+pub fn with_suppressed(mut message: String, suppressed: u64) -> String {
+    if suppressed > 0 {
+        message.push_str(&format!(" (suppressed {suppressed} similar)"));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn emits_once_per_interval_and_reports_suppressed() {
+        let mut logger = RateLimitedLogger::per_second();
+        let t0 = Instant::now();
+        assert_eq!(logger.check_at(t0), Some(0));
+
+        // 1 kHz 调用一秒
+        let emitted = (1..1000)
+            .filter(|ms| logger.check_at(t0 + Duration::from_millis(*ms)).is_some())
+            .count();
+        assert_eq!(emitted, 0);
+
+        assert_eq!(logger.check_at(t0 + Duration::from_millis(1000)), Some(999));
+        assert_eq!(logger.check_at(t0 + Duration::from_millis(1001)), None);
+    }
+
+    #[test]
+    fn keys_are_gated_independently_and_suppressed_count_goes_into_the_message() {
+        let limiter = KeyedRateLimiter::new(Duration::from_millis(100));
+        let t0 = Instant::now();
+        assert_eq!(limiter.check_at("parse", t0), Some(0));
+        assert_eq!(limiter.check_at("insert", t0), Some(0));
+        for ms in 1..100 {
+            assert_eq!(
+                limiter.check_at("parse", t0 + Duration::from_millis(ms)),
+                None
+            );
+        }
+        assert_eq!(
+            limiter.check_at("insert", t0 + Duration::from_millis(50)),
+            None
+        );
+
+        let suppressed = limiter
+            .check_at("parse", t0 + Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(suppressed, 99);
+        assert_eq!(
+            with_suppressed("IMU 数据解析失败".into(), suppressed),
+            "IMU 数据解析失败 (suppressed 99 similar)"
+        );
+        assert_eq!(with_suppressed("ok".into(), 0), "ok");
+        assert_eq!(
+            limiter.check_at("insert", t0 + Duration::from_millis(100)),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn concurrent_callers_share_one_gate_per_key() {
+        let limiter = Arc::new(KeyedRateLimiter::new(Duration::from_secs(3600)));
+        let t0 = Instant::now();
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let limiter = limiter.clone();
+                std::thread::spawn(move || {
+                    (0..5_000)
+                        .filter(|_| limiter.check_at("shared", t0).is_some())
+                        .count()
+                })
+            })
+            .collect();
+        let emitted: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        // 两个线程合计只放行一次，其余 9999 次全部计入下一次放行
+        assert_eq!(emitted, 1);
+        assert_eq!(
+            limiter.check_at("shared", t0 + Duration::from_secs(3600)),
+            Some(9_999)
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! 流式均值 / 方差累计（Welford 算法）。
+//!
+//! 零偏估计、重力估计、校准质量评分等都需要对标量或三维向量序列求均值与方差。
+//! 朴素的 `Σx² / n - mean²` 在均值远大于波动时（如重力模长 9.8 上叠加 mm/s² 级噪声）
+//! 会大数相消，这里按 Welford 增量更新 `mean` 与 `m2 = Σ(x - mean)²`，
+//! 并支持按 Chan 等人的公式合并两段独立累计。
+
+use math_f64::DVec3;
+use serde::{Deserialize, Serialize};
+
+/// 标量序列的流式统计量。
+///
+/// 空序列的均值与方差为 0，极值为 `None`。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl RunningStats {
+    /// 创建空的统计量。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入一个样本。
+    pub fn push(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// 合并另一段独立累计的统计量，结果与把两段样本依次 push 等价。
+    pub fn merge(&mut self, other: &RunningStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let weight = other.count as f64 / count as f64;
+        self.mean += delta * weight;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * weight;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+
+    /// 清空。
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 样本数。
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 是否尚无样本。
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// 均值。
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// 总体方差 `m2 / n`。
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// 总体标准差。
+    pub fn std(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// 最小值。
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// 最大值。
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+}
+
+/// 三维向量序列的逐分量流式统计量。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct RunningStatsVec3 {
+    /// x 分量。
+    pub x: RunningStats,
+    /// y 分量。
+    pub y: RunningStats,
+    /// z 分量。
+    pub z: RunningStats,
+}
+
+impl RunningStatsVec3 {
+    /// 创建空的统计量。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入一个样本。
+    pub fn push(&mut self, value: DVec3) {
+        self.x.push(value.x);
+        self.y.push(value.y);
+        self.z.push(value.z);
+    }
+
+    /// 逐分量合并另一段独立累计的统计量。
+    pub fn merge(&mut self, other: &RunningStatsVec3) {
+        self.x.merge(&other.x);
+        self.y.merge(&other.y);
+        self.z.merge(&other.z);
+    }
+
+    /// 清空。
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 样本数。
+    pub fn count(&self) -> u64 {
+        self.x.count()
+    }
+
+    /// 是否尚无样本。
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// 逐分量均值。
+    pub fn mean(&self) -> DVec3 {
+        DVec3::new(self.x.mean(), self.y.mean(), self.z.mean())
+    }
+
+    /// 逐分量总体方差。
+    pub fn variance(&self) -> DVec3 {
+        DVec3::new(self.x.variance(), self.y.variance(), self.z.variance())
+    }
+
+    /// 逐分量总体标准差。
+    pub fn std(&self) -> DVec3 {
+        DVec3::new(self.x.std(), self.y.std(), self.z.std())
+    }
+
+    /// 逐分量最小值。
+    pub fn min(&self) -> Option<DVec3> {
+        Some(DVec3::new(self.x.min()?, self.y.min()?, self.z.min()?))
+    }
+
+    /// 逐分量最大值。
+    pub fn max(&self) -> Option<DVec3> {
+        Some(DVec3::new(self.x.max()?, self.y.max()?, self.z.max()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 确定性伪随机序列，取值 [-1, 1)。
+    fn noise(n: usize) -> impl Iterator<Item = f64> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        (0..n).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+        })
+    }
+
+    #[test]
+    fn stays_accurate_with_large_offset_over_millions_of_samples() {
+        // 1e9 的偏置上叠加 ±1e-3 的波动：朴素平方和在此完全失效
+        const OFFSET: f64 = 1.0e9;
+        const N: usize = 2_000_000;
+        let mut stats = RunningStats::new();
+        let mut reference = RunningStats::new();
+        for x in noise(N) {
+            stats.push(OFFSET + x * 1.0e-3);
+            reference.push(x * 1.0e-3);
+        }
+        assert_eq!(stats.count(), N as u64);
+        assert!((stats.mean() - OFFSET - reference.mean()).abs() < 1.0e-6);
+        // 均匀分布 [-a, a) 的方差为 a²/3
+        let expected = 1.0e-6 / 3.0;
+        assert!((reference.variance() - expected).abs() / expected < 1.0e-2);
+        assert!(
+            (stats.variance() - reference.variance()).abs() / reference.variance() < 1.0e-3,
+            "{} vs {}",
+            stats.variance(),
+            reference.variance()
+        );
+
+        // 对照：朴素平方和公式只剩舍入误差
+        let (sum, sum_sq) = noise(N)
+            .map(|x| OFFSET + x * 1.0e-3)
+            .fold((0.0, 0.0), |(s, sq), x| (s + x, sq + x * x));
+        let naive = sum_sq / N as f64 - (sum / N as f64).powi(2);
+        assert!((naive - reference.variance()).abs() > reference.variance());
+    }
+
+    #[test]
+    fn merge_matches_sequential_push() {
+        let values: Vec<f64> = noise(1001).map(|x| 9.8 + x).collect();
+        let mut sequential = RunningStats::new();
+        values.iter().for_each(|&x| sequential.push(x));
+
+        for split in [0, 1, 500, 1000, 1001] {
+            let (left, right) = values.split_at(split);
+            let mut a = RunningStats::new();
+            let mut b = RunningStats::new();
+            left.iter().for_each(|&x| a.push(x));
+            right.iter().for_each(|&x| b.push(x));
+            a.merge(&b);
+            assert_eq!(a.count(), sequential.count());
+            assert!((a.mean() - sequential.mean()).abs() < 1.0e-12, "{split}");
+            assert!(
+                (a.variance() - sequential.variance()).abs() < 1.0e-12,
+                "{split}"
+            );
+            assert_eq!((a.min(), a.max()), (sequential.min(), sequential.max()));
+        }
+
+        let empty = RunningStats::new();
+        assert_eq!(
+            (empty.mean(), empty.variance(), empty.min()),
+            (0.0, 0.0, None)
+        );
+
+        let mut vec3 = RunningStatsVec3::new();
+        let mut halves = (RunningStatsVec3::new(), RunningStatsVec3::new());
+        for (i, &x) in values.iter().enumerate() {
+            let v = DVec3::new(x, -x, 2.0 * x);
+            vec3.push(v);
+            if i < 300 {
+                halves.0.push(v);
+            } else {
+                halves.1.push(v);
+            }
+        }
+        halves.0.merge(&halves.1);
+        assert!((halves.0.mean() - vec3.mean()).length() < 1.0e-12);
+        assert!((halves.0.variance() - vec3.variance()).length() < 1.0e-12);
+        assert!((vec3.std().z - 2.0 * vec3.std().x).abs() < 1.0e-12);
+        assert_eq!(vec3.min().unwrap().y, -vec3.max().unwrap().x);
+    }
+
+    #[test]
+    fn round_trips_through_serde() {
+        let mut stats = RunningStatsVec3::new();
+        stats.push(DVec3::new(1.0, 2.0, 3.0));
+        stats.push(DVec3::new(3.0, 2.0, 1.0));
+        let json = serde_json::to_string(&stats).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RunningStatsVec3>(&json).unwrap(),
+            stats
+        );
+        let empty = serde_json::to_string(&RunningStats::new()).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RunningStats>(&empty).unwrap(),
+            RunningStats::new()
+        );
+    }
+}
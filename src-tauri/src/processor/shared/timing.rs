@@ -0,0 +1,133 @@
+//! 标称采样间隔。
+//!
+//! 设备上报频率由 `IMUConfig.report_rate` 决定（默认 250 Hz）。处理链里按帧计数的
+//! 窗口（ZUPT 进入/退出、重力初始化）和按间隔判断的阈值（丢帧检测、排序窗口）都从
+//! 这里换算，不再隐含 4 ms 一帧。
+//!
+//! 低频上报（如 0.5 Hz）时一帧就超过整个判定窗口，按帧数换算会退化成"一帧即判定"。
+//! 采样间隔超过 [`WALL_TIME_WINDOW_INTERVAL_MS`] 后，[`WindowCounter`] 改为按设备时间跨度判定。
+
+use crate::processor::shared::DeviceTimeMs;
+
+/// 默认上报频率 (Hz)，与 `IMUConfig::default()` 一致。
+pub const DEFAULT_REPORT_RATE_HZ: u8 = 250;
+
+/// 默认标称采样间隔 (ms)。
+pub const DEFAULT_SAMPLE_INTERVAL_MS: f64 = 1000.0 / DEFAULT_REPORT_RATE_HZ as f64;
+
+/// 由上报频率换算标称采样间隔 (ms)。
+///
+/// 设备协议中 `report_rate = 0` 表示 0.5 Hz（2 s 一帧）。
+pub fn nominal_sample_interval_ms(report_rate: u8) -> f64 {
+    match report_rate {
+        0 => 2000.0,
+        rate => 1000.0 / rate as f64,
+    }
+}
+
+/// 把时间窗口 (ms) 换算成帧数，向上取整且至少 1 帧。
+///
+/// 先扣掉一个极小量，避免 12 ms / 4 ms 这类整除因浮点误差多算一帧。
+pub fn frames_for_window(window_ms: f64, sample_interval_ms: f64) -> u32 {
+    let frames = window_ms / sample_interval_ms;
+    if !frames.is_finite() || frames <= 0.0 {
+        return 1;
+    }
+    ((frames - 1e-9).ceil() as u32).max(1)
+}
+
+/// 采样间隔超过该值 (ms) 时，判定窗口改按设备时间跨度计算。
+pub const WALL_TIME_WINDOW_INTERVAL_MS: f64 = 100.0;
+
+/// 连续满足条件的判定窗口。
+///
+/// 高频时按帧计数，需要 [`frames_for_window`] 帧；采样间隔超过
+/// [`WALL_TIME_WINDOW_INTERVAL_MS`] 时改为要求首末两帧的设备时间跨度达到窗口，
+/// 窗口非零时至少需要两帧。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowCounter {
+    frames: u32,
+    since_ms: Option<DeviceTimeMs>,
+}
+
+impl WindowCounter {
+    /// 已连续满足条件的帧数。
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// 清零。
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 记录一帧，返回窗口是否已满；已满或条件中断时清零。
+    pub fn observe(
+        &mut self,
+        active: bool,
+        timestamp_ms: DeviceTimeMs,
+        window_ms: f64,
+        sample_interval_ms: f64,
+    ) -> bool {
+        if !active {
+            self.reset();
+            return false;
+        }
+        self.frames = self.frames.saturating_add(1);
+        let since_ms = *self.since_ms.get_or_insert(timestamp_ms);
+        let full = if sample_interval_ms > WALL_TIME_WINDOW_INTERVAL_MS {
+            timestamp_ms.delta_ms(since_ms) as f64 >= window_ms
+        } else {
+            self.frames >= frames_for_window(window_ms, sample_interval_ms)
+        };
+        if full {
+            self.reset();
+        }
+        full
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_and_window_conversion() {
+        assert_eq!(nominal_sample_interval_ms(250), 4.0);
+        assert_eq!(nominal_sample_interval_ms(100), 10.0);
+        assert_eq!(nominal_sample_interval_ms(0), 2000.0);
+        assert_eq!(DEFAULT_SAMPLE_INTERVAL_MS, 4.0);
+
+        assert_eq!(frames_for_window(12.0, 4.0), 3);
+        assert_eq!(frames_for_window(60.0, 4.0), 15);
+        assert_eq!(frames_for_window(60.0, 10.0), 6);
+        // 不足一帧按一帧算，窗口不能比一帧更短
+        assert_eq!(frames_for_window(12.0, 1000.0 / 30.0), 1);
+        assert_eq!(frames_for_window(0.0, 4.0), 1);
+    }
+
+    #[test]
+    fn window_counter_switches_to_device_time_at_low_rates() {
+        // 250 Hz：60 ms 窗口需要 15 帧，中断即清零
+        let mut counter = WindowCounter::default();
+        for i in 0..14u64 {
+            assert!(!counter.observe(true, DeviceTimeMs(i * 4), 60.0, 4.0));
+        }
+        assert_eq!(counter.frames(), 14);
+        assert!(!counter.observe(false, DeviceTimeMs(56), 60.0, 4.0));
+        assert_eq!(counter.frames(), 0);
+        let full = (0..15u64).map(|i| counter.observe(true, DeviceTimeMs(100 + i * 4), 60.0, 4.0));
+        assert_eq!(full.filter(|&f| f).count(), 1);
+
+        // 0.5 Hz：按帧数只要 1 帧，按设备时间需要跨度达到 3 s，即第三帧
+        assert_eq!(frames_for_window(3000.0, 2000.0), 2);
+        let mut counter = WindowCounter::default();
+        assert!(!counter.observe(true, DeviceTimeMs(10_000), 3000.0, 2000.0));
+        assert!(!counter.observe(true, DeviceTimeMs(12_000), 3000.0, 2000.0));
+        assert!(counter.observe(true, DeviceTimeMs(14_000), 3000.0, 2000.0));
+        assert_eq!(counter.frames(), 0);
+        // 短窗口也至少要两帧
+        assert!(!counter.observe(true, DeviceTimeMs(16_000), 60.0, 2000.0));
+        assert!(counter.observe(true, DeviceTimeMs(18_000), 60.0, 2000.0));
+    }
+}
@@ -0,0 +1,206 @@
+//! 时间单位新类型。
+//!
+//! 处理链里同时流转设备时间戳（设备上电后的毫秒数）、主机 UNIX 时间（毫秒）与积分步长（秒），
+//! 裸 `u64` / `f64` 混用时编译器无从区分，曾出现过毫秒 dt 被除了两次 1000 的问题。
+//! 三者分别包装为 [`DeviceTimeMs`]、[`HostTimeMs`] 与 [`DurationS`]，彼此之间只能经
+//! 显式方法换算；序列化保持透明，线上格式与录制数据不变。
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, Mul, Sub},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 设备时间戳（毫秒），来自数据帧，设备重启后从零开始。
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct DeviceTimeMs(pub u64);
+
+impl DeviceTimeMs {
+    /// 毫秒数。
+    pub fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    /// 距 `earlier` 的毫秒数；时间戳回退（设备重启、计数回绕）时为 0。
+    pub fn delta_ms(self, earlier: DeviceTimeMs) -> u64 {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// 距 `earlier` 的时长；时间戳回退时为零。
+    pub fn delta_s(self, earlier: DeviceTimeMs) -> DurationS {
+        DurationS::from_millis(self.delta_ms(earlier) as f64)
+    }
+
+    /// 距 `earlier` 的时长，毫秒差先钳位到 `[min_ms, max_ms]`（下限至少 1 ms）。
+    ///
+    /// 时间戳回退时按下限处理，积分步长始终为正且有界。
+    pub fn clamped_delta_s(self, earlier: DeviceTimeMs, min_ms: u64, max_ms: u64) -> DurationS {
+        let lower = min_ms.max(1);
+        let upper = max_ms.max(lower);
+        DurationS::from_millis(self.delta_ms(earlier).clamp(lower, upper) as f64)
+    }
+
+    /// 往后推 `ms` 毫秒，溢出时饱和。
+    pub fn add_ms(self, ms: u64) -> DeviceTimeMs {
+        DeviceTimeMs(self.0.saturating_add(ms))
+    }
+}
+
+impl fmt::Display for DeviceTimeMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms (device)", self.0)
+    }
+}
+
+/// 主机 UNIX 时间（毫秒）。
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct HostTimeMs(pub u64);
+
+impl HostTimeMs {
+    /// 毫秒数。
+    pub fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    /// 距 `earlier` 的毫秒数；主机时钟回拨时为 0。
+    pub fn delta_ms(self, earlier: HostTimeMs) -> u64 {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+impl fmt::Display for HostTimeMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms (host)", self.0)
+    }
+}
+
+/// 时长（秒），用作积分步长等。
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct DurationS(pub f64);
+
+impl DurationS {
+    /// 零时长。
+    pub const ZERO: DurationS = DurationS(0.0);
+
+    /// 由毫秒构造。
+    pub fn from_millis(ms: f64) -> DurationS {
+        DurationS(ms / 1000.0)
+    }
+
+    /// 秒数。
+    pub fn as_secs(self) -> f64 {
+        self.0
+    }
+
+    /// 毫秒数。
+    pub fn as_millis(self) -> f64 {
+        self.0 * 1000.0
+    }
+}
+
+impl Add for DurationS {
+    type Output = DurationS;
+
+    fn add(self, rhs: DurationS) -> DurationS {
+        DurationS(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for DurationS {
+    fn add_assign(&mut self, rhs: DurationS) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for DurationS {
+    type Output = DurationS;
+
+    fn sub(self, rhs: DurationS) -> DurationS {
+        DurationS(self.0 - rhs.0)
+    }
+}
+
+impl Mul<f64> for DurationS {
+    type Output = DurationS;
+
+    fn mul(self, rhs: f64) -> DurationS {
+        DurationS(self.0 * rhs)
+    }
+}
+
+impl Div<f64> for DurationS {
+    type Output = DurationS;
+
+    fn div(self, rhs: f64) -> DurationS {
+        DurationS(self.0 / rhs)
+    }
+}
+
+impl fmt::Display for DurationS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3} s", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_time_deltas_saturate_and_clamp() {
+        let earlier = DeviceTimeMs(1_000);
+        assert_eq!(DeviceTimeMs(1_004).delta_ms(earlier), 4);
+        assert_eq!(DeviceTimeMs(1_004).delta_s(earlier), DurationS(0.004));
+        assert_eq!(DeviceTimeMs(1_004).delta_s(earlier).as_millis(), 4.0);
+
+        // 设备重启或计数回绕：时间戳回退不产生负时长
+        assert_eq!(DeviceTimeMs(10).delta_ms(earlier), 0);
+        assert_eq!(DeviceTimeMs(10).delta_s(earlier), DurationS::ZERO);
+
+        // 积分步长钳位：回退按下限，长间隔按上限，下限至少 1 ms
+        let clamped = |now: u64, min_ms, max_ms| {
+            DeviceTimeMs(now)
+                .clamped_delta_s(earlier, min_ms, max_ms)
+                .as_secs()
+        };
+        assert_eq!(clamped(1_004, 1, 50), 0.004);
+        assert_eq!(clamped(10, 2, 50), 0.002);
+        assert_eq!(clamped(1_000, 0, 50), 0.001);
+        assert_eq!(clamped(6_000, 1, 50), 0.05);
+        // 上限小于下限时以下限为准
+        assert_eq!(clamped(6_000, 20, 5), 0.02);
+
+        assert_eq!(
+            DeviceTimeMs(u64::MAX - 1).add_ms(10),
+            DeviceTimeMs(u64::MAX)
+        );
+        assert_eq!(HostTimeMs(5).delta_ms(HostTimeMs(9)), 0);
+    }
+
+    #[test]
+    fn duration_arithmetic_and_transparent_serde() {
+        let mut dt = DurationS::from_millis(4.0) + DurationS(0.5);
+        dt += DurationS(0.5);
+        assert!((dt.as_secs() - 1.004).abs() < 1e-12);
+        assert!((((dt - DurationS(1.0)) * 2.0 / 4.0).as_millis() - 2.0).abs() < 1e-9);
+
+        assert_eq!(serde_json::to_string(&DeviceTimeMs(42)).unwrap(), "42");
+        assert_eq!(
+            serde_json::from_str::<HostTimeMs>("1700000000000").unwrap(),
+            HostTimeMs(1_700_000_000_000)
+        );
+        assert_eq!(serde_json::to_string(&DurationS(0.25)).unwrap(), "0.25");
+
+        assert_eq!(DeviceTimeMs(7).to_string(), "7 ms (device)");
+        assert_eq!(HostTimeMs(7).to_string(), "7 ms (host)");
+        assert_eq!(DurationS(0.004).to_string(), "0.004 s");
+    }
+}
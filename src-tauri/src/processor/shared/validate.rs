@@ -0,0 +1,153 @@
+//! 配置取值校验。
+//!
+//! `processor.toml` 里的笔误（负的 alpha、为零的重力、200 的加速度阈值）能通过反序列化，
+//! 却会让管线行为怪异且无从排查。各配置段实现 `validate`，把越界字段连同 TOML 路径
+//! 收集起来，由加载与更新入口统一拒绝。
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// 单个字段的校验错误。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigError {
+    /// TOML 字段路径，如 `zupt.accel_thresh`、`calibration.accel_matrix[0][0]`。
+    pub path: String,
+    /// 错误说明。
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// 一次校验发现的全部错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid pipeline config ({} error(s))", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n- {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// 错误收集器，按所在配置段拼接字段路径。
+#[derive(Debug, Default)]
+pub struct ConfigValidator {
+    prefix: Vec<String>,
+    errors: Vec<ConfigError>,
+}
+
+impl ConfigValidator {
+    /// 创建空收集器。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在子配置段 `name` 内执行校验。
+    pub fn section(&mut self, name: &str, validate: impl FnOnce(&mut Self)) {
+        self.prefix.push(name.to_string());
+        validate(self);
+        self.prefix.pop();
+    }
+
+    /// 记录一条错误。
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        let path = self
+            .prefix
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(field))
+            .collect::<Vec<_>>()
+            .join(".");
+        self.errors.push(ConfigError {
+            path,
+            message: message.into(),
+        });
+    }
+
+    /// `ok` 为假时记录错误。
+    pub fn check(&mut self, field: &str, ok: bool, message: impl Into<String>) {
+        if !ok {
+            self.error(field, message);
+        }
+    }
+
+    /// 要求有限值且位于闭区间 `[min, max]`。
+    pub fn in_range(&mut self, field: &str, value: f64, min: f64, max: f64) {
+        if !value.is_finite() || value < min || value > max {
+            self.error(field, format!("must be within [{min}, {max}], got {value}"));
+        }
+    }
+
+    /// 要求有限正数且不超过 `max`。
+    pub fn positive(&mut self, field: &str, value: f64, max: f64) {
+        if !value.is_finite() || value <= 0.0 || value > max {
+            self.error(field, format!("must be > 0 and <= {max}, got {value}"));
+        }
+    }
+
+    /// 要求有限非负数。
+    pub fn non_negative(&mut self, field: &str, value: f64) {
+        if !value.is_finite() || value < 0.0 {
+            self.error(field, format!("must be a finite value >= 0, got {value}"));
+        }
+    }
+
+    /// 要求有限值。
+    pub fn finite(&mut self, field: &str, value: f64) {
+        if !value.is_finite() {
+            self.error(field, format!("must be finite, got {value}"));
+        }
+    }
+
+    /// 结束校验。
+    pub fn finish(self) -> Result<(), ConfigErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors(self.errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_follow_nested_sections() {
+        let mut validator = ConfigValidator::new();
+        validator.in_range("gravity", 0.0, 9.5, 10.1);
+        validator.section("zupt", |v| {
+            v.positive("accel_thresh", 200.0, 50.0);
+            v.positive("gyro_thresh", 0.1, 10.0);
+            v.section("inner", |v| v.non_negative("tau", f64::NAN));
+        });
+        validator.finite("top", f64::INFINITY);
+
+        let errors = validator.finish().unwrap_err();
+        let paths: Vec<&str> = errors.0.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["gravity", "zupt.accel_thresh", "zupt.inner.tau", "top"]
+        );
+        assert_eq!(
+            errors.0[1].to_string(),
+            "zupt.accel_thresh: must be > 0 and <= 50, got 200"
+        );
+        assert!(errors
+            .to_string()
+            .starts_with("invalid pipeline config (4 error(s))\n- gravity:"));
+
+        assert!(ConfigValidator::new().finish().is_ok());
+    }
+}
@@ -0,0 +1,581 @@
+//! 处理器运行统计。
+//!
+//! 处理线程每输出一帧更新一次，蓝牙接收任务每个通知记一次到达时间、每秒写入一次输入速率，
+//! 命令侧随时读取快照。到达间隔抖动只在接收任务内按秒汇总，随输入速率一起写入调试日志。
+//! 全部字段为原子量，读写均不加锁，`Relaxed` 语序即可（只要求最终可见）。
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    processor::shared::{DeviceTimeMs, HostTimeMs},
+    types::status::ConnectionUptime,
+};
+
+/// 跨线程共享的统计句柄。
+pub type ProcessorStatsHandle = Arc<ProcessorStats>;
+
+/// 处理器运行统计。
+#[derive(Debug)]
+pub struct ProcessorStats {
+    frames_since_connect: AtomicU64,
+    has_frame: AtomicBool,
+    last_device_timestamp_ms: AtomicU64,
+    last_host_timestamp_ms: AtomicU64,
+    /// 0 表示尚无输入。
+    last_input_host_timestamp_ms: AtomicU64,
+    /// f64 位模式；`NaN` 表示无估计值。
+    gravity_estimate_bits: AtomicU64,
+    skipped_integrations: AtomicU64,
+    quat_freezes: AtomicU64,
+    parse_failures: AtomicU64,
+    non_finite_samples: AtomicU64,
+    /// 0 表示未连接。
+    connected_at_host_ms: AtomicU64,
+    /// 本次运行以来成功建立连接的次数。
+    connections: AtomicU64,
+    /// f64 位模式。
+    input_packets_per_sec_bits: AtomicU64,
+    /// f64 位模式。
+    input_frames_per_sec_bits: AtomicU64,
+    /// f64 位模式。
+    input_bytes_per_sec_bits: AtomicU64,
+}
+
+/// 统计快照。
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct ProcessorStatsSnapshot {
+    /// 本次连接以来输出的帧数。
+    pub frames_since_connect: u64,
+    /// 最近一帧的设备时间戳（ms），尚未处理任何帧时为空。
+    pub last_device_timestamp_ms: Option<u64>,
+    /// 最近一帧处理完成时的主机 UNIX 时间戳（ms）。
+    pub last_host_timestamp_ms: Option<u64>,
+    /// 最近一个蓝牙通知到达时的主机 UNIX 时间戳（ms）。
+    pub last_input_host_timestamp_ms: Option<u64>,
+    /// auto_gravity 估计的重力模长 (m/s²)。
+    pub gravity_estimate: Option<f64>,
+    /// 导航因帧间隔过大而跳过积分的累计次数。
+    pub skipped_integrations: u64,
+    /// 检测到设备四元数冻结、改用陀螺积分姿态的累计次数。
+    pub quat_freezes: u64,
+    /// 解析失败的累计包数。
+    pub parse_failures: u64,
+    /// 因某阶段输出非有限值而丢弃的累计帧数。
+    pub non_finite_samples: u64,
+    /// 当前连接建立时的主机 UNIX 时间戳（ms），未连接时为空。
+    pub connected_at_host_ms: Option<u64>,
+    /// 本次运行以来的重连次数（首次连接不计）。
+    pub reconnect_count: u64,
+    /// 最近一个统计窗口的蓝牙通知速率 (包/s)。
+    pub input_packets_per_sec: f64,
+    /// 最近一个统计窗口的输入帧速率 (帧/s)。
+    pub input_frames_per_sec: f64,
+    /// 最近一个统计窗口的蓝牙输入带宽 (B/s)。
+    pub input_bytes_per_sec: f64,
+}
+
+/// 输入速率（一个统计窗口的平均值）。
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct InputRate {
+    /// 通知速率 (包/s)。
+    pub packets_per_sec: f64,
+    /// 帧速率 (帧/s)；一个通知可能拼接多帧，高于通知速率说明设备在打包发送。
+    pub frames_per_sec: f64,
+    /// 带宽 (B/s)。
+    pub bytes_per_sec: f64,
+}
+
+/// 蓝牙接收任务内的输入计数窗口，每个通知累加一次，定期折算成速率。
+#[derive(Debug, Default)]
+pub struct InputRateWindow {
+    packets: u64,
+    frames: u64,
+    bytes: u64,
+}
+
+impl InputRateWindow {
+    /// 累加一个通知，`frames` 为其中包含的帧数。
+    pub fn record(&mut self, bytes: usize, frames: usize) {
+        self.packets += 1;
+        self.frames += frames as u64;
+        self.bytes += bytes as u64;
+    }
+
+    /// 按窗口时长折算速率并清零计数。
+    pub fn flush_tick(&mut self, elapsed: Duration) -> InputRate {
+        let secs = elapsed.as_secs_f64();
+        let rate = if secs > 0.0 {
+            InputRate {
+                packets_per_sec: self.packets as f64 / secs,
+                frames_per_sec: self.frames as f64 / secs,
+                bytes_per_sec: self.bytes as f64 / secs,
+            }
+        } else {
+            InputRate::default()
+        };
+        *self = Self::default();
+        rate
+    }
+}
+
+/// 蓝牙通知到达间隔的抖动（一个统计窗口的汇总）。
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct ArrivalJitter {
+    /// 窗口内的到达间隔个数。
+    pub intervals: u32,
+    /// 平均到达间隔 (ms)。
+    pub mean_ms: f64,
+    /// 到达间隔的 95 分位 (ms)。
+    pub p95_ms: f64,
+    /// 最大到达间隔 (ms)。
+    pub max_ms: f64,
+    /// 超过标称间隔 2 倍的到达间隔个数。
+    pub late: u32,
+}
+
+/// 蓝牙接收任务内的到达间隔窗口，每个通知记录一次主机接收时刻，定期汇总成抖动统计。
+///
+/// 上一个通知的接收时刻跨窗口保留，窗口边界上的间隔照常计入。
+#[derive(Debug, Default)]
+pub struct ArrivalJitterWindow {
+    last_arrival: Option<Instant>,
+    /// 到达间隔 (ms) 与后一个通知包含的帧数。
+    intervals: Vec<(f64, usize)>,
+}
+
+impl ArrivalJitterWindow {
+    /// 记录一个通知的主机接收时刻，`frames` 为其中包含的帧数。
+    pub fn record(&mut self, received_at: Instant, frames: usize) {
+        if let Some(last) = self.last_arrival.replace(received_at) {
+            let interval = received_at.saturating_duration_since(last);
+            self.intervals
+                .push((interval.as_secs_f64() * 1000.0, frames.max(1)));
+        }
+    }
+
+    /// 汇总窗口内的到达间隔并清空，`sample_interval_ms` 为设备标称采样间隔。
+    ///
+    /// 拼接 `n` 帧的通知标称覆盖 `n` 个采样间隔，超过其 2 倍记为迟到。
+    pub fn flush_tick(&mut self, sample_interval_ms: f64) -> ArrivalJitter {
+        if self.intervals.is_empty() {
+            return ArrivalJitter::default();
+        }
+        let late = self
+            .intervals
+            .iter()
+            .filter(|&&(interval_ms, frames)| {
+                interval_ms > 2.0 * sample_interval_ms * frames as f64
+            })
+            .count() as u32;
+        let mut sorted: Vec<f64> = self
+            .intervals
+            .drain(..)
+            .map(|(interval_ms, _)| interval_ms)
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        ArrivalJitter {
+            intervals: n as u32,
+            mean_ms: sorted.iter().sum::<f64>() / n as f64,
+            // 最近秩法：不小于 95% 样本的最小值
+            p95_ms: sorted[(n * 95).div_ceil(100) - 1],
+            max_ms: sorted[n - 1],
+            late,
+        }
+    }
+}
+
+impl Default for ProcessorStats {
+    fn default() -> Self {
+        Self {
+            frames_since_connect: AtomicU64::new(0),
+            has_frame: AtomicBool::new(false),
+            last_device_timestamp_ms: AtomicU64::new(0),
+            last_host_timestamp_ms: AtomicU64::new(0),
+            last_input_host_timestamp_ms: AtomicU64::new(0),
+            gravity_estimate_bits: AtomicU64::new(f64::NAN.to_bits()),
+            skipped_integrations: AtomicU64::new(0),
+            quat_freezes: AtomicU64::new(0),
+            parse_failures: AtomicU64::new(0),
+            non_finite_samples: AtomicU64::new(0),
+            connected_at_host_ms: AtomicU64::new(0),
+            connections: AtomicU64::new(0),
+            input_packets_per_sec_bits: AtomicU64::new(0.0f64.to_bits()),
+            input_frames_per_sec_bits: AtomicU64::new(0.0f64.to_bits()),
+            input_bytes_per_sec_bits: AtomicU64::new(0.0f64.to_bits()),
+        }
+    }
+}
+
+impl ProcessorStats {
+    /// 创建共享统计句柄。
+    pub fn new_handle() -> ProcessorStatsHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 记录一帧输出，主机时间取当前系统时间。
+    pub fn record_frame(&self, device_timestamp_ms: DeviceTimeMs) {
+        self.record_frame_at(device_timestamp_ms, host_now_ms());
+    }
+
+    /// 记录一帧输出，显式指定主机时间。
+    pub fn record_frame_at(
+        &self,
+        device_timestamp_ms: DeviceTimeMs,
+        host_timestamp_ms: HostTimeMs,
+    ) {
+        self.frames_since_connect.fetch_add(1, Ordering::Relaxed);
+        self.last_device_timestamp_ms
+            .store(device_timestamp_ms.as_millis(), Ordering::Relaxed);
+        self.last_host_timestamp_ms
+            .store(host_timestamp_ms.as_millis(), Ordering::Relaxed);
+        self.has_frame.store(true, Ordering::Relaxed);
+    }
+
+    /// 记录一个蓝牙通知到达，主机时间取当前系统时间。
+    pub fn record_input(&self) {
+        self.record_input_at(host_now_ms());
+    }
+
+    /// 记录一个蓝牙通知到达，显式指定主机时间。
+    pub fn record_input_at(&self, host_timestamp_ms: HostTimeMs) {
+        self.last_input_host_timestamp_ms
+            .store(host_timestamp_ms.as_millis(), Ordering::Relaxed);
+    }
+
+    /// 更新 auto_gravity 估计值。
+    pub fn set_gravity_estimate(&self, estimate: Option<f64>) {
+        let bits = estimate.unwrap_or(f64::NAN).to_bits();
+        self.gravity_estimate_bits.store(bits, Ordering::Relaxed);
+    }
+
+    /// 更新导航跳过积分的累计次数。
+    pub fn set_skipped_integrations(&self, count: u64) {
+        self.skipped_integrations.store(count, Ordering::Relaxed);
+    }
+
+    /// 更新设备四元数冻结的累计次数。
+    pub fn set_quat_freezes(&self, count: u64) {
+        self.quat_freezes.store(count, Ordering::Relaxed);
+    }
+
+    /// 更新解析失败的累计包数。
+    pub fn set_parse_failures(&self, count: u64) {
+        self.parse_failures.store(count, Ordering::Relaxed);
+    }
+
+    /// 更新非有限值丢帧的累计数。
+    pub fn set_non_finite_samples(&self, count: u64) {
+        self.non_finite_samples.store(count, Ordering::Relaxed);
+    }
+
+    /// 更新蓝牙输入速率。
+    pub fn set_input_rate(&self, rate: InputRate) {
+        self.input_packets_per_sec_bits
+            .store(rate.packets_per_sec.to_bits(), Ordering::Relaxed);
+        self.input_frames_per_sec_bits
+            .store(rate.frames_per_sec.to_bits(), Ordering::Relaxed);
+        self.input_bytes_per_sec_bits
+            .store(rate.bytes_per_sec.to_bits(), Ordering::Relaxed);
+    }
+
+    /// 记录一次成功连接，主机时间取当前系统时间。
+    pub fn record_connect(&self) {
+        self.record_connect_at(host_now_ms());
+    }
+
+    /// 记录一次成功连接：清零帧计数与输入速率，开始计连接时长，第二次起计为重连。
+    pub fn record_connect_at(&self, host_timestamp_ms: HostTimeMs) {
+        self.reset_connection();
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        self.connected_at_host_ms
+            .store(host_timestamp_ms.as_millis(), Ordering::Relaxed);
+    }
+
+    /// 记录断开连接，停止计连接时长；帧计数保留到下一次连接。
+    pub fn record_disconnect(&self) {
+        self.connected_at_host_ms.store(0, Ordering::Relaxed);
+    }
+
+    /// 新连接建立时清零帧计数与输入速率。
+    ///
+    /// 最近一帧时间戳保留，便于前端判断上一次数据停在何时。
+    pub fn reset_connection(&self) {
+        self.frames_since_connect.store(0, Ordering::Relaxed);
+        self.set_input_rate(InputRate::default());
+    }
+
+    /// 读取当前快照。
+    pub fn snapshot(&self) -> ProcessorStatsSnapshot {
+        let has_frame = self.has_frame.load(Ordering::Relaxed);
+        ProcessorStatsSnapshot {
+            frames_since_connect: self.frames_since_connect.load(Ordering::Relaxed),
+            last_device_timestamp_ms: has_frame
+                .then(|| self.last_device_timestamp_ms.load(Ordering::Relaxed)),
+            last_host_timestamp_ms: has_frame
+                .then(|| self.last_host_timestamp_ms.load(Ordering::Relaxed)),
+            last_input_host_timestamp_ms: Some(
+                self.last_input_host_timestamp_ms.load(Ordering::Relaxed),
+            )
+            .filter(|&ts| ts > 0),
+            gravity_estimate: Some(f64::from_bits(
+                self.gravity_estimate_bits.load(Ordering::Relaxed),
+            ))
+            .filter(|g| !g.is_nan()),
+            skipped_integrations: self.skipped_integrations.load(Ordering::Relaxed),
+            quat_freezes: self.quat_freezes.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            non_finite_samples: self.non_finite_samples.load(Ordering::Relaxed),
+            connected_at_host_ms: Some(self.connected_at_host_ms.load(Ordering::Relaxed))
+                .filter(|&ts| ts > 0),
+            reconnect_count: self.connections.load(Ordering::Relaxed).saturating_sub(1),
+            input_packets_per_sec: f64::from_bits(
+                self.input_packets_per_sec_bits.load(Ordering::Relaxed),
+            ),
+            input_frames_per_sec: f64::from_bits(
+                self.input_frames_per_sec_bits.load(Ordering::Relaxed),
+            ),
+            input_bytes_per_sec: f64::from_bits(
+                self.input_bytes_per_sec_bits.load(Ordering::Relaxed),
+            ),
+        }
+    }
+}
+
+impl ProcessorStatsSnapshot {
+    /// 设备运行时长与连接时长。
+    ///
+    /// 设备时间戳为上电以来的毫秒数，排序器已按 32 位回绕展开，直接取最近一帧即可；
+    /// 未连接或本次连接尚无帧时为空，不沿用上一次连接的旧值。
+    pub fn uptime(&self, now: HostTimeMs) -> ConnectionUptime {
+        let connected_at = self.connected_at_host_ms;
+        ConnectionUptime {
+            device_uptime_ms: self
+                .last_device_timestamp_ms
+                .filter(|_| connected_at.is_some() && self.frames_since_connect > 0),
+            connection_uptime_ms: connected_at.map(|at| now.as_millis().saturating_sub(at)),
+            frames_since_connect: self.frames_since_connect,
+            reconnect_count: self.reconnect_count,
+        }
+    }
+}
+
+/// 当前主机 UNIX 时间戳（ms）。
+pub fn host_now_ms() -> HostTimeMs {
+    HostTimeMs(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_tracks_frames_and_resets_on_connect() {
+        let stats = ProcessorStats::new_handle();
+        assert_eq!(
+            stats.snapshot(),
+            ProcessorStatsSnapshot {
+                frames_since_connect: 0,
+                last_device_timestamp_ms: None,
+                last_host_timestamp_ms: None,
+                last_input_host_timestamp_ms: None,
+                gravity_estimate: None,
+                skipped_integrations: 0,
+                quat_freezes: 0,
+                parse_failures: 0,
+                non_finite_samples: 0,
+                connected_at_host_ms: None,
+                reconnect_count: 0,
+                input_packets_per_sec: 0.0,
+                input_frames_per_sec: 0.0,
+                input_bytes_per_sec: 0.0,
+            }
+        );
+        stats.set_gravity_estimate(Some(9.86));
+        assert_eq!(stats.snapshot().gravity_estimate, Some(9.86));
+        stats.set_gravity_estimate(None);
+        assert_eq!(stats.snapshot().gravity_estimate, None);
+
+        let writer = {
+            let stats = stats.clone();
+            std::thread::spawn(move || {
+                for i in 0..250u64 {
+                    stats.record_frame_at(
+                        DeviceTimeMs(i * 4),
+                        HostTimeMs(1_700_000_000_000 + i * 4),
+                    );
+                }
+            })
+        };
+        writer.join().unwrap();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.frames_since_connect, 250);
+        assert_eq!(snapshot.last_device_timestamp_ms, Some(996));
+        assert_eq!(snapshot.last_host_timestamp_ms, Some(1_700_000_000_996));
+
+        stats.reset_connection();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.frames_since_connect, 0);
+        assert_eq!(snapshot.last_device_timestamp_ms, Some(996));
+
+        stats.record_frame(DeviceTimeMs(1000));
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.frames_since_connect, 1);
+        assert_eq!(snapshot.last_device_timestamp_ms, Some(1000));
+        assert!(snapshot.last_host_timestamp_ms.unwrap() > 1_700_000_000_996);
+    }
+
+    #[test]
+    fn uptime_follows_connect_frames_and_reconnect() {
+        let stats = ProcessorStats::new_handle();
+        let t0 = 1_700_000_000_000;
+        let uptime = |now: u64| stats.snapshot().uptime(HostTimeMs(now));
+        assert_eq!(uptime(t0), ConnectionUptime::default());
+
+        stats.record_connect_at(HostTimeMs(t0));
+        // 连上但尚无帧：设备运行时长未知
+        assert_eq!(
+            uptime(t0 + 500),
+            ConnectionUptime {
+                device_uptime_ms: None,
+                connection_uptime_ms: Some(500),
+                frames_since_connect: 0,
+                reconnect_count: 0,
+            }
+        );
+        // 回绕展开后的设备时间戳超过 32 位
+        let wrapped = (1u64 << 32) + 1_000;
+        for i in 0..10 {
+            stats.record_frame_at(
+                DeviceTimeMs(wrapped + i * 4),
+                HostTimeMs(t0 + 1_000 + i * 4),
+            );
+        }
+        assert_eq!(
+            uptime(t0 + 2_000),
+            ConnectionUptime {
+                device_uptime_ms: Some(wrapped + 36),
+                connection_uptime_ms: Some(2_000),
+                frames_since_connect: 10,
+                reconnect_count: 0,
+            }
+        );
+
+        // 断开：连接时长停止，不沿用旧的设备时间戳
+        stats.record_disconnect();
+        let disconnected = uptime(t0 + 3_000);
+        assert_eq!(disconnected.device_uptime_ms, None);
+        assert_eq!(disconnected.connection_uptime_ms, None);
+
+        // 重连（设备期间重启）：帧计数与连接时长重新开始，重连次数跨连接累计
+        stats.record_connect_at(HostTimeMs(t0 + 10_000));
+        stats.record_frame_at(DeviceTimeMs(800), HostTimeMs(t0 + 10_100));
+        assert_eq!(
+            uptime(t0 + 10_250),
+            ConnectionUptime {
+                device_uptime_ms: Some(800),
+                connection_uptime_ms: Some(250),
+                frames_since_connect: 1,
+                reconnect_count: 1,
+            }
+        );
+        stats.record_disconnect();
+        stats.record_connect_at(HostTimeMs(t0 + 20_000));
+        assert_eq!(uptime(t0 + 20_000).reconnect_count, 2);
+    }
+
+    #[test]
+    fn arrival_jitter_summarizes_steady_and_burst_arrivals() {
+        let start = Instant::now();
+        let at = |ms: f64| start + Duration::from_secs_f64(ms / 1000.0);
+
+        // 250 Hz 逐帧通知：间隔恒为 4 ms
+        let mut window = ArrivalJitterWindow::default();
+        for i in 0..=250 {
+            window.record(at(i as f64 * 4.0), 1);
+        }
+        let steady = window.flush_tick(4.0);
+        assert_eq!(steady.intervals, 250);
+        assert!((steady.mean_ms - 4.0).abs() < 1e-6);
+        assert!((steady.p95_ms - 4.0).abs() < 1e-6);
+        assert_eq!(steady.late, 0);
+
+        // 断流 1 s 后改为突发到达：连接事件每 30 ms 一次，每次交替送出 7 / 8 个通知，
+        // 包间相隔 0.1 ms
+        let mut last_ms = 0.0;
+        for burst in 0..10 {
+            let packets = if burst % 2 == 0 { 7 } else { 8 };
+            for k in 0..packets {
+                last_ms = 2000.0 + burst as f64 * 30.0 + k as f64 * 0.1;
+                window.record(at(last_ms), 1);
+            }
+        }
+        let bursty = window.flush_tick(4.0);
+        // 跨窗口的第一个间隔（1 s 断流）同样计入
+        assert_eq!(bursty.intervals, 75);
+        assert!((bursty.max_ms - 1000.0).abs() < 1e-6);
+        // 断流 + 9 个突发之间的间隔（29.3 / 29.4 ms）超过 8 ms
+        assert_eq!(bursty.late, 10);
+        // 65 个 0.1 ms、4 个 29.3 ms、5 个 29.4 ms、1 个 1000 ms：第 72 小的是 29.4 ms
+        assert!((bursty.p95_ms - 29.4).abs() < 1e-3, "p95 {}", bursty.p95_ms);
+        let expected_mean = (65.0 * 0.1 + 4.0 * 29.3 + 5.0 * 29.4 + 1000.0) / 75.0;
+        assert!((bursty.mean_ms - expected_mean).abs() < 1e-3);
+
+        // 拼接两帧的通知标称覆盖 8 ms，12 ms 不算迟到
+        window.record(at(last_ms + 12.0), 2);
+        assert_eq!(window.flush_tick(4.0).late, 0);
+        assert_eq!(window.flush_tick(4.0), ArrivalJitter::default());
+    }
+
+    #[test]
+    fn input_rate_window_flushes_packet_and_byte_rates() {
+        let mut window = InputRateWindow::default();
+        for _ in 0..500 {
+            window.record(51, 1);
+        }
+        // 拼接两帧的通知
+        window.record(20, 2);
+
+        let rate = window.flush_tick(Duration::from_millis(2000));
+        assert_eq!(rate.packets_per_sec, 250.5);
+        assert_eq!(rate.frames_per_sec, 251.0);
+        assert_eq!(rate.bytes_per_sec, (500.0 * 51.0 + 20.0) / 2.0);
+
+        let stats = ProcessorStats::new_handle();
+        stats.record_input_at(HostTimeMs(1_700_000_000_004));
+        assert_eq!(
+            stats.snapshot().last_input_host_timestamp_ms,
+            Some(1_700_000_000_004)
+        );
+
+        // 计数已清零，空窗口与零时长都得到 0
+        assert_eq!(
+            window.flush_tick(Duration::from_secs(1)),
+            InputRate::default()
+        );
+        window.record(51, 1);
+        assert_eq!(window.flush_tick(Duration::ZERO), InputRate::default());
+
+        stats.set_input_rate(rate);
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.input_packets_per_sec, 250.5);
+        assert_eq!(snapshot.input_frames_per_sec, 251.0);
+        assert_eq!(snapshot.input_bytes_per_sec, rate.bytes_per_sec);
+        stats.reset_connection();
+        assert_eq!(stats.snapshot().input_bytes_per_sec, 0.0);
+    }
+}
@@ -0,0 +1,266 @@
+//! 静止帧抑制。
+//!
+//! 设备放在桌上时绝大部分时间静止，处理线程仍以 250 Hz 向前端推送几乎相同的帧。
+//! 启用后，静止且位置、速度、姿态与上一次发出的完整帧相差都小于阈值的帧不再推送，
+//! 只按 `heartbeat_ms` 间隔发出轻量心跳；一旦超出阈值或离开静止立即恢复完整帧。
+//!
+//! 只作用于前端订阅流，录制线程始终收到完整帧。每条消息带按帧递增的 `seq`
+//! 与 `skipped`（自上一条消息以来被有意抑制的帧数），前端据此区分抑制与真实丢帧：
+//! `seq - 上一条 seq - 1 - skipped` 即通道满丢弃的帧数。
+//!
+//! 处理线程与录制回放共用同一个 [`StreamSeq`]，两者交替推送时序号也不会回退。
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    processor::{navigator::MotionState, shared::validate::ConfigValidator},
+    types::outputs::{ResponseData, StreamFrame, StreamHeartbeat, StreamMessage},
+};
+
+/// 静止帧抑制配置。
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct OutputSuppressConfig {
+    /// 是否启用。
+    pub enabled: bool,
+    /// 位置变化阈值 (m)。
+    pub position_eps_m: f64,
+    /// 速度变化阈值 (m/s)。
+    pub velocity_eps_mps: f64,
+    /// 姿态变化阈值 (°)。
+    pub attitude_eps_deg: f64,
+    /// 抑制期间的心跳间隔 (ms，设备时间)。
+    pub heartbeat_ms: u64,
+}
+
+impl Default for OutputSuppressConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            position_eps_m: 0.001,
+            velocity_eps_mps: 0.001,
+            attitude_eps_deg: 0.1,
+            heartbeat_ms: 200,
+        }
+    }
+}
+
+impl OutputSuppressConfig {
+    /// 校验取值范围：阈值非负，心跳间隔 10 ms ~ 10 s。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.non_negative("position_eps_m", self.position_eps_m);
+        v.non_negative("velocity_eps_mps", self.velocity_eps_mps);
+        v.in_range("attitude_eps_deg", self.attitude_eps_deg, 0.0, 180.0);
+        v.in_range("heartbeat_ms", self.heartbeat_ms as f64, 10.0, 10_000.0);
+    }
+
+    /// 两帧的位置、速度、姿态差异是否都在阈值内。
+    fn unchanged(&self, last: &ResponseData, data: &ResponseData) -> bool {
+        let dot = last.attitude.dot(data.attitude).abs().min(1.0);
+        let attitude_deg = (2.0 * dot.acos()).to_degrees();
+        (data.position - last.position).length() < self.position_eps_m
+            && (data.velocity - last.velocity).length() < self.velocity_eps_mps
+            && attitude_deg < self.attitude_eps_deg
+    }
+}
+
+/// 跨线程共享的消息序号句柄。
+pub type StreamSeqHandle = Arc<StreamSeq>;
+
+/// 前端订阅流的消息序号计数器。
+#[derive(Debug, Default)]
+pub struct StreamSeq(AtomicU64);
+
+impl StreamSeq {
+    /// 创建从 0 开始的共享计数器。
+    pub fn new_handle() -> StreamSeqHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 取出下一个序号。
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+/// 前端订阅流的静止帧抑制器，由处理线程与录制回放各持有一个。
+#[derive(Debug, Default)]
+pub struct StaticSuppressor {
+    /// 消息序号来源，默认独立计数。
+    seq: StreamSeqHandle,
+    /// 最近发出的完整帧，抑制判定以它为基准，缓慢漂移累积超过阈值后会发出新帧。
+    last_frame: Option<ResponseData>,
+    /// 最近一条消息（完整帧或心跳）的设备时间戳。
+    last_message_ms: u64,
+    /// 自上一条消息以来被抑制的帧数。
+    skipped: u64,
+}
+
+impl StaticSuppressor {
+    /// 从共享计数器取序号的抑制器。
+    pub fn with_seq(seq: StreamSeqHandle) -> Self {
+        Self {
+            seq,
+            ..Self::default()
+        }
+    }
+
+    /// 为一帧编号并决定发出完整帧、心跳或不发出。
+    pub fn process(
+        &mut self,
+        data: &ResponseData,
+        config: &OutputSuppressConfig,
+    ) -> Option<StreamMessage> {
+        let seq = self.seq.next();
+        let suppress = config.enabled
+            && data.motion_state == MotionState::Static
+            && self
+                .last_frame
+                .is_some_and(|last| config.unchanged(&last, data));
+        if !suppress {
+            self.last_frame = Some(*data);
+            return Some(StreamMessage::Frame(StreamFrame {
+                seq,
+                skipped: self.take_skipped(data.timestamp_ms),
+                snapshot: false,
+                backfill: false,
+                data: *data,
+            }));
+        }
+        // 时间戳回退（设备重启）时立即发心跳并重新计时
+        let elapsed_ms = data.timestamp_ms.checked_sub(self.last_message_ms);
+        if elapsed_ms.is_some_and(|elapsed| elapsed < config.heartbeat_ms) {
+            self.skipped += 1;
+            return None;
+        }
+        Some(StreamMessage::Heartbeat(StreamHeartbeat {
+            seq,
+            timestamp_ms: data.timestamp_ms,
+            unchanged: true,
+            skipped: self.take_skipped(data.timestamp_ms),
+            backfill: false,
+        }))
+    }
+
+    fn take_skipped(&mut self, timestamp_ms: u64) -> u64 {
+        self.last_message_ms = timestamp_ms;
+        std::mem::take(&mut self.skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+
+    use super::*;
+    use crate::processor::navigator::ZuptState;
+    use crate::types::outputs::RESPONSE_SCHEMA_VERSION;
+
+    fn data(timestamp_ms: u64, x: f64, motion_state: MotionState) -> ResponseData {
+        ResponseData {
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms,
+            accel: DVec3::ZERO,
+            accel_with_g: DVec3::ZERO,
+            gyro: DVec3::ZERO,
+            attitude: DQuat::IDENTITY,
+            velocity: DVec3::ZERO,
+            position: DVec3::new(x, 0.0, 0.0),
+            accel_saturated: false,
+            motion_state,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            host_time_estimate_ms: None,
+            debug_vectors: None,
+        }
+    }
+
+    fn seq(message: &StreamMessage) -> (u64, u64) {
+        (message.seq(), message.skipped())
+    }
+
+    #[test]
+    fn static_run_is_suppressed_and_motion_resumes_full_frames() {
+        let config = OutputSuppressConfig {
+            enabled: true,
+            ..OutputSuppressConfig::default()
+        };
+        let mut suppressor = StaticSuppressor::default();
+        let mut messages = Vec::new();
+        let mut ts = 0;
+        let mut push = |suppressor: &mut StaticSuppressor, x: f64, state: MotionState| {
+            let message = suppressor.process(&data(ts, x, state), &config);
+            ts += 4;
+            message
+        };
+
+        // 1 s 静止，位置抖动 0.1 mm：首帧完整，之后每 200 ms 一个心跳
+        for i in 0..250 {
+            let x = if i % 2 == 0 { 0.0 } else { 0.0001 };
+            messages.extend(push(&mut suppressor, x, MotionState::Static));
+        }
+        assert!(matches!(messages[0], StreamMessage::Frame(_)));
+        assert_eq!(messages.len(), 1 + 4);
+        for message in &messages[1..] {
+            let StreamMessage::Heartbeat(heartbeat) = message else {
+                panic!("expected heartbeat, got {message:?}");
+            };
+            assert!(heartbeat.unchanged);
+            assert_eq!(heartbeat.skipped, 49);
+        }
+
+        // 离开静止：第一帧就恢复完整帧
+        let moving = push(&mut suppressor, 0.0, MotionState::Moving).unwrap();
+        assert!(matches!(moving, StreamMessage::Frame(_)));
+        assert_eq!(seq(&moving), (250, 49));
+        messages.push(moving);
+        // 静止但累计漂移超过 1 mm：同样立即发完整帧
+        assert!(push(&mut suppressor, 0.0005, MotionState::Static).is_none());
+        let drifted = push(&mut suppressor, 0.0012, MotionState::Static).unwrap();
+        assert!(matches!(drifted, StreamMessage::Frame(_)));
+        assert_eq!(seq(&drifted), (252, 1));
+        messages.push(drifted);
+
+        // 每条消息的序号缺口都恰好由 skipped 标注
+        for pair in messages.windows(2) {
+            let (prev, _) = seq(&pair[0]);
+            let (next, skipped) = seq(&pair[1]);
+            assert_eq!(next - prev - 1, skipped);
+        }
+
+        // 关闭时每帧都发出
+        let mut disabled = StaticSuppressor::default();
+        for i in 0..10 {
+            let message = disabled
+                .process(
+                    &data(i * 4, 0.0, MotionState::Static),
+                    &OutputSuppressConfig::default(),
+                )
+                .unwrap();
+            assert_eq!(seq(&message), (i, 0));
+        }
+    }
+
+    #[test]
+    fn shared_seq_interleaves_without_going_back() {
+        let config = OutputSuppressConfig::default();
+        let seq = StreamSeq::new_handle();
+        let mut live = StaticSuppressor::with_seq(seq.clone());
+        let mut playback = StaticSuppressor::with_seq(seq);
+        let moving = data(0, 0.0, MotionState::Moving);
+
+        let mut seqs = Vec::new();
+        for _ in 0..2 {
+            seqs.extend(live.process(&moving, &config).map(|m| m.seq()));
+            seqs.extend(playback.process(&moving, &config).map(|m| m.seq()));
+        }
+        assert_eq!(seqs, [0, 1, 2, 3]);
+    }
+}
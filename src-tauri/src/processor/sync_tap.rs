@@ -0,0 +1,242 @@
+//! 敲击同步检测。
+//!
+//! 拍摄实验视频时，常用“在镜头前敲一下传感器”作为音视频与 IMU 的对齐点。
+//! 敲击表现为加速度模长短暂冲高：超过 `threshold_g` 且持续不超过 `max_duration_ms`。
+//! 挥动、推拉等正常运动要么达不到阈值，要么超过阈值的时间远长于一次敲击，均不计入。
+//! 敲击后的余振会在短时间内再次越过阈值，`refractory_ms` 内的后续冲高忽略。
+//! 检测基于含重力的原始加速度模长，与设备姿态和零位校准无关。
+
+use math_f64::DVec3;
+use serde::{Deserialize, Serialize};
+
+use crate::processor::shared::{validate::ConfigValidator, DeviceTimeMs};
+
+/// 敲击同步检测配置。
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SyncTapConfig {
+    /// 是否启用。
+    pub enabled: bool,
+    /// 加速度模长阈值 (g)，含重力。
+    pub threshold_g: f64,
+    /// 超过阈值的最长持续时间 (ms)，更长的冲高视为正常运动。
+    pub max_duration_ms: f64,
+    /// 一次敲击后忽略后续冲高的时长 (ms)，滤掉余振。
+    pub refractory_ms: f64,
+}
+
+impl Default for SyncTapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_g: 3.0,
+            max_duration_ms: 50.0,
+            refractory_ms: 500.0,
+        }
+    }
+}
+
+impl SyncTapConfig {
+    /// 校验取值范围：阈值 1.5 ~ 16 g（量程上限），持续时间 0 ~ 1000 ms，不应期 0 ~ 10000 ms。
+    pub fn validate(&self, v: &mut ConfigValidator) {
+        v.in_range("threshold_g", self.threshold_g, 1.5, 16.0);
+        v.positive("max_duration_ms", self.max_duration_ms, 1_000.0);
+        v.in_range("refractory_ms", self.refractory_ms, 0.0, 10_000.0);
+    }
+}
+
+/// `sync_tap` 事件载荷：检测到一次敲击，录制中写入同步事件。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SyncTap {
+    /// 峰值所在帧的设备时间戳 (ms)。
+    pub timestamp_ms: u64,
+    /// 峰值加速度模长 (g)。
+    pub peak_g: f64,
+    /// 超过阈值的持续时间 (ms)：首个超阈值帧到首个回落帧。
+    pub duration_ms: u64,
+}
+
+/// 正在进行的超阈值冲高。
+#[derive(Debug, Clone, Copy)]
+struct Excursion {
+    start_ms: u64,
+    peak_ms: u64,
+    peak_g: f64,
+}
+
+/// 敲击检测器，由管线持有，逐帧消费原始加速度。
+#[derive(Debug, Clone)]
+pub struct SyncTapDetector {
+    config: SyncTapConfig,
+    /// 重力加速度 (m/s²)，把模长换算为 g。
+    gravity: f64,
+    excursion: Option<Excursion>,
+    /// 上一次敲击峰值的设备时间戳，用于不应期。
+    last_tap_ms: Option<u64>,
+    /// 尚未被取走的敲击事件。
+    event: Option<SyncTap>,
+}
+
+impl SyncTapDetector {
+    /// 创建检测器；`gravity` 为重力加速度 (m/s²)。
+    pub fn new(config: SyncTapConfig, gravity: f64) -> Self {
+        Self {
+            config,
+            gravity,
+            excursion: None,
+            last_tap_ms: None,
+            event: None,
+        }
+    }
+
+    /// 原地更新配置；关闭时丢弃进行中的冲高与未取走的事件。
+    pub fn reconfigure(&mut self, config: SyncTapConfig) {
+        if !config.enabled {
+            self.reset();
+        }
+        self.config = config;
+    }
+
+    /// 清空状态。
+    pub fn reset(&mut self) {
+        self.excursion = None;
+        self.last_tap_ms = None;
+        self.event = None;
+    }
+
+    /// 取走最近一次敲击事件。
+    pub fn take_event(&mut self) -> Option<SyncTap> {
+        self.event.take()
+    }
+
+    /// 消费一帧含重力加速度 (m/s²)；冲高回落且满足敲击条件时返回事件（同时留待取走）。
+    pub fn update(&mut self, timestamp: DeviceTimeMs, accel_with_g: DVec3) -> Option<SyncTap> {
+        if !self.config.enabled {
+            return None;
+        }
+        let timestamp_ms = timestamp.as_millis();
+        let magnitude_g = accel_with_g.length() / self.gravity;
+        if magnitude_g >= self.config.threshold_g {
+            match self.excursion.as_mut() {
+                Some(excursion) if magnitude_g > excursion.peak_g => {
+                    excursion.peak_ms = timestamp_ms;
+                    excursion.peak_g = magnitude_g;
+                }
+                Some(_) => {}
+                None => {
+                    self.excursion = Some(Excursion {
+                        start_ms: timestamp_ms,
+                        peak_ms: timestamp_ms,
+                        peak_g: magnitude_g,
+                    });
+                }
+            }
+            return None;
+        }
+
+        let excursion = self.excursion.take()?;
+        let duration_ms = timestamp_ms.saturating_sub(excursion.start_ms);
+        if duration_ms as f64 > self.config.max_duration_ms {
+            return None;
+        }
+        if let Some(last_tap_ms) = self.last_tap_ms {
+            let since_ms = excursion.peak_ms.saturating_sub(last_tap_ms);
+            if (since_ms as f64) < self.config.refractory_ms {
+                return None;
+            }
+        }
+        self.last_tap_ms = Some(excursion.peak_ms);
+        let tap = SyncTap {
+            timestamp_ms: excursion.peak_ms,
+            peak_g: excursion.peak_g,
+            duration_ms,
+        };
+        self.event = Some(tap);
+        Some(tap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAVITY: f64 = 9.8;
+    const STEP_MS: u64 = 4;
+
+    fn detector() -> SyncTapDetector {
+        SyncTapDetector::new(
+            SyncTapConfig {
+                enabled: true,
+                ..SyncTapConfig::default()
+            },
+            GRAVITY,
+        )
+    }
+
+    /// 按 250 Hz 逐帧喂入加速度模长序列 (g)，方向取 +Z，返回检测到的敲击。
+    fn run(detector: &mut SyncTapDetector, profile_g: &[f64]) -> Vec<SyncTap> {
+        profile_g
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &g)| {
+                detector.update(
+                    DeviceTimeMs(1_000 + i as u64 * STEP_MS),
+                    DVec3::Z * g * GRAVITY,
+                )
+            })
+            .collect()
+    }
+
+    /// 静止 `before` 帧，冲高 `spike`，再静止 `after` 帧。
+    fn profile(before: usize, spike: &[f64], after: usize) -> Vec<f64> {
+        let mut profile = vec![1.0; before];
+        profile.extend_from_slice(spike);
+        profile.extend(std::iter::repeat_n(1.0, after));
+        profile
+    }
+
+    #[test]
+    fn short_spike_is_detected_at_its_peak() {
+        let mut detector = detector();
+        let taps = run(&mut detector, &profile(50, &[3.5, 6.0, 4.0], 50));
+        assert_eq!(taps.len(), 1);
+        let tap = taps[0];
+        assert_eq!(tap.timestamp_ms, 1_000 + 51 * STEP_MS);
+        assert!((tap.peak_g - 6.0).abs() < 1e-9);
+        assert_eq!(tap.duration_ms, 3 * STEP_MS);
+        assert_eq!(detector.take_event(), Some(tap));
+        assert_eq!(detector.take_event(), None);
+    }
+
+    #[test]
+    fn ringing_after_a_tap_is_ignored_until_refractory_ends() {
+        let mut detector = detector();
+        // 敲击后 40 ms 余振再次越过阈值，1 s 后第二次敲击
+        let mut samples = profile(50, &[5.0, 2.0], 8);
+        samples.extend(profile(0, &[3.2], 240));
+        samples.extend(profile(0, &[4.5], 10));
+        let taps = run(&mut detector, &samples);
+        assert_eq!(taps.len(), 2);
+        assert!((taps[0].peak_g - 5.0).abs() < 1e-9);
+        assert!((taps[1].peak_g - 4.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn smooth_swing_and_sustained_push_are_not_taps() {
+        let mut detector = detector();
+        // 1 s 内平滑摆到 2 g 再回落
+        let swing: Vec<f64> = (0..250)
+            .map(|i| 1.0 + (std::f64::consts::PI * i as f64 / 250.0).sin())
+            .collect();
+        assert!(run(&mut detector, &profile(50, &swing, 50)).is_empty());
+        // 超过阈值但持续 200 ms 的猛推
+        assert!(run(&mut detector, &profile(50, &[4.0; 50], 50)).is_empty());
+        assert_eq!(detector.take_event(), None);
+    }
+
+    #[test]
+    fn disabled_detector_reports_nothing() {
+        let mut detector = SyncTapDetector::new(SyncTapConfig::default(), GRAVITY);
+        assert!(run(&mut detector, &profile(10, &[8.0], 10)).is_empty());
+    }
+}
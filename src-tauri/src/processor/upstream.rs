@@ -0,0 +1,325 @@
+//! 蓝牙通知到处理线程的上游转发。
+//!
+//! 接收任务把每条通知包装成 [`RawImuData::Packet`] 送入上游通道。发送失败只有一种原因：
+//! 处理线程一侧的接收端已全部 drop，继续接收没有意义。此时转发立即停止，尝试让设备停止
+//! 主动上报（不再占用无线带宽），标记上游链路已关闭并推送一次 `pipeline_channel_closed`
+//! 事件。链路关闭后依赖数据流的命令返回明确的错误，直到重新连接设备或换上新的发送端。
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use flume::Sender;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::processor::{
+    parser::ImuParser,
+    stats::{host_now_ms, ArrivalJitterWindow, InputRate, InputRateWindow, ProcessorStatsHandle},
+    RawImuData,
+};
+
+/// 上游链路状态句柄（接收任务与客户端共享）。
+pub type UpstreamHealthHandle = Arc<UpstreamHealth>;
+
+/// `pipeline_channel_closed` 事件载荷。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PipelineChannelClosed {
+    /// 关闭前已转发的数据包数。
+    pub forwarded_packets: u64,
+    /// 关闭前已转发的数据帧数。
+    pub forwarded_frames: u64,
+    /// 接收任务已运行的时长 (ms)。
+    pub uptime_ms: u64,
+}
+
+/// 上游链路关闭时的动作，应用中由蓝牙客户端实现，测试中替换为记录器。
+pub trait UpstreamHooks {
+    /// 停止设备主动上报（0x18）。
+    fn stop_reporting(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+    /// 推送 `pipeline_channel_closed` 事件。
+    fn emit_closed(&self, closed: PipelineChannelClosed);
+}
+
+/// 上游链路状态：记录最近一次通道关闭，重新接上发送端前保持。
+#[derive(Debug, Default)]
+pub struct UpstreamHealth {
+    closed: Mutex<Option<PipelineChannelClosed>>,
+}
+
+impl UpstreamHealth {
+    /// 创建共享句柄。
+    pub fn new_handle() -> UpstreamHealthHandle {
+        Arc::new(Self::default())
+    }
+
+    /// 通道已关闭时返回关闭时的上下文。
+    pub fn closed(&self) -> Option<PipelineChannelClosed> {
+        *self.closed.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// 记录通道关闭；此前未关闭时返回 `true`（事件只推送一次）。
+    pub fn mark_closed(&self, closed: PipelineChannelClosed) -> bool {
+        let mut slot = self.closed.lock().unwrap_or_else(|p| p.into_inner());
+        let first = slot.is_none();
+        if first {
+            *slot = Some(closed);
+        }
+        first
+    }
+
+    /// 重新连接或换上新的发送端后清除关闭状态。
+    pub fn reset(&self) {
+        *self.closed.lock().unwrap_or_else(|p| p.into_inner()) = None;
+    }
+
+    /// 链路已关闭时返回给命令调用方的错误。
+    pub fn ensure_open(&self) -> anyhow::Result<()> {
+        match self.closed() {
+            Some(closed) => anyhow::bail!(
+                "处理管线通道已关闭（已转发 {} 帧，运行 {:.1} s），请重新连接设备或重启处理管线",
+                closed.forwarded_frames,
+                closed.uptime_ms as f64 / 1000.0
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 转发任务的结束原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardExit {
+    /// 通知流结束（设备断开或任务被替换）。
+    StreamEnded,
+    /// 上游通道关闭。
+    ChannelClosed(PipelineChannelClosed),
+}
+
+/// 通知转发任务的上下文。
+///
+/// * `tx`: 上游通道发送端
+/// * `stats`: 运行统计，每秒写入一次输入速率
+/// * `sample_interval_ms`: 标称采样间隔（f64 位模式），据此判定迟到的通知
+/// * `health`: 上游链路状态
+pub struct UpstreamForwarder {
+    /// 上游通道发送端。
+    pub tx: Sender<RawImuData>,
+    /// 运行统计。
+    pub stats: ProcessorStatsHandle,
+    /// 标称采样间隔（f64 位模式）。
+    pub sample_interval_ms: Arc<AtomicU64>,
+    /// 上游链路状态。
+    pub health: UpstreamHealthHandle,
+}
+
+impl UpstreamForwarder {
+    /// 把通知逐条转发到上游通道，直到通知流结束或通道关闭。
+    ///
+    /// `divert` 返回 `true` 的通知（原始设备命令的回复）交给等待方，不计入输入速率，也不进处理器。
+    pub async fn run<S, H>(
+        self,
+        notifications: S,
+        mut divert: impl FnMut(&[u8]) -> bool,
+        hooks: &H,
+    ) -> ForwardExit
+    where
+        S: Stream<Item = Vec<u8>>,
+        H: UpstreamHooks,
+    {
+        let Self {
+            tx,
+            stats,
+            sample_interval_ms,
+            health,
+        } = self;
+        let mut notifications = std::pin::pin!(notifications);
+        let started_at = Instant::now();
+        let mut forwarded_packets = 0u64;
+        let mut forwarded_frames = 0u64;
+        let mut window = InputRateWindow::default();
+        let mut jitter = ArrivalJitterWindow::default();
+        let mut last_report = Instant::now();
+        let mut exit = ForwardExit::StreamEnded;
+        while let Some(value) = notifications.next().await {
+            if divert(&value) {
+                continue;
+            }
+            let received_at = Instant::now();
+            let frames = ImuParser::split_frames(&value).len();
+            window.record(value.len(), frames);
+            jitter.record(received_at, frames);
+            let received_at_ms = host_now_ms();
+            stats.record_input_at(received_at_ms);
+            let packet = RawImuData::Packet {
+                data: value,
+                received_at,
+                received_at_ms: Some(received_at_ms),
+            };
+            // 当且仅当所有Receiver被drop时返回error：处理器已退出，继续接收没有意义
+            if tx.send_async(packet).await.is_err() {
+                let closed = PipelineChannelClosed {
+                    forwarded_packets,
+                    forwarded_frames,
+                    uptime_ms: started_at.elapsed().as_millis() as u64,
+                };
+                tracing::error!(
+                    forwarded_packets,
+                    forwarded_frames,
+                    uptime_ms = closed.uptime_ms,
+                    "上游通道已关闭, 停止转发IMU数据"
+                );
+                let first = health.mark_closed(closed);
+                if let Err(e) = hooks.stop_reporting().await {
+                    tracing::warn!("上游通道关闭后停止设备上报失败: {:#}", e);
+                }
+                if first {
+                    hooks.emit_closed(closed);
+                }
+                exit = ForwardExit::ChannelClosed(closed);
+                break;
+            }
+            forwarded_packets += 1;
+            forwarded_frames += frames as u64;
+
+            let elapsed = last_report.elapsed();
+            if elapsed > Duration::from_secs(1) {
+                let rate = window.flush_tick(elapsed);
+                stats.set_input_rate(rate);
+                let arrival =
+                    jitter.flush_tick(f64::from_bits(sample_interval_ms.load(Ordering::Relaxed)));
+                tracing::debug!(
+                    packets_per_sec = rate.packets_per_sec,
+                    frames_per_sec = rate.frames_per_sec,
+                    bytes_per_sec = rate.bytes_per_sec,
+                    interval_mean_ms = arrival.mean_ms,
+                    interval_p95_ms = arrival.p95_ms,
+                    interval_max_ms = arrival.max_ms,
+                    late_intervals = arrival.late,
+                    "蓝牙输入速率"
+                );
+                last_report = Instant::now();
+            }
+        }
+        stats.set_input_rate(InputRate::default());
+        exit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::processor::stats::ProcessorStats;
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        stops: AtomicUsize,
+        closed: Mutex<Vec<PipelineChannelClosed>>,
+    }
+
+    impl UpstreamHooks for RecordingHooks {
+        async fn stop_reporting(&self) -> anyhow::Result<()> {
+            self.stops.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn emit_closed(&self, closed: PipelineChannelClosed) {
+            self.closed.lock().unwrap().push(closed);
+        }
+    }
+
+    fn forwarder(tx: Sender<RawImuData>, health: &UpstreamHealthHandle) -> UpstreamForwarder {
+        UpstreamForwarder {
+            tx,
+            stats: ProcessorStats::new_handle(),
+            sample_interval_ms: Arc::new(AtomicU64::new(10f64.to_bits())),
+            health: health.clone(),
+        }
+    }
+
+    fn notification(seq: u8) -> Vec<u8> {
+        vec![0x11, seq]
+    }
+
+    #[tokio::test]
+    async fn receiver_drop_stops_forwarding_and_replacement_sender_restores_flow() {
+        let health = UpstreamHealth::new_handle();
+        let hooks = RecordingHooks::default();
+
+        // 处理线程收下两包后退出（无缓冲，第三包只能等到接收端 drop）
+        let (tx, rx) = flume::bounded(0);
+        let consumer = tokio::spawn(async move {
+            for _ in 0..2 {
+                rx.recv_async().await.unwrap();
+            }
+        });
+        let (notify_tx, notify_rx) = flume::unbounded();
+        for seq in 0..5 {
+            notify_tx.send(notification(seq)).unwrap();
+        }
+        let task = tokio::spawn({
+            let forwarder = forwarder(tx, &health);
+            async move {
+                let exit = forwarder
+                    .run(notify_rx.into_stream(), |_| false, &hooks)
+                    .await;
+                (exit, hooks)
+            }
+        });
+        consumer.await.unwrap();
+        // 通知流没有结束，任务仍须自行退出
+        let (exit, hooks) = tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("forwarding task should exit")
+            .unwrap();
+
+        let ForwardExit::ChannelClosed(closed) = exit else {
+            panic!("expected channel closed, got {exit:?}");
+        };
+        assert_eq!(closed.forwarded_packets, 2);
+        assert_eq!(hooks.stops.load(Ordering::Relaxed), 1);
+        assert_eq!(*hooks.closed.lock().unwrap(), [closed]);
+        assert_eq!(health.closed(), Some(closed));
+        assert!(health.ensure_open().is_err());
+        // 剩余通知留在流里，没有被空转消耗
+        assert!(notify_tx.len() >= 2);
+
+        // 同一链路再次失败不重复推送事件
+        let (dead_tx, dead_rx) = flume::bounded(1);
+        drop(dead_rx);
+        let exit = forwarder(dead_tx, &health)
+            .run(futures::stream::iter([notification(0)]), |_| false, &hooks)
+            .await;
+        assert!(matches!(exit, ForwardExit::ChannelClosed(_)));
+        assert_eq!(hooks.closed.lock().unwrap().len(), 1);
+
+        // 换上新的发送端后数据恢复流动
+        health.reset();
+        assert!(health.ensure_open().is_ok());
+        let (tx, rx) = flume::unbounded();
+        let exit = forwarder(tx, &health)
+            .run(
+                futures::stream::iter([notification(7), vec![0x50, 0x01], notification(8)]),
+                |value| value[0] != 0x11,
+                &hooks,
+            )
+            .await;
+        assert_eq!(exit, ForwardExit::StreamEnded);
+        let seqs: Vec<u8> = rx
+            .drain()
+            .map(|item| match item {
+                RawImuData::Packet { data, .. } => data[1],
+                _ => panic!("expected a packet"),
+            })
+            .collect();
+        assert_eq!(seqs, [7, 8]);
+        assert_eq!(hooks.closed.lock().unwrap().len(), 1);
+        assert_eq!(health.closed(), None);
+    }
+}
@@ -0,0 +1,403 @@
+//! 数据流看门狗。
+//!
+//! 设备保持蓝牙连接却停止上报（固件卡死）时，界面只会停在最后一帧。看门狗定期比较
+//! 最近一个上游数据包与最近一帧输出的主机时间，任一超过阈值即判定停滞：推送
+//! `data_stalled` 事件，并重发一次开启主动上报命令；数据恢复后推送 `data_resumed`。
+//!
+//! 阈值不低于 [`STALL_INTERVAL_FACTOR`] 个标称采样间隔，低频上报（0.5 Hz 时 2 s 一帧）
+//! 的正常帧间隔不会被误判为停滞。
+
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::processor::{shared::DEFAULT_SAMPLE_INTERVAL_MS, stats::ProcessorStatsSnapshot};
+
+/// 默认停滞阈值 (ms)。
+pub const DEFAULT_STALL_THRESHOLD_MS: u64 = 2000;
+
+/// 停滞阈值至少为标称采样间隔的倍数。
+pub const STALL_INTERVAL_FACTOR: f64 = 3.0;
+
+/// 停滞的环节。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowStage {
+    /// 收不到蓝牙数据包。
+    Input,
+    /// 数据包仍在到达，但处理管线没有输出。
+    Output,
+}
+
+/// `data_stalled` / `data_resumed` 事件载荷。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DataStall {
+    /// 停滞的环节。
+    pub stage: FlowStage,
+    /// 停滞时长 (ms)：停滞事件为判定时已持续的时长，恢复事件为整段停滞时长。
+    pub stalled_ms: u64,
+}
+
+/// 看门狗事件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogEvent {
+    /// 数据流停滞。
+    Stalled(DataStall),
+    /// 数据流恢复。
+    Resumed(DataStall),
+}
+
+/// 看门狗计数快照。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WatchdogSnapshot {
+    /// 停滞阈值 (ms)。
+    pub stall_threshold_ms: u64,
+    /// 实际生效的阈值 (ms)：配置阈值与采样间隔倍数中的较大者。
+    pub effective_threshold_ms: u64,
+    /// 当前停滞的环节，数据正常时为空。
+    pub stalled: Option<FlowStage>,
+    /// 累计停滞次数。
+    pub stall_count: u64,
+    /// 累计恢复尝试次数（重发开启上报命令）。
+    pub recovery_attempts: u64,
+    /// 其中写入失败的次数。
+    pub recovery_failures: u64,
+}
+
+/// 看门狗对外的动作，应用中由 `AppState` 实现，测试中替换为记录器。
+pub trait WatchdogHooks {
+    /// 设备是否已连接；未连接时不判定停滞。
+    fn is_connected(&self) -> impl Future<Output = bool> + Send;
+    /// 推送事件。
+    fn emit(&self, event: WatchdogEvent);
+    /// 重发开启主动上报命令。
+    fn recover(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveStall {
+    stage: FlowStage,
+    /// 最后一次有数据的主机时间。
+    since_ms: u64,
+}
+
+/// 数据流看门狗状态。
+#[derive(Debug)]
+pub struct DataFlowWatchdog {
+    stall_threshold_ms: u64,
+    /// 标称采样间隔 (ms)。
+    sample_interval_ms: f64,
+    /// 本次连接建立（被看门狗观察到）的主机时间，连接前的旧时间戳不参与判定。
+    connected_since_ms: Option<u64>,
+    stall: Option<ActiveStall>,
+    stall_count: u64,
+    recovery_attempts: u64,
+    recovery_failures: u64,
+}
+
+impl Default for DataFlowWatchdog {
+    fn default() -> Self {
+        Self::new(DEFAULT_STALL_THRESHOLD_MS)
+    }
+}
+
+impl DataFlowWatchdog {
+    /// 创建看门狗。
+    pub fn new(stall_threshold_ms: u64) -> Self {
+        Self {
+            stall_threshold_ms,
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            connected_since_ms: None,
+            stall: None,
+            stall_count: 0,
+            recovery_attempts: 0,
+            recovery_failures: 0,
+        }
+    }
+
+    /// 修改停滞阈值，下一次检查生效。
+    pub fn set_stall_threshold_ms(&mut self, stall_threshold_ms: u64) {
+        self.stall_threshold_ms = stall_threshold_ms;
+    }
+
+    /// 修改标称采样间隔 (ms)，下一次检查生效。
+    pub fn set_sample_interval_ms(&mut self, sample_interval_ms: f64) {
+        self.sample_interval_ms = sample_interval_ms;
+    }
+
+    /// 实际生效的停滞阈值 (ms)。
+    pub fn effective_threshold_ms(&self) -> u64 {
+        let scaled = (self.sample_interval_ms * STALL_INTERVAL_FACTOR).ceil();
+        if scaled.is_finite() && scaled > 0.0 {
+            self.stall_threshold_ms.max(scaled as u64)
+        } else {
+            self.stall_threshold_ms
+        }
+    }
+
+    /// 计数快照。
+    pub fn snapshot(&self) -> WatchdogSnapshot {
+        WatchdogSnapshot {
+            stall_threshold_ms: self.stall_threshold_ms,
+            effective_threshold_ms: self.effective_threshold_ms(),
+            stalled: self.stall.map(|stall| stall.stage),
+            stall_count: self.stall_count,
+            recovery_attempts: self.recovery_attempts,
+            recovery_failures: self.recovery_failures,
+        }
+    }
+
+    /// 按当前统计判定一次，停滞开始与结束时各返回一个事件。
+    ///
+    /// 断开连接时静默清除停滞状态，不推送恢复事件。
+    pub fn check(
+        &mut self,
+        now_ms: u64,
+        connected: bool,
+        stats: &ProcessorStatsSnapshot,
+    ) -> Option<WatchdogEvent> {
+        if !connected {
+            self.connected_since_ms = None;
+            self.stall = None;
+            return None;
+        }
+        let connected_since_ms = *self.connected_since_ms.get_or_insert(now_ms);
+        let last_seen = |host_ms: Option<u64>| host_ms.unwrap_or(0).max(connected_since_ms);
+        let last_input_ms = last_seen(stats.last_input_host_timestamp_ms);
+        let last_output_ms = last_seen(stats.last_host_timestamp_ms);
+        let threshold_ms = self.effective_threshold_ms();
+        let stalled_after = |last_ms: u64| now_ms.saturating_sub(last_ms) > threshold_ms;
+
+        // 没有输入时输出必然也停，按输入停滞报告
+        let current = if stalled_after(last_input_ms) {
+            Some((FlowStage::Input, last_input_ms))
+        } else if stalled_after(last_output_ms) {
+            Some((FlowStage::Output, last_output_ms))
+        } else {
+            None
+        };
+
+        match (self.stall, current) {
+            (None, Some((stage, since_ms))) => {
+                self.stall = Some(ActiveStall { stage, since_ms });
+                self.stall_count += 1;
+                Some(WatchdogEvent::Stalled(DataStall {
+                    stage,
+                    stalled_ms: now_ms - since_ms,
+                }))
+            }
+            (Some(stall), None) => {
+                self.stall = None;
+                Some(WatchdogEvent::Resumed(DataStall {
+                    stage: stall.stage,
+                    stalled_ms: now_ms.saturating_sub(stall.since_ms),
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// 判定一次并执行相应动作：推送事件，停滞开始时尝试一次恢复。
+    pub async fn tick<H: WatchdogHooks>(
+        &mut self,
+        now_ms: u64,
+        stats: &ProcessorStatsSnapshot,
+        hooks: &H,
+    ) {
+        let connected = hooks.is_connected().await;
+        let Some(event) = self.check(now_ms, connected, stats) else {
+            return;
+        };
+        hooks.emit(event);
+        match event {
+            WatchdogEvent::Stalled(stall) => {
+                tracing::warn!(stage = ?stall.stage, stalled_ms = stall.stalled_ms, "数据流停滞，重发开启上报命令");
+                self.recovery_attempts += 1;
+                if let Err(error) = hooks.recover().await {
+                    self.recovery_failures += 1;
+                    tracing::warn!("重发开启上报命令失败: {:#}", error);
+                }
+            }
+            WatchdogEvent::Resumed(stall) => {
+                tracing::info!(stage = ?stall.stage, stalled_ms = stall.stalled_ms, "数据流恢复");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    use super::*;
+    use crate::processor::{
+        shared::{DeviceTimeMs, HostTimeMs},
+        stats::ProcessorStats,
+    };
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        disconnected: AtomicBool,
+        events: Mutex<Vec<WatchdogEvent>>,
+        recoveries: AtomicUsize,
+    }
+
+    impl WatchdogHooks for RecordingHooks {
+        async fn is_connected(&self) -> bool {
+            !self.disconnected.load(Ordering::Relaxed)
+        }
+
+        fn emit(&self, event: WatchdogEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+
+        async fn recover(&self) -> anyhow::Result<()> {
+            self.recoveries.fetch_add(1, Ordering::Relaxed);
+            anyhow::bail!("device did not answer")
+        }
+    }
+
+    const T0: u64 = 1_700_000_000_000;
+
+    /// 以 250 Hz 送入输入与输出，每 100 ms 检查一次；`gap` 内设备不上报。
+    async fn run(
+        watchdog: &mut DataFlowWatchdog,
+        hooks: &RecordingHooks,
+        until_ms: u64,
+        gap: std::ops::Range<u64>,
+    ) {
+        let stats = ProcessorStats::new_handle();
+        for t in (0..=until_ms).step_by(4) {
+            if !gap.contains(&t) {
+                stats.record_input_at(HostTimeMs(T0 + t));
+                stats.record_frame_at(DeviceTimeMs(t), HostTimeMs(T0 + t));
+            }
+            if t % 100 == 0 {
+                watchdog.tick(T0 + t, &stats.snapshot(), hooks).await;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn input_gap_emits_stall_and_resume_with_single_recovery() {
+        let mut watchdog = DataFlowWatchdog::default();
+        let hooks = RecordingHooks::default();
+        run(&mut watchdog, &hooks, 10_000, 3_000..7_000).await;
+
+        // 最后一包在 2996 ms，5000 ms 时停滞超过 2 s；7000 ms 恢复
+        assert_eq!(
+            *hooks.events.lock().unwrap(),
+            [
+                WatchdogEvent::Stalled(DataStall {
+                    stage: FlowStage::Input,
+                    stalled_ms: 2_004,
+                }),
+                WatchdogEvent::Resumed(DataStall {
+                    stage: FlowStage::Input,
+                    stalled_ms: 4_004,
+                }),
+            ]
+        );
+        // 停滞期间检查了 20 次，只尝试恢复一次
+        assert_eq!(hooks.recoveries.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            watchdog.snapshot(),
+            WatchdogSnapshot {
+                stall_threshold_ms: DEFAULT_STALL_THRESHOLD_MS,
+                effective_threshold_ms: DEFAULT_STALL_THRESHOLD_MS,
+                stalled: None,
+                stall_count: 1,
+                recovery_attempts: 1,
+                recovery_failures: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn output_stall_and_connection_changes() {
+        let stats = ProcessorStats::new_handle();
+        let mut watchdog = DataFlowWatchdog::new(500);
+        let hooks = RecordingHooks::default();
+
+        // 连接前留下的旧时间戳不算停滞，从看门狗观察到连接开始计时
+        stats.record_frame_at(DeviceTimeMs(0), HostTimeMs(T0 - 60_000));
+        watchdog.tick(T0, &stats.snapshot(), &hooks).await;
+        assert!(hooks.events.lock().unwrap().is_empty());
+
+        // 输入仍在到达，输出停住
+        for t in (0..=1_000).step_by(4) {
+            stats.record_input_at(HostTimeMs(T0 + t));
+        }
+        watchdog.tick(T0 + 1_000, &stats.snapshot(), &hooks).await;
+        assert_eq!(
+            hooks.events.lock().unwrap().last(),
+            Some(&WatchdogEvent::Stalled(DataStall {
+                stage: FlowStage::Output,
+                stalled_ms: 1_000,
+            }))
+        );
+        assert_eq!(watchdog.snapshot().stalled, Some(FlowStage::Output));
+
+        // 断开时静默清除，不推送恢复
+        hooks.disconnected.store(true, Ordering::Relaxed);
+        watchdog.tick(T0 + 1_100, &stats.snapshot(), &hooks).await;
+        assert_eq!(hooks.events.lock().unwrap().len(), 1);
+        assert_eq!(watchdog.snapshot().stalled, None);
+        assert_eq!(hooks.recoveries.load(Ordering::Relaxed), 1);
+    }
+
+    /// 0.5 Hz 上报，蓝牙送达抖动让奇数帧晚到 500 ms；每 100 ms 检查一次。
+    async fn run_half_hertz(
+        watchdog: &mut DataFlowWatchdog,
+        hooks: &RecordingHooks,
+        until_ms: u64,
+        gap: std::ops::Range<u64>,
+    ) {
+        let stats = ProcessorStats::new_handle();
+        for t in (0..=until_ms).step_by(100) {
+            let even_frame = t % 4_000 == 0;
+            let late_odd_frame = t % 4_000 == 2_500;
+            if (even_frame || late_odd_frame) && !gap.contains(&t) {
+                stats.record_input_at(HostTimeMs(T0 + t));
+                stats.record_frame_at(DeviceTimeMs(t), HostTimeMs(T0 + t));
+            }
+            watchdog.tick(T0 + t, &stats.snapshot(), hooks).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn half_hertz_reporting_scales_the_threshold() {
+        let mut watchdog = DataFlowWatchdog::default();
+        watchdog.set_sample_interval_ms(2000.0);
+        assert_eq!(watchdog.effective_threshold_ms(), 6_000);
+        let hooks = RecordingHooks::default();
+
+        // 帧间隔在 1.5 s 与 2.5 s 间交替，超过配置的 2 s 也不算停滞
+        run_half_hertz(&mut watchdog, &hooks, 20_000, 0..0).await;
+        assert!(hooks.events.lock().unwrap().is_empty());
+
+        // 最后一帧在 4000 ms，缺 3 帧后才判定停滞
+        let mut watchdog = DataFlowWatchdog::default();
+        watchdog.set_sample_interval_ms(2000.0);
+        run_half_hertz(&mut watchdog, &hooks, 20_000, 6_000..12_000).await;
+        assert_eq!(
+            *hooks.events.lock().unwrap(),
+            [
+                WatchdogEvent::Stalled(DataStall {
+                    stage: FlowStage::Input,
+                    stalled_ms: 6_100,
+                }),
+                WatchdogEvent::Resumed(DataStall {
+                    stage: FlowStage::Input,
+                    stalled_ms: 8_000,
+                }),
+            ]
+        );
+        let snapshot = watchdog.snapshot();
+        assert_eq!(snapshot.stall_threshold_ms, DEFAULT_STALL_THRESHOLD_MS);
+        assert_eq!(snapshot.effective_threshold_ms, 6_000);
+    }
+}
@@ -0,0 +1,289 @@
+//! 录制样本的二进制批量编码。
+//!
+//! 长录制经 `get_recording_samples` 加载时大部分耗时在 JSON 序列化上。这里按定长
+//! 小端记录把样本写进一块连续内存，前端直接用 `DataView` 解码（见
+//! `src/utils/RecordingSampleBinary.ts`，布局常量与本文件一一对应）。
+//!
+//! 缓冲区 = 16 字节头 + `count` 条 176 字节记录，所有浮点字段 8 字节对齐：
+//!
+//! | 偏移 | 长度 | 内容 |
+//! |------|------|------|
+//! | 0    | 4    | 魔数 `IMUS` |
+//! | 4    | 2    | 布局版本（u16） |
+//! | 6    | 2    | 单条记录长度（u16） |
+//! | 8    | 8    | 记录条数（u64） |
+//!
+//! 单条记录布局见 [`SAMPLE_BINARY_FIELDS`]。
+
+use math_f64::DVec3;
+
+use crate::processor::navigator::{MotionState, PositionSource};
+use crate::types::outputs::ResponseData;
+
+/// 缓冲区魔数。
+pub const SAMPLE_BINARY_MAGIC: [u8; 4] = *b"IMUS";
+/// 记录布局版本，字段增删或调整顺序时递增。
+pub const SAMPLE_BINARY_VERSION: u16 = 1;
+/// 头部长度（字节）。
+pub const SAMPLE_BINARY_HEADER_SIZE: usize = 16;
+/// 单条记录长度（字节）。
+pub const SAMPLE_BINARY_RECORD_SIZE: usize = 176;
+
+/// 单条记录的字段布局：`(名称, 偏移, 长度)`，均为小端。
+///
+/// - `timestamp_ms` / `calc_timestamp_ms`: u64，设备时间戳与导航时间戳；
+/// - 向量为 3 个 f64（x, y, z），`attitude` 为 4 个 f64（x, y, z, w），
+///   分别取自 `DVec3::to_le_bytes` / `DQuat::to_le_bytes`；
+/// - `motion_state`: u8，0 = static，1 = moving，2 = unknown；
+/// - `accel_saturated`: u8，0 / 1；
+/// - `position_source`: u8，0 = integrated，1 = device_offset，2 = blended；
+/// - 末尾 5 字节填充为 0，保持下一条记录 8 字节对齐。
+pub const SAMPLE_BINARY_FIELDS: &[(&str, usize, usize)] = &[
+    ("timestamp_ms", 0, 8),
+    ("calc_timestamp_ms", 8, 8),
+    ("accel", 16, 24),
+    ("accel_with_g", 40, 24),
+    ("gyro", 64, 24),
+    ("attitude", 88, 32),
+    ("velocity", 120, 24),
+    ("position", 144, 24),
+    ("motion_state", 168, 1),
+    ("accel_saturated", 169, 1),
+    ("position_source", 170, 1),
+    ("padding", 171, 5),
+];
+
+/// 运动状态的编码值。
+pub fn motion_state_code(state: MotionState) -> u8 {
+    match state {
+        MotionState::Static => 0,
+        MotionState::Moving => 1,
+        MotionState::Unknown => 2,
+    }
+}
+
+/// 位置来源的编码值。
+pub fn position_source_code(source: PositionSource) -> u8 {
+    match source {
+        PositionSource::Integrated => 0,
+        PositionSource::DeviceOffset => 1,
+        PositionSource::Blended => 2,
+    }
+}
+
+/// 按预期条数分配缓冲区并写入头部，之后逐条调用 [`push_sample`]。
+pub fn begin_samples(count: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(SAMPLE_BINARY_HEADER_SIZE + count * SAMPLE_BINARY_RECORD_SIZE);
+    out.extend_from_slice(&SAMPLE_BINARY_MAGIC);
+    out.extend_from_slice(&SAMPLE_BINARY_VERSION.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_BINARY_RECORD_SIZE as u16).to_le_bytes());
+    out.extend_from_slice(&(count as u64).to_le_bytes());
+    out
+}
+
+/// 追加一条记录。
+pub fn push_sample(out: &mut Vec<u8>, calc_timestamp_ms: u64, data: &ResponseData) {
+    let vec3 = |out: &mut Vec<u8>, v: DVec3| out.extend_from_slice(&v.to_le_bytes());
+    out.extend_from_slice(&data.timestamp_ms.to_le_bytes());
+    out.extend_from_slice(&calc_timestamp_ms.to_le_bytes());
+    vec3(out, data.accel);
+    vec3(out, data.accel_with_g);
+    vec3(out, data.gyro);
+    out.extend_from_slice(&data.attitude.to_le_bytes());
+    vec3(out, data.velocity);
+    vec3(out, data.position);
+    out.extend_from_slice(&[
+        motion_state_code(data.motion_state),
+        data.accel_saturated as u8,
+        position_source_code(data.position_source),
+        0,
+        0,
+        0,
+        0,
+        0,
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use math_f64::DQuat;
+
+    use super::*;
+    use crate::processor::navigator::ZuptState;
+    use crate::types::outputs::RESPONSE_SCHEMA_VERSION;
+
+    /// 参考解码器：只按 [`SAMPLE_BINARY_FIELDS`] 的偏移读取，不复用编码路径。
+    fn decode(bytes: &[u8]) -> Vec<(u64, u64, ResponseData)> {
+        assert_eq!(bytes[..4], SAMPLE_BINARY_MAGIC);
+        assert_eq!(
+            u16::from_le_bytes([bytes[4], bytes[5]]),
+            SAMPLE_BINARY_VERSION
+        );
+        let record_size = u16::from_le_bytes([bytes[6], bytes[7]]) as usize;
+        let count = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) as usize;
+        assert_eq!(bytes.len(), SAMPLE_BINARY_HEADER_SIZE + count * record_size);
+
+        let field = |record: &[u8], name: &str| -> Vec<u8> {
+            let (_, offset, len) = SAMPLE_BINARY_FIELDS
+                .iter()
+                .find(|(field, _, _)| *field == name)
+                .unwrap();
+            record[*offset..offset + len].to_vec()
+        };
+        let u64_at =
+            |record: &[u8], name| u64::from_le_bytes(field(record, name).try_into().unwrap());
+        let vec3_at =
+            |record: &[u8], name| DVec3::from_le_bytes(field(record, name).try_into().unwrap());
+
+        bytes[SAMPLE_BINARY_HEADER_SIZE..]
+            .chunks_exact(record_size)
+            .map(|record| {
+                let motion_state = match field(record, "motion_state")[0] {
+                    0 => MotionState::Static,
+                    1 => MotionState::Moving,
+                    _ => MotionState::Unknown,
+                };
+                let position_source = match field(record, "position_source")[0] {
+                    1 => PositionSource::DeviceOffset,
+                    2 => PositionSource::Blended,
+                    _ => PositionSource::Integrated,
+                };
+                let data = ResponseData {
+                    schema_version: RESPONSE_SCHEMA_VERSION,
+                    timestamp_ms: u64_at(record, "timestamp_ms"),
+                    accel: vec3_at(record, "accel"),
+                    accel_with_g: vec3_at(record, "accel_with_g"),
+                    gyro: vec3_at(record, "gyro"),
+                    attitude: DQuat::from_le_bytes(field(record, "attitude").try_into().unwrap()),
+                    velocity: vec3_at(record, "velocity"),
+                    position: vec3_at(record, "position"),
+                    accel_saturated: field(record, "accel_saturated")[0] != 0,
+                    motion_state,
+                    zupt: ZuptState {
+                        is_static: motion_state == MotionState::Static,
+                        ..ZuptState::default()
+                    },
+                    position_source,
+                    jerk: None,
+                    ang_accel: None,
+                    position_sigma_m: 0.0,
+                    host_time_estimate_ms: None,
+                    debug_vectors: None,
+                };
+                (
+                    u64_at(record, "timestamp_ms"),
+                    u64_at(record, "calc_timestamp_ms"),
+                    data,
+                )
+            })
+            .collect()
+    }
+
+    fn sample(i: u64) -> ResponseData {
+        let v = i as f64 * 0.001;
+        let motion_state = [
+            MotionState::Static,
+            MotionState::Moving,
+            MotionState::Unknown,
+        ][i as usize % 3];
+        ResponseData {
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms: 1_000 + i * 4,
+            accel: DVec3::new(v, -v, 0.5 * v),
+            accel_with_g: DVec3::new(v, -v, 9.80665 + v),
+            gyro: DVec3::new(0.1 * v, 0.2 * v, -0.3 * v),
+            attitude: DQuat::from_rotation_z(v).normalize(),
+            velocity: DVec3::splat(v * 2.0),
+            position: DVec3::new(v * v, 1.0 / (1.0 + v), -v),
+            accel_saturated: i.is_multiple_of(7),
+            motion_state,
+            zupt: ZuptState {
+                is_static: motion_state == MotionState::Static,
+                ..ZuptState::default()
+            },
+            position_source: [
+                PositionSource::Integrated,
+                PositionSource::DeviceOffset,
+                PositionSource::Blended,
+            ][i as usize % 3],
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            host_time_estimate_ms: None,
+            debug_vectors: None,
+        }
+    }
+
+    fn encode(samples: &[ResponseData]) -> Vec<u8> {
+        let mut out = begin_samples(samples.len());
+        for data in samples {
+            push_sample(&mut out, data.timestamp_ms + 1, data);
+        }
+        out
+    }
+
+    #[test]
+    fn layout_is_contiguous_and_aligned() {
+        let mut next = 0;
+        for (name, offset, len) in SAMPLE_BINARY_FIELDS {
+            assert_eq!(*offset, next, "{name}");
+            if *len >= 8 {
+                assert_eq!(offset % 8, 0, "{name}");
+            }
+            next = offset + len;
+        }
+        assert_eq!(next, SAMPLE_BINARY_RECORD_SIZE);
+        assert_eq!(SAMPLE_BINARY_RECORD_SIZE % 8, 0);
+        assert_eq!(begin_samples(0).len(), SAMPLE_BINARY_HEADER_SIZE);
+    }
+
+    #[test]
+    fn round_trip_through_reference_decoder() {
+        let samples: Vec<ResponseData> = (0..50).map(sample).collect();
+        let bytes = encode(&samples);
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.len(), samples.len());
+        for ((timestamp_ms, calc_timestamp_ms, data), expected) in decoded.iter().zip(&samples) {
+            assert_eq!(*timestamp_ms, expected.timestamp_ms);
+            assert_eq!(*calc_timestamp_ms, expected.timestamp_ms + 1);
+            // 逐位一致：二者序列化为相同的 JSON
+            assert_eq!(
+                serde_json::to_string(data).unwrap(),
+                serde_json::to_string(expected).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn binary_encoding_beats_json_for_large_sessions() {
+        let samples: Vec<ResponseData> = (0..100_000).map(sample).collect();
+        // 取多次中的最短耗时，降低调度抖动的影响
+        let fastest = |f: &dyn Fn() -> usize| -> (Duration, usize) {
+            (0..3)
+                .map(|_| {
+                    let start = Instant::now();
+                    let len = f();
+                    (start.elapsed(), len)
+                })
+                .min()
+                .unwrap()
+        };
+        let (binary_time, binary_len) = fastest(&|| encode(&samples).len());
+        let (json_time, json_len) = fastest(&|| serde_json::to_vec(&samples).unwrap().len());
+
+        assert_eq!(
+            binary_len,
+            SAMPLE_BINARY_HEADER_SIZE + samples.len() * SAMPLE_BINARY_RECORD_SIZE
+        );
+        assert!(
+            binary_len < json_len,
+            "binary {binary_len} B vs json {json_len} B"
+        );
+        assert!(
+            binary_time * 5 <= json_time,
+            "binary {binary_time:?} vs json {json_time:?}"
+        );
+    }
+}
@@ -1,22 +1,83 @@
 //! 数据库连接与表结构维护。
+//!
+//! 表结构版本记在 `schema_meta` 表中，打开时版本已是 [`DB_SCHEMA_VERSION`] 就不再改动表结构。
+//! 需要迁移时先做完整性检查，再把数据库备份到同目录的 `.bak` 文件（24 小时内已有备份时跳过），
+//! 然后在一个事务中完成迁移并写入新版本；中途失败整体回滚，不会留下改了一半的表结构。
+//! 完整性检查（`PRAGMA quick_check`）在每个进程里对每个数据库文件做一次，
+//! 失败时返回 [`DatabaseError::Corrupt`]，命令据此带出 `DatabaseCorrupt` 错误码。
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
 
-use anyhow::Context;
-use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Schema, Statement};
+use anyhow::{bail, Context};
+use chrono::{DateTime, NaiveDateTime};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Schema, Statement, TransactionTrait};
 
-use crate::recorder::models;
+use crate::{
+    recorder::{location::RECORDING_DB_FILE, models},
+    types::{error::ErrorCode, recording::DatabaseIntegrity},
+};
 
-/// 录制数据库默认路径。
-pub fn recording_db_path() -> anyhow::Result<PathBuf> {
+/// 当前代码对应的表结构版本。
+///
+/// 改动表结构时加一，并在 [`migrate`] 中追加对应的迁移步骤。
+/// 没有 `schema_meta` 表的数据库（早期版本写出的或全新的）视为版本 0。
+pub const DB_SCHEMA_VERSION: i64 = 3;
+
+/// 迁移前备份的最短间隔：24 小时内已有备份时不再备份。
+pub const BACKUP_INTERVAL_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// 备份文件名中的时间格式（UTC）。
+const BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// 数据库无法安全使用的原因。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DatabaseError {
+    /// 完整性检查发现问题，附 SQLite 报告的问题列表。
+    #[error("录制数据库完整性检查失败: {}", .0.join("; "))]
+    Corrupt(Vec<String>),
+    /// 数据库由更新版本的程序写出，表结构版本高于当前支持的版本。
+    #[error("录制数据库结构版本 {found} 高于本程序支持的 {supported}")]
+    TooNew {
+        /// 数据库记录的版本。
+        found: i64,
+        /// 当前支持的版本。
+        supported: i64,
+    },
+}
+
+impl DatabaseError {
+    /// 对应的 IPC 错误码。
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DatabaseError::Corrupt(_) => ErrorCode::DatabaseCorrupt,
+            DatabaseError::TooNew { .. } => ErrorCode::DatabaseTooNew,
+        }
+    }
+}
+
+/// 旧版录制目录：工作目录（在 `src-tauri/` 下运行时取项目根目录）。
+///
+/// 应用内录制目录由 [`RecordingLocation`](crate::recorder::location::RecordingLocation)
+/// 解析，这里只作为最后的回退以及离线 replay 的默认位置。
+pub fn legacy_recording_dir() -> anyhow::Result<PathBuf> {
     let mut base_dir = std::env::current_dir().context("resolve current directory")?;
     if base_dir.file_name().is_some_and(|name| name == "src-tauri") {
         if let Some(parent) = base_dir.parent() {
             base_dir = parent.to_path_buf();
         }
     }
+    Ok(base_dir)
+}
+
+/// 旧版录制数据库路径（工作目录下），供离线 replay 默认使用。
+pub fn recording_db_path() -> anyhow::Result<PathBuf> {
+    let base_dir = legacy_recording_dir()?;
     std::fs::create_dir_all(&base_dir).context("ensure project directory exists")?;
-    Ok(base_dir.join("imu_recordings.sqlite"))
+    Ok(base_dir.join(RECORDING_DB_FILE))
 }
 
 /// 连接数据库。
@@ -28,9 +89,16 @@ pub async fn connect(path: &Path) -> anyhow::Result<DatabaseConnection> {
     Database::connect(url).await.context("open sqlite database")
 }
 
-/// 确保数据库表结构存在。
+/// 确保数据库表结构为当前版本：完整性检查、按需备份后迁移。
+///
+/// 版本已是当前版本时只设置连接参数，不执行任何表结构语句。
 pub async fn ensure_schema(conn: &DatabaseConnection) -> anyhow::Result<()> {
     let db_backend = conn.get_database_backend();
+    let db_path = main_db_path(conn).await?;
+    if let Some(path) = &db_path {
+        check_integrity_once(conn, path).await?;
+    }
+
     conn.execute(Statement::from_string(
         db_backend,
         "PRAGMA journal_mode=WAL;",
@@ -44,6 +112,296 @@ pub async fn ensure_schema(conn: &DatabaseConnection) -> anyhow::Result<()> {
     .await
     .context("set sqlite synchronous")?;
 
+    let tables = user_tables(conn).await?;
+    let version = read_schema_version(conn, &tables).await?;
+    if version == DB_SCHEMA_VERSION {
+        return Ok(());
+    }
+    if version > DB_SCHEMA_VERSION {
+        return Err(DatabaseError::TooNew {
+            found: version,
+            supported: DB_SCHEMA_VERSION,
+        }
+        .into());
+    }
+
+    // 全新的空库没有可丢的数据，不备份
+    if let Some(path) = db_path.as_deref().filter(|_| !tables.is_empty()) {
+        backup_before_migration(conn, path, chrono::Utc::now().timestamp_millis()).await?;
+    }
+    migrate(conn).await
+}
+
+/// 对数据库做完整性检查并读取表结构版本，不做迁移。
+///
+/// 与打开时的检查不同，每次调用都重新检查；数据库文件不存在时报错而不是新建。
+pub async fn verify_database(path: &Path) -> anyhow::Result<DatabaseIntegrity> {
+    if !path.exists() {
+        bail!("recording database {} does not exist", path.display());
+    }
+    let conn = connect(path).await?;
+    let problems = quick_check(&conn).await;
+    // 损坏的库读不出可信的版本，按 0 报告
+    let schema_version = if problems.is_empty() {
+        let tables = user_tables(&conn).await?;
+        read_schema_version(&conn, &tables).await?
+    } else {
+        0
+    };
+    Ok(DatabaseIntegrity {
+        ok: problems.is_empty(),
+        problems,
+        schema_version,
+        supported_schema_version: DB_SCHEMA_VERSION,
+        db_path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// 主数据库文件路径，内存数据库为 `None`。
+async fn main_db_path(conn: &DatabaseConnection) -> anyhow::Result<Option<PathBuf>> {
+    let rows = conn
+        .query_all(Statement::from_string(
+            conn.get_database_backend(),
+            "PRAGMA database_list;",
+        ))
+        .await
+        .context("query sqlite database list")?;
+    Ok(rows
+        .iter()
+        .find(|row| {
+            row.try_get::<String>("", "name")
+                .is_ok_and(|name| name == "main")
+        })
+        .and_then(|row| row.try_get::<String>("", "file").ok())
+        .filter(|file| !file.is_empty())
+        .map(PathBuf::from))
+}
+
+/// 每个进程对每个数据库文件只做一次完整性检查，通过后记下。
+async fn check_integrity_once(conn: &DatabaseConnection, path: &Path) -> anyhow::Result<()> {
+    static VERIFIED: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    let verified = VERIFIED.get_or_init(Mutex::default);
+    if verified
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .contains(path)
+    {
+        return Ok(());
+    }
+    let problems = quick_check(conn).await;
+    if !problems.is_empty() {
+        tracing::error!(?problems, "录制数据库完整性检查失败: {}", path.display());
+        return Err(DatabaseError::Corrupt(problems).into());
+    }
+    verified
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .insert(path.to_path_buf());
+    Ok(())
+}
+
+/// `PRAGMA quick_check`，返回发现的问题，没有问题时为空。
+///
+/// 损坏严重时检查语句本身就会失败，此时错误信息作为唯一的问题返回。
+async fn quick_check<C: ConnectionTrait>(conn: &C) -> Vec<String> {
+    let rows = match conn
+        .query_all(Statement::from_string(
+            conn.get_database_backend(),
+            "PRAGMA quick_check;",
+        ))
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return vec![e.to_string()],
+    };
+    let messages: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .collect();
+    match messages.as_slice() {
+        [ok] if ok == "ok" => Vec::new(),
+        [] => vec!["quick_check returned no result".to_string()],
+        _ => messages,
+    }
+}
+
+/// 用户表名（不含 SQLite 内部表）。
+async fn user_tables<C: ConnectionTrait>(conn: &C) -> anyhow::Result<HashSet<String>> {
+    let rows = conn
+        .query_all(Statement::from_string(
+            conn.get_database_backend(),
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%';",
+        ))
+        .await
+        .context("list sqlite tables")?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.try_get::<String>("", "name").ok())
+        .collect())
+}
+
+/// `schema_meta` 中记录的表结构版本，没有该表时为 0。
+async fn read_schema_version<C: ConnectionTrait>(
+    conn: &C,
+    tables: &HashSet<String>,
+) -> anyhow::Result<i64> {
+    if !tables.contains("schema_meta") {
+        return Ok(0);
+    }
+    let row = conn
+        .query_one(Statement::from_string(
+            conn.get_database_backend(),
+            "SELECT version FROM schema_meta WHERE id = 1;",
+        ))
+        .await
+        .context("read schema version")?;
+    Ok(match row {
+        Some(row) => row.try_get("", "version").context("read schema version")?,
+        None => 0,
+    })
+}
+
+/// 迁移前把数据库备份为同目录的 `<文件名>.<UTC 时间>.bak`，返回备份路径。
+///
+/// `BACKUP_INTERVAL_MS` 内已有备份时跳过并返回 `None`。用 `VACUUM INTO` 写出一致的快照，
+/// WAL 中尚未合并的写入也包含在内。
+pub async fn backup_before_migration<C: ConnectionTrait>(
+    conn: &C,
+    db_path: &Path,
+    now_ms: i64,
+) -> anyhow::Result<Option<PathBuf>> {
+    if let Some(latest_ms) = latest_backup_ms(db_path) {
+        if now_ms - latest_ms < BACKUP_INTERVAL_MS {
+            tracing::info!("24 小时内已有备份，迁移前不再备份: {}", db_path.display());
+            return Ok(None);
+        }
+    }
+    let time = DateTime::from_timestamp_millis(now_ms)
+        .context("backup timestamp out of range")?
+        .format(BACKUP_TIME_FORMAT);
+    let mut file_name = db_path
+        .file_name()
+        .context("database path has no file name")?
+        .to_os_string();
+    file_name.push(format!(".{time}.bak"));
+    let backup_path = db_path.with_file_name(file_name);
+    conn.execute(Statement::from_string(
+        conn.get_database_backend(),
+        format!(
+            "VACUUM INTO '{}';",
+            backup_path.to_string_lossy().replace('\'', "''")
+        ),
+    ))
+    .await
+    .with_context(|| format!("back up database to {}", backup_path.display()))?;
+    tracing::info!("迁移表结构前已备份数据库: {}", backup_path.display());
+    Ok(Some(backup_path))
+}
+
+/// 同目录下最近一次备份的时间（ms），按文件名中的时间判断。
+fn latest_backup_ms(db_path: &Path) -> Option<i64> {
+    let prefix = format!("{}.", db_path.file_name()?.to_string_lossy());
+    std::fs::read_dir(db_path.parent()?)
+        .ok()?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().to_string_lossy().to_string();
+            let time = name.strip_prefix(&prefix)?.strip_suffix(".bak")?;
+            let time = NaiveDateTime::parse_from_str(time, BACKUP_TIME_FORMAT).ok()?;
+            Some(time.and_utc().timestamp_millis())
+        })
+        .max()
+}
+
+/// 在一个事务中把表结构迁移到 [`DB_SCHEMA_VERSION`]。
+///
+/// 先写 `schema_meta` 占住写锁，再在事务内重新读取版本：并发打开同一数据库时
+/// 只有一个连接执行迁移，其余连接看到已是当前版本后直接提交。
+async fn migrate(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let txn = conn.begin().await.context("begin schema migration")?;
+    let db_backend = txn.get_database_backend();
+    txn.execute(Statement::from_string(
+        db_backend,
+        "CREATE TABLE IF NOT EXISTS schema_meta (
+            id             INTEGER NOT NULL PRIMARY KEY CHECK (id = 1),
+            version        INTEGER NOT NULL,
+            migrated_at_ms INTEGER NOT NULL
+        );",
+    ))
+    .await
+    .context("create schema_meta table")?;
+    txn.execute(Statement::from_string(
+        db_backend,
+        "INSERT OR IGNORE INTO schema_meta (id, version, migrated_at_ms) VALUES (1, 0, 0);",
+    ))
+    .await
+    .context("initialize schema_meta")?;
+
+    let from = read_schema_version(&txn, &user_tables(&txn).await?).await?;
+    if from >= DB_SCHEMA_VERSION {
+        txn.commit().await.context("commit schema migration")?;
+        return Ok(());
+    }
+    // 版本 1：早期版本写出的库补齐表、列与索引，新库建表
+    if from < 1 {
+        migrate_v1(&txn).await?;
+    }
+    // 版本 2：换算到其他会话标定系的位置与姿态列
+    if from < 2 {
+        migrate_v2(&txn).await?;
+    }
+    // 版本 3：深度录制的阶段中间值表
+    if from < 3 {
+        migrate_v3(&txn).await?;
+    }
+
+    txn.execute(Statement::from_sql_and_values(
+        db_backend,
+        "UPDATE schema_meta SET version = ?, migrated_at_ms = ? WHERE id = 1;",
+        [
+            DB_SCHEMA_VERSION.into(),
+            chrono::Utc::now().timestamp_millis().into(),
+        ],
+    ))
+    .await
+    .context("record schema version")?;
+    txn.commit().await.context("commit schema migration")?;
+    tracing::info!(from, to = DB_SCHEMA_VERSION, "录制数据库表结构已迁移");
+    Ok(())
+}
+
+/// 表中缺少的列逐一补上；列定义为 `(列名, 类型与约束)`。
+async fn add_missing_columns<C: ConnectionTrait>(
+    conn: &C,
+    table: &str,
+    columns: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    let existing: HashSet<String> = conn
+        .query_all(Statement::from_string(
+            conn.get_database_backend(),
+            format!("PRAGMA table_info({table});"),
+        ))
+        .await
+        .with_context(|| format!("query {table} columns"))?
+        .iter()
+        .filter_map(|row| row.try_get::<String>("", "name").ok())
+        .collect();
+    for (name, definition) in columns {
+        if existing.contains(*name) {
+            continue;
+        }
+        conn.execute(Statement::from_string(
+            conn.get_database_backend(),
+            format!("ALTER TABLE {table} ADD COLUMN {name} {definition};"),
+        ))
+        .await
+        .with_context(|| format!("add column {table}.{name}"))?;
+    }
+    Ok(())
+}
+
+/// 版本 1：建表并补齐早期版本缺少的列与索引。
+async fn migrate_v1<C: ConnectionTrait>(conn: &C) -> anyhow::Result<()> {
+    let db_backend = conn.get_database_backend();
     let schema = Schema::new(db_backend);
     let mut create_sessions = schema.create_table_from_entity(models::recording_sessions::Entity);
     create_sessions.if_not_exists();
@@ -57,6 +415,25 @@ pub async fn ensure_schema(conn: &DatabaseConnection) -> anyhow::Result<()> {
         .await
         .context("create imu_samples table")?;
 
+    // 兼容旧表：补齐运动状态、位置来源、离线平滑位置、位置不确定度与气压计列
+    add_missing_columns(
+        conn,
+        "imu_samples",
+        &[
+            ("motion_state", "TEXT NOT NULL DEFAULT 'unknown'"),
+            ("position_source", "TEXT NOT NULL DEFAULT 'integrated'"),
+            ("smoothed_position_x", "REAL"),
+            ("smoothed_position_y", "REAL"),
+            ("smoothed_position_z", "REAL"),
+            ("position_sigma_m", "REAL"),
+            ("baro_temperature_c", "REAL"),
+            ("baro_pressure_hpa", "REAL"),
+            ("baro_altitude_m", "REAL"),
+            ("baro_relative_altitude_m", "REAL"),
+        ],
+    )
+    .await?;
+
     conn.execute(Statement::from_string(
         db_backend,
         "CREATE INDEX IF NOT EXISTS idx_imu_samples_session_time
@@ -65,18 +442,99 @@ pub async fn ensure_schema(conn: &DatabaseConnection) -> anyhow::Result<()> {
     .await
     .context("create imu_samples index")?;
 
-    let _ = conn
-        .execute(Statement::from_string(
-            db_backend,
-            "ALTER TABLE recording_sessions ADD COLUMN name TEXT;",
-        ))
-        .await;
-    let _ = conn
-        .execute(Statement::from_string(
-            db_backend,
-            "ALTER TABLE recording_sessions ADD COLUMN tags TEXT;",
-        ))
-        .await;
+    let mut create_markers = schema.create_table_from_entity(models::recording_markers::Entity);
+    create_markers.if_not_exists();
+    conn.execute(db_backend.build(&create_markers))
+        .await
+        .context("create recording_markers table")?;
+
+    conn.execute(Statement::from_string(
+        db_backend,
+        "CREATE INDEX IF NOT EXISTS idx_recording_markers_session_time
+         ON recording_markers(session_id, timestamp_ms);",
+    ))
+    .await
+    .context("create recording_markers index")?;
+
+    let mut create_flags = schema.create_table_from_entity(models::recording_flags::Entity);
+    create_flags.if_not_exists();
+    conn.execute(db_backend.build(&create_flags))
+        .await
+        .context("create recording_flags table")?;
+
+    conn.execute(Statement::from_string(
+        db_backend,
+        "CREATE INDEX IF NOT EXISTS idx_recording_flags_session_time
+         ON recording_flags(session_id, from_ms);",
+    ))
+    .await
+    .context("create recording_flags index")?;
+
+    let mut create_pauses = schema.create_table_from_entity(models::recording_pauses::Entity);
+    create_pauses.if_not_exists();
+    conn.execute(db_backend.build(&create_pauses))
+        .await
+        .context("create recording_pauses table")?;
+
+    conn.execute(Statement::from_string(
+        db_backend,
+        "CREATE INDEX IF NOT EXISTS idx_recording_pauses_session_time
+         ON recording_pauses(session_id, paused_at_ms);",
+    ))
+    .await
+    .context("create recording_pauses index")?;
+
+    let mut create_segments = schema.create_table_from_entity(models::recording_segments::Entity);
+    create_segments.if_not_exists();
+    conn.execute(db_backend.build(&create_segments))
+        .await
+        .context("create recording_segments table")?;
+
+    conn.execute(Statement::from_string(
+        db_backend,
+        "CREATE INDEX IF NOT EXISTS idx_recording_segments_session_time
+         ON recording_segments(session_id, start_ms);",
+    ))
+    .await
+    .context("create recording_segments index")?;
+
+    let mut create_sync_events =
+        schema.create_table_from_entity(models::recording_sync_events::Entity);
+    create_sync_events.if_not_exists();
+    conn.execute(db_backend.build(&create_sync_events))
+        .await
+        .context("create recording_sync_events table")?;
+
+    conn.execute(Statement::from_string(
+        db_backend,
+        "CREATE INDEX IF NOT EXISTS idx_recording_sync_events_session_time
+         ON recording_sync_events(session_id, timestamp_ms);",
+    ))
+    .await
+    .context("create recording_sync_events index")?;
+
+    // 兼容旧表：补齐会话元数据、时钟同步、结构版本、暂停、航向对齐、版本信息与备注列，
+    // 旧会话的结构版本保持为空
+    add_missing_columns(
+        conn,
+        "recording_sessions",
+        &[
+            ("name", "TEXT"),
+            ("tags", "TEXT"),
+            ("stop_reason", "TEXT"),
+            ("parent_session_id", "INTEGER"),
+            ("interrupted", "BOOLEAN NOT NULL DEFAULT 0"),
+            ("clock_offset_ms", "REAL"),
+            ("clock_skew_ppm", "REAL"),
+            ("schema_version", "INTEGER"),
+            ("paused_ms", "INTEGER"),
+            ("heading_yaw_deg", "REAL"),
+            ("heading_aligned_at_ms", "INTEGER"),
+            ("metadata", "TEXT"),
+            ("notes", "TEXT"),
+        ],
+    )
+    .await?;
 
     conn.execute(Statement::from_string(
         db_backend,
@@ -98,18 +556,214 @@ pub async fn ensure_schema(conn: &DatabaseConnection) -> anyhow::Result<()> {
     .await
     .context("create device_calibrations table")?;
 
-    // 兼容旧表：添加 gyro_bias 列（已存在则忽略）
-    for col in ["gyro_bias_x", "gyro_bias_y", "gyro_bias_z"] {
-        let _ = conn
-            .execute(Statement::from_string(
-                db_backend,
-                format!(
-                    "ALTER TABLE device_calibrations ADD COLUMN {} REAL NOT NULL DEFAULT 0.0;",
-                    col
-                ),
-            ))
-            .await;
-    }
+    // 兼容旧表：补齐陀螺零偏列
+    add_missing_columns(
+        conn,
+        "device_calibrations",
+        &[
+            ("gyro_bias_x", "REAL NOT NULL DEFAULT 0.0"),
+            ("gyro_bias_y", "REAL NOT NULL DEFAULT 0.0"),
+            ("gyro_bias_z", "REAL NOT NULL DEFAULT 0.0"),
+        ],
+    )
+    .await?;
+
+    let mut create_profiles = schema.create_table_from_entity(models::device_profiles::Entity);
+    create_profiles.if_not_exists();
+    conn.execute(db_backend.build(&create_profiles))
+        .await
+        .context("create device_profiles table")?;
 
     Ok(())
 }
+
+/// 版本 2：`transform_recording` 写入的换算后位置与姿态列。
+async fn migrate_v2<C: ConnectionTrait>(conn: &C) -> anyhow::Result<()> {
+    add_missing_columns(
+        conn,
+        "imu_samples",
+        &[
+            ("transformed_position_x", "REAL"),
+            ("transformed_position_y", "REAL"),
+            ("transformed_position_z", "REAL"),
+            ("transformed_attitude_w", "REAL"),
+            ("transformed_attitude_x", "REAL"),
+            ("transformed_attitude_y", "REAL"),
+            ("transformed_attitude_z", "REAL"),
+        ],
+    )
+    .await
+}
+
+/// 版本 3：深度录制写入的 `imu_stage_samples` 表。
+async fn migrate_v3<C: ConnectionTrait>(conn: &C) -> anyhow::Result<()> {
+    let db_backend = conn.get_database_backend();
+    let schema = Schema::new(db_backend);
+    let mut create_stages = schema.create_table_from_entity(models::imu_stage_samples::Entity);
+    create_stages.if_not_exists();
+    conn.execute(db_backend.build(&create_stages))
+        .await
+        .context("create imu_stage_samples table")?;
+
+    conn.execute(Statement::from_string(
+        db_backend,
+        "CREATE INDEX IF NOT EXISTS idx_imu_stage_samples_session_time
+         ON imu_stage_samples(session_id, timestamp_ms);",
+    ))
+    .await
+    .context("create imu_stage_samples index")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("imu_vis_db_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn backups(dir: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[tokio::test]
+    async fn fresh_database_is_created_at_the_current_version_without_backup() {
+        let dir = temp_dir("fresh");
+        let db_path = dir.join("fresh.sqlite");
+        let conn = connect(&db_path).await.unwrap();
+        ensure_schema(&conn).await.unwrap();
+        // 再次打开不做任何迁移
+        ensure_schema(&conn).await.unwrap();
+        conn.close().await.unwrap();
+
+        let report = verify_database(&db_path).await.unwrap();
+        assert!(report.ok, "{:?}", report.problems);
+        assert_eq!(report.schema_version, DB_SCHEMA_VERSION);
+        assert_eq!(report.supported_schema_version, DB_SCHEMA_VERSION);
+        assert!(backups(&dir).is_empty());
+
+        assert!(verify_database(&dir.join("missing.sqlite")).await.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn newer_schema_version_is_rejected() {
+        let dir = temp_dir("too_new");
+        let db_path = dir.join("too_new.sqlite");
+        let conn = connect(&db_path).await.unwrap();
+        ensure_schema(&conn).await.unwrap();
+        conn.execute(Statement::from_string(
+            conn.get_database_backend(),
+            format!(
+                "UPDATE schema_meta SET version = {};",
+                DB_SCHEMA_VERSION + 1
+            ),
+        ))
+        .await
+        .unwrap();
+
+        let err = ensure_schema(&conn).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref(),
+            Some(&DatabaseError::TooNew {
+                found: DB_SCHEMA_VERSION + 1,
+                supported: DB_SCHEMA_VERSION,
+            })
+        );
+        conn.close().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn backups_are_skipped_within_the_interval() {
+        let dir = temp_dir("backup");
+        let db_path = dir.join("backup.sqlite");
+        let conn = connect(&db_path).await.unwrap();
+        ensure_schema(&conn).await.unwrap();
+
+        let now_ms = 1_760_000_000_000;
+        let first = backup_before_migration(&conn, &db_path, now_ms)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            first.file_name().unwrap(),
+            "backup.sqlite.20251009-085320.bak"
+        );
+        let hour_ms = 60 * 60 * 1000;
+        assert_eq!(
+            backup_before_migration(&conn, &db_path, now_ms + 23 * hour_ms)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(
+            backup_before_migration(&conn, &db_path, now_ms + 25 * hour_ms)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(backups(&dir).len(), 2);
+
+        // 备份本身是可用的数据库
+        let report = verify_database(&first).await.unwrap();
+        assert!(report.ok);
+        assert_eq!(report.schema_version, DB_SCHEMA_VERSION);
+        conn.close().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn corrupted_file_surfaces_the_integrity_error() {
+        let dir = temp_dir("corrupt");
+        let db_path = dir.join("corrupt.sqlite");
+        let conn = connect(&db_path).await.unwrap();
+        ensure_schema(&conn).await.unwrap();
+        conn.execute(Statement::from_string(
+            conn.get_database_backend(),
+            "PRAGMA wal_checkpoint(TRUNCATE);",
+        ))
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+
+        // 保留文件头页，其余页写成乱码；换一个文件名，相当于下次启动时打开
+        let mut bytes = std::fs::read(&db_path).unwrap();
+        let db_path = dir.join("corrupt_reopened.sqlite");
+        let page_size = u16::from_be_bytes([bytes[16], bytes[17]]) as usize;
+        assert!(bytes.len() > 2 * page_size);
+        for (i, byte) in bytes[page_size..].iter_mut().enumerate() {
+            *byte = (i * 7 + 3) as u8;
+        }
+        std::fs::write(&db_path, bytes).unwrap();
+
+        let report = verify_database(&db_path).await.unwrap();
+        assert!(!report.ok);
+        assert!(!report.problems.is_empty());
+
+        let conn = connect(&db_path).await.unwrap();
+        let err = ensure_schema(&conn).await.unwrap_err();
+        match err.downcast_ref::<DatabaseError>() {
+            Some(e @ DatabaseError::Corrupt(problems)) => {
+                assert!(!problems.is_empty());
+                assert_eq!(e.code(), ErrorCode::DatabaseCorrupt);
+            }
+            other => panic!("expected corrupt error, got {other:?}: {err:#}"),
+        }
+        // 损坏的库没有被当成旧库去迁移或备份
+        assert!(backups(&dir).is_empty());
+        conn.close().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,49 @@
+//! 设备标定结果存取。
+//!
+//! 标定向导的结果与录制共用同一个数据库（`device_calibrations` 表），
+//! 读写都经实体模型完成，表结构只在 [`db::ensure_schema`] 中维护。
+
+use std::path::Path;
+
+use anyhow::Context;
+use sea_orm::{sea_query::OnConflict, ActiveModelTrait, EntityTrait, Iterable};
+
+use crate::recorder::{db, models::device_calibrations};
+
+/// 保存设备标定结果，同一设备的旧记录被覆盖。
+pub async fn save_device_calibration(
+    db_path: &Path,
+    calibration: device_calibrations::Model,
+) -> anyhow::Result<()> {
+    let conn = db::connect(db_path).await?;
+    db::ensure_schema(&conn).await?;
+
+    let columns = device_calibrations::Column::iter()
+        .filter(|column| !matches!(column, device_calibrations::Column::DeviceId));
+    device_calibrations::Entity::insert(
+        device_calibrations::ActiveModel::from(calibration).reset_all(),
+    )
+    .on_conflict(
+        OnConflict::column(device_calibrations::Column::DeviceId)
+            .update_columns(columns)
+            .to_owned(),
+    )
+    .exec(&conn)
+    .await
+    .context("save device calibration")?;
+    Ok(())
+}
+
+/// 查询设备标定结果，未标定过时返回 `None`。
+pub async fn get_device_calibration(
+    db_path: &Path,
+    device_id: &str,
+) -> anyhow::Result<Option<device_calibrations::Model>> {
+    let conn = db::connect(db_path).await?;
+    db::ensure_schema(&conn).await?;
+
+    device_calibrations::Entity::find_by_id(device_id.to_string())
+        .one(&conn)
+        .await
+        .context("query device calibration")
+}
@@ -0,0 +1,294 @@
+//! 按设备保存的标定配置档案。
+//!
+//! 多台 IMU 的偏置、安装方向与姿态零位各不相同，而 `processor.toml` 只有一份。
+//! 档案按蓝牙 UUID 存在录制数据库的 `device_profiles` 表中，内容是配置中标定相关段的片段
+//! （[`PROFILE_SECTIONS`]）与姿态零位。连接时把片段逐层合并到基础配置上：档案中出现的字段
+//! 覆盖基础配置，未出现的沿用基础配置。
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use sea_orm::{sea_query::OnConflict, ActiveModelTrait, EntityTrait, Iterable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    processor::{calibration::AxisCalibration, pipeline::ProcessorPipelineConfig},
+    recorder::{db, models::device_profiles},
+};
+
+/// 档案可以覆盖的配置段。
+pub const PROFILE_SECTIONS: &[&str] = &["calibration"];
+
+/// 单台设备的标定档案。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    /// 设备 ID（蓝牙 UUID）。
+    pub device_id: String,
+    /// 合并到基础配置上的配置片段，顶层只能是 [`PROFILE_SECTIONS`] 中的段。
+    pub overrides: Value,
+    /// 姿态零位，为空时连接后沿用管线当前的零位。
+    pub axis_offset: Option<AxisCalibration>,
+    /// 保存时的主机 UNIX 时间戳（ms）。
+    pub updated_at_ms: i64,
+}
+
+impl DeviceProfile {
+    /// 以当前生效配置的标定段与姿态零位生成档案。
+    pub fn capture(
+        device_id: &str,
+        config: &ProcessorPipelineConfig,
+        axis_offset: AxisCalibration,
+        updated_at_ms: i64,
+    ) -> anyhow::Result<Self> {
+        let mut full = serde_json::to_value(config).context("serialize pipeline config")?;
+        let overrides = PROFILE_SECTIONS
+            .iter()
+            .filter_map(|section| Some((section.to_string(), full.get_mut(*section)?.take())))
+            .collect();
+        Ok(Self {
+            device_id: device_id.to_string(),
+            overrides: Value::Object(overrides),
+            axis_offset: Some(axis_offset),
+            updated_at_ms,
+        })
+    }
+
+    /// 把档案合并到基础配置上，合并结果须通过配置校验。
+    pub fn apply_to(
+        &self,
+        base: &ProcessorPipelineConfig,
+    ) -> anyhow::Result<ProcessorPipelineConfig> {
+        let Value::Object(overrides) = &self.overrides else {
+            bail!("device profile overrides must be an object");
+        };
+        if let Some(section) = overrides
+            .keys()
+            .find(|key| !PROFILE_SECTIONS.contains(&key.as_str()))
+        {
+            bail!("device profile cannot override section `{section}`");
+        }
+        let mut merged = serde_json::to_value(base).context("serialize pipeline config")?;
+        merge_value(&mut merged, &self.overrides);
+        let config: ProcessorPipelineConfig =
+            serde_json::from_value(merged).context("parse merged pipeline config")?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// 连接设备时使用的配置：有档案时合并到基础配置上，没有时就是基础配置。
+pub fn config_for_device(
+    base: &ProcessorPipelineConfig,
+    profile: Option<&DeviceProfile>,
+) -> anyhow::Result<ProcessorPipelineConfig> {
+    match profile {
+        Some(profile) => profile.apply_to(base),
+        None => Ok(base.clone()),
+    }
+}
+
+/// 逐层合并：对象按键递归，其余值（含数组）整体替换。
+fn merge_value(base: &mut Value, patch: &Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(key) {
+                    Some(slot) => merge_value(slot, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
+
+/// 保存设备档案，同一设备的旧档案被覆盖。
+pub async fn save_device_profile(db_path: &Path, profile: &DeviceProfile) -> anyhow::Result<()> {
+    let conn = db::connect(db_path).await?;
+    db::ensure_schema(&conn).await?;
+
+    let model = device_profiles::Model {
+        device_id: profile.device_id.clone(),
+        overrides: profile.overrides.to_string(),
+        axis_offset: profile
+            .axis_offset
+            .map(|offset| serde_json::to_string(&offset))
+            .transpose()
+            .context("serialize axis offset")?,
+        updated_at_ms: profile.updated_at_ms,
+    };
+    let columns = device_profiles::Column::iter()
+        .filter(|column| !matches!(column, device_profiles::Column::DeviceId));
+    device_profiles::Entity::insert(device_profiles::ActiveModel::from(model).reset_all())
+        .on_conflict(
+            OnConflict::column(device_profiles::Column::DeviceId)
+                .update_columns(columns)
+                .to_owned(),
+        )
+        .exec(&conn)
+        .await
+        .context("save device profile")?;
+    Ok(())
+}
+
+/// 查询设备档案，没有档案时返回 `None`。
+pub async fn get_device_profile(
+    db_path: &Path,
+    device_id: &str,
+) -> anyhow::Result<Option<DeviceProfile>> {
+    let conn = db::connect(db_path).await?;
+    db::ensure_schema(&conn).await?;
+
+    let Some(model) = device_profiles::Entity::find_by_id(device_id.to_string())
+        .one(&conn)
+        .await
+        .context("query device profile")?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(DeviceProfile {
+        device_id: model.device_id,
+        overrides: serde_json::from_str(&model.overrides).context("parse profile overrides")?,
+        axis_offset: model
+            .axis_offset
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .context("parse profile axis offset")?,
+        updated_at_ms: model.updated_at_ms,
+    }))
+}
+
+/// 删除设备档案，返回档案是否存在。
+pub async fn delete_device_profile(db_path: &Path, device_id: &str) -> anyhow::Result<bool> {
+    let conn = db::connect(db_path).await?;
+    db::ensure_schema(&conn).await?;
+
+    let result = device_profiles::Entity::delete_by_id(device_id.to_string())
+        .exec(&conn)
+        .await
+        .context("delete device profile")?;
+    Ok(result.rows_affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use math_f64::{DQuat, DVec3};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        processor::calibration::GyroUnit,
+        recorder::test_support::{remove_db, temp_db},
+    };
+
+    fn profile(overrides: Value) -> DeviceProfile {
+        DeviceProfile {
+            device_id: "imu-a".to_string(),
+            overrides,
+            axis_offset: None,
+            updated_at_ms: 1,
+        }
+    }
+
+    #[test]
+    fn profile_fields_override_base_and_the_rest_is_kept() {
+        let mut base = ProcessorPipelineConfig::default();
+        base.calibration.gyro_unit = GyroUnit::RadPerSec;
+        base.calibration.accel_bias = DVec3::new(0.1, 0.1, 0.1);
+        base.zupt.gyro_thresh *= 2.0;
+
+        let merged = profile(json!({
+            "calibration": { "accel_bias": { "x": 0.3, "y": -0.2, "z": 0.0 } }
+        }))
+        .apply_to(&base)
+        .unwrap();
+        assert_eq!(merged.calibration.accel_bias, DVec3::new(0.3, -0.2, 0.0));
+        // 档案没有写到的字段与其他段沿用基础配置
+        assert_eq!(merged.calibration.gyro_unit, GyroUnit::RadPerSec);
+        assert_eq!(merged.calibration.gyro_matrix, base.calibration.gyro_matrix);
+        assert_eq!(merged.zupt.gyro_thresh, base.zupt.gyro_thresh);
+
+        // 只允许标定段，合并结果仍要通过校验
+        let foreign = profile(json!({ "zupt": { "gyro_thresh": 1.0 } }));
+        assert!(foreign.apply_to(&base).is_err());
+        let invalid = profile(json!({ "calibration": { "accel_bias": { "x": 9.8 } } }));
+        assert!(invalid.apply_to(&base).is_err());
+    }
+
+    #[test]
+    fn capture_then_apply_reproduces_the_calibration_section() {
+        let mut tuned = ProcessorPipelineConfig::default();
+        tuned.calibration.gyro_bias = DVec3::new(0.01, -0.02, 0.03);
+        tuned.calibration.accel_matrix[0][0] = 1.02;
+        let offset = AxisCalibration {
+            angle_offset: DVec3::new(1.0, 2.0, 90.0),
+            quat_offset: DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+        };
+        let captured = DeviceProfile::capture("imu-a", &tuned, offset, 5).unwrap();
+        assert_eq!(
+            captured
+                .overrides
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["calibration"]
+        );
+
+        let applied = captured
+            .apply_to(&ProcessorPipelineConfig::default())
+            .unwrap();
+        assert_eq!(applied.calibration, tuned.calibration);
+        assert_eq!(captured.axis_offset, Some(offset));
+    }
+
+    #[tokio::test]
+    async fn device_without_profile_uses_the_base_config() {
+        let db_path = temp_db("device_profiles_missing");
+        let mut base = ProcessorPipelineConfig::default();
+        base.calibration.gyro_bias = DVec3::new(0.02, 0.0, 0.0);
+
+        let profile = get_device_profile(&db_path, "imu-new").await.unwrap();
+        assert_eq!(profile, None);
+        let config = config_for_device(&base, profile.as_ref()).unwrap();
+        assert_eq!(config.content_hash(), base.content_hash());
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn profiles_round_trip_through_the_database() {
+        let db_path = temp_db("device_profiles");
+        let mut config = ProcessorPipelineConfig::default();
+        config.calibration.accel_bias = DVec3::new(0.05, 0.0, -0.05);
+        let offset = AxisCalibration {
+            angle_offset: DVec3::new(0.0, 0.0, 73.74),
+            quat_offset: DQuat::from_xyzw(0.0, 0.0, 0.6, 0.8),
+        };
+        let saved = DeviceProfile::capture("imu-a", &config, offset, 10).unwrap();
+        save_device_profile(&db_path, &saved).await.unwrap();
+        assert_eq!(
+            get_device_profile(&db_path, "imu-a").await.unwrap(),
+            Some(saved.clone())
+        );
+
+        // 同一设备再次保存覆盖旧档案
+        let mut newer = saved.clone();
+        newer.axis_offset = None;
+        newer.updated_at_ms = 20;
+        save_device_profile(&db_path, &newer).await.unwrap();
+        assert_eq!(
+            get_device_profile(&db_path, "imu-a").await.unwrap(),
+            Some(newer)
+        );
+
+        assert_eq!(get_device_profile(&db_path, "imu-b").await.unwrap(), None);
+        assert!(delete_device_profile(&db_path, "imu-a").await.unwrap());
+        assert!(!delete_device_profile(&db_path, "imu-a").await.unwrap());
+        assert_eq!(get_device_profile(&db_path, "imu-a").await.unwrap(), None);
+        remove_db(&db_path);
+    }
+}
@@ -0,0 +1,161 @@
+//! 录制磁盘空间保护。
+//!
+//! 开始录制前检查目标卷剩余空间，录制过程中按间隔复查；低于阈值时录制线程
+//! 主动结束会话，避免磁盘写满后每 4 ms 一次插入失败刷屏。
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// 默认最小剩余空间（500 MB）。
+pub const DEFAULT_MIN_FREE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// 默认复查间隔。
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 单条样本落盘的估计字节数（约 36 个 REAL 列 + 行头 + 会话时间索引条目）。
+pub const ESTIMATED_SAMPLE_BYTES: u64 = 400;
+
+/// 深度录制每帧阶段中间值落盘的估计字节数（约 30 个 REAL 列 + 行头 + 索引条目）。
+pub const ESTIMATED_STAGE_SAMPLE_BYTES: u64 = 340;
+
+/// 剩余空间查询接口，测试中可注入假实现模拟磁盘写满。
+pub trait DiskSpaceProvider: Send + Sync {
+    /// 返回 `path` 所在卷的可用字节数，无法查询时返回 `None`（不阻止录制）。
+    fn available_bytes(&self, path: &Path) -> Option<u64>;
+}
+
+/// 查询操作系统的剩余空间。
+pub struct SystemDiskSpace;
+
+impl DiskSpaceProvider for SystemDiskSpace {
+    fn available_bytes(&self, path: &Path) -> Option<u64> {
+        // 数据库文件可能尚未创建：向上找到第一个存在的目录
+        let existing = path.ancestors().find(|p| p.exists())?;
+        statvfs_available(existing)
+    }
+}
+
+#[cfg(unix)]
+fn statvfs_available(path: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path 为合法的 NUL 结尾字符串，stat 为可写的本地变量。
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return None;
+    }
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn statvfs_available(_path: &Path) -> Option<u64> {
+    // 非 Unix 平台暂不支持查询，退化为不检查
+    None
+}
+
+/// 录制会话的磁盘空间检查器。
+pub struct DiskGuard {
+    min_free_bytes: u64,
+    check_interval: Duration,
+    last_check: Option<Instant>,
+    last_available: Option<u64>,
+}
+
+impl DiskGuard {
+    /// 创建检查器。
+    pub fn new(min_free_bytes: u64, check_interval: Duration) -> Self {
+        Self {
+            min_free_bytes,
+            check_interval,
+            last_check: None,
+            last_available: None,
+        }
+    }
+
+    /// 立即检查一次，剩余空间不足时返回可用字节数。
+    pub fn check_now(&mut self, provider: &dyn DiskSpaceProvider, path: &Path) -> Result<(), u64> {
+        self.last_check = Some(Instant::now());
+        self.last_available = provider.available_bytes(path);
+        match self.last_available {
+            Some(available) if available < self.min_free_bytes => Err(available),
+            _ => Ok(()),
+        }
+    }
+
+    /// 距上次检查超过间隔时复查，否则直接通过。
+    pub fn poll(&mut self, provider: &dyn DiskSpaceProvider, path: &Path) -> Result<(), u64> {
+        match self.last_check {
+            Some(last) if last.elapsed() < self.check_interval => Ok(()),
+            _ => self.check_now(provider, path),
+        }
+    }
+
+    /// 最近一次查询到的可用字节数。
+    pub fn last_available(&self) -> Option<u64> {
+        self.last_available
+    }
+
+    /// 最小剩余空间阈值。
+    pub fn min_free_bytes(&self) -> u64 {
+        self.min_free_bytes
+    }
+}
+
+/// 按已写入样本数与录制时长估计每分钟写入字节数，时长不足 1 秒时返回 `None`。
+pub fn estimate_bytes_per_minute(sample_count: u64, elapsed_ms: i64) -> Option<f64> {
+    per_minute(sample_count, ESTIMATED_SAMPLE_BYTES, elapsed_ms)
+}
+
+/// 深度录制阶段中间值的每分钟写入字节数估计，规则同 [`estimate_bytes_per_minute`]。
+pub fn estimate_stage_bytes_per_minute(sample_count: u64, elapsed_ms: i64) -> Option<f64> {
+    per_minute(sample_count, ESTIMATED_STAGE_SAMPLE_BYTES, elapsed_ms)
+}
+
+fn per_minute(rows: u64, row_bytes: u64, elapsed_ms: i64) -> Option<f64> {
+    if elapsed_ms < 1000 {
+        return None;
+    }
+    let bytes = rows.saturating_mul(row_bytes) as f64;
+    Some(bytes * 60_000.0 / elapsed_ms as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(u64);
+
+    impl DiskSpaceProvider for Fixed {
+        fn available_bytes(&self, _path: &Path) -> Option<u64> {
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    fn guard_rechecks_only_after_interval() {
+        let path = Path::new("unused");
+        let mut guard = DiskGuard::new(100, Duration::from_secs(3600));
+        assert_eq!(guard.poll(&Fixed(200), path), Ok(()));
+        // 间隔未到，不会重新查询
+        assert_eq!(guard.poll(&Fixed(50), path), Ok(()));
+        assert_eq!(guard.last_available(), Some(200));
+        assert_eq!(guard.check_now(&Fixed(50), path), Err(50));
+
+        assert!(
+            SystemDiskSpace
+                .available_bytes(&std::env::temp_dir().join("missing/db.sqlite"))
+                .is_some_and(|bytes| bytes > 0)
+                || cfg!(not(unix))
+        );
+        assert_eq!(estimate_bytes_per_minute(250, 1000), Some(6_000_000.0));
+        assert_eq!(estimate_bytes_per_minute(10, 500), None);
+        assert_eq!(
+            estimate_stage_bytes_per_minute(250, 1000),
+            Some(5_100_000.0)
+        );
+    }
+}
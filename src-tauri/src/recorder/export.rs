@@ -0,0 +1,316 @@
+//! 导出进度与取消。
+//!
+//! 长录制导出（数十万行）要跑数秒到数十秒。CSV 与 Parquet 导出都按批分页查询、逐批写出
+//! 到 `.partial` 临时文件，完成后再改名为目标文件。每批之后：
+//!
+//! 1. 检查取消标志，已取消时删除临时文件并返回 [`ExportError::Cancelled`]；
+//! 2. 通过 [`ExportOptions::on_progress`] 报告进度。两次报告至少间隔 `progress_interval`，
+//!    最后一批总会报告，前端据此显示 100%。
+//!
+//! 同一会话同时只允许一个导出：[`ExportRegistry::begin`] 登记会话并给出取消标志，
+//! 返回的 [`ExportGuard`] 释放时注销；`cancel_export` 命令经注册表置位取消标志。
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{types::error::ErrorCode, units::UnitConverter};
+
+/// CSV 导出每批查询的样本行数。
+pub const DEFAULT_EXPORT_BATCH_ROWS: usize = 5000;
+
+/// 两次进度报告的最小间隔。
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `export_progress` 事件载荷。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ExportProgress {
+    /// 导出的会话 ID。
+    pub session_id: i64,
+    /// 已写出的样本行数。
+    pub rows_done: u64,
+    /// 导出开始时统计的样本总行数。
+    pub rows_total: u64,
+    /// 已写出的字节数（Parquet 为已落盘的编码字节数）。
+    pub bytes_written: u64,
+}
+
+/// 导出错误，失败响应据此附带 [`ErrorCode`]。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExportError {
+    /// 该会话已有导出在进行。
+    #[error("会话 {0} 正在导出")]
+    InProgress(i64),
+    /// 导出被取消，未完成的文件已删除。
+    #[error("导出已取消")]
+    Cancelled,
+}
+
+impl ExportError {
+    /// 对应的 IPC 错误码。
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ExportError::InProgress(_) => ErrorCode::ExportInProgress,
+            ExportError::Cancelled => ErrorCode::ExportCancelled,
+        }
+    }
+}
+
+/// 导出选项：分批大小、附加列、导出单位、进度回调与取消标志。
+pub struct ExportOptions {
+    /// CSV 每批查询的样本行数（Parquet 按行组分批）。
+    pub batch_rows: usize,
+    /// 两次进度报告的最小间隔。
+    pub progress_interval: Duration,
+    /// CSV 末尾追加 `flags` 列，列出覆盖每个样本的数据质量标记（Parquet 忽略）。
+    pub include_flags: bool,
+    /// 位置与速度列的导出单位，默认国际单位。
+    pub units: UnitConverter,
+    /// 进度回调（如推送 `export_progress` 事件）。
+    pub on_progress: Option<Box<dyn FnMut(ExportProgress) + Send>>,
+    /// 取消标志，每批之间检查。
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            batch_rows: DEFAULT_EXPORT_BATCH_ROWS,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            include_flags: false,
+            units: UnitConverter::default(),
+            on_progress: None,
+            cancel: None,
+        }
+    }
+}
+
+/// 单次导出的进度跟踪：累计行数与字节数，按间隔限频报告。
+pub(super) struct ExportTracker {
+    progress: ExportProgress,
+    options: ExportOptions,
+    last_report: Option<Instant>,
+    /// 最近一次报告的行数，避免结束时重复报告。
+    reported_rows: Option<u64>,
+}
+
+impl ExportTracker {
+    pub(super) fn new(session_id: i64, rows_total: u64, options: ExportOptions) -> Self {
+        Self {
+            progress: ExportProgress {
+                session_id,
+                rows_done: 0,
+                rows_total,
+                bytes_written: 0,
+            },
+            options,
+            last_report: None,
+            reported_rows: None,
+        }
+    }
+
+    /// 已被取消时返回 [`ExportError::Cancelled`]。
+    pub(super) fn ensure_not_cancelled(&self) -> Result<(), ExportError> {
+        match &self.options.cancel {
+            Some(cancel) if cancel.load(Ordering::Relaxed) => Err(ExportError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// 记录一批写出：`rows` 为本批行数，`bytes_written` 为累计字节数。
+    pub(super) fn advance(&mut self, rows: u64, bytes_written: u64) {
+        self.progress.rows_done += rows;
+        self.progress.bytes_written = bytes_written;
+        let due = self
+            .last_report
+            .is_none_or(|last| last.elapsed() >= self.options.progress_interval);
+        if due || self.progress.rows_done == self.progress.rows_total {
+            self.report();
+        }
+    }
+
+    /// 导出完成：最终进度尚未报告时补报一次，返回写出的行数。
+    pub(super) fn finish(&mut self, bytes_written: u64) -> u64 {
+        self.progress.bytes_written = bytes_written;
+        if self.reported_rows != Some(self.progress.rows_done) {
+            self.report();
+        }
+        self.progress.rows_done
+    }
+
+    fn report(&mut self) {
+        if let Some(on_progress) = self.options.on_progress.as_mut() {
+            on_progress(self.progress);
+        }
+        self.last_report = Some(Instant::now());
+        self.reported_rows = Some(self.progress.rows_done);
+    }
+}
+
+/// 导出临时文件路径：目标文件名后追加 `.partial`。
+pub(super) fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    path.with_file_name(name)
+}
+
+/// 进行中的导出登记表，跨命令共享。
+#[derive(Debug, Clone, Default)]
+pub struct ExportRegistry {
+    active: Arc<Mutex<HashMap<i64, Arc<AtomicBool>>>>,
+}
+
+impl ExportRegistry {
+    /// 登记会话导出；该会话已有导出在进行时返回 [`ExportError::InProgress`]。
+    pub fn begin(&self, session_id: i64) -> Result<ExportGuard, ExportError> {
+        let mut active = self.lock();
+        if active.contains_key(&session_id) {
+            return Err(ExportError::InProgress(session_id));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        active.insert(session_id, cancel.clone());
+        Ok(ExportGuard {
+            registry: self.clone(),
+            session_id,
+            cancel,
+        })
+    }
+
+    /// 请求取消会话的导出，返回是否有导出在进行。
+    pub fn cancel(&self, session_id: i64) -> bool {
+        match self.lock().get(&session_id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<i64, Arc<AtomicBool>>> {
+        self.active
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 导出登记守卫，释放时注销会话。
+#[derive(Debug)]
+pub struct ExportGuard {
+    registry: ExportRegistry,
+    session_id: i64,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ExportGuard {
+    /// 本次导出的取消标志，传给 [`ExportOptions::cancel`]。
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancel.clone()
+    }
+}
+
+impl Drop for ExportGuard {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording_tracker(
+        rows_total: u64,
+        progress_interval: Duration,
+    ) -> (ExportTracker, Arc<Mutex<Vec<ExportProgress>>>) {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let tracker = ExportTracker::new(
+            7,
+            rows_total,
+            ExportOptions {
+                progress_interval,
+                on_progress: Some(Box::new(move |p| sink.lock().unwrap().push(p))),
+                ..ExportOptions::default()
+            },
+        );
+        (tracker, reports)
+    }
+
+    #[test]
+    fn second_export_of_the_same_session_is_rejected_until_the_first_ends() {
+        let registry = ExportRegistry::default();
+        let guard = registry.begin(1).unwrap();
+        assert_eq!(registry.begin(1).unwrap_err(), ExportError::InProgress(1));
+        // 其他会话不受影响
+        let other = registry.begin(2).unwrap();
+
+        assert!(registry.cancel(1));
+        assert!(guard.cancel_flag().load(Ordering::Relaxed));
+        assert!(!other.cancel_flag().load(Ordering::Relaxed));
+
+        drop(guard);
+        assert!(!registry.cancel(1));
+        assert!(!registry
+            .begin(1)
+            .unwrap()
+            .cancel_flag()
+            .load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn progress_is_throttled_but_the_last_batch_is_always_reported() {
+        let (mut tracker, reports) = recording_tracker(300, Duration::from_secs(3600));
+        tracker.advance(100, 10);
+        tracker.advance(100, 20);
+        tracker.advance(100, 30);
+        assert_eq!(tracker.finish(30), 300);
+
+        let rows: Vec<u64> = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|p| p.rows_done)
+            .collect();
+        assert_eq!(rows, vec![100, 300]);
+    }
+
+    #[test]
+    fn finish_reports_rows_beyond_the_initial_count_once() {
+        let (mut tracker, reports) = recording_tracker(0, Duration::ZERO);
+        assert_eq!(tracker.finish(0), 0);
+        assert_eq!(tracker.finish(0), 0);
+        assert_eq!(reports.lock().unwrap().len(), 1);
+
+        let (mut tracker, reports) = recording_tracker(1, Duration::from_secs(3600));
+        tracker.advance(1, 5);
+        tracker.advance(1, 9);
+        assert_eq!(tracker.finish(9), 2);
+        let last = *reports.lock().unwrap().last().unwrap();
+        assert_eq!((last.rows_done, last.bytes_written), (2, 9));
+    }
+
+    #[test]
+    fn cancel_flag_is_observed() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let tracker = ExportTracker::new(
+            1,
+            10,
+            ExportOptions {
+                cancel: Some(cancel.clone()),
+                ..ExportOptions::default()
+            },
+        );
+        assert_eq!(tracker.ensure_not_cancelled(), Ok(()));
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(tracker.ensure_not_cancelled(), Err(ExportError::Cancelled));
+    }
+}
@@ -0,0 +1,162 @@
+//! 导出文件名清洗。
+//!
+//! 导出文件以会话名称命名，而名称由用户随意输入（如 `test: 3/5 "final"`）。
+//! 这里把名称转换成在 Windows、macOS 与 Linux 上都合法的文件名：
+//!
+//! 1. 路径分隔符与 Windows 保留字符（`<>:"/\|?*`）及控制字符替换为 `_`；
+//! 2. 去掉首尾空白与结尾的 `.`（Windows 会静默丢弃结尾的点和空格）；
+//! 3. 按 UTF-8 字节截断到 [`MAX_FILENAME_STEM_BYTES`]，不切断多字节字符；
+//! 4. 避开 Windows 设备名（`CON`、`NUL`、`COM1` 等，带扩展名同样保留），在设备名后插入 `_`；
+//! 5. 结果为空时退回 `session_<id>`。
+//!
+//! 目标目录中已有同名文件时，[`unique_path`] 追加 ` (n)` 后缀，不覆盖旧导出。
+
+use std::path::{Path, PathBuf};
+
+/// 清洗后文件名主干的最大字节数，给前缀、序号后缀与扩展名留出余量（常见上限 255 字节）。
+pub const MAX_FILENAME_STEM_BYTES: usize = 120;
+
+/// Windows 保留的设备名，不区分大小写，带扩展名同样保留。
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 把会话名称清洗为跨平台安全的文件名主干（不含扩展名），保证非空。
+pub fn sanitize_filename(name: &str, session_id: i64) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let mut stem = trim_edges(&replaced).to_string();
+    if stem.len() > MAX_FILENAME_STEM_BYTES {
+        let mut end = MAX_FILENAME_STEM_BYTES;
+        while !stem.is_char_boundary(end) {
+            end -= 1;
+        }
+        stem.truncate(end);
+        stem = trim_edges(&stem).to_string();
+    }
+    if stem.is_empty() {
+        return format!("session_{session_id}");
+    }
+    if let Some(base_len) = reserved_base_len(&stem) {
+        stem.insert(base_len, '_');
+    }
+    stem
+}
+
+/// 在 `dir` 中为 `stem.extension` 找一个尚不存在的路径，已存在时依次尝试 `stem (2)`、`stem (3)`……
+pub fn unique_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let candidate = dir.join(format!("{stem}.{extension}"));
+    if !candidate.exists() {
+        return candidate;
+    }
+    (2..)
+        .map(|n| dir.join(format!("{stem} ({n}).{extension}")))
+        .find(|path| !path.exists())
+        .expect("unbounded suffix range")
+}
+
+/// 去掉首尾空白与结尾的点。
+fn trim_edges(name: &str) -> &str {
+    name.trim()
+        .trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+}
+
+/// 主干（第一个 `.` 之前的部分，去掉结尾空白）为 Windows 设备名时返回其字节长度。
+fn reserved_base_len(stem: &str) -> Option<usize> {
+    let base = stem.split('.').next().unwrap_or(stem).trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| base.eq_ignore_ascii_case(reserved))
+        .then_some(base.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_characters_are_replaced() {
+        assert_eq!(
+            sanitize_filename(r#"test: 3/5 "final""#, 1),
+            "test_ 3_5 _final_"
+        );
+        assert_eq!(sanitize_filename("a\\b|c?d*e<f>g", 1), "a_b_c_d_e_f_g");
+        assert_eq!(sanitize_filename("tab\there\nnew", 1), "tab_here_new");
+    }
+
+    #[test]
+    fn windows_device_names_are_avoided() {
+        assert_eq!(sanitize_filename("CON", 1), "CON_");
+        assert_eq!(sanitize_filename("nul", 1), "nul_");
+        assert_eq!(sanitize_filename("Com3", 1), "Com3_");
+        assert_eq!(sanitize_filename("lpt1.backup", 1), "lpt1_.backup");
+        assert_eq!(sanitize_filename("aux .txt", 1), "aux_ .txt");
+        // 只有完整匹配才算设备名
+        assert_eq!(sanitize_filename("console", 1), "console");
+        assert_eq!(sanitize_filename("COM10", 1), "COM10");
+    }
+
+    #[test]
+    fn trailing_dots_and_spaces_are_trimmed() {
+        assert_eq!(sanitize_filename("  walk. . ", 1), "walk");
+        assert_eq!(sanitize_filename("v1.2", 1), "v1.2");
+        assert_eq!(sanitize_filename("...", 7), "session_7");
+        assert_eq!(sanitize_filename("", 8), "session_8");
+        assert_eq!(sanitize_filename("   ", 9), "session_9");
+    }
+
+    #[test]
+    fn unicode_is_kept_and_truncated_on_char_boundaries() {
+        assert_eq!(sanitize_filename("空中书写 ✍️ 测试", 1), "空中书写 ✍️ 测试");
+
+        // 3 字节汉字：120 字节正好 40 个字
+        let long = "写".repeat(100);
+        let stem = sanitize_filename(&long, 1);
+        assert_eq!(stem, "写".repeat(40));
+
+        // 截断点落在多字节字符中间时退回到前一个字符边界
+        let mixed = format!("a{}", "写".repeat(100));
+        let stem = sanitize_filename(&mixed, 1);
+        assert!(stem.len() <= MAX_FILENAME_STEM_BYTES);
+        assert_eq!(stem.len(), 1 + 39 * 3);
+
+        // 截断后露出的结尾点与空格同样去掉
+        let padded = format!("{}. tail", "x".repeat(MAX_FILENAME_STEM_BYTES - 1));
+        assert_eq!(
+            sanitize_filename(&padded, 1),
+            "x".repeat(MAX_FILENAME_STEM_BYTES - 1)
+        );
+    }
+
+    #[test]
+    fn collisions_get_numeric_suffixes() {
+        let dir =
+            std::env::temp_dir().join(format!("imu_vis_filename_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = unique_path(&dir, "walk", "csv");
+        assert_eq!(first, dir.join("walk.csv"));
+        std::fs::write(&first, "").unwrap();
+        let second = unique_path(&dir, "walk", "csv");
+        assert_eq!(second, dir.join("walk (2).csv"));
+        std::fs::write(&second, "").unwrap();
+        assert_eq!(unique_path(&dir, "walk", "csv"), dir.join("walk (3).csv"));
+        // 扩展名不同不算冲突
+        assert_eq!(
+            unique_path(&dir, "walk", "parquet"),
+            dir.join("walk.parquet")
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+//! 数据质量标记区间：校验与 CSV 导出时的区间扫描。
+//!
+//! 审阅者在回放中圈出有问题的时段（"传感器松动" 等），标记按设备时间戳存为闭区间，
+//! 区间之间允许重叠。导出 CSV 时样本按时间戳升序流过 [`FlagSweep`]：标记按起点排序后
+//! 依次进入活动集合，终点早于当前样本的移出，每个样本只看活动集合，不必逐行扫描全部标记。
+
+use anyhow::ensure;
+
+use crate::types::recording::RecordingFlag;
+
+/// 校验标记区间：起点不晚于终点，且落在会话样本的时间范围 `[first_ms, last_ms]` 内。
+pub(super) fn check_flag_range(
+    from_ms: i64,
+    to_ms: i64,
+    first_ms: i64,
+    last_ms: i64,
+) -> anyhow::Result<()> {
+    ensure!(
+        from_ms <= to_ms,
+        "flag range starts after it ends: {from_ms} > {to_ms}"
+    );
+    ensure!(
+        from_ms >= first_ms && to_ms <= last_ms,
+        "flag range {from_ms}..={to_ms} ms is outside the session ({first_ms}..={last_ms} ms)"
+    );
+    Ok(())
+}
+
+/// 规整标记文本：去掉首尾空白，不能为空，不能换行（导出时一行一个样本）。
+pub(super) fn normalize_flag_label(label: &str) -> anyhow::Result<String> {
+    let label = label.trim();
+    ensure!(!label.is_empty(), "flag label is empty");
+    ensure!(
+        !label.contains(['\n', '\r']),
+        "flag label must be a single line"
+    );
+    Ok(label.to_string())
+}
+
+/// 按样本时间戳扫描标记区间，给出每个样本的 `flags` 列。
+pub(super) struct FlagSweep {
+    /// 按起点升序（同起点按 ID）排列的标记。
+    pending: std::vec::IntoIter<RecordingFlag>,
+    /// 下一个尚未进入活动集合的标记。
+    next: Option<RecordingFlag>,
+    /// 覆盖上一个样本的标记，保持起点顺序。
+    active: Vec<RecordingFlag>,
+    /// 活动集合中最早的终点，样本越过它时才需要移出。
+    next_expiry: i64,
+    /// 活动集合对应的 CSV 字段，集合变化时重建。
+    field: String,
+}
+
+impl FlagSweep {
+    /// 由会话的全部标记构造，顺序不限。
+    pub(super) fn new(mut flags: Vec<RecordingFlag>) -> Self {
+        flags.sort_by_key(|flag| (flag.from_ms, flag.id));
+        let mut pending = flags.into_iter();
+        let next = pending.next();
+        Self {
+            pending,
+            next,
+            active: Vec::new(),
+            next_expiry: i64::MAX,
+            field: String::new(),
+        }
+    }
+
+    /// 覆盖 `timestamp_ms` 的标记文本，以 `; ` 分隔并按 CSV 规则转义；无标记时为空。
+    ///
+    /// 时间戳须不减（导出按时间戳升序查询样本）。
+    pub(super) fn field_at(&mut self, timestamp_ms: i64) -> &str {
+        let mut changed = false;
+        if timestamp_ms > self.next_expiry {
+            self.active.retain(|flag| flag.to_ms >= timestamp_ms);
+            changed = true;
+        }
+        while let Some(flag) = self.next.take_if(|flag| flag.from_ms <= timestamp_ms) {
+            // 在当前样本之前已经结束的区间（落在两个样本之间的短标记）不覆盖任何样本
+            if flag.to_ms >= timestamp_ms {
+                self.active.push(flag);
+                changed = true;
+            }
+            self.next = self.pending.next();
+        }
+        if changed {
+            self.next_expiry = self
+                .active
+                .iter()
+                .map(|flag| flag.to_ms)
+                .min()
+                .unwrap_or(i64::MAX);
+            self.field = csv_field(self.active.iter().map(|flag| flag.label.as_str()));
+        }
+        &self.field
+    }
+}
+
+/// 把多个标记文本拼成一个 CSV 字段，含逗号或引号时加引号。
+fn csv_field<'a>(labels: impl Iterator<Item = &'a str>) -> String {
+    let joined = labels.collect::<Vec<_>>().join("; ");
+    if joined.contains([',', '"']) {
+        format!("\"{}\"", joined.replace('"', "\"\""))
+    } else {
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(id: i64, from_ms: i64, to_ms: i64, label: &str) -> RecordingFlag {
+        RecordingFlag {
+            id,
+            session_id: 1,
+            from_ms,
+            to_ms,
+            label: label.to_string(),
+            created_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn sweep_reports_overlapping_flags_per_sample() {
+        let mut sweep = FlagSweep::new(vec![
+            flag(3, 30, 30, "tap"),
+            flag(1, 10, 40, "slipped"),
+            flag(2, 20, 50, "bumped, re-taped"),
+            flag(4, 62, 64, "between samples"),
+            flag(5, 80, 90, "say \"hi\""),
+        ]);
+        let fields: Vec<(i64, String)> = [0, 10, 20, 30, 40, 50, 60, 70, 80, 100]
+            .into_iter()
+            .map(|ts| (ts, sweep.field_at(ts).to_string()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (0, String::new()),
+                (10, "slipped".into()),
+                (20, "\"slipped; bumped, re-taped\"".into()),
+                (30, "\"slipped; bumped, re-taped; tap\"".into()),
+                (40, "\"slipped; bumped, re-taped\"".into()),
+                (50, "\"bumped, re-taped\"".into()),
+                (60, String::new()),
+                (70, String::new()),
+                (80, "\"say \"\"hi\"\"\"".into()),
+                (100, String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn range_and_label_validation() {
+        assert!(check_flag_range(10, 20, 0, 100).is_ok());
+        assert!(check_flag_range(0, 100, 0, 100).is_ok());
+        assert!(check_flag_range(20, 10, 0, 100).is_err());
+        assert!(check_flag_range(-1, 10, 0, 100).is_err());
+        assert!(check_flag_range(10, 101, 0, 100).is_err());
+
+        assert_eq!(normalize_flag_label("  slipped ").unwrap(), "slipped");
+        assert!(normalize_flag_label("   ").is_err());
+        assert!(normalize_flag_label("a\nb").is_err());
+    }
+}
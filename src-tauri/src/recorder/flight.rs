@@ -0,0 +1,150 @@
+//! 飞行记录仪：常驻内存的最近 N 秒输出帧。
+//!
+//! 录制线程把每一帧复制进预分配的环形缓冲区，不论当前是否在录制；反馈问题时
+//! 通过 `dump_flight_recorder` 把缓冲区快照写成普通录制会话，不必事先开始录制。
+//! 缓冲区在创建（或上报频率变化）时一次性分配，逐帧写入只淘汰最旧的帧，不再分配。
+
+use crate::processor::{
+    output::OutputFrame,
+    shared::{frames_for_window, nominal_sample_interval_ms, BoundedBuffer, BufferRegistry},
+};
+
+/// 默认保留时长（秒）。
+pub const DEFAULT_FLIGHT_RECORDER_SECS: f64 = 30.0;
+
+/// 飞行记录仪导出会话的标签。
+pub const FLIGHT_RECORDER_TAG: &str = "flight_recorder";
+
+/// 按保留时长与上报频率换算环形缓冲区容量（帧），时长不为正时返回 0（关闭）。
+pub fn flight_recorder_capacity(secs: f64, report_rate: u8) -> usize {
+    if !secs.is_finite() || secs <= 0.0 {
+        return 0;
+    }
+    frames_for_window(secs * 1000.0, nominal_sample_interval_ms(report_rate)) as usize
+}
+
+/// 定长环形缓冲区，满后覆盖最旧的帧，登记在全局缓冲区登记表中。
+pub struct FlightRecorder {
+    frames: BoundedBuffer<OutputFrame>,
+}
+
+impl FlightRecorder {
+    /// 创建容量为 `capacity` 帧的缓冲区，容量为 0 时不记录。
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: BoundedBuffer::new(capacity)
+                .register(BufferRegistry::global(), "flight_recorder"),
+        }
+    }
+
+    /// 是否启用。
+    pub fn enabled(&self) -> bool {
+        self.capacity() > 0
+    }
+
+    /// 容量（帧）。
+    pub fn capacity(&self) -> usize {
+        self.frames.max_items()
+    }
+
+    /// 已保留的帧数。
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 是否尚无帧。
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 写入一帧，缓冲区已满时覆盖最旧的帧。
+    pub fn push(&mut self, frame: &OutputFrame) {
+        self.frames.push(*frame);
+    }
+
+    /// 按时间先后迭代已保留的帧。
+    pub fn iter(&self) -> impl Iterator<Item = &OutputFrame> {
+        self.frames.iter()
+    }
+
+    /// 调整容量，保留最新的帧（上报频率变化时调用）。
+    pub fn resize(&mut self, capacity: usize) {
+        self.frames.set_max_items(capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::{navigator::NavState, parser::ImuSampleRaw, shared::DeviceTimeMs};
+    use math_f64::{DQuat, DVec3};
+
+    fn frame(timestamp_ms: u64) -> OutputFrame {
+        OutputFrame {
+            raw: ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(timestamp_ms),
+                accel_no_g: DVec3::ZERO,
+                accel_with_g: DVec3::ZERO,
+                gyro: DVec3::ZERO,
+                quat: DQuat::IDENTITY,
+                angle: DVec3::ZERO,
+                offset: DVec3::ZERO,
+                accel_nav: DVec3::ZERO,
+                baro: None,
+            },
+            nav: NavState {
+                timestamp_ms,
+                position: DVec3::ZERO,
+                velocity: DVec3::ZERO,
+                attitude: DQuat::IDENTITY,
+            },
+            motion_state: Default::default(),
+            zupt: Default::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.0,
+            clock_sync: None,
+            heading_yaw_deg: None,
+            baro_relative_altitude_m: None,
+            debug_vectors: None,
+            stages: None,
+        }
+    }
+
+    fn timestamps(recorder: &FlightRecorder) -> Vec<u64> {
+        recorder
+            .iter()
+            .map(|f| f.raw.timestamp_ms.as_millis())
+            .collect()
+    }
+
+    #[test]
+    fn ring_keeps_newest_frames_in_order_and_resizes() {
+        assert_eq!(flight_recorder_capacity(30.0, 250), 7500);
+        assert_eq!(flight_recorder_capacity(2.0, 100), 200);
+        assert_eq!(flight_recorder_capacity(0.0, 250), 0);
+
+        let mut recorder = FlightRecorder::new(4);
+        for ts in 0..3 {
+            recorder.push(&frame(ts));
+        }
+        assert_eq!(timestamps(&recorder), [0, 1, 2]);
+        for ts in 3..10 {
+            recorder.push(&frame(ts));
+        }
+        assert_eq!(timestamps(&recorder), [6, 7, 8, 9]);
+
+        recorder.resize(2);
+        assert_eq!(timestamps(&recorder), [8, 9]);
+        recorder.resize(3);
+        recorder.push(&frame(10));
+        recorder.push(&frame(11));
+        assert_eq!(timestamps(&recorder), [9, 10, 11]);
+
+        let mut disabled = FlightRecorder::new(0);
+        disabled.push(&frame(0));
+        assert!(!disabled.enabled());
+        assert!(disabled.is_empty());
+    }
+}
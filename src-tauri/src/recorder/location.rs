@@ -0,0 +1,253 @@
+//! 录制数据库存放位置。
+//!
+//! 解析顺序：
+//! 1. 用户通过 `set_recording_directory` 持久化的目录；
+//! 2. 应用数据目录（Tauri `app_data_dir`）；
+//! 3. 工作目录（旧行为，见 [`db::legacy_recording_dir`]）。
+//!
+//! 切换目录不会迁移已有数据库，旧录制仍留在原目录。
+//!
+//! 设置文件还可通过 `min_free_mb` 调整录制所需的最小剩余空间（默认 500 MB），
+//! 通过 `flight_recorder_secs` 调整飞行记录仪保留时长（默认 30 s，0 表示关闭）。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::recorder::{db, disk::DEFAULT_MIN_FREE_BYTES, flight::DEFAULT_FLIGHT_RECORDER_SECS};
+
+/// 录制数据库文件名。
+pub const RECORDING_DB_FILE: &str = "imu_recordings.sqlite";
+
+/// 持久化设置文件名（位于应用配置目录）。
+const SETTINGS_FILE: &str = "recording.toml";
+
+/// 可写性检查时创建的临时文件名。
+const WRITE_PROBE_FILE: &str = ".imu_vis_write_probe";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct RecordingSettings {
+    directory: Option<PathBuf>,
+    #[serde(default)]
+    min_free_mb: Option<u64>,
+    #[serde(default)]
+    flight_recorder_secs: Option<f64>,
+}
+
+/// 按优先级选出录制目录，全部缺失时退回当前目录。
+pub fn resolve_directory(
+    persisted: Option<PathBuf>,
+    app_data_dir: Option<PathBuf>,
+    legacy_dir: Option<PathBuf>,
+) -> PathBuf {
+    persisted
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .or(app_data_dir)
+        .or(legacy_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// 确认目录存在且可写：必要时创建目录，再写入并删除一个临时文件。
+pub fn ensure_writable(dir: &Path) -> anyhow::Result<()> {
+    if dir.exists() && !dir.is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("create recording directory {}", dir.display()))?;
+    let probe = dir.join(WRITE_PROBE_FILE);
+    std::fs::write(&probe, b"probe")
+        .with_context(|| format!("recording directory {} is not writable", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// 录制目录状态，由 `AppState` 持有，所有录制入口都通过它解析数据库路径。
+pub struct RecordingLocation {
+    directory: RwLock<PathBuf>,
+    default_directory: PathBuf,
+    settings_path: Option<PathBuf>,
+    min_free_mb: Option<u64>,
+    flight_recorder_secs: Option<f64>,
+}
+
+impl RecordingLocation {
+    /// 读取持久化设置并解析当前目录。
+    ///
+    /// * `config_dir`: 设置文件所在目录，`None` 时不持久化
+    /// * `app_data_dir`: 默认录制目录
+    pub fn load(config_dir: Option<PathBuf>, app_data_dir: Option<PathBuf>) -> Self {
+        let settings_path = config_dir.map(|dir| dir.join(SETTINGS_FILE));
+        let settings = settings_path
+            .as_deref()
+            .map(|path| {
+                read_settings(path).unwrap_or_else(|error| {
+                    tracing::warn!("读取录制目录设置失败，使用默认目录: {error:#}");
+                    RecordingSettings::default()
+                })
+            })
+            .unwrap_or_default();
+        let persisted = settings.directory;
+        let default_directory =
+            resolve_directory(None, app_data_dir, db::legacy_recording_dir().ok());
+        let directory = resolve_directory(persisted, Some(default_directory.clone()), None);
+        tracing::info!("录制目录: {}", directory.display());
+        Self {
+            directory: RwLock::new(directory),
+            default_directory,
+            settings_path,
+            min_free_mb: settings.min_free_mb,
+            flight_recorder_secs: settings.flight_recorder_secs,
+        }
+    }
+
+    /// 录制所需的最小剩余空间（字节）。
+    pub fn min_free_bytes(&self) -> u64 {
+        self.min_free_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+            .unwrap_or(DEFAULT_MIN_FREE_BYTES)
+    }
+
+    /// 飞行记录仪保留时长（秒），不为正时关闭。
+    pub fn flight_recorder_secs(&self) -> f64 {
+        self.flight_recorder_secs
+            .unwrap_or(DEFAULT_FLIGHT_RECORDER_SECS)
+    }
+
+    /// 当前录制目录。
+    pub fn directory(&self) -> PathBuf {
+        self.directory
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 未设置自定义目录时使用的默认目录。
+    pub fn default_directory(&self) -> &Path {
+        &self.default_directory
+    }
+
+    /// 当前录制数据库路径。
+    pub fn db_path(&self) -> PathBuf {
+        self.directory().join(RECORDING_DB_FILE)
+    }
+
+    /// 校验并切换录制目录，同时写入设置文件。
+    pub fn set_directory(&self, directory: PathBuf) -> anyhow::Result<PathBuf> {
+        ensure_writable(&directory)?;
+        let directory = directory.canonicalize().unwrap_or(directory);
+        if let Some(path) = &self.settings_path {
+            write_settings(
+                path,
+                &RecordingSettings {
+                    directory: Some(directory.clone()),
+                    min_free_mb: self.min_free_mb,
+                    flight_recorder_secs: self.flight_recorder_secs,
+                },
+            )?;
+        }
+        *self
+            .directory
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = directory.clone();
+        tracing::info!("录制目录已切换: {}", directory.display());
+        Ok(directory)
+    }
+}
+
+fn read_settings(path: &Path) -> anyhow::Result<RecordingSettings> {
+    if !path.exists() {
+        return Ok(RecordingSettings::default());
+    }
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("parse {}", path.display()))
+}
+
+fn write_settings(path: &Path, settings: &RecordingSettings) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create settings directory")?;
+    }
+    let content = toml::to_string_pretty(settings).context("serialize recording settings")?;
+    std::fs::write(path, content).with_context(|| format!("write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("imu_vis_location_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn resolution_prefers_persisted_then_app_data_then_legacy() {
+        let persisted = PathBuf::from("/data/custom");
+        let app_data = PathBuf::from("/data/app");
+        let legacy = PathBuf::from("/work");
+
+        assert_eq!(
+            resolve_directory(
+                Some(persisted.clone()),
+                Some(app_data.clone()),
+                Some(legacy.clone())
+            ),
+            persisted
+        );
+        assert_eq!(
+            resolve_directory(
+                Some(PathBuf::new()),
+                Some(app_data.clone()),
+                Some(legacy.clone())
+            ),
+            app_data
+        );
+        assert_eq!(resolve_directory(None, None, Some(legacy.clone())), legacy);
+        assert_eq!(resolve_directory(None, None, None), PathBuf::from("."));
+    }
+
+    #[test]
+    fn set_directory_persists_and_reloads() {
+        let root = temp_dir("persist");
+        let config_dir = root.join("config");
+        let app_data = root.join("data");
+        let custom = root.join("custom");
+
+        let location = RecordingLocation::load(Some(config_dir.clone()), Some(app_data.clone()));
+        assert_eq!(location.directory(), app_data);
+        assert_eq!(location.db_path(), app_data.join(RECORDING_DB_FILE));
+
+        let applied = location.set_directory(custom.clone()).unwrap();
+        assert_eq!(applied, custom.canonicalize().unwrap());
+        assert!(!custom.join(WRITE_PROBE_FILE).exists());
+
+        let reloaded = RecordingLocation::load(Some(config_dir), Some(app_data.clone()));
+        assert_eq!(reloaded.directory(), applied);
+        assert_eq!(reloaded.default_directory(), app_data.as_path());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn set_directory_rejects_unwritable_target() {
+        let root = temp_dir("reject");
+        std::fs::create_dir_all(&root).unwrap();
+        // 目标是普通文件：既不能作为目录也无法写入探针文件
+        let file = root.join("not_a_dir");
+        std::fs::write(&file, b"x").unwrap();
+
+        let location = RecordingLocation::load(None, Some(root.clone()));
+        assert!(location.set_directory(file.clone()).is_err());
+        // 父路径是文件时 create_dir_all 失败
+        assert!(location.set_directory(file.join("child")).is_err());
+        assert_eq!(location.directory(), root);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
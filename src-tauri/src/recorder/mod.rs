@@ -1,10 +1,39 @@
 //! 录制模块入口与公共接口。
 
+pub mod binary;
 pub mod db;
+pub mod device_calibration;
+pub mod device_profile;
+pub mod disk;
+mod export;
+pub mod filename;
+mod flags;
+pub mod flight;
+pub mod location;
 pub mod models;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
 mod service;
+mod startup;
+mod statistics;
+#[cfg(test)]
+mod test_support;
 
+pub use export::{
+    ExportError, ExportGuard, ExportOptions, ExportProgress, ExportRegistry,
+    DEFAULT_EXPORT_BATCH_ROWS, DEFAULT_PROGRESS_INTERVAL,
+};
 pub use service::{
-    delete_recording, export_session_csv, get_recording_samples, list_recordings, spawn_recorder,
-    start_recording, stop_recording, update_recording_meta, RecorderCommand, RecordingStartInput,
+    add_recording_marker, compare_recordings, delete_recording, delete_recording_flag,
+    delete_recordings, dump_flight_recorder, export_recording_csv_to_dir, export_session_csv,
+    flag_sample_range, get_recording_flags, get_recording_markers, get_recording_pauses,
+    get_recording_samples, get_recording_samples_binary, get_recording_segments,
+    get_recording_statistics, get_stage_samples, get_sync_events, import_session_csv,
+    list_recordings, pause_recording, record_sync_pulse, recording_status, resume_recording,
+    smooth_recording, spawn_recorder, stop_recording, transform_recording, update_recording_meta,
+    RecorderCommand, RecorderOptions, PAUSE_MARKER_LABEL, RESUME_MARKER_LABEL,
+};
+pub use startup::{
+    repair_recordings, start_recording, RecordingSplit, RecordingStartInput, RecordingStillStarting,
 };
+pub use statistics::{StatisticsCache, STATISTICS_CACHE_TTL};
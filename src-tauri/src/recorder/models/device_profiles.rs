@@ -0,0 +1,24 @@
+//! device_profiles 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 设备标定档案数据模型。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "device_profiles")]
+pub struct Model {
+    /// 设备 ID（蓝牙 UUID）。
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub device_id: String,
+    /// 覆盖到基础配置上的配置片段（JSON）。
+    pub overrides: String,
+    /// 姿态零位（JSON），未保存时为空。
+    pub axis_offset: Option<String>,
+    /// 保存时间戳（ms）。
+    pub updated_at_ms: i64,
+}
+
+/// 无关联关系。
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -42,6 +42,25 @@ pub struct Model {
     pub calc_position_y: f64,
     pub calc_position_z: f64,
     pub calc_timestamp_ms: i64,
+    #[sea_orm(default_value = "unknown")]
+    pub motion_state: String,
+    #[sea_orm(default_value = "integrated")]
+    pub position_source: String,
+    pub smoothed_position_x: Option<f64>,
+    pub smoothed_position_y: Option<f64>,
+    pub smoothed_position_z: Option<f64>,
+    pub transformed_position_x: Option<f64>,
+    pub transformed_position_y: Option<f64>,
+    pub transformed_position_z: Option<f64>,
+    pub transformed_attitude_w: Option<f64>,
+    pub transformed_attitude_x: Option<f64>,
+    pub transformed_attitude_y: Option<f64>,
+    pub transformed_attitude_z: Option<f64>,
+    pub position_sigma_m: Option<f64>,
+    pub baro_temperature_c: Option<f64>,
+    pub baro_pressure_hpa: Option<f64>,
+    pub baro_altitude_m: Option<f64>,
+    pub baro_relative_altitude_m: Option<f64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
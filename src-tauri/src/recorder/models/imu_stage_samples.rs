@@ -0,0 +1,78 @@
+//! imu_stage_samples 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 深度录制的逐帧阶段中间值，与同会话 `imu_samples` 按设备时间戳一一对应。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "imu_stage_samples")]
+pub struct Model {
+    /// 自增主键。
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 所属会话 ID。
+    pub session_id: i64,
+    /// 设备时间戳（ms）。
+    pub timestamp_ms: i64,
+    /// 标定后的加速度 (m/s²)。
+    pub cal_accel_x: f64,
+    pub cal_accel_y: f64,
+    pub cal_accel_z: f64,
+    /// 标定后的角速度 (rad/s)。
+    pub cal_gyro_x: f64,
+    pub cal_gyro_y: f64,
+    pub cal_gyro_z: f64,
+    /// 滤波后的加速度 (m/s²)。
+    pub filt_accel_x: f64,
+    pub filt_accel_y: f64,
+    pub filt_accel_z: f64,
+    /// 滤波后的角速度 (rad/s)。
+    pub filt_gyro_x: f64,
+    pub filt_gyro_y: f64,
+    pub filt_gyro_z: f64,
+    /// 世界系线加速度 (m/s²)。
+    pub linear_accel_x: f64,
+    pub linear_accel_y: f64,
+    pub linear_accel_z: f64,
+    /// ZUPT 前的速度 (m/s)。
+    pub pre_zupt_velocity_x: f64,
+    pub pre_zupt_velocity_y: f64,
+    pub pre_zupt_velocity_z: f64,
+    /// ZUPT 前的位置 (m)。
+    pub pre_zupt_position_x: f64,
+    pub pre_zupt_position_y: f64,
+    pub pre_zupt_position_z: f64,
+    /// ZUPT 后的速度 (m/s)。
+    pub post_zupt_velocity_x: f64,
+    pub post_zupt_velocity_y: f64,
+    pub post_zupt_velocity_z: f64,
+    /// ZUPT 后的位置 (m)。
+    pub post_zupt_position_x: f64,
+    pub post_zupt_position_y: f64,
+    pub post_zupt_position_z: f64,
+    /// 本帧是否判定为静止。
+    pub is_static: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    RecordingSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::RecordingSession => Entity::belongs_to(super::recording_sessions::Entity)
+                .from(Column::SessionId)
+                .to(super::recording_sessions::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::recording_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecordingSession.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
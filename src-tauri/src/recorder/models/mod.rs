@@ -1,5 +1,12 @@
 //! 录制数据库实体定义。
 
 pub mod device_calibrations;
+pub mod device_profiles;
 pub mod imu_samples;
+pub mod imu_stage_samples;
+pub mod recording_flags;
+pub mod recording_markers;
+pub mod recording_pauses;
+pub mod recording_segments;
 pub mod recording_sessions;
+pub mod recording_sync_events;
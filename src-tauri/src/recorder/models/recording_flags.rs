@@ -0,0 +1,46 @@
+//! recording_flags 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 数据质量标记区间数据模型。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "recording_flags")]
+pub struct Model {
+    /// 自增主键。
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 所属会话 ID。
+    pub session_id: i64,
+    /// 区间起点设备时间戳（ms，含）。
+    pub from_ms: i64,
+    /// 区间终点设备时间戳（ms，含）。
+    pub to_ms: i64,
+    /// 标记文本。
+    pub label: String,
+    /// 创建时的主机 UNIX 时间戳（ms）。
+    pub created_at_ms: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    RecordingSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::RecordingSession => Entity::belongs_to(super::recording_sessions::Entity)
+                .from(Column::SessionId)
+                .to(super::recording_sessions::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::recording_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecordingSession.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,44 @@
+//! recording_markers 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 录制标记数据模型。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "recording_markers")]
+pub struct Model {
+    /// 自增主键，同一会话内即插入顺序。
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 所属会话 ID。
+    pub session_id: i64,
+    /// 设备时间戳（ms），取打标记时最近一条已录制样本的时间戳。
+    pub timestamp_ms: i64,
+    /// 主机 UNIX 时间戳（ms）。
+    pub host_ts_ms: i64,
+    /// 标记文本。
+    pub label: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    RecordingSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::RecordingSession => Entity::belongs_to(super::recording_sessions::Entity)
+                .from(Column::SessionId)
+                .to(super::recording_sessions::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::recording_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecordingSession.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
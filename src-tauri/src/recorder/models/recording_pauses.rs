@@ -0,0 +1,44 @@
+//! recording_pauses 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 录制暂停区间数据模型，恢复（或暂停中停止）时写入。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "recording_pauses")]
+pub struct Model {
+    /// 自增主键。
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 所属会话 ID。
+    pub session_id: i64,
+    /// 暂停时的主机 UNIX 时间戳（ms）。
+    pub paused_at_ms: i64,
+    /// 恢复（或停止）时的主机 UNIX 时间戳（ms）。
+    pub resumed_at_ms: i64,
+    /// 暂停前最后一条已录制样本的设备时间戳（ms），暂停时尚无样本则为空。
+    pub timestamp_ms: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    RecordingSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::RecordingSession => Entity::belongs_to(super::recording_sessions::Entity)
+                .from(Column::SessionId)
+                .to(super::recording_sessions::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::recording_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecordingSession.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,54 @@
+//! recording_segments 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 运动分段数据模型。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "recording_segments")]
+pub struct Model {
+    /// 自增主键。
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 所属会话 ID。
+    pub session_id: i64,
+    /// 起点设备时间戳（ms）。
+    pub start_ms: i64,
+    /// 终点设备时间戳（ms）。
+    pub end_ms: i64,
+    /// 时长（ms）。
+    pub duration_ms: i64,
+    /// 路径长度（m）。
+    pub path_length_m: f64,
+    /// 位移 x（m）。
+    pub displacement_x: f64,
+    /// 位移 y（m）。
+    pub displacement_y: f64,
+    /// 位移 z（m）。
+    pub displacement_z: f64,
+    /// 峰值速度（m/s）。
+    pub peak_speed_mps: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    RecordingSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::RecordingSession => Entity::belongs_to(super::recording_sessions::Entity)
+                .from(Column::SessionId)
+                .to(super::recording_sessions::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::recording_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecordingSession.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -13,17 +13,41 @@ pub struct Model {
     pub name: Option<String>,
     pub tags: Option<String>,
     pub sample_count: i64,
+    pub stop_reason: Option<String>,
+    pub parent_session_id: Option<i64>,
+    #[sea_orm(default_value = false)]
+    pub interrupted: bool,
+    /// 设备时间 0 对应的主机 UNIX 时间 (ms)，录制期间时钟同步未建立时为空。
+    pub clock_offset_ms: Option<f64>,
+    /// 设备时钟相对主机的频偏 (ppm)，正值表示设备偏快。
+    pub clock_skew_ppm: Option<f64>,
+    /// 写入时的 `ResponseData` 结构版本，早期录制没有该列（视为 1）。
+    pub schema_version: Option<i64>,
+    /// 暂停累计时长 (ms，主机时间)，不计入录制时长；早期录制为空。
+    pub paused_ms: Option<i64>,
+    /// 结束时生效的航向对齐旋转 (°)，样本中的导航输出已按它旋转；未对齐时为空。
+    pub heading_yaw_deg: Option<f64>,
+    /// 该对齐开始生效的设备时间戳 (ms)。
+    pub heading_aligned_at_ms: Option<i64>,
+    /// 录制开始时的应用与设备版本信息（JSON，`RecordingMetadata`），早期录制为空。
+    pub metadata: Option<String>,
+    /// 审阅备注（自由文本），未填写时为空。
+    pub notes: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     ImuSamples,
+    RecordingFlags,
+    RecordingMarkers,
 }
 
 impl RelationTrait for Relation {
     fn def(&self) -> RelationDef {
         match self {
             Self::ImuSamples => Entity::has_many(super::imu_samples::Entity).into(),
+            Self::RecordingFlags => Entity::has_many(super::recording_flags::Entity).into(),
+            Self::RecordingMarkers => Entity::has_many(super::recording_markers::Entity).into(),
         }
     }
 }
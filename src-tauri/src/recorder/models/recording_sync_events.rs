@@ -0,0 +1,52 @@
+//! recording_sync_events 表实体。
+
+use sea_orm::entity::prelude::*;
+
+/// 同步事件数据模型（手动同步脉冲与自动检测的敲击）。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "recording_sync_events")]
+pub struct Model {
+    /// 自增主键。
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 所属会话 ID。
+    pub session_id: i64,
+    /// 事件类型：`manual` 或 `tap`。
+    pub kind: String,
+    /// 触发来源：手动脉冲为调用方给定的名称，敲击为 `accel_tap`。
+    pub source: String,
+    /// 设备时间戳（ms）：手动脉冲取最近一条已录制样本，敲击取峰值帧。
+    pub timestamp_ms: i64,
+    /// 记录事件时的主机 UNIX 时间戳（ms）。
+    pub host_ts_ms: i64,
+    /// 时钟同步偏移（ms），尚无同步结果时为空。
+    pub clock_offset_ms: Option<f64>,
+    /// 时钟同步频偏（ppm），与偏移同时存在。
+    pub clock_skew_ppm: Option<f64>,
+    /// 敲击的峰值加速度模长（g），手动脉冲为空。
+    pub peak_g: Option<f64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    RecordingSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::RecordingSession => Entity::belongs_to(super::recording_sessions::Entity)
+                .from(Column::SessionId)
+                .to(super::recording_sessions::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::recording_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecordingSession.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
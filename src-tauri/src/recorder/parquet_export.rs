@@ -0,0 +1,545 @@
+//! 录制会话导出为 Parquet（`parquet` feature）。
+//!
+//! 数据分析流程用 pandas/pyarrow 读取录制；CSV 读得慢且丢失类型。这里每列带类型
+//! （时间戳 i64、数值 f64、可空列保留空值），会话名称、标签与导出时的管线配置写进
+//! 文件元数据（键以 `imu_vis.` 开头）。样本按行组分页查询、逐组写出，长录制导出时
+//! 内存只占一个行组；每组之间检查取消并报告进度（见 [`ExportOptions`]）。
+//! 位置与速度列按 `options.units` 换算，非国际单位时列名标注单位。
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::{
+    arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties, format::KeyValue,
+};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, Paginator, PaginatorTrait, QueryFilter,
+    QueryOrder, SelectModel,
+};
+
+use crate::{
+    processor::{clock_sync::device_to_host_ms, pipeline::ProcessorPipelineConfig},
+    recorder::{
+        db,
+        export::{partial_path, ExportOptions, ExportTracker},
+        filename::unique_path,
+        models,
+        service::{export_file_stem, parse_tags, readable_schema_version},
+    },
+    units::{Quantity, UnitConverter},
+};
+
+/// 每个行组的样本行数，250 Hz 下约 33 s。
+pub const PARQUET_ROW_GROUP_ROWS: usize = 8192;
+
+type Sample = models::imu_samples::Model;
+/// 列定义：列名与从样本取值的函数。
+type Column<T> = (&'static str, fn(&Sample) -> T);
+
+/// 非空的 f64 列：列名与取值。
+const F64_COLUMNS: [Column<f64>; 32] = [
+    ("accel_no_g_x", |s| s.accel_no_g_x),
+    ("accel_no_g_y", |s| s.accel_no_g_y),
+    ("accel_no_g_z", |s| s.accel_no_g_z),
+    ("accel_with_g_x", |s| s.accel_with_g_x),
+    ("accel_with_g_y", |s| s.accel_with_g_y),
+    ("accel_with_g_z", |s| s.accel_with_g_z),
+    ("gyro_x", |s| s.gyro_x),
+    ("gyro_y", |s| s.gyro_y),
+    ("gyro_z", |s| s.gyro_z),
+    ("quat_w", |s| s.quat_w),
+    ("quat_x", |s| s.quat_x),
+    ("quat_y", |s| s.quat_y),
+    ("quat_z", |s| s.quat_z),
+    ("angle_x", |s| s.angle_x),
+    ("angle_y", |s| s.angle_y),
+    ("angle_z", |s| s.angle_z),
+    ("offset_x", |s| s.offset_x),
+    ("offset_y", |s| s.offset_y),
+    ("offset_z", |s| s.offset_z),
+    ("accel_nav_x", |s| s.accel_nav_x),
+    ("accel_nav_y", |s| s.accel_nav_y),
+    ("accel_nav_z", |s| s.accel_nav_z),
+    ("calc_attitude_w", |s| s.calc_attitude_w),
+    ("calc_attitude_x", |s| s.calc_attitude_x),
+    ("calc_attitude_y", |s| s.calc_attitude_y),
+    ("calc_attitude_z", |s| s.calc_attitude_z),
+    ("calc_velocity_x", |s| s.calc_velocity_x),
+    ("calc_velocity_y", |s| s.calc_velocity_y),
+    ("calc_velocity_z", |s| s.calc_velocity_z),
+    ("calc_position_x", |s| s.calc_position_x),
+    ("calc_position_y", |s| s.calc_position_y),
+    ("calc_position_z", |s| s.calc_position_z),
+];
+
+/// 可空的 f64 列（平滑后的位置、换算到其他标定系的位置与姿态、位置不确定度）。
+const OPTIONAL_F64_COLUMNS: [Column<Option<f64>>; 11] = [
+    ("smoothed_position_x", |s| s.smoothed_position_x),
+    ("smoothed_position_y", |s| s.smoothed_position_y),
+    ("smoothed_position_z", |s| s.smoothed_position_z),
+    ("transformed_position_x", |s| s.transformed_position_x),
+    ("transformed_position_y", |s| s.transformed_position_y),
+    ("transformed_position_z", |s| s.transformed_position_z),
+    ("transformed_attitude_w", |s| s.transformed_attitude_w),
+    ("transformed_attitude_x", |s| s.transformed_attitude_x),
+    ("transformed_attitude_y", |s| s.transformed_attitude_y),
+    ("transformed_attitude_z", |s| s.transformed_attitude_z),
+    ("position_sigma_m", |s| s.position_sigma_m),
+];
+
+/// 带单位的列（位置、速度与位置不确定度）对应的物理量，其余列不换算。
+fn column_quantity(name: &str) -> Option<Quantity> {
+    if name.starts_with("calc_velocity_") {
+        Some(Quantity::Speed)
+    } else if name.contains("position_") {
+        Some(Quantity::Length)
+    } else {
+        None
+    }
+}
+
+/// 按导出单位换算列值，不带单位的列原样返回。
+fn convert_column(units: UnitConverter, name: &str, value: f64) -> f64 {
+    match column_quantity(name) {
+        Some(quantity) => units.from_si(quantity, value),
+        None => value,
+    }
+}
+
+/// 导出列名，带单位的列见 [`UnitConverter::column_name`]。
+fn export_column_name(units: UnitConverter, name: &str) -> String {
+    match column_quantity(name) {
+        Some(quantity) => units.column_name(name, quantity),
+        None => name.to_string(),
+    }
+}
+
+/// 将会话样本导出为 Parquet 文件，返回写出的样本行数。
+///
+/// 录制不保存处理时的管线配置，元数据中的 `imu_vis.pipeline_config` 是导出时的配置。
+/// 先写到同目录的临时文件，完成后再改名，失败或取消时不会留下半个文件。
+pub async fn export_recording_parquet(
+    db_path: &Path,
+    session_id: i64,
+    path: &Path,
+    pipeline_config: &ProcessorPipelineConfig,
+    options: ExportOptions,
+) -> anyhow::Result<u64> {
+    write_parquet(
+        db_path,
+        session_id,
+        path,
+        pipeline_config,
+        PARQUET_ROW_GROUP_ROWS,
+        options,
+    )
+    .await
+}
+
+/// 将会话样本导出为 Parquet 文件，写入 `dir`（不存在时创建），返回文件路径与样本行数。
+///
+/// 文件名与 CSV 导出相同，由清洗后的会话名称得到，同名文件已存在时追加序号。
+pub async fn export_recording_parquet_to_dir(
+    db_path: &Path,
+    session_id: i64,
+    dir: &Path,
+    pipeline_config: &ProcessorPipelineConfig,
+    options: ExportOptions,
+) -> anyhow::Result<(PathBuf, u64)> {
+    let session = {
+        let db = db::connect(db_path).await?;
+        db::ensure_schema(&db).await?;
+        models::recording_sessions::Entity::find_by_id(session_id)
+            .one(&db)
+            .await
+            .context("query recording session")?
+            .context("no session found")?
+    };
+    std::fs::create_dir_all(dir).context("create exports directory")?;
+    let path = unique_path(dir, &export_file_stem(&session), "parquet");
+    let rows =
+        export_recording_parquet(db_path, session_id, &path, pipeline_config, options).await?;
+    Ok((path, rows))
+}
+
+async fn write_parquet(
+    db_path: &Path,
+    session_id: i64,
+    path: &Path,
+    pipeline_config: &ProcessorPipelineConfig,
+    row_group_rows: usize,
+    options: ExportOptions,
+) -> anyhow::Result<u64> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let session = models::recording_sessions::Entity::find_by_id(session_id)
+        .one(&db)
+        .await
+        .context("query recording session")?
+        .context("no session found")?;
+    let schema_version = readable_schema_version(&session)?;
+    let clock_sync = session.clock_offset_ms.zip(session.clock_skew_ppm);
+
+    let mut metadata = vec![
+        KeyValue::new("imu_vis.session_id".into(), session.id.to_string()),
+        KeyValue::new(
+            "imu_vis.started_at_ms".into(),
+            session.started_at_ms.to_string(),
+        ),
+        KeyValue::new("imu_vis.schema_version".into(), schema_version.to_string()),
+        KeyValue::new(
+            "imu_vis.tags".into(),
+            serde_json::to_string(&parse_tags(session.tags.clone()))?,
+        ),
+        KeyValue::new(
+            "imu_vis.pipeline_config".into(),
+            serde_json::to_string(pipeline_config)?,
+        ),
+    ];
+    if let Some(name) = &session.name {
+        metadata.push(KeyValue::new("imu_vis.session_name".into(), name.clone()));
+    }
+    if let Some(device_id) = &session.device_id {
+        metadata.push(KeyValue::new("imu_vis.device_id".into(), device_id.clone()));
+    }
+
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_max_row_group_size(row_group_rows)
+        .set_key_value_metadata(Some(metadata))
+        .build();
+    let pages = models::imu_samples::Entity::find()
+        .filter(models::imu_samples::Column::SessionId.eq(session_id))
+        .order_by_asc(models::imu_samples::Column::TimestampMs)
+        .order_by_asc(models::imu_samples::Column::Id)
+        .paginate(&db, row_group_rows as u64);
+    let rows_total = pages.num_items().await.context("count recording samples")?;
+    let units = options.units;
+    let tracker = ExportTracker::new(session_id, rows_total, options);
+
+    let schema = sample_schema(units);
+    let partial = partial_path(path);
+    let file = File::create(&partial)
+        .with_context(|| format!("create parquet file {}", partial.display()))?;
+    let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+    let result = write_pages(pages, writer, &schema, clock_sync, units, tracker).await;
+
+    match result {
+        Ok(rows) => {
+            std::fs::rename(&partial, path)
+                .with_context(|| format!("move parquet file to {}", path.display()))?;
+            tracing::info!(session_id, rows, "导出 Parquet {}", path.display());
+            Ok(rows)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+/// 逐页查询样本并各写成一个行组，每组之前检查取消、之后报告进度。
+async fn write_pages<'db>(
+    mut pages: Paginator<'db, DatabaseConnection, SelectModel<Sample>>,
+    mut writer: ArrowWriter<File>,
+    schema: &SchemaRef,
+    clock_sync: Option<(f64, f64)>,
+    units: UnitConverter,
+    mut tracker: ExportTracker,
+) -> anyhow::Result<u64> {
+    loop {
+        tracker.ensure_not_cancelled()?;
+        let Some(samples) = pages
+            .fetch_and_next()
+            .await
+            .context("query recording samples")?
+        else {
+            break;
+        };
+        writer.write(&sample_batch(schema, &samples, clock_sync, units)?)?;
+        tracker.advance(samples.len() as u64, writer.bytes_written() as u64);
+    }
+    writer.finish()?;
+    Ok(tracker.finish(writer.bytes_written() as u64))
+}
+
+/// 样本表结构：时间戳与数值列之后是可空列、状态字符串与换算后的主机时间。
+fn sample_schema(units: UnitConverter) -> SchemaRef {
+    let mut fields = vec![Field::new("timestamp_ms", DataType::Int64, false)];
+    fields.extend(
+        F64_COLUMNS
+            .iter()
+            .map(|(name, _)| Field::new(export_column_name(units, name), DataType::Float64, false)),
+    );
+    fields.push(Field::new("calc_timestamp_ms", DataType::Int64, false));
+    fields.extend(
+        OPTIONAL_F64_COLUMNS
+            .iter()
+            .map(|(name, _)| Field::new(export_column_name(units, name), DataType::Float64, true)),
+    );
+    fields.push(Field::new("motion_state", DataType::Utf8, false));
+    fields.push(Field::new("position_source", DataType::Utf8, false));
+    // 录制期间建立过时钟同步时按会话参数换算，否则为空
+    fields.push(Field::new("host_time_ms", DataType::Float64, true));
+    Arc::new(Schema::new(fields))
+}
+
+fn sample_batch(
+    schema: &SchemaRef,
+    samples: &[Sample],
+    clock_sync: Option<(f64, f64)>,
+    units: UnitConverter,
+) -> anyhow::Result<RecordBatch> {
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(Int64Array::from_iter_values(
+        samples.iter().map(|s| s.timestamp_ms),
+    ))];
+    columns.extend(F64_COLUMNS.iter().map(|(name, get)| {
+        Arc::new(Float64Array::from_iter_values(
+            samples.iter().map(|s| convert_column(units, name, get(s))),
+        )) as ArrayRef
+    }));
+    columns.push(Arc::new(Int64Array::from_iter_values(
+        samples.iter().map(|s| s.calc_timestamp_ms),
+    )));
+    columns.extend(OPTIONAL_F64_COLUMNS.iter().map(|(name, get)| {
+        Arc::new(
+            samples
+                .iter()
+                .map(|s| get(s).map(|value| convert_column(units, name, value)))
+                .collect::<Float64Array>(),
+        ) as ArrayRef
+    }));
+    columns.push(Arc::new(StringArray::from_iter_values(
+        samples.iter().map(|s| s.motion_state.as_str()),
+    )));
+    columns.push(Arc::new(StringArray::from_iter_values(
+        samples.iter().map(|s| s.position_source.as_str()),
+    )));
+    columns.push(Arc::new(
+        samples
+            .iter()
+            .map(|s| {
+                clock_sync.map(|(offset_ms, skew_ppm)| {
+                    device_to_host_ms(offset_ms, skew_ppm, s.timestamp_ms as u64)
+                })
+            })
+            .collect::<Float64Array>(),
+    ));
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::recorder::{import_session_csv, update_recording_meta, ExportError, ExportProgress};
+
+    #[tokio::test]
+    async fn exported_session_reads_back_with_types_and_metadata() {
+        let db_path = std::env::temp_dir().join(format!(
+            "imu_vis_parquet_test_{}.sqlite",
+            std::process::id()
+        ));
+        let csv_path = db_path.with_extension("csv");
+        let parquet_path = db_path.with_extension("parquet");
+        let _ = std::fs::remove_file(&db_path);
+        let mut csv = String::from(
+            "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+             calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+             calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z\n",
+        );
+        for i in 0..5 {
+            csv.push_str(&format!(
+                "{},{},0,0,0,0,0,1,0,0,0\n",
+                1000 + i * 4,
+                i as f64 * 0.1
+            ));
+        }
+        std::fs::write(&csv_path, csv).unwrap();
+        let (meta, _) = import_session_csv(&db_path, &csv_path, Some("walk".into()))
+            .await
+            .unwrap();
+        update_recording_meta(
+            &db_path,
+            meta.id,
+            Some("walk".into()),
+            Some(vec!["lab".into(), "slow".into()]),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // 每组 2 行：5 行样本写成 3 个行组
+        let config = ProcessorPipelineConfig::default();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let options = ExportOptions {
+            progress_interval: Duration::ZERO,
+            on_progress: Some(Box::new(move |p: ExportProgress| {
+                sink.lock().unwrap().push((p.rows_done, p.rows_total))
+            })),
+            ..ExportOptions::default()
+        };
+        let rows = write_parquet(&db_path, meta.id, &parquet_path, &config, 2, options)
+            .await
+            .unwrap();
+        assert_eq!(rows, 5);
+        assert!(!partial_path(&parquet_path).exists());
+        assert_eq!(*reports.lock().unwrap(), vec![(2, 5), (4, 5), (5, 5)]);
+
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&parquet_path).unwrap()).unwrap();
+        let file_meta = builder.metadata().file_metadata();
+        assert_eq!(file_meta.num_rows(), 5);
+        assert_eq!(builder.metadata().num_row_groups(), 3);
+        let kv = |key: &str| {
+            file_meta
+                .key_value_metadata()
+                .unwrap()
+                .iter()
+                .find(|kv| kv.key == key)
+                .and_then(|kv| kv.value.clone())
+        };
+        assert_eq!(kv("imu_vis.session_name").as_deref(), Some("walk"));
+        assert_eq!(kv("imu_vis.tags").as_deref(), Some(r#"["lab","slow"]"#));
+        let stored: ProcessorPipelineConfig =
+            serde_json::from_str(&kv("imu_vis.pipeline_config").unwrap()).unwrap();
+        assert_eq!(stored.content_hash(), config.content_hash());
+
+        let batches: Vec<RecordBatch> = builder.build().unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), 5);
+        // 读取端会把行组合并成批次，按最后一行校验
+        let last = batches.last().unwrap();
+        let timestamps = last
+            .column_by_name("timestamp_ms")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(timestamps.value(timestamps.len() - 1), 1016);
+        let x = last
+            .column_by_name("calc_position_x")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert!((x.value(x.len() - 1) - 0.4).abs() < 1e-12);
+        // 导入的会话没有平滑位置与时钟同步，这两类列为空
+        let smoothed = last.column_by_name("smoothed_position_x").unwrap();
+        assert_eq!(smoothed.null_count(), smoothed.len());
+        let host_time = last.column_by_name("host_time_ms").unwrap();
+        assert_eq!(host_time.null_count(), host_time.len());
+
+        // 会话不存在时失败且不留文件
+        let missing = db_path.with_extension("missing.parquet");
+        assert!(write_parquet(
+            &db_path,
+            meta.id + 1,
+            &missing,
+            &config,
+            2,
+            ExportOptions::default()
+        )
+        .await
+        .is_err());
+        assert!(!missing.exists() && !partial_path(&missing).exists());
+
+        // 第一个行组写出后取消：返回取消错误，临时文件删除
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let options = ExportOptions {
+            progress_interval: Duration::ZERO,
+            on_progress: Some(Box::new(move |_| flag.store(true, Ordering::Relaxed))),
+            cancel: Some(cancel),
+            ..ExportOptions::default()
+        };
+        let cancelled = db_path.with_extension("cancelled.parquet");
+        let err = write_parquet(&db_path, meta.id, &cancelled, &config, 2, options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&ExportError::Cancelled));
+        assert!(!cancelled.exists() && !partial_path(&cancelled).exists());
+
+        // 按单位偏好换算位置列并在列名标注单位，姿态列不变
+        let options = ExportOptions {
+            units: UnitConverter::new(crate::units::UnitPreferences {
+                length: crate::units::LengthUnit::Millimeter,
+                ..Default::default()
+            }),
+            ..ExportOptions::default()
+        };
+        let in_mm = db_path.with_extension("mm.parquet");
+        write_parquet(&db_path, meta.id, &in_mm, &config, 8, options)
+            .await
+            .unwrap();
+        let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(&in_mm).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(batch.column_by_name("calc_position_x").is_none());
+        let x = batch
+            .column_by_name("calc_position_x[mm]")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert!((x.value(4) - 400.0).abs() < 1e-9);
+        assert!(batch.column_by_name("position_sigma[mm]").is_some());
+        assert!(batch.column_by_name("calc_velocity_x").is_some());
+        assert!(batch.column_by_name("calc_attitude_w").is_some());
+        let _ = std::fs::remove_file(&in_mm);
+
+        // 导出到目录：文件名取自会话名称，重名时追加序号
+        let dir = db_path.with_extension("exports");
+        let _ = std::fs::remove_dir_all(&dir);
+        let (first, rows) = export_recording_parquet_to_dir(
+            &db_path,
+            meta.id,
+            &dir,
+            &config,
+            ExportOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(rows, 5);
+        assert_eq!(first, dir.join("imu_walk.parquet"));
+        let (second, _) = export_recording_parquet_to_dir(
+            &db_path,
+            meta.id,
+            &dir,
+            &config,
+            ExportOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(second, dir.join("imu_walk (2).parquet"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        for path in [&parquet_path, &csv_path] {
+            let _ = std::fs::remove_file(path);
+        }
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", db_path.display()));
+        }
+    }
+}
@@ -1,20 +1,82 @@
 //! 录制业务逻辑。
 
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::Context;
+use anyhow::{anyhow, bail, ensure, Context};
 use flume::{Receiver, Sender};
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use sea_orm::{
+    sea_query::{Expr, LikeExpr, NullOrdering, SimpleExpr},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    Order, Paginator, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait,
+    SelectModel, Set, Statement, TransactionTrait,
+};
 
 use crate::{
-    processor::output::{is_accel_saturated, OutputFrame},
-    recorder::{db, models},
+    headless::{run_pipeline, sample_row_to_raw},
+    processor::{
+        analysis::{
+            anchor_mask, compare_tracks, count_anchors, gravity_residual_deg,
+            logic::MAX_PLOT_POINTS, relative_rotation, rotation_deg, smooth_positions,
+            transform_point, ComparisonReport, SmoothedPoint, SmoothedTrajectory, TrajectoryPoint,
+            TransformedPoint, TransformedTrajectory,
+        },
+        clock_sync::{device_to_host_ms, ClockSyncEstimate},
+        fault::{FaultInjector, FaultInjectorHandle},
+        navigator::{MotionState, PositionSource},
+        output::{
+            is_accel_saturated, OutputFrame, StageCapture, StageCaptureHandle, StageSnapshot,
+        },
+        pipeline::ProcessorPipelineConfig,
+        scheduling::{apply_thread_hints, SchedulingStatusHandle, ThreadPriority},
+        segment::MotionSegment,
+        shared::{rate_limit, DEFAULT_REPORT_RATE_HZ},
+        sync_tap::SyncTap,
+    },
+    recorder::{
+        binary, db,
+        disk::{
+            estimate_bytes_per_minute, estimate_stage_bytes_per_minute, DiskGuard,
+            DiskSpaceProvider, SystemDiskSpace, DEFAULT_CHECK_INTERVAL, DEFAULT_MIN_FREE_BYTES,
+        },
+        export::{partial_path, ExportOptions, ExportTracker},
+        filename::{sanitize_filename, unique_path},
+        flags::{check_flag_range, normalize_flag_label, FlagSweep},
+        flight::{
+            flight_recorder_capacity, FlightRecorder, DEFAULT_FLIGHT_RECORDER_SECS,
+            FLIGHT_RECORDER_TAG,
+        },
+        models,
+        startup::{
+            prepare_session, repair_interrupted_sessions, RecordingSplit, RecordingStartInput,
+        },
+        statistics::StatisticsCache,
+    },
     types::{
-        outputs::ResponseData,
-        recording::{RecordingMeta, RecordingStatus},
+        bluetooth::DeviceInformation,
+        outputs::{ResponseData, RESPONSE_SCHEMA_VERSION},
+        recording::{
+            RecordingCalibration, RecordingFlag, RecordingMarker, RecordingMeta, RecordingMetadata,
+            RecordingPage, RecordingPause, RecordingQuery, RecordingSegment, RecordingSortBy,
+            RecordingStageSample, RecordingStatistics, RecordingStatus, RecordingStopped,
+            RecordingSyncEvent, StopReason, SyncEventKind, TagUsage,
+        },
     },
+    units::{Quantity, UnitConverter},
 };
 
+/// 导航暂停时写入的标记文本。
+pub const PAUSE_MARKER_LABEL: &str = "processing_paused";
+/// 导航恢复时写入的标记文本，与前一个暂停标记围成暂停区间。
+pub const RESUME_MARKER_LABEL: &str = "processing_resumed";
+/// 等待录制线程回复控制命令的上限。
+///
+/// 超时后返回错误，命令不会因录制线程卡死而一直挂起。开始录制另用
+/// [`RECORDER_START_TIMEOUT`](super::startup::RECORDER_START_TIMEOUT)。
+pub const RECORDER_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
 /// 录制控制命令。
 pub enum RecorderCommand {
     /// 开始录制。
@@ -27,24 +89,122 @@ pub enum RecorderCommand {
         name: Option<String>,
         /// 标签列表。
         tags: Option<Vec<String>>,
+        /// 自动分段条件。
+        split: RecordingSplit,
+        /// 应用与设备版本信息（装箱，避免命令枚举整体变大）。
+        metadata: Option<Box<RecordingMetadata>>,
+        /// 是否深度录制。
+        deep: bool,
         /// 返回通道。
         reply: Sender<anyhow::Result<RecordingStatus>>,
     },
-    /// 停止录制。
+    /// 停止录制，暂停中停止时先结束未关闭的暂停区间。
     Stop {
         /// 返回通道。
         reply: Sender<anyhow::Result<RecordingStatus>>,
     },
+    /// 暂停当前会话：之后到达的数据直接丢弃，会话保持打开。
+    Pause {
+        /// 返回通道。
+        reply: Sender<anyhow::Result<RecordingStatus>>,
+    },
+    /// 恢复暂停的会话，写入暂停区间。
+    Resume {
+        /// 返回通道。
+        reply: Sender<anyhow::Result<RecordingStatus>>,
+    },
+    /// 在当前会话中插入标记。
+    Marker {
+        /// 标记文本。
+        label: String,
+        /// 返回通道。
+        reply: Sender<anyhow::Result<RecordingMarker>>,
+    },
+    /// 查询当前录制状态。
+    Status {
+        /// 返回通道。
+        reply: Sender<RecordingStatus>,
+    },
+    /// 写入运动分段，未在录制时忽略。
+    Segment {
+        /// 分段。
+        segment: MotionSegment,
+    },
+    /// 在当前会话中记录手动同步脉冲。
+    SyncPulse {
+        /// 触发来源（如 `camera_a`、`clapper`）。
+        source: String,
+        /// 返回通道。
+        reply: Sender<anyhow::Result<RecordingSyncEvent>>,
+    },
+    /// 写入自动检测到的敲击同步事件，未在录制时忽略。
+    SyncTap {
+        /// 敲击。
+        tap: SyncTap,
+    },
+    /// 修复未正常结束的会话（跳过正在录制的会话），返回修复数量。
+    Repair {
+        /// 数据库路径。
+        db_path: PathBuf,
+        /// 返回通道。
+        reply: Sender<anyhow::Result<u64>>,
+    },
+    /// 把飞行记录仪当前保留的帧写成新的录制会话。
+    DumpFlightRecorder {
+        /// 数据库路径。
+        db_path: PathBuf,
+        /// 会话名称。
+        name: Option<String>,
+        /// 返回通道。
+        reply: Sender<anyhow::Result<RecordingMeta>>,
+    },
+    /// 调整飞行记录仪容量（帧），保留最新的帧；0 表示关闭。
+    ResizeFlightRecorder {
+        /// 新容量（帧）。
+        capacity: usize,
+    },
+    /// 对录制线程应用调度提示（处理线程启动时按配置发送）。
+    ApplyScheduling {
+        /// 要设置的优先级，为空表示未请求。
+        priority: Option<ThreadPriority>,
+        /// 结果写入处。
+        status: SchedulingStatusHandle,
+    },
 }
 
-/// 开始录制参数。
-pub struct RecordingStartInput {
-    /// 设备 ID。
-    pub device_id: Option<String>,
-    /// 录制名称。
-    pub name: Option<String>,
-    /// 标签列表。
-    pub tags: Option<Vec<String>>,
+/// 录制线程选项。
+pub struct RecorderOptions {
+    /// 剩余空间查询实现。
+    pub disk_space: Arc<dyn DiskSpaceProvider>,
+    /// 最小剩余空间（字节），低于该值拒绝开始 / 自动停止录制。
+    pub min_free_bytes: u64,
+    /// 录制过程中复查剩余空间的间隔。
+    pub check_interval: Duration,
+    /// 录制线程主动结束会话时的回调（如推送 `recording_stopped` 事件）。
+    pub on_stopped: Option<Box<dyn Fn(RecordingStopped) + Send>>,
+    /// 飞行记录仪容量（帧），0 表示关闭。
+    pub flight_recorder_frames: usize,
+    /// 故障注入钩子，可让样本写入按概率失败（仅开发构建生效）。
+    pub faults: FaultInjectorHandle,
+    /// 与管线共用的阶段中间值采集开关，仅在深度录制期间打开。
+    pub stage_capture: StageCaptureHandle,
+}
+
+impl Default for RecorderOptions {
+    fn default() -> Self {
+        Self {
+            disk_space: Arc::new(SystemDiskSpace),
+            min_free_bytes: DEFAULT_MIN_FREE_BYTES,
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            on_stopped: None,
+            flight_recorder_frames: flight_recorder_capacity(
+                DEFAULT_FLIGHT_RECORDER_SECS,
+                DEFAULT_REPORT_RATE_HZ,
+            ),
+            faults: FaultInjector::new_handle(),
+            stage_capture: StageCapture::new_handle(),
+        }
+    }
 }
 
 struct ActiveSession {
@@ -52,6 +212,105 @@ struct ActiveSession {
     session_id: i64,
     db_path: PathBuf,
     sample_count: u64,
+    /// 当前分段首条样本的设备时间戳，用于按时长分段。
+    first_timestamp_ms: Option<u64>,
+    /// 最近一条已写入样本的设备时间戳，用于对齐标记。
+    last_timestamp_ms: Option<u64>,
+    started_at_ms: i64,
+    device_id: Option<String>,
+    /// 当前分段的名称（第二段起带 ` (n)` 后缀）。
+    name: Option<String>,
+    /// 用户给定的名称，生成分段名称用。
+    base_name: Option<String>,
+    tags: Option<Vec<String>>,
+    split: RecordingSplit,
+    metadata: Option<RecordingMetadata>,
+    /// 是否深度录制，分段后沿用。
+    deep: bool,
+    /// 分段序号，从 1 开始。
+    part: u32,
+    /// 第一段的会话 ID，第一段自身为空。
+    parent_session_id: Option<i64>,
+    /// 最近一帧带出的时钟同步结果，结束分段时写入会话。
+    clock_sync: Option<ClockSyncEstimate>,
+    /// 最近一帧生效的航向对齐旋转 (°)，结束分段时写入会话。
+    heading_yaw_deg: Option<f64>,
+    /// 上述对齐开始生效的设备时间戳 (ms)。
+    heading_aligned_at_ms: Option<u64>,
+    disk_guard: DiskGuard,
+    /// 当前暂停开始的主机时间 (ms)，未暂停时为空。
+    paused_at_ms: Option<i64>,
+    /// 当前分段已结束的暂停累计时长 (ms，主机时间)。
+    paused_ms: i64,
+    /// 当前分段因暂停跳过的设备时间 (ms)，按时长分段时扣除。
+    paused_device_ms: u64,
+    /// 刚恢复、尚未收到下一帧：该帧与暂停前最后一帧的设备时间差计入暂停。
+    resume_gap_pending: bool,
+}
+
+impl ActiveSession {
+    /// 下一帧（设备时间戳 `timestamp_ms`）是否应写入新分段。
+    fn split_due(&self, timestamp_ms: u64) -> bool {
+        let Some(first) = self.first_timestamp_ms else {
+            return false;
+        };
+        let span_ms = timestamp_ms
+            .saturating_sub(first)
+            .saturating_sub(self.paused_device_ms);
+        self.split.reached(self.sample_count, span_ms)
+    }
+
+    /// 截至 `now_ms` 的暂停累计时长 (ms)，含尚未结束的暂停。
+    fn paused_total_ms(&self, now_ms: i64) -> i64 {
+        let open = self
+            .paused_at_ms
+            .map_or(0, |paused_at| (now_ms - paused_at).max(0));
+        self.paused_ms + open
+    }
+
+    fn status(&self) -> RecordingStatus {
+        let now = now_ms();
+        // 暂停期间不写入，按实际录制时长估计
+        let elapsed_ms = now - self.started_at_ms - self.paused_total_ms(now);
+        let stage_bytes_per_minute = self
+            .deep
+            .then(|| estimate_stage_bytes_per_minute(self.sample_count, elapsed_ms))
+            .flatten();
+        RecordingStatus {
+            recording: true,
+            paused: self.paused_at_ms.is_some(),
+            session_id: Some(self.session_id),
+            db_path: Some(self.db_path.to_string_lossy().to_string()),
+            sample_count: Some(self.sample_count),
+            started_at_ms: Some(self.started_at_ms),
+            name: self.name.clone(),
+            tags: self.tags.clone(),
+            bytes_per_minute: estimate_bytes_per_minute(self.sample_count, elapsed_ms)
+                .map(|bytes| bytes + stage_bytes_per_minute.unwrap_or(0.0)),
+            deep: self.deep,
+            stage_bytes_per_minute,
+            available_bytes: self.disk_guard.last_available(),
+            stop_reason: None,
+        }
+    }
+}
+
+fn idle_status() -> RecordingStatus {
+    RecordingStatus {
+        recording: false,
+        paused: false,
+        session_id: None,
+        db_path: None,
+        sample_count: None,
+        started_at_ms: None,
+        name: None,
+        tags: None,
+        bytes_per_minute: None,
+        deep: false,
+        stage_bytes_per_minute: None,
+        available_bytes: None,
+        stop_reason: None,
+    }
 }
 
 /// 启动录制任务。
@@ -59,7 +318,17 @@ struct ActiveSession {
 /// 使用独立的 OS 线程 + 专属单线程 tokio runtime，与 Tauri IPC runtime 完全隔离。
 /// 这样可以避免 IPC 负载（序列化、前端推送）占用 runtime worker，导致录制消费
 /// 延迟进而反压 pipeline 线程的 `record_tx.send()`。
-pub fn spawn_recorder(data_rx: Receiver<OutputFrame>, control_rx: Receiver<RecorderCommand>) {
+///
+/// 每次写入样本后按 `options.check_interval` 复查磁盘剩余空间，不足时结束会话
+/// （`stop_reason = "disk_full"`）并调用 `options.on_stopped`，之后不再尝试写入。
+///
+/// 不论是否在录制，每一帧都复制进飞行记录仪（容量 `options.flight_recorder_frames`），
+/// 供 [`dump_flight_recorder`] 导出最近一段数据。
+pub fn spawn_recorder(
+    data_rx: Receiver<OutputFrame>,
+    control_rx: Receiver<RecorderCommand>,
+    options: RecorderOptions,
+) {
     std::thread::Builder::new()
         .name("imu-recorder".into())
         .spawn(move || {
@@ -69,12 +338,15 @@ pub fn spawn_recorder(data_rx: Receiver<OutputFrame>, control_rx: Receiver<Recor
                 .expect("recorder runtime build failed");
             rt.block_on(async move {
                 let mut active: Option<ActiveSession> = None;
+                let mut flight = FlightRecorder::new(options.flight_recorder_frames);
                 loop {
                     tokio::select! {
                         biased;
                         command = control_rx.recv_async() => {
                             match command {
-                                Ok(command) => handle_command(command, &mut active).await,
+                                Ok(command) => {
+                                    handle_command(command, &mut active, &mut flight, &options).await;
+                                }
                                 Err(_) => {
                                     if active.is_none() {
                                         break;
@@ -85,11 +357,8 @@ pub fn spawn_recorder(data_rx: Receiver<OutputFrame>, control_rx: Receiver<Recor
                         data = data_rx.recv_async() => {
                             match data {
                                 Ok(data) => {
-                                    if let Some(session) = active.as_mut() {
-                                        if let Err(error) = insert_sample(session, &data).await {
-                                            tracing::error!("Recorder insert failed: {error:#}");
-                                        }
-                                    }
+                                    flight.push(&data);
+                                    record_frame(&mut active, &data, &options).await
                                 }
                                 Err(_) => break,
                             }
@@ -101,98 +370,468 @@ pub fn spawn_recorder(data_rx: Receiver<OutputFrame>, control_rx: Receiver<Recor
         .expect("failed to spawn recorder thread");
 }
 
-/// 通过录制通道启动录制。
-pub async fn start_recording(
+/// 通过录制通道停止录制。
+pub async fn stop_recording(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+) -> anyhow::Result<RecordingStatus> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::Stop { reply: reply_tx })
+        .context("recorder thread not available")?;
+    recv_reply(reply_rx).await?
+}
+
+/// 通过录制通道暂停当前会话，未在录制或已暂停时返回错误。
+pub async fn pause_recording(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+) -> anyhow::Result<RecordingStatus> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::Pause { reply: reply_tx })
+        .context("recorder thread not available")?;
+    recv_reply(reply_rx).await?
+}
+
+/// 通过录制通道恢复暂停的会话，未在录制或未暂停时返回错误。
+pub async fn resume_recording(
     recorder_tx: &flume::Sender<RecorderCommand>,
-    input: RecordingStartInput,
 ) -> anyhow::Result<RecordingStatus> {
-    let db_path = db::recording_db_path()?;
     let (reply_tx, reply_rx) = flume::bounded(1);
     recorder_tx
-        .send(RecorderCommand::Start {
+        .send(RecorderCommand::Resume { reply: reply_tx })
+        .context("recorder thread not available")?;
+    recv_reply(reply_rx).await?
+}
+
+/// 通过录制通道在当前会话中插入标记。
+pub async fn add_recording_marker(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    label: String,
+) -> anyhow::Result<RecordingMarker> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::Marker {
+            label,
+            reply: reply_tx,
+        })
+        .context("recorder thread not available")?;
+    recv_reply(reply_rx).await?
+}
+
+/// 通过录制通道在当前会话中记录手动同步脉冲。
+///
+/// 同时保存主机时间、最近一条已录制样本的设备时间与当前时钟同步结果，
+/// 未在录制时返回错误。
+pub async fn record_sync_pulse(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    source: String,
+) -> anyhow::Result<RecordingSyncEvent> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::SyncPulse {
+            source,
+            reply: reply_tx,
+        })
+        .context("recorder thread not available")?;
+    recv_reply(reply_rx).await?
+}
+
+/// 通过录制通道把飞行记录仪保留的最近帧写成新的录制会话。
+///
+/// 飞行记录仪关闭或尚无数据时返回错误。
+pub async fn dump_flight_recorder(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    db_path: PathBuf,
+    name: Option<String>,
+) -> anyhow::Result<RecordingMeta> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::DumpFlightRecorder {
             db_path,
-            device_id: input.device_id,
-            name: input.name,
-            tags: input.tags,
+            name,
             reply: reply_tx,
         })
         .context("recorder thread not available")?;
-    reply_rx
-        .recv_async()
-        .await
-        .context("recorder reply channel closed")?
+    recv_reply(reply_rx).await?
 }
 
-/// 通过录制通道停止录制。
-pub async fn stop_recording(
+/// 通过录制通道查询当前录制状态。
+pub async fn recording_status(
     recorder_tx: &flume::Sender<RecorderCommand>,
 ) -> anyhow::Result<RecordingStatus> {
     let (reply_tx, reply_rx) = flume::bounded(1);
     recorder_tx
-        .send(RecorderCommand::Stop { reply: reply_tx })
+        .send(RecorderCommand::Status { reply: reply_tx })
         .context("recorder thread not available")?;
-    reply_rx
-        .recv_async()
+    recv_reply(reply_rx).await
+}
+
+/// 等待录制线程回复，超时视为录制线程无响应。
+pub(super) async fn recv_reply<T>(reply_rx: Receiver<T>) -> anyhow::Result<T> {
+    recv_reply_within(reply_rx, RECORDER_REPLY_TIMEOUT, || {
+        anyhow!(
+            "recorder thread did not reply within {} s",
+            RECORDER_REPLY_TIMEOUT.as_secs()
+        )
+    })
+    .await
+}
+
+/// 在 `timeout` 内等待录制线程回复，超时时返回 `on_timeout` 给出的错误。
+pub(super) async fn recv_reply_within<T>(
+    reply_rx: Receiver<T>,
+    timeout: Duration,
+    on_timeout: impl FnOnce() -> anyhow::Error,
+) -> anyhow::Result<T> {
+    tokio::time::timeout(timeout, reply_rx.recv_async())
         .await
-        .context("recorder reply channel closed")?
+        .map_err(|_| on_timeout())?
+        .context("recorder reply channel closed")
+}
+
+async fn record_frame(
+    active: &mut Option<ActiveSession>,
+    frame: &OutputFrame,
+    options: &RecorderOptions,
+) {
+    let Some(session) = active.as_mut() else {
+        return;
+    };
+    // 暂停期间的数据直接丢弃，不缓存
+    if session.paused_at_ms.is_some() {
+        return;
+    }
+    if std::mem::take(&mut session.resume_gap_pending) {
+        if let Some(last) = session.last_timestamp_ms {
+            session.paused_device_ms += frame.raw.timestamp_ms.as_millis().saturating_sub(last);
+        }
+    }
+    // 深度录制开始时通道里还有未采集阶段值的帧，丢弃它们，保证阶段行与样本行一一对应
+    if session.deep && session.sample_count == 0 && frame.stages.is_none() {
+        return;
+    }
+    // 在两次写入之间切换分段，当前帧直接写入新段，不丢帧
+    if session.split_due(frame.raw.timestamp_ms.as_millis()) {
+        if let Err(error) = split_session(session).await {
+            tracing::error!("Recorder split failed, continuing current session: {error:#}");
+        }
+    }
+    let provider = options.disk_space.as_ref();
+    // 写入失败时立即复查，磁盘写满不必等到下一个检查间隔
+    let inserted = if options.faults.fail_recorder_insert() {
+        Err(anyhow!("injected recorder insert failure"))
+    } else {
+        insert_sample(session, frame).await
+    };
+    let space = match inserted {
+        Ok(()) => session.disk_guard.poll(provider, &session.db_path),
+        Err(error) => {
+            rate_limit::PER_SECOND.error("recorder_insert", || {
+                format!("Recorder insert failed: {error:#}")
+            });
+            session.disk_guard.check_now(provider, &session.db_path)
+        }
+    };
+    if let Err(available) = space {
+        if let Some(session) = active.take() {
+            stop_on_low_disk(session, available, options).await;
+        }
+    }
+}
+
+/// 按当前会话是否为深度录制开关管线的阶段中间值采集。
+fn sync_stage_capture(active: &Option<ActiveSession>, options: &RecorderOptions) {
+    options
+        .stage_capture
+        .set_enabled(active.as_ref().is_some_and(|session| session.deep));
+}
+
+async fn stop_on_low_disk(session: ActiveSession, available: u64, options: &RecorderOptions) {
+    tracing::warn!(
+        session_id = session.session_id,
+        available,
+        min_free_bytes = session.disk_guard.min_free_bytes(),
+        "磁盘剩余空间不足，自动停止录制"
+    );
+    let event = RecordingStopped {
+        session_id: session.session_id,
+        reason: StopReason::DiskFull,
+        sample_count: session.sample_count,
+        available_bytes: Some(available),
+    };
+    // 会话已从 active 中取出，先关掉阶段采集再通知
+    options.stage_capture.set_enabled(false);
+    if let Err(error) = stop_session(session, StopReason::DiskFull).await {
+        tracing::error!("Recorder stop failed on low disk space: {error:#}");
+    }
+    if let Some(on_stopped) = &options.on_stopped {
+        on_stopped(event);
+    }
 }
 
-async fn handle_command(command: RecorderCommand, active: &mut Option<ActiveSession>) {
+async fn handle_command(
+    command: RecorderCommand,
+    active: &mut Option<ActiveSession>,
+    flight: &mut FlightRecorder,
+    options: &RecorderOptions,
+) {
     match command {
         RecorderCommand::Start {
             db_path,
             device_id,
             name,
             tags,
+            split,
+            metadata,
+            deep,
             reply,
         } => {
             if let Some(session) = active.take() {
-                if let Err(error) = stop_session(session).await {
+                if let Err(error) = stop_session(session, StopReason::User).await {
                     tracing::error!("Recorder stop failed while restarting: {error:#}");
                 }
             }
-            match start_session(db_path, device_id, name, tags).await {
-                Ok((session, status)) => {
-                    *active = Some(session);
-                    let _ = reply.send(Ok(status));
-                }
-                Err(error) => {
-                    let _ = reply.send(Err(error));
-                }
-            }
+            let input = RecordingStartInput {
+                device_id,
+                name,
+                tags,
+                split,
+                metadata: metadata.map(|metadata| *metadata),
+                deep,
+            };
+            let start = start_session(db_path, input, options);
+            let result = start.await.map(|(session, status)| {
+                *active = Some(session);
+                status
+            });
+            // 回复前开关阶段采集：调用方收到回复时，深度录制的第一帧已带阶段数据
+            sync_stage_capture(active, options);
+            let _ = reply.send(result);
         }
         RecorderCommand::Stop { reply } => {
             let status = if let Some(session) = active.take() {
-                stop_session(session).await
+                stop_session(session, StopReason::User).await
             } else {
-                Ok(RecordingStatus {
-                    recording: false,
-                    session_id: None,
-                    db_path: None,
-                    sample_count: None,
-                    started_at_ms: None,
-                    name: None,
-                    tags: None,
-                })
+                Ok(idle_status())
+            };
+            sync_stage_capture(active, options);
+            let _ = reply.send(status);
+        }
+        RecorderCommand::Pause { reply } => {
+            let result = match active.as_mut() {
+                Some(session) => pause_session(session),
+                None => Err(anyhow!("no active recording")),
+            };
+            sync_stage_capture(active, options);
+            let _ = reply.send(result);
+        }
+        RecorderCommand::Resume { reply } => {
+            let result = match active.as_mut() {
+                Some(session) => resume_session(session).await,
+                None => Err(anyhow!("no active recording")),
+            };
+            sync_stage_capture(active, options);
+            let _ = reply.send(result);
+        }
+        RecorderCommand::Marker { label, reply } => {
+            let result = match active.as_ref() {
+                Some(session) => insert_marker(session, label).await,
+                None => Err(anyhow!("no active recording")),
+            };
+            let _ = reply.send(result);
+        }
+        RecorderCommand::Status { reply } => {
+            let status = match active.as_ref() {
+                Some(session) => session.status(),
+                None => idle_status(),
             };
             let _ = reply.send(status);
         }
+        RecorderCommand::Segment { segment } => {
+            if let Some(session) = active.as_ref() {
+                if let Err(error) = insert_segment(session, &segment).await {
+                    tracing::error!("Recorder segment insert failed: {error:#}");
+                }
+            }
+        }
+        RecorderCommand::SyncPulse { source, reply } => {
+            let result = match active.as_ref() {
+                Some(session) => insert_sync_pulse(session, source).await,
+                None => Err(anyhow!("no active recording")),
+            };
+            let _ = reply.send(result);
+        }
+        RecorderCommand::SyncTap { tap } => {
+            if let Some(session) = active.as_ref() {
+                if let Err(error) = insert_sync_tap(session, &tap).await {
+                    tracing::error!("Recorder sync tap insert failed: {error:#}");
+                }
+            }
+        }
+        RecorderCommand::Repair { db_path, reply } => {
+            let active_session_id = active
+                .as_ref()
+                .filter(|session| session.db_path == db_path)
+                .map(|session| session.session_id);
+            let result = repair_interrupted_sessions(&db_path, active_session_id).await;
+            if let Err(error) = &result {
+                tracing::error!("Recorder repair failed: {error:#}");
+            }
+            let _ = reply.send(result);
+        }
+        RecorderCommand::DumpFlightRecorder {
+            db_path,
+            name,
+            reply,
+        } => {
+            let result = write_flight_recorder(&db_path, name, flight).await;
+            if let Err(error) = &result {
+                tracing::error!("Flight recorder dump failed: {error:#}");
+            }
+            let _ = reply.send(result);
+        }
+        RecorderCommand::ResizeFlightRecorder { capacity } => flight.resize(capacity),
+        RecorderCommand::ApplyScheduling { priority, status } => {
+            status.set_recorder(apply_thread_hints("recorder", priority, None));
+        }
+    }
+}
+
+/// 把飞行记录仪保留的帧按时间顺序写成一个已结束的会话。
+///
+/// 会话的开始时间按保留数据的设备时间跨度从当前时间倒推，带 [`FLIGHT_RECORDER_TAG`] 标签。
+async fn write_flight_recorder(
+    db_path: &Path,
+    name: Option<String>,
+    flight: &FlightRecorder,
+) -> anyhow::Result<RecordingMeta> {
+    ensure!(flight.enabled(), "flight recorder is disabled");
+    let (Some(first), Some(last)) = (flight.iter().next(), flight.iter().last()) else {
+        bail!("flight recorder is empty");
+    };
+    let span_ms = last.raw.timestamp_ms.delta_ms(first.raw.timestamp_ms) as i64;
+
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+    let txn = db.begin().await.context("begin transaction")?;
+
+    let stopped_at_ms = now_ms();
+    let started_at_ms = stopped_at_ms - span_ms;
+    let tags = Some(vec![FLIGHT_RECORDER_TAG.to_string()]);
+    // 飞行记录仪不持有设备连接信息，只记录应用版本
+    let metadata = RecordingMetadata::current(DeviceInformation::default(), None);
+    let session_id = insert_session(
+        &txn,
+        started_at_ms,
+        &None,
+        &name,
+        &tags,
+        None,
+        Some(&metadata),
+    )
+    .await?;
+    let frames: Vec<&OutputFrame> = flight.iter().collect();
+    for chunk in frames.chunks(IMPORT_BATCH_ROWS) {
+        models::imu_samples::Entity::insert_many(
+            chunk.iter().map(|frame| sample_model(session_id, frame)),
+        )
+        .exec(&txn)
+        .await
+        .context("insert flight recorder samples")?;
+    }
+    let session = models::recording_sessions::ActiveModel {
+        id: Set(session_id),
+        stopped_at_ms: Set(Some(stopped_at_ms)),
+        sample_count: Set(frames.len() as i64),
+        ..Default::default()
     }
+    .update(&txn)
+    .await
+    .context("update flight recorder session")?;
+    txn.commit().await.context("commit transaction")?;
+
+    tracing::info!(
+        session_id,
+        samples = frames.len(),
+        span_ms,
+        "导出飞行记录仪"
+    );
+    Ok(session_to_meta(session))
 }
 
 async fn start_session(
     db_path: PathBuf,
-    device_id: Option<String>,
-    name: Option<String>,
-    tags: Option<Vec<String>>,
+    input: RecordingStartInput,
+    options: &RecorderOptions,
 ) -> anyhow::Result<(ActiveSession, RecordingStatus)> {
-    let db = db::connect(&db_path).await?;
-    db::ensure_schema(&db).await?;
+    let RecordingStartInput {
+        device_id,
+        name,
+        tags,
+        split,
+        metadata,
+        deep,
+    } = input;
+    let (db, disk_guard) = prepare_session(&db_path, &split, options).await?;
 
     let started_at_ms = now_ms();
+    let session_id = insert_session(
+        &db,
+        started_at_ms,
+        &device_id,
+        &name,
+        &tags,
+        None,
+        metadata.as_ref(),
+    )
+    .await?;
+
+    let session = ActiveSession {
+        db,
+        session_id,
+        db_path,
+        sample_count: 0,
+        first_timestamp_ms: None,
+        last_timestamp_ms: None,
+        started_at_ms,
+        device_id,
+        base_name: name.clone(),
+        name,
+        tags,
+        split,
+        metadata,
+        deep,
+        part: 1,
+        parent_session_id: None,
+        clock_sync: None,
+        heading_yaw_deg: None,
+        heading_aligned_at_ms: None,
+        disk_guard,
+        paused_at_ms: None,
+        paused_ms: 0,
+        paused_device_ms: 0,
+        resume_gap_pending: false,
+    };
+    let status = session.status();
+    Ok((session, status))
+}
+
+async fn insert_session<C: ConnectionTrait>(
+    db: &C,
+    started_at_ms: i64,
+    device_id: &Option<String>,
+    name: &Option<String>,
+    tags: &Option<Vec<String>>,
+    parent_session_id: Option<i64>,
+    metadata: Option<&RecordingMetadata>,
+) -> anyhow::Result<i64> {
     let tags_json = tags
         .as_ref()
         .map(|value| serde_json::to_string(value).unwrap_or_default());
+    let metadata_json = metadata
+        .map(serde_json::to_string)
+        .transpose()
+        .context("serialize recording metadata")?;
 
     let session = models::recording_sessions::ActiveModel {
         started_at_ms: Set(started_at_ms),
@@ -201,65 +840,213 @@ async fn start_session(
         name: Set(name.clone()),
         tags: Set(tags_json),
         sample_count: Set(0),
+        parent_session_id: Set(parent_session_id),
+        interrupted: Set(false),
+        schema_version: Set(Some(RESPONSE_SCHEMA_VERSION as i64)),
+        metadata: Set(metadata_json),
         ..Default::default()
     };
     let insert = session
-        .insert(&db)
+        .insert(db)
         .await
         .context("insert recording session")?;
+    Ok(insert.id)
+}
 
-    let status = RecordingStatus {
-        recording: true,
-        session_id: Some(insert.id),
-        db_path: Some(db_path.to_string_lossy().to_string()),
-        sample_count: Some(0),
-        started_at_ms: Some(started_at_ms),
-        name,
-        tags,
-    };
+/// 结束当前分段并在同一数据库中开始下一段，名称、标签与分段条件沿用第一段。
+async fn split_session(session: &mut ActiveSession) -> anyhow::Result<()> {
+    let part = session.part + 1;
+    let parent_session_id = session.parent_session_id.unwrap_or(session.session_id);
+    let name = session
+        .base_name
+        .as_ref()
+        .map(|base| format!("{base} ({part})"));
+    let started_at_ms = now_ms();
+    // 先建新段：失败时继续写当前段，不丢数据
+    let session_id = insert_session(
+        &session.db,
+        started_at_ms,
+        &session.device_id,
+        &name,
+        &session.tags,
+        Some(parent_session_id),
+        session.metadata.as_ref(),
+    )
+    .await?;
+    if let Err(error) = finalize_session(session, StopReason::Split).await {
+        tracing::error!("Recorder failed to finalize split part: {error:#}");
+    }
+    tracing::info!(
+        finished = session.session_id,
+        session_id,
+        parent_session_id,
+        part,
+        samples = session.sample_count,
+        "录制自动分段"
+    );
 
-    Ok((
-        ActiveSession {
-            db,
-            session_id: insert.id,
-            db_path,
-            sample_count: 0,
-        },
-        status,
-    ))
+    session.session_id = session_id;
+    session.sample_count = 0;
+    session.first_timestamp_ms = None;
+    session.last_timestamp_ms = None;
+    session.started_at_ms = started_at_ms;
+    session.name = name;
+    session.part = part;
+    session.parent_session_id = Some(parent_session_id);
+    // 分段只在写入样本时发生，此时必然未暂停
+    session.paused_ms = 0;
+    session.paused_device_ms = 0;
+    Ok(())
 }
 
-async fn stop_session(session: ActiveSession) -> anyhow::Result<RecordingStatus> {
-    let stopped_at_ms = now_ms();
+/// 写入当前分段的结束时间、样本数与录制期间累计的会话参数。
+async fn finalize_session(session: &ActiveSession, reason: StopReason) -> anyhow::Result<()> {
+    let clock_sync = session.clock_sync;
     let update = models::recording_sessions::ActiveModel {
         id: Set(session.session_id),
-        stopped_at_ms: Set(Some(stopped_at_ms)),
+        stopped_at_ms: Set(Some(now_ms())),
         sample_count: Set(session.sample_count as i64),
+        stop_reason: Set(Some(reason.as_str().to_string())),
+        clock_offset_ms: Set(clock_sync.map(|sync| sync.offset_ms)),
+        clock_skew_ppm: Set(clock_sync.map(|sync| sync.skew_ppm)),
+        paused_ms: Set(Some(session.paused_ms)),
+        heading_yaw_deg: Set(session.heading_yaw_deg),
+        heading_aligned_at_ms: Set(session.heading_aligned_at_ms.map(|ts| ts as i64)),
         ..Default::default()
     };
     update
         .update(&session.db)
         .await
         .context("update recording session")?;
+    Ok(())
+}
+
+async fn stop_session(
+    mut session: ActiveSession,
+    reason: StopReason,
+) -> anyhow::Result<RecordingStatus> {
+    // 暂停中停止：停止时刻即恢复时刻，关闭暂停区间
+    if session.paused_at_ms.is_some() {
+        if let Err(error) = close_pause(&mut session).await {
+            tracing::error!("Recorder failed to close pause interval on stop: {error:#}");
+        }
+    }
+    finalize_session(&session, reason).await?;
 
     Ok(RecordingStatus {
         recording: false,
+        paused: false,
         session_id: Some(session.session_id),
         db_path: Some(session.db_path.to_string_lossy().to_string()),
         sample_count: Some(session.sample_count),
         started_at_ms: None,
         name: None,
         tags: None,
+        bytes_per_minute: None,
+        deep: session.deep,
+        stage_bytes_per_minute: None,
+        available_bytes: session.disk_guard.last_available(),
+        stop_reason: Some(reason),
     })
 }
 
+fn pause_session(session: &mut ActiveSession) -> anyhow::Result<RecordingStatus> {
+    ensure!(
+        session.paused_at_ms.is_none(),
+        "recording is already paused"
+    );
+    session.paused_at_ms = Some(now_ms());
+    tracing::info!(session_id = session.session_id, "录制暂停");
+    Ok(session.status())
+}
+
+async fn resume_session(session: &mut ActiveSession) -> anyhow::Result<RecordingStatus> {
+    ensure!(session.paused_at_ms.is_some(), "recording is not paused");
+    close_pause(session).await?;
+    session.resume_gap_pending = true;
+    tracing::info!(
+        session_id = session.session_id,
+        paused_ms = session.paused_ms,
+        "录制恢复"
+    );
+    Ok(session.status())
+}
+
+/// 写入当前暂停区间并累计暂停时长；写入失败时保持暂停。
+async fn close_pause(session: &mut ActiveSession) -> anyhow::Result<()> {
+    let Some(paused_at_ms) = session.paused_at_ms else {
+        return Ok(());
+    };
+    let resumed_at_ms = now_ms().max(paused_at_ms);
+    models::recording_pauses::ActiveModel {
+        session_id: Set(session.session_id),
+        paused_at_ms: Set(paused_at_ms),
+        resumed_at_ms: Set(resumed_at_ms),
+        timestamp_ms: Set(session.last_timestamp_ms.map(|ts| ts as i64)),
+        ..Default::default()
+    }
+    .insert(&session.db)
+    .await
+    .context("insert recording pause")?;
+    session.paused_ms += resumed_at_ms - paused_at_ms;
+    session.paused_at_ms = None;
+    Ok(())
+}
+
 async fn insert_sample(session: &mut ActiveSession, frame: &OutputFrame) -> anyhow::Result<()> {
+    match (session.deep, &frame.stages) {
+        // 阶段行与样本行在同一事务中写入，两表始终一一对应
+        (true, Some(stages)) => {
+            let txn = session
+                .db
+                .begin()
+                .await
+                .context("begin sample transaction")?;
+            sample_model(session.session_id, frame)
+                .insert(&txn)
+                .await
+                .context("insert imu sample")?;
+            stage_model(
+                session.session_id,
+                frame.raw.timestamp_ms.as_millis(),
+                stages,
+            )
+            .insert(&txn)
+            .await
+            .context("insert imu stage sample")?;
+            txn.commit().await.context("commit sample transaction")?;
+        }
+        _ => {
+            sample_model(session.session_id, frame)
+                .insert(&session.db)
+                .await
+                .context("insert imu sample")?;
+        }
+    }
+
+    session.sample_count += 1;
+    let timestamp_ms = frame.raw.timestamp_ms.as_millis();
+    session.first_timestamp_ms.get_or_insert(timestamp_ms);
+    session.last_timestamp_ms = Some(timestamp_ms);
+    // 设备复位后重新积累锚点期间沿用上一次结果，重新建立后以新时间轴为准
+    if frame.clock_sync.is_some() {
+        session.clock_sync = frame.clock_sync;
+    }
+    // 对齐角变化时记下生效时刻，离线重跑据此复现录制时的输出
+    if frame.heading_yaw_deg != session.heading_yaw_deg {
+        session.heading_yaw_deg = frame.heading_yaw_deg;
+        session.heading_aligned_at_ms = frame.heading_yaw_deg.map(|_| timestamp_ms);
+    }
+    Ok(())
+}
+
+fn sample_model(session_id: i64, frame: &OutputFrame) -> models::imu_samples::ActiveModel {
     let raw = &frame.raw;
     let nav = &frame.nav;
 
-    let sample = models::imu_samples::ActiveModel {
-        session_id: Set(session.session_id),
-        timestamp_ms: Set(raw.timestamp_ms as i64),
+    models::imu_samples::ActiveModel {
+        session_id: Set(session_id),
+        timestamp_ms: Set(raw.timestamp_ms.as_millis() as i64),
         accel_no_g_x: Set(raw.accel_no_g.x),
         accel_no_g_y: Set(raw.accel_no_g.y),
         accel_no_g_z: Set(raw.accel_no_g.z),
@@ -293,86 +1080,464 @@ async fn insert_sample(session: &mut ActiveSession, frame: &OutputFrame) -> anyh
         calc_position_y: Set(nav.position.y),
         calc_position_z: Set(nav.position.z),
         calc_timestamp_ms: Set(nav.timestamp_ms as i64),
+        motion_state: Set(frame.motion_state.as_str().to_string()),
+        position_source: Set(frame.position_source.as_str().to_string()),
+        position_sigma_m: Set(Some(frame.position_sigma_m)),
+        baro_temperature_c: Set(raw.baro.map(|baro| baro.temperature_c)),
+        baro_pressure_hpa: Set(raw.baro.map(|baro| baro.pressure_hpa)),
+        baro_altitude_m: Set(raw.baro.map(|baro| baro.altitude_m)),
+        baro_relative_altitude_m: Set(frame.baro_relative_altitude_m),
         ..Default::default()
-    };
+    }
+}
 
-    sample
-        .insert(&session.db)
-        .await
-        .context("insert imu sample")?;
+fn stage_model(
+    session_id: i64,
+    timestamp_ms: u64,
+    stages: &StageSnapshot,
+) -> models::imu_stage_samples::ActiveModel {
+    let pre = &stages.nav_pre_zupt;
+    let post = &stages.nav_post_zupt;
 
-    session.sample_count += 1;
+    models::imu_stage_samples::ActiveModel {
+        session_id: Set(session_id),
+        timestamp_ms: Set(timestamp_ms as i64),
+        cal_accel_x: Set(stages.calibrated_accel.x),
+        cal_accel_y: Set(stages.calibrated_accel.y),
+        cal_accel_z: Set(stages.calibrated_accel.z),
+        cal_gyro_x: Set(stages.calibrated_gyro.x),
+        cal_gyro_y: Set(stages.calibrated_gyro.y),
+        cal_gyro_z: Set(stages.calibrated_gyro.z),
+        filt_accel_x: Set(stages.filtered_accel.x),
+        filt_accel_y: Set(stages.filtered_accel.y),
+        filt_accel_z: Set(stages.filtered_accel.z),
+        filt_gyro_x: Set(stages.filtered_gyro.x),
+        filt_gyro_y: Set(stages.filtered_gyro.y),
+        filt_gyro_z: Set(stages.filtered_gyro.z),
+        linear_accel_x: Set(stages.linear_accel.x),
+        linear_accel_y: Set(stages.linear_accel.y),
+        linear_accel_z: Set(stages.linear_accel.z),
+        pre_zupt_velocity_x: Set(pre.velocity.x),
+        pre_zupt_velocity_y: Set(pre.velocity.y),
+        pre_zupt_velocity_z: Set(pre.velocity.z),
+        pre_zupt_position_x: Set(pre.position.x),
+        pre_zupt_position_y: Set(pre.position.y),
+        pre_zupt_position_z: Set(pre.position.z),
+        post_zupt_velocity_x: Set(post.velocity.x),
+        post_zupt_velocity_y: Set(post.velocity.y),
+        post_zupt_velocity_z: Set(post.velocity.z),
+        post_zupt_position_x: Set(post.position.x),
+        post_zupt_position_y: Set(post.position.y),
+        post_zupt_position_z: Set(post.position.z),
+        is_static: Set(stages.is_static),
+        ..Default::default()
+    }
+}
+
+async fn insert_marker(session: &ActiveSession, label: String) -> anyhow::Result<RecordingMarker> {
+    let host_ts_ms = now_ms();
+    // 尚未写入任何样本时没有设备时间可用，退回主机时间
+    let timestamp_ms = session
+        .last_timestamp_ms
+        .map(|ts| ts as i64)
+        .unwrap_or(host_ts_ms);
+
+    let marker = models::recording_markers::ActiveModel {
+        session_id: Set(session.session_id),
+        timestamp_ms: Set(timestamp_ms),
+        host_ts_ms: Set(host_ts_ms),
+        label: Set(label),
+        ..Default::default()
+    };
+    let insert = marker
+        .insert(&session.db)
+        .await
+        .context("insert recording marker")?;
+
+    Ok(marker_to_meta(insert))
+}
+
+async fn insert_segment(session: &ActiveSession, segment: &MotionSegment) -> anyhow::Result<()> {
+    let row = models::recording_segments::ActiveModel {
+        session_id: Set(session.session_id),
+        start_ms: Set(segment.start_ms as i64),
+        end_ms: Set(segment.end_ms as i64),
+        duration_ms: Set(segment.duration_ms as i64),
+        path_length_m: Set(segment.path_length_m),
+        displacement_x: Set(segment.displacement.x),
+        displacement_y: Set(segment.displacement.y),
+        displacement_z: Set(segment.displacement.z),
+        peak_speed_mps: Set(segment.peak_speed_mps),
+        ..Default::default()
+    };
+    row.insert(&session.db)
+        .await
+        .context("insert recording segment")?;
+    Ok(())
+}
+
+async fn insert_sync_pulse(
+    session: &ActiveSession,
+    source: String,
+) -> anyhow::Result<RecordingSyncEvent> {
+    let host_ts_ms = now_ms();
+    // 与标记一致：尚未写入任何样本时退回主机时间
+    let timestamp_ms = session
+        .last_timestamp_ms
+        .map(|ts| ts as i64)
+        .unwrap_or(host_ts_ms);
+    insert_sync_event(session, SyncEventKind::Manual, source, timestamp_ms, None).await
+}
+
+async fn insert_sync_tap(session: &ActiveSession, tap: &SyncTap) -> anyhow::Result<()> {
+    insert_sync_event(
+        session,
+        SyncEventKind::Tap,
+        "accel_tap".to_string(),
+        tap.timestamp_ms as i64,
+        Some(tap.peak_g),
+    )
+    .await?;
     Ok(())
 }
 
+async fn insert_sync_event(
+    session: &ActiveSession,
+    kind: SyncEventKind,
+    source: String,
+    timestamp_ms: i64,
+    peak_g: Option<f64>,
+) -> anyhow::Result<RecordingSyncEvent> {
+    let clock_sync = session.clock_sync;
+    let event = models::recording_sync_events::ActiveModel {
+        session_id: Set(session.session_id),
+        kind: Set(kind.as_str().to_string()),
+        source: Set(source),
+        timestamp_ms: Set(timestamp_ms),
+        host_ts_ms: Set(now_ms()),
+        clock_offset_ms: Set(clock_sync.map(|sync| sync.offset_ms)),
+        clock_skew_ppm: Set(clock_sync.map(|sync| sync.skew_ppm)),
+        peak_g: Set(peak_g),
+        ..Default::default()
+    };
+    let insert = event
+        .insert(&session.db)
+        .await
+        .context("insert recording sync event")?;
+
+    Ok(sync_event_to_meta(insert))
+}
+
 /// 删除指定录制会话及其所有样本数据。
-pub async fn delete_recording(session_id: i64) -> anyhow::Result<()> {
-    let db_path = db::recording_db_path()?;
-    let db = db::connect(&db_path).await?;
+pub async fn delete_recording(db_path: &Path, session_id: i64) -> anyhow::Result<()> {
+    let db = db::connect(db_path).await?;
     db::ensure_schema(&db).await?;
 
+    delete_session(&db, session_id).await
+}
+
+/// 批量删除录制会话，返回已删除的会话 ID 与警告。
+///
+/// 单个会话不存在或删除失败时跳过并记入警告，不影响其余会话；一个都没删掉时返回错误。
+pub async fn delete_recordings(
+    db_path: &Path,
+    session_ids: &[i64],
+) -> anyhow::Result<(Vec<i64>, Vec<String>)> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let mut deleted = Vec::new();
+    let mut warnings = Vec::new();
+    for &session_id in session_ids {
+        let result = async {
+            models::recording_sessions::Entity::find_by_id(session_id)
+                .one(&db)
+                .await
+                .context("query recording session")?
+                .context("no session found")?;
+            // 每个会话一个事务，失败时不留下删了一半的会话
+            let txn = db.begin().await.context("begin transaction")?;
+            delete_session(&txn, session_id).await?;
+            txn.commit().await.context("commit transaction")
+        }
+        .await;
+        match result {
+            Ok(()) => deleted.push(session_id),
+            Err(error) => warnings.push(format!("session {session_id}: {error:#}")),
+        }
+    }
+    if deleted.is_empty() && !warnings.is_empty() {
+        bail!("no recording deleted: {}", warnings.join("; "));
+    }
+    Ok((deleted, warnings))
+}
+
+async fn delete_session<C: ConnectionTrait>(db: &C, session_id: i64) -> anyhow::Result<()> {
     // 先删子表（外键约束），再删主记录
+    models::recording_markers::Entity::delete_many()
+        .filter(models::recording_markers::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await
+        .context("delete recording markers")?;
+
+    models::recording_flags::Entity::delete_many()
+        .filter(models::recording_flags::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await
+        .context("delete recording flags")?;
+
+    models::recording_pauses::Entity::delete_many()
+        .filter(models::recording_pauses::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await
+        .context("delete recording pauses")?;
+
+    models::recording_segments::Entity::delete_many()
+        .filter(models::recording_segments::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await
+        .context("delete recording segments")?;
+
+    models::recording_sync_events::Entity::delete_many()
+        .filter(models::recording_sync_events::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await
+        .context("delete recording sync events")?;
+
+    models::imu_stage_samples::Entity::delete_many()
+        .filter(models::imu_stage_samples::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await
+        .context("delete imu stage samples")?;
+
     models::imu_samples::Entity::delete_many()
         .filter(models::imu_samples::Column::SessionId.eq(session_id))
-        .exec(&db)
+        .exec(db)
         .await
         .context("delete imu samples")?;
 
     models::recording_sessions::Entity::delete_by_id(session_id)
-        .exec(&db)
+        .exec(db)
         .await
         .context("delete recording session")?;
 
     Ok(())
 }
 
-/// 列出录制会话。
-pub async fn list_recordings() -> anyhow::Result<Vec<RecordingMeta>> {
-    let db_path = db::recording_db_path()?;
-    let db = db::connect(&db_path).await?;
+/// 按条件分页列出录制会话，同时返回满足条件的总数。
+pub async fn list_recordings(
+    db_path: &Path,
+    query: &RecordingQuery,
+) -> anyhow::Result<RecordingPage> {
+    use models::recording_sessions::{Column, Entity};
+
+    let db = db::connect(db_path).await?;
     db::ensure_schema(&db).await?;
 
-    let sessions = models::recording_sessions::Entity::find()
-        .order_by_desc(models::recording_sessions::Column::StartedAtMs)
+    let mut select = Entity::find().filter(recording_filter(query));
+    let total_count = select
+        .clone()
+        .count(&db)
+        .await
+        .context("count recording sessions")?;
+
+    let order = if query.ascending {
+        Order::Asc
+    } else {
+        Order::Desc
+    };
+    let sort_expr: SimpleExpr = match query.sort_by {
+        RecordingSortBy::StartedAt => Expr::col(Column::StartedAtMs).into(),
+        RecordingSortBy::Duration => recording_duration_expr(),
+        RecordingSortBy::SampleCount => Expr::col(Column::SampleCount).into(),
+    };
+    QueryTrait::query(&mut select).order_by_expr_with_nulls(sort_expr, order, NullOrdering::Last);
+    // 同值时按 ID 排，保证分页稳定
+    let sessions = select
+        .order_by(Column::Id, Order::Desc)
+        .limit(query.limit)
+        .offset(query.offset)
         .all(&db)
         .await
         .context("query recording sessions")?;
 
-    let list = sessions
-        .into_iter()
-        .map(|session| RecordingMeta {
-            id: session.id,
-            started_at_ms: session.started_at_ms,
-            stopped_at_ms: session.stopped_at_ms,
-            sample_count: session.sample_count,
-            name: session.name,
-            tags: parse_tags(session.tags),
+    Ok(RecordingPage {
+        items: sessions.into_iter().map(session_to_meta).collect(),
+        total_count,
+    })
+}
+
+/// 汇总统计中返回的标签数上限。
+const TOP_TAG_LIMIT: u64 = 10;
+
+/// 全部录制的汇总统计：会话数、样本数、总时长、库文件大小、首末录制时间与常用标签。
+///
+/// 结果在 `cache` 中保留几秒，概览页频繁刷新时不会反复扫库。数据库不存在时返回全零，不创建。
+pub async fn get_recording_statistics(
+    db_path: &Path,
+    cache: &StatisticsCache,
+) -> anyhow::Result<RecordingStatistics> {
+    if let Some(statistics) = cache.get(db_path) {
+        return Ok(statistics);
+    }
+    let statistics = query_recording_statistics(db_path).await?;
+    cache.store(db_path, statistics.clone());
+    Ok(statistics)
+}
+
+async fn query_recording_statistics(db_path: &Path) -> anyhow::Result<RecordingStatistics> {
+    if !db_path.exists() {
+        return Ok(RecordingStatistics::default());
+    }
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let totals = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT COUNT(*) AS session_count,
+                    COALESCE(SUM(sample_count), 0) AS sample_count,
+                    COALESCE(SUM(stopped_at_ms - started_at_ms - COALESCE(paused_ms, 0)), 0)
+                        AS total_duration_ms,
+                    MIN(started_at_ms) AS first_started_at_ms,
+                    MAX(started_at_ms) AS last_started_at_ms
+             FROM recording_sessions;",
+        ))
+        .await
+        .context("aggregate recording sessions")?
+        .context("recording statistics query returned no row")?;
+    let session_count: i64 = totals.try_get("", "session_count")?;
+    let sample_count: i64 = totals.try_get("", "sample_count")?;
+
+    // 非法 JSON 的旧数据按无标签处理，与标签过滤一致
+    let tag_rows = db
+        .query_all(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT json_each.value AS tag, COUNT(*) AS count
+             FROM recording_sessions,
+                  json_each(CASE WHEN json_valid(tags) THEN tags ELSE '[]' END)
+             WHERE json_each.type = 'text'
+             GROUP BY json_each.value
+             ORDER BY count DESC, tag ASC
+             LIMIT ?;",
+            [TOP_TAG_LIMIT.into()],
+        ))
+        .await
+        .context("count recording tags")?;
+    let top_tags = tag_rows
+        .iter()
+        .map(|row| {
+            let count: i64 = row.try_get("", "count")?;
+            Ok(TagUsage {
+                tag: row.try_get("", "tag")?,
+                count: count as u64,
+            })
         })
-        .collect();
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(RecordingStatistics {
+        session_count: session_count as u64,
+        sample_count: sample_count as u64,
+        total_duration_ms: totals.try_get("", "total_duration_ms")?,
+        db_size_bytes: database_size_bytes(db_path),
+        first_started_at_ms: totals.try_get("", "first_started_at_ms")?,
+        last_started_at_ms: totals.try_get("", "last_started_at_ms")?,
+        top_tags,
+    })
+}
+
+/// 数据库占用的字节数：主文件加上尚未合并回主文件的 WAL。
+fn database_size_bytes(db_path: &Path) -> u64 {
+    let wal_path = PathBuf::from(format!("{}-wal", db_path.display()));
+    [db_path, wal_path.as_path()]
+        .into_iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// 会话时长 (ms)，不含暂停时间；未结束的会话为 NULL。
+fn recording_duration_expr() -> SimpleExpr {
+    use models::recording_sessions::Column;
+
+    Expr::col(Column::StoppedAtMs)
+        .sub(Expr::col(Column::StartedAtMs))
+        .sub(Expr::cust("COALESCE(paused_ms, 0)"))
+}
+
+/// 把查询条件翻译为 WHERE 子句。
+fn recording_filter(query: &RecordingQuery) -> Condition {
+    use models::recording_sessions::Column;
+
+    let mut condition = Condition::all();
+    if let Some(name) = query.name.as_deref().filter(|name| !name.is_empty()) {
+        // SQLite 的 LIKE 对 ASCII 不区分大小写；转义通配符，按字面子串匹配
+        let pattern = format!("%{}%", escape_like(name));
+        condition =
+            condition.add(Expr::col(Column::Name).like(LikeExpr::new(pattern).escape('\\')));
+    }
+    if !query.tags.is_empty() {
+        // tags 列是 JSON 数组字符串：展开后逐个整标签比较，不会命中其他标签的子串
+        let placeholders = vec!["?"; query.tags.len()].join(", ");
+        let sql = format!(
+            "EXISTS (SELECT 1 FROM json_each(CASE WHEN json_valid(tags) THEN tags ELSE '[]' END) \
+             WHERE json_each.value IN ({placeholders}))"
+        );
+        condition = condition.add(Expr::cust_with_values(sql, query.tags.iter().cloned()));
+    }
+    if let Some(from) = query.started_from_ms {
+        condition = condition.add(Column::StartedAtMs.gte(from));
+    }
+    if let Some(to) = query.started_to_ms {
+        condition = condition.add(Column::StartedAtMs.lte(to));
+    }
+    if let Some(min_duration_ms) = query.min_duration_ms {
+        condition = condition.add(Expr::expr(recording_duration_expr()).gte(min_duration_ms));
+    }
+    if let Some(device_id) = &query.device_id {
+        condition = condition.add(Column::DeviceId.eq(device_id.as_str()));
+    }
+    condition
+}
 
-    Ok(list)
+/// 转义 LIKE 通配符，配合 `ESCAPE '\'` 使用。
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 /// 更新录制会话元信息。
+///
+/// `notes` 为空时保留原备注，传入空白文本时清除备注。
 pub async fn update_recording_meta(
+    db_path: &Path,
     session_id: i64,
     name: Option<String>,
     tags: Option<Vec<String>>,
+    notes: Option<String>,
 ) -> anyhow::Result<RecordingMeta> {
-    let db_path = db::recording_db_path()?;
-    let db = db::connect(&db_path).await?;
+    let db = db::connect(db_path).await?;
     db::ensure_schema(&db).await?;
 
     let tags_json = tags
         .as_ref()
         .map(|value| serde_json::to_string(value).unwrap_or_default());
 
-    let update = models::recording_sessions::ActiveModel {
+    let mut update = models::recording_sessions::ActiveModel {
         id: Set(session_id),
         name: Set(name),
         tags: Set(tags_json),
         ..Default::default()
     };
+    if let Some(notes) = notes {
+        update.notes = Set(Some(notes).filter(|notes| !notes.trim().is_empty()));
+    }
     update
         .update(&db)
         .await
@@ -384,21 +1549,17 @@ pub async fn update_recording_meta(
         .context("fetch updated recording metadata")?
         .context("recording session not found")?;
 
-    Ok(RecordingMeta {
-        id: session.id,
-        started_at_ms: session.started_at_ms,
-        stopped_at_ms: session.stopped_at_ms,
-        sample_count: session.sample_count,
-        name: session.name,
-        tags: parse_tags(session.tags),
-    })
+    Ok(session_to_meta(session))
 }
 
 /// 获取录制样本。
-pub async fn get_recording_samples(session_id: i64) -> anyhow::Result<Vec<ResponseData>> {
-    let db_path = db::recording_db_path()?;
-    let db = db::connect(&db_path).await?;
+pub async fn get_recording_samples(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<ResponseData>> {
+    let db = db::connect(db_path).await?;
     db::ensure_schema(&db).await?;
+    let schema_version = session_schema_version(&db, session_id).await?;
 
     let samples = models::imu_samples::Entity::find()
         .filter(models::imu_samples::Column::SessionId.eq(session_id))
@@ -407,123 +1568,3063 @@ pub async fn get_recording_samples(session_id: i64) -> anyhow::Result<Vec<Respon
         .await
         .context("query recording samples")?;
 
-    let data = samples.into_iter().map(sample_to_response_data).collect();
+    let data = samples
+        .into_iter()
+        .map(|sample| sample_to_response_data(sample, schema_version))
+        .collect();
     Ok(data)
 }
 
-/// 将指定会话的样本导出为 CSV 文件，返回导出的文件路径。
-pub async fn export_session_csv(session_id: i64) -> anyhow::Result<std::path::PathBuf> {
-    use std::fmt::Write as FmtWrite;
+/// 按设备时间戳区间读取录制样本，编码为定长二进制记录（布局见 [`binary`](super::binary)）。
+///
+/// `from_ms` / `to_ms` 为闭区间边界，缺省表示不限。
+pub async fn get_recording_samples_binary(
+    db_path: &Path,
+    session_id: i64,
+    from_ms: Option<u64>,
+    to_ms: Option<u64>,
+) -> anyhow::Result<Vec<u8>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+    let schema_version = session_schema_version(&db, session_id).await?;
+
+    let mut query = models::imu_samples::Entity::find()
+        .filter(models::imu_samples::Column::SessionId.eq(session_id));
+    if let Some(from_ms) = from_ms {
+        query = query.filter(models::imu_samples::Column::TimestampMs.gte(from_ms as i64));
+    }
+    if let Some(to_ms) = to_ms {
+        query = query.filter(models::imu_samples::Column::TimestampMs.lte(to_ms as i64));
+    }
+    let samples = query
+        .order_by_asc(models::imu_samples::Column::TimestampMs)
+        .all(&db)
+        .await
+        .context("query recording samples")?;
+
+    let mut out = binary::begin_samples(samples.len());
+    for sample in samples {
+        let calc_timestamp_ms = sample.calc_timestamp_ms as u64;
+        binary::push_sample(
+            &mut out,
+            calc_timestamp_ms,
+            &sample_to_response_data(sample, schema_version),
+        );
+    }
+    Ok(out)
+}
 
-    let db_path = db::recording_db_path()?;
-    let db = db::connect(&db_path).await?;
+/// 按设备时间戳区间读取深度录制的阶段中间值，非深度录制的会话返回空列表。
+///
+/// `from_ms` / `to_ms` 为闭区间边界，缺省表示不限。
+pub async fn get_stage_samples(
+    db_path: &Path,
+    session_id: i64,
+    from_ms: Option<u64>,
+    to_ms: Option<u64>,
+) -> anyhow::Result<Vec<RecordingStageSample>> {
+    let db = db::connect(db_path).await?;
     db::ensure_schema(&db).await?;
 
-    let session = models::recording_sessions::Entity::find()
-        .filter(models::recording_sessions::Column::Id.eq(session_id))
-        .one(&db)
+    let mut query = models::imu_stage_samples::Entity::find()
+        .filter(models::imu_stage_samples::Column::SessionId.eq(session_id));
+    if let Some(from_ms) = from_ms {
+        query = query.filter(models::imu_stage_samples::Column::TimestampMs.gte(from_ms as i64));
+    }
+    if let Some(to_ms) = to_ms {
+        query = query.filter(models::imu_stage_samples::Column::TimestampMs.lte(to_ms as i64));
+    }
+    let stages = query
+        .order_by_asc(models::imu_stage_samples::Column::TimestampMs)
+        .order_by_asc(models::imu_stage_samples::Column::Id)
+        .all(&db)
         .await
-        .context("query reqcording session")?
-        .context("no session found")?;
+        .context("query recording stage samples")?;
 
-    let samples = models::imu_samples::Entity::find()
-        .filter(models::imu_samples::Column::SessionId.eq(session_id))
-        .order_by_asc(models::imu_samples::Column::TimestampMs)
+    Ok(stages.into_iter().map(stage_to_meta).collect())
+}
+
+/// 按设备时间戳对齐两段录制并计算对比指标。
+pub async fn compare_recordings(
+    db_path: &Path,
+    session_a: i64,
+    session_b: i64,
+) -> anyhow::Result<ComparisonReport> {
+    let mut tracks = Vec::with_capacity(2);
+    for session_id in [session_a, session_b] {
+        let samples = get_recording_samples(db_path, session_id).await?;
+        if samples.is_empty() {
+            return Err(anyhow!("session {session_id} has no samples"));
+        }
+        tracks.push(
+            samples
+                .iter()
+                .map(TrajectoryPoint::from)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    compare_tracks(&tracks[0], &tracks[1])
+        .with_context(|| format!("compare sessions {session_a} and {session_b}"))
+}
+
+/// 离线平滑录制轨迹。
+///
+/// 用 `config` 重跑整段原始样本得到静止判定与因果积分，以静止锚点回摊残余漂移。
+/// 重跑时关闭回溯修正，否则锚点处的残差会被修正两次。
+/// `write` 为真时把平滑位置写入 `smoothed_position_*` 列；返回降采样后的轨迹。
+pub async fn smooth_recording(
+    db_path: &Path,
+    session_id: i64,
+    mut config: ProcessorPipelineConfig,
+    write: bool,
+) -> anyhow::Result<SmoothedTrajectory> {
+    use models::imu_samples::{Column, Entity};
+
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let rows = Entity::find()
+        .filter(Column::SessionId.eq(session_id))
+        .order_by_asc(Column::TimestampMs)
         .all(&db)
         .await
         .context("query recording samples")?;
+    if rows.is_empty() {
+        bail!("session {session_id} has no samples");
+    }
 
-    let export_dir = db_path
-        .parent()
-        .context("db path has no parent")?
-        .join("exports");
-    std::fs::create_dir_all(&export_dir).context("create exports directory")?;
+    config.zupt.backward_correction = false;
+    let min_static_ms = config.segment.min_static_ms;
+    let track: Vec<(TrajectoryPoint, bool)> =
+        run_pipeline(config, rows.iter().map(sample_row_to_raw))
+            .into_iter()
+            .map(|frame| {
+                let point = TrajectoryPoint {
+                    timestamp_ms: frame.nav.timestamp_ms,
+                    position: frame.nav.position,
+                    velocity: frame.nav.velocity,
+                    attitude: frame.nav.attitude,
+                };
+                (point, frame.is_static)
+            })
+            .collect();
+    let positions = smooth_positions(&track, min_static_ms);
+    let anchors = anchor_mask(&track, min_static_ms);
+    let (Some((causal_last, _)), Some(&smoothed_last)) = (track.last(), positions.last()) else {
+        bail!("pipeline produced no frames for session {session_id}");
+    };
 
-    let now = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-    let file_path = export_dir.join(if let Some(name) = session.name {
-        format!("imu_{}.csv", name)
-    } else {
-        format!("imu_{now}.csv")
-    });
+    if write {
+        let txn = db.begin().await.context("begin smoothing transaction")?;
+        for ((point, _), position) in track.iter().zip(&positions) {
+            Entity::update_many()
+                .col_expr(Column::SmoothedPositionX, Expr::value(position.x))
+                .col_expr(Column::SmoothedPositionY, Expr::value(position.y))
+                .col_expr(Column::SmoothedPositionZ, Expr::value(position.z))
+                .filter(Column::SessionId.eq(session_id))
+                .filter(Column::TimestampMs.eq(point.timestamp_ms as i64))
+                .exec(&txn)
+                .await
+                .context("write smoothed position")?;
+        }
+        txn.commit().await.context("commit smoothed positions")?;
+    }
 
-    let mut csv = String::new();
-    writeln!(
-        csv,
-        "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
-         calc_velocity_x,calc_velocity_y,calc_velocity_z,\
-         calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z"
-    )?;
-    for s in samples {
-        writeln!(
-            csv,
-            "{},{},{},{},{},{},{},{},{},{},{}",
-            s.timestamp_ms,
-            s.calc_position_x,
-            s.calc_position_y,
-            s.calc_position_z,
-            s.calc_velocity_x,
-            s.calc_velocity_y,
-            s.calc_velocity_z,
-            s.calc_attitude_w,
-            s.calc_attitude_x,
-            s.calc_attitude_y,
-            s.calc_attitude_z,
-        )?;
-    }
-
-    tokio::fs::write(&file_path, csv)
-        .await
-        .context("write csv file")?;
-    Ok(file_path)
-}
-
-fn parse_tags(tags_json: Option<String>) -> Vec<String> {
-    tags_json
-        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
-        .unwrap_or_default()
+    let stride = track.len().div_ceil(MAX_PLOT_POINTS).max(1);
+    let points = track
+        .iter()
+        .zip(&positions)
+        .zip(&anchors)
+        .step_by(stride)
+        .map(|(((point, _), position), &is_anchor)| SmoothedPoint {
+            timestamp_ms: point.timestamp_ms,
+            causal_position: point.position,
+            smoothed_position: *position,
+            is_anchor,
+        })
+        .collect();
+
+    Ok(SmoothedTrajectory {
+        session_id,
+        frame_count: track.len(),
+        anchor_count: count_anchors(&anchors),
+        causal_final_position: causal_last.position,
+        smoothed_final_position: smoothed_last,
+        written: write,
+        points,
+    })
 }
 
-fn sample_to_response_data(sample: models::imu_samples::Model) -> ResponseData {
+/// 把会话 `session_id` 的计算位置与姿态换算到会话 `target_session_id` 的标定世界系。
+///
+/// 两段会话都需要录制时保存的姿态零位（见 [`RecordingCalibration`]），早期录制没有该信息时报错。
+/// 原始数据不受影响；`write` 为真时把换算结果写入 `transformed_*` 列。返回降采样后的轨迹。
+pub async fn transform_recording(
+    db_path: &Path,
+    session_id: i64,
+    target_session_id: i64,
+    write: bool,
+) -> anyhow::Result<TransformedTrajectory> {
     use math_f64::{DQuat, DVec3};
+    use models::imu_samples::{Column, Entity};
 
-    let accel_with_g = DVec3::new(
-        sample.accel_with_g_x,
-        sample.accel_with_g_y,
-        sample.accel_with_g_z,
-    );
-    ResponseData {
-        timestamp_ms: sample.timestamp_ms as u64,
-        accel: DVec3::new(
-            sample.accel_no_g_x,
-            sample.accel_no_g_y,
-            sample.accel_no_g_z,
-        ),
-        accel_with_g,
-        gyro: DVec3::new(sample.gyro_x, sample.gyro_y, sample.gyro_z),
-        attitude: DQuat::from_xyzw(
-            sample.calc_attitude_x,
-            sample.calc_attitude_y,
-            sample.calc_attitude_z,
-            sample.calc_attitude_w,
-        ),
-        velocity: DVec3::new(
-            sample.calc_velocity_x,
-            sample.calc_velocity_y,
-            sample.calc_velocity_z,
-        ),
-        position: DVec3::new(
-            sample.calc_position_x,
-            sample.calc_position_y,
-            sample.calc_position_z,
-        ),
-        // 回放场景也标记饱和段：用与实时路径相同的阈值 helper
-        accel_saturated: is_accel_saturated(accel_with_g),
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let from = session_calibration(&db, session_id).await?;
+    let to = session_calibration(&db, target_session_id).await?;
+    let rotation = relative_rotation(&from, &to);
+
+    let rows = Entity::find()
+        .filter(Column::SessionId.eq(session_id))
+        .order_by_asc(Column::TimestampMs)
+        .all(&db)
+        .await
+        .context("query recording samples")?;
+    if rows.is_empty() {
+        bail!("session {session_id} has no samples");
+    }
+    let track: Vec<(TrajectoryPoint, TrajectoryPoint)> = rows
+        .into_iter()
+        .map(|row| {
+            let point = TrajectoryPoint {
+                timestamp_ms: row.timestamp_ms as u64,
+                position: DVec3::new(
+                    row.calc_position_x,
+                    row.calc_position_y,
+                    row.calc_position_z,
+                ),
+                velocity: DVec3::new(
+                    row.calc_velocity_x,
+                    row.calc_velocity_y,
+                    row.calc_velocity_z,
+                ),
+                attitude: DQuat::from_xyzw(
+                    row.calc_attitude_x,
+                    row.calc_attitude_y,
+                    row.calc_attitude_z,
+                    row.calc_attitude_w,
+                ),
+            };
+            (point, transform_point(&point, rotation))
+        })
+        .collect();
+
+    if write {
+        let txn = db.begin().await.context("begin transform transaction")?;
+        for (_, point) in &track {
+            Entity::update_many()
+                .col_expr(Column::TransformedPositionX, Expr::value(point.position.x))
+                .col_expr(Column::TransformedPositionY, Expr::value(point.position.y))
+                .col_expr(Column::TransformedPositionZ, Expr::value(point.position.z))
+                .col_expr(Column::TransformedAttitudeW, Expr::value(point.attitude.w))
+                .col_expr(Column::TransformedAttitudeX, Expr::value(point.attitude.x))
+                .col_expr(Column::TransformedAttitudeY, Expr::value(point.attitude.y))
+                .col_expr(Column::TransformedAttitudeZ, Expr::value(point.attitude.z))
+                .filter(Column::SessionId.eq(session_id))
+                .filter(Column::TimestampMs.eq(point.timestamp_ms as i64))
+                .exec(&txn)
+                .await
+                .context("write transformed trajectory")?;
+        }
+        txn.commit()
+            .await
+            .context("commit transformed trajectory")?;
     }
+
+    let stride = track.len().div_ceil(MAX_PLOT_POINTS).max(1);
+    let points = track
+        .iter()
+        .step_by(stride)
+        .map(|(original, point)| TransformedPoint {
+            timestamp_ms: point.timestamp_ms,
+            original_position: original.position,
+            position: point.position,
+            attitude: point.attitude,
+        })
+        .collect();
+
+    Ok(TransformedTrajectory {
+        session_id,
+        target_session_id,
+        relative_rotation: rotation,
+        rotation_deg: rotation_deg(rotation),
+        gravity_residual_deg: gravity_residual_deg(&from, &to),
+        frame_count: track.len(),
+        written: write,
+        points,
+    })
 }
 
-fn now_ms() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|duration| duration.as_millis() as i64)
-        .unwrap_or_default()
+/// 读取会话录制时保存的标定，缺失时给出明确的错误。
+async fn session_calibration(
+    db: &DatabaseConnection,
+    session_id: i64,
+) -> anyhow::Result<RecordingCalibration> {
+    let session = models::recording_sessions::Entity::find_by_id(session_id)
+        .one(db)
+        .await
+        .context("query recording session")?
+        .with_context(|| format!("session {session_id} not found"))?;
+    parse_metadata(session.metadata)
+        .and_then(|metadata| metadata.calibration)
+        .with_context(|| {
+            format!(
+                "session {session_id} has no calibration metadata (recorded before calibration was stored)"
+            )
+        })
+}
+
+/// 获取录制标记，按设备时间戳升序（同一时间戳按插入顺序）。
+pub async fn get_recording_markers(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingMarker>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    query_markers(&db, session_id).await
+}
+
+async fn query_markers(
+    db: &sea_orm::DatabaseConnection,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingMarker>> {
+    let markers = models::recording_markers::Entity::find()
+        .filter(models::recording_markers::Column::SessionId.eq(session_id))
+        .order_by_asc(models::recording_markers::Column::TimestampMs)
+        .order_by_asc(models::recording_markers::Column::Id)
+        .all(db)
+        .await
+        .context("query recording markers")?;
+
+    Ok(markers.into_iter().map(marker_to_meta).collect())
+}
+
+/// 给会话的一段样本打上数据质量标记，返回新标记。
+///
+/// 区间按设备时间戳闭区间 `[from_ms, to_ms]` 存储，须落在会话样本的时间范围内，
+/// 与已有标记可以重叠。
+pub async fn flag_sample_range(
+    db_path: &Path,
+    session_id: i64,
+    from_ms: i64,
+    to_ms: i64,
+    label: &str,
+) -> anyhow::Result<RecordingFlag> {
+    let label = normalize_flag_label(label)?;
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    models::recording_sessions::Entity::find_by_id(session_id)
+        .one(&db)
+        .await
+        .context("query recording session")?
+        .context("no session found")?;
+    let span = db
+        .query_one(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT MIN(timestamp_ms) AS first_ms, MAX(timestamp_ms) AS last_ms
+             FROM imu_samples WHERE session_id = ?;",
+            [session_id.into()],
+        ))
+        .await
+        .context("query recording time range")?
+        .context("time range query returned no row")?;
+    let first_ms: Option<i64> = span.try_get("", "first_ms")?;
+    let last_ms: Option<i64> = span.try_get("", "last_ms")?;
+    let (Some(first_ms), Some(last_ms)) = (first_ms, last_ms) else {
+        bail!("session {session_id} has no samples to flag");
+    };
+    check_flag_range(from_ms, to_ms, first_ms, last_ms)?;
+
+    let flag = models::recording_flags::ActiveModel {
+        session_id: Set(session_id),
+        from_ms: Set(from_ms),
+        to_ms: Set(to_ms),
+        label: Set(label),
+        created_at_ms: Set(now_ms()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .context("insert recording flag")?;
+
+    Ok(flag_to_meta(flag))
+}
+
+/// 获取会话的数据质量标记，按区间起点升序（同一起点按创建顺序）。
+pub async fn get_recording_flags(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingFlag>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    query_flags(&db, session_id).await
+}
+
+async fn query_flags(
+    db: &sea_orm::DatabaseConnection,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingFlag>> {
+    let flags = models::recording_flags::Entity::find()
+        .filter(models::recording_flags::Column::SessionId.eq(session_id))
+        .order_by_asc(models::recording_flags::Column::FromMs)
+        .order_by_asc(models::recording_flags::Column::Id)
+        .all(db)
+        .await
+        .context("query recording flags")?;
+
+    Ok(flags.into_iter().map(flag_to_meta).collect())
+}
+
+/// 删除一条数据质量标记。
+pub async fn delete_recording_flag(db_path: &Path, flag_id: i64) -> anyhow::Result<()> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let result = models::recording_flags::Entity::delete_by_id(flag_id)
+        .exec(&db)
+        .await
+        .context("delete recording flag")?;
+    ensure!(result.rows_affected > 0, "no flag found with id {flag_id}");
+    Ok(())
+}
+
+/// 获取录制暂停区间，按暂停时间升序。
+pub async fn get_recording_pauses(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingPause>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let pauses = models::recording_pauses::Entity::find()
+        .filter(models::recording_pauses::Column::SessionId.eq(session_id))
+        .order_by_asc(models::recording_pauses::Column::PausedAtMs)
+        .order_by_asc(models::recording_pauses::Column::Id)
+        .all(&db)
+        .await
+        .context("query recording pauses")?;
+
+    Ok(pauses.into_iter().map(pause_to_meta).collect())
+}
+
+/// 获取录制中检测到的运动分段，按起点设备时间戳升序。
+pub async fn get_recording_segments(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingSegment>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    query_segments(&db, session_id).await
+}
+
+async fn query_segments(
+    db: &sea_orm::DatabaseConnection,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingSegment>> {
+    let segments = models::recording_segments::Entity::find()
+        .filter(models::recording_segments::Column::SessionId.eq(session_id))
+        .order_by_asc(models::recording_segments::Column::StartMs)
+        .order_by_asc(models::recording_segments::Column::Id)
+        .all(db)
+        .await
+        .context("query recording segments")?;
+
+    Ok(segments.into_iter().map(segment_to_meta).collect())
+}
+
+/// 获取录制同步事件（手动脉冲与敲击），按设备时间戳升序。
+pub async fn get_sync_events(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<RecordingSyncEvent>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let events = models::recording_sync_events::Entity::find()
+        .filter(models::recording_sync_events::Column::SessionId.eq(session_id))
+        .order_by_asc(models::recording_sync_events::Column::TimestampMs)
+        .order_by_asc(models::recording_sync_events::Column::Id)
+        .all(&db)
+        .await
+        .context("query recording sync events")?;
+
+    Ok(events.into_iter().map(sync_event_to_meta).collect())
+}
+
+/// 将指定会话的样本导出为 CSV 文件（数据库同目录下的 `exports/`），返回导出的文件路径。
+pub async fn export_session_csv(
+    db_path: &Path,
+    session_id: i64,
+    options: ExportOptions,
+) -> anyhow::Result<std::path::PathBuf> {
+    let export_dir = db_path
+        .parent()
+        .context("db path has no parent")?
+        .join("exports");
+    export_recording_csv_to_dir(db_path, session_id, &export_dir, options).await
+}
+
+/// 将指定会话的样本导出为 CSV 文件，写入 `dir`（不存在时创建），返回导出的文件路径。
+///
+/// 文件名由会话名称经 [`sanitize_filename`] 清洗得到（未命名时用导出时刻），
+/// 同名文件已存在时追加序号，不覆盖。样本按 `options.batch_rows` 分页查询、逐页写到
+/// 同目录的临时文件，完成后再改名；取消或失败时删除临时文件。
+/// `options.include_flags` 为真时末尾追加 `flags` 列，列出覆盖该样本的数据质量标记。
+/// 位置与速度按 `options.units` 换算，非国际单位时列名标注单位（见 [`UnitConverter::column_name`]）。
+pub async fn export_recording_csv_to_dir(
+    db_path: &Path,
+    session_id: i64,
+    dir: &Path,
+    options: ExportOptions,
+) -> anyhow::Result<std::path::PathBuf> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let session = models::recording_sessions::Entity::find()
+        .filter(models::recording_sessions::Column::Id.eq(session_id))
+        .one(&db)
+        .await
+        .context("query reqcording session")?
+        .context("no session found")?;
+    readable_schema_version(&session)?;
+    let flags = if options.include_flags {
+        Some(FlagSweep::new(query_flags(&db, session_id).await?))
+    } else {
+        None
+    };
+
+    let pages = models::imu_samples::Entity::find()
+        .filter(models::imu_samples::Column::SessionId.eq(session_id))
+        .order_by_asc(models::imu_samples::Column::TimestampMs)
+        .order_by_asc(models::imu_samples::Column::Id)
+        .paginate(&db, options.batch_rows.max(1) as u64);
+    let rows_total = pages.num_items().await.context("count recording samples")?;
+    let units = options.units;
+    let tracker = ExportTracker::new(session_id, rows_total, options);
+
+    std::fs::create_dir_all(dir).context("create exports directory")?;
+    let file_path = unique_path(dir, &export_file_stem(&session), "csv");
+    let partial = partial_path(&file_path);
+    match write_csv_pages(pages, &partial, &session, flags, units, tracker).await {
+        Ok(rows) => {
+            std::fs::rename(&partial, &file_path)
+                .with_context(|| format!("move csv file to {}", file_path.display()))?;
+            tracing::info!(session_id, rows, "导出 CSV {}", file_path.display());
+            Ok(file_path)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+/// 逐页查询样本写成 CSV 行，每页之前检查取消、之后报告进度，返回写出的行数。
+///
+/// 给出 `flags` 时每行末尾追加覆盖该样本的标记；位置与速度按 `units` 换算。
+async fn write_csv_pages(
+    mut pages: Paginator<'_, DatabaseConnection, SelectModel<models::imu_samples::Model>>,
+    path: &Path,
+    session: &models::recording_sessions::Model,
+    mut flags: Option<FlagSweep>,
+    units: UnitConverter,
+    mut tracker: ExportTracker,
+) -> anyhow::Result<u64> {
+    use std::{fmt::Write as FmtWrite, io::Write as IoWrite};
+
+    let mut file = std::io::BufWriter::new(
+        std::fs::File::create(path)
+            .with_context(|| format!("create csv file {}", path.display()))?,
+    );
+    // 录制期间建立过时钟同步时按会话参数换算主机时间，否则该列留空
+    let clock_sync = session.clock_offset_ms.zip(session.clock_skew_ppm);
+
+    let mut chunk = String::new();
+    // 版本信息以 `#` 注释行写在表头之前，导入时忽略
+    for (key, value) in csv_metadata_comments(session) {
+        writeln!(chunk, "# {key}: {value}")?;
+    }
+    chunk.push_str("timestamp_ms");
+    for (column, quantity) in CSV_UNIT_COLUMNS {
+        write!(chunk, ",{}", units.column_name(column, quantity))?;
+    }
+    chunk.push_str(",calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z,host_time_ms");
+    if flags.is_some() {
+        chunk.push_str(",flags");
+    }
+    chunk.push('\n');
+    file.write_all(chunk.as_bytes()).context("write csv file")?;
+    let mut bytes_written = chunk.len() as u64;
+
+    loop {
+        tracker.ensure_not_cancelled()?;
+        let Some(samples) = pages
+            .fetch_and_next()
+            .await
+            .context("query recording samples")?
+        else {
+            break;
+        };
+        chunk.clear();
+        for s in &samples {
+            let host_time_ms = clock_sync
+                .map(|(offset_ms, skew_ppm)| {
+                    let host_ms = device_to_host_ms(offset_ms, skew_ppm, s.timestamp_ms as u64);
+                    format!("{host_ms:.3}")
+                })
+                .unwrap_or_default();
+            write!(
+                chunk,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                s.timestamp_ms,
+                units.from_si(Quantity::Length, s.calc_position_x),
+                units.from_si(Quantity::Length, s.calc_position_y),
+                units.from_si(Quantity::Length, s.calc_position_z),
+                units.from_si(Quantity::Speed, s.calc_velocity_x),
+                units.from_si(Quantity::Speed, s.calc_velocity_y),
+                units.from_si(Quantity::Speed, s.calc_velocity_z),
+                s.calc_attitude_w,
+                s.calc_attitude_x,
+                s.calc_attitude_y,
+                s.calc_attitude_z,
+                host_time_ms,
+            )?;
+            if let Some(flags) = flags.as_mut() {
+                chunk.push(',');
+                chunk.push_str(flags.field_at(s.timestamp_ms));
+            }
+            chunk.push('\n');
+        }
+        file.write_all(chunk.as_bytes()).context("write csv file")?;
+        bytes_written += chunk.len() as u64;
+        tracker.advance(samples.len() as u64, bytes_written);
+    }
+
+    file.flush().context("write csv file")?;
+    Ok(tracker.finish(bytes_written))
+}
+
+/// CSV 中带单位的列（位置与速度），按此顺序写在 `timestamp_ms` 之后。
+const CSV_UNIT_COLUMNS: [(&str, Quantity); 6] = [
+    ("calc_position_x", Quantity::Length),
+    ("calc_position_y", Quantity::Length),
+    ("calc_position_z", Quantity::Length),
+    ("calc_velocity_x", Quantity::Speed),
+    ("calc_velocity_y", Quantity::Speed),
+    ("calc_velocity_z", Quantity::Speed),
+];
+
+/// CSV 表头前的 `# key: value` 注释行：结构版本与录制开始时的应用、设备版本信息，缺失的项不写。
+fn csv_metadata_comments(
+    session: &models::recording_sessions::Model,
+) -> Vec<(&'static str, String)> {
+    let mut comments = vec![("schema_version", stored_schema_version(session).to_string())];
+    let Some(metadata) = parse_metadata(session.metadata.clone()) else {
+        return comments;
+    };
+    comments.push(("app_version", metadata.app_version));
+    let device = metadata.device;
+    let device_fields = [
+        ("manufacturer", device.manufacturer),
+        ("model_number", device.model_number),
+        ("firmware_revision", device.firmware_revision),
+        ("hardware_revision", device.hardware_revision),
+        ("software_revision", device.software_revision),
+        ("imu_config", metadata.imu_config_hex),
+    ];
+    comments.extend(
+        device_fields
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, value))),
+    );
+    comments
+}
+
+/// 导出文件名主干：`imu_<清洗后的会话名称>`，未命名时用导出时刻（UTC）。
+pub(super) fn export_file_stem(session: &models::recording_sessions::Model) -> String {
+    match session.name.as_deref() {
+        Some(name) => format!("imu_{}", sanitize_filename(name, session.id)),
+        None => format!("imu_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S")),
+    }
+}
+
+/// CSV 导入时逐条列出的跳过行数上限，其余合并为一条警告。
+const MAX_IMPORT_WARNINGS: usize = 20;
+
+/// 每批写入的样本行数，避免超出 SQLite 单条语句的参数上限。
+const IMPORT_BATCH_ROWS: usize = 500;
+
+/// 导入 [`export_session_csv`] 格式的轨迹 CSV 为新的录制会话，返回会话与警告。
+///
+/// 以 `#` 开头的注释行（导出时写入的版本信息）忽略。
+/// 列按表头名称匹配，顺序不限，多余的列忽略；缺列时整体失败。位置与速度列带单位标注时
+/// 换算回国际单位。无法解析、含非有限值
+/// 或时间戳回退的行跳过并记入警告；没有一行可用时返回错误。
+/// CSV 只含计算结果，导入会话的原始传感器列为零，不能用于重新处理。
+pub async fn import_session_csv(
+    db_path: &Path,
+    csv_path: &Path,
+    name: Option<String>,
+) -> anyhow::Result<(RecordingMeta, Vec<String>)> {
+    let content = tokio::fs::read_to_string(csv_path)
+        .await
+        .with_context(|| format!("read csv file {}", csv_path.display()))?;
+    let (points, warnings) = parse_trajectory_csv(&content)?;
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        bail!("csv contains no valid rows: {}", warnings.join("; "));
+    };
+    let span_ms = (last.timestamp_ms - first.timestamp_ms) as i64;
+
+    let name = name.or_else(|| {
+        csv_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+    });
+
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+    let txn = db.begin().await.context("begin transaction")?;
+
+    let started_at_ms = now_ms();
+    let session_id = insert_session(&txn, started_at_ms, &None, &name, &None, None, None).await?;
+    for chunk in points.chunks(IMPORT_BATCH_ROWS) {
+        models::imu_samples::Entity::insert_many(
+            chunk.iter().map(|point| imported_sample(session_id, point)),
+        )
+        .exec(&txn)
+        .await
+        .context("insert imported samples")?;
+    }
+    let session = models::recording_sessions::ActiveModel {
+        id: Set(session_id),
+        stopped_at_ms: Set(Some(started_at_ms + span_ms)),
+        sample_count: Set(points.len() as i64),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .context("update imported session")?;
+    txn.commit().await.context("commit transaction")?;
+
+    tracing::info!(
+        session_id,
+        samples = points.len(),
+        skipped = warnings.len(),
+        "导入 CSV 录制 {}",
+        csv_path.display()
+    );
+    Ok((session_to_meta(session), warnings))
+}
+
+/// 解析轨迹 CSV，返回可用的行与被跳过行的警告。
+fn parse_trajectory_csv(content: &str) -> anyhow::Result<(Vec<TrajectoryPoint>, Vec<String>)> {
+    use math_f64::{DQuat, DVec3};
+
+    const COLUMNS: [&str; 11] = [
+        "timestamp_ms",
+        "calc_position_x",
+        "calc_position_y",
+        "calc_position_z",
+        "calc_velocity_x",
+        "calc_velocity_y",
+        "calc_velocity_z",
+        "calc_attitude_w",
+        "calc_attitude_x",
+        "calc_attitude_y",
+        "calc_attitude_z",
+    ];
+
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#'));
+    let (_, header) = lines.next().context("csv is empty")?;
+    let header: Vec<&str> = header.split(',').map(str::trim).collect();
+    // 位置与速度列可能带单位标注（如 `calc_position_x[cm]`），读入时换算回国际单位
+    let mut index = [0usize; COLUMNS.len()];
+    let mut units = [None; COLUMNS.len()];
+    for ((slot, unit), column) in index.iter_mut().zip(units.iter_mut()).zip(COLUMNS) {
+        let quantity = CSV_UNIT_COLUMNS
+            .iter()
+            .find(|(name, _)| *name == column)
+            .map(|(_, quantity)| *quantity);
+        (*slot, *unit) = header
+            .iter()
+            .enumerate()
+            .find_map(|(position, name)| match quantity {
+                Some(quantity) => UnitConverter::parse_column_name(name, quantity)
+                    .filter(|(base, _)| *base == column)
+                    .map(|(_, converter)| (position, Some((quantity, converter)))),
+                None => (*name == column).then_some((position, None)),
+            })
+            .with_context(|| format!("csv header is missing column {column}"))?;
+    }
+
+    let mut points: Vec<TrajectoryPoint> = Vec::new();
+    let mut skipped = Vec::new();
+    for (line_index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let parsed = (|| -> anyhow::Result<TrajectoryPoint> {
+            let field = |column: usize| {
+                fields
+                    .get(index[column])
+                    .copied()
+                    .with_context(|| format!("missing {}", COLUMNS[column]))
+            };
+            let timestamp_ms: u64 = field(0)?
+                .parse()
+                .with_context(|| format!("invalid {}", COLUMNS[0]))?;
+            let mut values = [0.0f64; 10];
+            for (offset, value) in values.iter_mut().enumerate() {
+                let column = offset + 1;
+                *value = field(column)?
+                    .parse()
+                    .with_context(|| format!("invalid {}", COLUMNS[column]))?;
+                ensure!(value.is_finite(), "non-finite {}", COLUMNS[column]);
+                if let Some((quantity, converter)) = units[column] {
+                    *value = converter.to_si(quantity, *value);
+                }
+            }
+            if let Some(previous) = points.last() {
+                ensure!(
+                    timestamp_ms >= previous.timestamp_ms,
+                    "timestamp {timestamp_ms} goes back before {}",
+                    previous.timestamp_ms
+                );
+            }
+            let [px, py, pz, vx, vy, vz, qw, qx, qy, qz] = values;
+            Ok(TrajectoryPoint {
+                timestamp_ms,
+                position: DVec3::new(px, py, pz),
+                velocity: DVec3::new(vx, vy, vz),
+                attitude: DQuat::from_xyzw(qx, qy, qz, qw),
+            })
+        })();
+        match parsed {
+            Ok(point) => points.push(point),
+            Err(error) => skipped.push(format!("line {}: {error:#}", line_index + 1)),
+        }
+    }
+
+    let mut warnings: Vec<String> = skipped.iter().take(MAX_IMPORT_WARNINGS).cloned().collect();
+    if skipped.len() > MAX_IMPORT_WARNINGS {
+        warnings.push(format!(
+            "{} more rows skipped",
+            skipped.len() - MAX_IMPORT_WARNINGS
+        ));
+    }
+    Ok((points, warnings))
+}
+
+fn imported_sample(session_id: i64, point: &TrajectoryPoint) -> models::imu_samples::ActiveModel {
+    models::imu_samples::ActiveModel {
+        session_id: Set(session_id),
+        timestamp_ms: Set(point.timestamp_ms as i64),
+        accel_no_g_x: Set(0.0),
+        accel_no_g_y: Set(0.0),
+        accel_no_g_z: Set(0.0),
+        accel_with_g_x: Set(0.0),
+        accel_with_g_y: Set(0.0),
+        accel_with_g_z: Set(0.0),
+        gyro_x: Set(0.0),
+        gyro_y: Set(0.0),
+        gyro_z: Set(0.0),
+        quat_w: Set(1.0),
+        quat_x: Set(0.0),
+        quat_y: Set(0.0),
+        quat_z: Set(0.0),
+        angle_x: Set(0.0),
+        angle_y: Set(0.0),
+        angle_z: Set(0.0),
+        offset_x: Set(0.0),
+        offset_y: Set(0.0),
+        offset_z: Set(0.0),
+        accel_nav_x: Set(0.0),
+        accel_nav_y: Set(0.0),
+        accel_nav_z: Set(0.0),
+        calc_attitude_w: Set(point.attitude.w),
+        calc_attitude_x: Set(point.attitude.x),
+        calc_attitude_y: Set(point.attitude.y),
+        calc_attitude_z: Set(point.attitude.z),
+        calc_velocity_x: Set(point.velocity.x),
+        calc_velocity_y: Set(point.velocity.y),
+        calc_velocity_z: Set(point.velocity.z),
+        calc_position_x: Set(point.position.x),
+        calc_position_y: Set(point.position.y),
+        calc_position_z: Set(point.position.z),
+        calc_timestamp_ms: Set(point.timestamp_ms as i64),
+        motion_state: Set(MotionState::Unknown.as_str().to_string()),
+        position_source: Set(PositionSource::Integrated.as_str().to_string()),
+        ..Default::default()
+    }
+}
+
+pub(super) fn parse_tags(tags_json: Option<String>) -> Vec<String> {
+    tags_json
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn session_to_meta(session: models::recording_sessions::Model) -> RecordingMeta {
+    let schema_version = stored_schema_version(&session);
+    RecordingMeta {
+        id: session.id,
+        started_at_ms: session.started_at_ms,
+        stopped_at_ms: session.stopped_at_ms,
+        sample_count: session.sample_count,
+        name: session.name,
+        tags: parse_tags(session.tags),
+        stop_reason: session.stop_reason,
+        parent_session_id: session.parent_session_id,
+        interrupted: session.interrupted,
+        schema_version,
+        paused_ms: session.paused_ms.unwrap_or_default(),
+        heading_yaw_deg: session.heading_yaw_deg,
+        heading_aligned_at_ms: session.heading_aligned_at_ms,
+        metadata: parse_metadata(session.metadata),
+        notes: session.notes,
+    }
+}
+
+/// 解析会话的版本信息，早期录制没有该列，无法解析时同样视为缺失。
+fn parse_metadata(metadata_json: Option<String>) -> Option<RecordingMetadata> {
+    metadata_json.and_then(|raw| serde_json::from_str(&raw).ok())
+}
+
+/// 会话写入时的结构版本，早期录制没有该列，视为 1。
+fn stored_schema_version(session: &models::recording_sessions::Model) -> u32 {
+    session
+        .schema_version
+        .map_or(1, |version| u32::try_from(version).unwrap_or(u32::MAX))
+}
+
+/// 检查会话能否由当前应用读取：比 [`RESPONSE_SCHEMA_VERSION`] 新的录制可能含有
+/// 无法理解的字段或含义，拒绝读取而不是给出错误的数据。
+pub(super) fn readable_schema_version(
+    session: &models::recording_sessions::Model,
+) -> anyhow::Result<u32> {
+    let version = stored_schema_version(session);
+    ensure!(
+        version <= RESPONSE_SCHEMA_VERSION,
+        "recording session {} was written with schema version {version}, \
+         but this app only reads schema versions 1..={RESPONSE_SCHEMA_VERSION}; \
+         upgrade the app to open it",
+        session.id
+    );
+    Ok(version)
+}
+
+/// 读取会话样本前查询其结构版本；会话不存在时没有样本可读，按当前版本处理。
+async fn session_schema_version<C: ConnectionTrait>(
+    db: &C,
+    session_id: i64,
+) -> anyhow::Result<u32> {
+    let session = models::recording_sessions::Entity::find_by_id(session_id)
+        .one(db)
+        .await
+        .context("query recording session")?;
+    match session {
+        Some(session) => readable_schema_version(&session),
+        None => Ok(RESPONSE_SCHEMA_VERSION),
+    }
+}
+
+fn segment_to_meta(segment: models::recording_segments::Model) -> RecordingSegment {
+    use math_f64::DVec3;
+
+    let displacement = DVec3::new(
+        segment.displacement_x,
+        segment.displacement_y,
+        segment.displacement_z,
+    );
+    RecordingSegment {
+        id: segment.id,
+        session_id: segment.session_id,
+        start_ms: segment.start_ms,
+        end_ms: segment.end_ms,
+        duration_ms: segment.duration_ms,
+        path_length_m: segment.path_length_m,
+        displacement,
+        displacement_m: displacement.length(),
+        peak_speed_mps: segment.peak_speed_mps,
+    }
+}
+
+fn sync_event_to_meta(event: models::recording_sync_events::Model) -> RecordingSyncEvent {
+    let host_time_ms = match (event.clock_offset_ms, event.clock_skew_ppm) {
+        (Some(offset_ms), Some(skew_ppm)) => Some(device_to_host_ms(
+            offset_ms,
+            skew_ppm,
+            event.timestamp_ms.max(0) as u64,
+        )),
+        _ => None,
+    };
+    RecordingSyncEvent {
+        id: event.id,
+        session_id: event.session_id,
+        kind: SyncEventKind::from_stored(&event.kind),
+        source: event.source,
+        timestamp_ms: event.timestamp_ms,
+        host_ts_ms: event.host_ts_ms,
+        clock_offset_ms: event.clock_offset_ms,
+        clock_skew_ppm: event.clock_skew_ppm,
+        host_time_ms,
+        peak_g: event.peak_g,
+    }
+}
+
+fn stage_to_meta(stage: models::imu_stage_samples::Model) -> RecordingStageSample {
+    use math_f64::DVec3;
+
+    RecordingStageSample {
+        timestamp_ms: stage.timestamp_ms as u64,
+        calibrated_accel: DVec3::new(stage.cal_accel_x, stage.cal_accel_y, stage.cal_accel_z),
+        calibrated_gyro: DVec3::new(stage.cal_gyro_x, stage.cal_gyro_y, stage.cal_gyro_z),
+        filtered_accel: DVec3::new(stage.filt_accel_x, stage.filt_accel_y, stage.filt_accel_z),
+        filtered_gyro: DVec3::new(stage.filt_gyro_x, stage.filt_gyro_y, stage.filt_gyro_z),
+        linear_accel: DVec3::new(
+            stage.linear_accel_x,
+            stage.linear_accel_y,
+            stage.linear_accel_z,
+        ),
+        pre_zupt_velocity: DVec3::new(
+            stage.pre_zupt_velocity_x,
+            stage.pre_zupt_velocity_y,
+            stage.pre_zupt_velocity_z,
+        ),
+        pre_zupt_position: DVec3::new(
+            stage.pre_zupt_position_x,
+            stage.pre_zupt_position_y,
+            stage.pre_zupt_position_z,
+        ),
+        post_zupt_velocity: DVec3::new(
+            stage.post_zupt_velocity_x,
+            stage.post_zupt_velocity_y,
+            stage.post_zupt_velocity_z,
+        ),
+        post_zupt_position: DVec3::new(
+            stage.post_zupt_position_x,
+            stage.post_zupt_position_y,
+            stage.post_zupt_position_z,
+        ),
+        is_static: stage.is_static,
+    }
+}
+
+fn pause_to_meta(pause: models::recording_pauses::Model) -> RecordingPause {
+    RecordingPause {
+        id: pause.id,
+        session_id: pause.session_id,
+        paused_at_ms: pause.paused_at_ms,
+        resumed_at_ms: pause.resumed_at_ms,
+        duration_ms: pause.resumed_at_ms - pause.paused_at_ms,
+        timestamp_ms: pause.timestamp_ms,
+    }
+}
+
+fn flag_to_meta(flag: models::recording_flags::Model) -> RecordingFlag {
+    RecordingFlag {
+        id: flag.id,
+        session_id: flag.session_id,
+        from_ms: flag.from_ms,
+        to_ms: flag.to_ms,
+        label: flag.label,
+        created_at_ms: flag.created_at_ms,
+    }
+}
+
+fn marker_to_meta(marker: models::recording_markers::Model) -> RecordingMarker {
+    RecordingMarker {
+        id: marker.id,
+        session_id: marker.session_id,
+        timestamp_ms: marker.timestamp_ms,
+        host_ts_ms: marker.host_ts_ms,
+        label: marker.label,
+    }
+}
+
+/// 把样本行还原为当前结构的 `ResponseData`，按会话的 `schema_version` 为旧录制
+/// 缺失的字段补默认值。
+fn sample_to_response_data(
+    sample: models::imu_samples::Model,
+    schema_version: u32,
+) -> ResponseData {
+    use math_f64::{DQuat, DVec3};
+
+    use crate::processor::navigator::{MotionState, ZuptState};
+
+    let motion_state = MotionState::from_stored(&sample.motion_state);
+    let position_source = PositionSource::from_stored(&sample.position_source);
+    let accel_with_g = DVec3::new(
+        sample.accel_with_g_x,
+        sample.accel_with_g_y,
+        sample.accel_with_g_z,
+    );
+    ResponseData {
+        schema_version: RESPONSE_SCHEMA_VERSION,
+        timestamp_ms: sample.timestamp_ms as u64,
+        accel: DVec3::new(
+            sample.accel_no_g_x,
+            sample.accel_no_g_y,
+            sample.accel_no_g_z,
+        ),
+        accel_with_g,
+        gyro: DVec3::new(sample.gyro_x, sample.gyro_y, sample.gyro_z),
+        attitude: DQuat::from_xyzw(
+            sample.calc_attitude_x,
+            sample.calc_attitude_y,
+            sample.calc_attitude_z,
+            sample.calc_attitude_w,
+        ),
+        velocity: DVec3::new(
+            sample.calc_velocity_x,
+            sample.calc_velocity_y,
+            sample.calc_velocity_z,
+        ),
+        position: DVec3::new(
+            sample.calc_position_x,
+            sample.calc_position_y,
+            sample.calc_position_z,
+        ),
+        // 回放场景也标记饱和段：用与实时路径相同的阈值 helper
+        accel_saturated: is_accel_saturated(accel_with_g),
+        motion_state,
+        // 录制只保存运动状态，范数与锁定位置不落盘
+        zupt: ZuptState {
+            is_static: motion_state == MotionState::Static,
+            ..ZuptState::default()
+        },
+        position_source,
+        // 派生信号不落盘，回放时可由加速度/角速度重新差分
+        jerk: None,
+        ang_accel: None,
+        // 版本 1 的录制没有该列，回放时视为 0
+        position_sigma_m: match schema_version {
+            1 => 0.0,
+            _ => sample.position_sigma_m.unwrap_or_default(),
+        },
+        // 主机时间按会话的时钟同步参数换算，见 CSV 导出
+        host_time_estimate_ms: None,
+        // 调试向量可由样本重新推算，不落盘
+        debug_vectors: None,
+    }
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use math_f64::{DQuat, DVec3};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    use super::*;
+    use crate::{
+        processor::{
+            navigator::NavState,
+            scheduling::{SchedulingStatus, ThreadHintsReport},
+            shared::DeviceTimeMs,
+        },
+        recorder::{
+            start_recording,
+            test_support::{
+                feed, frame, options_with_space, remove_db, start, temp_db, FakeDiskSpace,
+            },
+        },
+    };
+
+    async fn marker(
+        control_tx: &Sender<RecorderCommand>,
+        label: &str,
+    ) -> anyhow::Result<RecordingMarker> {
+        add_recording_marker(control_tx, label.to_string()).await
+    }
+
+    #[tokio::test]
+    async fn markers_align_with_recorded_samples() {
+        let db_path = temp_db("markers");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        // 未录制时插入标记应报错
+        assert!(marker(&control_tx, "too early").await.is_err());
+
+        let (reply_tx, reply_rx) = flume::bounded(1);
+        control_tx
+            .send(RecorderCommand::Start {
+                db_path: db_path.clone(),
+                device_id: None,
+                name: Some("markers".into()),
+                tags: None,
+                split: RecordingSplit::default(),
+                metadata: None,
+                deep: false,
+                reply: reply_tx,
+            })
+            .unwrap();
+        let session_id = reply_rx
+            .recv_async()
+            .await
+            .unwrap()
+            .unwrap()
+            .session_id
+            .unwrap();
+
+        // 尚无样本：退回主机时间
+        let before_samples = marker(&control_tx, "before samples").await.unwrap();
+        assert_eq!(before_samples.timestamp_ms, before_samples.host_ts_ms);
+
+        for ts in [1000, 1004, 1008] {
+            data_tx.send(frame(ts)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let first = marker(&control_tx, "gesture 1").await.unwrap();
+        let second = marker(&control_tx, "sensor bumped").await.unwrap();
+        assert_eq!(first.timestamp_ms, 1008);
+        assert_eq!(second.timestamp_ms, 1008);
+
+        data_tx.send(frame(1012)).unwrap();
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let third = marker(&control_tx, "gesture 2").await.unwrap();
+        assert_eq!(third.timestamp_ms, 1012);
+
+        let status = recording_status(&control_tx).await.unwrap();
+        assert!(status.recording);
+        assert_eq!(status.session_id, Some(session_id));
+        assert_eq!(status.sample_count, Some(4));
+        assert_eq!(status.name.as_deref(), Some("markers"));
+
+        let (reply_tx, reply_rx) = flume::bounded(1);
+        control_tx
+            .send(RecorderCommand::Stop { reply: reply_tx })
+            .unwrap();
+        let status = reply_rx.recv_async().await.unwrap().unwrap();
+        assert_eq!(status.sample_count, Some(4));
+        assert!(marker(&control_tx, "after stop").await.is_err());
+        assert!(!recording_status(&control_tx).await.unwrap().recording);
+
+        let db = db::connect(&db_path).await.unwrap();
+        let markers = query_markers(&db, session_id).await.unwrap();
+        let labels: Vec<&str> = markers.iter().map(|m| m.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["gesture 1", "sensor bumped", "gesture 2", "before samples"]
+        );
+
+        // 每个设备时间标记都能在样本中找到对应时间戳
+        let sample_ts: Vec<i64> = models::imu_samples::Entity::find()
+            .filter(models::imu_samples::Column::SessionId.eq(session_id))
+            .all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        for m in &markers[..3] {
+            assert!(sample_ts.contains(&m.timestamp_ms));
+        }
+
+        drop(db);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn segments_are_stored_with_active_session() {
+        let db_path = temp_db("segments");
+
+        let (_data_tx, data_rx) = flume::bounded::<OutputFrame>(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let segment = |start_ms: u64, x: f64| MotionSegment {
+            start_ms,
+            end_ms: start_ms + 300,
+            duration_ms: 300,
+            path_length_m: x * 1.5,
+            displacement: DVec3::new(x, 0.0, 0.0),
+            displacement_m: x,
+            peak_speed_mps: 0.4,
+        };
+        // 未录制时丢弃
+        control_tx
+            .send(RecorderCommand::Segment {
+                segment: segment(0, 0.1),
+            })
+            .unwrap();
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        for (start_ms, x) in [(2000, 0.3), (1000, 0.2)] {
+            control_tx
+                .send(RecorderCommand::Segment {
+                    segment: segment(start_ms, x),
+                })
+                .unwrap();
+        }
+        stop_recording(&control_tx).await.unwrap();
+
+        let segments = get_recording_segments(&db_path, session_id).await.unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].start_ms, 1000);
+        assert_eq!(segments[0].end_ms, 1300);
+        assert!((segments[0].displacement_m - 0.2).abs() < 1e-12);
+        assert!((segments[1].path_length_m - 0.45).abs() < 1e-12);
+        assert_eq!(segments[1].displacement, DVec3::new(0.3, 0.0, 0.0));
+
+        delete_recording(&db_path, session_id).await.unwrap();
+        assert!(get_recording_segments(&db_path, session_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn sync_events_carry_device_host_and_clock_sync_times() {
+        let db_path = temp_db("sync_events");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let tap = SyncTap {
+            timestamp_ms: 1004,
+            peak_g: 5.5,
+            duration_ms: 8,
+        };
+        // 未录制时手动脉冲报错，敲击直接丢弃
+        assert!(record_sync_pulse(&control_tx, "camera".into())
+            .await
+            .is_err());
+        control_tx.send(RecorderCommand::SyncTap { tap }).unwrap();
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        // 尚无时钟同步结果
+        data_tx.send(frame(1000)).unwrap();
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let before_sync = record_sync_pulse(&control_tx, "clapper".into())
+            .await
+            .unwrap();
+        assert_eq!(before_sync.timestamp_ms, 1000);
+        assert_eq!(before_sync.host_time_ms, None);
+
+        let sync = ClockSyncEstimate {
+            offset_ms: 1_700_000_000_000.0,
+            skew_ppm: 0.0,
+            anchors: 5,
+            residual_ms: 0.5,
+        };
+        let mut synced = frame(1008);
+        synced.clock_sync = Some(sync);
+        data_tx.send(synced).unwrap();
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        control_tx.send(RecorderCommand::SyncTap { tap }).unwrap();
+        let pulse = record_sync_pulse(&control_tx, "camera".into())
+            .await
+            .unwrap();
+        assert_eq!(pulse.kind, SyncEventKind::Manual);
+        assert_eq!(pulse.timestamp_ms, 1008);
+        assert_eq!(pulse.clock_offset_ms, Some(sync.offset_ms));
+        assert_eq!(
+            pulse.host_time_ms,
+            Some(sync.host_time_ms(DeviceTimeMs(1008)))
+        );
+        assert!(pulse.host_ts_ms >= before_sync.host_ts_ms);
+        stop_recording(&control_tx).await.unwrap();
+
+        // 按设备时间排序：敲击峰值早于之后的手动脉冲
+        let events = get_sync_events(&db_path, session_id).await.unwrap();
+        let sources: Vec<&str> = events.iter().map(|e| e.source.as_str()).collect();
+        assert_eq!(sources, ["clapper", "accel_tap", "camera"]);
+        let tap_event = &events[1];
+        assert_eq!(tap_event.kind, SyncEventKind::Tap);
+        assert_eq!(tap_event.timestamp_ms, 1004);
+        assert_eq!(tap_event.peak_g, Some(5.5));
+        assert_eq!(
+            tap_event.host_time_ms,
+            Some(sync.host_time_ms(DeviceTimeMs(1004)))
+        );
+        assert_eq!(events[2].peak_g, None);
+
+        delete_recording(&db_path, session_id).await.unwrap();
+        assert!(get_sync_events(&db_path, session_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn smoothing_writes_positions_back_to_samples() {
+        let db_path = temp_db("smooth");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        for i in 0..1500 {
+            data_tx.send(frame(1000 + i * 4)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        stop_recording(&control_tx).await.unwrap();
+
+        let config = ProcessorPipelineConfig::default();
+        let preview = smooth_recording(&db_path, session_id, config.clone(), false)
+            .await
+            .unwrap();
+        assert!(!preview.written);
+        assert!(preview.frame_count > 1400, "{}", preview.frame_count);
+        // 降采样到绘图点数以内，静止录制只有一个锚点段且不产生位移
+        assert!(preview.points.len() <= MAX_PLOT_POINTS);
+        assert_eq!(preview.anchor_count, 1);
+        assert!(preview.smoothed_final_position.length() < 1e-9);
+        assert!(preview.points.last().unwrap().is_anchor);
+
+        let unwritten = models::imu_samples::Entity::find()
+            .filter(models::imu_samples::Column::SmoothedPositionX.is_not_null())
+            .all(&db::connect(&db_path).await.unwrap())
+            .await
+            .unwrap();
+        assert!(unwritten.is_empty());
+
+        let written = smooth_recording(&db_path, session_id, config.clone(), true)
+            .await
+            .unwrap();
+        assert!(written.written);
+        let rows = models::imu_samples::Entity::find()
+            .filter(models::imu_samples::Column::SmoothedPositionX.is_not_null())
+            .all(&db::connect(&db_path).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), written.frame_count);
+        assert!(rows.iter().all(|row| row.smoothed_position_z == Some(0.0)));
+
+        assert!(smooth_recording(&db_path, session_id + 1, config, false)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("has no samples"));
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn binary_samples_match_json_path_and_time_range() {
+        let db_path = temp_db("binary");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        for i in 0..100 {
+            data_tx.send(frame(1000 + i * 4)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        stop_recording(&control_tx).await.unwrap();
+
+        let count = |bytes: &[u8]| u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+        let timestamp = |bytes: &[u8], i: usize| {
+            let at = binary::SAMPLE_BINARY_HEADER_SIZE + i * binary::SAMPLE_BINARY_RECORD_SIZE;
+            u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+        };
+
+        let samples = get_recording_samples(&db_path, session_id).await.unwrap();
+        let all = get_recording_samples_binary(&db_path, session_id, None, None)
+            .await
+            .unwrap();
+        assert_eq!(count(&all), samples.len() as u64);
+        let mut expected = binary::begin_samples(samples.len());
+        for data in &samples {
+            binary::push_sample(&mut expected, data.timestamp_ms, data);
+        }
+        assert_eq!(all, expected);
+
+        // 闭区间 [1040, 1080] 含 11 帧
+        let range = get_recording_samples_binary(&db_path, session_id, Some(1040), Some(1080))
+            .await
+            .unwrap();
+        assert_eq!(count(&range), 11);
+        assert_eq!(timestamp(&range, 0), 1040);
+        assert_eq!(timestamp(&range, 10), 1080);
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn csv_import_and_bulk_delete_report_skipped_items() {
+        let db_path = temp_db("import");
+        let csv_path = db_path.with_extension("csv");
+        // 列顺序与导出不同，含一列多余列
+        std::fs::write(
+            &csv_path,
+            "calc_attitude_w,timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+             calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+             calc_attitude_x,calc_attitude_y,calc_attitude_z,note\n\
+             1,1000,0.1,0,0,0,0,0,0,0,0,a\n\
+             1,1004,0.2,0,0,0,0,0,0,0,0,b\n\
+             1,oops,0.3,0,0,0,0,0,0,0,0,c\n\
+             1,1002,0.3,0,0,0,0,0,0,0,0,d\n\
+             1,1008,NaN,0,0,0,0,0,0,0,0,e\n\
+             \n\
+             1,1012,0.4,0,0,0,0,0,0,0,0,f\n",
+        )
+        .unwrap();
+
+        let (meta, warnings) = import_session_csv(&db_path, &csv_path, None).await.unwrap();
+        assert_eq!(meta.sample_count, 3);
+        assert_eq!(
+            meta.name.as_deref(),
+            csv_path.file_stem().and_then(|s| s.to_str())
+        );
+        assert_eq!(meta.stopped_at_ms, Some(meta.started_at_ms + 12));
+        assert_eq!(warnings.len(), 3, "{warnings:?}");
+        assert!(warnings[0].starts_with("line 4: invalid timestamp_ms"));
+        assert!(warnings[1].starts_with("line 5: timestamp 1002 goes back"));
+        assert!(warnings[2].starts_with("line 6: non-finite calc_position_x"));
+
+        let samples = get_recording_samples(&db_path, meta.id).await.unwrap();
+        let xs: Vec<f64> = samples.iter().map(|s| s.position.x).collect();
+        assert_eq!(xs, [0.1, 0.2, 0.4]);
+
+        // 导出再导入：格式互通，没有警告
+        let exported = export_session_csv(&db_path, meta.id, ExportOptions::default())
+            .await
+            .unwrap();
+        let (reimported, warnings) =
+            import_session_csv(&db_path, &exported, Some("round trip".into()))
+                .await
+                .unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(reimported.sample_count, 3);
+        assert_eq!(reimported.name.as_deref(), Some("round trip"));
+
+        // 缺列整体失败
+        std::fs::write(&csv_path, "timestamp_ms,calc_position_x\n1000,0.1\n").unwrap();
+        let error = import_session_csv(&db_path, &csv_path, None)
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("missing column calc_position_y"));
+
+        let missing = reimported.id + 100;
+        let (deleted, warnings) = delete_recordings(&db_path, &[meta.id, missing, reimported.id])
+            .await
+            .unwrap();
+        assert_eq!(deleted, [meta.id, reimported.id]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with(&format!("session {missing}: no session found")));
+        assert!(list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items
+            .is_empty());
+        assert!(get_recording_samples(&db_path, meta.id)
+            .await
+            .unwrap()
+            .is_empty());
+        // 一个都没删掉时报错
+        assert!(delete_recordings(&db_path, &[missing]).await.is_err());
+
+        let _ = std::fs::remove_file(&csv_path);
+        let _ = std::fs::remove_file(&exported);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn csv_export_to_dir_sanitizes_name_and_keeps_earlier_exports() {
+        let db_path = temp_db("export_dir");
+        let csv_path = db_path.with_extension("csv");
+        let dir = db_path.with_extension("exports");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::write(
+            &csv_path,
+            "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+             calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+             calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z\n\
+             1000,0.1,0,0,0,0,0,1,0,0,0\n",
+        )
+        .unwrap();
+        let (meta, _) =
+            import_session_csv(&db_path, &csv_path, Some(r#"test: 3/5 "final""#.into()))
+                .await
+                .unwrap();
+
+        let first = export_recording_csv_to_dir(&db_path, meta.id, &dir, ExportOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(first, dir.join("imu_test_ 3_5 _final_.csv"));
+        let second = export_recording_csv_to_dir(&db_path, meta.id, &dir, ExportOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(second, dir.join("imu_test_ 3_5 _final_ (2).csv"));
+        assert!(first.exists() && second.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_file(&csv_path);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn csv_export_converts_units_and_import_reads_them_back() {
+        use crate::units::{LengthUnit, SpeedUnit, UnitPreferences};
+
+        let db_path = temp_db("export_units");
+        let csv_path = db_path.with_extension("csv");
+        std::fs::write(
+            &csv_path,
+            "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+             calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+             calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z\n\
+             1000,0.25,-1.5,0,1.25,0,0,1,0,0,0\n",
+        )
+        .unwrap();
+        let (meta, _) = import_session_csv(&db_path, &csv_path, None).await.unwrap();
+
+        let options = ExportOptions {
+            units: UnitConverter::new(UnitPreferences {
+                length: LengthUnit::Centimeter,
+                speed: SpeedUnit::KilometerPerHour,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let exported = export_session_csv(&db_path, meta.id, options)
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(&exported).unwrap();
+        let mut lines = content.lines().filter(|line| !line.starts_with('#'));
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header[1], "calc_position_x[cm]");
+        assert_eq!(header[4], "calc_velocity_x[km/h]");
+        assert_eq!(header[7], "calc_attitude_w");
+        let row: Vec<f64> = lines
+            .next()
+            .unwrap()
+            .split(',')
+            .take(7)
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert_eq!(row[1..3], [25.0, -150.0]);
+        assert!((row[4] - 4.5).abs() < 1e-9);
+
+        // 带单位的列名导入时换算回国际单位
+        let (reimported, warnings) = import_session_csv(&db_path, &exported, None).await.unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        let samples = get_recording_samples(&db_path, reimported.id)
+            .await
+            .unwrap();
+        assert_eq!(samples[0].position.x, 0.25);
+        assert_eq!(samples[0].position.y, -1.5);
+        assert!((samples[0].velocity.x - 1.25).abs() < 1e-9);
+
+        let _ = std::fs::remove_file(&csv_path);
+        let _ = std::fs::remove_file(&exported);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn notes_and_flags_round_trip_and_mark_exported_rows() {
+        let db_path = temp_db("flags");
+        let csv_path = db_path.with_extension("csv");
+        std::fs::write(
+            &csv_path,
+            "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+             calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+             calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z\n\
+             1000,0,0,0,0,0,0,1,0,0,0\n\
+             1010,0,0,0,0,0,0,1,0,0,0\n\
+             1020,0,0,0,0,0,0,1,0,0,0\n\
+             1030,0,0,0,0,0,0,1,0,0,0\n\
+             1040,0,0,0,0,0,0,1,0,0,0\n",
+        )
+        .unwrap();
+        let (meta, _) = import_session_csv(&db_path, &csv_path, Some("walk".into()))
+            .await
+            .unwrap();
+        assert_eq!(meta.notes, None);
+
+        // 不传备注时保留原值，空白文本清除
+        let notes = "sensor slipped\nre-taped at 1030";
+        let updated = update_recording_meta(&db_path, meta.id, None, None, Some(notes.into()))
+            .await
+            .unwrap();
+        assert_eq!(updated.notes.as_deref(), Some(notes));
+        let updated = update_recording_meta(&db_path, meta.id, Some("walk".into()), None, None)
+            .await
+            .unwrap();
+        assert_eq!(updated.notes.as_deref(), Some(notes));
+        let updated = update_recording_meta(&db_path, meta.id, None, None, Some("  ".into()))
+            .await
+            .unwrap();
+        assert_eq!(updated.notes, None);
+
+        // 区间须在样本时间范围内，起点不晚于终点
+        for (from_ms, to_ms) in [(990, 1010), (1030, 1041), (1020, 1010)] {
+            assert!(
+                flag_sample_range(&db_path, meta.id, from_ms, to_ms, "bad")
+                    .await
+                    .is_err(),
+                "{from_ms}..={to_ms}"
+            );
+        }
+        assert!(flag_sample_range(&db_path, meta.id, 1000, 1010, " ")
+            .await
+            .is_err());
+        assert!(flag_sample_range(&db_path, meta.id + 100, 1000, 1010, "x")
+            .await
+            .is_err());
+
+        // 重叠区间照常写入，查询按起点排序
+        let bumped = flag_sample_range(&db_path, meta.id, 1010, 1040, "bumped, re-taped")
+            .await
+            .unwrap();
+        let slipped = flag_sample_range(&db_path, meta.id, 1000, 1020, " slipped ")
+            .await
+            .unwrap();
+        let tap = flag_sample_range(&db_path, meta.id, 1030, 1030, "tap")
+            .await
+            .unwrap();
+        assert_eq!(slipped.label, "slipped");
+        let flags = get_recording_flags(&db_path, meta.id).await.unwrap();
+        assert_eq!(flags, [slipped.clone(), bumped.clone(), tap.clone()]);
+
+        let read_rows = |path: &Path| -> Vec<String> {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .map(str::to_string)
+                .collect()
+        };
+        let plain = export_session_csv(&db_path, meta.id, ExportOptions::default())
+            .await
+            .unwrap();
+        assert!(read_rows(&plain)[0].ends_with(",host_time_ms"));
+
+        let options = ExportOptions {
+            include_flags: true,
+            ..ExportOptions::default()
+        };
+        let flagged = export_session_csv(&db_path, meta.id, options)
+            .await
+            .unwrap();
+        let rows = read_rows(&flagged);
+        assert!(rows[0].ends_with(",host_time_ms,flags"));
+        let flag_column: Vec<&str> = rows[1..]
+            .iter()
+            .map(|row| row.splitn(13, ',').last().unwrap())
+            .collect();
+        assert_eq!(
+            flag_column,
+            [
+                "slipped",
+                "\"slipped; bumped, re-taped\"",
+                "\"slipped; bumped, re-taped\"",
+                "\"bumped, re-taped; tap\"",
+                "\"bumped, re-taped\"",
+            ]
+        );
+        // 多出的 flags 列不影响导入
+        let (reimported, warnings) = import_session_csv(&db_path, &flagged, None).await.unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(reimported.sample_count, 5);
+
+        delete_recording_flag(&db_path, tap.id).await.unwrap();
+        assert!(delete_recording_flag(&db_path, tap.id).await.is_err());
+        let flags = get_recording_flags(&db_path, meta.id).await.unwrap();
+        assert_eq!(flags, [slipped, bumped]);
+
+        // 删除会话时一并删除标记
+        delete_recording(&db_path, meta.id).await.unwrap();
+        assert!(get_recording_flags(&db_path, meta.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let _ = std::fs::remove_file(&csv_path);
+        let _ = std::fs::remove_file(&plain);
+        let _ = std::fs::remove_file(&flagged);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn long_csv_export_reports_progress_and_cancels_cleanly() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        };
+
+        use crate::recorder::{ExportError, ExportProgress};
+
+        const ROWS: u64 = 50_000;
+        let db_path = temp_db("export_progress");
+        let csv_path = db_path.with_extension("csv");
+        let dir = db_path.with_extension("exports");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut csv = String::from(
+            "timestamp_ms,calc_position_x,calc_position_y,calc_position_z,\
+             calc_velocity_x,calc_velocity_y,calc_velocity_z,\
+             calc_attitude_w,calc_attitude_x,calc_attitude_y,calc_attitude_z\n",
+        );
+        for i in 0..ROWS {
+            csv.push_str(&format!("{},{},0,0,0,0,0,1,0,0,0\n", 1000 + i * 4, i));
+        }
+        std::fs::write(&csv_path, csv).unwrap();
+        let (meta, _) = import_session_csv(&db_path, &csv_path, Some("long".into()))
+            .await
+            .unwrap();
+        assert_eq!(meta.sample_count as u64, ROWS);
+
+        // 每批 700 行，不限频：每批都报告，行数与字节数单调递增
+        let reports = Arc::new(Mutex::new(Vec::<ExportProgress>::new()));
+        let sink = reports.clone();
+        let options = ExportOptions {
+            batch_rows: 700,
+            progress_interval: Duration::ZERO,
+            include_flags: false,
+            units: UnitConverter::default(),
+            on_progress: Some(Box::new(move |p| sink.lock().unwrap().push(p))),
+            cancel: None,
+        };
+        let path = export_recording_csv_to_dir(&db_path, meta.id, &dir, options)
+            .await
+            .unwrap();
+        let reports = reports.lock().unwrap().clone();
+        assert_eq!(reports.len(), ROWS.div_ceil(700) as usize);
+        assert!(reports.windows(2).all(|w| {
+            w[1].rows_done > w[0].rows_done && w[1].bytes_written > w[0].bytes_written
+        }));
+        let last = reports.last().unwrap();
+        assert_eq!(
+            (last.session_id, last.rows_done, last.rows_total),
+            (meta.id, ROWS, ROWS)
+        );
+        assert_eq!(last.bytes_written, std::fs::metadata(&path).unwrap().len());
+        let exported = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            exported
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .count() as u64,
+            ROWS + 1
+        );
+
+        // 写到一半取消：返回取消错误，目录中不留下任何文件
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let options = ExportOptions {
+            batch_rows: 700,
+            progress_interval: Duration::ZERO,
+            include_flags: false,
+            units: UnitConverter::default(),
+            on_progress: Some(Box::new(move |p| {
+                if p.rows_done >= ROWS / 2 {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            })),
+            cancel: Some(cancel.clone()),
+        };
+        std::fs::remove_file(&path).unwrap();
+        let error = export_recording_csv_to_dir(&db_path, meta.id, &dir, options)
+            .await
+            .unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&ExportError::Cancelled));
+        assert!(cancel.load(Ordering::Relaxed));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_file(&csv_path);
+        remove_db(&db_path);
+    }
+
+    #[cfg(any(debug_assertions, feature = "fault-injection"))]
+    #[tokio::test]
+    async fn injected_insert_failures_skip_samples_without_stopping_session() {
+        use crate::processor::fault::FaultInjectionConfig;
+
+        let db_path = temp_db("fault_insert");
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let faults = FaultInjector::new_handle();
+        spawn_recorder(
+            data_rx,
+            control_rx,
+            RecorderOptions {
+                faults: faults.clone(),
+                ..options_with_space(Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX))))
+            },
+        );
+        faults
+            .configure(FaultInjectionConfig {
+                recorder_insert_fail: 0.5,
+                seed: Some(7),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        for i in 0..40 {
+            data_tx.send(frame(1000 + i * 4)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let failures = faults.status().unwrap().injected.recorder_insert_failures;
+        assert!(failures > 0 && failures < 40, "failures = {failures}");
+        // 写入失败只丢当前样本，会话继续录制
+        assert!(recording_status(&control_tx).await.unwrap().recording);
+
+        faults.configure(FaultInjectionConfig::default()).unwrap();
+        assert_eq!(faults.status(), None);
+        data_tx.send(frame(2000)).unwrap();
+        stop_recording(&control_tx).await.unwrap();
+        assert_eq!(
+            get_recording_samples(&db_path, session_id)
+                .await
+                .unwrap()
+                .len() as u64,
+            41 - failures
+        );
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn low_disk_space_stops_active_session() {
+        let db_path = temp_db("low_disk");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let (event_tx, event_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(10_000)));
+        spawn_recorder(
+            data_rx,
+            control_rx,
+            RecorderOptions {
+                on_stopped: Some(Box::new(move |event| {
+                    let _ = event_tx.send(event);
+                })),
+                ..options_with_space(disk_space.clone())
+            },
+        );
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        for ts in [1000, 1004, 1008] {
+            data_tx.send(frame(ts)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let status = recording_status(&control_tx).await.unwrap();
+        assert!(status.recording);
+        assert_eq!(status.available_bytes, Some(10_000));
+
+        // 录制中跨过阈值：写完当前样本后结束会话并推送事件
+        disk_space.0.store(900, Ordering::Relaxed);
+        data_tx.send(frame(1012)).unwrap();
+        let event = event_rx.recv_async().await.unwrap();
+        assert_eq!(event.session_id, session_id);
+        assert_eq!(event.reason, StopReason::DiskFull);
+        assert_eq!(event.sample_count, 4);
+        assert_eq!(event.available_bytes, Some(900));
+        assert!(!recording_status(&control_tx).await.unwrap().recording);
+
+        // 之后的样本不再写入
+        data_tx.send(frame(1016)).unwrap();
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let meta = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[0].sample_count, 4);
+        assert_eq!(meta[0].stop_reason.as_deref(), Some("disk_full"));
+        assert!(meta[0].stopped_at_ms.is_some());
+        assert_eq!(
+            get_recording_samples(&db_path, session_id)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn sample_limit_splits_into_linked_sessions() {
+        let db_path = temp_db("split");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let first_id = start_recording(
+            &control_tx,
+            db_path.clone(),
+            RecordingStartInput {
+                device_id: None,
+                name: Some("walk test".into()),
+                tags: Some(vec!["outdoor".into()]),
+                metadata: None,
+                deep: false,
+                split: RecordingSplit {
+                    max_duration_min: None,
+                    max_samples: Some(5),
+                },
+            },
+        )
+        .await
+        .unwrap()
+        .session_id
+        .unwrap();
+
+        // 一次性塞入 8 帧：切段发生在录制循环内部，不依赖控制命令
+        for i in 0..8 {
+            data_tx.send(frame(1000 + i * 4)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let status = recording_status(&control_tx).await.unwrap();
+        assert!(status.recording);
+        assert_ne!(status.session_id, Some(first_id));
+        assert_eq!(status.sample_count, Some(3));
+        assert_eq!(status.name.as_deref(), Some("walk test (2)"));
+        let second_id = status.session_id.unwrap();
+        stop_recording(&control_tx).await.unwrap();
+
+        let meta = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(meta.len(), 2);
+        let first = meta.iter().find(|m| m.id == first_id).unwrap();
+        let second = meta.iter().find(|m| m.id == second_id).unwrap();
+        assert_eq!(first.parent_session_id, None);
+        assert_eq!(second.parent_session_id, Some(first_id));
+        assert_eq!((first.sample_count, second.sample_count), (5, 3));
+        assert_eq!(first.stop_reason.as_deref(), Some("split"));
+        assert_eq!(second.stop_reason.as_deref(), Some("user"));
+        assert_eq!(second.name.as_deref(), Some("walk test (2)"));
+        assert_eq!(second.tags, ["outdoor"]);
+
+        // 两段时间戳首尾相接，没有漏帧
+        let first_ts: Vec<u64> = get_recording_samples(&db_path, first_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        let second_ts: Vec<u64> = get_recording_samples(&db_path, second_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        assert_eq!(first_ts, [1000, 1004, 1008, 1012, 1016]);
+        assert_eq!(second_ts, [1020, 1024, 1028]);
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn pause_and_resume_stay_in_one_session() {
+        let db_path = temp_db("pause");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        // 未在录制时不能暂停或恢复
+        assert!(pause_recording(&control_tx).await.is_err());
+        assert!(resume_recording(&control_tx).await.is_err());
+
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        feed(&data_tx, &[1000, 1004, 1008]).await;
+
+        let status = pause_recording(&control_tx).await.unwrap();
+        assert!(status.recording && status.paused);
+        assert_eq!(status.session_id, Some(session_id));
+        let error = pause_recording(&control_tx).await.unwrap_err();
+        assert!(error.to_string().contains("already paused"), "{error}");
+
+        // 暂停期间的帧被丢弃
+        feed(&data_tx, &[1012, 1016, 1020]).await;
+        assert_eq!(
+            recording_status(&control_tx).await.unwrap().sample_count,
+            Some(3)
+        );
+
+        let status = resume_recording(&control_tx).await.unwrap();
+        assert!(status.recording && !status.paused);
+        assert_eq!(status.session_id, Some(session_id));
+        let error = resume_recording(&control_tx).await.unwrap_err();
+        assert!(error.to_string().contains("not paused"), "{error}");
+        feed(&data_tx, &[1024, 1028]).await;
+
+        // 暂停中直接停止：暂停区间随之关闭
+        pause_recording(&control_tx).await.unwrap();
+        feed(&data_tx, &[1032]).await;
+        let stopped = stop_recording(&control_tx).await.unwrap();
+        assert!(!stopped.recording && !stopped.paused);
+        assert_eq!(stopped.sample_count, Some(5));
+        assert!(pause_recording(&control_tx).await.is_err());
+
+        let meta = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[0].sample_count, 5);
+        let timestamps: Vec<u64> = get_recording_samples(&db_path, session_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        assert_eq!(timestamps, [1000, 1004, 1008, 1024, 1028]);
+
+        let pauses = get_recording_pauses(&db_path, session_id).await.unwrap();
+        assert_eq!(pauses.len(), 2);
+        assert_eq!(pauses[0].timestamp_ms, Some(1008));
+        assert_eq!(pauses[1].timestamp_ms, Some(1028));
+        assert!(pauses[0].resumed_at_ms <= pauses[1].paused_at_ms);
+        for pause in &pauses {
+            assert_eq!(pause.session_id, session_id);
+            assert_eq!(pause.duration_ms, pause.resumed_at_ms - pause.paused_at_ms);
+        }
+
+        remove_db(&db_path);
+    }
+
+    /// 管线在采集开关打开后送出的帧：阶段值按时间戳区分，便于核对对应关系。
+    fn staged_frame(timestamp_ms: u64) -> OutputFrame {
+        let mut frame = frame(timestamp_ms);
+        let t = timestamp_ms as f64;
+        let pre_zupt = NavState {
+            velocity: DVec3::new(t, 0.0, 0.0),
+            ..frame.nav
+        };
+        frame.stages = Some(StageSnapshot {
+            calibrated_accel: DVec3::new(0.0, 0.0, t),
+            calibrated_gyro: DVec3::ZERO,
+            filtered_accel: DVec3::new(0.0, 0.0, t),
+            filtered_gyro: DVec3::ZERO,
+            linear_accel: DVec3::ZERO,
+            nav_pre_zupt: pre_zupt,
+            nav_post_zupt: frame.nav,
+            is_static: true,
+        });
+        frame
+    }
+
+    #[tokio::test]
+    async fn deep_recording_writes_one_stage_row_per_sample() {
+        let db_path = temp_db("deep");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        let options = options_with_space(disk_space);
+        let stage_capture = options.stage_capture.clone();
+        spawn_recorder(data_rx, control_rx, options);
+
+        let input = RecordingStartInput {
+            device_id: None,
+            name: None,
+            tags: None,
+            split: RecordingSplit::default(),
+            metadata: None,
+            deep: true,
+        };
+        let status = start_recording(&control_tx, db_path.clone(), input)
+            .await
+            .unwrap();
+        assert!(status.deep && stage_capture.enabled());
+        let deep_id = status.session_id.unwrap();
+
+        // 开关打开前已在通道中的帧没有阶段值，不写入
+        data_tx.send(frame(996)).unwrap();
+        for timestamp_ms in (1000..=1016).step_by(4) {
+            data_tx.send(staged_frame(timestamp_ms)).unwrap();
+        }
+        feed(&data_tx, &[]).await;
+        let stopped = stop_recording(&control_tx).await.unwrap();
+        assert_eq!(stopped.sample_count, Some(5));
+        assert!(!stage_capture.enabled());
+
+        let sample_ts: Vec<u64> = get_recording_samples(&db_path, deep_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        let stages = get_stage_samples(&db_path, deep_id, None, None)
+            .await
+            .unwrap();
+        let stage_ts: Vec<u64> = stages.iter().map(|s| s.timestamp_ms).collect();
+        assert_eq!(sample_ts, [1000, 1004, 1008, 1012, 1016]);
+        assert_eq!(stage_ts, sample_ts);
+        for stage in &stages {
+            let t = stage.timestamp_ms as f64;
+            assert_eq!(stage.filtered_accel, DVec3::new(0.0, 0.0, t));
+            assert_eq!(stage.pre_zupt_velocity, DVec3::new(t, 0.0, 0.0));
+            assert_eq!(stage.post_zupt_velocity, DVec3::ZERO);
+            assert!(stage.is_static);
+        }
+        let ranged: Vec<u64> = get_stage_samples(&db_path, deep_id, Some(1004), Some(1012))
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        assert_eq!(ranged, [1004, 1008, 1012]);
+
+        // 普通录制不打开采集，也不写阶段行
+        let plain_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        assert!(!stage_capture.enabled());
+        data_tx.send(staged_frame(2000)).unwrap();
+        feed(&data_tx, &[2004]).await;
+        let stopped = stop_recording(&control_tx).await.unwrap();
+        assert_eq!(stopped.sample_count, Some(2));
+        assert!(!stopped.deep);
+        assert!(get_stage_samples(&db_path, plain_id, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        delete_recording(&db_path, deep_id).await.unwrap();
+        assert!(get_stage_samples(&db_path, deep_id, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn paused_time_is_excluded_from_duration_and_split() {
+        let db_path = temp_db("pause_stats");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        // 60ms 切段：暂停造成的设备时间跳变不计入分段时长
+        let first_id = start_recording(
+            &control_tx,
+            db_path.clone(),
+            RecordingStartInput {
+                device_id: None,
+                name: None,
+                tags: None,
+                metadata: None,
+                deep: false,
+                split: RecordingSplit {
+                    max_duration_min: Some(0.001),
+                    max_samples: None,
+                },
+            },
+        )
+        .await
+        .unwrap()
+        .session_id
+        .unwrap();
+        let before: Vec<u64> = (0..=10).map(|i| 1000 + i * 4).collect();
+        feed(&data_tx, &before).await;
+
+        pause_recording(&control_tx).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        resume_recording(&control_tx).await.unwrap();
+        feed(&data_tx, &[1200, 1210]).await;
+        let status = recording_status(&control_tx).await.unwrap();
+        assert_eq!(status.session_id, Some(first_id));
+        assert_eq!(status.sample_count, Some(13));
+
+        feed(&data_tx, &[1230]).await;
+        let status = recording_status(&control_tx).await.unwrap();
+        assert_ne!(status.session_id, Some(first_id));
+        stop_recording(&control_tx).await.unwrap();
+
+        // 会话时长扣除暂停时间：时长过滤按实际录制时长判断
+        let first = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .find(|m| m.id == first_id)
+            .unwrap();
+        let pauses = get_recording_pauses(&db_path, first_id).await.unwrap();
+        assert_eq!(pauses.len(), 1);
+        assert!(first.paused_ms >= 300);
+        assert_eq!(first.paused_ms, pauses[0].duration_ms);
+        let recorded_ms = first.stopped_at_ms.unwrap() - first.started_at_ms - first.paused_ms;
+        let with_min_duration = |min_duration_ms| RecordingQuery {
+            min_duration_ms: Some(min_duration_ms),
+            ..RecordingQuery::default()
+        };
+        let ids = |page: RecordingPage| page.items.iter().map(|m| m.id).collect::<Vec<_>>();
+        let hits = list_recordings(&db_path, &with_min_duration(recorded_ms))
+            .await
+            .unwrap();
+        assert!(ids(hits).contains(&first_id));
+        let hits = list_recordings(&db_path, &with_min_duration(recorded_ms + 1))
+            .await
+            .unwrap();
+        assert!(!ids(hits).contains(&first_id));
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn heading_alignment_is_stored_with_session() {
+        let db_path = temp_db("heading");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        // 录制中途对齐，随后重新对齐：会话记下结束时生效的角度与其生效时刻
+        let session_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        let aligned = |timestamp_ms, yaw_deg| OutputFrame {
+            heading_yaw_deg: Some(yaw_deg),
+            ..frame(timestamp_ms)
+        };
+        feed(&data_tx, &[1000, 1004]).await;
+        for frame in [
+            aligned(1008, -135.0),
+            aligned(1012, -135.0),
+            aligned(1016, 90.0),
+            aligned(1020, 90.0),
+        ] {
+            data_tx.send(frame).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        stop_recording(&control_tx).await.unwrap();
+
+        let meta = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items;
+        let meta = meta.iter().find(|m| m.id == session_id).unwrap();
+        assert_eq!(meta.heading_yaw_deg, Some(90.0));
+        assert_eq!(meta.heading_aligned_at_ms, Some(1016));
+
+        // 未对齐的录制保持为空
+        start(&control_tx, &db_path).await.unwrap();
+        feed(&data_tx, &[2000]).await;
+        let unaligned_id = stop_recording(&control_tx)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        let meta = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items;
+        let meta = meta.iter().find(|m| m.id == unaligned_id).unwrap();
+        assert_eq!(
+            (meta.heading_yaw_deg, meta.heading_aligned_at_ms),
+            (None, None)
+        );
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn transform_overlays_sessions_recorded_under_rotated_calibration() {
+        use std::f64::consts::FRAC_PI_2;
+
+        use crate::processor::analysis::logic::quat_angle_deg;
+
+        let db_path = temp_db("transform");
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        // 同一条设备系轨迹，在两个相差 90°（绕 Z）的零位下各录一段
+        let device_path = |i: u64| {
+            let t = i as f64 * 0.01;
+            (
+                DVec3::new(t, 0.5 * t * t, 0.1 * t),
+                DQuat::from_euler_zyx(0.2 * t, -0.1 * t, 0.3 * t),
+            )
+        };
+        let record = |calibration: Option<RecordingCalibration>| {
+            let control_tx = control_tx.clone();
+            let data_tx = data_tx.clone();
+            let db_path = db_path.clone();
+            async move {
+                let mut metadata = RecordingMetadata::current(DeviceInformation::default(), None);
+                metadata.calibration = calibration;
+                let input = RecordingStartInput {
+                    device_id: None,
+                    name: None,
+                    tags: None,
+                    split: RecordingSplit::default(),
+                    metadata: Some(metadata),
+                    deep: false,
+                };
+                start_recording(&control_tx, db_path, input).await.unwrap();
+                let offset = calibration.map_or(DQuat::IDENTITY, |c| c.axis_quat_offset);
+                for i in 0..200 {
+                    let (position, attitude) = device_path(i);
+                    let mut frame = frame(1000 + i * 4);
+                    frame.nav.position = offset.rotate_vec3(position);
+                    frame.nav.attitude = offset * attitude;
+                    data_tx.send(frame).unwrap();
+                }
+                while !data_tx.is_empty() {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                stop_recording(&control_tx)
+                    .await
+                    .unwrap()
+                    .session_id
+                    .unwrap()
+            }
+        };
+        let calibration = |offset: DQuat| RecordingCalibration {
+            axis_quat_offset: offset,
+            gravity_ref: DVec3::new(0.0, 0.0, 9.80665),
+        };
+        let session_a = record(Some(calibration(DQuat::IDENTITY))).await;
+        let session_b = record(Some(calibration(DQuat::from_rotation_z(FRAC_PI_2)))).await;
+        let legacy = record(None).await;
+
+        let preview = transform_recording(&db_path, session_a, session_b, false)
+            .await
+            .unwrap();
+        assert!(!preview.written);
+        assert_eq!(preview.frame_count, 200);
+        assert!((preview.rotation_deg - 90.0).abs() < 1e-9);
+        assert!(preview.gravity_residual_deg < 1e-9);
+
+        // 换算后的 A 与 B 逐点重合
+        let target: Vec<_> = get_recording_samples(&db_path, session_b).await.unwrap();
+        for point in &preview.points {
+            let expected = target
+                .iter()
+                .find(|data| data.timestamp_ms == point.timestamp_ms)
+                .unwrap();
+            assert!((point.position - expected.position).length() < 1e-9);
+            assert!(quat_angle_deg(point.attitude, expected.attitude) < 1e-6);
+        }
+        let last = preview.points.last().unwrap();
+        assert!((last.original_position - last.position).length() > 0.1);
+
+        // 预览不写库；写入时只填充 A 的换算列
+        let connection = db::connect(&db_path).await.unwrap();
+        let transformed = || {
+            models::imu_samples::Entity::find()
+                .filter(models::imu_samples::Column::TransformedPositionX.is_not_null())
+                .all(&connection)
+        };
+        assert!(transformed().await.unwrap().is_empty());
+        let written = transform_recording(&db_path, session_a, session_b, true)
+            .await
+            .unwrap();
+        assert!(written.written);
+        let rows = transformed().await.unwrap();
+        assert_eq!(rows.len(), 200);
+        assert!(rows.iter().all(|row| row.session_id == session_a));
+        let row = rows.iter().max_by_key(|row| row.timestamp_ms).unwrap();
+        assert!((row.transformed_position_x.unwrap() - last.position.x).abs() < 1e-9);
+        assert!((row.transformed_position_y.unwrap() - last.position.y).abs() < 1e-9);
+        // 原始计算位置保持不变
+        assert_eq!(row.calc_position_x, last.original_position.x);
+
+        // 缺少标定信息的会话给出明确的错误
+        for (from, to) in [(legacy, session_b), (session_a, legacy)] {
+            let error = transform_recording(&db_path, from, to, false)
+                .await
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("no calibration metadata"), "{error}");
+        }
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn version_metadata_round_trips_to_meta_and_csv_header() {
+        let db_path = temp_db("metadata");
+        let dir = db_path.with_extension("exports");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let record = |metadata: Option<RecordingMetadata>| {
+            let control_tx = control_tx.clone();
+            let data_tx = data_tx.clone();
+            let db_path = db_path.clone();
+            async move {
+                let input = RecordingStartInput {
+                    device_id: None,
+                    name: None,
+                    tags: None,
+                    split: RecordingSplit::default(),
+                    metadata,
+                    deep: false,
+                };
+                start_recording(&control_tx, db_path, input).await.unwrap();
+                feed(&data_tx, &[1000, 1004]).await;
+                stop_recording(&control_tx)
+                    .await
+                    .unwrap()
+                    .session_id
+                    .unwrap()
+            }
+        };
+        let find_meta = |session_id: i64| {
+            let db_path = db_path.clone();
+            async move {
+                list_recordings(&db_path, &RecordingQuery::default())
+                    .await
+                    .unwrap()
+                    .items
+                    .into_iter()
+                    .find(|meta| meta.id == session_id)
+                    .unwrap()
+            }
+        };
+
+        // 设备提供设备信息服务：固件以 \0 填充、硬件版本带空白，未知特征忽略
+        let device = DeviceInformation::from_reads([
+            (0x2a26, b"V1.2.3\0\0".to_vec()),
+            (0x2a27, b" rev B ".to_vec()),
+            (0x2a28, Vec::new()),
+            (0x1234, b"ignored".to_vec()),
+        ]);
+        assert_eq!(device.firmware_revision.as_deref(), Some("V1.2.3"));
+        assert_eq!(device.hardware_revision.as_deref(), Some("rev B"));
+        assert_eq!(device.software_revision, None);
+        let metadata = RecordingMetadata::current(device, Some(&[0x12, 0x05, 0xff])).with_uptime(
+            crate::types::status::ConnectionUptime {
+                device_uptime_ms: Some(3_600_000),
+                connection_uptime_ms: Some(120_000),
+                frames_since_connect: 30_000,
+                reconnect_count: 2,
+            },
+        );
+        assert_eq!(metadata.imu_config_hex.as_deref(), Some("1205ff"));
+
+        let session_id = record(Some(metadata.clone())).await;
+        assert_eq!(find_meta(session_id).await.metadata, Some(metadata));
+        let csv_path =
+            export_recording_csv_to_dir(&db_path, session_id, &dir, ExportOptions::default())
+                .await
+                .unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let comments: Vec<&str> = csv.lines().take_while(|l| l.starts_with('#')).collect();
+        assert_eq!(
+            comments,
+            [
+                format!("# schema_version: {RESPONSE_SCHEMA_VERSION}"),
+                format!("# app_version: {}", env!("CARGO_PKG_VERSION")),
+                "# firmware_revision: V1.2.3".to_string(),
+                "# hardware_revision: rev B".to_string(),
+                "# imu_config: 1205ff".to_string(),
+            ]
+        );
+        // 带注释行的导出仍可导入
+        let (imported, warnings) = import_session_csv(&db_path, &csv_path, None).await.unwrap();
+        assert_eq!((imported.sample_count, warnings.len()), (2, 0));
+        assert_eq!(imported.metadata, None);
+
+        // 设备没有设备信息服务：设备字段全部为空，CSV 只写应用版本
+        let absent = DeviceInformation::from_reads(Vec::new());
+        assert!(absent.is_empty());
+        let session_id = record(Some(RecordingMetadata::current(absent, None))).await;
+        let meta = find_meta(session_id).await;
+        let stored = meta.metadata.unwrap();
+        assert!(stored.device.is_empty());
+        assert_eq!(stored.imu_config_hex, None);
+        let csv_path =
+            export_recording_csv_to_dir(&db_path, session_id, &dir, ExportOptions::default())
+                .await
+                .unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let comments = csv.lines().take_while(|l| l.starts_with('#')).count();
+        assert_eq!(comments, 2);
+
+        // 未记录版本信息（早期录制）
+        let session_id = record(None).await;
+        assert_eq!(find_meta(session_id).await.metadata, None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn unanswered_commands_time_out_and_fresh_recorder_answers() {
+        // 录制线程未响应：命令在超时后报错而不是一直挂起
+        let (control_tx, control_rx) = flume::unbounded();
+        let db_path = temp_db("unanswered");
+        let started = std::time::Instant::now();
+        let error = recording_status(&control_tx).await.unwrap_err();
+        assert!(error.to_string().contains("did not reply"), "{error}");
+        assert!(started.elapsed() < RECORDER_REPLY_TIMEOUT * 2);
+
+        // 录制线程已退出：立即报错
+        drop(control_rx);
+        let error = stop_recording(&control_tx).await.unwrap_err();
+        assert!(error.to_string().contains("not available"), "{error}");
+
+        // 尚无任何数据输入的录制线程照常应答
+        let (_data_tx, data_rx) = flume::bounded::<OutputFrame>(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+        let status = start(&control_tx, &db_path).await.unwrap();
+        assert!(status.recording);
+        assert!(!stop_recording(&control_tx).await.unwrap().recording);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn list_recordings_filters_sorts_and_pages() {
+        let db_path = temp_db("list_filter");
+        let db = db::connect(&db_path).await.unwrap();
+        db::ensure_schema(&db).await.unwrap();
+
+        // (名称, 标签 JSON, 开始, 结束, 样本数, 设备)
+        let rows = [
+            (
+                Some("Walk Outdoor"),
+                Some(r#"["outdoor","walk"]"#),
+                1_000,
+                Some(61_000),
+                15_000,
+                Some("dev-a"),
+            ),
+            (
+                Some("desk 2D"),
+                Some(r#"["outdoors-test"]"#),
+                2_000,
+                Some(12_000),
+                2_500,
+                Some("dev-b"),
+            ),
+            (
+                Some("100%_run"),
+                Some(r#"["run"]"#),
+                3_000,
+                None,
+                50,
+                Some("dev-a"),
+            ),
+            (None, None, 4_000, Some(34_000), 7_500, None),
+            // 旧数据中的非法 JSON 不应命中标签过滤，也不应让查询出错
+            (
+                Some("legacy"),
+                Some("outdoor"),
+                5_000,
+                Some(5_500),
+                10,
+                None,
+            ),
+        ];
+        let mut ids = Vec::new();
+        for (name, tags, started, stopped, samples, device) in rows {
+            let session = models::recording_sessions::ActiveModel {
+                started_at_ms: Set(started),
+                stopped_at_ms: Set(stopped),
+                device_id: Set(device.map(str::to_string)),
+                name: Set(name.map(str::to_string)),
+                tags: Set(tags.map(str::to_string)),
+                sample_count: Set(samples),
+                parent_session_id: Set(None),
+                interrupted: Set(false),
+                ..Default::default()
+            };
+            ids.push(session.insert(&db).await.unwrap().id);
+        }
+        let [walk, desk, run, untitled, legacy] = ids[..] else {
+            unreachable!()
+        };
+
+        let list = |query: RecordingQuery| {
+            let db_path = db_path.clone();
+            async move {
+                let page = list_recordings(&db_path, &query).await.unwrap();
+                let ids: Vec<i64> = page.items.iter().map(|meta| meta.id).collect();
+                (ids, page.total_count)
+            }
+        };
+
+        // 默认：全部，按开始时间倒序
+        assert_eq!(
+            list(RecordingQuery::default()).await,
+            (vec![legacy, untitled, run, desk, walk], 5)
+        );
+
+        // 名称：不区分大小写；通配符按字面匹配
+        let by_name = |name: &str| RecordingQuery {
+            name: Some(name.into()),
+            ..RecordingQuery::default()
+        };
+        assert_eq!(list(by_name("outdoor")).await.0, [walk]);
+        assert_eq!(list(by_name("%")).await.0, [run]);
+        assert_eq!(list(by_name("_")).await.0, [run]);
+
+        // 标签：整标签匹配，"outdoor" 不命中 "outdoors-test"，也不命中非法 JSON
+        let by_tags = |tags: &[&str]| RecordingQuery {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..RecordingQuery::default()
+        };
+        assert_eq!(list(by_tags(&["outdoor"])).await.0, [walk]);
+        assert_eq!(list(by_tags(&["outdoors"])).await.0, Vec::<i64>::new());
+        assert_eq!(list(by_tags(&["run", "walk"])).await.0, [run, walk]);
+
+        // 开始时间区间（含端点）
+        let range = RecordingQuery {
+            started_from_ms: Some(2_000),
+            started_to_ms: Some(4_000),
+            ..RecordingQuery::default()
+        };
+        assert_eq!(list(range).await.0, [untitled, run, desk]);
+
+        // 最短时长：未结束的会话不满足
+        let long = RecordingQuery {
+            min_duration_ms: Some(30_000),
+            ..RecordingQuery::default()
+        };
+        assert_eq!(list(long).await.0, [untitled, walk]);
+
+        let device = RecordingQuery {
+            device_id: Some("dev-a".into()),
+            ..RecordingQuery::default()
+        };
+        assert_eq!(list(device).await.0, [run, walk]);
+
+        // 组合条件
+        let combined = RecordingQuery {
+            device_id: Some("dev-a".into()),
+            min_duration_ms: Some(1_000),
+            ..by_tags(&["walk", "run"])
+        };
+        assert_eq!(list(combined).await, (vec![walk], 1));
+
+        // 排序：时长为空的排在最后
+        let sorted = |sort_by, ascending| RecordingQuery {
+            sort_by,
+            ascending,
+            ..RecordingQuery::default()
+        };
+        assert_eq!(
+            list(sorted(RecordingSortBy::Duration, false)).await.0,
+            [walk, untitled, desk, legacy, run]
+        );
+        assert_eq!(
+            list(sorted(RecordingSortBy::Duration, true)).await.0,
+            [legacy, desk, untitled, walk, run]
+        );
+        assert_eq!(
+            list(sorted(RecordingSortBy::SampleCount, true)).await.0,
+            [legacy, run, desk, untitled, walk]
+        );
+        assert_eq!(
+            list(sorted(RecordingSortBy::StartedAt, true)).await.0,
+            [walk, desk, run, untitled, legacy]
+        );
+
+        // 分页：总数不受 limit/offset 影响
+        let page = RecordingQuery {
+            limit: Some(2),
+            offset: Some(1),
+            ..RecordingQuery::default()
+        };
+        assert_eq!(list(page).await, (vec![untitled, run], 5));
+        let filtered_page = RecordingQuery {
+            limit: Some(1),
+            ..by_tags(&["outdoor", "run"])
+        };
+        assert_eq!(list(filtered_page).await, (vec![run], 2));
+
+        drop(db);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn recording_statistics_aggregate_all_sessions() {
+        let db_path = temp_db("statistics");
+        let cache = StatisticsCache::default();
+
+        // 数据库不存在：全零，不报错也不创建
+        let empty = get_recording_statistics(&db_path, &cache).await.unwrap();
+        assert_eq!(empty, RecordingStatistics::default());
+        assert!(!db_path.exists());
+
+        // 只有表结构的空库：计数为零，大小为库文件大小
+        let db = db::connect(&db_path).await.unwrap();
+        db::ensure_schema(&db).await.unwrap();
+        let empty = get_recording_statistics(&db_path, &StatisticsCache::default())
+            .await
+            .unwrap();
+        assert_eq!(empty.session_count, 0);
+        assert_eq!(empty.sample_count, 0);
+        assert_eq!(empty.total_duration_ms, 0);
+        assert_eq!(empty.first_started_at_ms, None);
+        assert_eq!(empty.last_started_at_ms, None);
+        assert!(empty.top_tags.is_empty());
+        assert!(empty.db_size_bytes > 0);
+
+        // (标签 JSON, 开始, 结束, 暂停, 样本数)
+        let rows = [
+            (
+                Some(r#"["walk","outdoor"]"#),
+                1_000,
+                Some(61_000),
+                None,
+                15_000,
+            ),
+            (
+                Some(r#"["walk"]"#),
+                70_000,
+                Some(100_000),
+                Some(5_000),
+                6_000,
+            ),
+            // 未结束的会话计入会话数与样本数，不计入时长
+            (Some(r#"["run","walk"]"#), 200_000, None, None, 500),
+            // 非法 JSON 与无标签不计入标签统计
+            (Some("outdoor"), 150_000, Some(151_000), None, 10),
+            (None, 50_000, Some(52_000), None, 0),
+        ];
+        for (tags, started, stopped, paused, samples) in rows {
+            models::recording_sessions::ActiveModel {
+                started_at_ms: Set(started),
+                stopped_at_ms: Set(stopped),
+                paused_ms: Set(paused),
+                tags: Set(tags.map(str::to_string)),
+                sample_count: Set(samples),
+                parent_session_id: Set(None),
+                interrupted: Set(false),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        // 缓存未过期时仍返回旧结果
+        assert_eq!(
+            get_recording_statistics(&db_path, &cache).await.unwrap(),
+            RecordingStatistics::default()
+        );
+
+        let statistics = get_recording_statistics(&db_path, &StatisticsCache::default())
+            .await
+            .unwrap();
+        assert_eq!(statistics.session_count, 5);
+        assert_eq!(statistics.sample_count, 21_510);
+        assert_eq!(
+            statistics.total_duration_ms,
+            60_000 + 25_000 + 1_000 + 2_000
+        );
+        assert_eq!(statistics.first_started_at_ms, Some(1_000));
+        assert_eq!(statistics.last_started_at_ms, Some(200_000));
+        assert_eq!(
+            statistics.top_tags,
+            vec![
+                TagUsage {
+                    tag: "walk".into(),
+                    count: 3
+                },
+                TagUsage {
+                    tag: "outdoor".into(),
+                    count: 1
+                },
+                TagUsage {
+                    tag: "run".into(),
+                    count: 1
+                },
+            ]
+        );
+        let on_disk: u64 = ["", "-wal"]
+            .iter()
+            .filter_map(|suffix| std::fs::metadata(format!("{}{suffix}", db_path.display())).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        assert_eq!(statistics.db_size_bytes, on_disk);
+
+        drop(db);
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn flight_recorder_dumps_last_seconds_in_order() {
+        let db_path = temp_db("flight");
+
+        // 0.2 s @ 250 Hz = 50 帧
+        let capacity = flight_recorder_capacity(0.2, 250);
+        assert_eq!(capacity, 50);
+        let (data_tx, data_rx) = flume::bounded(256);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(
+            data_rx,
+            control_rx,
+            RecorderOptions {
+                flight_recorder_frames: capacity,
+                ..options_with_space(disk_space)
+            },
+        );
+
+        let dump =
+            |name: &str| dump_flight_recorder(&control_tx, db_path.clone(), Some(name.into()));
+        assert!(dump("empty").await.is_err());
+
+        // 未在录制：写满后再覆盖一圈半
+        let timestamps: Vec<u64> = (0..125).map(|i| 1000 + i * 4).collect();
+        for &ts in &timestamps {
+            data_tx.send(frame(ts)).unwrap();
+        }
+        while !data_tx.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let meta = dump("before crash").await.unwrap();
+        assert_eq!(meta.sample_count, capacity as i64);
+        assert_eq!(meta.name.as_deref(), Some("before crash"));
+        assert_eq!(meta.tags, [FLIGHT_RECORDER_TAG]);
+        assert_eq!(
+            meta.stopped_at_ms.unwrap() - meta.started_at_ms,
+            (capacity as i64 - 1) * 4
+        );
+
+        let dumped: Vec<u64> = get_recording_samples(&db_path, meta.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|sample| sample.timestamp_ms)
+            .collect();
+        assert_eq!(dumped, timestamps[timestamps.len() - capacity..]);
+
+        // 导出不清空缓冲区，也不影响正常录制
+        assert!(!recording_status(&control_tx).await.unwrap().recording);
+        let again = dump("again").await.unwrap();
+        assert_ne!(again.id, meta.id);
+        assert_eq!(again.sample_count, capacity as i64);
+
+        // 缩小容量后只保留最新的帧
+        control_tx
+            .send(RecorderCommand::ResizeFlightRecorder { capacity: 10 })
+            .unwrap();
+        let resized = dump("resized").await.unwrap();
+        assert_eq!(resized.sample_count, 10);
+        control_tx
+            .send(RecorderCommand::ResizeFlightRecorder { capacity: 0 })
+            .unwrap();
+        assert!(dump("disabled").await.is_err());
+
+        remove_db(&db_path);
+    }
+
+    #[tokio::test]
+    async fn scheduling_hints_are_applied_on_the_recorder_thread() {
+        let (_data_tx, data_rx) = flume::bounded::<OutputFrame>(1);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(u64::MAX)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+
+        let status = SchedulingStatus::new_handle();
+        control_tx
+            .send(RecorderCommand::ApplyScheduling {
+                priority: None,
+                status: status.clone(),
+            })
+            .unwrap();
+        // 命令按顺序处理，状态查询返回时调度提示已应用
+        recording_status(&control_tx).await.unwrap();
+        let report = status.snapshot();
+        assert_eq!(report.recorder, Some(ThreadHintsReport::default()));
+        assert_eq!(report.processor, None);
+    }
 }
@@ -0,0 +1,423 @@
+//! 开始录制与录制前的准备。
+//!
+//! 开始录制命令与参数、开始前的检查（分段条件、磁盘剩余空间、数据库结构），
+//! 以及启动和切换录制目录时修复上次未正常结束的会话。会话开始后的写入、分段与停止见 `service`。
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, ensure, Context};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    Set, Statement,
+};
+
+use crate::{
+    recorder::{
+        db,
+        disk::DiskGuard,
+        models,
+        service::{recv_reply, recv_reply_within, RecorderCommand, RecorderOptions},
+    },
+    types::{
+        error::ErrorCode,
+        recording::{RecordingMetadata, RecordingStatus},
+    },
+};
+
+/// 等待录制线程回复开始录制的上限。
+///
+/// 数据库的完整性检查、备份与迁移在启动和切换录制目录时已排进录制线程，
+/// 但用户此时立即开始录制，命令会排在这些工作之后；数百 MB 的旧库迁移远超
+/// [`RECORDER_REPLY_TIMEOUT`](super::service::RECORDER_REPLY_TIMEOUT)。
+/// 超时报告 [`RecordingStillStarting`] 而不是失败。
+pub const RECORDER_START_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 开始录制在 [`RECORDER_START_TIMEOUT`] 内没有得到回复。
+///
+/// 命令仍在录制线程的队列里，会话稍后多半照常开始，调用方应查询录制状态而不是重试。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("录制仍在启动中（{0} s 内未完成，可能正在迁移录制数据库），请稍后查询录制状态")]
+pub struct RecordingStillStarting(pub u64);
+
+impl RecordingStillStarting {
+    /// 对应的 IPC 错误码。
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::RecordingStarting
+    }
+}
+
+/// 开始录制参数。
+pub struct RecordingStartInput {
+    /// 设备 ID。
+    pub device_id: Option<String>,
+    /// 录制名称。
+    pub name: Option<String>,
+    /// 标签列表。
+    pub tags: Option<Vec<String>>,
+    /// 自动分段条件。
+    pub split: RecordingSplit,
+    /// 应用与设备版本信息，写入会话（各分段相同）。
+    pub metadata: Option<RecordingMetadata>,
+    /// 深度录制：同时把每帧各阶段中间值写入 `imu_stage_samples`，供事后排查。
+    pub deep: bool,
+}
+
+/// 自动分段条件，任一条件满足即结束当前会话并接着写入新的分段。
+///
+/// 时长按设备时间戳计，与样本对齐；两个条件都为空时不分段。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecordingSplit {
+    /// 单段最长时长（分钟）。
+    pub max_duration_min: Option<f64>,
+    /// 单段最多样本数。
+    pub max_samples: Option<u64>,
+}
+
+impl RecordingSplit {
+    fn validate(&self) -> anyhow::Result<()> {
+        if let Some(minutes) = self.max_duration_min {
+            ensure!(
+                minutes.is_finite() && minutes > 0.0,
+                "max_duration_min must be positive, got {minutes}"
+            );
+        }
+        ensure!(self.max_samples != Some(0), "max_samples must be positive");
+        Ok(())
+    }
+
+    /// 当前段已有 `sample_count` 个样本、下一帧距段首 `span_ms` 时，是否应切到新段。
+    pub(super) fn reached(&self, sample_count: u64, span_ms: u64) -> bool {
+        self.max_samples.is_some_and(|max| sample_count >= max)
+            || self
+                .max_duration_min
+                .is_some_and(|minutes| span_ms as f64 >= minutes * 60_000.0)
+    }
+}
+
+/// 通过录制通道启动录制。
+///
+/// 等待上限为 [`RECORDER_START_TIMEOUT`]，超时返回 [`RecordingStillStarting`]。
+pub async fn start_recording(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    db_path: PathBuf,
+    input: RecordingStartInput,
+) -> anyhow::Result<RecordingStatus> {
+    start_recording_within(recorder_tx, db_path, input, RECORDER_START_TIMEOUT).await
+}
+
+async fn start_recording_within(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    db_path: PathBuf,
+    input: RecordingStartInput,
+    timeout: Duration,
+) -> anyhow::Result<RecordingStatus> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::Start {
+            db_path,
+            device_id: input.device_id,
+            name: input.name,
+            tags: input.tags,
+            split: input.split,
+            metadata: input.metadata.map(Box::new),
+            deep: input.deep,
+            reply: reply_tx,
+        })
+        .context("recorder thread not available")?;
+    recv_reply_within(reply_rx, timeout, || {
+        RecordingStillStarting(timeout.as_secs()).into()
+    })
+    .await?
+}
+
+/// 开始会话前的检查：分段条件合法、剩余空间足够，打开数据库并确保表结构。
+///
+/// 返回数据库连接与已完成首次检查的磁盘守卫。
+pub(super) async fn prepare_session(
+    db_path: &Path,
+    split: &RecordingSplit,
+    options: &RecorderOptions,
+) -> anyhow::Result<(DatabaseConnection, DiskGuard)> {
+    split.validate()?;
+    let mut disk_guard = DiskGuard::new(options.min_free_bytes, options.check_interval);
+    if let Err(available) = disk_guard.check_now(options.disk_space.as_ref(), db_path) {
+        bail!(
+            "insufficient disk space for recording: {} MB available, at least {} MB required",
+            available / (1024 * 1024),
+            options.min_free_bytes / (1024 * 1024)
+        );
+    }
+
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+    Ok((db, disk_guard))
+}
+
+/// 通过录制通道修复未正常结束的会话，返回修复数量。
+///
+/// 经录制线程执行，保证不会误改正在录制的会话。
+pub async fn repair_recordings(
+    recorder_tx: &flume::Sender<RecorderCommand>,
+    db_path: PathBuf,
+) -> anyhow::Result<u64> {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    recorder_tx
+        .send(RecorderCommand::Repair {
+            db_path,
+            reply: reply_tx,
+        })
+        .context("recorder thread not available")?;
+    recv_reply(reply_rx).await?
+}
+
+/// 修复 `stopped_at_ms` 为空的会话（应用崩溃时录制未正常结束）。
+///
+/// 样本数取实际行数；结束时间取开始时间加样本设备时间跨度（设备时间与主机时间
+/// 不同源，只能按跨度换算），无样本时等于开始时间。修复后标记 `interrupted`。
+/// `active_session_id` 为正在录制的会话，跳过。数据库不存在时不创建。
+pub(super) async fn repair_interrupted_sessions(
+    db_path: &Path,
+    active_session_id: Option<i64>,
+) -> anyhow::Result<u64> {
+    if !db_path.exists() {
+        return Ok(0);
+    }
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+
+    let sessions = models::recording_sessions::Entity::find()
+        .filter(models::recording_sessions::Column::StoppedAtMs.is_null())
+        .all(&db)
+        .await
+        .context("query unfinished recording sessions")?;
+
+    let mut repaired = 0;
+    for session in sessions {
+        if Some(session.id) == active_session_id {
+            continue;
+        }
+        let span = db
+            .query_one(Statement::from_sql_and_values(
+                db.get_database_backend(),
+                "SELECT COUNT(*) AS sample_count,
+                        MIN(timestamp_ms) AS first_ms,
+                        MAX(timestamp_ms) AS last_ms
+                 FROM imu_samples WHERE session_id = ?;",
+                [session.id.into()],
+            ))
+            .await
+            .context("count recording samples")?
+            .context("sample count query returned no row")?;
+        let sample_count: i64 = span.try_get("", "sample_count")?;
+        let first_ms: Option<i64> = span.try_get("", "first_ms")?;
+        let last_ms: Option<i64> = span.try_get("", "last_ms")?;
+        let duration_ms = match (first_ms, last_ms) {
+            (Some(first), Some(last)) => (last - first).max(0),
+            _ => 0,
+        };
+
+        let update = models::recording_sessions::ActiveModel {
+            id: Set(session.id),
+            stopped_at_ms: Set(Some(session.started_at_ms + duration_ms)),
+            sample_count: Set(sample_count),
+            interrupted: Set(true),
+            ..Default::default()
+        };
+        update
+            .update(&db)
+            .await
+            .context("repair recording session")?;
+        tracing::warn!(
+            session_id = session.id,
+            sample_count,
+            duration_ms,
+            "已修复未正常结束的录制会话"
+        );
+        repaired += 1;
+    }
+
+    Ok(repaired)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicU64, Arc};
+
+    use super::*;
+    use crate::{
+        processor::output::OutputFrame,
+        recorder::{
+            list_recordings, recording_status,
+            service::RECORDER_REPLY_TIMEOUT,
+            spawn_recorder, stop_recording,
+            test_support::{feed, options_with_space, remove_db, start, temp_db, FakeDiskSpace},
+        },
+        types::recording::RecordingQuery,
+    };
+
+    fn default_input() -> RecordingStartInput {
+        RecordingStartInput {
+            device_id: None,
+            name: None,
+            tags: None,
+            split: RecordingSplit::default(),
+            metadata: None,
+            deep: false,
+        }
+    }
+
+    fn spawn_with_space(
+        available: u64,
+    ) -> (flume::Sender<OutputFrame>, flume::Sender<RecorderCommand>) {
+        let (data_tx, data_rx) = flume::bounded(64);
+        let (control_tx, control_rx) = flume::unbounded();
+        let disk_space = Arc::new(FakeDiskSpace(AtomicU64::new(available)));
+        spawn_recorder(data_rx, control_rx, options_with_space(disk_space));
+        (data_tx, control_tx)
+    }
+
+    /// 开始一段录制并写入 `timestamps`，随后录制线程在会话进行中退出（模拟崩溃）。
+    async fn crash_during(db_path: &Path, timestamps: &[u64]) -> RecordingStatus {
+        let (data_tx, control_tx) = spawn_with_space(u64::MAX);
+        let started = start(&control_tx, db_path).await.unwrap();
+        feed(&data_tx, timestamps).await;
+        // 数据通道关闭时录制线程直接退出，不结束会话；等它退出后控制通道随之关闭
+        drop(data_tx);
+        while recording_status(&control_tx).await.is_ok() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        started
+    }
+
+    #[test]
+    fn split_limits_count_samples_and_device_time() {
+        let none = RecordingSplit::default();
+        assert!(!none.reached(u64::MAX, u64::MAX));
+        assert!(none.validate().is_ok());
+
+        let by_duration = RecordingSplit {
+            max_duration_min: Some(1.0),
+            max_samples: None,
+        };
+        assert!(!by_duration.reached(100_000, 59_996));
+        assert!(by_duration.reached(1, 60_000));
+
+        assert!(RecordingSplit {
+            max_duration_min: Some(0.0),
+            max_samples: None,
+        }
+        .validate()
+        .is_err());
+        assert!(RecordingSplit {
+            max_duration_min: None,
+            max_samples: Some(0),
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn low_disk_space_refuses_start() {
+        let db_path = temp_db("low_disk_start");
+        let (_data_tx, control_tx) = spawn_with_space(500);
+
+        // 低于阈值：拒绝开始，也不创建数据库
+        let error = start(&control_tx, &db_path).await.unwrap_err();
+        assert!(error.to_string().contains("insufficient disk space"));
+        assert!(!recording_status(&control_tx).await.unwrap().recording);
+        assert!(!db_path.exists());
+    }
+
+    #[tokio::test]
+    async fn unanswered_start_reports_still_starting() {
+        // 录制线程未响应：开始录制超时报告为仍在启动，而不是失败
+        let (control_tx, _control_rx) = flume::unbounded();
+        let timeout = Duration::from_millis(200);
+        let error = start_recording_within(
+            &control_tx,
+            temp_db("still_starting"),
+            default_input(),
+            timeout,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RecordingStillStarting>(),
+            Some(&RecordingStillStarting(0))
+        );
+        assert!(RECORDER_START_TIMEOUT > RECORDER_REPLY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn repair_backfills_interrupted_sessions() {
+        let db_path = temp_db("repair");
+        let timestamps: Vec<u64> = (0..50).map(|i| 5000 + i * 4).collect();
+        let crashed = crash_during(&db_path, &timestamps).await;
+        let empty = crash_during(&db_path, &[]).await;
+
+        let (data_tx, control_tx) = spawn_with_space(u64::MAX);
+        let finished_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        feed(&data_tx, &[1000]).await;
+        stop_recording(&control_tx).await.unwrap();
+
+        // 正在录制的会话不能被修复
+        let active_id = start(&control_tx, &db_path)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        feed(&data_tx, &[9000]).await;
+
+        let repaired = repair_recordings(&control_tx, db_path.clone())
+            .await
+            .unwrap();
+        assert_eq!(repaired, 2);
+
+        let meta = list_recordings(&db_path, &RecordingQuery::default())
+            .await
+            .unwrap()
+            .items;
+        let by_id = |id: Option<i64>| meta.iter().find(|m| Some(m.id) == id).unwrap();
+        let crashed_meta = by_id(crashed.session_id);
+        assert!(crashed_meta.interrupted);
+        assert_eq!(crashed_meta.sample_count, 50);
+        assert_eq!(
+            crashed_meta.stopped_at_ms,
+            crashed.started_at_ms.map(|started| started + 49 * 4)
+        );
+        let empty_meta = by_id(empty.session_id);
+        assert!(empty_meta.interrupted);
+        assert_eq!(empty_meta.sample_count, 0);
+        assert_eq!(empty_meta.stopped_at_ms, empty.started_at_ms);
+        assert!(!by_id(Some(finished_id)).interrupted);
+        let still_active = by_id(Some(active_id));
+        assert!(!still_active.interrupted);
+        assert!(still_active.stopped_at_ms.is_none());
+
+        // 再次运行无事可做；不存在的数据库不会被创建
+        assert_eq!(
+            repair_recordings(&control_tx, db_path.clone())
+                .await
+                .unwrap(),
+            0
+        );
+        let missing = temp_db("repair_missing");
+        assert_eq!(
+            repair_recordings(&control_tx, missing.clone())
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(!missing.exists());
+
+        stop_recording(&control_tx).await.unwrap();
+        remove_db(&db_path);
+    }
+}
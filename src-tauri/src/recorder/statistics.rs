@@ -0,0 +1,84 @@
+//! 录制汇总统计的短时缓存。
+//!
+//! 统计要扫描全部会话并展开每个会话的标签，录制库大时每次都查会拖慢频繁刷新的概览页。
+//! 结果按数据库路径缓存 [`STATISTICS_CACHE_TTL`]，过期或换了录制目录后重新查询。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::types::recording::RecordingStatistics;
+
+/// 统计结果的缓存时长。
+pub const STATISTICS_CACHE_TTL: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+struct CachedStatistics {
+    computed_at: Instant,
+    db_path: PathBuf,
+    statistics: RecordingStatistics,
+}
+
+/// 录制统计缓存，跨命令共享。
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsCache {
+    entry: Arc<Mutex<Option<CachedStatistics>>>,
+}
+
+impl StatisticsCache {
+    /// 取 `db_path` 的缓存结果；不存在或已过期时返回 `None`。
+    pub fn get(&self, db_path: &Path) -> Option<RecordingStatistics> {
+        self.get_at(db_path, Instant::now())
+    }
+
+    /// 按给定时刻判断是否过期，便于测试。
+    pub fn get_at(&self, db_path: &Path, now: Instant) -> Option<RecordingStatistics> {
+        self.lock()
+            .as_ref()
+            .filter(|cached| {
+                cached.db_path == db_path
+                    && now.saturating_duration_since(cached.computed_at) < STATISTICS_CACHE_TTL
+            })
+            .map(|cached| cached.statistics.clone())
+    }
+
+    /// 缓存 `db_path` 的最新统计结果。
+    pub fn store(&self, db_path: &Path, statistics: RecordingStatistics) {
+        *self.lock() = Some(CachedStatistics {
+            computed_at: Instant::now(),
+            db_path: db_path.to_path_buf(),
+            statistics,
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<CachedStatistics>> {
+        self.entry
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_statistics_expire_and_are_keyed_by_path() {
+        let cache = StatisticsCache::default();
+        let db_path = Path::new("/tmp/a.sqlite");
+        assert_eq!(cache.get(db_path), None);
+
+        let statistics = RecordingStatistics {
+            session_count: 3,
+            ..RecordingStatistics::default()
+        };
+        cache.store(db_path, statistics.clone());
+        assert_eq!(cache.get(db_path), Some(statistics));
+        assert_eq!(cache.get(Path::new("/tmp/b.sqlite")), None);
+
+        let expired = Instant::now() + STATISTICS_CACHE_TTL;
+        assert_eq!(cache.get_at(db_path, expired), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! 录制模块测试共用的夹具：合成帧、临时数据库、可调剩余空间与录制线程操作。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use flume::Sender;
+use math_f64::{DQuat, DVec3};
+
+use crate::{
+    processor::{
+        fault::FaultInjector,
+        navigator::{MotionState, NavState, ZuptState},
+        output::{OutputFrame, StageCapture},
+        parser::ImuSampleRaw,
+        shared::DeviceTimeMs,
+    },
+    recorder::{
+        disk::DiskSpaceProvider, start_recording, RecorderCommand, RecorderOptions, RecordingSplit,
+        RecordingStartInput,
+    },
+    types::recording::RecordingStatus,
+};
+
+/// 静止的合成输出帧。
+pub(super) fn frame(timestamp_ms: u64) -> OutputFrame {
+    OutputFrame {
+        raw: ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        },
+        nav: NavState {
+            timestamp_ms,
+            position: DVec3::ZERO,
+            velocity: DVec3::ZERO,
+            attitude: DQuat::IDENTITY,
+        },
+        motion_state: MotionState::Static,
+        zupt: ZuptState::default(),
+        position_source: Default::default(),
+        jerk: None,
+        ang_accel: None,
+        position_sigma_m: 0.0,
+        clock_sync: None,
+        heading_yaw_deg: None,
+        baro_relative_altitude_m: None,
+        debug_vectors: None,
+        stages: None,
+    }
+}
+
+/// 可在测试中调整剩余空间的假实现。
+pub(super) struct FakeDiskSpace(pub(super) AtomicU64);
+
+impl DiskSpaceProvider for FakeDiskSpace {
+    fn available_bytes(&self, _path: &Path) -> Option<u64> {
+        Some(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// 录制线程选项：剩余空间由 `disk_space` 决定，阈值 1000 字节，每次写入都复查。
+pub(super) fn options_with_space(disk_space: Arc<FakeDiskSpace>) -> RecorderOptions {
+    RecorderOptions {
+        disk_space,
+        min_free_bytes: 1000,
+        check_interval: Duration::ZERO,
+        on_stopped: None,
+        flight_recorder_frames: 0,
+        faults: FaultInjector::new_handle(),
+        stage_capture: StageCapture::new_handle(),
+    }
+}
+
+/// 临时目录中的测试数据库路径，先删掉上次残留的文件。
+pub(super) fn temp_db(name: &str) -> PathBuf {
+    let db_path =
+        std::env::temp_dir().join(format!("imu_vis_{name}_test_{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&db_path);
+    db_path
+}
+
+/// 删除测试数据库及其 WAL 文件。
+pub(super) fn remove_db(db_path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", db_path.display()));
+    }
+}
+
+/// 以默认参数开始录制。
+pub(super) async fn start(
+    control_tx: &Sender<RecorderCommand>,
+    db_path: &Path,
+) -> anyhow::Result<RecordingStatus> {
+    start_recording(
+        control_tx,
+        db_path.to_path_buf(),
+        RecordingStartInput {
+            device_id: None,
+            name: None,
+            tags: None,
+            split: RecordingSplit::default(),
+            metadata: None,
+            deep: false,
+        },
+    )
+    .await
+}
+
+/// 逐帧送入并等待录制线程取空数据通道。
+pub(super) async fn feed(data_tx: &Sender<OutputFrame>, timestamps: &[u64]) {
+    for &timestamp_ms in timestamps {
+        data_tx.send(frame(timestamp_ms)).unwrap();
+    }
+    while !data_tx.is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
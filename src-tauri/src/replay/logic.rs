@@ -0,0 +1,613 @@
+//! 回放控制器：按设备时间戳与倍速把录制帧送往输出分发。
+
+use std::{
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use math_f64::{DQuat, DVec3};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::{
+    headless::sample_row_to_raw,
+    processor::{
+        fanout::OutputItem,
+        navigator::{MotionState, NavState, PositionSource, ZuptState},
+        output::{OutputBuilder, OutputFrame},
+        pipeline::{
+            diagnostics::{diagnostics_channel, QueueProbe},
+            ProcessorPipeline, ProcessorPipelineConfig,
+        },
+        suppress::{OutputSuppressConfig, StaticSuppressor, StreamSeqHandle},
+    },
+    recorder::{db, models},
+    replay::types::{
+        PlaybackError, PlaybackMode, PlaybackState, PlaybackStatus, MAX_PLAYBACK_SPEED,
+        MIN_PLAYBACK_SPEED,
+    },
+};
+
+/// 读取会话全部样本并还原为录制时的输出帧（按设备时间戳升序）。
+pub async fn load_session_frames(
+    db_path: &Path,
+    session_id: i64,
+) -> anyhow::Result<Vec<OutputFrame>> {
+    let db = db::connect(db_path).await?;
+    db::ensure_schema(&db).await?;
+    let rows = models::imu_samples::Entity::find()
+        .filter(models::imu_samples::Column::SessionId.eq(session_id))
+        .order_by_asc(models::imu_samples::Column::TimestampMs)
+        .all(&db)
+        .await
+        .context("query playback samples")?;
+    Ok(rows.iter().map(stored_frame).collect())
+}
+
+/// 把一行录制样本还原为输出帧：原始样本与存储的导航结果。
+pub fn stored_frame(row: &models::imu_samples::Model) -> OutputFrame {
+    let motion_state = MotionState::from_stored(&row.motion_state);
+    OutputFrame {
+        raw: sample_row_to_raw(row),
+        nav: NavState {
+            timestamp_ms: row.calc_timestamp_ms as u64,
+            position: DVec3::new(
+                row.calc_position_x,
+                row.calc_position_y,
+                row.calc_position_z,
+            ),
+            velocity: DVec3::new(
+                row.calc_velocity_x,
+                row.calc_velocity_y,
+                row.calc_velocity_z,
+            ),
+            attitude: DQuat::from_xyzw(
+                row.calc_attitude_x,
+                row.calc_attitude_y,
+                row.calc_attitude_z,
+                row.calc_attitude_w,
+            ),
+        },
+        motion_state,
+        // 录制只保存运动状态，范数与锁定位置不落盘
+        zupt: ZuptState {
+            is_static: motion_state == MotionState::Static,
+            ..ZuptState::default()
+        },
+        position_source: PositionSource::from_stored(&row.position_source),
+        jerk: None,
+        ang_accel: None,
+        position_sigma_m: row.position_sigma_m.unwrap_or_default(),
+        clock_sync: None,
+        heading_yaw_deg: None,
+        baro_relative_altitude_m: row.baro_relative_altitude_m,
+        debug_vectors: None,
+        stages: None,
+    }
+}
+
+/// 循环回绕时末帧与首帧之间的最小设备时间间隔（ms）。
+///
+/// 单帧会话或全部帧时间戳相同时首两帧间隔为 0，回绕后的首帧总是已经到期，
+/// [`PlaybackController::poll`] 会一直循环下去。
+const MIN_WRAP_GAP_MS: u64 = 1;
+
+/// 回放时钟锚点：主机时刻 `host` 对应设备时间 `device_ms`。
+#[derive(Debug, Clone, Copy)]
+struct PlaybackClock {
+    host: Instant,
+    device_ms: f64,
+}
+
+/// 回放控制器，持有已加载会话的全部帧。
+///
+/// 不自己计时：驱动方按 [`next_due`](Self::next_due) 等待，再以当前时刻调用
+/// [`poll`](Self::poll) 取出到期的帧。消息序号由同一个静止抑制器连续编号，
+/// 跳转、循环与重新加载都不会让序号回退；用 [`with_seq`](Self::with_seq) 与处理线程共用
+/// 序号计数器时，回放消息接在实时消息之后编号。
+pub struct PlaybackController {
+    session_id: Option<i64>,
+    frames: Vec<OutputFrame>,
+    mode: PlaybackMode,
+    pipeline: Option<ProcessorPipeline>,
+    suppressor: StaticSuppressor,
+    suppress: OutputSuppressConfig,
+    state: PlaybackState,
+    speed: f64,
+    looping: bool,
+    cursor: usize,
+    position_ms: Option<u64>,
+    clock: Option<PlaybackClock>,
+}
+
+impl Default for PlaybackController {
+    fn default() -> Self {
+        Self {
+            session_id: None,
+            frames: Vec::new(),
+            mode: PlaybackMode::default(),
+            pipeline: None,
+            suppressor: StaticSuppressor::default(),
+            suppress: OutputSuppressConfig::default(),
+            state: PlaybackState::Idle,
+            speed: 1.0,
+            looping: false,
+            cursor: 0,
+            position_ms: None,
+            clock: None,
+        }
+    }
+}
+
+impl PlaybackController {
+    /// 从共享计数器取消息序号的控制器。
+    pub fn with_seq(seq: StreamSeqHandle) -> Self {
+        Self {
+            suppressor: StaticSuppressor::with_seq(seq),
+            ..Self::default()
+        }
+    }
+
+    /// 加载会话帧并暂停在首帧；倍速与循环设置保留。
+    pub fn load(
+        &mut self,
+        session_id: i64,
+        frames: Vec<OutputFrame>,
+        mode: PlaybackMode,
+        config: ProcessorPipelineConfig,
+    ) -> Result<PlaybackStatus, PlaybackError> {
+        if frames.is_empty() {
+            return Err(PlaybackError::EmptySession(session_id));
+        }
+        self.session_id = Some(session_id);
+        self.position_ms = Some(frames[0].raw.timestamp_ms.as_millis());
+        self.frames = frames;
+        self.mode = mode;
+        self.suppress = config.output_suppress;
+        self.pipeline = match mode {
+            PlaybackMode::Stored => None,
+            PlaybackMode::Reprocess => Some(new_pipeline(config)),
+        };
+        self.state = PlaybackState::Paused;
+        self.cursor = 0;
+        self.clock = None;
+        Ok(self.status())
+    }
+
+    /// 开始或继续播放；已播放到末尾时从头开始。
+    pub fn play(&mut self, now: Instant) -> Result<PlaybackStatus, PlaybackError> {
+        self.ensure_loaded()?;
+        if self.state == PlaybackState::Ended {
+            self.rewind();
+        }
+        if self.state != PlaybackState::Playing {
+            self.state = PlaybackState::Playing;
+            self.anchor(now);
+        }
+        Ok(self.status())
+    }
+
+    /// 暂停，位置保持不变。
+    pub fn pause(&mut self) -> Result<PlaybackStatus, PlaybackError> {
+        self.ensure_loaded()?;
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+            self.clock = None;
+        }
+        Ok(self.status())
+    }
+
+    /// 跳转到不早于 `timestamp_ms` 的第一帧；重跑管线时先重置管线状态。
+    pub fn seek(
+        &mut self,
+        timestamp_ms: u64,
+        now: Instant,
+    ) -> Result<PlaybackStatus, PlaybackError> {
+        self.ensure_loaded()?;
+        let start_ms = self.frames[0].raw.timestamp_ms.as_millis();
+        let end_ms = self.frames[self.frames.len() - 1]
+            .raw
+            .timestamp_ms
+            .as_millis();
+        if !(start_ms..=end_ms).contains(&timestamp_ms) {
+            return Err(PlaybackError::SeekOutOfRange {
+                timestamp_ms,
+                start_ms,
+                end_ms,
+            });
+        }
+        self.cursor = self
+            .frames
+            .partition_point(|frame| frame.raw.timestamp_ms.as_millis() < timestamp_ms);
+        self.position_ms = Some(self.frames[self.cursor].raw.timestamp_ms.as_millis());
+        self.reset_pipeline();
+        match self.state {
+            PlaybackState::Playing => self.anchor(now),
+            PlaybackState::Ended => self.state = PlaybackState::Paused,
+            PlaybackState::Idle | PlaybackState::Paused => {}
+        }
+        Ok(self.status())
+    }
+
+    /// 设置倍速（0.25 ~ 8），播放中从当前位置按新倍速继续。
+    pub fn set_speed(&mut self, speed: f64, now: Instant) -> Result<PlaybackStatus, PlaybackError> {
+        if !(MIN_PLAYBACK_SPEED..=MAX_PLAYBACK_SPEED).contains(&speed) {
+            return Err(PlaybackError::InvalidSpeed(speed));
+        }
+        if let Some(clock) = self.clock {
+            self.clock = Some(PlaybackClock {
+                host: now,
+                device_ms: self.device_ms_at(clock, now),
+            });
+        }
+        self.speed = speed;
+        Ok(self.status())
+    }
+
+    /// 开启或关闭循环。
+    pub fn set_loop(&mut self, enabled: bool) -> PlaybackStatus {
+        self.looping = enabled;
+        self.status()
+    }
+
+    /// 取出截至 `now` 已到期的帧，按播放顺序排列。
+    pub fn poll(&mut self, now: Instant) -> Vec<OutputItem> {
+        let mut items = Vec::new();
+        while let Some(due) = self.next_due() {
+            if due > now {
+                break;
+            }
+            let frame = self.frames[self.cursor];
+            self.cursor += 1;
+            self.position_ms = Some(frame.raw.timestamp_ms.as_millis());
+            items.extend(self.emit(frame));
+            if self.cursor == self.frames.len() {
+                self.wrap_or_end(due);
+            }
+        }
+        items
+    }
+
+    /// 下一帧的到期时刻，未在播放时为空。
+    pub fn next_due(&self) -> Option<Instant> {
+        let clock = self
+            .clock
+            .filter(|_| self.state == PlaybackState::Playing)?;
+        let frame = self.frames.get(self.cursor)?;
+        let ahead_ms = (frame.raw.timestamp_ms.as_millis() as f64 - clock.device_ms).max(0.0);
+        // 按纳秒取整，避免浮点误差让整毫秒的间隔差出 1 ns
+        let ahead_ns = (ahead_ms / self.speed * 1e6).round() as u64;
+        Some(clock.host + Duration::from_nanos(ahead_ns))
+    }
+
+    /// 当前回放状态。
+    pub fn status(&self) -> PlaybackStatus {
+        PlaybackStatus {
+            session_id: self.session_id,
+            state: self.state,
+            mode: self.mode,
+            speed: self.speed,
+            looping: self.looping,
+            position_ms: self.position_ms,
+            start_ms: self
+                .frames
+                .first()
+                .map(|frame| frame.raw.timestamp_ms.as_millis()),
+            end_ms: self
+                .frames
+                .last()
+                .map(|frame| frame.raw.timestamp_ms.as_millis()),
+            frame_index: self.cursor,
+            frame_count: self.frames.len(),
+        }
+    }
+
+    fn ensure_loaded(&self) -> Result<(), PlaybackError> {
+        match self.state {
+            PlaybackState::Idle => Err(PlaybackError::NotLoaded),
+            _ => Ok(()),
+        }
+    }
+
+    /// 以 `now` 为起点，从当前帧开始计时。
+    fn anchor(&mut self, now: Instant) {
+        self.clock = self.frames.get(self.cursor).map(|frame| PlaybackClock {
+            host: now,
+            device_ms: frame.raw.timestamp_ms.as_millis() as f64,
+        });
+    }
+
+    fn device_ms_at(&self, clock: PlaybackClock, now: Instant) -> f64 {
+        let elapsed_ms = now.saturating_duration_since(clock.host).as_secs_f64() * 1000.0;
+        clock.device_ms + elapsed_ms * self.speed
+    }
+
+    /// 播完最后一帧：循环时回到首帧，按首两帧的间隔（至少 [`MIN_WRAP_GAP_MS`]）
+    /// 接在末帧之后继续计时。
+    fn wrap_or_end(&mut self, last_due: Instant) {
+        if !self.looping {
+            self.state = PlaybackState::Ended;
+            self.clock = None;
+            return;
+        }
+        self.rewind();
+        let gap_ms = match &self.frames[..] {
+            [first, second, ..] => second.raw.timestamp_ms.delta_ms(first.raw.timestamp_ms),
+            _ => 0,
+        }
+        .max(MIN_WRAP_GAP_MS);
+        self.clock = Some(PlaybackClock {
+            host: last_due,
+            device_ms: self.frames[0].raw.timestamp_ms.as_millis() as f64 - gap_ms as f64,
+        });
+    }
+
+    fn rewind(&mut self) {
+        self.cursor = 0;
+        self.position_ms = Some(self.frames[0].raw.timestamp_ms.as_millis());
+        self.reset_pipeline();
+    }
+
+    fn reset_pipeline(&mut self) {
+        if let Some(pipeline) = self.pipeline.as_mut() {
+            pipeline.reset();
+        }
+    }
+
+    fn emit(&mut self, stored: OutputFrame) -> Option<OutputItem> {
+        let frame = match self.pipeline.as_mut() {
+            Some(pipeline) => pipeline.process_sample_raw(stored.raw)?,
+            None => stored,
+        };
+        let data = OutputBuilder::build(&frame);
+        let message = self.suppressor.process(&data, &self.suppress);
+        Some(OutputItem { frame, message })
+    }
+}
+
+/// 回放专用管线：诊断关闭，不连接真实队列。
+fn new_pipeline(config: ProcessorPipelineConfig) -> ProcessorPipeline {
+    let (diag_tx, _diag_rx) = diagnostics_channel(1);
+    ProcessorPipeline::new(
+        config,
+        Arc::new(AtomicBool::new(false)),
+        diag_tx,
+        QueueProbe::detached(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        processor::{parser::ImuSampleRaw, shared::DeviceTimeMs, suppress::StreamSeq},
+        types::outputs::StreamMessage,
+    };
+
+    /// 250 Hz 合成会话：`count` 帧，首帧时间戳 1000 ms，位置随时间线性增长。
+    fn session(count: u64) -> Vec<OutputFrame> {
+        (0..count)
+            .map(|i| {
+                let timestamp_ms = 1_000 + i * 4;
+                OutputFrame {
+                    raw: ImuSampleRaw {
+                        timestamp_ms: DeviceTimeMs(timestamp_ms),
+                        accel_no_g: DVec3::ZERO,
+                        accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+                        gyro: DVec3::ZERO,
+                        quat: DQuat::IDENTITY,
+                        angle: DVec3::ZERO,
+                        offset: DVec3::ZERO,
+                        accel_nav: DVec3::ZERO,
+                        baro: None,
+                    },
+                    nav: NavState {
+                        timestamp_ms,
+                        position: DVec3::new(i as f64, 0.0, 0.0),
+                        velocity: DVec3::ZERO,
+                        attitude: DQuat::IDENTITY,
+                    },
+                    motion_state: MotionState::Moving,
+                    zupt: ZuptState::default(),
+                    position_source: PositionSource::default(),
+                    jerk: None,
+                    ang_accel: None,
+                    position_sigma_m: 0.0,
+                    clock_sync: None,
+                    heading_yaw_deg: None,
+                    baro_relative_altitude_m: None,
+                    debug_vectors: None,
+                    stages: None,
+                }
+            })
+            .collect()
+    }
+
+    fn loaded(count: u64, mode: PlaybackMode) -> PlaybackController {
+        let mut controller = PlaybackController::default();
+        controller
+            .load(7, session(count), mode, ProcessorPipelineConfig::default())
+            .unwrap();
+        controller
+    }
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    fn timestamps(items: &[OutputItem]) -> Vec<u64> {
+        items
+            .iter()
+            .map(|item| item.frame.raw.timestamp_ms.as_millis())
+            .collect()
+    }
+
+    fn seqs(items: &[OutputItem]) -> Vec<u64> {
+        items
+            .iter()
+            .filter_map(|item| item.message.as_ref().map(StreamMessage::seq))
+            .collect()
+    }
+
+    #[test]
+    fn double_speed_halves_frame_spacing() {
+        let t0 = Instant::now();
+        let mut normal = loaded(100, PlaybackMode::Stored);
+        let mut fast = loaded(100, PlaybackMode::Stored);
+        normal.play(t0).unwrap();
+        fast.set_speed(2.0, t0).unwrap();
+        fast.play(t0).unwrap();
+
+        // 首帧立即到期，之后 1x 每 4 ms 一帧，2x 每 2 ms 一帧
+        assert_eq!(timestamps(&fast.poll(t0)), [1_000]);
+        assert_eq!(fast.next_due(), Some(t0 + ms(2)));
+        assert_eq!(normal.poll(t0).len(), 1);
+        assert_eq!(normal.next_due(), Some(t0 + ms(4)));
+
+        // 主机时间 20 ms：1x 播到设备时间 +20 ms，2x 播到 +40 ms
+        assert_eq!(
+            timestamps(&normal.poll(t0 + ms(20))),
+            [1_004, 1_008, 1_012, 1_016, 1_020]
+        );
+        assert_eq!(fast.poll(t0 + ms(20)).len(), 10);
+        assert_eq!(fast.status().position_ms, Some(1_040));
+
+        // 中途改倍速从当前位置继续，不跳帧
+        fast.set_speed(1.0, t0 + ms(20)).unwrap();
+        assert_eq!(fast.next_due(), Some(t0 + ms(24)));
+        assert_eq!(
+            fast.set_speed(16.0, t0),
+            Err(PlaybackError::InvalidSpeed(16.0))
+        );
+    }
+
+    #[test]
+    fn seek_jumps_to_the_first_frame_at_or_after_the_target() {
+        let t0 = Instant::now();
+        let mut controller = loaded(100, PlaybackMode::Stored);
+
+        let status = controller.seek(1_201, t0).unwrap();
+        assert_eq!(status.position_ms, Some(1_204));
+        assert_eq!(status.frame_index, 51);
+        assert_eq!(status.state, PlaybackState::Paused);
+        // 暂停时不输出
+        assert!(controller.poll(t0 + ms(100)).is_empty());
+
+        controller.play(t0).unwrap();
+        let items = controller.poll(t0 + ms(8));
+        assert_eq!(timestamps(&items), [1_204, 1_208, 1_212]);
+        // 存储模式直接播放录制时的导航结果
+        assert_eq!(items[0].frame.nav.position.x, 51.0);
+
+        // 播放中跳转：从新位置重新计时
+        controller.seek(1_000, t0 + ms(8)).unwrap();
+        assert_eq!(timestamps(&controller.poll(t0 + ms(8))), [1_000]);
+
+        assert_eq!(
+            controller.seek(5_000, t0),
+            Err(PlaybackError::SeekOutOfRange {
+                timestamp_ms: 5_000,
+                start_ms: 1_000,
+                end_ms: 1_396,
+            })
+        );
+        assert_eq!(
+            PlaybackController::default().seek(1_000, t0),
+            Err(PlaybackError::NotLoaded)
+        );
+    }
+
+    #[test]
+    fn seek_resets_the_pipeline_when_reprocessing() {
+        let t0 = Instant::now();
+        let mut controller = loaded(100, PlaybackMode::Reprocess);
+        controller.play(t0).unwrap();
+        let first_pass = controller.poll(t0 + ms(40));
+        assert!(!first_pass.is_empty());
+
+        // 跳回开头后管线从头积分，输出与第一次播放相同
+        controller.seek(1_000, t0 + ms(40)).unwrap();
+        let second_pass = controller.poll(t0 + ms(80));
+        assert_eq!(timestamps(&second_pass), timestamps(&first_pass));
+        let positions = |items: &[OutputItem]| -> Vec<DVec3> {
+            items.iter().map(|item| item.frame.nav.position).collect()
+        };
+        assert_eq!(positions(&second_pass), positions(&first_pass));
+    }
+
+    #[test]
+    fn looping_wraps_with_continuous_seq_and_pacing() {
+        let t0 = Instant::now();
+        let mut controller = loaded(5, PlaybackMode::Stored);
+        controller.set_loop(true);
+        controller.play(t0).unwrap();
+
+        // 5 帧跨 16 ms，第二轮首帧接在末帧后 4 ms
+        let items = controller.poll(t0 + ms(16));
+        assert_eq!(timestamps(&items), [1_000, 1_004, 1_008, 1_012, 1_016]);
+        assert_eq!(controller.status().frame_index, 0);
+        assert_eq!(controller.next_due(), Some(t0 + ms(20)));
+
+        let wrapped = controller.poll(t0 + ms(24));
+        assert_eq!(timestamps(&wrapped), [1_000, 1_004]);
+        let all: Vec<u64> = seqs(&items).into_iter().chain(seqs(&wrapped)).collect();
+        assert_eq!(all, (0..7).collect::<Vec<_>>());
+
+        // 关闭循环后播到末尾停止，再次播放从头开始
+        controller.set_loop(false);
+        controller.poll(t0 + ms(100));
+        assert_eq!(controller.status().state, PlaybackState::Ended);
+        assert_eq!(controller.next_due(), None);
+        controller.play(t0 + ms(100)).unwrap();
+        assert_eq!(seqs(&controller.poll(t0 + ms(100))), [10]);
+    }
+
+    #[test]
+    fn looping_a_single_timestamp_session_advances_by_the_minimum_gap() {
+        let t0 = Instant::now();
+        for count in [1, 3] {
+            let mut frames = session(count);
+            for frame in &mut frames {
+                frame.raw.timestamp_ms = DeviceTimeMs(1_000);
+            }
+            let mut controller = PlaybackController::default();
+            controller
+                .load(
+                    7,
+                    frames,
+                    PlaybackMode::Stored,
+                    ProcessorPipelineConfig::default(),
+                )
+                .unwrap();
+            controller.set_loop(true);
+            controller.play(t0).unwrap();
+
+            // 每轮至少间隔 1 ms：10 ms 内播 11 轮，而不是无限回绕
+            let items = controller.poll(t0 + ms(10));
+            assert_eq!(items.len(), 11 * count as usize);
+            assert_eq!(controller.next_due(), Some(t0 + ms(11)));
+        }
+    }
+
+    #[test]
+    fn shared_seq_continues_after_live_messages() {
+        let seq = StreamSeq::new_handle();
+        let live: Vec<u64> = (0..3).map(|_| seq.next()).collect();
+        assert_eq!(live, [0, 1, 2]);
+
+        let t0 = Instant::now();
+        let mut controller = PlaybackController::with_seq(seq.clone());
+        controller
+            .load(
+                7,
+                session(3),
+                PlaybackMode::Stored,
+                ProcessorPipelineConfig::default(),
+            )
+            .unwrap();
+        controller.play(t0).unwrap();
+        assert_eq!(seqs(&controller.poll(t0 + ms(8))), [3, 4, 5]);
+        assert_eq!(seq.next(), 6);
+    }
+}
@@ -0,0 +1,22 @@
+//! 应用内录制回放。
+//!
+//! 把已录制会话按设备时间戳与倍速重新送进输出分发，前端看到的就像设备在实时上报：
+//! 支持播放、暂停、跳转、倍速（0.25 ~ 8 倍）与循环。数据来源二选一：
+//!
+//! - [`PlaybackMode::Stored`]：直接播放录制时存储的导航结果，跳转只是移动游标；
+//! - [`PlaybackMode::Reprocess`]：把原始样本重新送进管线，跳转与循环时先重置管线状态。
+//!
+//! 这里只放与 Tauri 无关的部分，计时驱动与事件推送见 `AppState`。
+
+/// 回放控制器与会话加载。
+pub mod logic;
+/// 回放状态与错误类型。
+pub mod types;
+
+/// 回放控制器与会话加载。
+pub use logic::{load_session_frames, stored_frame, PlaybackController};
+/// 回放状态与错误类型。
+pub use types::{
+    PlaybackError, PlaybackMode, PlaybackRefused, PlaybackState, PlaybackStatus,
+    MAX_PLAYBACK_SPEED, MIN_PLAYBACK_SPEED, PLAYBACK_STATUS_INTERVAL,
+};
@@ -0,0 +1,112 @@
+//! 回放状态与错误类型。
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::error::ErrorCode;
+
+/// 最低回放倍速。
+pub const MIN_PLAYBACK_SPEED: f64 = 0.25;
+/// 最高回放倍速。
+pub const MAX_PLAYBACK_SPEED: f64 = 8.0;
+/// 播放中推送 `playback_status` 事件的间隔。
+pub const PLAYBACK_STATUS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 回放数据来源。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackMode {
+    /// 直接播放录制时存储的导航结果，跳转即可。
+    #[default]
+    Stored,
+    /// 把原始样本重新送进管线，跳转与循环时重置管线状态。
+    Reprocess,
+}
+
+/// 回放状态。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackState {
+    /// 未加载会话。
+    #[default]
+    Idle,
+    /// 播放中。
+    Playing,
+    /// 已暂停（加载后也处于暂停）。
+    Paused,
+    /// 播放到末尾且未开启循环。
+    Ended,
+}
+
+/// `get_playback_status` 返回值与 `playback_status` 事件载荷。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlaybackStatus {
+    /// 已加载的会话 ID。
+    pub session_id: Option<i64>,
+    /// 回放状态。
+    pub state: PlaybackState,
+    /// 数据来源。
+    pub mode: PlaybackMode,
+    /// 倍速。
+    pub speed: f64,
+    /// 是否循环。
+    pub looping: bool,
+    /// 当前位置（设备时间戳，毫秒）：最近播放的一帧，跳转后为目标帧。
+    pub position_ms: Option<u64>,
+    /// 会话首帧设备时间戳（毫秒）。
+    pub start_ms: Option<u64>,
+    /// 会话末帧设备时间戳（毫秒）。
+    pub end_ms: Option<u64>,
+    /// 下一帧的下标。
+    pub frame_index: usize,
+    /// 会话总帧数。
+    pub frame_count: usize,
+}
+
+/// 回放控制错误。
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PlaybackError {
+    /// 尚未加载会话。
+    #[error("尚未加载回放会话")]
+    NotLoaded,
+    /// 会话没有样本。
+    #[error("会话 {0} 没有样本，无法回放")]
+    EmptySession(i64),
+    /// 倍速超出范围。
+    #[error("回放倍速 {0} 超出范围，应为 {MIN_PLAYBACK_SPEED} ~ {MAX_PLAYBACK_SPEED}")]
+    InvalidSpeed(f64),
+    /// 跳转目标不在会话时间范围内。
+    #[error("跳转位置 {timestamp_ms} ms 不在会话范围 {start_ms} ~ {end_ms} ms 内")]
+    SeekOutOfRange {
+        /// 请求的设备时间戳。
+        timestamp_ms: u64,
+        /// 会话首帧时间戳。
+        start_ms: u64,
+        /// 会话末帧时间戳。
+        end_ms: u64,
+    },
+}
+
+/// 实时数据占用输出时拒绝开始回放。
+///
+/// 回放帧与实时帧共用前端订阅，同时进行会在界面上交错，因此设备上报或录制期间不允许播放。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PlaybackRefused {
+    /// 设备已连接并在上报数据。
+    #[error("设备正在上报数据，请先断开设备再回放")]
+    DeviceStreaming,
+    /// 正在录制。
+    #[error("正在录制，请先停止录制再回放")]
+    RecordingActive,
+}
+
+impl PlaybackRefused {
+    /// IPC 错误码。
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PlaybackRefused::DeviceStreaming => ErrorCode::PlaybackDeviceStreaming,
+            PlaybackRefused::RecordingActive => ErrorCode::PlaybackRecordingActive,
+        }
+    }
+}
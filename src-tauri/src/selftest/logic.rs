@@ -0,0 +1,200 @@
+//! 自检脚本的可复用部分：合成运动、参考结果与各环节校验。
+
+use std::{
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
+
+use anyhow::{bail, ensure, Context};
+use math_f64::{DQuat, DVec3};
+
+use crate::{
+    processor::{
+        analysis::quat_angle_deg,
+        output::OutputFrame,
+        parser::{ImuParser, ImuSampleRaw},
+        pipeline::{
+            diagnostics::{diagnostics_channel, QueueProbe},
+            ProcessorPipeline, ProcessorPipelineConfig,
+        },
+        shared::DeviceTimeMs,
+        stats::ProcessorStatsSnapshot,
+    },
+    recorder,
+};
+
+/// 合成运动的总时长（设备时间），样本数按标称采样间隔换算。
+pub const PROFILE_DURATION_MS: f64 = 1000.0;
+/// 首帧设备时间戳。
+const PROFILE_START_MS: u64 = 10_000;
+/// 加速/减速段的世界系加速度 (m/s²)。
+const PROFILE_ACCEL_MS2: f64 = 0.5;
+/// 设备绕 Z 轴的固定偏航角（度），让机体系与世界系不重合。
+const PROFILE_YAW_DEG: f64 = 30.0;
+const GRAVITY: f64 = 9.80665;
+
+/// 实时链路与离线参考的位置容差 (m)。
+pub const POSITION_TOLERANCE_M: f64 = 1e-6;
+/// 实时链路与离线参考的姿态容差（度）。
+pub const ATTITUDE_TOLERANCE_DEG: f64 = 1e-3;
+/// 处理线程吞吐的下限（帧/秒），远低于正常值，只用于发现卡死或严重拥塞。
+pub const MIN_PLAUSIBLE_FRAME_RATE: f64 = 100.0;
+
+/// 已知的合成运动：静止 200 ms → 沿世界 x 匀加速 200 ms → 匀减速 200 ms → 静止。
+///
+/// 按 `sample_interval_ms` 采样，与设备当前上报频率一致。峰值速度 0.1 m/s，
+/// 理想位移 0.02 m；实际结果取决于当前管线配置，校验时以同一配置的离线参考为准。
+pub fn motion_profile(sample_interval_ms: f64) -> Vec<ImuSampleRaw> {
+    let attitude = DQuat::from_rotation_z(PROFILE_YAW_DEG.to_radians());
+    let to_body = attitude.inverse();
+    let samples = (PROFILE_DURATION_MS / sample_interval_ms).round() as usize;
+    (0..samples)
+        .map(|i| {
+            let t_ms = (i as f64 * sample_interval_ms).round();
+            let accel_world = if (200.0..400.0).contains(&t_ms) {
+                DVec3::X * PROFILE_ACCEL_MS2
+            } else if (400.0..600.0).contains(&t_ms) {
+                DVec3::X * -PROFILE_ACCEL_MS2
+            } else {
+                DVec3::ZERO
+            };
+            ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(PROFILE_START_MS + t_ms as u64),
+                accel_no_g: to_body.rotate_vec3(accel_world),
+                accel_with_g: to_body.rotate_vec3(accel_world + DVec3::Z * GRAVITY),
+                gyro: DVec3::ZERO,
+                quat: attitude,
+                angle: DVec3::Z * PROFILE_YAW_DEG,
+                offset: DVec3::ZERO,
+                accel_nav: accel_world,
+                baro: None,
+            }
+        })
+        .collect()
+}
+
+/// 把样本编码成 0x11 数据包，与 BLE 通知的载荷一致。
+pub fn encode_packets(samples: &[ImuSampleRaw]) -> Vec<Vec<u8>> {
+    samples.iter().map(ImuParser::encode).collect()
+}
+
+/// 用给定配置与标称采样间隔在当前线程逐包运行管线（含解析），作为实时链路的参考结果。
+pub fn run_packets(
+    config: ProcessorPipelineConfig,
+    sample_interval_ms: f64,
+    packets: &[Vec<u8>],
+) -> Vec<OutputFrame> {
+    let (diag_tx, _diag_rx) = diagnostics_channel(1);
+    let mut pipeline = ProcessorPipeline::new(
+        config,
+        Arc::new(AtomicBool::new(false)),
+        diag_tx,
+        QueueProbe::detached(),
+    );
+    pipeline.set_sample_interval_ms(sample_interval_ms);
+    packets
+        .iter()
+        .filter_map(|packet| pipeline.process_packet(packet))
+        .collect()
+}
+
+/// 校验输出帧数与参考一致。
+pub fn verify_frame_count(expected: usize, received: usize) -> anyhow::Result<String> {
+    ensure!(
+        received == expected,
+        "pipeline produced {received} of {expected} expected frames"
+    );
+    Ok(format!("{received} frames"))
+}
+
+/// 校验最终导航状态与参考一致。
+pub fn verify_final_state(expected: &OutputFrame, actual: &OutputFrame) -> anyhow::Result<String> {
+    ensure!(
+        actual.nav.timestamp_ms == expected.nav.timestamp_ms,
+        "final frame timestamp {} ms, expected {} ms",
+        actual.nav.timestamp_ms,
+        expected.nav.timestamp_ms
+    );
+    let position_error = actual.nav.position.distance(expected.nav.position);
+    ensure!(
+        position_error <= POSITION_TOLERANCE_M,
+        "final position {:?} deviates {position_error:.3e} m from expected {:?}",
+        actual.nav.position,
+        expected.nav.position
+    );
+    let attitude_error = quat_angle_deg(actual.nav.attitude, expected.nav.attitude);
+    ensure!(
+        attitude_error <= ATTITUDE_TOLERANCE_DEG,
+        "final attitude deviates {attitude_error:.3e}° from expected"
+    );
+    let p = actual.nav.position;
+    Ok(format!(
+        "position=({:.4}, {:.4}, {:.4}) m, Δp={position_error:.1e} m, Δq={attitude_error:.1e}°",
+        p.x, p.y, p.z
+    ))
+}
+
+/// 校验处理器统计在自检期间正常推进。
+///
+/// * `before` / `after`: 推送数据前后的统计快照
+/// * `expected_frames`: 参考输出帧数
+/// * `last_timestamp_ms`: 参考最后一帧的设备时间戳
+/// * `elapsed`: 从推送首包到收齐输出的耗时
+pub fn verify_stats(
+    before: &ProcessorStatsSnapshot,
+    after: &ProcessorStatsSnapshot,
+    expected_frames: usize,
+    last_timestamp_ms: u64,
+    elapsed: Duration,
+) -> anyhow::Result<String> {
+    let frames = after
+        .frames_since_connect
+        .saturating_sub(before.frames_since_connect);
+    ensure!(
+        frames == expected_frames as u64,
+        "stats counted {frames} frames, expected {expected_frames}"
+    );
+    ensure!(
+        after.last_device_timestamp_ms == Some(last_timestamp_ms),
+        "stats last device timestamp {:?}, expected {last_timestamp_ms}",
+        after.last_device_timestamp_ms
+    );
+    ensure!(
+        after.last_host_timestamp_ms >= before.last_host_timestamp_ms
+            && after.last_host_timestamp_ms.is_some(),
+        "stats host timestamp did not advance"
+    );
+    let rate = frames as f64 / elapsed.as_secs_f64().max(1e-6);
+    ensure!(
+        rate >= MIN_PLAUSIBLE_FRAME_RATE,
+        "processing rate {rate:.0} frames/s below {MIN_PLAUSIBLE_FRAME_RATE} frames/s"
+    );
+    Ok(format!("{frames} frames at {rate:.0} frames/s"))
+}
+
+/// 读回录制会话，校验样本数与最终位置。
+pub async fn verify_recording_readback(
+    db_path: &Path,
+    session_id: i64,
+    expected: &[OutputFrame],
+) -> anyhow::Result<String> {
+    let samples = recorder::get_recording_samples(db_path, session_id)
+        .await
+        .context("recorder read-back failed")?;
+    ensure!(
+        samples.len() == expected.len(),
+        "recorder stored {} of {} frames",
+        samples.len(),
+        expected.len()
+    );
+    let (Some(stored), Some(reference)) = (samples.last(), expected.last()) else {
+        bail!("recorder stored no frames");
+    };
+    let position_error = stored.position.distance(reference.nav.position);
+    ensure!(
+        position_error <= POSITION_TOLERANCE_M,
+        "recorded final position deviates {position_error:.3e} m from expected"
+    );
+    Ok(format!("{} samples read back", samples.len()))
+}
@@ -0,0 +1,21 @@
+//! 无硬件自检。
+//!
+//! 合成一段已知运动的 0x11 数据包，经真实上游通道送进处理线程，再逐环节核对：
+//! 管线输出帧数、最终位置/姿态、处理器统计、录制写入与读回。期望值由同一配置
+//! 在当前线程离线重跑得到，因此与 processor.toml 的具体取值无关。
+//!
+//! 这里只放与 Tauri 无关的部分，应用内的编排见 `AppState::run_self_test`，
+//! 集成测试直接复用本模块。
+
+/// 合成数据与各环节校验。
+pub mod logic;
+/// 自检报告类型。
+pub mod types;
+
+/// 合成数据与各环节校验。
+pub use logic::{
+    encode_packets, motion_profile, run_packets, verify_final_state, verify_frame_count,
+    verify_recording_readback, verify_stats, PROFILE_DURATION_MS,
+};
+/// 自检报告类型。
+pub use types::{SelfTestCheck, SelfTestReport, SelfTestStage};
@@ -0,0 +1,100 @@
+//! 自检报告类型。
+
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// 检查项所属环节，失败时据此定位问题出在哪一段链路。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestStage {
+    /// 前置条件（设备未连接、未在录制）。
+    Preflight,
+    /// 上游通道（BLE 接收任务 → 处理线程）。
+    Upstream,
+    /// 解析与管线输出。
+    Pipeline,
+    /// 导航结果。
+    Navigation,
+    /// 处理器运行统计。
+    Stats,
+    /// 录制写入与读回。
+    Recorder,
+}
+
+/// 单个检查项结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    /// 所属环节。
+    pub stage: SelfTestStage,
+    /// 检查项名称。
+    pub name: String,
+    /// 是否通过。
+    pub passed: bool,
+    /// 耗时（毫秒）。
+    pub duration_ms: f64,
+    /// 通过时为测量摘要，失败时为带环节前缀的错误链。
+    pub detail: String,
+}
+
+/// 自检报告。
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// 全部检查项均通过。
+    pub passed: bool,
+    /// 总耗时（毫秒）。
+    pub total_ms: f64,
+    /// 按执行顺序排列的检查项。
+    pub checks: Vec<SelfTestCheck>,
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl SelfTestReport {
+    /// 开始计时。
+    pub fn start() -> Self {
+        Self {
+            passed: true,
+            total_ms: 0.0,
+            checks: Vec::new(),
+            started: Some(Instant::now()),
+        }
+    }
+
+    /// 记录一个检查项，耗时从 `started` 起算。
+    pub fn record(
+        &mut self,
+        stage: SelfTestStage,
+        name: &str,
+        started: Instant,
+        result: anyhow::Result<String>,
+    ) -> bool {
+        let passed = result.is_ok();
+        let detail = match result {
+            Ok(detail) => detail,
+            Err(error) => format!("{error:#}"),
+        };
+        self.passed &= passed;
+        self.checks.push(SelfTestCheck {
+            stage,
+            name: name.to_string(),
+            passed,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            detail,
+        });
+        passed
+    }
+
+    /// 结束计时。
+    pub fn finish(mut self) -> Self {
+        if let Some(started) = self.started.take() {
+            self.total_ms = started.elapsed().as_secs_f64() * 1000.0;
+        }
+        self
+    }
+
+    /// 第一个失败的检查项。
+    pub fn first_failure(&self) -> Option<&SelfTestCheck> {
+        self.checks.iter().find(|check| !check.passed)
+    }
+}
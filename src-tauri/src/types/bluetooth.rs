@@ -1,7 +1,10 @@
 //! 蓝牙外设信息类型。
 
+#[cfg(feature = "gui")]
 use btleplug::{api::Peripheral as _, platform::Peripheral};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::error::ErrorCode;
 
 #[derive(Debug, Default, Serialize)]
 /// 蓝牙外设信息。
@@ -14,10 +17,98 @@ pub struct PeripheralInfo {
     pub local_name: Option<String>,
     /// The most recent Received Signal Strength Indicator for the device
     pub rssi: Option<i16>,
+    /// 距最近一次被扫描发现的毫秒数，本次运行从未发现过时为空。
+    pub last_seen_ms_ago: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// 本机蓝牙适配器信息。
+pub struct BluetoothAdapterInfo {
+    /// 适配器 ID，用于 `select_bluetooth_adapter`。
+    pub id: String,
+    /// 平台给出的适配器描述（Linux 为 `hci0 (usb:...)`，Windows / macOS 为固定名称）。
+    pub name: String,
+    /// 是否已开启，平台不提供时为空。
+    pub powered: Option<bool>,
+    /// 是否为当前使用的适配器。
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// 蓝牙可用性诊断。
+pub struct BluetoothStatus {
+    /// 蓝牙服务是否可用（能否枚举适配器）。
+    pub manager_available: bool,
+    /// 适配器数量。
+    pub adapter_count: usize,
+    /// 当前使用的适配器 ID；蓝牙不可用或所选适配器已移除时为空。
+    pub selected_adapter: Option<String>,
+    /// 不可用的原因。
+    pub error: Option<String>,
+    /// 不可用原因的错误码。
+    pub error_code: Option<ErrorCode>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+/// 设备信息服务（Device Information Service, 0x180A）读到的版本信息。
+///
+/// 服务与各特征都是可选的，设备不提供或读取失败的字段为空。
+pub struct DeviceInformation {
+    /// 制造商名称（0x2A29）。
+    pub manufacturer: Option<String>,
+    /// 型号（0x2A24）。
+    pub model_number: Option<String>,
+    /// 固件版本（0x2A26）。
+    pub firmware_revision: Option<String>,
+    /// 硬件版本（0x2A27）。
+    pub hardware_revision: Option<String>,
+    /// 软件版本（0x2A28）。
+    pub software_revision: Option<String>,
+}
+
+impl DeviceInformation {
+    /// 设备信息服务的 16 位 UUID。
+    pub const SERVICE_UUID16: u16 = 0x180a;
+    /// 需要读取的特征（16 位 UUID）。
+    pub const CHARACTERISTIC_UUID16S: [u16; 5] = [0x2a29, 0x2a24, 0x2a26, 0x2a27, 0x2a28];
+
+    /// 由读到的 `(特征 UUID16, 值)` 构造，未知特征忽略，空值视为缺失。
+    pub fn from_reads(reads: impl IntoIterator<Item = (u16, Vec<u8>)>) -> Self {
+        let mut info = Self::default();
+        for (uuid, value) in reads {
+            let slot = match uuid {
+                0x2a29 => &mut info.manufacturer,
+                0x2a24 => &mut info.model_number,
+                0x2a26 => &mut info.firmware_revision,
+                0x2a27 => &mut info.hardware_revision,
+                0x2a28 => &mut info.software_revision,
+                _ => continue,
+            };
+            *slot = decode_dis_string(&value);
+        }
+        info
+    }
+
+    /// 是否一个字段都没有（设备不提供设备信息服务）。
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 设备信息特征为 UTF-8 字符串，部分固件以 `\0` 填充；去掉控制字符与首尾空白，空串视为缺失。
+fn decode_dis_string(value: &[u8]) -> Option<String> {
+    let text: String = String::from_utf8_lossy(value)
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
 }
 
 impl PeripheralInfo {
     /// 从外设实例提取信息。
+    #[cfg(feature = "gui")]
     pub async fn from_peripheral(p: &Peripheral) -> anyhow::Result<PeripheralInfo> {
         let properties = p
             .properties()
@@ -30,6 +121,7 @@ impl PeripheralInfo {
             address: p.address().to_string(),
             local_name: properties.local_name,
             rssi: properties.rssi,
+            last_seen_ms_ago: None,
         })
     }
 }
@@ -0,0 +1,33 @@
+//! IPC 结构化错误码。
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+/// 失败响应附带的错误码，前端据此区分处理（提示开启蓝牙、重新选择适配器等），
+/// 不必解析错误消息文本。
+pub enum ErrorCode {
+    /// 系统蓝牙不可用：蓝牙关闭、缺少权限或蓝牙服务异常。
+    BluetoothUnavailable,
+    /// 蓝牙服务正常，但没有可用的适配器或指定的适配器不存在。
+    AdapterNotFound,
+    /// 当前构建未编译该功能（例如未启用对应的 cargo feature）。
+    NotSupported,
+    /// 该会话已有导出在进行。
+    ExportInProgress,
+    /// 导出被 `cancel_export` 取消，未完成的文件已删除。
+    ExportCancelled,
+    /// 录制数据库完整性检查失败。
+    DatabaseCorrupt,
+    /// 录制数据库由更新版本的程序写出，当前版本不能打开。
+    DatabaseTooNew,
+    /// 连接失败后重新扫描未发现设备：设备可能已关机或超出范围。
+    DeviceOffline,
+    /// 连接失败，但重新扫描时发现了设备（信号弱或被其他主机占用）。
+    DeviceUnreachable,
+    /// 开始录制等待超时，录制线程仍在处理（多为迁移录制数据库），稍后查询录制状态。
+    RecordingStarting,
+    /// 设备正在上报数据，不能开始回放。
+    PlaybackDeviceStreaming,
+    /// 正在录制，不能开始回放。
+    PlaybackRecordingActive,
+}
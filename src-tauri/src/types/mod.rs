@@ -2,7 +2,11 @@
 
 /// 蓝牙相关类型。
 pub mod bluetooth;
+/// IPC 错误码。
+pub mod error;
 /// 输出数据类型。
 pub mod outputs;
 /// 录制相关类型。
 pub mod recording;
+/// 应用状态快照类型。
+pub mod status;
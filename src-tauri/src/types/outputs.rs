@@ -1,11 +1,28 @@
 //! 输出数据类型。
 
 use math_f64::{DQuat, DVec3};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::processor::{
+    batcher::{DEFAULT_BATCH_MAX_LEN, DEFAULT_BATCH_WINDOW_MS},
+    navigator::{DebugVectors, MotionState, PositionSource, ZuptState},
+};
+
+/// [`ResponseData`] 的结构版本，增删字段或改变字段含义时加 1。
+///
+/// 录制会话记下写入时的版本，回放时按版本补齐旧录制缺失字段的默认值，
+/// 比当前版本新的录制拒绝读取。
+///
+/// - 1：早期录制，会话表没有 `schema_version` 列，也没有位置不确定度
+/// - 2：新增 `position_sigma_m`、`host_time_estimate_ms` 与 `schema_version`
+/// - 3：新增 `debug_vectors`（仅实时输出，录制中恒为空）
+pub const RESPONSE_SCHEMA_VERSION: u32 = 3;
 
 #[derive(Debug, Clone, Copy, Serialize)]
 /// 前端响应数据，扁平化结构，仅包含展示所需字段
 pub struct ResponseData {
+    /// 结构版本，恒为 [`RESPONSE_SCHEMA_VERSION`]，前端据此判断字段是否齐全。
+    pub schema_version: u32,
     /// 时间戳（毫秒）
     pub timestamp_ms: u64,
     /// 去重力加速度（m/s²）
@@ -25,4 +42,171 @@ pub struct ResponseData {
     /// 用于前端在 3D 轨迹和 chart 上标红提醒。详见
     /// `docs/imu_saturation_research.md`。
     pub accel_saturated: bool,
+    /// 运动状态（静止/运动/未判定），前端据此给轨迹着色。
+    pub motion_state: MotionState,
+    /// ZUPT 检测结果。
+    pub zupt: ZuptState,
+    /// 位置来源（本地积分 / 设备位置 / 融合）。
+    pub position_source: PositionSource,
+    /// 加加速度（m/s³），派生信号未启用时为空。
+    pub jerk: Option<DVec3>,
+    /// 角加速度（rad/s²），派生信号未启用时为空。
+    pub ang_accel: Option<DVec3>,
+    /// 位置不确定度（m，1σ 启发式估计），静止或手动设置位置后归零。
+    pub position_sigma_m: f64,
+    /// 按时钟同步换算的主机 UNIX 时间（ms），同步尚未建立时为空。
+    pub host_time_estimate_ms: Option<f64>,
+    /// 重力参考与世界系加速度（m/s²，与位置同一坐标系），未启用 `debug_vectors` 时为空。
+    pub debug_vectors: Option<DebugVectors>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+/// 前端输出订阅中的完整帧：`ResponseData` 加上序号。
+pub struct StreamFrame {
+    /// 帧序号，处理线程每输出一帧加 1（含被抑制的帧）。
+    pub seq: u64,
+    /// 自上一条消息以来有意未发出的帧数（静止抑制，或回放时按频率抽取）。
+    pub skipped: u64,
+    /// 订阅时先发的最新一帧快照，序号为回放与实时消息的分界，不参与缺口计算。
+    pub snapshot: bool,
+    /// 订阅时回放的历史帧。
+    pub backfill: bool,
+    /// 帧数据。
+    #[serde(flatten)]
+    pub data: ResponseData,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+/// 静止抑制期间代替完整帧发出的心跳。
+pub struct StreamHeartbeat {
+    /// 当前帧序号。
+    pub seq: u64,
+    /// 当前帧时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 与上一个完整帧相比无变化，恒为 `true`，前端据此区分心跳与完整帧。
+    pub unchanged: bool,
+    /// 自上一条消息以来有意未发出的帧数（静止抑制，或回放时按频率抽取）。
+    pub skipped: u64,
+    /// 订阅时回放的历史心跳。
+    pub backfill: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(untagged)]
+// 按值经通道传递，装箱会让每帧多一次分配
+#[allow(clippy::large_enum_variant)]
+/// 前端输出订阅消息。
+pub enum StreamMessage {
+    /// 完整帧。
+    Frame(StreamFrame),
+    /// 静止心跳。
+    Heartbeat(StreamHeartbeat),
+}
+
+impl StreamMessage {
+    /// 帧序号。
+    pub fn seq(&self) -> u64 {
+        match self {
+            Self::Frame(frame) => frame.seq,
+            Self::Heartbeat(heartbeat) => heartbeat.seq,
+        }
+    }
+
+    /// 设备时间戳（毫秒）。
+    pub fn timestamp_ms(&self) -> u64 {
+        match self {
+            Self::Frame(frame) => frame.data.timestamp_ms,
+            Self::Heartbeat(heartbeat) => heartbeat.timestamp_ms,
+        }
+    }
+
+    /// 自上一条消息以来有意未发出的帧数。
+    pub fn skipped(&self) -> u64 {
+        match self {
+            Self::Frame(frame) => frame.skipped,
+            Self::Heartbeat(heartbeat) => heartbeat.skipped,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+// 构造后立即序列化发出，装箱单条消息只会多一次分配
+#[allow(clippy::large_enum_variant)]
+/// 输出订阅通道上的一条消息：攒批订阅为 `output_batch` 数组，否则为单条消息。
+pub enum OutputEvent {
+    /// 单条消息（`batching: false`）。
+    Message(StreamMessage),
+    /// 一批按顺序排列的消息。
+    Batch {
+        /// 批内消息，序号递增。
+        output_batch: Vec<StreamMessage>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+/// 输出订阅选项，缺省时既不发快照也不回放，实时消息按默认窗口攒批。
+pub struct OutputSubscribeOptions {
+    /// 订阅时先发最新一帧快照（`snapshot: true`）。
+    pub include_snapshot: bool,
+    /// 回放最近多少毫秒的历史（`backfill: true`），0 表示不回放。
+    pub backfill_ms: u64,
+    /// 回放帧的最高频率 (Hz)，为空时不抽取。
+    pub max_rate_hz: Option<f64>,
+    /// 实时帧的最高频率 (Hz)，为空时不抽取；心跳不受影响。
+    pub live_max_rate_hz: Option<f64>,
+    /// 实时帧的频率模式，自适应时不能再给 `live_max_rate_hz`。
+    pub rate_mode: OutputRateMode,
+    /// 攒批发送（`output_batch`），关闭时每条消息单独发送。
+    pub batching: bool,
+    /// 攒批窗口 (ms)：一批的第一条消息最多等待这么久。
+    pub batch_window_ms: u64,
+    /// 每批最多消息数。
+    pub batch_max_frames: usize,
+    /// 消息编码，CBOR 时以字节数组发出，见 [`crate::processor::output_encoding`]。
+    pub encoding: OutputEncoding,
+}
+
+impl Default for OutputSubscribeOptions {
+    fn default() -> Self {
+        Self {
+            include_snapshot: false,
+            backfill_ms: 0,
+            max_rate_hz: None,
+            live_max_rate_hz: None,
+            rate_mode: OutputRateMode::default(),
+            batching: true,
+            batch_window_ms: DEFAULT_BATCH_WINDOW_MS,
+            batch_max_frames: DEFAULT_BATCH_MAX_LEN,
+            encoding: OutputEncoding::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+/// 实时帧的频率模式。
+pub enum OutputRateMode {
+    /// 按 `live_max_rate_hz` 固定抽取。
+    #[default]
+    Fixed,
+    /// 按运动强度在 `min_hz` 与 `max_hz` 之间自适应，静止转为运动后的第一帧立即发出。
+    Adaptive {
+        /// 静止时的频率 (Hz)。
+        min_hz: f64,
+        /// 快速运动时的频率 (Hz)。
+        max_hz: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// 输出订阅的消息编码。
+pub enum OutputEncoding {
+    /// JSON 文本。
+    #[default]
+    Json,
+    /// CBOR 字节数组，解码后与 JSON 结构相同。
+    Cbor,
 }
@@ -1,12 +1,20 @@
 //! 录制相关类型。
 
-use serde::Serialize;
+use math_f64::{DQuat, DVec3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    processor::output::StageSnapshot,
+    types::{bluetooth::DeviceInformation, status::ConnectionUptime},
+};
 
 #[derive(Debug, Clone, Serialize)]
 /// 录制状态。
 pub struct RecordingStatus {
     /// 是否正在录制。
     pub recording: bool,
+    /// 是否处于暂停（会话仍然打开，到达的数据被丢弃）。
+    pub paused: bool,
     /// 会话 ID。
     pub session_id: Option<i64>,
     /// 数据库路径。
@@ -19,6 +27,52 @@ pub struct RecordingStatus {
     pub name: Option<String>,
     /// 标签列表。
     pub tags: Option<Vec<String>>,
+    /// 按当前写入速率估计的每分钟占用字节数（深度录制时含阶段中间值）。
+    pub bytes_per_minute: Option<f64>,
+    /// 是否为深度录制（同时写入各阶段中间值）。
+    pub deep: bool,
+    /// 其中阶段中间值的每分钟估计字节数，非深度录制时为空。
+    pub stage_bytes_per_minute: Option<f64>,
+    /// 录制卷最近一次查询到的可用字节数。
+    pub available_bytes: Option<u64>,
+    /// 会话结束原因（仅停止后返回）。
+    pub stop_reason: Option<StopReason>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 录制结束原因。
+pub enum StopReason {
+    /// 用户停止（包括开始新录制时替换旧会话）。
+    User,
+    /// 磁盘剩余空间低于阈值。
+    DiskFull,
+    /// 达到自动分段条件，录制在下一段继续。
+    Split,
+}
+
+impl StopReason {
+    /// 数据库中保存的字符串。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StopReason::User => "user",
+            StopReason::DiskFull => "disk_full",
+            StopReason::Split => "split",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 录制被录制线程主动结束时推送的 `recording_stopped` 事件。
+pub struct RecordingStopped {
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 结束原因。
+    pub reason: StopReason,
+    /// 已写入的样本数。
+    pub sample_count: u64,
+    /// 触发时的可用字节数。
+    pub available_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -36,4 +90,336 @@ pub struct RecordingMeta {
     pub name: Option<String>,
     /// 标签列表。
     pub tags: Vec<String>,
+    /// 结束原因（`user`、`disk_full`、`split`），旧录制为空。
+    pub stop_reason: Option<String>,
+    /// 自动分段时第一段的会话 ID，同一次录制的各段据此分组；第一段与未分段录制为空。
+    pub parent_session_id: Option<i64>,
+    /// 会话未正常结束（应用崩溃等），结束时间与样本数由修复流程从样本回填。
+    pub interrupted: bool,
+    /// 写入时的 `ResponseData` 结构版本，早期录制为 1。
+    pub schema_version: u32,
+    /// 暂停累计时长（毫秒），不计入录制时长。
+    pub paused_ms: i64,
+    /// 航向对齐旋转 (°)，样本中的导航输出已按它旋转；录制期间未对齐时为空。
+    pub heading_yaw_deg: Option<f64>,
+    /// 该对齐开始生效的设备时间戳 (ms)。
+    pub heading_aligned_at_ms: Option<i64>,
+    /// 录制开始时的应用与设备版本信息，早期录制为空。
+    pub metadata: Option<RecordingMetadata>,
+    /// 审阅备注，未填写时为空。
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+/// 录制开始时采集的版本信息。
+///
+/// 解析比例与管线行为随应用和固件版本变化，分析旧录制时据此判断数据来源。
+/// `ResponseData` 结构版本另存于 [`RecordingMeta::schema_version`]。
+pub struct RecordingMetadata {
+    /// 应用版本（`CARGO_PKG_VERSION`）。
+    pub app_version: String,
+    /// 设备信息服务读到的固件 / 硬件版本，设备不提供时各字段为空。
+    pub device: DeviceInformation,
+    /// 开始录制时生效的设备配置帧（小写十六进制），未知时为空。
+    pub imu_config_hex: Option<String>,
+    /// 开始录制时的设备运行时长与连接时长，未记录时为空。
+    pub uptime: Option<ConnectionUptime>,
+    /// 开始录制时生效的姿态零位与重力参考，早期录制为空。
+    pub calibration: Option<RecordingCalibration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+/// 录制所在的世界系：姿态零位与重力参考。
+///
+/// 导航系 = `axis_quat_offset` × 设备自身的参考系，重新校准零位后世界系随之改变，
+/// 不同标定下录制的轨迹据此换算到同一坐标系再叠加。
+pub struct RecordingCalibration {
+    /// 姿态零位四元数（左乘到设备姿态上）。
+    pub axis_quat_offset: DQuat,
+    /// 世界系重力参考向量 (m/s²)。
+    pub gravity_ref: DVec3,
+}
+
+impl RecordingMetadata {
+    /// 以当前应用版本构造。
+    pub fn current(device: DeviceInformation, imu_config: Option<&[u8]>) -> Self {
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            device,
+            imu_config_hex: imu_config
+                .map(|bytes| bytes.iter().map(|byte| format!("{byte:02x}")).collect()),
+            uptime: None,
+            calibration: None,
+        }
+    }
+
+    /// 附上开始录制时的运行时长。
+    pub fn with_uptime(mut self, uptime: ConnectionUptime) -> Self {
+        self.uptime = Some(uptime);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 录制列表排序字段。
+pub enum RecordingSortBy {
+    /// 开始时间。
+    #[default]
+    StartedAt,
+    /// 时长（未结束的会话排在最后）。
+    Duration,
+    /// 样本数。
+    SampleCount,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+/// 录制列表查询条件，各项均可省略；省略全部时按开始时间倒序返回所有会话。
+pub struct RecordingQuery {
+    /// 名称子串，不区分大小写。
+    pub name: Option<String>,
+    /// 命中任一标签即可（整标签精确匹配）。
+    pub tags: Vec<String>,
+    /// 开始时间下限（毫秒，含）。
+    pub started_from_ms: Option<i64>,
+    /// 开始时间上限（毫秒，含）。
+    pub started_to_ms: Option<i64>,
+    /// 最短时长（毫秒），未结束的会话不满足。
+    pub min_duration_ms: Option<i64>,
+    /// 设备 ID。
+    pub device_id: Option<String>,
+    /// 排序字段。
+    pub sort_by: RecordingSortBy,
+    /// 是否升序，默认倒序。
+    pub ascending: bool,
+    /// 返回条数上限，省略时不限。
+    pub limit: Option<u64>,
+    /// 跳过的条数。
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 一页录制会话。
+pub struct RecordingPage {
+    /// 本页会话。
+    pub items: Vec<RecordingMeta>,
+    /// 满足过滤条件的会话总数（不受分页影响）。
+    pub total_count: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+/// 全部录制的汇总统计，数据库为空时各项为零。
+pub struct RecordingStatistics {
+    /// 会话总数。
+    pub session_count: u64,
+    /// 样本总数。
+    pub sample_count: u64,
+    /// 已结束会话的总时长（毫秒），不含暂停时间。
+    pub total_duration_ms: i64,
+    /// 数据库文件大小（字节），含 WAL 文件。
+    pub db_size_bytes: u64,
+    /// 最早一次录制的开始时间戳（毫秒）。
+    pub first_started_at_ms: Option<i64>,
+    /// 最近一次录制的开始时间戳（毫秒）。
+    pub last_started_at_ms: Option<i64>,
+    /// 使用最多的标签，按次数倒序。
+    pub top_tags: Vec<TagUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+/// 标签使用次数。
+pub struct TagUsage {
+    /// 标签。
+    pub tag: String,
+    /// 带该标签的会话数。
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 录制标记。
+pub struct RecordingMarker {
+    /// 标记 ID。
+    pub id: i64,
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 设备时间戳（毫秒），与样本 `timestamp_ms` 对齐。
+    pub timestamp_ms: i64,
+    /// 主机时间戳（毫秒）。
+    pub host_ts_ms: i64,
+    /// 标记文本。
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+/// 数据质量标记区间，审阅时圈出的问题时段，区间之间可以重叠。
+pub struct RecordingFlag {
+    /// 标记 ID。
+    pub id: i64,
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 区间起点设备时间戳（毫秒，含），与样本 `timestamp_ms` 对齐。
+    pub from_ms: i64,
+    /// 区间终点设备时间戳（毫秒，含）。
+    pub to_ms: i64,
+    /// 标记文本。
+    pub label: String,
+    /// 创建时的主机时间戳（毫秒）。
+    pub created_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// 同步事件类型。
+pub enum SyncEventKind {
+    /// 调用 `record_sync_pulse` 手动记录（如外部触发器、拍板）。
+    Manual,
+    /// 管线自动检测到的敲击（`sync_tap`）。
+    Tap,
+}
+
+impl SyncEventKind {
+    /// 录制表 `kind` 列的存储值。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Tap => "tap",
+        }
+    }
+
+    /// 从录制表存储值解析，未知取值视为 [`Manual`](Self::Manual)。
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "tap" => Self::Tap,
+            _ => Self::Manual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 录制同步事件，用于把外部视频 / 音频与 IMU 数据对齐。
+pub struct RecordingSyncEvent {
+    /// 事件 ID。
+    pub id: i64,
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 事件类型。
+    pub kind: SyncEventKind,
+    /// 触发来源。
+    pub source: String,
+    /// 设备时间戳（毫秒），与样本 `timestamp_ms` 对齐。
+    pub timestamp_ms: i64,
+    /// 记录事件时的主机 UNIX 时间戳（毫秒）。
+    pub host_ts_ms: i64,
+    /// 时钟同步偏移（毫秒），尚无同步结果时为空。
+    pub clock_offset_ms: Option<f64>,
+    /// 时钟同步频偏（ppm）。
+    pub clock_skew_ppm: Option<f64>,
+    /// 按时钟同步把 `timestamp_ms` 换算成的主机 UNIX 时间（毫秒），无同步结果时为空。
+    pub host_time_ms: Option<f64>,
+    /// 敲击的峰值加速度模长（g），手动事件为空。
+    pub peak_g: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 录制暂停区间，前端据此在回放中标出暂停处。
+pub struct RecordingPause {
+    /// 区间 ID。
+    pub id: i64,
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 暂停时的主机时间戳（毫秒）。
+    pub paused_at_ms: i64,
+    /// 恢复（或停止）时的主机时间戳（毫秒）。
+    pub resumed_at_ms: i64,
+    /// 暂停时长（毫秒）。
+    pub duration_ms: i64,
+    /// 暂停前最后一条样本的设备时间戳（毫秒），与样本 `timestamp_ms` 对齐；
+    /// 暂停区间即该样本与下一条样本之间的空档。暂停时尚无样本则为空。
+    pub timestamp_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 深度录制的一帧阶段中间值，与同会话样本按 `timestamp_ms` 一一对应。
+pub struct RecordingStageSample {
+    /// 设备时间戳（毫秒）。
+    pub timestamp_ms: u64,
+    /// 标定后的加速度 (m/s²)。
+    pub calibrated_accel: DVec3,
+    /// 标定后的角速度 (rad/s)。
+    pub calibrated_gyro: DVec3,
+    /// 滤波后的加速度 (m/s²)。
+    pub filtered_accel: DVec3,
+    /// 滤波后的角速度 (rad/s)。
+    pub filtered_gyro: DVec3,
+    /// 世界系线加速度 (m/s²)。
+    pub linear_accel: DVec3,
+    /// ZUPT 前的速度 (m/s)。
+    pub pre_zupt_velocity: DVec3,
+    /// ZUPT 前的位置 (m)。
+    pub pre_zupt_position: DVec3,
+    /// ZUPT 后的速度 (m/s)。
+    pub post_zupt_velocity: DVec3,
+    /// ZUPT 后的位置 (m)。
+    pub post_zupt_position: DVec3,
+    /// 本帧是否判定为静止。
+    pub is_static: bool,
+}
+
+impl RecordingStageSample {
+    /// 由管线输出帧的阶段中间值构造，离线重跑时与数据库中的行同形。
+    pub fn from_snapshot(timestamp_ms: u64, stages: &StageSnapshot) -> Self {
+        Self {
+            timestamp_ms,
+            calibrated_accel: stages.calibrated_accel,
+            calibrated_gyro: stages.calibrated_gyro,
+            filtered_accel: stages.filtered_accel,
+            filtered_gyro: stages.filtered_gyro,
+            linear_accel: stages.linear_accel,
+            pre_zupt_velocity: stages.nav_pre_zupt.velocity,
+            pre_zupt_position: stages.nav_pre_zupt.position,
+            post_zupt_velocity: stages.nav_post_zupt.velocity,
+            post_zupt_position: stages.nav_post_zupt.position,
+            is_static: stages.is_static,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 录制中检测到的运动分段。
+pub struct RecordingSegment {
+    /// 分段 ID。
+    pub id: i64,
+    /// 会话 ID。
+    pub session_id: i64,
+    /// 起点设备时间戳（毫秒），与样本 `timestamp_ms` 对齐。
+    pub start_ms: i64,
+    /// 终点设备时间戳（毫秒）。
+    pub end_ms: i64,
+    /// 时长（毫秒）。
+    pub duration_ms: i64,
+    /// 路径长度（米）。
+    pub path_length_m: f64,
+    /// 位移向量（米）。
+    pub displacement: DVec3,
+    /// 位移模长（米）。
+    pub displacement_m: f64,
+    /// 峰值速度（米/秒）。
+    pub peak_speed_mps: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// 录制数据库的完整性检查结果与表结构版本。
+pub struct DatabaseIntegrity {
+    /// 完整性检查是否通过。
+    pub ok: bool,
+    /// 检查发现的问题，通过时为空。
+    pub problems: Vec<String>,
+    /// 数据库记录的表结构版本，尚未迁移过的旧库或损坏的库为 0。
+    pub schema_version: i64,
+    /// 当前程序的表结构版本。
+    pub supported_schema_version: i64,
+    /// 数据库文件路径。
+    pub db_path: String,
 }
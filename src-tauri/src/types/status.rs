@@ -0,0 +1,81 @@
+//! 应用状态快照类型。
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    processor::{
+        fault::FaultInjectionStatus, scheduling::SchedulingReport, shared::BufferUsage,
+        watchdog::WatchdogSnapshot,
+    },
+    types::{bluetooth::PeripheralInfo, recording::RecordingStatus},
+};
+
+/// 连接与处理状态快照。
+///
+/// 供前端重载或重新挂载后一次性恢复界面状态。
+#[derive(Debug, Serialize)]
+pub struct AppStatus {
+    /// 当前连接的设备，未连接时为空。
+    pub connected_peripheral: Option<PeripheralInfo>,
+    /// 录制状态。
+    pub recording: RecordingStatus,
+    /// 本次连接以来处理器输出的帧数。
+    pub frames_since_connect: u64,
+    /// 设备上电以来的运行时长 (ms)，取最近一帧设备时间戳（已展开 32 位回绕），
+    /// 未连接或本次连接尚无帧时为空。
+    pub device_uptime_ms: Option<u64>,
+    /// 本次连接已持续的时长 (ms)，未连接时为空。
+    pub connection_uptime_ms: Option<u64>,
+    /// 本次运行以来的重连次数（首次连接不计）。
+    pub reconnect_count: u64,
+    /// 最近一帧的设备时间戳（ms）。
+    pub last_device_timestamp_ms: Option<u64>,
+    /// 最近一帧处理完成时的主机时间戳（ms）。
+    pub last_host_timestamp_ms: Option<u64>,
+    /// auto_gravity 估计的重力模长 (m/s²)，未启用或未完成时为空。
+    pub gravity_estimate: Option<f64>,
+    /// 导航因帧间隔过大（主机休眠、长时间断流）而跳过积分的累计次数。
+    pub skipped_integrations: u64,
+    /// 检测到设备四元数冻结、改用陀螺积分姿态的累计次数。
+    pub quat_freezes: u64,
+    /// 解析失败的累计包数（CRC/帧头错误、字节损坏）。
+    pub parse_failures: u64,
+    /// 某阶段输出 NaN/Inf 而被丢弃的累计帧数。
+    pub non_finite_samples: u64,
+    /// 最近一个统计窗口（至少 1 s）的蓝牙通知速率 (包/s)。
+    pub input_packets_per_sec: f64,
+    /// 最近一个统计窗口的输入帧速率 (帧/s)，一个通知可能拼接多帧。
+    pub input_frames_per_sec: f64,
+    /// 最近一个统计窗口的蓝牙输入带宽 (B/s)。
+    pub input_bytes_per_sec: f64,
+    /// 设备上报频率 (Hz)，0 表示 0.5 Hz。
+    pub report_rate: u8,
+    /// 设备上报频率换算成的实际频率 (Hz)，`report_rate = 0` 时为 0.5。
+    pub report_rate_hz: f64,
+    /// 当前生效 pipeline 配置的哈希，用于判断前端缓存的配置是否过期。
+    pub pipeline_config_hash: String,
+    /// 数据流看门狗状态与停滞计数。
+    pub watchdog: WatchdogSnapshot,
+    /// 常驻内存缓冲区（输出历史、飞行记录仪等）的条数与估算字节数。
+    pub memory: Vec<BufferUsage>,
+    /// 生效中的故障注入（仅开发构建），未启用时为空。
+    pub fault_injection: Option<FaultInjectionStatus>,
+    /// 处理线程与录制线程的调度提示是否生效。
+    pub scheduling: SchedulingReport,
+}
+
+/// 设备运行时长与连接时长，用于把温漂、电量问题与运行时长对应起来。
+///
+/// 开始录制时写入会话元数据。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ConnectionUptime {
+    /// 设备上电以来的运行时长 (ms)，未知时为空。
+    pub device_uptime_ms: Option<u64>,
+    /// 本次连接已持续的时长 (ms)，未连接时为空。
+    pub connection_uptime_ms: Option<u64>,
+    /// 本次连接以来处理器输出的帧数。
+    pub frames_since_connect: u64,
+    /// 本次运行以来的重连次数（首次连接不计）。
+    pub reconnect_count: u64,
+}
@@ -0,0 +1,531 @@
+//! 展示与导出边界的单位换算。
+//!
+//! 管线内部与录制数据库一律使用国际单位（长度 m、速度 m/s、角度 rad；设备上报的欧拉角
+//! 按设备约定以度存储）。用户可以通过 `set_unit_preferences` 选择展示单位，偏好持久化到
+//! 应用配置目录的 `units.toml`，只在导出（CSV/Parquet）与 `get_latest_frame_display`
+//! 这类展示出口经 [`UnitConverter`] 换算，修改偏好不需要重启管线。
+//!
+//! 导出的列换算到非国际单位时在列名后标注单位（如 `calc_position_x[cm]`）；国际单位的
+//! 列名保持不变，已有的脚本与导入流程照常读取。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::Context;
+use math_f64::{DQuat, DVec3};
+use serde::{Deserialize, Serialize};
+
+use crate::{processor::calibration::logic::euler_zyx_deg, types::outputs::ResponseData};
+
+/// 持久化设置文件名（位于应用配置目录）。
+const SETTINGS_FILE: &str = "units.toml";
+
+/// 展示用单位：符号与相对国际单位的比例（1 个该单位等于多少国际单位）。
+pub trait Unit: Copy + Sized + 'static {
+    /// 全部取值。
+    const ALL: &'static [Self];
+    /// 1 个该单位对应的国际单位数值。
+    fn si_per_unit(self) -> f64;
+    /// 单位符号，同时是序列化名称。
+    fn symbol(self) -> &'static str;
+}
+
+/// 在同一物理量的两个单位之间换算。
+pub fn convert<U: Unit>(value: f64, from: U, to: U) -> f64 {
+    if from.si_per_unit() == to.si_per_unit() {
+        return value;
+    }
+    value * from.si_per_unit() / to.si_per_unit()
+}
+
+/// 长度单位。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LengthUnit {
+    /// 米。
+    #[default]
+    #[serde(rename = "m")]
+    Meter,
+    /// 厘米。
+    #[serde(rename = "cm")]
+    Centimeter,
+    /// 毫米。
+    #[serde(rename = "mm")]
+    Millimeter,
+}
+
+impl Unit for LengthUnit {
+    const ALL: &'static [Self] = &[Self::Meter, Self::Centimeter, Self::Millimeter];
+
+    fn si_per_unit(self) -> f64 {
+        match self {
+            Self::Meter => 1.0,
+            Self::Centimeter => 0.01,
+            Self::Millimeter => 0.001,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Meter => "m",
+            Self::Centimeter => "cm",
+            Self::Millimeter => "mm",
+        }
+    }
+}
+
+/// 角度单位。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AngleUnit {
+    /// 度（前端一直按度显示，作为默认）。
+    #[default]
+    #[serde(rename = "deg")]
+    Degree,
+    /// 弧度。
+    #[serde(rename = "rad")]
+    Radian,
+}
+
+impl Unit for AngleUnit {
+    const ALL: &'static [Self] = &[Self::Degree, Self::Radian];
+
+    fn si_per_unit(self) -> f64 {
+        match self {
+            Self::Degree => std::f64::consts::PI / 180.0,
+            Self::Radian => 1.0,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Degree => "deg",
+            Self::Radian => "rad",
+        }
+    }
+}
+
+/// 速度单位。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedUnit {
+    /// 米每秒。
+    #[default]
+    #[serde(rename = "m/s")]
+    MeterPerSecond,
+    /// 千米每小时。
+    #[serde(rename = "km/h")]
+    KilometerPerHour,
+}
+
+impl Unit for SpeedUnit {
+    const ALL: &'static [Self] = &[Self::MeterPerSecond, Self::KilometerPerHour];
+
+    fn si_per_unit(self) -> f64 {
+        match self {
+            Self::MeterPerSecond => 1.0,
+            Self::KilometerPerHour => 1000.0 / 3600.0,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::MeterPerSecond => "m/s",
+            Self::KilometerPerHour => "km/h",
+        }
+    }
+}
+
+/// 用户的单位偏好。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnitPreferences {
+    /// 长度（位置、位置不确定度）。
+    pub length: LengthUnit,
+    /// 角度（欧拉角）。
+    pub angle: AngleUnit,
+    /// 速度。
+    pub speed: SpeedUnit,
+}
+
+/// 导出列或展示值的物理量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    /// 长度，国际单位 m。
+    Length,
+    /// 角度，国际单位 rad。
+    Angle,
+    /// 速度，国际单位 m/s。
+    Speed,
+}
+
+/// 按单位偏好在国际单位与展示单位之间换算，导出与展示共用这一处。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnitConverter {
+    preferences: UnitPreferences,
+}
+
+impl UnitConverter {
+    /// 按偏好创建换算器；默认值即国际单位（角度为度）。
+    pub fn new(preferences: UnitPreferences) -> Self {
+        Self { preferences }
+    }
+
+    /// 国际单位换算到展示单位。
+    pub fn from_si(&self, quantity: Quantity, value: f64) -> f64 {
+        match quantity {
+            Quantity::Length => convert(value, LengthUnit::Meter, self.preferences.length),
+            Quantity::Angle => convert(value, AngleUnit::Radian, self.preferences.angle),
+            Quantity::Speed => convert(value, SpeedUnit::MeterPerSecond, self.preferences.speed),
+        }
+    }
+
+    /// 展示单位换算回国际单位。
+    pub fn to_si(&self, quantity: Quantity, value: f64) -> f64 {
+        match quantity {
+            Quantity::Length => convert(value, self.preferences.length, LengthUnit::Meter),
+            Quantity::Angle => convert(value, self.preferences.angle, AngleUnit::Radian),
+            Quantity::Speed => convert(value, self.preferences.speed, SpeedUnit::MeterPerSecond),
+        }
+    }
+
+    /// 展示单位符号。
+    pub fn symbol(&self, quantity: Quantity) -> &'static str {
+        match quantity {
+            Quantity::Length => self.preferences.length.symbol(),
+            Quantity::Angle => self.preferences.angle.symbol(),
+            Quantity::Speed => self.preferences.speed.symbol(),
+        }
+    }
+
+    /// 展示单位是否就是导出列的存储单位（此时列名与数值都不变）。
+    fn is_stored_unit(&self, quantity: Quantity) -> bool {
+        match quantity {
+            Quantity::Length => self.preferences.length == LengthUnit::Meter,
+            Quantity::Angle => self.preferences.angle == AngleUnit::Radian,
+            Quantity::Speed => self.preferences.speed == SpeedUnit::MeterPerSecond,
+        }
+    }
+
+    /// 导出列名：换算到非国际单位时标注单位，如 `calc_position_x[cm]`；
+    /// 列名本身以 `_m` 结尾（如 `position_sigma_m`）时去掉旧后缀。
+    pub fn column_name(&self, name: &str, quantity: Quantity) -> String {
+        if self.is_stored_unit(quantity) {
+            return name.to_string();
+        }
+        let base = name.strip_suffix("_m").unwrap_or(name);
+        format!("{base}[{}]", self.symbol(quantity))
+    }
+
+    /// 按 [`column_name`](Self::column_name) 的约定拆出列名中的单位，返回基础列名与换算器。
+    ///
+    /// 没有单位标注时按国际单位处理；单位无法识别时返回 `None`。
+    pub fn parse_column_name(name: &str, quantity: Quantity) -> Option<(&str, Self)> {
+        let Some((base, rest)) = name.split_once('[') else {
+            return Some((name, Self::default().with_stored_unit(quantity)));
+        };
+        let symbol = rest.strip_suffix(']')?;
+        let mut preferences = UnitPreferences::default();
+        match quantity {
+            Quantity::Length => {
+                preferences.length = *LengthUnit::ALL.iter().find(|u| u.symbol() == symbol)?
+            }
+            Quantity::Angle => {
+                preferences.angle = *AngleUnit::ALL.iter().find(|u| u.symbol() == symbol)?
+            }
+            Quantity::Speed => {
+                preferences.speed = *SpeedUnit::ALL.iter().find(|u| u.symbol() == symbol)?
+            }
+        }
+        Some((base, Self::new(preferences)))
+    }
+
+    fn with_stored_unit(mut self, quantity: Quantity) -> Self {
+        match quantity {
+            Quantity::Length => self.preferences.length = LengthUnit::Meter,
+            Quantity::Angle => self.preferences.angle = AngleUnit::Radian,
+            Quantity::Speed => self.preferences.speed = SpeedUnit::MeterPerSecond,
+        }
+        self
+    }
+
+    /// 国际单位数值格式化为带单位的展示字符串。
+    pub fn format(&self, quantity: Quantity, value: f64) -> String {
+        let converted = self.from_si(quantity, value);
+        let decimals = match (quantity, self.symbol(quantity)) {
+            (Quantity::Length, "m") | (Quantity::Speed, _) | (Quantity::Angle, "rad") => 3,
+            _ => 1,
+        };
+        format!("{converted:.decimals$} {}", self.symbol(quantity))
+    }
+
+    /// 按偏好格式化一帧输出，供前端浮层直接显示。
+    pub fn frame_display(&self, frame: &ResponseData) -> FrameDisplay {
+        let vec3 = |quantity: Quantity, v: DVec3| v.to_array().map(|x| self.format(quantity, x));
+        FrameDisplay {
+            timestamp_ms: frame.timestamp_ms,
+            position: vec3(Quantity::Length, frame.position),
+            velocity: vec3(Quantity::Speed, frame.velocity),
+            speed: self.format(Quantity::Speed, frame.velocity.length()),
+            euler: vec3(Quantity::Angle, euler_zyx_rad(frame.attitude)),
+            position_sigma: self.format(Quantity::Length, frame.position_sigma_m),
+            units: self.preferences,
+        }
+    }
+}
+
+/// 四元数转 Z-Y-X 欧拉角（弧度）。
+fn euler_zyx_rad(q: DQuat) -> DVec3 {
+    euler_zyx_deg(q).map(f64::to_radians)
+}
+
+/// `get_latest_frame_display` 的返回值：按单位偏好格式化好的最新一帧。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameDisplay {
+    /// 设备时间戳（ms）。
+    pub timestamp_ms: u64,
+    /// 位置 [x, y, z]。
+    pub position: [String; 3],
+    /// 速度 [x, y, z]。
+    pub velocity: [String; 3],
+    /// 速度模长。
+    pub speed: String,
+    /// 姿态欧拉角 [roll, pitch, yaw]。
+    pub euler: [String; 3],
+    /// 位置不确定度。
+    pub position_sigma: String,
+    /// 格式化时使用的单位偏好。
+    pub units: UnitPreferences,
+}
+
+/// 单位偏好状态，由 `AppState` 持有；修改即时生效并写入设置文件。
+pub struct UnitPreferencesStore {
+    preferences: RwLock<UnitPreferences>,
+    settings_path: Option<PathBuf>,
+}
+
+impl UnitPreferencesStore {
+    /// 读取持久化的偏好，`config_dir` 为 `None` 时不持久化。
+    pub fn load(config_dir: Option<PathBuf>) -> Self {
+        let settings_path = config_dir.map(|dir| dir.join(SETTINGS_FILE));
+        let preferences = settings_path
+            .as_deref()
+            .map(|path| {
+                read_settings(path).unwrap_or_else(|error| {
+                    tracing::warn!("读取单位偏好失败，使用默认单位: {error:#}");
+                    UnitPreferences::default()
+                })
+            })
+            .unwrap_or_default();
+        Self {
+            preferences: RwLock::new(preferences),
+            settings_path,
+        }
+    }
+
+    /// 当前偏好。
+    pub fn get(&self) -> UnitPreferences {
+        *self
+            .preferences
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 当前偏好对应的换算器。
+    pub fn converter(&self) -> UnitConverter {
+        UnitConverter::new(self.get())
+    }
+
+    /// 修改偏好并写入设置文件。
+    pub fn set(&self, preferences: UnitPreferences) -> anyhow::Result<()> {
+        if let Some(path) = &self.settings_path {
+            write_settings(path, &preferences)?;
+        }
+        *self
+            .preferences
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = preferences;
+        tracing::info!(?preferences, "单位偏好已更新");
+        Ok(())
+    }
+}
+
+fn read_settings(path: &Path) -> anyhow::Result<UnitPreferences> {
+    if !path.exists() {
+        return Ok(UnitPreferences::default());
+    }
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("parse {}", path.display()))
+}
+
+fn write_settings(path: &Path, preferences: &UnitPreferences) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create settings directory")?;
+    }
+    let content = toml::to_string_pretty(preferences).context("serialize unit preferences")?;
+    std::fs::write(path, content).with_context(|| format!("write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        processor::navigator::{MotionState, ZuptState},
+        types::outputs::RESPONSE_SCHEMA_VERSION,
+    };
+
+    const SAMPLES: [f64; 6] = [0.0, 1.0, -2.5, 0.123_456_789, 1e-6, 12_345.678];
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    fn check_pairs<U: Unit + std::fmt::Debug>(known: &[(U, U, f64)]) {
+        for &from in U::ALL {
+            for &to in U::ALL {
+                for value in SAMPLES {
+                    let there = convert(value, from, to);
+                    // 往返换算回到原值
+                    assert!(
+                        close(convert(there, to, from), value),
+                        "{from:?} <-> {to:?}"
+                    );
+                    // 经国际单位中转与直接换算一致
+                    let via_si = value * from.si_per_unit() / to.si_per_unit();
+                    assert!(close(there, via_si), "{from:?} -> {to:?}");
+                }
+                if from.symbol() == to.symbol() {
+                    assert_eq!(convert(SAMPLES[3], from, to), SAMPLES[3]);
+                }
+            }
+        }
+        for &(from, to, factor) in known {
+            assert!(close(convert(1.0, from, to), factor), "{from:?} -> {to:?}");
+        }
+    }
+
+    #[test]
+    fn every_unit_pair_converts_and_round_trips() {
+        use AngleUnit::*;
+        use LengthUnit::*;
+        use SpeedUnit::*;
+
+        check_pairs(&[
+            (Meter, Centimeter, 100.0),
+            (Meter, Millimeter, 1000.0),
+            (Centimeter, Millimeter, 10.0),
+            (Millimeter, Meter, 0.001),
+        ]);
+        check_pairs(&[
+            (Radian, Degree, 180.0 / std::f64::consts::PI),
+            (Degree, Radian, std::f64::consts::PI / 180.0),
+        ]);
+        check_pairs(&[
+            (MeterPerSecond, KilometerPerHour, 3.6),
+            (KilometerPerHour, MeterPerSecond, 1.0 / 3.6),
+        ]);
+
+        // 反复往返不累积误差
+        let converter = UnitConverter::new(UnitPreferences {
+            length: Millimeter,
+            angle: Degree,
+            speed: KilometerPerHour,
+        });
+        for quantity in [Quantity::Length, Quantity::Angle, Quantity::Speed] {
+            let mut value = 0.987_654_321;
+            for _ in 0..1000 {
+                value = converter.to_si(quantity, converter.from_si(quantity, value));
+            }
+            assert!(close(value, 0.987_654_321), "{quantity:?}");
+        }
+    }
+
+    #[test]
+    fn column_names_carry_the_unit_and_parse_back() {
+        let si = UnitConverter::new(UnitPreferences {
+            angle: AngleUnit::Radian,
+            ..UnitPreferences::default()
+        });
+        assert_eq!(
+            si.column_name("calc_position_x", Quantity::Length),
+            "calc_position_x"
+        );
+
+        let cm = UnitConverter::new(UnitPreferences {
+            length: LengthUnit::Centimeter,
+            speed: SpeedUnit::KilometerPerHour,
+            ..UnitPreferences::default()
+        });
+        assert_eq!(
+            cm.column_name("calc_position_x", Quantity::Length),
+            "calc_position_x[cm]"
+        );
+        assert_eq!(
+            cm.column_name("position_sigma_m", Quantity::Length),
+            "position_sigma[cm]"
+        );
+        assert_eq!(
+            cm.column_name("calc_velocity_z", Quantity::Speed),
+            "calc_velocity_z[km/h]"
+        );
+
+        let (base, parsed) =
+            UnitConverter::parse_column_name("calc_position_x[cm]", Quantity::Length).unwrap();
+        assert_eq!(base, "calc_position_x");
+        assert!(close(parsed.to_si(Quantity::Length, 150.0), 1.5));
+        let (base, parsed) =
+            UnitConverter::parse_column_name("calc_velocity_y", Quantity::Speed).unwrap();
+        assert_eq!(base, "calc_velocity_y");
+        assert_eq!(parsed.to_si(Quantity::Speed, 2.0), 2.0);
+        assert!(
+            UnitConverter::parse_column_name("calc_position_x[ft]", Quantity::Length).is_none()
+        );
+    }
+
+    #[test]
+    fn preferences_persist_and_frame_display_uses_them() {
+        let dir = std::env::temp_dir().join(format!("imu_vis_units_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let store = UnitPreferencesStore::load(Some(dir.clone()));
+        assert_eq!(store.get(), UnitPreferences::default());
+        let preferences = UnitPreferences {
+            length: LengthUnit::Centimeter,
+            angle: AngleUnit::Degree,
+            speed: SpeedUnit::KilometerPerHour,
+        };
+        store.set(preferences).unwrap();
+        assert_eq!(
+            UnitPreferencesStore::load(Some(dir.clone())).get(),
+            preferences
+        );
+
+        let frame = ResponseData {
+            schema_version: RESPONSE_SCHEMA_VERSION,
+            timestamp_ms: 40,
+            accel: DVec3::ZERO,
+            accel_with_g: DVec3::ZERO,
+            gyro: DVec3::ZERO,
+            attitude: DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            velocity: DVec3::new(3.0, 4.0, 0.0),
+            position: DVec3::new(1.234, -0.05, 0.0),
+            accel_saturated: false,
+            motion_state: MotionState::Moving,
+            zupt: ZuptState::default(),
+            position_source: Default::default(),
+            jerk: None,
+            ang_accel: None,
+            position_sigma_m: 0.02,
+            host_time_estimate_ms: None,
+            debug_vectors: None,
+        };
+        let display = store.converter().frame_display(&frame);
+        assert_eq!(display.position, ["123.4 cm", "-5.0 cm", "0.0 cm"]);
+        assert_eq!(display.speed, "18.000 km/h");
+        assert_eq!(display.euler[2], "90.0 deg");
+        assert_eq!(display.position_sigma, "2.0 cm");
+        assert_eq!(display.units, preferences);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
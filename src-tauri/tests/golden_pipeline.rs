@@ -0,0 +1,362 @@
+//! 管线回归测试：固定配置下逐包重跑 `tests/fixtures/golden` 中的合成数据包，
+//! 与提交的参考轨迹逐检查点比较位置与姿态。
+//!
+//! 每个场景对应两个文件：
+//! - `<name>.packets`：0x11 数据包，每行一个十六进制编码的包（与 BLE 通知载荷一致）
+//! - `<name>.golden.json`：检查点上的参考位置/姿态与容差
+//!
+//! 处理配置固定为 `pipeline.toml`，不随 `processor.toml` 或默认配置变化。
+//!
+//! 有意修改 ZUPT、滤波或积分算法后，重新生成参考轨迹（同时按场景定义重写数据包）：
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test --test golden_pipeline
+//! ```
+//!
+//! 然后用 `git diff tests/fixtures/golden` 确认变化符合预期再提交。
+
+use std::path::{Path, PathBuf};
+
+use math_f64::{DQuat, DVec3};
+use serde::{Deserialize, Serialize};
+
+use tauri_app_lib::{
+    processor::{
+        output::OutputFrame, parser::ImuSampleRaw, pipeline::ProcessorPipelineConfig,
+        shared::DeviceTimeMs,
+    },
+    selftest::{encode_packets, run_packets},
+};
+
+const GRAVITY: f64 = 9.80665;
+/// 场景的采样间隔 (ms)，即 100 Hz 上报。
+const SAMPLE_INTERVAL_MS: u64 = 10;
+/// 首帧设备时间戳 (ms)。
+const START_MS: u64 = 10_000;
+/// 检查点间隔 (ms)。
+const CHECKPOINT_INTERVAL_MS: u64 = 250;
+/// 位置容差 (m)：同一输入应逐位复现，只为跨平台浮点差异留余量。
+const POSITION_TOLERANCE_M: f64 = 1e-6;
+/// 姿态容差 (°)。
+const ATTITUDE_TOLERANCE_DEG: f64 = 1e-4;
+
+/// 参考轨迹上的检查点。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Checkpoint {
+    timestamp_ms: u64,
+    position: [f64; 3],
+    /// `[w, x, y, z]`。
+    attitude: [f64; 4],
+    is_static: bool,
+}
+
+/// 一个场景的参考输出。
+#[derive(Debug, Serialize, Deserialize)]
+struct Golden {
+    position_tolerance_m: f64,
+    attitude_tolerance_deg: f64,
+    frame_count: usize,
+    checkpoints: Vec<Checkpoint>,
+}
+
+/// 合成场景：名称与每帧 (世界系线加速度 m/s², 绕 z 角速度 °/s)。
+struct Scenario {
+    name: &'static str,
+    motion: fn(u64) -> (DVec3, f64),
+    duration_ms: u64,
+}
+
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "static_hold",
+        motion: |_| (DVec3::ZERO, 0.0),
+        duration_ms: 2_000,
+    },
+    Scenario {
+        name: "straight_line_with_stops",
+        // 静止 → +x 加速/减速 → 停 → -x 加速/减速 → 停，两段各约 4 cm
+        motion: |t| match t {
+            1_000..=1_399 => (DVec3::X * 0.5, 0.0),
+            1_400..=1_799 => (DVec3::X * -0.5, 0.0),
+            2_600..=2_999 => (DVec3::X * -0.5, 0.0),
+            3_000..=3_399 => (DVec3::X * 0.5, 0.0),
+            _ => (DVec3::ZERO, 0.0),
+        },
+        duration_ms: 4_400,
+    },
+    Scenario {
+        name: "rotation_in_place",
+        // 静止 → 绕 z 匀速 90°/s 转 1 s → 静止
+        motion: |t| match t {
+            800..=1_799 => (DVec3::ZERO, 90.0),
+            _ => (DVec3::ZERO, 0.0),
+        },
+        duration_ms: 2_600,
+    },
+];
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden")
+}
+
+/// 确定性的小幅噪声，范围 [-1, 1]，使滤波与 ZUPT 判定不在理想零值上。
+fn noise(i: u64, channel: u64) -> f64 {
+    let mut x = i
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        .wrapping_add(channel.wrapping_mul(0xBF58_476D_1CE4_E5B9));
+    x ^= x >> 31;
+    x = x.wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 29;
+    (x % 2_001) as f64 / 1_000.0 - 1.0
+}
+
+/// 按场景定义生成样本：姿态由角速度积分，加速度按姿态转到机体系。
+fn synthesize(scenario: &Scenario) -> Vec<ImuSampleRaw> {
+    let mut yaw_deg = 0.0;
+    (0..scenario.duration_ms / SAMPLE_INTERVAL_MS)
+        .map(|i| {
+            let t = i * SAMPLE_INTERVAL_MS;
+            let (accel_world, gyro_z) = (scenario.motion)(t);
+            yaw_deg += gyro_z * SAMPLE_INTERVAL_MS as f64 / 1000.0;
+            let attitude = DQuat::from_rotation_z(yaw_deg.to_radians());
+            let to_body = attitude.inverse();
+            let accel_noise = DVec3::new(noise(i, 0), noise(i, 1), noise(i, 2)) * 0.02;
+            let gyro_noise = DVec3::new(noise(i, 3), noise(i, 4), noise(i, 5)) * 0.2;
+            let accel_body = to_body.rotate_vec3(accel_world) + accel_noise;
+            ImuSampleRaw {
+                timestamp_ms: DeviceTimeMs(START_MS + t),
+                accel_no_g: accel_body,
+                accel_with_g: accel_body + to_body.rotate_vec3(DVec3::Z * GRAVITY),
+                gyro: DVec3::Z * gyro_z + gyro_noise,
+                quat: attitude,
+                angle: DVec3::Z * yaw_deg,
+                offset: DVec3::ZERO,
+                accel_nav: accel_world,
+                baro: None,
+            }
+        })
+        .collect()
+}
+
+fn to_hex(packet: &[u8]) -> String {
+    packet.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(line: &str) -> Vec<u8> {
+    (0..line.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&line[i..i + 2], 16).expect("invalid hex in packet fixture"))
+        .collect()
+}
+
+fn read_packets(name: &str) -> Vec<Vec<u8>> {
+    let path = fixture_dir().join(format!("{name}.packets"));
+    let content = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "读取 {} 失败: {e}；用 UPDATE_GOLDEN=1 cargo test --test golden_pipeline 生成",
+            path.display()
+        )
+    });
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(from_hex)
+        .collect()
+}
+
+/// 读取固定配置；重新生成时若缺失则以默认配置创建。
+fn pinned_config() -> ProcessorPipelineConfig {
+    let path = fixture_dir().join("pipeline.toml");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() && !path.exists() {
+        std::fs::create_dir_all(fixture_dir()).unwrap();
+        let content = toml::to_string_pretty(&ProcessorPipelineConfig::default()).unwrap();
+        std::fs::write(&path, content).unwrap();
+    }
+    ProcessorPipelineConfig::load_from_path(&path).expect("load pinned pipeline.toml")
+}
+
+fn run(config: ProcessorPipelineConfig, packets: &[Vec<u8>]) -> Vec<OutputFrame> {
+    run_packets(config, SAMPLE_INTERVAL_MS as f64, packets)
+}
+
+/// 从首帧起每隔 [`CHECKPOINT_INTERVAL_MS`] 取一帧，并包含最后一帧。
+fn checkpoints(frames: &[OutputFrame]) -> Vec<Checkpoint> {
+    let start = frames.first().map_or(0, |frame| frame.nav.timestamp_ms);
+    let last = frames.len().saturating_sub(1);
+    frames
+        .iter()
+        .enumerate()
+        .filter(|(i, frame)| {
+            (frame.nav.timestamp_ms - start).is_multiple_of(CHECKPOINT_INTERVAL_MS) || *i == last
+        })
+        .map(|(_, frame)| {
+            let (p, q) = (frame.nav.position, frame.nav.attitude);
+            Checkpoint {
+                timestamp_ms: frame.nav.timestamp_ms,
+                position: [p.x, p.y, p.z],
+                attitude: [q.w, q.x, q.y, q.z],
+                is_static: frame.zupt.is_static,
+            }
+        })
+        .collect()
+}
+
+/// 检查点四元数，归一化后比较（设备四元数经 i16 量化，模长不严格为 1）。
+fn quat(q: [f64; 4]) -> DQuat {
+    DQuat {
+        w: q[0],
+        x: q[1],
+        y: q[2],
+        z: q[3],
+    }
+    .normalize()
+}
+
+/// 与参考轨迹比较，返回全部不一致之处。
+fn compare(name: &str, golden: &Golden, frames: &[OutputFrame]) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    if frames.len() != golden.frame_count {
+        mismatches.push(format!(
+            "{name}: {} frames, expected {}",
+            frames.len(),
+            golden.frame_count
+        ));
+    }
+    let actual = checkpoints(frames);
+    if actual.len() != golden.checkpoints.len() {
+        mismatches.push(format!(
+            "{name}: {} checkpoints, expected {}",
+            actual.len(),
+            golden.checkpoints.len()
+        ));
+    }
+    for (actual, expected) in actual.iter().zip(&golden.checkpoints) {
+        let t = expected.timestamp_ms;
+        if actual.timestamp_ms != t {
+            mismatches.push(format!(
+                "{name}: checkpoint at {} ms, expected {t} ms",
+                actual.timestamp_ms
+            ));
+            continue;
+        }
+        let position_error = DVec3::from(actual.position).distance(DVec3::from(expected.position));
+        if position_error > golden.position_tolerance_m {
+            mismatches.push(format!(
+                "{name} @ {t} ms: position {:?} deviates {position_error:.3e} m from {:?}",
+                actual.position, expected.position
+            ));
+        }
+        let dot = quat(actual.attitude)
+            .dot(quat(expected.attitude))
+            .abs()
+            .min(1.0);
+        let attitude_error = (2.0 * dot.acos()).to_degrees();
+        if attitude_error > golden.attitude_tolerance_deg {
+            mismatches.push(format!(
+                "{name} @ {t} ms: attitude deviates {attitude_error:.3e}°"
+            ));
+        }
+        if actual.is_static != expected.is_static {
+            mismatches.push(format!(
+                "{name} @ {t} ms: is_static {}, expected {}",
+                actual.is_static, expected.is_static
+            ));
+        }
+    }
+    mismatches
+}
+
+fn read_golden(name: &str) -> Golden {
+    let path = fixture_dir().join(format!("{name}.golden.json"));
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("读取 {} 失败: {e}", path.display()));
+    serde_json::from_str(&content).expect("parse golden json")
+}
+
+/// 按场景定义重写数据包与参考轨迹。
+fn regenerate(config: &ProcessorPipelineConfig) {
+    std::fs::create_dir_all(fixture_dir()).unwrap();
+    for scenario in SCENARIOS {
+        let packets = encode_packets(&synthesize(scenario));
+        let lines: Vec<String> = packets.iter().map(|packet| to_hex(packet)).collect();
+        let dir = fixture_dir();
+        std::fs::write(
+            dir.join(format!("{}.packets", scenario.name)),
+            lines.join("\n") + "\n",
+        )
+        .unwrap();
+
+        let frames = run(config.clone(), &packets);
+        let golden = Golden {
+            position_tolerance_m: POSITION_TOLERANCE_M,
+            attitude_tolerance_deg: ATTITUDE_TOLERANCE_DEG,
+            frame_count: frames.len(),
+            checkpoints: checkpoints(&frames),
+        };
+        std::fs::write(
+            dir.join(format!("{}.golden.json", scenario.name)),
+            serde_json::to_string_pretty(&golden).unwrap() + "\n",
+        )
+        .unwrap();
+        eprintln!("regenerated golden: {}", scenario.name);
+    }
+}
+
+#[test]
+fn pipeline_matches_golden_trajectories() {
+    let config = pinned_config();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        regenerate(&config);
+    }
+
+    let mismatches: Vec<String> = SCENARIOS
+        .iter()
+        .flat_map(|scenario| {
+            let frames = run(config.clone(), &read_packets(scenario.name));
+            compare(scenario.name, &read_golden(scenario.name), &frames)
+        })
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "管线输出偏离参考轨迹（有意修改时用 UPDATE_GOLDEN=1 重新生成）:\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn fixtures_exercise_motion_stops_and_rotation() {
+    let line = read_golden("straight_line_with_stops");
+    let moved = line
+        .checkpoints
+        .iter()
+        .map(|checkpoint| checkpoint.position[0].abs())
+        .fold(0.0, f64::max);
+    assert!(moved > 0.02, "直线场景位移过小: {moved} m");
+    assert!(line.checkpoints.iter().any(|c| c.is_static));
+    assert!(line.checkpoints.iter().any(|c| !c.is_static));
+
+    let rotation = read_golden("rotation_in_place");
+    let first = quat(rotation.checkpoints[0].attitude);
+    let last = quat(rotation.checkpoints.last().unwrap().attitude);
+    let turned = (2.0 * first.dot(last).abs().min(1.0).acos()).to_degrees();
+    assert!(
+        (turned - 90.0).abs() < 2.0,
+        "原地旋转约 90°，实际 {turned}°"
+    );
+}
+
+#[test]
+fn changed_zupt_threshold_is_detected() {
+    let mut config = pinned_config();
+    config.zupt.accel_enter_thresh *= 0.5;
+    config.zupt.accel_exit_thresh *= 0.5;
+
+    let mismatches: Vec<String> = SCENARIOS
+        .iter()
+        .flat_map(|scenario| {
+            let frames = run(config.clone(), &read_packets(scenario.name));
+            compare(scenario.name, &read_golden(scenario.name), &frames)
+        })
+        .collect();
+    assert!(!mismatches.is_empty(), "调整 ZUPT 阈值后输出应偏离参考轨迹");
+}
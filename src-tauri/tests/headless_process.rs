@@ -0,0 +1,186 @@
+//! 离线处理端到端测试：在临时 SQLite 中构造一段小录制，经库入口重跑管线并输出结果。
+
+use std::path::{Path, PathBuf};
+
+use math_f64::DVec3;
+use sea_orm::{ActiveModelTrait, ActiveValue::NotSet, EntityTrait, Set};
+
+use tauri_app_lib::{
+    headless::{process_recording, OutputFormat, ProcessOptions},
+    processor::pipeline::ProcessorPipelineConfig,
+    recorder::{db, models},
+};
+
+const SAMPLES: u64 = 300;
+const GRAVITY: f64 = 9.80665;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("imu_vis_headless_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 静止 → x 向加速/减速脉冲 → 静止，4 ms 一帧。
+fn sample_row(session_id: i64, i: u64) -> models::imu_samples::ActiveModel {
+    let ax = match i {
+        100..=124 => 1.0,
+        125..=149 => -1.0,
+        _ => 0.0,
+    };
+    let model = models::imu_samples::Model {
+        id: 0,
+        session_id,
+        timestamp_ms: (1000 + i * 4) as i64,
+        accel_no_g_x: ax,
+        accel_no_g_y: 0.0,
+        accel_no_g_z: 0.0,
+        accel_with_g_x: ax,
+        accel_with_g_y: 0.0,
+        accel_with_g_z: GRAVITY,
+        gyro_x: 0.0,
+        gyro_y: 0.0,
+        gyro_z: 0.0,
+        quat_w: 1.0,
+        quat_x: 0.0,
+        quat_y: 0.0,
+        quat_z: 0.0,
+        angle_x: 0.0,
+        angle_y: 0.0,
+        angle_z: 0.0,
+        offset_x: 0.0,
+        offset_y: 0.0,
+        offset_z: 0.0,
+        accel_nav_x: 0.0,
+        accel_nav_y: 0.0,
+        accel_nav_z: 0.0,
+        calc_attitude_w: 1.0,
+        calc_attitude_x: 0.0,
+        calc_attitude_y: 0.0,
+        calc_attitude_z: 0.0,
+        calc_velocity_x: 0.0,
+        calc_velocity_y: 0.0,
+        calc_velocity_z: 0.0,
+        calc_position_x: 0.0,
+        calc_position_y: 0.0,
+        calc_position_z: 0.0,
+        calc_timestamp_ms: (1000 + i * 4) as i64,
+        motion_state: "unknown".to_string(),
+        position_source: "integrated".to_string(),
+        smoothed_position_x: None,
+        smoothed_position_y: None,
+        smoothed_position_z: None,
+        transformed_position_x: None,
+        transformed_position_y: None,
+        transformed_position_z: None,
+        transformed_attitude_w: None,
+        transformed_attitude_x: None,
+        transformed_attitude_y: None,
+        transformed_attitude_z: None,
+        position_sigma_m: None,
+        baro_temperature_c: None,
+        baro_pressure_hpa: None,
+        baro_altitude_m: None,
+        baro_relative_altitude_m: None,
+    };
+    let mut active: models::imu_samples::ActiveModel = model.into();
+    active.id = NotSet;
+    active
+}
+
+async fn write_fixture(db_path: &Path) -> i64 {
+    let conn = db::connect(db_path).await.unwrap();
+    db::ensure_schema(&conn).await.unwrap();
+    let session = models::recording_sessions::ActiveModel {
+        started_at_ms: Set(1_700_000_000_000),
+        stopped_at_ms: Set(Some(1_700_000_001_200)),
+        name: Set(Some("fixture".into())),
+        sample_count: Set(SAMPLES as i64),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    models::imu_samples::Entity::insert_many((0..SAMPLES).map(|i| sample_row(session.id, i)))
+        .exec(&conn)
+        .await
+        .unwrap();
+    session.id
+}
+
+#[tokio::test]
+async fn process_recording_replays_fixture_end_to_end() {
+    let dir = temp_dir("e2e");
+    let db_path = dir.join("fixture.sqlite");
+    let session_id = write_fixture(&db_path).await;
+
+    let csv_path = dir.join("out.csv");
+    let summary = process_recording(ProcessOptions {
+        input: db_path.clone(),
+        session: Some("fixture".into()),
+        config: ProcessorPipelineConfig::default(),
+        output: Some(csv_path.clone()),
+        format: OutputFormat::Csv,
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(summary.session_id, session_id);
+    assert_eq!(
+        (summary.samples, summary.frames),
+        (SAMPLES as usize, SAMPLES as usize)
+    );
+    assert!(summary.final_position.x > 0.003, "{summary}");
+    assert!(
+        (summary.final_position_error_m - summary.final_position.length()).abs() < 1e-12,
+        "录制值全 0 时误差即最终位移: {summary}"
+    );
+    assert!(summary.max_speed_mps > 0.01, "{summary}");
+    assert!(
+        summary.static_fraction > 0.5 && summary.static_fraction < 1.0,
+        "{summary}"
+    );
+
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), SAMPLES as usize + 1);
+    assert!(lines[0].ends_with(",is_static"));
+
+    // 按 id 选会话、输出 JSON，结果应与 CSV 一致
+    let json_path = dir.join("out.json");
+    let again = process_recording(ProcessOptions {
+        input: db_path,
+        session: Some(session_id.to_string()),
+        config: ProcessorPipelineConfig::default(),
+        output: Some(json_path.clone()),
+        format: OutputFormat::Json,
+    })
+    .await
+    .unwrap();
+    assert_eq!(again.final_position, summary.final_position);
+    let frames: Vec<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(frames.len(), SAMPLES as usize);
+    let last_x = frames.last().unwrap()["position"]["x"].as_f64().unwrap();
+    assert_eq!(last_x, summary.final_position.x);
+    assert_eq!(DVec3::ZERO, summary.recorded_final_position);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn process_recording_rejects_missing_database() {
+    let dir = temp_dir("missing");
+    let missing = dir.join("nope.sqlite");
+    let result = process_recording(ProcessOptions {
+        input: missing.clone(),
+        session: None,
+        config: ProcessorPipelineConfig::default(),
+        output: None,
+        format: OutputFormat::Csv,
+    })
+    .await;
+    assert!(result.is_err());
+    assert!(!missing.exists(), "不应为错误路径创建空库");
+    let _ = std::fs::remove_dir_all(&dir);
+}
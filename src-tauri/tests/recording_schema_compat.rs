@@ -0,0 +1,334 @@
+//! 录制库旧表结构兼容测试。
+//!
+//! `tests/fixtures/legacy_recordings.sqlite` 取自早期版本写出的数据库（两段录制各 100 帧、
+//! 一条设备标定），表结构停留在加入运动状态、位置来源、平滑位置、标记、分段、
+//! 结束原因与陀螺零偏之前。当前代码打开它时应原地补齐表结构，旧数据照常可读，
+//! 新录制与标定照常写入。
+//!
+//! 旧会话没有 `schema_version`，按版本 1 读取并补齐新增字段的默认值；
+//! 版本比当前应用新的会话拒绝读取。
+//!
+//! 夹具没有 `schema_meta` 表，首次打开时先备份再迁移，迁移后记录表结构版本。
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use math_f64::{DQuat, DVec3};
+use sea_orm::{ConnectionTrait, Statement};
+
+use tauri_app_lib::{
+    processor::{
+        navigator::{MotionState, NavState, ZuptState},
+        output::OutputFrame,
+        parser::ImuSampleRaw,
+        shared::DeviceTimeMs,
+    },
+    recorder::{
+        db,
+        device_calibration::{get_device_calibration, save_device_calibration},
+        export_session_csv, get_recording_samples, get_recording_samples_binary, list_recordings,
+        spawn_recorder, start_recording, stop_recording, ExportOptions, RecorderOptions,
+        RecordingSplit, RecordingStartInput,
+    },
+    types::{outputs::RESPONSE_SCHEMA_VERSION, recording::RecordingQuery},
+};
+
+const LEGACY_DEVICE: &str = "31:40:1B:F4:FC:F0";
+
+/// 把夹具复制到临时目录，测试只改副本。
+fn legacy_copy(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("imu_vis_compat_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("imu_recordings.sqlite");
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/legacy_recordings.sqlite");
+    std::fs::copy(fixture, &db_path).unwrap();
+    db_path
+}
+
+/// 直接在夹具副本上执行 SQL，模拟其它版本写出的数据。
+async fn execute(db_path: &Path, sql: &str) {
+    let conn = db::connect(db_path).await.unwrap();
+    let backend = conn.get_database_backend();
+    conn.execute(Statement::from_string(backend, sql))
+        .await
+        .unwrap();
+}
+
+fn frame(timestamp_ms: u64) -> OutputFrame {
+    OutputFrame {
+        raw: ImuSampleRaw {
+            timestamp_ms: DeviceTimeMs(timestamp_ms),
+            accel_no_g: DVec3::ZERO,
+            accel_with_g: DVec3::new(0.0, 0.0, 9.80665),
+            gyro: DVec3::ZERO,
+            quat: DQuat::IDENTITY,
+            angle: DVec3::ZERO,
+            offset: DVec3::ZERO,
+            accel_nav: DVec3::ZERO,
+            baro: None,
+        },
+        nav: NavState {
+            timestamp_ms,
+            position: DVec3::ZERO,
+            velocity: DVec3::ZERO,
+            attitude: DQuat::IDENTITY,
+        },
+        motion_state: MotionState::Static,
+        zupt: ZuptState::default(),
+        position_source: Default::default(),
+        jerk: None,
+        ang_accel: None,
+        position_sigma_m: 0.0,
+        clock_sync: None,
+        heading_yaw_deg: None,
+        baro_relative_altitude_m: None,
+        debug_vectors: None,
+        stages: None,
+    }
+}
+
+fn backups(db_path: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(db_path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+        .collect()
+}
+
+#[tokio::test]
+async fn legacy_database_is_backed_up_once_before_migration() {
+    let db_path = legacy_copy("backup");
+    let before = db::verify_database(&db_path).await.unwrap();
+    assert!(before.ok, "{:?}", before.problems);
+    assert_eq!(before.schema_version, 0);
+
+    list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    let after = db::verify_database(&db_path).await.unwrap();
+    assert_eq!(after.schema_version, db::DB_SCHEMA_VERSION);
+
+    // 备份是迁移前的旧库：版本 0，数据完整
+    let backups = backups(&db_path);
+    assert_eq!(backups.len(), 1, "{backups:?}");
+    let backup = db::verify_database(&backups[0]).await.unwrap();
+    assert!(backup.ok);
+    assert_eq!(backup.schema_version, 0);
+    let conn = db::connect(&backups[0]).await.unwrap();
+    let row = conn
+        .query_one(Statement::from_string(
+            conn.get_database_backend(),
+            "SELECT COUNT(*) AS n FROM imu_samples;",
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.try_get::<i64>("", "n").unwrap(), 200);
+    conn.close().await.unwrap();
+
+    // 已是当前版本，再次打开不再迁移也不再备份
+    list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(self::backups(&db_path).len(), 1);
+
+    let _ = std::fs::remove_dir_all(db_path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn legacy_database_is_read_and_extended_in_place() {
+    let db_path = legacy_copy("legacy");
+
+    // 旧会话：缺失的列取默认值
+    let page = list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, 2);
+    let names: Vec<_> = page.items.iter().map(|m| m.name.as_deref()).collect();
+    assert_eq!(names, [Some("正方形"), Some("直线")]);
+    for meta in &page.items {
+        assert_eq!(meta.sample_count, 100);
+        assert_eq!(meta.tags, ["可用"]);
+        assert_eq!(meta.stop_reason, None);
+        assert_eq!(meta.parent_session_id, None);
+        assert!(!meta.interrupted);
+        assert_eq!(meta.schema_version, 1);
+    }
+
+    let samples = get_recording_samples(&db_path, 21).await.unwrap();
+    assert_eq!(samples.len(), 100);
+    assert_eq!(samples[0].timestamp_ms, 493_168_110);
+    assert!(samples
+        .windows(2)
+        .all(|w| w[0].timestamp_ms <= w[1].timestamp_ms));
+    assert!(samples
+        .iter()
+        .all(|s| s.motion_state == MotionState::Unknown));
+    assert!((samples[0].accel_with_g.z - 9.8765625).abs() < 1e-9);
+    assert!(samples
+        .iter()
+        .all(|s| s.schema_version == RESPONSE_SCHEMA_VERSION && s.position_sigma_m == 0.0));
+
+    // 旧标定：补齐的陀螺零偏列为 0，覆盖写入后读回新值
+    let legacy = get_device_calibration(&db_path, LEGACY_DEVICE)
+        .await
+        .unwrap()
+        .expect("legacy calibration");
+    assert!((legacy.accel_scale_x - 1.00139145166918).abs() < 1e-12);
+    assert_eq!(
+        (legacy.gyro_bias_x, legacy.gyro_bias_y, legacy.gyro_bias_z),
+        (0.0, 0.0, 0.0)
+    );
+    let updated = tauri_app_lib::recorder::models::device_calibrations::Model {
+        gyro_bias_z: 0.01,
+        created_at_ms: legacy.created_at_ms + 1,
+        ..legacy.clone()
+    };
+    save_device_calibration(&db_path, updated.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        get_device_calibration(&db_path, LEGACY_DEVICE)
+            .await
+            .unwrap(),
+        Some(updated)
+    );
+    assert_eq!(
+        get_device_calibration(&db_path, "unknown").await.unwrap(),
+        None
+    );
+
+    // 新录制写入同一数据库，与旧会话并存
+    let (data_tx, data_rx) = flume::bounded(64);
+    let (control_tx, control_rx) = flume::unbounded();
+    spawn_recorder(
+        data_rx,
+        control_rx,
+        RecorderOptions {
+            min_free_bytes: 0,
+            ..RecorderOptions::default()
+        },
+    );
+    let status = start_recording(
+        &control_tx,
+        db_path.clone(),
+        RecordingStartInput {
+            device_id: Some(LEGACY_DEVICE.into()),
+            name: Some("new".into()),
+            tags: None,
+            split: RecordingSplit::default(),
+            metadata: None,
+            deep: false,
+        },
+    )
+    .await
+    .unwrap();
+    let session_id = status.session_id.unwrap();
+    assert!(session_id > 22);
+    for ts in 0..10 {
+        data_tx.send(frame(1000 + ts * 4)).unwrap();
+    }
+    while !data_tx.is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let stopped = stop_recording(&control_tx).await.unwrap();
+    assert_eq!(stopped.sample_count, Some(10));
+
+    let page = list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, 3);
+    assert_eq!(page.items[0].id, session_id);
+    assert_eq!(page.items[0].stop_reason.as_deref(), Some("user"));
+    assert_eq!(page.items[0].schema_version, RESPONSE_SCHEMA_VERSION);
+    let samples = get_recording_samples(&db_path, session_id).await.unwrap();
+    assert_eq!(samples.len(), 10);
+    assert!(samples
+        .iter()
+        .all(|s| s.motion_state == MotionState::Static));
+
+    let _ = std::fs::remove_dir_all(db_path.parent().unwrap());
+}
+
+/// 当前表结构下的版本 1 会话：该版本没有位置不确定度，列里即使有值也补默认值 0；
+/// 标为当前版本后照常读出。
+#[tokio::test]
+async fn previous_schema_rows_are_filled_with_defaults() {
+    let db_path = legacy_copy("previous");
+    // 打开一次即补齐到当前表结构，旧会话的 schema_version 保持为空
+    list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    execute(
+        &db_path,
+        "UPDATE imu_samples SET position_sigma_m = 0.25, motion_state = 'static' WHERE session_id = 21;",
+    )
+    .await;
+
+    let samples = get_recording_samples(&db_path, 21).await.unwrap();
+    assert_eq!(samples.len(), 100);
+    for sample in &samples {
+        assert_eq!(sample.schema_version, RESPONSE_SCHEMA_VERSION);
+        assert_eq!(sample.position_sigma_m, 0.0);
+        assert_eq!(sample.host_time_estimate_ms, None);
+        assert_eq!(sample.jerk, None);
+        assert_eq!(sample.motion_state, MotionState::Static);
+    }
+
+    execute(
+        &db_path,
+        &format!("UPDATE recording_sessions SET schema_version = {RESPONSE_SCHEMA_VERSION} WHERE id = 21;"),
+    )
+    .await;
+    let samples = get_recording_samples(&db_path, 21).await.unwrap();
+    assert!(samples.iter().all(|s| s.position_sigma_m == 0.25));
+
+    let _ = std::fs::remove_dir_all(db_path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn sessions_newer_than_the_app_are_rejected() {
+    let db_path = legacy_copy("too_new");
+    list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    let newer = RESPONSE_SCHEMA_VERSION + 1;
+    execute(
+        &db_path,
+        &format!("UPDATE recording_sessions SET schema_version = {newer} WHERE id = 22;"),
+    )
+    .await;
+
+    // 列表照常显示，并标出版本
+    let page = list_recordings(&db_path, &RecordingQuery::default())
+        .await
+        .unwrap();
+    let meta = page.items.iter().find(|meta| meta.id == 22).unwrap();
+    assert_eq!(meta.schema_version, newer);
+
+    let err = get_recording_samples(&db_path, 22).await.unwrap_err();
+    let message = format!("{err:#}");
+    assert!(
+        message.contains(&format!("schema version {newer}"))
+            && message.contains(&format!("1..={RESPONSE_SCHEMA_VERSION}")),
+        "{message}"
+    );
+    assert!(get_recording_samples_binary(&db_path, 22, None, None)
+        .await
+        .is_err());
+    assert!(export_session_csv(&db_path, 22, ExportOptions::default())
+        .await
+        .is_err());
+
+    // 同库的旧会话不受影响
+    assert_eq!(
+        get_recording_samples(&db_path, 21).await.unwrap().len(),
+        100
+    );
+
+    let _ = std::fs::remove_dir_all(db_path.parent().unwrap());
+}
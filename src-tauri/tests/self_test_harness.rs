@@ -0,0 +1,127 @@
+//! 自检校验逻辑测试：离线参考与离线处理入口一致、校验能发现偏差、录制读回闭环。
+
+use std::{path::PathBuf, time::Duration};
+
+use tauri_app_lib::{
+    headless::run_pipeline,
+    processor::{parser::ImuParser, pipeline::ProcessorPipelineConfig},
+    recorder::{
+        recording_status, spawn_recorder, start_recording, stop_recording, RecorderOptions,
+        RecordingSplit, RecordingStartInput,
+    },
+    selftest::{
+        encode_packets, motion_profile, run_packets, verify_final_state, verify_frame_count,
+        verify_recording_readback,
+    },
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("imu_vis_selftest_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn ample_disk() -> RecorderOptions {
+    RecorderOptions {
+        min_free_bytes: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reference_matches_headless_replay_and_detects_drift() {
+    let packets = encode_packets(&motion_profile(4.0));
+    let reference = run_packets(ProcessorPipelineConfig::default(), 4.0, &packets);
+    assert!(verify_frame_count(250, reference.len()).is_ok());
+    assert!(verify_frame_count(250, reference.len() - 1).is_err());
+
+    // 经编码/解析往返后的结果应与直接喂样本的离线处理一致
+    let samples = packets.iter().map(|p| ImuParser::parse(p).unwrap());
+    let headless = run_pipeline(ProcessorPipelineConfig::default(), samples);
+    let (expected, replayed) = (reference.last().unwrap(), headless.last().unwrap());
+    assert_eq!(expected.nav.position, replayed.nav.position);
+    assert!(
+        expected.nav.position.x > 0.005,
+        "合成运动应产生沿 x 的位移: {:?}",
+        expected.nav.position
+    );
+
+    assert!(verify_final_state(expected, expected).is_ok());
+    let mut drifted = *expected;
+    drifted.nav.position.x += 1e-3;
+    let error = verify_final_state(expected, &drifted).unwrap_err();
+    assert!(error.to_string().contains("final position"), "{error}");
+}
+
+#[tokio::test]
+async fn recorder_round_trip_and_unwritable_path() {
+    let dir = temp_dir("recorder");
+    let reference = run_packets(
+        ProcessorPipelineConfig::default(),
+        4.0,
+        &encode_packets(&motion_profile(4.0)),
+    );
+
+    let (data_tx, data_rx) = flume::bounded(reference.len());
+    let (control_tx, control_rx) = flume::unbounded();
+    spawn_recorder(data_rx, control_rx, ample_disk());
+
+    let db_path = dir.join("selftest.sqlite");
+    let status = start_recording(
+        &control_tx,
+        db_path.clone(),
+        RecordingStartInput {
+            device_id: None,
+            name: Some("self-test".into()),
+            tags: None,
+            split: RecordingSplit::default(),
+            metadata: None,
+            deep: false,
+        },
+    )
+    .await
+    .unwrap();
+    let session_id = status.session_id.unwrap();
+    for frame in &reference {
+        data_tx.send_async(*frame).await.unwrap();
+    }
+    // 控制命令优先于数据，先等样本落盘再停止
+    for _ in 0..300 {
+        let status = recording_status(&control_tx).await.unwrap();
+        if status.sample_count == Some(reference.len() as u64) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    stop_recording(&control_tx).await.unwrap();
+    let detail = verify_recording_readback(&db_path, session_id, &reference)
+        .await
+        .unwrap();
+    assert!(detail.contains(&reference.len().to_string()), "{detail}");
+    assert!(
+        verify_recording_readback(&db_path, session_id, &reference[..10])
+            .await
+            .is_err()
+    );
+
+    // 录制目录不可写（父路径是普通文件）时开始录制应报错
+    let blocker = dir.join("not_a_dir");
+    std::fs::write(&blocker, b"x").unwrap();
+    let result = start_recording(
+        &control_tx,
+        blocker.join("selftest.sqlite"),
+        RecordingStartInput {
+            device_id: None,
+            name: None,
+            tags: None,
+            split: RecordingSplit::default(),
+            metadata: None,
+            deep: false,
+        },
+    )
+    .await;
+    assert!(result.is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
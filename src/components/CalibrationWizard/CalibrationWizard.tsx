@@ -14,9 +14,9 @@ import {
   message,
 } from 'antd';
 import { Channel } from '@tauri-apps/api/core';
-import { imuApi } from '../../services/imu';
+import { imuApi, unbatchOutput } from '../../services/imu';
 import { useBluetooth } from '../../hooks/useBluetooth';
-import type { ResponseData, Vector3 } from '../../types';
+import type { OutputEvent, StreamMessage, Vector3 } from '../../types';
 
 const { Title, Text, Paragraph } = Typography;
 
@@ -90,8 +90,11 @@ export const CalibrationWizard = ({ deviceAddress }: Props) => {
 
   // 标定向导期间直接订阅实时输出，避免额外全局订阅造成双重消息分发开销。
   useEffect(() => {
-    const channel = new Channel<ResponseData>();
-    channel.onmessage = (data: ResponseData) => {
+    const handleMessage = (data: StreamMessage) => {
+      // 静止抑制的心跳不带传感器数据（标定采集需关闭 output_suppress）
+      if ("unchanged" in data) {
+        return;
+      }
       const v = data.accel_with_g;
       const g = data.gyro;
       setLiveAccel({ x: v.x, y: v.y, z: v.z });
@@ -104,10 +107,19 @@ export const CalibrationWizard = ({ deviceAddress }: Props) => {
         }
       }
     };
-    imuApi.subscribeOutput(channel);
+    const channel = new Channel<OutputEvent>();
+    channel.onmessage = (event) => {
+      unbatchOutput(event).forEach(handleMessage);
+    };
+    const subscription = imuApi.subscribeOutput(channel);
 
     return () => {
       channel.onmessage = () => {};
+      subscription.then((res) => {
+        if (res.success && res.data !== undefined) {
+          imuApi.unsubscribeOutput(res.data);
+        }
+      });
     };
   }, []);
 
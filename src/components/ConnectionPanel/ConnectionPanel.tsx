@@ -1,10 +1,11 @@
-import { useEffect, useRef, useState } from 'react';
+import { useCallback, useEffect, useRef, useState } from 'react';
 import { Button, Card, Col, Empty, Form, InputNumber, Row, Select, Space, Switch, Tag, message } from 'antd';
 import { ReloadOutlined, PoweroffOutlined, CheckCircleOutlined, SignalFilled } from '@ant-design/icons';
 import Text from "antd/es/typography/Text";
 
 import { useBluetooth } from '../../hooks/useBluetooth';
-import { ProcessorPipelineConfig } from '../../types';
+import { imuApi } from '../../services/imu';
+import { BluetoothAdapterInfo, ProcessorPipelineConfig } from '../../types';
 
 import styles from "./ConnectionPanel.module.scss";
 
@@ -12,20 +13,32 @@ const DEFAULT_SEARCH_VALUE = "im";
 const numberRules = [{ required: true, message: '必填' }];
 
 const DEFAULT_CONFIG: ProcessorPipelineConfig = {
-  global: { gravity: 9.80665 },
+  global: { gravity: 9.80665, auto_gravity: { enabled: false, window_frames: 250 }, unsafe_device_commands: false },
+  reassembly: { enabled: true, max_hold_ms: 100, max_held_bytes: 128 },
   calibration: {
     passby: false,
+    gyro_unit: 'deg_per_sec',
+    accel_map: ['x', 'y', 'z'],
+    gyro_map: ['x', 'y', 'z'],
     accel_bias: { x: 0, y: 0, z: 0 },
     gyro_bias: { x: 0, y: 0, z: 0 },
     accel_matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
     gyro_matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
   },
+  sequencer: { reorder: false, lookahead_frames: 2, lookahead_ms: 8, reset_on_reboot: false },
+  calibration_quality: { window_ms: 1000, min_score: 60, reject_below_min: true },
   filter: { passby: false, alpha: 0.9 },
+  derived: { enabled: false, alpha: 0.7 },
   trajectory: {
     passby: false,
     integrator: 'trapezoid',
     dt_min_ms: 1,
     dt_max_ms: 50,
+    max_integration_dt_ms: 50,
+    accel_deadband_ms2: 0,
+    accel_deadband_mode: 'magnitude',
+    accel_deadband_bypass_ms2: 1.0,
+    position_sigma_accel_noise: 0.05,
   },
   zupt: {
     passby: false,
@@ -36,14 +49,16 @@ const DEFAULT_CONFIG: ProcessorPipelineConfig = {
     accel_enter_thresh: 0.22,
     gyro_exit_thresh: 0.2,
     accel_exit_thresh: 0.3,
-    enter_frames: 3,
-    exit_frames: 3,
+    enter_window_ms: 12,
+    exit_window_ms: 12,
     vel_decay_tau_ms: 70,
     pos_lock_tau_ms: 110,
     vel_zero_eps: 0.03,
     backward_correction: false,
   },
+  plane_constraint: { mode: 'none', height_m: 0 },
   navigator_impl: 'legacy',
+  position_source: { mode: 'integrated', blend_weight: 0.05 },
   eskf: {
     gyro_noise: 0.005,
     accel_noise: 0.05,
@@ -57,6 +72,16 @@ const DEFAULT_CONFIG: ProcessorPipelineConfig = {
     init_sigma_gyro_bias: 0.01,
     init_sigma_accel_bias: 0.1,
   },
+  segment: { enabled: false, min_duration_ms: 200, min_path_length_m: 0.01, min_static_ms: 100 },
+  idle: { enabled: true, grace_period_ms: 30000 },
+  output_suppress: { enabled: false, position_eps_m: 0.001, velocity_eps_mps: 0.001, attitude_eps_deg: 0.1, heartbeat_ms: 200 },
+  quat_health: { enabled: true, freeze_eps_deg: 0.001, gyro_thresh: 0.3, freeze_frames: 25, blend_frames: 25 },
+  accel_source: { mode: 'with_g', consistency_thresh_ms2: 0.5, consistency_frames: 25 },
+  auto_origin: { enabled: false, static_ms: 500 },
+  baro: { enabled: false, gain: 0.2 },
+  heading_align: { enabled: false, min_displacement_m: 0.3, min_static_ms: 100 },
+  sync_tap: { enabled: false, threshold_g: 3, max_duration_ms: 50, refractory_ms: 500 },
+  debug_vectors: { enabled: false },
 };
 
 const getRssiColor = (rssi?: number) => {
@@ -71,6 +96,31 @@ export const ConnectionPanel = () => {
   const { scanning, devices, connectedDevice, toggleScan, connect, disconnect } = useBluetooth();
   const [selectedDeviceId, setSelectedDeviceId] = useState<string | null>(null);
   const [searchvalue, setSearchvalue] = useState(DEFAULT_SEARCH_VALUE);
+  const [adapters, setAdapters] = useState<BluetoothAdapterInfo[]>([]);
+  const [bluetoothError, setBluetoothError] = useState<string | null>(null);
+
+  const refreshAdapters = useCallback(async () => {
+    const res = await imuApi.listBluetoothAdapters();
+    if (res.success && res.data) {
+      setAdapters(res.data);
+      setBluetoothError(res.data.length === 0 ? '没找到蓝牙适配器' : null);
+    } else {
+      setAdapters([]);
+      setBluetoothError(res.code === 'BluetoothUnavailable' ? '蓝牙不可用，请开启系统蓝牙' : (res.message ?? '蓝牙不可用'));
+    }
+  }, []);
+
+  useEffect(() => {
+    refreshAdapters().catch(console.error);
+  }, [refreshAdapters]);
+
+  const handleSelectAdapter = async (id: string) => {
+    const res = await imuApi.selectBluetoothAdapter(id);
+    if (!res.success) {
+      message.error(`切换蓝牙适配器失败：${res.message ?? '未知错误'}`);
+    }
+    await refreshAdapters();
+  };
 
   useEffect(() => {
     if (connectedDevice) {
@@ -105,15 +155,32 @@ export const ConnectionPanel = () => {
           <Tag color="blue" className={styles.deviceTag}>
             已发现 <strong>{devices.length}</strong> 台设备
           </Tag>
+          {bluetoothError && (
+            <Tag color="error" onClick={() => refreshAdapters()}>{bluetoothError}</Tag>
+          )}
         </Col>
         <Col>
-          <Button
-            icon={<ReloadOutlined spin={scanning} />}
-            onClick={toggleScan}
-            type={scanning ? "default" : "primary"}
-          >
-            {scanning ? "扫描中..." : "开始扫描"}
-          </Button>
+          <Space>
+            {adapters.length > 1 && (
+              <Select
+                style={{ minWidth: 160 }}
+                value={adapters.find((adapter) => adapter.selected)?.id}
+                onChange={handleSelectAdapter}
+                disabled={isConnected || scanning}
+                options={adapters.map((adapter) => ({
+                  label: adapter.powered === false ? `${adapter.id}（未开启）` : adapter.id,
+                  value: adapter.id,
+                }))}
+              />
+            )}
+            <Button
+              icon={<ReloadOutlined spin={scanning} />}
+              onClick={toggleScan}
+              type={scanning ? "default" : "primary"}
+            >
+              {scanning ? "扫描中..." : "开始扫描"}
+            </Button>
+          </Space>
         </Col>
       </Row>
 
@@ -204,7 +271,7 @@ export const SettingsPanel = () => {
   const {
     connectedDevice,
     getPipelineConfig,
-    updatePipelineConfig,
+    patchPipelineConfig,
     savePipelineConfig,
     setNeedsCalibration,
   } = useBluetooth();
@@ -213,6 +280,7 @@ export const SettingsPanel = () => {
   const autoApplyTimerRef = useRef<number | undefined>(undefined);
   const latestFullConfigRef = useRef<ProcessorPipelineConfig | null>(null);
   const integrator = Form.useWatch(['trajectory', 'integrator'], form);
+  const headingAlignEnabled = Form.useWatch(['heading_align', 'enabled'], form);
   const zuptImplType = Form.useWatch(['zupt', 'impl_type'], form);
 
   useEffect(() => {
@@ -252,17 +320,36 @@ export const SettingsPanel = () => {
         if (!baseConfig) return;
         const config: ProcessorPipelineConfig = {
           global: formValues.global ?? baseConfig.global,
+          reassembly: formValues.reassembly ?? baseConfig.reassembly,
+          sequencer: formValues.sequencer ?? baseConfig.sequencer,
           calibration: formValues.calibration ?? baseConfig.calibration,
+          calibration_quality: formValues.calibration_quality ?? baseConfig.calibration_quality,
           filter: formValues.filter ?? baseConfig.filter,
+          derived: formValues.derived ?? baseConfig.derived,
           trajectory: formValues.trajectory ?? baseConfig.trajectory,
           zupt: formValues.zupt ?? baseConfig.zupt,
+          plane_constraint: formValues.plane_constraint ?? baseConfig.plane_constraint,
           navigator_impl: formValues.navigator_impl ?? baseConfig.navigator_impl,
           eskf: formValues.eskf ?? baseConfig.eskf,
+          position_source: formValues.position_source ?? baseConfig.position_source,
+          segment: formValues.segment ?? baseConfig.segment,
+          idle: formValues.idle ?? baseConfig.idle,
+          output_suppress: formValues.output_suppress ?? baseConfig.output_suppress,
+          quat_health: formValues.quat_health ?? baseConfig.quat_health,
+          accel_source: formValues.accel_source ?? baseConfig.accel_source,
+          auto_origin: formValues.auto_origin ?? baseConfig.auto_origin,
+          baro: formValues.baro ?? baseConfig.baro,
+          heading_align: formValues.heading_align ?? baseConfig.heading_align,
+          sync_tap: formValues.sync_tap ?? baseConfig.sync_tap,
+          debug_vectors: formValues.debug_vectors ?? baseConfig.debug_vectors,
         };
         console.info('[SettingsPanel] apply pipeline config:', JSON.stringify(config, null, 2));
-        const updated = await updatePipelineConfig(config);
-        if (updated) {
+        const mode = await patchPipelineConfig(config);
+        if (mode) {
           latestFullConfigRef.current = config;
+          if (mode === 'reset') {
+            message.info('结构性参数已变更，轨迹已重置');
+          }
         }
       } catch {
         // ignore invalid intermediate input
@@ -279,6 +366,15 @@ export const SettingsPanel = () => {
     }
   };
 
+  const handleArmHeading = async () => {
+    const res = await imuApi.armHeadingFromMotion();
+    if (!res.success) {
+      message.error(`定航向失败：${res.message ?? '未知错误'}`);
+      return;
+    }
+    message.info('请沿期望的 +X 方向平移设备');
+  };
+
   if (!connectedDevice) {
     return (
       <div className={`${styles.connectionPanel} ${styles.settingsPanel}`}>
@@ -332,6 +428,42 @@ export const SettingsPanel = () => {
               <Form.Item label="重力加速度" tooltip="全局重力常数（m/s²），用于线加速度计算。" name={['global', 'gravity']} rules={numberRules} className={styles.compactItem}>
                 <InputNumber className={styles.numberInput} />
               </Form.Item>
+              <Form.Item label="自动估计重力" tooltip="校准后首段持续静止期内用实测加速度模长替代重力常数（方向仍取自校准）。" name={['global', 'auto_gravity', 'enabled']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
+              <Form.Item label="估计窗口(帧)" tooltip="需连续静止的帧数，结果超出 9.5–10.1 m/s² 会被拒绝。" name={['global', 'auto_gravity', 'window_frames']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={1} />
+              </Form.Item>
+              <Form.Item label="分片重组" tooltip="MTU 较小时一帧可能被拆到两个通知里，开启后跨通知拼接；关闭则直接丢弃截断帧。" name={['reassembly', 'enabled']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
+              <Form.Item label="分片暂存(ms)" tooltip="等待后续分片的最长时间，超时丢弃（1–1000 ms）。" name={['reassembly', 'max_hold_ms']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={1} max={1000} />
+              </Form.Item>
+              <Form.Item label="乱序重排" tooltip="按设备时间戳缓冲几帧再放行，把迟到的蓝牙包插回正确位置；会增加相应延迟。重复时间戳始终丢弃。" name={['sequencer', 'reorder']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
+              <Form.Item label="重排缓冲(帧)" tooltip="前瞻缓冲最多暂存的帧数。" name={['sequencer', 'lookahead_frames']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={0} />
+              </Form.Item>
+              <Form.Item label="重排窗口(ms)" tooltip="缓冲时间跨度上限；时间戳回退超过该值视为设备复位。" name={['sequencer', 'lookahead_ms']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={0} />
+              </Form.Item>
+              <Form.Item label="重启时重置导航" tooltip="设备重启（时间戳回到零附近）时一并清零速度与位置；关闭时轨迹从重启前的位置继续。设备配置总会自动重新下发。" name={['sequencer', 'reset_on_reboot']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
+              <Form.Item label="跳过标定" tooltip="开启后不扣除偏置、不应用标定矩阵，仅把角速度换算为 rad/s。" name={['calibration', 'passby']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
+              <Form.Item label="校准采样(ms)" tooltip="姿态零位校准前采集的数据时长，据此评估设备是否静止（100–5000 ms）。" name={['calibration_quality', 'window_ms']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={100} max={5000} />
+              </Form.Item>
+              <Form.Item label="校准最低分" tooltip="综合加速度抖动、角速度与重力偏差的 0–100 分，低于该值视为校准时设备未静止。" name={['calibration_quality', 'min_score']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={0} max={100} />
+              </Form.Item>
+              <Form.Item label="低分拒绝校准" tooltip="开启时分数不足不应用校准并提示保持静止；关闭时照常应用，仅提示分数。" name={['calibration_quality', 'reject_below_min']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
             </Card>
           </Col>
           <Col xs={24} lg={6}>
@@ -342,6 +474,12 @@ export const SettingsPanel = () => {
               <Form.Item label="平滑系数(alpha)" tooltip="低通滤波系数，越大越平滑但响应越慢。" name={['filter', 'alpha']} rules={numberRules} className={styles.compactItem}>
                 <InputNumber className={styles.numberInput} />
               </Form.Item>
+              <Form.Item label="派生信号" tooltip="计算加加速度与角加速度（jerk / ang_accel），关闭时不计算。" name={['derived', 'enabled']} valuePropName="checked">
+                <Switch />
+              </Form.Item>
+              <Form.Item label="派生平滑(alpha)" tooltip="差分结果的低通系数，差分会放大噪声，越大越平滑。" name={['derived', 'alpha']} rules={numberRules} className={styles.compactItem}>
+                <InputNumber className={styles.numberInput} min={0} max={0.99} step={0.05} />
+              </Form.Item>
             </Card>
           </Col>
           <Col xs={24} lg={12}>
@@ -386,6 +524,123 @@ export const SettingsPanel = () => {
                   </Form.Item>
                 </Col>
               </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="跳过积分间隔 max_integration_dt_ms" tooltip="帧间隔超过该值（且超过 dt_max_ms 与 3 倍采样间隔）时不积分该帧，并重置 ZUPT，避免休眠恢复后位置瞬移。" name={['trajectory', 'max_integration_dt_ms']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={1} />
+                  </Form.Item>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="加速度死区 (m/s²)" tooltip="低于该值的线加速度视为 0 再积分，抑制慢速书写等近静止动作中的噪声漂移；0 表示关闭。" name={['trajectory', 'accel_deadband_ms2']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={0} max={1} step={0.01} />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="死区模式" tooltip="按模长整体判定，或逐轴判定。" name={['trajectory', 'accel_deadband_mode']} className={styles.compactItem}>
+                    <Select
+                      options={[
+                        { label: '模长', value: 'magnitude' },
+                        { label: '逐轴', value: 'per_axis' },
+                      ]}
+                    />
+                  </Form.Item>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="首次静止定原点" tooltip="开机后位置保持为 0，直到设备首次连续静止达到设定时长，再把该处设为世界原点；拿起、放下过程不计入轨迹。" name={['auto_origin', 'enabled']} valuePropName="checked" className={styles.compactItem}>
+                    <Switch />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="静止时长(ms)" tooltip="需连续静止多久才锚定原点。" name={['auto_origin', 'static_ms']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={0} max={10000} />
+                  </Form.Item>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="气压高度辅助" tooltip="订阅设备气压计，首段静止时的气压作为参考，把轨迹高度缓慢拉向气压换算的相对高度；修改后需重新连接设备。" name={['baro', 'enabled']} valuePropName="checked" className={styles.compactItem}>
+                    <Switch />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="气压增益(1/s)" tooltip="互补滤波增益，时间常数为 1/增益 秒；越大越信任气压计。" name={['baro', 'gain']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={0.01} max={10} step={0.05} />
+                  </Form.Item>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="首段运动定航向" tooltip="启用后点击“定航向”，下一段水平位移足够的运动方向会被旋转为 +X；只旋转输出，可重复执行。" name={['heading_align', 'enabled']} valuePropName="checked" className={styles.compactItem}>
+                    <Switch />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="最小位移(m)" tooltip="水平位移小于该值的运动不用于定航向。" name={['heading_align', 'min_displacement_m']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={0.01} max={10} step={0.1} />
+                  </Form.Item>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Button onClick={handleArmHeading} disabled={!headingAlignEnabled}>
+                    定航向
+                  </Button>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="敲击同步" tooltip="在镜头前敲一下传感器：加速度短暂超过阈值即记为视频对齐点，录制中写入同步事件。" name={['sync_tap', 'enabled']} valuePropName="checked" className={styles.compactItem}>
+                    <Switch />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="敲击阈值(g)" tooltip="含重力的加速度模长超过该值、且很快回落（默认 50 ms 内）视为一次敲击。" name={['sync_tap', 'threshold_g']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={1.5} max={16} step={0.5} />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="调试向量" tooltip="输出帧附带重力参考、世界系加速度与线性加速度，用于在 3D 视图中画箭头；原地生效，不重置轨迹，也不写入录制。" name={['debug_vectors', 'enabled']} valuePropName="checked" className={styles.compactItem}>
+                    <Switch />
+                  </Form.Item>
+                </Col>
+              </Row>
+              <Row gutter={12}>
+                <Col xs={24} md={12}>
+                  <Form.Item label="平面约束" tooltip="桌面 2D 模式：每帧去掉沿重力方向的速度并把高度钳到固定值，水平运动不受影响。" name={['plane_constraint', 'mode']} rules={numberRules} className={styles.compactItem}>
+                    <Select
+                      options={[
+                        { label: '不约束', value: 'none' },
+                        { label: '水平面', value: 'horizontal' },
+                      ]}
+                    />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="平面高度 (m)" tooltip="约束平面沿重力方向的高度。" name={['plane_constraint', 'height_m']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="位置来源" tooltip="integrated=本地积分，device_offset=设备固件输出的位置，blended=运动中按权重向设备位置靠拢、静止时只信本地积分。" name={['position_source', 'mode']} rules={numberRules} className={styles.compactItem}>
+                    <Select
+                      options={[
+                        { label: '本地积分', value: 'integrated' },
+                        { label: '设备位置', value: 'device_offset' },
+                        { label: '融合', value: 'blended' },
+                      ]}
+                    />
+                  </Form.Item>
+                </Col>
+                <Col xs={24} md={12}>
+                  <Form.Item label="融合权重" tooltip="blended 模式下每帧向设备位置靠拢的比例，0 = 纯积分，1 = 运动中完全跟随设备。" name={['position_source', 'blend_weight']} rules={numberRules} className={styles.compactItem}>
+                    <InputNumber className={styles.numberInput} min={0} max={1} step={0.01} />
+                  </Form.Item>
+                </Col>
+              </Row>
               <Text type="secondary" style={{ fontSize: 12 }}>
                 当前: {integrator === 'legacy_euler' ? '旧版一阶欧拉积分' : integrator === 'rk4' ? 'RK4' : '梯形积分'}
               </Text>
@@ -448,12 +703,12 @@ export const SettingsPanel = () => {
                   </Row>
                   <Row gutter={12}>
                     <Col xs={24} sm={12} lg={5}>
-                      <Form.Item label="进入帧数" tooltip="连续满足进入阈值多少帧后切换为静止。" name={['zupt', 'enter_frames']} rules={numberRules} className={styles.compactItem}>
+                      <Form.Item label="进入时长(ms)" tooltip="连续满足进入阈值多久后切换为静止，按设备上报频率换算成帧数。" name={['zupt', 'enter_window_ms']} rules={numberRules} className={styles.compactItem}>
                         <InputNumber className={styles.numberInput} />
                       </Form.Item>
                     </Col>
                     <Col xs={24} sm={12} lg={5}>
-                      <Form.Item label="退出帧数" tooltip="连续满足退出阈值多少帧后切换为运动。" name={['zupt', 'exit_frames']} rules={numberRules} className={styles.compactItem}>
+                      <Form.Item label="退出时长(ms)" tooltip="连续满足退出阈值多久后切换为运动，按设备上报频率换算成帧数。" name={['zupt', 'exit_window_ms']} rules={numberRules} className={styles.compactItem}>
                         <InputNumber className={styles.numberInput} />
                       </Form.Item>
                     </Col>
@@ -136,6 +136,29 @@ const StatusDashboard = ({ latestRef }: { latestRef: React.RefObject<PipelineDia
         <span className={styles.statusLabel}>ZUPT 计数</span>
         <span className={styles.statusMono}>in:{snap.zupt_enter_count} out:{snap.zupt_exit_count}</span>
       </div>
+      <div className={styles.statusCard}>
+        <span className={styles.statusLabel}>时间戳</span>
+        <span className={styles.statusMono}>
+          重复:{snap.seq_duplicates} 迟到:{snap.seq_late} 重排:{snap.seq_reordered} 复位:{snap.seq_device_resets} 丢帧:{snap.seq_gaps}/{snap.seq_missing_frames}
+        </span>
+      </div>
+      <div className={styles.statusCard}>
+        <span className={styles.statusLabel}>蓝牙分片</span>
+        <span className={styles.statusMono}>拼接:{snap.ble_reassembled} 丢弃:{snap.ble_reassembly_dropped}</span>
+      </div>
+      <div className={styles.statusCard}>
+        <span className={styles.statusLabel}>输出频率</span>
+        <span className={styles.statusMono}>
+          {snap.out_subscriber_rates
+            .filter((r) => r.kind !== 'frame')
+            .map((r) => `#${r.id}:${r.rate_hz === null ? '全速' : r.rate_hz.toFixed(0)}${r.intensity === null ? '' : `(${(r.intensity * 100).toFixed(0)}%)`}`)
+            .join(' ') || '--'}
+        </span>
+      </div>
+      <div className={styles.statusCard}>
+        <span className={styles.statusLabel}>姿态归一化</span>
+        <span className={styles.statusMono}>{snap.nav_quat_renormalizations}</span>
+      </div>
       <div className={styles.statusCard}>
         <span className={(snap.accel_saturated || smooth.saturatedRate > 0.01) ? styles.ledRed : styles.ledGreen} />
         <span className={styles.statusLabel}>加速度饱和</span>
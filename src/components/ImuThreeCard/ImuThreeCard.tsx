@@ -45,9 +45,11 @@ export const ImuThreeCard: React.FC<ImuThreeCardProps> = ({ source, replayTrailR
   const [posZ, setPosZ] = useState(0);
 
   const handleCalibrateZ = async () => {
+    const hide = message.loading("请保持设备静止，正在采样评估校准质量…", 0);
     const res = await imuApi.setAxisCalibration();
+    hide();
     if (res.success) {
-      message.success("姿态已校准");
+      message.success(`姿态已校准（质量 ${res.data?.score.toFixed(0) ?? '-'} 分）`);
       // 校准后清空轨迹
       setTrailResetToken((token) => token + 1);
     } else {
@@ -55,6 +57,20 @@ export const ImuThreeCard: React.FC<ImuThreeCardProps> = ({ source, replayTrailR
     }
   };
 
+  const handleCalibrateAll = async () => {
+    const hide = message.loading("请保持设备静止，正在采样校验静止…", 0);
+    const res = await imuApi.calibrateAll();
+    hide();
+    if (res.success) {
+      message.success(
+        `水平、航向与原点已校准（静止 ${res.data?.quality.score.toFixed(0) ?? '-'} 分）`
+      );
+      setTrailResetToken((token) => token + 1);
+    } else {
+      message.error(res.message || "一键校准失败");
+    }
+  };
+
   const handleSetPosition = async () => {
     const res = await imuApi.setPosition(posX, posY, posZ);
     if (res.success) {
@@ -109,6 +125,18 @@ export const ImuThreeCard: React.FC<ImuThreeCardProps> = ({ source, replayTrailR
             </Tooltip>
           </div>
 
+          {/* 一键校准：姿态 + 航向 + 原点 */}
+          <div className={styles.imuControl}>
+            <Tooltip title={connectedDevice ? "静置设备，一次完成姿态、航向与原点校准" : "请先连接设备"}>
+              <Button
+                onClick={handleCalibrateAll}
+                disabled={!connectedDevice}
+              >
+                一键校准
+              </Button>
+            </Tooltip>
+          </div>
+
           {/* 位置校正 */}
           <div className={styles.imuControl}>
             <Tooltip
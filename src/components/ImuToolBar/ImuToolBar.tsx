@@ -30,6 +30,8 @@ type ImuToolBarProps = {
   onExitReplay: () => void;
   /** 点击”开始/停止录制”时的回调。 */
   onToggleRecording: () => void;
+  /** 点击“暂停/继续”时的回调。 */
+  onTogglePauseRecording: () => void;
   /** 设备电量（0–100），null 表示未知。 */
   batteryLevel?: number | null;
 };
@@ -45,8 +47,11 @@ export const ImuToolBar: React.FC<ImuToolBarProps> = ({
   onRestartReplay,
   onExitReplay,
   onToggleRecording,
+  onTogglePauseRecording,
   batteryLevel,
 }) => {
+  const paused = recording && (recordingStatus?.paused ?? false);
+
   const { colorScheme, toggleColorScheme } = useColorScheme();
   const { developerMode, toggleDeveloperMode } = useDeveloperMode();
   const [recordingsOpen, setRecordingsOpen] = useState(false);
@@ -110,11 +115,16 @@ export const ImuToolBar: React.FC<ImuToolBarProps> = ({
                 {recording ? "停止录制" : "开始录制"}
               </Button>
             </Tooltip>
+            <Button onClick={onTogglePauseRecording} disabled={!recording}>
+              {paused ? "继续" : "暂停"}
+            </Button>
             <Tag
-              color={recording ? "red" : "default"}
+              color={paused ? "orange" : recording ? "red" : "default"}
               className={styles.recordingStatusTag}
             >
-              {recording ? `录制中: ${recordingStatus?.session_id ?? "-"}` : "录制: 关闭"}
+              {recording
+                ? `${paused ? "已暂停" : "录制中"}: ${recordingStatus?.session_id ?? "-"}`
+                : "录制: 关闭"}
             </Tag>
           </div>
           <div className={styles.imuControl}>
@@ -1,5 +1,5 @@
 import { useCallback, useEffect, useMemo, useState } from 'react';
-import { Button, Input, message, Popconfirm, Select, Space, Table, Tooltip } from 'antd';
+import { Button, Input, message, Popconfirm, Select, Space, Table, Tag, Tooltip } from 'antd';
 import {
   DeleteOutlined,
   DownloadOutlined,
@@ -22,9 +22,9 @@ type EditState = {
 
 const formatTimestamp = (ts: number) => new Date(ts).toLocaleString();
 
-const formatDuration = (start: number, end?: number | null) => {
+const formatDuration = (start: number, end?: number | null, pausedMs = 0) => {
   if (!end) return '-';
-  const seconds = Math.max(0, Math.round((end - start) / 1000));
+  const seconds = Math.max(0, Math.round((end - start - pausedMs) / 1000));
   return `${seconds}s`;
 };
 
@@ -43,6 +43,15 @@ export const RecordingsPanel = () => {
   const [edits, setEdits] = useState<Record<number, EditState>>({});
   const [exporting, setExporting] = useState<number | null>(null);
   const [deleting, setDeleting] = useState<number | null>(null);
+  const [searchName, setSearchName] = useState('');
+  const [searchTags, setSearchTags] = useState<string[]>([]);
+
+  // 名称与标签筛选在后端完成，条件变化即重新查询
+  const applySearch = useCallback((name: string, tags: string[]) => {
+    setSearchName(name);
+    setSearchTags(tags);
+    refreshRecordings({ name: name.trim() || undefined, tags });
+  }, [refreshRecordings]);
 
   const handleExport = useCallback(async (sessionId: number) => {
     setExporting(sessionId);
@@ -109,7 +118,15 @@ export const RecordingsPanel = () => {
         title: '时长',
         key: 'duration',
         width: 56,
-        render: (_, record) => formatDuration(record.started_at_ms, record.stopped_at_ms),
+        render: (_, record) => {
+          const duration = formatDuration(record.started_at_ms, record.stopped_at_ms, record.paused_ms);
+          if (!record.interrupted) return duration;
+          return (
+            <Tooltip title="录制中断（应用异常退出），时长与样本数由已写入的样本回填">
+              <Tag color="warning">{duration}</Tag>
+            </Tooltip>
+          );
+        },
       },
       {
         title: '样本数',
@@ -223,7 +240,22 @@ export const RecordingsPanel = () => {
     <div className={styles.recordingsPanel}>
       <div className={styles.recordingsHeader}>
         <Space>
-          <Button onClick={refreshRecordings}>刷新</Button>
+          <Input.Search
+            allowClear
+            placeholder="按名称搜索"
+            defaultValue={searchName}
+            onSearch={(value) => applySearch(value, searchTags)}
+            style={{ width: 160 }}
+          />
+          <Select
+            mode="tags"
+            allowClear
+            placeholder="按标签筛选"
+            value={searchTags}
+            onChange={(tags: string[]) => applySearch(searchName, tags)}
+            style={{ minWidth: 140 }}
+          />
+          <Button onClick={() => refreshRecordings()}>刷新</Button>
           <Tooltip title="退出回放会恢复实时数据更新">
             <Button disabled={!replaying} onClick={exitReplay}>
               退出回放
@@ -29,13 +29,15 @@ export const useDiagnostics = (enabled: boolean): DiagnosticsSource => {
     latestRef.current = null;
     streamStartMsRef.current = null;
 
-    const channel = new Channel<PipelineDiagnostics>();
-    channel.onmessage = (msg) => {
-      if (streamStartMsRef.current === null) {
-        streamStartMsRef.current = msg.timestamp_ms;
+    const channel = new Channel<PipelineDiagnostics[]>();
+    channel.onmessage = (batch) => {
+      for (const msg of batch) {
+        if (streamStartMsRef.current === null) {
+          streamStartMsRef.current = msg.timestamp_ms;
+        }
+        latestRef.current = msg;
+        bufferRef.current.push(msg, streamStartMsRef.current);
       }
-      latestRef.current = msg;
-      bufferRef.current.push(msg, streamStartMsRef.current);
     };
 
     imuApi.subscribeDiagnostics(channel);
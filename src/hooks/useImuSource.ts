@@ -1,7 +1,7 @@
 import { useCallback, useEffect, useMemo, useRef } from "react";
 import { Channel } from "@tauri-apps/api/core";
-import { imuApi } from "../services/imu";
-import { ResponseData } from "../types";
+import { imuApi, unbatchOutput } from "../services/imu";
+import { OutputEvent, ResponseData, StreamMessage } from "../types";
 import { ImuHistoryBuffer } from "../utils/ImuHistoryBuffer";
 
 export type ImuSource = {
@@ -36,6 +36,11 @@ type UseImuSourceOptions = {
   onLargeDt?: (dtMs: number) => void;
 };
 
+/** 订阅时回放的历史时长（ms）。 */
+const BACKFILL_MS = 5000;
+/** 回放帧的最高频率（Hz），与图表刷新频率相当即可。 */
+const BACKFILL_MAX_RATE_HZ = 60;
+
 export const useImuSource = ({
   enabled,
   capacity,
@@ -78,13 +83,25 @@ export const useImuSource = ({
     }
 
     activeRef.current = true;
-    const channel = new Channel<ResponseData>();
-    channel.onmessage = (msg) => {
+    const handleMessage = (message: StreamMessage) => {
       if (!activeRef.current || sourceModeRef.current !== "live") {
         return;
       }
+      // 订阅时的快照：先让 3D 视图停在当前姿态，不进图表（随后的回放覆盖同一时段）
+      if ("snapshot" in message && message.snapshot) {
+        latestRef.current = message;
+        return;
+      }
+      // 静止心跳：数据与上一个完整帧相同，沿用它并更新时间戳，图表继续走时
+      const msg: ResponseData | null = "unchanged" in message
+        ? latestRef.current && { ...latestRef.current, timestamp_ms: message.timestamp_ms }
+        : message;
+      if (msg === null) {
+        return;
+      }
       const prev = prevLiveTsRef.current;
-      if (prev !== null) {
+      // skipped > 0 表示间隔来自静止抑制或回放抽取而不是丢帧，不提示
+      if (prev !== null && message.skipped === 0 && !message.backfill) {
         const dt = msg.timestamp_ms - prev;
         if (dt > largeDtThresholdMs && onLargeDt) {
           const now = Date.now();
@@ -101,13 +118,27 @@ export const useImuSource = ({
       }
       bufferRef.current.push(msg, streamStartMsRef.current);
     };
+    const channel = new Channel<OutputEvent>();
+    channel.onmessage = (event) => {
+      unbatchOutput(event).forEach(handleMessage);
+    };
 
-    imuApi.subscribeOutput(channel);
+    // 重新订阅（如页面重载）时先回放最近的历史，轨迹与图表立即有内容
+    const subscription = imuApi.subscribeOutput(channel, {
+      include_snapshot: true,
+      backfill_ms: BACKFILL_MS,
+      max_rate_hz: BACKFILL_MAX_RATE_HZ,
+    });
 
     return () => {
       activeRef.current = false;
       cancelReplayFrame();
       channel.onmessage = () => { };
+      subscription.then((res) => {
+        if (res.success && res.data !== undefined) {
+          imuApi.unsubscribeOutput(res.data);
+        }
+      });
     };
   }, [cancelReplayFrame, enabled]);
 
@@ -49,6 +49,7 @@ export const ImuRealtimePanel = ({
     restartReplay,
     exitReplay,
     toggleRecording,
+    togglePauseRecording,
     batteryLevel,
   } = useBluetooth();
   /** 跟踪图表区域是否折叠。 */
@@ -221,6 +222,7 @@ export const ImuRealtimePanel = ({
             onRestartReplay={restartReplay}
             onExitReplay={exitReplay}
             onToggleRecording={toggleRecording}
+            onTogglePauseRecording={togglePauseRecording}
             batteryLevel={batteryLevel}
           />
         </Card>
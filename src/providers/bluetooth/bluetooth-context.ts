@@ -1,9 +1,11 @@
 import { createContext } from 'react';
 
 import type {
+  ConfigApplyMode,
   PeripheralInfo,
   ProcessorPipelineConfig,
   RecordingMeta,
+  RecordingQuery,
   RecordingStatus,
   ResponseData,
 } from '../../types';
@@ -34,8 +36,8 @@ export type BluetoothContextValue = {
   replaySessionId: number | null;
   /** 回放版本号，用于触发重新回放。 */
   replayVersion: number;
-  /** 刷新录制列表。 */
-  refreshRecordings: () => Promise<void>;
+  /** 刷新录制列表；传入条件时替换当前筛选，省略时沿用上一次的条件。 */
+  refreshRecordings: (query?: RecordingQuery) => Promise<void>;
   /** 更新录制元信息（名称、标签）。 */
   updateRecordingMeta: (sessionId: number, name?: string, tags?: string[]) => Promise<void>;
   /** 加载指定会话的回放数据。 */
@@ -56,10 +58,14 @@ export type BluetoothContextValue = {
   disconnect: () => Promise<void>;
   /** 切换录制状态（开始/停止）。 */
   toggleRecording: () => Promise<void>;
+  /** 切换录制暂停状态（暂停/恢复），不结束当前会话。 */
+  togglePauseRecording: () => Promise<void>;
   /** 获取当前流水线配置。 */
   getPipelineConfig: () => Promise<ProcessorPipelineConfig | null>;
   /** 更新流水线配置并立即生效。 */
   updatePipelineConfig: (config: ProcessorPipelineConfig) => Promise<boolean>;
+  /** 增量应用流水线配置，返回实际生效方式；失败返回 null。 */
+  patchPipelineConfig: (config: ProcessorPipelineConfig) => Promise<ConfigApplyMode | null>;
   /** 将当前生效配置保存到配置文件。 */
   savePipelineConfig: () => Promise<boolean>;
   /** 是否需要显示标定向导（首次连接未标定设备时为 true）。 */
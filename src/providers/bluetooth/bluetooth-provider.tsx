@@ -7,8 +7,19 @@ import {
   ProcessorPipelineConfig,
   ResponseData,
   RecordingMeta,
+  RecordingQuery,
   RecordingStatus,
+  RecordingStopped,
+  DataStall,
+  PipelineChannelClosed,
+  StreamIdleState,
+  ConfigInvalid,
+  DeviceReset,
+  QuatFreezeEvent,
+  AccelInconsistencyEvent,
+  HeadingAligned,
 } from '../../types';
+import { RESPONSE_SCHEMA_VERSION } from '../../utils/ResponseSchema';
 import { BluetoothContext, type BluetoothContextValue, type DataMode } from './bluetooth-context';
 
 const useBluetoothInternal = (): BluetoothContextValue => {
@@ -72,6 +83,18 @@ const useBluetoothInternal = (): BluetoothContextValue => {
     return () => clearInterval(timer);
   }, [connectedDevice]);
 
+  // 检查前后端 ResponseData 结构版本是否一致（混合版本开发时字段可能缺失）
+  useEffect(() => {
+    imuApi.getSchemaVersion()
+      .then((res) => {
+        if (res.success && res.data != null && res.data !== RESPONSE_SCHEMA_VERSION) {
+          console.warn(`ResponseData 结构版本不一致：后端 ${res.data}，前端 ${RESPONSE_SCHEMA_VERSION}`);
+          message.warning(`前后端数据结构版本不一致（后端 ${res.data}，前端 ${RESPONSE_SCHEMA_VERSION}），部分字段可能缺失`);
+        }
+      })
+      .catch((e) => console.error(e));
+  }, []);
+
   // 监听配置更新事件
   useEffect(() => {
     let unlisten: UnlistenFn | null = null;
@@ -92,6 +115,249 @@ const useBluetoothInternal = (): BluetoothContextValue => {
     };
   }, []);
 
+  // 监听 processor.toml 校验失败
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<ConfigInvalid>('config_invalid', (event) => {
+          const { stage, errors } = event.payload;
+          const fallback = stage === 'startup' ? '已使用默认配置' : '保留当前配置';
+          const details = errors.map((e) => `${e.path}: ${e.message}`).join('；');
+          message.warning({
+            content: `processor.toml 校验失败，${fallback}。${details}`,
+            duration: 10,
+          });
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 监听数据流停滞 / 恢复（设备保持连接但停止上报）
+  useEffect(() => {
+    const unlisteners: UnlistenFn[] = [];
+    const stageLabel = (stage: DataStall['stage']) => (stage === 'input' ? '设备数据' : '处理输出');
+    const setupListener = async () => {
+      try {
+        unlisteners.push(await listen<DataStall>('data_stalled', (event) => {
+          const { stage, stalled_ms } = event.payload;
+          message.warning({
+            key: 'data_stall',
+            content: `${stageLabel(stage)}已停滞 ${(stalled_ms / 1000).toFixed(1)} s，正在尝试恢复上报`,
+            duration: 0,
+          });
+        }));
+        unlisteners.push(await listen<DataStall>('data_resumed', (event) => {
+          const { stage, stalled_ms } = event.payload;
+          message.success({
+            key: 'data_stall',
+            content: `${stageLabel(stage)}已恢复（中断 ${(stalled_ms / 1000).toFixed(1)} s）`,
+          });
+        }));
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, []);
+
+  // 监听上游通道关闭（处理管线已退出，设备已停止上报）
+  useEffect(() => {
+    let unlisten: UnlistenFn | undefined;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<PipelineChannelClosed>('pipeline_channel_closed', (event) => {
+          const { forwarded_frames, uptime_ms } = event.payload;
+          message.error({
+            key: 'pipeline_channel_closed',
+            content: `处理管线已停止（已接收 ${forwarded_frames} 帧，运行 ${(uptime_ms / 1000).toFixed(1)} s），请重新连接设备`,
+            duration: 0,
+          });
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 监听空闲模式（无人订阅且未录制时设备停止上报）
+  useEffect(() => {
+    const unlisteners: UnlistenFn[] = [];
+    const setupListener = async () => {
+      try {
+        unlisteners.push(await listen<StreamIdleState>('stream_idle', () => {
+          message.info({
+            key: 'stream_idle',
+            content: '无人使用数据，设备已暂停上报以省电',
+            duration: 0,
+          });
+        }));
+        unlisteners.push(await listen<StreamIdleState>('stream_active', () => {
+          message.destroy('stream_idle');
+        }));
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, []);
+
+  // 监听设备重启：后端已自动重新下发设备配置
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<DeviceReset>('device_rebooted', (event) => {
+          const { previous_timestamp_ms } = event.payload;
+          message.warning(`设备已重启（运行 ${(previous_timestamp_ms / 1000).toFixed(0)} s 后），已重新下发设备配置`);
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 监听设备四元数冻结：冻结期间后端以陀螺积分姿态代替
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<QuatFreezeEvent>('quat_freeze', (event) => {
+          if (event.payload.frozen) {
+            message.warning({
+              key: 'quat_freeze',
+              content: '设备姿态输出停止更新，已暂用陀螺积分姿态',
+              duration: 0,
+            });
+          } else {
+            message.destroy('quat_freeze');
+          }
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 监听加速度来源交叉检查（accel_source.mode = auto）：两路加速度持续不一致时提示
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<AccelInconsistencyEvent>('accel_inconsistent', (event) => {
+          if (event.payload.inconsistent) {
+            message.warning({
+              key: 'accel_inconsistent',
+              content: `含重力与去重力加速度不一致（相差 ${event.payload.residual_ms2.toFixed(2)} m/s²）`,
+              duration: 0,
+            });
+          } else {
+            message.destroy('accel_inconsistent');
+          }
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 监听首段运动定航向完成
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<HeadingAligned>('heading_aligned', (event) => {
+          message.success(
+            `航向已对齐：旋转 ${event.payload.yaw_deg.toFixed(1)}°（位移 ${event.payload.displacement_m.toFixed(2)} m）`
+          );
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 停滞与空闲提示在断开连接后不再有意义
+  useEffect(() => {
+    if (!connectedDevice) {
+      message.destroy('data_stall');
+      message.destroy('stream_idle');
+      message.destroy('quat_freeze');
+      message.destroy('accel_inconsistent');
+    }
+  }, [connectedDevice]);
+
+  // 监听后端主动结束录制（如磁盘空间不足）
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<RecordingStopped>('recording_stopped', (event) => {
+          const { reason, sample_count } = event.payload;
+          setRecording(false);
+          setRecordingStatus(null);
+          if (reason === 'disk_full') {
+            message.warning(`磁盘剩余空间不足，录制已自动停止（${sample_count} 条数据）`);
+          } else {
+            message.info(`录制已停止（${sample_count} 条数据）`);
+          }
+        });
+      } catch (e) {
+        console.error(e);
+      }
+    };
+    setupListener();
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
   useEffect(() => {
     let interval: number;
     if (scanning) {
@@ -99,6 +365,7 @@ const useBluetoothInternal = (): BluetoothContextValue => {
         try {
           const response = await imuApi.listPeripherals();
           setDevices(response.data || []);
+          response.warnings?.forEach((warning) => console.warn(warning));
         } catch (e) {
           console.error(e);
         }
@@ -259,7 +526,7 @@ const useBluetoothInternal = (): BluetoothContextValue => {
       }
     } catch (e) {
       console.error(e);
-      message.error('开始录制失败');
+      message.error(`开始录制失败：${e instanceof Error ? e.message : String(e)}`);
     }
   }, []);
 
@@ -290,6 +557,22 @@ const useBluetoothInternal = (): BluetoothContextValue => {
     }
   }, [recording, startRecording, stopRecording]);
 
+  // 切换暂停状态：暂停期间会话保持打开，恢复后继续写入同一会话
+  const togglePauseRecording = useCallback(async () => {
+    const paused = recordingStatus?.paused ?? false;
+    try {
+      const res = paused ? await imuApi.resumeRecording() : await imuApi.pauseRecording();
+      if (res.success && res.data) {
+        setRecordingStatus(res.data);
+      } else {
+        throw new Error(res.message || '未知错误');
+      }
+    } catch (e) {
+      console.error(e);
+      message.error(`${paused ? '恢复' : '暂停'}录制失败：${e instanceof Error ? e.message : String(e)}`);
+    }
+  }, [recordingStatus]);
+
   // 获取当前 pipeline 配置
   const getPipelineConfig = useCallback(async () => {
     try {
@@ -320,6 +603,21 @@ const useBluetoothInternal = (): BluetoothContextValue => {
     }
   }, []);
 
+  // 增量应用 pipeline 配置：可调参数不重置轨迹
+  const patchPipelineConfig = useCallback(async (config: ProcessorPipelineConfig) => {
+    try {
+      const res = await imuApi.patchPipelineConfig(config);
+      if (!res.success || !res.data) {
+        throw new Error(res.message || '未知错误');
+      }
+      return res.data;
+    } catch (e) {
+      console.error(e);
+      message.error('更新流水线配置失败');
+      return null;
+    }
+  }, []);
+
   // 保存当前生效配置到 processor.toml
   const savePipelineConfig = useCallback(async () => {
     try {
@@ -337,11 +635,16 @@ const useBluetoothInternal = (): BluetoothContextValue => {
   }, []);
 
   // 刷新录制列表
-  const refreshRecordings = useCallback(async () => {
+  // 记住最近一次的筛选条件，删除/改名后的刷新沿用
+  const recordingQueryRef = useRef<RecordingQuery | undefined>(undefined);
+  const refreshRecordings = useCallback(async (query?: RecordingQuery) => {
+    if (query !== undefined) {
+      recordingQueryRef.current = query;
+    }
     try {
-      const res = await imuApi.listRecordings();
+      const res = await imuApi.listRecordings(recordingQueryRef.current);
       if (res.success && res.data) {
-        setRecordings(res.data);
+        setRecordings(res.data.items);
       } else {
         throw new Error(res.message || '未知错误');
       }
@@ -371,7 +674,7 @@ const useBluetoothInternal = (): BluetoothContextValue => {
   // 加载并回放录制的数据
   const loadRecording = useCallback(async (sessionId: number) => {
     try {
-      const res = await imuApi.getRecordingSamples(sessionId);
+      const res = await imuApi.getRecordingSamplesBinary(sessionId);
       if (!res.success || !res.data) {
         throw new Error(res.message || '未知错误');
       }
@@ -387,7 +690,7 @@ const useBluetoothInternal = (): BluetoothContextValue => {
       message.success('录制数据已加载');
     } catch (e) {
       console.error(e);
-      message.error('加载录制数据失败');
+      message.error(`加载录制数据失败：${e instanceof Error ? e.message : String(e)}`);
     }
   }, [enterReplayMode]);
 
@@ -443,8 +746,10 @@ const useBluetoothInternal = (): BluetoothContextValue => {
     connect,
     disconnect,
     toggleRecording,
+    togglePauseRecording,
     getPipelineConfig,
     updatePipelineConfig,
+    patchPipelineConfig,
     savePipelineConfig,
     needsCalibration,
     setNeedsCalibration,
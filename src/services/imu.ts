@@ -1,70 +1,235 @@
 import { invoke, Channel } from "@tauri-apps/api/core";
 import {
+  AppStatus,
+  AttitudeFrame,
+  BluetoothAdapterInfo,
+  BluetoothStatus,
+  CalibrationQuality,
+  CalibrationWizardSnapshot,
+  ClockSyncEstimate,
+  ComparisonReport,
+  ConfigApplyMode,
   PeripheralInfo,
+  PlaybackMode,
+  PlaybackStatus,
   PipelineDiagnostics,
   ProcessorPipelineConfig,
   ResponseData,
+  StreamMessage,
+  OutputEvent,
+  OutputSubscribeOptions,
+  RecordingFlag,
+  RecordingMarker,
+  RecordingPause,
+  RecordingSegment,
+  RecordingStageSample,
+  RecordingSyncEvent,
   RecordingMeta,
+  RecordingPage,
+  RecordingQuery,
+  RecordingStatistics,
   RecordingStatus,
+  DatabaseIntegrity,
   DeviceCalibrationData,
+  DeviceProfile,
+  ErrorCode,
+  FaultInjectionConfig,
+  FaultInjectionStatus,
+  FrameDisplay,
+  FullCalibration,
+  ImuSampleRaw,
+  SampleExplanation,
+  SelfTestReport,
+  SmoothedTrajectory,
+  TransformedTrajectory,
+  UnitPreferences,
 } from "../types";
+import { decodeOutputEvent } from "../utils/OutputCbor";
+import { decodeRecordingSamples } from "../utils/RecordingSampleBinary";
+import { upgradeResponseData } from "../utils/ResponseSchema";
 
 // 通用 API 响应接口
+// 批量操作部分成功时 success 仍为 true，data 为已完成的部分，跳过的条目列在 warnings 中
 export interface imuApiResponse<T> {
   success: boolean;
   data?: T;
   message?: string;
+  warnings?: string[];
+  code?: ErrorCode; // 部分失败原因提供结构化错误码
 }
 
+// 把输出订阅的一条通道消息展开为按顺序排列的消息（攒批或单条）
+// encoding: 'cbor' 的订阅收到 ArrayBuffer，先解码为同样结构的 OutputEvent
+export const unbatchOutput = (raw: OutputEvent | ArrayBuffer): StreamMessage[] => {
+  const event = raw instanceof ArrayBuffer ? decodeOutputEvent(raw) : raw;
+  return "output_batch" in event ? event.output_batch : [event];
+};
+
 // IMU 服务 API，封装了与 Tauri 后端的通信
 export const imuApi = {
   // 启动蓝牙扫描
   startScan: () => invoke<void>("start_scan"),
   // 停止蓝牙扫描
   stopScan: () => invoke<void>("stop_scan"),
-  // 获取扫描到的外设列表
-  listPeripherals: () => invoke<imuApiResponse<PeripheralInfo[]>>("list_peripherals"),
-  // 连接指定外设
-  connect: (targetUuid: string) => invoke<imuApiResponse<PeripheralInfo>>("connect_peripheral", { targetUuid }),
+  // 获取扫描到的外设列表，只含 maxAgeMs（默认 30 s）内被发现过的设备与当前连接的设备
+  listPeripherals: (maxAgeMs?: number) =>
+    invoke<imuApiResponse<PeripheralInfo[]>>("list_peripherals", { maxAgeMs }),
+  // 列出本机蓝牙适配器
+  listBluetoothAdapters: () =>
+    invoke<imuApiResponse<BluetoothAdapterInfo[]>>("list_bluetooth_adapters"),
+  // 指定之后扫描与连接使用的蓝牙适配器（须先断开设备）
+  selectBluetoothAdapter: (id: string) =>
+    invoke<imuApiResponse<BluetoothAdapterInfo>>("select_bluetooth_adapter", { id }),
+  // 蓝牙可用性诊断
+  getBluetoothStatus: () => invoke<imuApiResponse<BluetoothStatus>>("get_bluetooth_status"),
+  // 连接指定外设；连接中断、超时等瞬时错误自动重试，maxAttempts 缺省 3 次
+  connect: (targetUuid: string, maxAttempts?: number) =>
+    invoke<imuApiResponse<PeripheralInfo>>("connect_peripheral", { targetUuid, maxAttempts }),
   // 断开当前连接
   disconnect: () => invoke<imuApiResponse<PeripheralInfo>>("disconnect_peripheral"),
   // 设置姿态矫正值（按当前姿态作为零位，由后端读取最新姿态）
-  setAxisCalibration: () => invoke<imuApiResponse<void>>("set_axis_calibration"),
+  // 先采集约 1 s 数据评分，低分被拒绝时 success = false，data 仍带评分
+  setAxisCalibration: () => invoke<imuApiResponse<CalibrationQuality>>("set_axis_calibration"),
+  // 一次性完成水平、航向与原点校准，未通过静止校验时什么都不改
+  calibrateAll: () => invoke<imuApiResponse<FullCalibration>>("calibrate_all"),
   // 设置位置（手动校正）
   setPosition: (x: number, y: number, z: number) =>
     invoke<imuApiResponse<void>>("set_position", { x, y, z }),
+  // 重新等待下一段持续静止来锚定世界系原点（期间位置固定为零）
+  reAnchorOrigin: () => invoke<imuApiResponse<void>>("re_anchor_origin"),
+  // 把下一段足够长的运动方向对齐为输出 +X（需启用 heading_align，可重复调用）
+  armHeadingFromMotion: () => invoke<imuApiResponse<void>>("arm_heading_from_motion"),
+  // 标定向导当前状态（零位校准、一次性校准、航向对齐），变化经 calibration_state_changed 事件推送
+  getCalibrationState: () =>
+    invoke<imuApiResponse<CalibrationWizardSnapshot>>("get_calibration_state"),
   // 获取当前 pipeline 配置
   getPipelineConfig: () =>
     invoke<imuApiResponse<ProcessorPipelineConfig>>("get_pipeline_config"),
   // 更新 pipeline 配置（实时生效）
   updatePipelineConfig: (config: ProcessorPipelineConfig) =>
     invoke<imuApiResponse<void>>("update_pipeline_config", { config }),
+  // 增量应用 pipeline 配置（阈值类参数不重置轨迹），返回实际生效方式
+  patchPipelineConfig: (config: ProcessorPipelineConfig) =>
+    invoke<imuApiResponse<ConfigApplyMode>>("patch_pipeline_config", { config }),
   // 将当前生效 pipeline 配置写入 processor.toml
   savePipelineConfig: () =>
     invoke<imuApiResponse<void>>("save_pipeline_config"),
+  // 暂停 / 恢复导航：轨迹冻结，原始数据照常推送；data 为状态是否改变
+  pauseProcessing: () =>
+    invoke<imuApiResponse<boolean>>("pause_processing"),
+  resumeProcessing: () =>
+    invoke<imuApiResponse<boolean>>("resume_processing"),
 
   // 订阅数据输出
   // onEvent: Tauri Channel，用于接收实时数据流
-  subscribeOutput: (onEvent: Channel<ResponseData>) =>
-    invoke("subscribe_output", { onEvent }),
+  // 启用静止抑制时，静止期间只收到心跳（StreamHeartbeat）
+  // options 可要求先收到快照与近期历史回放（snapshot / backfill 标记），再接实时消息
+  // 每个订阅有独立队列，返回订阅编号，组件卸载时交给 unsubscribeOutput
+  // 默认按批发送，用 unbatchOutput 展开；options.batching = false 时逐条发送
+  // options.encoding = 'cbor' 时通道收到 ArrayBuffer（解码后结构不变），序列化开销与体积更小
+  subscribeOutput: (
+    onEvent: Channel<OutputEvent> | Channel<ArrayBuffer>,
+    options?: OutputSubscribeOptions,
+  ) =>
+    invoke<imuApiResponse<number>>("subscribe_output", { onEvent, options }),
+  // 订阅高频姿态流（3D 姿态视图），频率不超过 maxRateHz，返回订阅编号
+  subscribeAttitude: (maxRateHz: number, onEvent: Channel<AttitudeFrame>) =>
+    invoke<imuApiResponse<number>>("subscribe_attitude", { maxRateHz, onEvent }),
+  // 注销输出订阅或姿态流订阅
+  unsubscribeOutput: (id: number) =>
+    invoke<imuApiResponse<boolean>>("unsubscribe_output", { id }),
 
-  // 订阅管线诊断数据流（开发者模式）
-  subscribeDiagnostics: (onEvent: Channel<PipelineDiagnostics>) =>
+  // 获取最新一帧（无需订阅，尚无数据时 data 为 null）
+  getLatestFrame: () =>
+    invoke<imuApiResponse<ResponseData | null>>("get_latest_frame"),
+  // 最新一帧距今毫秒数，用于判断数据是否过期
+  getLatestFrameAgeMs: () =>
+    invoke<imuApiResponse<number | null>>("get_latest_frame_age_ms"),
+  // 按单位偏好格式化好的最新一帧，供叠加层直接显示
+  getLatestFrameDisplay: () =>
+    invoke<imuApiResponse<FrameDisplay | null>>("get_latest_frame_display"),
+  // 获取单位偏好
+  getUnitPreferences: () =>
+    invoke<imuApiResponse<UnitPreferences>>("get_unit_preferences"),
+  // 设置单位偏好（持久化，导出与展示立即生效）
+  setUnitPreferences: (preferences: UnitPreferences) =>
+    invoke<imuApiResponse<UnitPreferences>>("set_unit_preferences", { preferences }),
+  // 后端 ResponseData 结构版本，与前端 RESPONSE_SCHEMA_VERSION 比对
+  getSchemaVersion: () => invoke<imuApiResponse<number>>("get_schema_version"),
+
+  // 订阅管线诊断数据流（开发者模式），每条消息是按帧顺序攒批的数组
+  subscribeDiagnostics: (onEvent: Channel<PipelineDiagnostics[]>) =>
     invoke("subscribe_diagnostics", { onEvent }),
+  // 在实时管线状态的副本上演算一个样本，返回各阶段输入/输出/参数（仅 debug 构建）
+  explainSample: (sample: ImuSampleRaw) =>
+    invoke<imuApiResponse<SampleExplanation>>("explain_sample", { sample }),
+  // 设备/主机时钟同步结果（偏移、频偏），尚未建立时为 null
+  getClockSync: () =>
+    invoke<imuApiResponse<ClockSyncEstimate | null>>("get_clock_sync"),
 
   // 开始录制数据
-  startRecording: (options?: { name?: string; tags?: string[] }) =>
+  // max_duration_min / max_samples：任一达到即自动分段，新段名称追加 " (n)"
+  // deep：深度录制，同时写入各阶段中间值（体积约翻倍）
+  startRecording: (options?: {
+    name?: string;
+    tags?: string[];
+    max_duration_min?: number;
+    max_samples?: number;
+    deep?: boolean;
+  }) =>
     invoke<imuApiResponse<RecordingStatus>>("start_recording", { options }),
   // 停止录制
   stopRecording: () => invoke<imuApiResponse<RecordingStatus>>("stop_recording"),
-  // 获取录制列表
-  listRecordings: () => invoke<imuApiResponse<RecordingMeta[]>>("list_recordings"),
-  // 更新录制元数据（名称、标签）
-  updateRecordingMeta: (sessionId: number, name?: string, tags?: string[]) =>
-    invoke<imuApiResponse<RecordingMeta>>("update_recording_meta", { sessionId, name, tags }),
+  // 暂停录制：会话保持打开，暂停期间的帧被丢弃
+  pauseRecording: () => invoke<imuApiResponse<RecordingStatus>>("pause_recording"),
+  // 恢复录制，继续写入同一会话
+  resumeRecording: () => invoke<imuApiResponse<RecordingStatus>>("resume_recording"),
+  // 按条件分页获取录制列表，省略条件时返回全部
+  listRecordings: (query?: RecordingQuery) =>
+    invoke<imuApiResponse<RecordingPage>>("list_recordings", { query }),
+  // 全部录制的汇总统计，后端缓存几秒
+  getRecordingStatistics: () =>
+    invoke<imuApiResponse<RecordingStatistics>>("get_recording_statistics"),
+  // 修复未正常结束的录制，返回修复数量
+  repairRecordings: () => invoke<imuApiResponse<number>>("repair_recordings"),
+  // 更新录制元数据（名称、标签、审阅备注）；不传 notes 时保留原备注，空白文本清除
+  updateRecordingMeta: (sessionId: number, name?: string, tags?: string[], notes?: string) =>
+    invoke<imuApiResponse<RecordingMeta>>("update_recording_meta", { sessionId, name, tags, notes }),
   // 获取指定录制的样本数据
-  getRecordingSamples: (sessionId: number) =>
-    invoke<imuApiResponse<ResponseData[]>>("get_recording_samples", { sessionId }),
+  getRecordingSamples: async (sessionId: number): Promise<imuApiResponse<ResponseData[]>> => {
+    const res = await invoke<imuApiResponse<ResponseData[]>>("get_recording_samples", { sessionId });
+    return res.data ? { ...res, data: res.data.map(upgradeResponseData) } : res;
+  },
+  // 以二进制批量获取录制样本（长录制加载更快），可按设备时间戳区间截取
+  getRecordingSamplesBinary: async (
+    sessionId: number,
+    fromMs?: number,
+    toMs?: number,
+  ): Promise<imuApiResponse<ResponseData[]>> => {
+    try {
+      const buffer = await invoke<ArrayBuffer>("get_recording_samples_binary", { sessionId, fromMs, toMs });
+      return { success: true, data: decodeRecordingSamples(buffer) };
+    } catch (e) {
+      return { success: false, message: e instanceof Error ? e.message : String(e) };
+    }
+  },
+  // 获取深度录制的阶段中间值，可按设备时间戳区间截取
+  getStageSamples: (sessionId: number, fromMs?: number, toMs?: number) =>
+    invoke<imuApiResponse<RecordingStageSample[]>>("get_stage_samples", { sessionId, fromMs, toMs }),
+  // 按设备时间戳对齐两段录制并计算对比指标
+  compareRecordings: (sessionA: number, sessionB: number) =>
+    invoke<imuApiResponse<ComparisonReport>>("compare_recordings", { sessionA, sessionB }),
+  // 用当前配置重跑录制并离线平滑轨迹，write 为真时写回样本表
+  smoothRecording: (sessionId: number, write?: boolean) =>
+    invoke<imuApiResponse<SmoothedTrajectory>>("smooth_recording", { sessionId, write }),
+
+  // 把录制轨迹换算到另一会话的标定世界系；write 为真时写入 transformed_* 列
+  transformRecording: (sessionId: number, targetSessionId: number, write?: boolean) =>
+    invoke<imuApiResponse<TransformedTrajectory>>("transform_recording", {
+      sessionId,
+      targetSessionId,
+      write,
+    }),
 
   // 保存设备标定结果到 SQLite
   saveDeviceCalibration: (
@@ -86,15 +251,142 @@ export const imuApi = {
   getDeviceCalibration: (deviceId: string) =>
     invoke<imuApiResponse<DeviceCalibrationData | null>>("get_device_calibration", { deviceId }),
 
-  // 将指定会话导出为 CSV，返回导出文件的绝对路径
-  exportSessionCsv: (sessionId: number) =>
-    invoke<imuApiResponse<string>>("export_session_csv", { sessionId }),
+  // 以当前生效的标定配置与姿态零位保存已连接设备的档案
+  saveDeviceProfile: () => invoke<imuApiResponse<DeviceProfile>>("save_device_profile"),
+
+  // 查询设备档案
+  getDeviceProfile: (deviceId: string) =>
+    invoke<imuApiResponse<DeviceProfile | null>>("get_device_profile", { deviceId }),
+
+  // 删除设备档案，返回档案是否存在
+  deleteDeviceProfile: (deviceId: string) =>
+    invoke<imuApiResponse<boolean>>("delete_device_profile", { deviceId }),
+
+  // 将指定会话导出为 CSV，返回导出文件的绝对路径；includeFlags 时追加 flags 列
+  exportSessionCsv: (sessionId: number, includeFlags?: boolean) =>
+    invoke<imuApiResponse<string>>("export_session_csv", { sessionId, includeFlags }),
+
+  // 导出会话为 CSV 到指定目录，文件名由会话名称清洗得到（重名追加序号），返回文件路径
+  exportRecordingCsvToDir: (sessionId: number, dir: string, includeFlags?: boolean) =>
+    invoke<imuApiResponse<string>>("export_recording_csv_to_dir", { sessionId, dir, includeFlags }),
+
+  // 导出会话为 Parquet（需以 parquet feature 构建，否则返回 NotSupported）；path 为目录时按会话名称命名，返回文件路径
+  exportRecordingParquet: (sessionId: number, path: string) =>
+    invoke<imuApiResponse<string>>("export_recording_parquet", { sessionId, path }),
+
+  // 取消会话正在进行的导出（进度见 export_progress 事件），返回是否有导出在进行
+  cancelExport: (sessionId: number) =>
+    invoke<imuApiResponse<boolean>>("cancel_export", { sessionId }),
+
+  // 把飞行记录仪保留的最近一段数据写成新录制（无需事先开始录制）
+  dumpFlightRecorder: (name?: string) =>
+    invoke<imuApiResponse<RecordingMeta>>("dump_flight_recorder", { name }),
+
+  // 在当前录制中插入标记（未录制时返回错误）
+  addRecordingMarker: (label: string) =>
+    invoke<imuApiResponse<RecordingMarker>>("add_recording_marker", { label }),
+  // 在当前录制中记录同步脉冲（主机时间 + 设备时间 + 时钟同步），用于对齐外部视频
+  recordSyncPulse: (source: string) =>
+    invoke<imuApiResponse<RecordingSyncEvent>>("record_sync_pulse", { source }),
+  // 获取指定录制的同步事件（手动脉冲与自动敲击）
+  getSyncEvents: (sessionId: number) =>
+    invoke<imuApiResponse<RecordingSyncEvent[]>>("get_sync_events", { sessionId }),
+  // 获取指定录制的标记列表
+  getRecordingMarkers: (sessionId: number) =>
+    invoke<imuApiResponse<RecordingMarker[]>>("get_recording_markers", { sessionId }),
+  // 给录制中 [fromMs, toMs]（设备时间戳）的样本打上数据质量标记，区间须在会话时间范围内
+  flagSampleRange: (sessionId: number, fromMs: number, toMs: number, label: string) =>
+    invoke<imuApiResponse<RecordingFlag>>("flag_sample_range", { sessionId, fromMs, toMs, label }),
+  // 获取指定录制的数据质量标记，按区间起点升序
+  getRecordingFlags: (sessionId: number) =>
+    invoke<imuApiResponse<RecordingFlag[]>>("get_recording_flags", { sessionId }),
+  // 删除一条数据质量标记
+  deleteRecordingFlag: (flagId: number) =>
+    invoke<imuApiResponse<void>>("delete_recording_flag", { flagId }),
+  // 获取指定录制的暂停区间
+  getRecordingPauses: (sessionId: number) =>
+    invoke<imuApiResponse<RecordingPause[]>>("get_recording_pauses", { sessionId }),
+  // 获取指定录制中检测到的运动分段
+  getRecordingSegments: (sessionId: number) =>
+    invoke<imuApiResponse<RecordingSegment[]>>("get_recording_segments", { sessionId }),
 
   // 删除指定录制会话及其所有样本数据
   deleteRecording: (sessionId: number) =>
     invoke<imuApiResponse<void>>("delete_recording", { sessionId }),
+  // 批量删除录制，返回已删除的会话 ID（部分失败时见 warnings）
+  deleteRecordings: (sessionIds: number[]) =>
+    invoke<imuApiResponse<number[]>>("delete_recordings", { sessionIds }),
+  // 导入轨迹 CSV（export_session_csv 格式）为新录制，跳过的行见 warnings
+  importSessionCsv: (path: string, name?: string) =>
+    invoke<imuApiResponse<RecordingMeta>>("import_session_csv", { path, name }),
+
+  // 检查录制数据库完整性与表结构版本（不做迁移）
+  verifyRecordingsDatabase: () =>
+    invoke<imuApiResponse<DatabaseIntegrity>>("verify_recordings_database"),
+  // 获取当前录制目录
+  getRecordingDirectory: () =>
+    invoke<imuApiResponse<string>>("get_recording_directory"),
+  // 获取默认录制目录（未自定义时使用）
+  getDefaultRecordingDirectory: () =>
+    invoke<imuApiResponse<string>>("get_default_recording_directory"),
+  // 设置录制目录（校验可写并持久化，已有录制不迁移）
+  setRecordingDirectory: (path: string) =>
+    invoke<imuApiResponse<string>>("set_recording_directory", { path }),
 
   // 读取已连接设备的电量（0–100）
   getBatteryLevel: () =>
     invoke<imuApiResponse<number>>("get_battery_level"),
+
+  // 设置设备上报频率 (Hz)，已连接时立即写入设备
+  setReportRate: (reportRate: number) =>
+    invoke<imuApiResponse<void>>("set_report_rate", { reportRate }),
+
+  // 原样写入一条原始设备命令（1 ~ 20 字节），需配置 unsafe_device_commands 或 debug 构建
+  writeDeviceCommand: (bytes: number[]) =>
+    invoke<imuApiResponse<void>>("write_device_command", { bytes }),
+
+  // 读取上一条原始设备命令的回复（十六进制字符串），超时返回失败
+  readDeviceResponse: (timeoutMs: number) =>
+    invoke<imuApiResponse<string>>("read_device_response", { timeoutMs }),
+
+  // 运行时调整日志级别（target 为空时调整默认级别），重启后恢复 logging.toml
+  setLogLevel: (target: string, level: 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace') =>
+    invoke<imuApiResponse<null>>("set_log_level", { target, level }),
+
+  // 获取连接、录制与处理状态快照（前端重载后恢复界面用）
+  getAppStatus: () =>
+    invoke<imuApiResponse<AppStatus>>("get_app_status"),
+  // 设置数据流停滞阈值（ms，至少 100），超过即推送 data_stalled
+  setStallThreshold: (thresholdMs: number) =>
+    invoke<imuApiResponse<null>>("set_stall_threshold", { thresholdMs }),
+  // 设置随机故障注入（仅开发构建，否则返回 not_supported），返回生效状态，关闭时为 null
+  setFaultInjection: (config: Partial<FaultInjectionConfig>) =>
+    invoke<imuApiResponse<FaultInjectionStatus | null>>("set_fault_injection", { config }),
+  // 生效中的故障注入配置与注入次数
+  getFaultInjection: () =>
+    invoke<imuApiResponse<FaultInjectionStatus | null>>("get_fault_injection"),
+
+  // 加载录制会话用于回放（暂停在首帧），帧经输出订阅送达；mode 默认 stored
+  playbackLoad: (sessionId: number, mode?: PlaybackMode) =>
+    invoke<imuApiResponse<PlaybackStatus>>("playback_load", { sessionId, mode }),
+  // 开始或继续回放，已播完时从头开始
+  playbackPlay: () => invoke<imuApiResponse<PlaybackStatus>>("playback_play"),
+  // 暂停回放
+  playbackPause: () => invoke<imuApiResponse<PlaybackStatus>>("playback_pause"),
+  // 跳转到不早于 timestampMs（设备时间戳）的第一帧
+  playbackSeek: (timestampMs: number) =>
+    invoke<imuApiResponse<PlaybackStatus>>("playback_seek", { timestampMs }),
+  // 设置回放倍速（0.25 ~ 8）
+  playbackSetSpeed: (multiplier: number) =>
+    invoke<imuApiResponse<PlaybackStatus>>("playback_set_speed", { multiplier }),
+  // 开启或关闭循环回放
+  playbackSetLoop: (enabled: boolean) =>
+    invoke<imuApiResponse<PlaybackStatus>>("playback_set_loop", { enabled }),
+  // 获取回放状态；播放中另有 playback_status 事件定期推送
+  getPlaybackStatus: () =>
+    invoke<imuApiResponse<PlaybackStatus>>("get_playback_status"),
+
+  // 无硬件自检（需断开设备且未在录制），record 默认 true
+  runSelfTest: (record?: boolean) =>
+    invoke<imuApiResponse<SelfTestReport>>("run_self_test", { record }),
 };
@@ -15,6 +15,7 @@ export interface Quaternion {
 
 // 后端返回的响应数据（扁平化结构）
 export interface ResponseData {
+  schema_version: number;  // 结构版本（RESPONSE_SCHEMA_VERSION），旧载荷由 upgradeResponseData 补齐
   timestamp_ms: number;    // 时间戳（毫秒）
   accel: Vector3;          // 去重力加速度（m/s²）
   accel_with_g: Vector3;   // 含重力加速度（m/s²，用于标定）
@@ -23,17 +24,129 @@ export interface ResponseData {
   velocity: Vector3;       // 速度（m/s，计算值）
   position: Vector3;       // 位置（m，计算值）
   accel_saturated: boolean; // 加速度计是否触发饱和（IM948 ±16g 量程硬截断）
+  motion_state: MotionState; // 运动状态（静止/运动/未判定）
+  zupt: ZuptState;         // ZUPT 检测结果
+  position_source: PositionSource; // 位置来源（本地积分 / 设备位置 / 融合）
+  jerk: Vector3 | null;      // 加加速度（m/s³，派生信号，未启用时为 null）
+  ang_accel: Vector3 | null; // 角加速度（rad/s²，派生信号，未启用时为 null）
+  position_sigma_m: number; // 位置不确定度（m，1σ 启发式估计，静止后归零）
+  host_time_estimate_ms: number | null; // 按时钟同步换算的主机 UNIX 时间（ms），同步未建立时为 null
+  debug_vectors: DebugVectors | null; // 重力参考与加速度调试向量，未启用 debug_vectors 或回放录制时为 null
+}
+
+// 输出订阅（subscribe_output）中的完整帧
+export interface StreamFrame extends ResponseData {
+  seq: number;        // 帧序号，每帧加 1（含被抑制的帧）
+  skipped: number;    // 自上一条消息以来有意未发出的帧数（静止抑制或回放抽取）；seq 缺口减去它即为丢帧数
+  snapshot: boolean;  // 订阅时先发的最新一帧快照，不参与缺口计算
+  backfill: boolean;  // 订阅时回放的历史帧
+}
+
+// 静止抑制期间代替完整帧发出的心跳，位置/速度/姿态与上一个完整帧相同
+export interface StreamHeartbeat {
+  seq: number;
+  timestamp_ms: number;
+  unchanged: true;
+  skipped: number;
+  backfill: boolean;
+}
+
+export type StreamMessage = StreamFrame | StreamHeartbeat;
+
+// 输出订阅通道上的一条消息：默认按批发送（output_batch，顺序不变），batching: false 时为单条消息
+export type OutputEvent = StreamMessage | { output_batch: StreamMessage[] };
+
+// 输出订阅选项：先发快照、再回放最近 backfill_ms 的历史（按 max_rate_hz 抽取），之后才是实时消息
+export interface OutputSubscribeOptions {
+  include_snapshot?: boolean;
+  backfill_ms?: number;
+  max_rate_hz?: number | null;
+  live_max_rate_hz?: number | null; // 实时帧最高频率，为空时不抽取
+  // 实时帧频率模式；adaptive 时按运动强度在 min_hz–max_hz 之间调整，不能同时给 live_max_rate_hz
+  rate_mode?: OutputRateMode;
+  batching?: boolean;        // 攒批发送，默认开启
+  batch_window_ms?: number;  // 攒批窗口（1–100 ms，默认 16），即攒批带来的最大额外延迟
+  batch_max_frames?: number; // 每批最多消息数（1–64，默认 8）
+  encoding?: OutputEncoding; // 消息编码，默认 json；cbor 时通道收到 ArrayBuffer，用 unbatchOutput 解码展开
+}
+
+// 输出订阅的消息编码
+export type OutputEncoding = 'json' | 'cbor';
+
+export type OutputRateMode =
+  | { kind: 'fixed' }
+  | { kind: 'adaptive'; min_hz: number; max_hz: number };
+
+// 输出订阅当前的频率（诊断数据 out_subscriber_rates）
+export interface SubscriberRate {
+  id: number;
+  kind: 'full' | 'attitude' | 'frame';
+  rate_hz: number | null;    // 当前最高频率，不抽取时为 null
+  intensity: number | null;  // 自适应订阅的运动强度 0–1
+}
+
+// 高频姿态流单帧（subscribe_attitude），quat 为 [x, y, z, w]
+export interface AttitudeFrame {
+  seq: number;           // 本次订阅内的发出序号，不连续表示丢帧
+  timestamp_ms: number;  // 设备时间戳（毫秒）
+  quat: [number, number, number, number];
+}
+
+// 运动状态
+export type MotionState = 'static' | 'moving' | 'unknown';
+
+// 输出位置来源
+export type PositionSource = 'integrated' | 'device_offset' | 'blended';
+
+// ZUPT 检测结果
+export interface ZuptState {
+  is_static: boolean;               // 本帧是否判定为静止
+  gyro_norm: number;                // 陀螺仪范数（rad/s）
+  accel_lin_norm: number;           // 线加速度范数（m/s²）
+  locked_position: Vector3 | null;  // 静止锁定位置（仅 Legacy 硬锁）
+}
+
+// 导航调试向量（m/s²，与输出位置同一坐标系）
+export interface DebugVectors {
+  gravity_ref: Vector3;  // 重力参考向量
+  accel_world: Vector3;  // 世界系加速度（含重力）
+  accel_lin: Vector3;    // 世界系线性加速度 = accel_world - gravity_ref（钳位与死区之前）
 }
 
 // 录制状态
 export interface RecordingStatus {
   recording: boolean;         // 是否正在录制
+  paused: boolean;            // 是否处于暂停（仍属同一会话）
   session_id?: number | null; // 当前会话 ID
   db_path?: string | null;    // 数据库路径
   sample_count?: number | null; // 已采样数量
   started_at_ms?: number | null; // 开始时间
   name?: string | null;       // 录制名称
   tags?: string[] | null;     // 标签
+  bytes_per_minute?: number | null; // 估计每分钟写入字节数（深度录制时含阶段中间值）
+  deep: boolean;              // 是否深度录制
+  stage_bytes_per_minute?: number | null; // 其中阶段中间值的估计每分钟字节数
+  available_bytes?: number | null;  // 录制卷可用字节数
+  stop_reason?: StopReason | null;  // 结束原因（停止后返回）
+}
+
+// 录制结束原因
+export type StopReason = 'user' | 'disk_full' | 'split';
+
+// 录制被后端主动结束时推送的事件（recording_stopped）
+export interface RecordingStopped {
+  session_id: number;
+  reason: StopReason;
+  sample_count: number;
+  available_bytes?: number | null;
+}
+
+// 导出进度事件（export_progress），每秒至多数次，最后一批必报
+export interface ExportProgress {
+  session_id: number;
+  rows_done: number;      // 已写出的样本行数
+  rows_total: number;     // 导出开始时的样本总行数
+  bytes_written: number;  // 已写出的字节数
 }
 
 // 录制元数据
@@ -44,6 +157,208 @@ export interface RecordingMeta {
   sample_count: number;
   name?: string | null;
   tags: string[];
+  stop_reason?: string | null;
+  parent_session_id?: number | null; // 自动分段时第一段的会话 ID，用于分组
+  interrupted: boolean; // 未正常结束（崩溃后已修复），结束时间与样本数为回填值
+  schema_version: number; // 写入时的 ResponseData 结构版本，早期录制为 1
+  paused_ms: number; // 暂停累计时长，已从时长统计中扣除
+  heading_yaw_deg?: number | null; // 航向对齐旋转（°），样本中的导航输出已按它旋转
+  heading_aligned_at_ms?: number | null; // 该对齐开始生效的设备时间戳
+  metadata?: RecordingMetadata | null; // 录制开始时的应用与设备版本信息，早期录制为空
+  notes?: string | null; // 审阅备注
+}
+
+// 设备信息服务（0x180A）读到的版本信息，设备不提供的字段为空
+export interface DeviceInformation {
+  manufacturer: string | null;
+  model_number: string | null;
+  firmware_revision: string | null;
+  hardware_revision: string | null;
+  software_revision: string | null;
+}
+
+// 录制开始时采集的版本信息
+export interface RecordingMetadata {
+  app_version: string;
+  device: DeviceInformation;
+  imu_config_hex: string | null; // 开始录制时生效的设备配置帧（十六进制）
+  uptime?: ConnectionUptime | null; // 开始录制时的设备运行时长与连接时长
+  calibration?: RecordingCalibration | null; // 开始录制时的姿态零位与重力参考，早期录制为空
+}
+
+// 录制所在的世界系
+export interface RecordingCalibration {
+  axis_quat_offset: Quaternion; // 姿态零位四元数
+  gravity_ref: Vector3;         // 世界系重力参考 (m/s²)
+}
+
+// 录制列表查询条件（list_recordings），各项均可省略
+export interface RecordingQuery {
+  name?: string;                 // 名称子串，不区分大小写
+  tags?: string[];               // 命中任一标签（整标签匹配）
+  started_from_ms?: number;      // 开始时间下限（含）
+  started_to_ms?: number;        // 开始时间上限（含）
+  min_duration_ms?: number;      // 最短时长，未结束的会话不满足
+  device_id?: string;
+  sort_by?: 'started_at' | 'duration' | 'sample_count'; // 默认 started_at
+  ascending?: boolean;           // 默认倒序
+  limit?: number;
+  offset?: number;
+}
+
+// 一页录制会话，total_count 为满足条件的总数
+export interface RecordingPage {
+  items: RecordingMeta[];
+  total_count: number;
+}
+
+// 标签使用次数
+export interface TagUsage {
+  tag: string;
+  count: number;
+}
+
+// 全部录制的汇总统计（总时长只计已结束的会话，库大小含 WAL 文件）
+export interface RecordingStatistics {
+  session_count: number;
+  sample_count: number;
+  total_duration_ms: number;
+  db_size_bytes: number;
+  first_started_at_ms: number | null;
+  last_started_at_ms: number | null;
+  top_tags: TagUsage[];
+}
+
+// 回放数据来源：stored 播放存储的导航结果，reprocess 把原始样本重新送进管线
+export type PlaybackMode = 'stored' | 'reprocess';
+
+// 回放状态
+export type PlaybackState = 'idle' | 'playing' | 'paused' | 'ended';
+
+// get_playback_status 返回值与 playback_status 事件载荷（时间均为设备时间戳 ms）
+export interface PlaybackStatus {
+  session_id: number | null;
+  state: PlaybackState;
+  mode: PlaybackMode;
+  speed: number;            // 倍速 0.25 ~ 8
+  looping: boolean;
+  position_ms: number | null;
+  start_ms: number | null;
+  end_ms: number | null;
+  frame_index: number;
+  frame_count: number;
+}
+
+// 录制标记（timestamp_ms 为设备时间，与样本对齐）
+export interface RecordingMarker {
+  id: number;
+  session_id: number;
+  timestamp_ms: number;
+  host_ts_ms: number;
+  label: string;
+}
+
+// 数据质量标记区间（设备时间戳闭区间），区间之间可以重叠
+export interface RecordingFlag {
+  id: number;
+  session_id: number;
+  from_ms: number;
+  to_ms: number;
+  label: string;
+  created_at_ms: number;
+}
+
+// 录制同步事件：手动脉冲（record_sync_pulse）或自动检测的敲击
+export interface RecordingSyncEvent {
+  id: number;
+  session_id: number;
+  kind: 'manual' | 'tap';
+  source: string;
+  timestamp_ms: number;            // 设备时间戳（ms），与样本对齐
+  host_ts_ms: number;              // 记录事件时的主机 UNIX 时间（ms）
+  clock_offset_ms?: number | null; // 时钟同步偏移（ms）
+  clock_skew_ppm?: number | null;  // 时钟同步频偏（ppm）
+  host_time_ms?: number | null;    // 按时钟同步换算的主机 UNIX 时间（ms）
+  peak_g?: number | null;          // 敲击峰值（g）
+}
+
+// 录制暂停区间（paused_at_ms/resumed_at_ms 为主机时间，timestamp_ms 为暂停前最后一帧的设备时间）
+export interface RecordingPause {
+  id: number;
+  session_id: number;
+  paused_at_ms: number;
+  resumed_at_ms: number;
+  duration_ms: number;
+  timestamp_ms?: number | null;
+}
+
+// 深度录制的一帧阶段中间值，与同会话样本按 timestamp_ms 一一对应
+export interface RecordingStageSample {
+  timestamp_ms: number;
+  calibrated_accel: Vector3;   // 标定后加速度 (m/s²)
+  calibrated_gyro: Vector3;    // 标定后角速度 (rad/s)
+  filtered_accel: Vector3;     // 滤波后加速度
+  filtered_gyro: Vector3;      // 滤波后角速度
+  linear_accel: Vector3;       // 世界系线加速度
+  pre_zupt_velocity: Vector3;  // ZUPT 前速度 (m/s)
+  pre_zupt_position: Vector3;  // ZUPT 前位置 (m)
+  post_zupt_velocity: Vector3; // ZUPT 后速度
+  post_zupt_position: Vector3; // ZUPT 后位置
+  is_static: boolean;
+}
+
+// 运动分段（motion_segment 事件载荷，start_ms/end_ms 为设备时间）
+export interface MotionSegment {
+  start_ms: number;
+  end_ms: number;
+  duration_ms: number;
+  path_length_m: number;   // 路径长度（逐帧位置增量之和）
+  displacement: Vector3;   // 位移向量
+  displacement_m: number;  // 位移模长
+  peak_speed_mps: number;  // 峰值速度
+}
+
+// 录制中保存的运动分段
+export interface RecordingSegment extends MotionSegment {
+  id: number;
+  session_id: number;
+}
+
+// 两段录制的 A/B 对比报告（compare_recordings）
+export interface ComparisonReport {
+  overlap_start_ms: number;          // 重叠窗口起点（设备时间）
+  overlap_end_ms: number;            // 重叠窗口终点（设备时间）
+  aligned_samples: number;           // 对齐点数
+  position_rmse_m: number;           // 位置 RMSE
+  displacement_a: Vector3;           // A 在重叠窗口内的位移
+  displacement_b: Vector3;           // B 在重叠窗口内的位移
+  final_displacement_diff_m: number; // 位移差模长
+  max_velocity_diff_mps: number;     // 最大速度差
+  attitude_diff: { mean_deg: number; rms_deg: number; max_deg: number };
+  trajectory: { timestamp_ms: number; position_a: Vector3; position_b: Vector3 }[]; // 降采样对齐轨迹
+}
+
+// 离线平滑结果（smooth_recording）
+export interface SmoothedTrajectory {
+  session_id: number;
+  frame_count: number;               // 重跑管线输出的帧数
+  anchor_count: number;              // 静止锚点段数
+  causal_final_position: Vector3;    // 实时管线的最终位置
+  smoothed_final_position: Vector3;  // 平滑后的最终位置
+  written: boolean;                  // 是否已写入 smoothed_position_* 列
+  points: { timestamp_ms: number; causal_position: Vector3; smoothed_position: Vector3; is_anchor: boolean }[]; // 降采样轨迹
+}
+
+// 标定世界系换算结果（transform_recording）
+export interface TransformedTrajectory {
+  session_id: number;
+  target_session_id: number;
+  relative_rotation: Quaternion;     // 从原会话到目标会话世界系的相对旋转
+  rotation_deg: number;              // 相对旋转角度（度）
+  gravity_residual_deg: number;      // 换算后的重力方向偏差（度）
+  frame_count: number;               // 换算的样本数
+  written: boolean;                  // 是否已写入 transformed_* 列
+  points: { timestamp_ms: number; original_position: Vector3; position: Vector3; attitude: Quaternion }[]; // 降采样轨迹
 }
 
 // 蓝牙外设信息
@@ -52,29 +367,272 @@ export interface PeripheralInfo {
   address: string;   // MAC 地址
   local_name?: string; // 设备名称
   rssi?: number;     // 信号强度
+  last_seen_ms_ago?: number | null; // 距最近一次被扫描发现的毫秒数，本次运行未发现过时为空
+}
+
+// 本机蓝牙适配器（list_bluetooth_adapters）
+export interface BluetoothAdapterInfo {
+  id: string;              // 适配器 ID，同名时追加 #2、#3
+  name: string;            // 平台给出的描述
+  powered?: boolean | null; // 是否已开启，平台不提供时为空
+  selected: boolean;       // 是否为当前使用的适配器
+}
+
+// 失败响应的结构化错误码
+export type ErrorCode =
+  | 'BluetoothUnavailable'
+  | 'AdapterNotFound'
+  | 'NotSupported'
+  | 'ExportInProgress'  // 该会话已有导出在进行
+  | 'ExportCancelled'   // 导出被 cancel_export 取消，未完成的文件已删除
+  | 'DatabaseCorrupt'   // 录制数据库完整性检查失败
+  | 'DatabaseTooNew'    // 录制数据库由更新版本的程序写出
+  | 'DeviceOffline'     // 连接失败后重新扫描未发现设备，设备可能已关机或超出范围
+  | 'DeviceUnreachable' // 连接失败，但重新扫描时发现了设备（信号弱或被其他主机占用）
+  | 'RecordingStarting' // 开始录制等待超时，录制线程仍在处理（多为迁移数据库），稍后查询录制状态
+  | 'PlaybackDeviceStreaming' // 设备正在上报数据，不能开始回放
+  | 'PlaybackRecordingActive'; // 正在录制，不能开始回放
+
+// 录制数据库检查结果（verify_recordings_database / recordings_database_corrupt 事件）
+export interface DatabaseIntegrity {
+  ok: boolean;
+  problems: string[];               // 检查发现的问题，通过时为空
+  schema_version: number;           // 数据库表结构版本，未迁移的旧库或损坏的库为 0
+  supported_schema_version: number; // 当前程序的表结构版本
+  db_path: string;
+}
+
+// 蓝牙可用性诊断（get_bluetooth_status）
+export interface BluetoothStatus {
+  manager_available: boolean;        // 蓝牙服务是否可用
+  adapter_count: number;
+  selected_adapter?: string | null;  // 当前使用的适配器 ID
+  error?: string | null;
+  error_code?: ErrorCode | null;
+}
+
+// 连接与处理状态快照（get_app_status）
+export interface AppStatus {
+  connected_peripheral?: PeripheralInfo | null; // 当前连接设备
+  recording: RecordingStatus;                   // 录制状态
+  frames_since_connect: number;                 // 本次连接以来的输出帧数
+  device_uptime_ms?: number | null;             // 设备上电以来的运行时长（已展开时间戳回绕）
+  connection_uptime_ms?: number | null;         // 本次连接已持续的时长，未连接时为空
+  reconnect_count: number;                      // 本次运行以来的重连次数（首次连接不计）
+  last_device_timestamp_ms?: number | null;     // 最近一帧设备时间戳
+  last_host_timestamp_ms?: number | null;       // 最近一帧主机时间戳
+  gravity_estimate?: number | null;             // auto_gravity 估计的重力模长
+  skipped_integrations: number;                 // 因帧间隔过大跳过积分的累计次数
+  quat_freezes: number;                         // 设备四元数冻结、改用陀螺积分姿态的累计次数
+  parse_failures: number;                       // 解析失败的累计包数
+  non_finite_samples: number;                   // 某阶段输出 NaN/Inf 而被丢弃的累计帧数
+  input_packets_per_sec: number;                // 最近一个统计窗口（至少 1 s）的蓝牙通知速率 (包/s)
+  input_frames_per_sec: number;                 // 最近一个统计窗口的输入帧速率 (帧/s)，一个通知可能拼接多帧
+  input_bytes_per_sec: number;                  // 最近一个统计窗口的蓝牙输入带宽 (B/s)
+  report_rate: number;                          // 设备上报频率 (Hz)，0 表示 0.5 Hz
+  report_rate_hz: number;                       // 实际上报频率 (Hz)，report_rate 为 0 时为 0.5
+  pipeline_config_hash: string;                 // 当前 pipeline 配置哈希
+  watchdog: WatchdogSnapshot;                   // 数据流看门狗状态与停滞计数
+  memory: BufferUsage[];                        // 常驻内存缓冲区的占用
+  fault_injection?: FaultInjectionStatus | null; // 生效中的故障注入（仅开发构建）
+  scheduling: SchedulingReport;                 // 处理/录制线程调度提示是否生效
+}
+
+// 可注入 NaN 或延迟的管线阶段
+export type FaultStage = 'calibration' | 'filter' | 'navigator';
+
+// 单项调度提示结果
+export type HintOutcome =
+  | { status: 'not_requested' }
+  | { status: 'applied' }
+  | { status: 'failed'; error: string };
+
+// 一个线程的调度提示结果
+export interface ThreadHintsReport {
+  priority: HintOutcome;
+  affinity: HintOutcome;
+}
+
+// 各线程调度提示结果，线程尚未启动时为 null
+export interface SchedulingReport {
+  processor?: ThreadHintsReport | null;
+  recorder?: ThreadHintsReport | null;
+}
+
+// 故障注入配置（set_fault_injection），概率在 [0, 1]，全部为 0 即关闭
+export interface FaultInjectionConfig {
+  drop_packet: number;          // 丢弃整个上游通知的概率
+  corrupt_byte: number;         // 解析前篡改一个随机字节的概率
+  nan: number;                  // 向 nan_stage 输出写入 NaN 的概率
+  nan_stage: FaultStage;
+  delay: number;                // 让 delay_stage 延迟 delay_ms 的概率
+  delay_stage: FaultStage;
+  delay_ms: number;             // 每次延迟时长 (ms)，最多 1000
+  recorder_insert_fail: number; // 录制写入失败的概率
+  seed?: number | null;         // 随机数种子，固定后故障序列可复现
+}
+
+// 自上次配置以来各类故障的注入次数
+export interface FaultCounts {
+  dropped_packets: number;
+  corrupted_packets: number;
+  nan_injections: number;
+  delays: number;
+  recorder_insert_failures: number;
+}
+
+// 生效中的故障注入
+export interface FaultInjectionStatus {
+  config: FaultInjectionConfig;
+  injected: FaultCounts;
+}
+
+// 设备运行时长与连接时长（ms）
+export interface ConnectionUptime {
+  device_uptime_ms: number | null;
+  connection_uptime_ms: number | null;
+  frames_since_connect: number;
+  reconnect_count: number;
+}
+
+// 常驻内存缓冲区的条数与估算字节数
+export interface BufferUsage {
+  name: string;
+  items: number;
+  bytes: number;
+  max_items: number;
+  max_bytes: number | null;
+}
+
+// 数据流停滞的环节：收不到蓝牙包 / 有包但管线无输出
+export type FlowStage = 'input' | 'output';
+
+// data_stalled / data_resumed 事件载荷
+export interface DataStall {
+  stage: FlowStage;
+  stalled_ms: number; // 停滞事件为已持续时长，恢复事件为整段停滞时长
+}
+
+// pipeline_channel_closed 事件载荷：处理管线退出，蓝牙接收任务已停止转发
+export interface PipelineChannelClosed {
+  forwarded_packets: number;
+  forwarded_frames: number;
+  uptime_ms: number; // 接收任务已运行的时长
+}
+
+// stream_idle / stream_active 事件载荷
+export interface StreamIdleState {
+  idle: boolean;
+  duration_ms: number; // 空闲事件为已无人消费时长，恢复事件为整段空闲时长
+}
+
+// 数据流看门狗快照
+export interface WatchdogSnapshot {
+  stall_threshold_ms: number;
+  effective_threshold_ms: number; // 实际生效阈值：配置阈值与 3 倍采样间隔中的较大者
+  stalled: FlowStage | null;   // 当前停滞环节
+  stall_count: number;         // 累计停滞次数
+  recovery_attempts: number;   // 重发开启上报命令次数
+  recovery_failures: number;   // 其中写入失败次数
+}
+
+// 自检检查项所属环节
+export type SelfTestStage =
+  | 'preflight'   // 前置条件（设备未连接、未在录制）
+  | 'upstream'    // 上游通道
+  | 'pipeline'    // 解析与管线输出
+  | 'navigation'  // 导航结果
+  | 'stats'       // 处理器运行统计
+  | 'recorder';   // 录制写入与读回
+
+// 自检单项结果
+export interface SelfTestCheck {
+  stage: SelfTestStage;
+  name: string;
+  passed: boolean;
+  duration_ms: number;
+  detail: string;      // 通过时为测量摘要，失败时为错误链
+}
+
+// 自检报告（run_self_test）
+export interface SelfTestReport {
+  passed: boolean;
+  total_ms: number;
+  checks: SelfTestCheck[];
+}
+
+// 传感器轴到外壳轴的重映射：带符号轴置换（如 ["-y", "x", "z"]）或 3x3 旋转矩阵
+export type AxisMap = [string, string, string] | number[][];
+
+// 增量配置的实际生效方式：in_place 保留轨迹状态，reset 重建管线
+export type ConfigApplyMode = 'in_place' | 'reset';
+
+// 配置校验错误：path 为 processor.toml 中的字段路径（如 zupt.accel_thresh）
+export interface ConfigError {
+  path: string;
+  message: string;
+}
+
+// config_invalid 事件载荷：processor.toml 未通过校验（启动时回退默认配置，热重载时保留当前配置）
+export interface ConfigInvalid {
+  stage: 'startup' | 'hot_reload';
+  errors: ConfigError[];
 }
 
 // Pipeline 配置类型
 export interface ProcessorPipelineConfig {
   global: {
     gravity: number;
+    auto_gravity: {
+      enabled: boolean;      // 静止期自动估计重力模长
+      window_frames: number; // 估计窗口帧数（需连续静止）
+    };
+    unsafe_device_commands?: boolean; // 允许原始设备命令（debug 构建始终允许）
+  };
+  reassembly: {
+    enabled: boolean;        // 拼接被 MTU 截断、跨通知到达的帧
+    max_hold_ms: number;     // 分片最长暂存时间，超时丢弃
+    max_held_bytes: number;  // 暂存分片字节上限
+  };
+  sequencer: {
+    reorder: boolean;          // 按设备时间戳前瞻重排（增加延迟）
+    lookahead_frames: number;  // 前瞻缓冲帧数
+    lookahead_ms: number;      // 前瞻窗口；回退超过该值视为设备复位
+    reset_on_reboot: boolean;  // 设备重启时一并重置速度与位置
   };
   calibration: {
     passby: boolean;
+    gyro_unit: 'deg_per_sec' | 'rad_per_sec'; // 原始角速度单位，标定阶段统一换算为 rad/s
+    accel_map: AxisMap; // 加速度计/姿态安装方向重映射
+    gyro_map: AxisMap;  // 陀螺仪安装方向重映射
     accel_bias: Vector3;
     gyro_bias: Vector3;
     accel_matrix: number[][];
     gyro_matrix: number[][];
   };
+  calibration_quality: {
+    window_ms: number;          // 零位校准前的采样窗口（100–5000 ms）
+    min_score: number;          // 最低可接受分数（0–100）
+    reject_below_min: boolean;  // 低于最低分时拒绝应用校准
+  };
   filter: {
     passby: boolean;
     alpha: number;
   };
+  derived: {
+    enabled: boolean;          // 计算加加速度与角加速度
+    alpha: number;             // 差分结果的平滑系数
+  };
   trajectory: {
     passby: boolean;
     integrator: 'legacy_euler' | 'trapezoid' | 'rk4';
     dt_min_ms: number;
     dt_max_ms: number;
+    max_integration_dt_ms: number; // 帧间隔超过该值时跳过积分并重置 ZUPT
+    accel_deadband_ms2: number; // 线加速度死区（m/s²），0 = 关闭
+    accel_deadband_mode: 'magnitude' | 'per_axis';
+    accel_deadband_bypass_ms2: number; // 运动中线加速度达到该值时不施加死区
+    position_sigma_accel_noise: number; // 位置不确定度的加速度噪声谱密度（m/s²/√Hz）
   };
   zupt: {
     passby: boolean;
@@ -85,14 +643,22 @@ export interface ProcessorPipelineConfig {
     accel_enter_thresh: number;
     gyro_exit_thresh: number;
     accel_exit_thresh: number;
-    enter_frames: number;
-    exit_frames: number;
+    enter_window_ms: number; // 进入静止需连续满足阈值的时长 (ms)
+    exit_window_ms: number;  // 退出静止需连续满足阈值的时长 (ms)
     vel_decay_tau_ms: number;
     pos_lock_tau_ms: number;
     vel_zero_eps: number;
     backward_correction: boolean;
   };
+  plane_constraint: {
+    mode: 'none' | 'horizontal'; // 平面约束（桌面 2D 模式）
+    height_m: number;            // 约束平面沿重力方向的高度
+  };
   navigator_impl: 'legacy' | 'eskf';
+  position_source: {
+    mode: PositionSource;      // 位置来源
+    blend_weight: number;      // blended 模式每帧向设备位置靠拢的权重 [0, 1]
+  };
   eskf: {
     gyro_noise: number;
     accel_noise: number;
@@ -106,6 +672,76 @@ export interface ProcessorPipelineConfig {
     init_sigma_gyro_bias: number;
     init_sigma_accel_bias: number;
   };
+  segment: {
+    enabled: boolean;          // 运动分段检测（motion_segment 事件）
+    min_duration_ms: number;   // 最短时长，更短视为抖动
+    min_path_length_m: number; // 最短路径长度，更短视为抖动
+    min_static_ms: number;     // 静止持续多久才算一段结束
+  };
+  idle: {
+    enabled: boolean;          // 无人消费时停止设备上报（stream_idle / stream_active 事件）
+    grace_period_ms: number;   // 无人消费多久后停止上报
+  };
+  output_suppress: {
+    enabled: boolean;          // 静止且无变化时只向前端发心跳，录制不受影响
+    position_eps_m: number;    // 位置变化阈值（m）
+    velocity_eps_mps: number;  // 速度变化阈值（m/s）
+    attitude_eps_deg: number;  // 姿态变化阈值（°）
+    heartbeat_ms: number;      // 抑制期间的心跳间隔（ms）
+  };
+  quat_health: {
+    enabled: boolean;          // 设备四元数冻结时改用陀螺积分姿态（quat_freeze 事件）
+    freeze_eps_deg: number;    // 相邻帧姿态差小于该值视为未变化（°）
+    gyro_thresh: number;       // 角速度超过该值（rad/s）时四元数不变才计入冻结
+    freeze_frames: number;     // 连续多少帧不变判定为冻结
+    blend_frames: number;      // 恢复后过渡回设备姿态的帧数
+  };
+  accel_source: {
+    mode: 'with_g' | 'no_g' | 'auto'; // 积分用的加速度：含重力减重力参考 / 设备去重力 / 含重力并交叉检查
+    consistency_thresh_ms2: number;   // auto 下两路世界系线加速度之差超过该值视为不一致（m/s²）
+    consistency_frames: number;       // 连续多少帧超过（或低于）阈值才判定不一致（或恢复）
+  };
+  auto_origin: {
+    enabled: boolean;          // 连接/重置后位置固定为零，首段持续静止处作为原点（origin_anchored 事件）
+    static_ms: number;         // 判定持续静止所需时长（ms）
+  };
+  baro: {
+    enabled: boolean;          // 订阅气压计，积分位置 z 向气压相对高度收敛（需重新连接设备）
+    gain: number;              // 互补滤波增益（1/s），时间常数 = 1 / gain 秒
+  };
+  heading_align: {
+    enabled: boolean;          // 允许 arm_heading_from_motion：下一段运动方向旋转为输出 +X（heading_aligned 事件）
+    min_displacement_m: number; // 用于对齐的最小水平位移（m）
+    min_static_ms: number;     // 运动后需静止多久才算结束（ms）
+  };
+  sync_tap: {
+    enabled: boolean;          // 自动检测敲击作为视频对齐点（sync_tap 事件，录制中写入同步事件）
+    threshold_g: number;       // 含重力的加速度模长阈值（g）
+    max_duration_ms: number;   // 超过阈值的最长持续时间（ms），更长视为正常运动
+    refractory_ms: number;     // 一次敲击后忽略余振的时长（ms）
+  };
+  debug_vectors: {
+    enabled: boolean;          // 输出帧附带重力参考与加速度调试向量（ResponseData.debug_vectors）
+  };
+}
+
+// origin_anchored 事件载荷：世界系原点已锚定
+export interface OriginAnchored {
+  timestamp_ms: number;        // 锚定帧的设备时间戳（ms）
+}
+
+// heading_aligned 事件载荷：输出航向已按首段运动方向对齐
+export interface HeadingAligned {
+  timestamp_ms: number;        // 运动段结束的设备时间戳（ms）
+  yaw_deg: number;             // 施加到输出的绕 Z 旋转（°，逆时针为正）
+  displacement_m: number;      // 该运动段的水平位移（m）
+}
+
+// sync_tap 事件载荷：检测到一次敲击
+export interface SyncTap {
+  timestamp_ms: number;        // 峰值帧的设备时间戳（ms）
+  peak_g: number;              // 峰值加速度模长（g）
+  duration_ms: number;         // 超过阈值的持续时间（ms）
 }
 
 // 设备标定数据
@@ -123,17 +759,70 @@ export interface DeviceCalibrationData {
   created_at_ms: number;
 }
 
+// 设备标定档案：连接该设备时合并到基础配置上
+export interface DeviceProfile {
+  device_id: string;
+  /** 覆盖的配置片段，目前只有标定段 */
+  overrides: {
+    calibration?: Partial<ProcessorPipelineConfig['calibration']>;
+  };
+  /** 姿态零位，为空时连接后沿用当前零位 */
+  axis_offset: {
+    angle_offset: Vector3;
+    quat_offset: Quaternion;
+  } | null;
+  /** 保存时间戳（ms）*/
+  updated_at_ms: number;
+}
+
+// 展示与导出的单位偏好，管线内部始终为国际单位
+export interface UnitPreferences {
+  length: 'm' | 'cm' | 'mm';
+  angle: 'deg' | 'rad';
+  speed: 'm/s' | 'km/h';
+}
+
+// 按单位偏好格式化好的最新一帧（get_latest_frame_display），字符串已带单位
+export interface FrameDisplay {
+  timestamp_ms: number;
+  position: [string, string, string];
+  velocity: [string, string, string];
+  /** 速度模长 */
+  speed: string;
+  /** 欧拉角 [roll, pitch, yaw] */
+  euler: [string, string, string];
+  position_sigma: string;
+  /** 格式化时使用的单位偏好 */
+  units: UnitPreferences;
+}
+
 // 管线诊断数据（后端 PipelineDiagnostics 对应）
 export interface PipelineDiagnostics {
   timestamp_ms: number;
+  // 时间戳排序（累计）
+  seq_duplicates: number;
+  seq_late: number;
+  seq_reordered: number;
+  seq_device_resets: number;
+  seq_gaps: number;            // 丢帧次数（间隔超过标称 1.5 倍）
+  seq_missing_frames: number;  // 估算丢失帧数
+  // 蓝牙分片重组（累计）
+  ble_reassembled: number;        // 跨通知拼接成功的帧数
+  ble_reassembly_dropped: number; // 超时或无法拼接而丢弃的分片数
   // 标定阶段
+  cal_bypassed: boolean;       // calibration.passby：跳过时只做角速度单位换算
   cal_accel_bias: Vector3;
   cal_gyro_bias: Vector3;
   cal_accel_pre: Vector3;
   cal_accel_post: Vector3;
   cal_gyro_pre: Vector3;
+  cal_gyro_unit: 'deg_per_sec' | 'rad_per_sec';
   cal_gyro_post: Vector3;
+  // 加速度来源（auto 模式的交叉检查）
+  accel_source_residual: number | null;  // 两路世界系线加速度之差（m/s²），非 auto 为 null
+  accel_source_inconsistent: boolean;    // 两路加速度持续不一致
   // 滤波阶段
+  filt_bypassed: boolean;      // filter.passby：跳过时前后取值相同
   filt_accel_pre: Vector3;
   filt_accel_post: Vector3;
   filt_gyro_pre: Vector3;
@@ -146,7 +835,18 @@ export interface PipelineDiagnostics {
   zupt_exit_count: number;
   // 导航阶段
   nav_dt: number;
+  nav_skipped_integrations: number;
+  nav_quat_renormalizations: number; // 姿态模长超出容差、完整归一化的累计次数
   nav_linear_accel: Vector3;
+  nav_gravity_estimate: number | null;
+  nav_plane_removed_velocity: number;
+  nav_deadband_clipped: number;
+  nav_integrated_position: Vector3; // 本地积分位置
+  nav_device_position: Vector3;     // 设备输出位置（已转到导航系并对齐原点）
+  // 气压计高度辅助
+  baro_pressure_hpa: number | null;        // 原始气压（hPa），未订阅时为空
+  baro_relative_altitude_m: number | null; // 相对参考的气压高度（m）
+  baro_correction_m: number;               // 本帧施加到积分 z 的修正量（m）
   // 饱和检测：本帧加速度计是否触发饱和（IM948 ±16g）
   accel_saturated: boolean;
   // ESKF 专属
@@ -163,4 +863,127 @@ export interface PipelineDiagnostics {
   perf_downstream_queue_len: number;
   perf_record_queue_len: number;
   perf_ble_interval_ms: number;
+  perf_receive_latency_ms: number | null; // 通知到达到开始处理的延迟（ms），合成包为 null
+  // 输出分发
+  out_subscriber_rates: SubscriberRate[];
+}
+
+// 原始 IMU 样本（后端 ImuSampleRaw 对应，explain_sample 的输入）
+export interface ImuSampleRaw {
+  timestamp_ms: number;
+  accel_no_g: Vector3;     // 去重力加速度（m/s²）
+  accel_with_g: Vector3;   // 含重力加速度（m/s²）
+  gyro: Vector3;           // 角速度（原始单位，见 calibration.gyro_unit）
+  quat: Quaternion;        // 姿态四元数
+  angle: Vector3;          // 欧拉角（°）
+  offset: Vector3;         // 设备输出位置（m）
+  accel_nav: Vector3;      // 导航系加速度（m/s²）
+  baro?: BaroSample | null; // 气压计数据，未订阅时为空
+}
+
+// 气压计数据块
+export interface BaroSample {
+  temperature_c: number;   // 板上温度（℃）
+  pressure_hpa: number;    // 气压（hPa）
+  altitude_m: number;      // 设备换算的海拔高度（m）
+}
+
+// 单个阶段的输入、输出与影响判定的参数
+export interface StageExplanation {
+  stage: 'remap_axes' | 'axis_calibration' | 'calibration' | 'quat_health' | 'accel_source' | 'filter' | 'derived' | 'navigator' | 'heading' | 'segment' | 'sync_tap';
+  bypassed: boolean;   // 被 passby 或功能开关跳过，输出即输入
+  input: unknown;
+  output: unknown;
+  params: Record<string, unknown>;
+}
+
+// 样本流经整条管线的解释（explain_sample 返回，仅 debug 构建可用）
+export interface SampleExplanation {
+  timestamp_ms: number;
+  paused: boolean;                 // 导航暂停时导航阶段只输出冻结状态
+  stages: StageExplanation[];      // 按处理顺序排列
+  output: ResponseData;            // 该样本实际处理时会输出的数据
+}
+
+// 姿态零位校准的质量评分（set_axis_calibration 返回）
+// score = 100 × (0.4·加速度模长方差项 + 0.4·角速度均值项 + 0.2·重力偏差项)
+export interface CalibrationQuality {
+  score: number;
+  accel_norm_variance: number; // (m/s²)²
+  gyro_norm_mean: number;      // rad/s
+  gravity_deviation: number;   // m/s²
+  sample_count: number;
+  min_score: number;
+  applied: boolean;            // 低分被拒绝时为 false
+}
+
+// 一次性校准生效的偏移
+export interface CalibrationOffsets {
+  quat_offset: Quaternion;     // 姿态零位四元数偏移
+  angle_offset: Vector3;       // 欧拉角偏移（°）：x/y 为水平，z 为航向
+  heading_offset_deg: number;  // 航向零点（°）
+  gravity_ref: Vector3;        // 更新后的世界系重力参考（m/s²）
+}
+
+// 水平 + 航向 + 原点一次性校准结果（calibrate_all 返回）
+export interface FullCalibration {
+  quality: CalibrationQuality;
+  offsets: CalibrationOffsets | null;  // 未通过静止校验时为 null，任何状态都未改变
+}
+
+// 标定向导流程；six_face 预留给六面标定，后端尚未实现
+export type CalibrationFlow = "axis" | "calibrate_all" | "heading_align" | "six_face";
+
+// 标定向导的最终结果，按 kind 区分流程
+export type CalibrationWizardResult =
+  | ({ kind: "axis" } & CalibrationQuality)
+  | ({ kind: "calibrate_all" } & FullCalibration)
+  | ({ kind: "heading_align" } & HeadingAligned);
+
+// 标定向导状态
+export type CalibrationWizardState =
+  | { state: "idle" }
+  | { state: "waiting_for_stillness"; elapsed_ms: number; required_ms: number } // elapsed 按 100 ms 取整
+  | { state: "waiting_for_motion" }                                              // 航向对齐等待运动
+  | { state: "capturing_face"; face: number; progress: number }                  // 面序号 0–5，进度 0–1
+  | { state: "computing" }
+  | { state: "done"; result: CalibrationWizardResult }
+  | { state: "failed"; reason: string };
+
+// get_calibration_state 返回值与 calibration_state_changed 事件载荷
+export interface CalibrationWizardSnapshot {
+  seq: number;                    // 每次变化加一，丢弃序号更小的事件或查询结果
+  flow: CalibrationFlow | null;   // 空闲时为 null
+  state: CalibrationWizardState;
+}
+
+// 设备四元数冻结事件（quat_freeze）：进入冻结与恢复各推送一次
+export interface QuatFreezeEvent {
+  timestamp_ms: number;  // 设备时间戳
+  frozen: boolean;       // true = 检测到冻结、改用陀螺积分；false = 恢复、过渡回设备姿态
+  freeze_count: number;  // 累计冻结次数
+}
+
+// 加速度不一致事件（accel_inconsistent）：auto 模式下 accel_with_g 与 accel_no_g 持续不一致与恢复各推送一次
+export interface AccelInconsistencyEvent {
+  timestamp_ms: number;        // 设备时间戳
+  inconsistent: boolean;       // true = 持续不一致；false = 恢复一致
+  residual_ms2: number;        // 判定帧的世界系线加速度之差（m/s²）
+  inconsistent_count: number;  // 累计不一致次数
+}
+
+// 设备/主机时钟同步结果（get_clock_sync）：host = offset_ms + device / (1 + skew_ppm·1e-6)
+export interface ClockSyncEstimate {
+  offset_ms: number;    // 设备时间 0 对应的主机 UNIX 时间（ms）
+  skew_ppm: number;     // 设备时钟相对主机的频偏（ppm），正值表示设备偏快
+  anchors: number;      // 参与拟合的锚点数
+  residual_ms: number;  // 锚点残差中位数（ms）
+}
+
+// 设备复位事件（device_reset）：设备时间戳大幅回退
+// rebooted 为 true 时另推送 device_rebooted，后端会自动重新下发设备配置
+export interface DeviceReset {
+  previous_timestamp_ms: number;
+  timestamp_ms: number;
+  rebooted: boolean;
 }
@@ -0,0 +1,142 @@
+import { OutputEvent } from "../types";
+
+/**
+ * subscribe_output 在 encoding: 'cbor' 时发出的字节数组，布局见 src-tauri/src/processor/output_encoding.rs。
+ *
+ * 解码结果与 JSON 订阅的 OutputEvent 结构相同：
+ * - 完整帧：map，依次为 seq、skipped、snapshot、backfill，随后是 ResponseData 的全部字段（平铺）
+ * - 心跳：StreamHeartbeat 的字段组成的 map
+ * - 攒批：{ output_batch: [...] }
+ *
+ * 后端只写定长数组/map、整数、文本、布尔、null 与 f16/f32/f64 浮点，这里只解码这些类型。
+ */
+
+const textDecoder = new TextDecoder();
+
+/** IEEE 754 半精度转 number。 */
+const halfToNumber = (bits: number): number => {
+  const sign = bits & 0x8000 ? -1 : 1;
+  const exponent = (bits >> 10) & 0x1f;
+  const fraction = bits & 0x3ff;
+  if (exponent === 0) {
+    return sign * fraction * 2 ** -24;
+  }
+  if (exponent === 0x1f) {
+    return fraction ? NaN : sign * Infinity;
+  }
+  return sign * (1 + fraction / 1024) * 2 ** (exponent - 15);
+};
+
+class CborReader {
+  private offset = 0;
+
+  constructor(private readonly view: DataView) {}
+
+  get done(): boolean {
+    return this.offset === this.view.byteLength;
+  }
+
+  /** 读取初始字节后的长度/数值参数。 */
+  private argument(info: number): number {
+    const { view } = this;
+    if (info < 24) {
+      return info;
+    }
+    const at = this.offset;
+    switch (info) {
+      case 24:
+        this.offset += 1;
+        return view.getUint8(at);
+      case 25:
+        this.offset += 2;
+        return view.getUint16(at);
+      case 26:
+        this.offset += 4;
+        return view.getUint32(at);
+      case 27:
+        this.offset += 8;
+        return Number(view.getBigUint64(at));
+      default:
+        throw new Error(`不支持的 CBOR 长度编码: ${info}`);
+    }
+  }
+
+  read(): unknown {
+    const { view } = this;
+    const initial = view.getUint8(this.offset);
+    this.offset += 1;
+    const major = initial >> 5;
+    const info = initial & 0x1f;
+    if (major === 7) {
+      return this.readSimple(info);
+    }
+    const value = this.argument(info);
+    switch (major) {
+      case 0:
+        return value;
+      case 1:
+        return -1 - value;
+      case 2: {
+        const bytes = new Uint8Array(view.buffer, view.byteOffset + this.offset, value);
+        this.offset += value;
+        return bytes.slice();
+      }
+      case 3: {
+        const bytes = new Uint8Array(view.buffer, view.byteOffset + this.offset, value);
+        this.offset += value;
+        return textDecoder.decode(bytes);
+      }
+      case 4:
+        return Array.from({ length: value }, () => this.read());
+      case 5: {
+        const map: Record<string, unknown> = {};
+        for (let i = 0; i < value; i += 1) {
+          const key = this.read();
+          map[String(key)] = this.read();
+        }
+        return map;
+      }
+      default:
+        throw new Error(`不支持的 CBOR 主类型: ${major}`);
+    }
+  }
+
+  private readSimple(info: number): unknown {
+    const { view } = this;
+    const at = this.offset;
+    switch (info) {
+      case 20:
+        return false;
+      case 21:
+        return true;
+      case 22:
+      case 23:
+        return null;
+      case 25:
+        this.offset += 2;
+        return halfToNumber(view.getUint16(at));
+      case 26:
+        this.offset += 4;
+        return view.getFloat32(at);
+      case 27:
+        this.offset += 8;
+        return view.getFloat64(at);
+      default:
+        throw new Error(`不支持的 CBOR 简单值: ${info}`);
+    }
+  }
+}
+
+/**
+ * 解码 CBOR 输出订阅的一条通道消息。
+ * @param buffer - 通道收到的 ArrayBuffer
+ * @returns 与 JSON 订阅相同结构的 OutputEvent
+ */
+export const decodeOutputEvent = (buffer: ArrayBuffer): OutputEvent => {
+  const reader = new CborReader(new DataView(buffer));
+  const event = reader.read() as OutputEvent;
+  if (!reader.done) {
+    throw new Error('CBOR 输出消息末尾有多余字节');
+  }
+  return event;
+};
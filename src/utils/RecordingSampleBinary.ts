@@ -0,0 +1,99 @@
+import { MotionState, PositionSource, ResponseData } from "../types";
+import { RESPONSE_SCHEMA_VERSION } from "./ResponseSchema";
+
+/**
+ * get_recording_samples_binary 返回的二进制布局，与 src-tauri/src/recorder/binary.rs 一一对应。
+ *
+ * 缓冲区 = 16 字节头 + count 条定长记录，全部小端：
+ * - 头部：魔数 "IMUS"(4) + 布局版本 u16 + 单条记录长度 u16 + 记录条数 u64
+ * - 记录：见 SAMPLE_BINARY_FIELDS（偏移, 长度）
+ */
+export const SAMPLE_BINARY_MAGIC = "IMUS";
+export const SAMPLE_BINARY_VERSION = 1;
+export const SAMPLE_BINARY_HEADER_SIZE = 16;
+export const SAMPLE_BINARY_RECORD_SIZE = 176;
+
+export const SAMPLE_BINARY_FIELDS = {
+  timestamp_ms: [0, 8],
+  calc_timestamp_ms: [8, 8],
+  accel: [16, 24],
+  accel_with_g: [40, 24],
+  gyro: [64, 24],
+  attitude: [88, 32], // x, y, z, w
+  velocity: [120, 24],
+  position: [144, 24],
+  motion_state: [168, 1],
+  accel_saturated: [169, 1],
+  position_source: [170, 1],
+  padding: [171, 5],
+} as const;
+
+const MOTION_STATES: MotionState[] = ['static', 'moving', 'unknown'];
+const POSITION_SOURCES: PositionSource[] = ['integrated', 'device_offset', 'blended'];
+
+const readVec3 = (view: DataView, offset: number) => ({
+  x: view.getFloat64(offset, true),
+  y: view.getFloat64(offset + 8, true),
+  z: view.getFloat64(offset + 16, true),
+});
+
+/**
+ * 解码二进制录制样本。
+ * @param buffer - get_recording_samples_binary 返回的 ArrayBuffer
+ * @returns 与 get_recording_samples 相同结构的样本（派生信号与 ZUPT 范数不落盘，置空）
+ */
+export const decodeRecordingSamples = (buffer: ArrayBuffer): ResponseData[] => {
+  const view = new DataView(buffer);
+  const magic = String.fromCharCode(...new Uint8Array(buffer, 0, 4));
+  if (magic !== SAMPLE_BINARY_MAGIC) {
+    throw new Error(`录制样本格式错误: magic ${magic}`);
+  }
+  const version = view.getUint16(4, true);
+  if (version !== SAMPLE_BINARY_VERSION) {
+    throw new Error(`不支持的录制样本布局版本: ${version}`);
+  }
+  const recordSize = view.getUint16(6, true);
+  const count = Number(view.getBigUint64(8, true));
+  if (buffer.byteLength !== SAMPLE_BINARY_HEADER_SIZE + count * recordSize) {
+    throw new Error('录制样本长度与头部不符');
+  }
+
+  const f = SAMPLE_BINARY_FIELDS;
+  const samples: ResponseData[] = new Array(count);
+  for (let i = 0; i < count; i += 1) {
+    const base = SAMPLE_BINARY_HEADER_SIZE + i * recordSize;
+    const motionState = MOTION_STATES[view.getUint8(base + f.motion_state[0])] ?? 'unknown';
+    const q = base + f.attitude[0];
+    samples[i] = {
+      schema_version: RESPONSE_SCHEMA_VERSION,
+      timestamp_ms: Number(view.getBigUint64(base + f.timestamp_ms[0], true)),
+      accel: readVec3(view, base + f.accel[0]),
+      accel_with_g: readVec3(view, base + f.accel_with_g[0]),
+      gyro: readVec3(view, base + f.gyro[0]),
+      attitude: {
+        x: view.getFloat64(q, true),
+        y: view.getFloat64(q + 8, true),
+        z: view.getFloat64(q + 16, true),
+        w: view.getFloat64(q + 24, true),
+      },
+      velocity: readVec3(view, base + f.velocity[0]),
+      position: readVec3(view, base + f.position[0]),
+      accel_saturated: view.getUint8(base + f.accel_saturated[0]) !== 0,
+      motion_state: motionState,
+      zupt: {
+        is_static: motionState === 'static',
+        gyro_norm: 0,
+        accel_lin_norm: 0,
+        locked_position: null,
+      },
+      position_source: POSITION_SOURCES[view.getUint8(base + f.position_source[0])] ?? 'integrated',
+      jerk: null,
+      ang_accel: null,
+      // 二进制格式不含位置不确定度、主机时间与调试向量
+      position_sigma_m: 0,
+      host_time_estimate_ms: null,
+      debug_vectors: null,
+    };
+  }
+  return samples;
+};
@@ -0,0 +1,28 @@
+import { ResponseData } from "../types";
+
+/**
+ * ResponseData 结构版本，与 src-tauri/src/types/outputs.rs 的 RESPONSE_SCHEMA_VERSION 一致。
+ *
+ * - 1：早期录制，没有位置不确定度
+ * - 2：新增 position_sigma_m、host_time_estimate_ms 与 schema_version
+ * - 3：新增 debug_vectors（仅实时输出，录制中恒为 null）
+ */
+export const RESPONSE_SCHEMA_VERSION = 3;
+
+/**
+ * 为旧版本载荷补齐缺失字段，混合版本开发或旧导出数据也能按当前结构使用。
+ * @param raw - 后端或旧数据中的一帧，字段可能不全
+ * @returns 当前结构的一帧
+ */
+export const upgradeResponseData = (
+  raw: Omit<
+    ResponseData,
+    'schema_version' | 'position_sigma_m' | 'host_time_estimate_ms' | 'debug_vectors'
+  > & Partial<ResponseData>,
+): ResponseData => ({
+  ...raw,
+  position_sigma_m: raw.position_sigma_m ?? 0,
+  host_time_estimate_ms: raw.host_time_estimate_ms ?? null,
+  debug_vectors: raw.debug_vectors ?? null,
+  schema_version: RESPONSE_SCHEMA_VERSION,
+});